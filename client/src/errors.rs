@@ -0,0 +1,20 @@
+use derive_more::{Display, Error};
+
+#[derive(Debug, Display, Error)]
+pub enum ClientError {
+	#[display("invalid url: {url}")]
+	InvalidUrl { url: String },
+
+	#[display("request failed: {error}")]
+	Request { error: reqwest::Error },
+
+	// error returned by the server, along with its request id (if any)
+	#[display("api error ({status}): {error}")]
+	Api { status: u16, error: String, request_id: Option<String> },
+}
+
+impl From<reqwest::Error> for ClientError {
+	fn from(error: reqwest::Error) -> ClientError {
+		ClientError::Request { error }
+	}
+}
@@ -0,0 +1,255 @@
+//! Rust client for the Barreleye API.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), barreleye_client::ClientError> {
+//! let client = barreleye_client::Client::new("http://localhost:2277")?.with_api_key("sk_...");
+//! let info = client.get_info("0x0000000000000000000000000000000000000000").await?;
+//! println!("{:?}", info.risk.level);
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use url::Url;
+
+pub use crate::{errors::ClientError, models::*};
+
+mod errors;
+mod models;
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorBody {
+	error: String,
+	request_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+	url: Url,
+	api_key: Option<String>,
+	http: reqwest::Client,
+}
+
+impl Client {
+	pub fn new(url: &str) -> ClientResult<Self> {
+		let url = Url::parse(url).map_err(|_| ClientError::InvalidUrl { url: url.to_string() })?;
+		Ok(Self { url, api_key: None, http: reqwest::Client::new() })
+	}
+
+	// api key or session token sent as a bearer token
+	pub fn with_api_key(self, api_key: &str) -> Self {
+		Self { api_key: Some(api_key.to_string()), ..self }
+	}
+
+	// eg: to set timeouts or client certificates
+	pub fn with_http_client(self, http: reqwest::Client) -> Self {
+		Self { http, ..self }
+	}
+
+	fn request(&self, method: Method, path: &str) -> ClientResult<RequestBuilder> {
+		let url = self
+			.url
+			.join(path)
+			.map_err(|_| ClientError::InvalidUrl { url: format!("{}{path}", self.url) })?;
+
+		let mut builder = self.http.request(method, url);
+		if let Some(api_key) = &self.api_key {
+			builder = builder.bearer_auth(api_key);
+		}
+
+		Ok(builder)
+	}
+
+	async fn send(&self, builder: RequestBuilder) -> ClientResult<reqwest::Response> {
+		let response = builder.send().await?;
+
+		let status = response.status();
+		if status.is_success() {
+			return Ok(response);
+		}
+
+		let (error, request_id) = match response.json::<ErrorBody>().await {
+			Ok(body) => (body.error, body.request_id),
+			Err(_) => (status.canonical_reason().unwrap_or_default().to_string(), None),
+		};
+
+		Err(ClientError::Api { status: status.as_u16(), error, request_id })
+	}
+
+	async fn get<Q, T>(&self, path: &str, query: &Q) -> ClientResult<T>
+	where
+		Q: Serialize + ?Sized,
+		T: DeserializeOwned,
+	{
+		let builder = self.request(Method::GET, path)?.query(query);
+		Ok(self.send(builder).await?.json().await?)
+	}
+
+	async fn post<B, T>(&self, path: &str, body: &B) -> ClientResult<T>
+	where
+		B: Serialize + ?Sized,
+		T: DeserializeOwned,
+	{
+		let builder = self.request(Method::POST, path)?.json(body);
+		Ok(self.send(builder).await?.json().await?)
+	}
+
+	async fn put<B>(&self, path: &str, body: &B) -> ClientResult<()>
+	where
+		B: Serialize + ?Sized,
+	{
+		self.send(self.request(Method::PUT, path)?.json(body)).await?;
+		Ok(())
+	}
+
+	async fn delete<B>(&self, path: &str, body: &B) -> ClientResult<()>
+	where
+		B: Serialize + ?Sized,
+	{
+		self.send(self.request(Method::DELETE, path)?.json(body)).await?;
+		Ok(())
+	}
+
+	// `q` is an address or an entity id
+	pub async fn get_info(&self, q: &str) -> ClientResult<Info> {
+		self.get("/v1/info", &[("q", q)]).await
+	}
+
+	pub async fn is_healthy(&self) -> ClientResult<bool> {
+		let response = self.request(Method::GET, "/v1/heartbeat")?.send().await?;
+		Ok(response.status() == StatusCode::OK)
+	}
+
+	pub async fn list_networks(&self, pagination: &Pagination) -> ClientResult<Vec<Network>> {
+		Ok(self.get::<_, NetworksResponse>("/v1/networks", pagination).await?.networks)
+	}
+
+	pub async fn get_network(&self, id: &str) -> ClientResult<Network> {
+		Ok(self.get::<_, NetworkResponse>(&format!("/v1/networks/{id}"), &()).await?.network)
+	}
+
+	pub async fn create_network(&self, network: &CreateNetwork) -> ClientResult<Network> {
+		self.post("/v1/networks", network).await
+	}
+
+	pub async fn update_network(&self, id: &str, network: &UpdateNetwork) -> ClientResult<()> {
+		self.put(&format!("/v1/networks/{id}"), network).await
+	}
+
+	pub async fn delete_networks(&self, ids: &[&str]) -> ClientResult<()> {
+		self.delete("/v1/networks", &json!({ "networks": ids })).await
+	}
+
+	pub async fn list_keys(&self, pagination: &Pagination) -> ClientResult<Vec<ApiKey>> {
+		Ok(self.get::<_, KeysResponse>("/v1/keys", pagination).await?.keys)
+	}
+
+	pub async fn get_key(&self, id: &str) -> ClientResult<ApiKey> {
+		Ok(self.get::<_, KeyResponse>(&format!("/v1/keys/{id}"), &()).await?.key)
+	}
+
+	// the returned key's secret is only available this once
+	pub async fn create_key(&self, key: &CreateKey) -> ClientResult<ApiKey> {
+		self.post("/v1/keys", key).await
+	}
+
+	pub async fn update_key(&self, id: &str, key: &UpdateKey) -> ClientResult<()> {
+		self.put(&format!("/v1/keys/{id}"), key).await
+	}
+
+	pub async fn delete_keys(&self, ids: &[&str]) -> ClientResult<()> {
+		self.delete("/v1/keys", &json!({ "keys": ids })).await
+	}
+
+	pub async fn list_entities(&self, pagination: &Pagination) -> ClientResult<EntitiesResponse> {
+		self.get("/v1/entities", pagination).await
+	}
+
+	pub async fn get_entity(&self, id: &str) -> ClientResult<EntityResponse> {
+		self.get(&format!("/v1/entities/{id}"), &()).await
+	}
+
+	pub async fn create_entity(&self, entity: &CreateEntity) -> ClientResult<Entity> {
+		self.post("/v1/entities", entity).await
+	}
+
+	pub async fn update_entity(&self, id: &str, entity: &UpdateEntity) -> ClientResult<()> {
+		self.put(&format!("/v1/entities/{id}"), entity).await
+	}
+
+	pub async fn delete_entities(&self, ids: &[&str]) -> ClientResult<()> {
+		self.delete("/v1/entities", &json!({ "entities": ids })).await
+	}
+
+	pub async fn list_tags(&self, pagination: &Pagination) -> ClientResult<TagsResponse> {
+		self.get("/v1/tags", pagination).await
+	}
+
+	pub async fn get_tag(&self, id: &str) -> ClientResult<TagResponse> {
+		self.get(&format!("/v1/tags/{id}"), &()).await
+	}
+
+	pub async fn create_tag(&self, tag: &CreateTag) -> ClientResult<Tag> {
+		self.post("/v1/tags", tag).await
+	}
+
+	pub async fn update_tag(&self, id: &str, tag: &UpdateTag) -> ClientResult<()> {
+		self.put(&format!("/v1/tags/{id}"), tag).await
+	}
+
+	pub async fn delete_tags(&self, ids: &[&str]) -> ClientResult<()> {
+		self.delete("/v1/tags", &json!({ "tags": ids })).await
+	}
+
+	pub async fn list_addresses(&self, pagination: &Pagination) -> ClientResult<AddressesResponse> {
+		self.get("/v1/addresses", pagination).await
+	}
+
+	pub async fn get_address(&self, id: &str) -> ClientResult<AddressResponse> {
+		self.get(&format!("/v1/addresses/{id}"), &()).await
+	}
+
+	pub async fn create_addresses(
+		&self,
+		addresses: &CreateAddresses,
+	) -> ClientResult<Vec<Address>> {
+		self.post("/v1/addresses", addresses).await
+	}
+
+	pub async fn delete_addresses(&self, ids: &[&str]) -> ClientResult<()> {
+		self.delete("/v1/addresses", &json!({ "addresses": ids })).await
+	}
+
+	// pass `next_cursor` back in `ListTransfers::cursor` to get the next page
+	pub async fn list_transfers(&self, query: &ListTransfers) -> ClientResult<TransfersResponse> {
+		self.get("/v1/transfers", query).await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_deserialize() {
+		let key = serde_json::from_value::<ApiKey>(json!({
+			"id": "key_123",
+			"isActive": true,
+			"clientIdentity": null,
+			"createdAt": "2024-01-01T00:00:00",
+			"key": null,
+		}))
+		.unwrap();
+		assert_eq!(key.id, "key_123");
+		assert!(key.usage.is_none());
+
+		let error = serde_json::from_value::<ErrorBody>(json!({ "error": "not found" })).unwrap();
+		assert_eq!(error.error, "not found");
+		assert!(error.request_id.is_none());
+	}
+}
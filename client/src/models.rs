@@ -0,0 +1,550 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+// @NOTE these mirror the request payloads and responses of the server's v1
+// handlers (`server/src/handlers/v1`); keep them in sync when those change
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Architecture {
+	#[default]
+	Bitcoin,
+	Evm,
+	Solana,
+	Tron,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RiskLevel {
+	Low,
+	High,
+	Critical,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RiskReason {
+	Entity,
+	Source,
+	Mixer,
+	Approval,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Network {
+	pub id: String,
+	pub name: String,
+	pub architecture: Architecture,
+	pub chain_id: i64,
+	pub block_time: i64,
+	pub rpc_endpoint: String,
+	pub rpc_endpoints: Option<Value>,
+	pub rps: i32,
+	pub token_allowlist: Option<Value>,
+	pub token_denylist: Option<Value>,
+	pub large_transfer_threshold: Option<String>,
+	pub confirmations: i32,
+	pub wrapped_native_tokens: Option<Value>,
+	pub dormancy_days: Option<i32>,
+	pub price_id: Option<String>,
+	pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKey {
+	pub id: String,
+	pub is_active: bool,
+	pub client_identity: Option<String>,
+	pub created_at: NaiveDateTime,
+	// only returned once, right after the key is created
+	pub key: Option<String>,
+	pub usage: Option<ApiKeyUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyUsage {
+	pub request_count: i64,
+	pub last_used_at: Option<NaiveDateTime>,
+	pub top_endpoints: Vec<EndpointUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointUsage {
+	pub endpoint: String,
+	pub request_count: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entity {
+	pub id: String,
+	pub name: Option<String>,
+	pub description: String,
+	pub data: Value,
+	pub created_at: NaiveDateTime,
+	pub tags: Option<Vec<String>>,
+	pub addresses: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+	pub id: String,
+	pub name: String,
+	pub risk_level: RiskLevel,
+	pub is_mixer: bool,
+	pub is_exchange: bool,
+	pub is_bridge: bool,
+	pub category: Option<String>,
+	pub created_at: NaiveDateTime,
+	pub entities: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Address {
+	pub id: String,
+	pub network: String,
+	pub address: String,
+	pub description: String,
+	pub data: Value,
+	pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Token {
+	pub id: String,
+	pub name: String,
+	pub symbol: String,
+	pub address: String,
+	pub decimals: i16,
+	pub created_at: NaiveDateTime,
+}
+
+// GET /v1/info
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoRisk {
+	pub level: RiskLevel,
+	pub reasons: HashSet<RiskReason>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoAsset {
+	pub network: String,
+	pub token: Option<String>,
+	pub balance: String,
+	pub balance_formatted: Option<String>,
+	// only for assets with a known price
+	pub value_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoToken {
+	pub id: String,
+	pub name: String,
+	pub symbol: String,
+	pub address: String,
+	pub decimals: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoSource {
+	pub network: String,
+	pub entity: String,
+	pub from: String,
+	pub to: String,
+	pub hops: u64,
+	pub obfuscated: bool,
+	#[serde(default)]
+	pub bridges: Vec<String>,
+	// only with `expand=transfers`
+	pub transfers: Option<Vec<InfoSourceTransfer>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoSourceTransfer {
+	pub network: String,
+	pub tx_hash: String,
+	pub block_height: u64,
+	pub from: String,
+	pub to: String,
+	pub token: Option<String>,
+	pub amount: String,
+	pub value_usd: Option<f64>,
+	pub transferred_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoApproval {
+	pub network: String,
+	pub token: String,
+	pub owner: String,
+	pub spender: String,
+	pub entity: Option<String>,
+	pub amount: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoActivity {
+	pub network: String,
+	pub address: String,
+	pub last_sent_at: Option<NaiveDateTime>,
+	pub dormant_days: Option<u64>,
+	pub coin_age: Vec<InfoCoinAge>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoCoinAge {
+	pub token: Option<String>,
+	pub days: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoFunder {
+	pub network: String,
+	pub address: String,
+	pub funder: String,
+	pub entity: Option<String>,
+	pub tx_hash: String,
+	pub block_height: u64,
+	pub funded_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoNetwork {
+	pub id: String,
+	pub name: String,
+	pub chain_id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoEntity {
+	pub id: String,
+	pub name: Option<String>,
+	pub description: String,
+	pub data: Value,
+	pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoTag {
+	pub id: String,
+	pub name: String,
+	pub risk_level: RiskLevel,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {
+	pub addresses: Vec<String>,
+	pub risk: InfoRisk,
+	pub assets: Vec<InfoAsset>,
+	pub tokens: Vec<InfoToken>,
+	pub sources: Vec<InfoSource>,
+	#[serde(default)]
+	pub approvals: Vec<InfoApproval>,
+	#[serde(default)]
+	pub activity: Vec<InfoActivity>,
+	#[serde(default)]
+	pub funders: Vec<InfoFunder>,
+	pub networks: Vec<InfoNetwork>,
+	pub entities: Vec<InfoEntity>,
+	pub tags: Vec<InfoTag>,
+}
+
+// /v1/networks
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateNetwork {
+	pub id: Option<String>,
+	pub name: String,
+	pub architecture: Architecture,
+	pub block_time: u64,
+	pub rpc_endpoint: String,
+	// fallbacks, in order
+	pub rpc_endpoints: Option<Vec<String>>,
+	pub chain_id: Option<u64>,
+	pub rps: Option<u32>,
+	pub token_allowlist: Option<Vec<String>>,
+	pub token_denylist: Option<Vec<String>>,
+	// in usd
+	pub large_transfer_threshold: Option<String>,
+	pub confirmations: Option<u32>,
+	pub wrapped_native_tokens: Option<Vec<String>>,
+	pub dormancy_days: Option<u32>,
+	pub price_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNetwork {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub architecture: Option<Architecture>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub chain_id: Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub block_time: Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub rpc_endpoint: Option<String>,
+	// an empty list removes the fallbacks
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub rpc_endpoints: Option<Vec<String>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub rps: Option<u32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub token_allowlist: Option<Vec<String>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub token_denylist: Option<Vec<String>>,
+	// in usd, and an empty string removes the threshold
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub large_transfer_threshold: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub confirmations: Option<u32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub wrapped_native_tokens: Option<Vec<String>>,
+	// zero turns dormancy alerts off
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub dormancy_days: Option<u32>,
+	// an empty string turns prices off
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub price_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkResponse {
+	pub network: Network,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworksResponse {
+	pub networks: Vec<Network>,
+}
+
+// /v1/keys
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateKey {
+	pub id: Option<String>,
+	pub client_identity: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateKey {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub is_active: Option<bool>,
+	// an empty string unmaps the client certificate
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub client_identity: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyResponse {
+	pub key: ApiKey,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeysResponse {
+	pub keys: Vec<ApiKey>,
+}
+
+// /v1/entities
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateEntity {
+	pub id: Option<String>,
+	pub name: Option<String>,
+	pub description: String,
+	pub data: Option<Value>,
+	pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateEntity {
+	// `Some(None)` removes the name
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<Option<String>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub data: Option<Value>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityResponse {
+	pub entity: Entity,
+	pub tags: Vec<Tag>,
+	pub addresses: Vec<Address>,
+	pub networks: Vec<Network>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntitiesResponse {
+	pub entities: Vec<Entity>,
+	pub tags: Vec<Tag>,
+	pub addresses: Vec<Address>,
+	pub networks: Vec<Network>,
+}
+
+// /v1/tags
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTag {
+	pub id: Option<String>,
+	pub name: String,
+	pub risk_level: RiskLevel,
+	pub is_mixer: Option<bool>,
+	pub is_exchange: Option<bool>,
+	pub is_bridge: Option<bool>,
+	pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTag {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub risk_level: Option<RiskLevel>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub is_mixer: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub is_exchange: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub is_bridge: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagResponse {
+	pub tag: Tag,
+	pub entities: Vec<Entity>,
+	pub addresses: Vec<Address>,
+	pub networks: Vec<Network>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagsResponse {
+	pub tags: Vec<Tag>,
+	pub entities: Vec<Entity>,
+	pub addresses: Vec<Address>,
+	pub networks: Vec<Network>,
+}
+
+// /v1/addresses
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAddress {
+	pub address: String,
+	pub description: String,
+	pub data: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAddresses {
+	pub entity: String,
+	pub network: String,
+	pub addresses: Vec<CreateAddress>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressResponse {
+	pub address: Address,
+	pub networks: Vec<Network>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressesResponse {
+	pub addresses: Vec<Address>,
+	pub networks: Vec<Network>,
+}
+
+// GET /v1/transfers
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTransfers {
+	pub network: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub address: Option<String>,
+	// eg: `amount>1e18 AND asset=USDT AND direction=in`
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub filter: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub cursor: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transfer {
+	pub id: String,
+	pub block_height: u64,
+	pub tx_hash: String,
+	pub from: String,
+	pub to: String,
+	pub token: Option<String>,
+	pub amount: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransfersResponse {
+	pub network: String,
+	pub transfers: Vec<Transfer>,
+	pub tokens: Vec<Token>,
+	pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub offset: Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub limit: Option<u64>,
+}
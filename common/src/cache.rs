@@ -0,0 +1,163 @@
+use chrono::NaiveDateTime;
+use eyre::Result;
+use sea_orm::ConnectionTrait;
+use std::{collections::HashMap, hash::Hash};
+use tokio::{
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+use crate::{
+	models::{Address, Balance, Config, ConfigKey, Entity, JoinedTag, PrimaryId},
+	BlockHeight,
+};
+
+pub type EntityCache = Cache<String, Vec<(Address, Entity, Vec<JoinedTag>)>>;
+pub type BalanceCache = Cache<(PrimaryId, String, BlockHeight), Vec<Balance>>;
+pub type ResponseCache = Cache<String, serde_json::Value>;
+
+struct Entry<V> {
+	value: V,
+	inserted_at: Instant,
+	accessed_at: Instant,
+}
+
+// @NOTE in-process cache with a ttl and least-recently-used eviction. it's
+// invalidated as a whole whenever `marker` config key is touched (same way
+// networks are reconnected when `ConfigKey::NetworksUpdated` changes)
+pub struct Cache<K, V> {
+	marker: ConfigKey,
+	ttl: Duration,
+	capacity: usize,
+	data: Mutex<HashMap<K, Entry<V>>>,
+	synced_at: Mutex<Option<NaiveDateTime>>,
+}
+
+impl<K, V> Cache<K, V>
+where
+	K: Eq + Hash + Clone,
+	V: Clone,
+{
+	pub fn new(marker: ConfigKey, ttl: u64, capacity: usize) -> Self {
+		Self {
+			marker,
+			ttl: Duration::from_secs(ttl),
+			capacity,
+			data: Mutex::new(HashMap::new()),
+			synced_at: Mutex::new(None),
+		}
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		!self.ttl.is_zero() && self.capacity > 0
+	}
+
+	pub async fn get(&self, key: &K) -> Option<V> {
+		if !self.is_enabled() {
+			return None;
+		}
+
+		let mut data = self.data.lock().await;
+
+		match data.get_mut(key) {
+			Some(entry) if entry.inserted_at.elapsed() < self.ttl => {
+				entry.accessed_at = Instant::now();
+				Some(entry.value.clone())
+			}
+			Some(_) => {
+				data.remove(key);
+				None
+			}
+			None => None,
+		}
+	}
+
+	pub async fn insert(&self, key: K, value: V) {
+		if !self.is_enabled() {
+			return;
+		}
+
+		let mut data = self.data.lock().await;
+
+		// evict expired entries first, then least recently used ones
+		if data.len() >= self.capacity && !data.contains_key(&key) {
+			data.retain(|_, entry| entry.inserted_at.elapsed() < self.ttl);
+
+			while data.len() >= self.capacity {
+				let lru_key = data
+					.iter()
+					.min_by_key(|(_, entry)| entry.accessed_at)
+					.map(|(key, _)| key.clone());
+
+				match lru_key {
+					Some(lru_key) => data.remove(&lru_key),
+					None => break,
+				};
+			}
+		}
+
+		let now = Instant::now();
+		data.insert(key, Entry { value, inserted_at: now, accessed_at: now });
+	}
+
+	pub async fn clear(&self) {
+		self.data.lock().await.clear();
+	}
+
+	pub async fn len(&self) -> usize {
+		self.data.lock().await.len()
+	}
+
+	pub async fn is_empty(&self) -> bool {
+		self.len().await == 0
+	}
+
+	pub async fn sync<C>(&self, c: &C) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		if !self.is_enabled() {
+			return Ok(());
+		}
+
+		// marker is only set once something has changed
+		let updated_at = Config::get::<_, u8>(c, self.marker).await?.map(|v| v.updated_at);
+
+		let mut synced_at = self.synced_at.lock().await;
+		if updated_at.is_some() && *synced_at < updated_at {
+			self.clear().await;
+			*synced_at = updated_at;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn test_cache_evicts_least_recently_used() {
+		let cache = Cache::<u8, u8>::new(ConfigKey::EntitiesUpdated, 60, 2);
+
+		cache.insert(1, 1).await;
+		cache.insert(2, 2).await;
+		assert_eq!(cache.get(&1).await, Some(1));
+
+		cache.insert(3, 3).await;
+		assert_eq!(cache.len().await, 2);
+		assert_eq!(cache.get(&1).await, Some(1));
+		assert_eq!(cache.get(&2).await, None);
+		assert_eq!(cache.get(&3).await, Some(3));
+	}
+
+	#[tokio::test]
+	async fn test_cache_disabled() {
+		let cache = Cache::<u8, u8>::new(ConfigKey::EntitiesUpdated, 0, 2);
+
+		cache.insert(1, 1).await;
+		assert_eq!(cache.get(&1).await, None);
+		assert!(cache.is_empty().await);
+	}
+}
@@ -1,6 +1,6 @@
 use base64::{engine::general_purpose, Engine as _};
 use bitcoin::{consensus::encode, Block, BlockHash};
-use bitcoincore_rpc_json::GetBlockchainInfoResult;
+use bitcoincore_rpc_json::{GetBlockchainInfoResult, ScanTxOutResult};
 use derive_more::{Display, Error};
 use eyre::Result;
 use reqwest::header::AUTHORIZATION;
@@ -85,6 +85,15 @@ impl Client {
 		Ok(encode::deserialize_hex(result.as_str().unwrap())?)
 	}
 
+	// unspent outputs of `address` at the node's tip. slow, since it goes
+	// through the whole utxo set
+	pub async fn scan_tx_out_set(&self, address: &str) -> Result<ScanTxOutResult> {
+		let result = self
+			.request("scantxoutset", &["start".into(), json!([format!("addr({address})")])])
+			.await?;
+		Ok(serde_json::from_value(result)?)
+	}
+
 	async fn request(&self, method: &str, params: &[JsonValue]) -> Result<JsonValue> {
 		let client = reqwest::Client::new();
 		let mut req = client.post(&self.url);
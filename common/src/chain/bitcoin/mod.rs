@@ -1,16 +1,24 @@
 use async_trait::async_trait;
 use bitcoin::{address::Address, Network as BitcoinNetwork};
 use eyre::Result;
+use futures::future;
 use std::{collections::HashMap, str::FromStr, sync::Arc};
+use tokio::sync::Semaphore;
 use url::Url;
 
 use crate::{
-	chain::{ChainTrait, ModuleId, ModuleTrait, WarehouseData},
-	models::Network,
-	utils, BlockHeight, RateLimiter, Storage,
+	chain::{
+		BlockHashes, ChainTrait, ModuleId, ModuleTrait, PluginModule, WarehouseData,
+		MAX_CONCURRENT_TRANSACTIONS, U256,
+	},
+	metrics::Counter,
+	models::{Block, Network},
+	producers, utils, BlockHeight, RateLimiter, Storage,
 };
 use client::{Auth, Client};
-use modules::{BitcoinBalance, BitcoinCoinbase, BitcoinModuleTrait, BitcoinTransfer};
+pub use modules::BitcoinModuleTrait;
+use modules::{BitcoinBalance, BitcoinCoinbase, BitcoinFeeTransfer, BitcoinTransfer};
+pub use schema::Transaction as BitcoinTransaction;
 use schema::{
 	Block as ParquetBlock, Input as ParquetInput, Output as ParquetOutput, ParquetFile,
 	Transaction as ParquetTransaction,
@@ -26,6 +34,7 @@ pub struct Bitcoin {
 	client: Option<Arc<Client>>,
 	bitcoin_network: BitcoinNetwork,
 	rate_limiter: Option<Arc<RateLimiter>>,
+	rpc_counter: Option<Counter>,
 	modules: Vec<Box<dyn BitcoinModuleTrait>>,
 }
 
@@ -46,19 +55,36 @@ impl Bitcoin {
 			client: None,
 			bitcoin_network,
 			rate_limiter: utils::get_rate_limiter(rps),
+			rpc_counter: None,
 			modules: vec![
 				Box::new(BitcoinTransfer::new(network_id)),
 				Box::new(BitcoinBalance::new(network_id)),
 				Box::new(BitcoinCoinbase::new(network_id)),
+				Box::new(BitcoinFeeTransfer::new(network_id)),
 			],
 		}
 	}
+
+	pub fn with_plugin_modules(mut self, plugin_modules: Vec<PluginModule>) -> Self {
+		for plugin_module in plugin_modules.into_iter() {
+			#[allow(irrefutable_let_patterns)]
+			if let PluginModule::Bitcoin(module) = plugin_module {
+				self.modules.push(module);
+			}
+		}
+
+		self
+	}
 }
 
 #[async_trait]
 impl ChainTrait for Bitcoin {
 	async fn connect(&mut self) -> Result<bool> {
-		if let Ok(u) = Url::parse(&self.network.rpc_endpoint) {
+		for rpc_endpoint in self.network.get_rpc_endpoints().into_iter() {
+			let Ok(u) = Url::parse(&rpc_endpoint) else {
+				continue;
+			};
+
 			let auth = match (u.username(), u.password()) {
 				(username, Some(password)) => {
 					Auth::UserPass(username.to_string(), password.to_string())
@@ -70,10 +96,11 @@ impl ChainTrait for Bitcoin {
 				rate_limiter.until_ready().await;
 			}
 
-			let client = Client::new_without_retry(&self.network.rpc_endpoint, auth.clone());
+			let client = Client::new_without_retry(&rpc_endpoint, auth.clone());
 			if client.get_blockchain_info().await.is_ok() {
-				self.client = Some(Arc::new(Client::new(&self.network.rpc_endpoint, auth)));
-				self.rpc = Some(self.network.rpc_endpoint.clone());
+				self.client = Some(Arc::new(Client::new(&rpc_endpoint, auth)));
+				self.rpc = Some(rpc_endpoint);
+				break;
 			}
 		}
 
@@ -100,6 +127,14 @@ impl ChainTrait for Bitcoin {
 		self.rate_limiter.clone()
 	}
 
+	fn set_rpc_counter(&mut self, counter: Counter) {
+		self.rpc_counter = Some(counter);
+	}
+
+	fn get_rpc_counter(&self) -> Option<Counter> {
+		self.rpc_counter.clone()
+	}
+
 	fn format_address(&self, address: &str) -> String {
 		if let Ok(unknown_address) = Address::from_str(address) {
 			if let Ok(parsed_address) = unknown_address.require_network(self.bitcoin_network) {
@@ -115,6 +150,42 @@ impl ChainTrait for Bitcoin {
 		Ok(self.client.as_ref().unwrap().get_block_count().await?)
 	}
 
+	// @NOTE `scantxoutset` only sees the current utxo set, so the balance is as
+	// of the node's tip regardless of `block_height`
+	async fn get_balance(
+		&self,
+		address: &str,
+		_block_height: BlockHeight,
+	) -> Result<Option<(BlockHeight, U256)>> {
+		self.rate_limit().await;
+		let result = self.client.as_ref().unwrap().scan_tx_out_set(address).await?;
+
+		Ok(result.height.map(|height| (height, U256::from(result.total_amount.to_sat()))))
+	}
+
+	async fn get_block_hash(&self, block_height: BlockHeight) -> Result<Option<String>> {
+		self.rate_limit().await;
+		let block_hash = self.client.as_ref().unwrap().get_block_hash(block_height).await?;
+
+		Ok(Some(block_hash.to_string()))
+	}
+
+	async fn get_block_producer_tag(&self, block_height: BlockHeight) -> Result<Option<String>> {
+		let client = self.client.as_ref().unwrap();
+
+		self.rate_limit().await;
+		let block_hash = client.get_block_hash(block_height).await?;
+		self.rate_limit().await;
+		let block = client.get_block(&block_hash).await?;
+
+		Ok(block
+			.txdata
+			.first()
+			.filter(|tx| tx.is_coinbase())
+			.and_then(|tx| tx.input.first())
+			.and_then(|txin| producers::get_tag(txin.script_sig.as_bytes())))
+	}
+
 	async fn process_block(
 		&self,
 		storage: Arc<Storage>,
@@ -131,25 +202,49 @@ impl ChainTrait for Bitcoin {
 			_ => return Ok(ret),
 		};
 
+		// record block hash, so it can be checked for canonicality later
+		warehouse_data.blocks.insert(Block::new(
+			self.network.network_id,
+			block_height,
+			&block.hash.to_string(),
+			&block.prev_blockhash.to_string(),
+			block.time,
+		));
+
 		let all_txs = ParquetTransaction::get_all(&storage_db)?;
-		let all_tx_inputs = ParquetInput::get_all(&storage_db, None)?;
-		let all_tx_outputs = ParquetOutput::get_all(&storage_db, None)?;
+		// group inputs and outputs by their tx
+		let mut all_tx_inputs = HashMap::<_, Vec<ParquetInput>>::new();
+		for input in ParquetInput::get_all(&storage_db, None)?.into_iter() {
+			all_tx_inputs.entry(input.tx_hash).or_default().push(input);
+		}
+		let mut all_tx_outputs = HashMap::<_, Vec<ParquetOutput>>::new();
+		for output in ParquetOutput::get_all(&storage_db, None)?.into_iter() {
+			all_tx_outputs.entry(output.tx_hash).or_default().push(output);
+		}
 
-		for tx in all_txs.into_iter() {
-			warehouse_data += self
-				.process_transaction(
+		// process txs concurrently
+		let semaphore = &Semaphore::new(MAX_CONCURRENT_TRANSACTIONS);
+		let futures = all_txs.into_iter().map(|tx| {
+			let tx_inputs = all_tx_inputs.remove(&tx.hash).unwrap_or_default();
+			let tx_outputs = all_tx_outputs.remove(&tx.hash).unwrap_or_default();
+			let module_ids = module_ids.clone();
+
+			async move {
+				let _permit = semaphore.acquire().await?;
+				self.process_transaction(
 					block_height,
 					block.time,
-					tx.clone(),
-					all_tx_inputs
-						.clone()
-						.into_iter()
-						.filter(|i| i.tx_hash == tx.clone().hash)
-						.collect(),
-					all_tx_outputs.clone().into_iter().filter(|o| o.tx_hash == tx.hash).collect(),
-					module_ids.clone(),
+					tx,
+					tx_inputs,
+					tx_outputs,
+					module_ids,
 				)
-				.await?;
+				.await
+			}
+		});
+
+		for tx_warehouse_data in future::try_join_all(futures).await?.into_iter() {
+			warehouse_data += tx_warehouse_data;
 		}
 
 		ret = Some(warehouse_data);
@@ -161,13 +256,24 @@ impl ChainTrait for Bitcoin {
 		&self,
 		storage: Arc<Storage>,
 		block_height: BlockHeight,
-	) -> Result<bool> {
+	) -> Result<Option<BlockHashes>> {
+		let mut ret = None;
 		let storage_db = storage.get(self.network.network_id, block_height)?;
 
+		// already extracted (eg: by a chunk worker before it restarted)
+		if let Some(block_hashes) = storage_db.get_manifest()? {
+			return Ok(Some(block_hashes));
+		}
+
 		self.rate_limit().await;
 		if let Ok(block_hash) = self.client.as_ref().unwrap().get_block_hash(block_height).await {
 			self.rate_limit().await;
 			if let Ok(block) = self.client.as_ref().unwrap().get_block(&block_hash).await {
+				ret = Some(BlockHashes {
+					hash: block_hash.to_string(),
+					parent_hash: block.header.prev_blockhash.to_string(),
+				});
+
 				storage_db.insert(ParquetBlock {
 					hash: block_hash,
 					version: block.header.version,
@@ -207,14 +313,19 @@ impl ChainTrait for Bitcoin {
 			}
 		}
 
-		storage_db.commit(vec![
+		let files = vec![
 			ParquetFile::Blocks.to_string(),
 			ParquetFile::Transactions.to_string(),
 			ParquetFile::Inputs.to_string(),
 			ParquetFile::Outputs.to_string(),
-		])?;
+		];
 
-		Ok(true)
+		storage_db.commit(files.clone())?;
+		if let Some(block_hashes) = &ret {
+			storage_db.save_manifest(&files, block_hashes)?;
+		}
+
+		Ok(ret)
 	}
 }
 
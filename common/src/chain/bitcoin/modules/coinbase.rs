@@ -48,6 +48,7 @@ impl BitcoinModuleTrait for BitcoinCoinbase {
 					self.network_id,
 					block_height,
 					&tx_hash.clone(),
+					0,
 					"",
 					&to,
 					None,
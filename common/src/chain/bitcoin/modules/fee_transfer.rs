@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use eyre::Result;
+use std::collections::HashMap;
+
+use crate::{
+	chain::{
+		bitcoin::{modules::BitcoinModuleTrait, schema::Transaction as ParquetTransaction},
+		ModuleId, ModuleTrait, WarehouseData, U256,
+	},
+	models::{PrimaryId, Transfer},
+	BlockHeight,
+};
+
+pub struct BitcoinFeeTransfer {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for BitcoinFeeTransfer {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::BitcoinFeeTransfer
+	}
+}
+
+#[async_trait]
+impl BitcoinModuleTrait for BitcoinFeeTransfer {
+	// @NOTE the fee (inputs - outputs) is what's left over from a tx, and the
+	// miner claims it through the coinbase. it's recorded as sent to "" by the
+	// inputs, pro-rata, mirroring coinbase transfers that come from "". that way
+	// an address's outgoing transfers add up to everything it spent
+	async fn run(
+		&self,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: ParquetTransaction,
+		inputs: HashMap<String, u64>,
+		outputs: HashMap<String, u64>,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+
+		if tx.is_coinbase {
+			return Ok(ret);
+		}
+
+		// skip if some inputs couldn't be resolved to an address
+		let input_amount_total: u64 = inputs.values().sum();
+		let output_amount_total: u64 = outputs.values().sum();
+		let fee = input_amount_total.saturating_sub(output_amount_total);
+		if fee == 0 {
+			return Ok(ret);
+		}
+
+		let tx_hash = tx.hash.to_string();
+		let batch_amount = U256::from_str_radix(&fee.to_string(), 10)?;
+
+		for (from, amount) in inputs.into_iter() {
+			let amount = ((amount as f64 / input_amount_total as f64) * fee as f64).round();
+
+			ret.transfers.insert(Transfer::new(
+				self.get_id(),
+				self.network_id,
+				block_height,
+				&tx_hash,
+				0,
+				&from,
+				"",
+				None,
+				U256::from_str_radix(&amount.to_string(), 10)?,
+				batch_amount,
+				block_time,
+			));
+		}
+
+		Ok(ret)
+	}
+}
@@ -8,10 +8,12 @@ use crate::{
 };
 pub use balance::BitcoinBalance;
 pub use coinbase::BitcoinCoinbase;
+pub use fee_transfer::BitcoinFeeTransfer;
 pub use transfer::BitcoinTransfer;
 
 mod balance;
 mod coinbase;
+mod fee_transfer;
 mod transfer;
 
 #[async_trait]
@@ -61,6 +61,7 @@ impl BitcoinModuleTrait for BitcoinTransfer {
 						self.network_id,
 						block_height,
 						&tx_hash.clone(),
+						0,
 						&from,
 						&to,
 						None,
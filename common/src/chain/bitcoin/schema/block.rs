@@ -2,7 +2,7 @@ use bitcoin::{
 	blockdata::block::Version, hash_types::TxMerkleNode, hashes::Hash, pow::CompactTarget,
 	BlockHash,
 };
-use duckdb::{params, Connection};
+use duckdb::{params, Appender, Connection};
 use eyre::Result;
 
 use super::ParquetFile;
@@ -23,9 +23,8 @@ impl Block {
 	pub fn get(storage_db: &StorageDb) -> Result<Option<Block>> {
 		let mut ret = None;
 
-		if let Some(path) = storage_db.get_path(&ParquetFile::Blocks.to_string())? {
-			let mut statement =
-				storage_db.db.prepare(&format!("SELECT * FROM read_parquet('{path}')"))?;
+		if let Some(source) = storage_db.get_source(&ParquetFile::Blocks.to_string())? {
+			let mut statement = storage_db.db.prepare(&format!("SELECT * FROM {source}"))?;
 			let mut rows = statement.query([])?;
 
 			if let Some(row) = rows.next()? {
@@ -50,9 +49,13 @@ impl Block {
 }
 
 impl StorageModelTrait for Block {
+	fn get_table(&self) -> String {
+		ParquetFile::Blocks.to_string()
+	}
+
 	fn create_table(&self, db: &Connection) -> Result<()> {
 		db.execute_batch(&format!(
-			r#"CREATE TEMP TABLE IF NOT EXISTS {} (
+			r#"CREATE TABLE IF NOT EXISTS {} (
                 hash BLOB NOT NULL,
                 version INT32 NOT NULL,
                 prev_blockhash BLOB NOT NULL,
@@ -67,28 +70,16 @@ impl StorageModelTrait for Block {
 		Ok(())
 	}
 
-	fn insert(&self, db: &Connection) -> Result<()> {
-		self.create_table(db)?;
-
-		db.execute(
-			&format!(
-				r#"INSERT INTO {} (
-                    hash, version, prev_blockhash, merkle_root, time, bits, nonce
-                ) VALUES (
-                    ?, ?, ?, ?, ?, ?, ?
-                );"#,
-				ParquetFile::Blocks
-			),
-			params![
-				<BlockHash as AsRef<[u8]>>::as_ref(&self.hash),
-				self.version.to_consensus(),
-				<BlockHash as AsRef<[u8]>>::as_ref(&self.prev_blockhash),
-				<TxMerkleNode as AsRef<[u8]>>::as_ref(&self.merkle_root),
-				self.time,
-				self.bits.to_consensus(),
-				self.nonce,
-			],
-		)?;
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			<BlockHash as AsRef<[u8]>>::as_ref(&self.hash),
+			self.version.to_consensus(),
+			<BlockHash as AsRef<[u8]>>::as_ref(&self.prev_blockhash),
+			<TxMerkleNode as AsRef<[u8]>>::as_ref(&self.merkle_root),
+			self.time,
+			self.bits.to_consensus(),
+			self.nonce,
+		])?;
 
 		Ok(())
 	}
@@ -1,5 +1,5 @@
 use bitcoin::hashes::{self, sha256d::Hash};
-use duckdb::{params, Connection};
+use duckdb::{params, Appender, Connection};
 use eyre::Result;
 
 use super::ParquetFile;
@@ -16,8 +16,8 @@ impl Input {
 	pub fn get_all(storage_db: &StorageDb, tx_hash: Option<Hash>) -> Result<Vec<Input>> {
 		let mut ret = vec![];
 
-		if let Some(path) = storage_db.get_path(&ParquetFile::Inputs.to_string())? {
-			let mut query = format!("SELECT * FROM read_parquet('{path}')");
+		if let Some(source) = storage_db.get_source(&ParquetFile::Inputs.to_string())? {
+			let mut query = format!("SELECT * FROM {source}");
 			if let Some(hash) = tx_hash {
 				query.push_str(&format!(" WHERE tx_hash='{hash}'"));
 			}
@@ -42,9 +42,13 @@ impl Input {
 }
 
 impl StorageModelTrait for Input {
+	fn get_table(&self) -> String {
+		ParquetFile::Inputs.to_string()
+	}
+
 	fn create_table(&self, db: &Connection) -> Result<()> {
 		db.execute_batch(&format!(
-			r#"CREATE TEMP TABLE IF NOT EXISTS {} (
+			r#"CREATE TABLE IF NOT EXISTS {} (
                 tx_hash BLOB NOT NULL,
                 previous_output_tx_hash BLOB NOT NULL,
                 previous_output_vout UINT32 NOT NULL
@@ -55,24 +59,12 @@ impl StorageModelTrait for Input {
 		Ok(())
 	}
 
-	fn insert(&self, db: &Connection) -> Result<()> {
-		self.create_table(db)?;
-
-		db.execute(
-			&format!(
-				r#"INSERT INTO {} (
-                    tx_hash, previous_output_tx_hash, previous_output_vout
-                ) VALUES (
-                    ?, ?, ?
-                );"#,
-				ParquetFile::Inputs
-			),
-			params![
-				<Hash as AsRef<[u8]>>::as_ref(&self.tx_hash),
-				<Hash as AsRef<[u8]>>::as_ref(&self.previous_output_tx_hash),
-				self.previous_output_vout
-			],
-		)?;
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			<Hash as AsRef<[u8]>>::as_ref(&self.tx_hash),
+			<Hash as AsRef<[u8]>>::as_ref(&self.previous_output_tx_hash),
+			self.previous_output_vout
+		])?;
 
 		Ok(())
 	}
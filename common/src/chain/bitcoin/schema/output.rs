@@ -3,7 +3,7 @@ use bitcoin::{
 	blockdata::script::ScriptBuf,
 	hashes::{self, sha256d::Hash},
 };
-use duckdb::{params, Connection};
+use duckdb::{params, Appender, Connection};
 use eyre::Result;
 
 use super::ParquetFile;
@@ -20,8 +20,8 @@ impl Output {
 	pub fn get_all(storage_db: &StorageDb, tx_hash: Option<Hash>) -> Result<Vec<Output>> {
 		let mut ret = vec![];
 
-		if let Some(path) = storage_db.get_path(&ParquetFile::Outputs.to_string())? {
-			let mut query = format!("SELECT * FROM read_parquet('{path}')");
+		if let Some(source) = storage_db.get_source(&ParquetFile::Outputs.to_string())? {
+			let mut query = format!("SELECT * FROM {source}");
 			if let Some(hash) = tx_hash {
 				query.push_str(&format!(" WHERE tx_hash='{hash}'"));
 			}
@@ -46,9 +46,13 @@ impl Output {
 }
 
 impl StorageModelTrait for Output {
+	fn get_table(&self) -> String {
+		ParquetFile::Outputs.to_string()
+	}
+
 	fn create_table(&self, db: &Connection) -> Result<()> {
 		db.execute_batch(&format!(
-			r#"CREATE TEMP TABLE IF NOT EXISTS {} (
+			r#"CREATE TABLE IF NOT EXISTS {} (
                 tx_hash BLOB NOT NULL,
                 value UINT64 NOT NULL,
                 script_pubkey BLOB NOT NULL
@@ -59,24 +63,12 @@ impl StorageModelTrait for Output {
 		Ok(())
 	}
 
-	fn insert(&self, db: &Connection) -> Result<()> {
-		self.create_table(db)?;
-
-		db.execute(
-			&format!(
-				r#"INSERT INTO {} (
-                    tx_hash, value, script_pubkey
-                ) VALUES (
-                    ?, ?, ?
-                );"#,
-				ParquetFile::Outputs
-			),
-			params![
-				<Hash as AsRef<[u8]>>::as_ref(&self.tx_hash),
-				self.value.to_sat(),
-				self.script_pubkey.clone().into_bytes(),
-			],
-		)?;
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			<Hash as AsRef<[u8]>>::as_ref(&self.tx_hash),
+			self.value.to_sat(),
+			self.script_pubkey.clone().into_bytes(),
+		])?;
 
 		Ok(())
 	}
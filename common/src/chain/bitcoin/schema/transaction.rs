@@ -2,7 +2,7 @@ use bitcoin::{
 	blockdata::{locktime::absolute::LockTime, transaction::Version},
 	hashes::{self, sha256d::Hash},
 };
-use duckdb::{params, Connection};
+use duckdb::{params, Appender, Connection};
 use eyre::Result;
 
 use super::ParquetFile;
@@ -22,9 +22,8 @@ impl Transaction {
 	pub fn get_all(storage_db: &StorageDb) -> Result<Vec<Transaction>> {
 		let mut ret = vec![];
 
-		if let Some(path) = storage_db.get_path(&ParquetFile::Transactions.to_string())? {
-			let mut statement =
-				storage_db.db.prepare(&format!("SELECT * FROM read_parquet('{path}')"))?;
+		if let Some(source) = storage_db.get_source(&ParquetFile::Transactions.to_string())? {
+			let mut statement = storage_db.db.prepare(&format!("SELECT * FROM {source}"))?;
 			let mut rows = statement.query([])?;
 
 			while let Some(row) = rows.next()? {
@@ -46,9 +45,13 @@ impl Transaction {
 }
 
 impl StorageModelTrait for Transaction {
+	fn get_table(&self) -> String {
+		ParquetFile::Transactions.to_string()
+	}
+
 	fn create_table(&self, db: &Connection) -> Result<()> {
 		db.execute_batch(&format!(
-			r#"CREATE TEMP TABLE IF NOT EXISTS {} (
+			r#"CREATE TABLE IF NOT EXISTS {} (
                 hash BLOB NOT NULL,
                 version INT32 NOT NULL,
                 lock_time UINT32 NOT NULL,
@@ -62,27 +65,15 @@ impl StorageModelTrait for Transaction {
 		Ok(())
 	}
 
-	fn insert(&self, db: &Connection) -> Result<()> {
-		self.create_table(db)?;
-
-		db.execute(
-			&format!(
-				r#"INSERT INTO {} (
-                    hash, version, lock_time, input_count, output_count, is_coinbase
-                ) VALUES (
-                    ?, ?, ?, ?, ?, ?
-                );"#,
-				ParquetFile::Transactions
-			),
-			params![
-				<Hash as AsRef<[u8]>>::as_ref(&self.hash),
-				self.version.0,
-				self.lock_time.to_consensus_u32(),
-				self.input_count,
-				self.output_count,
-				self.is_coinbase
-			],
-		)?;
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			<Hash as AsRef<[u8]>>::as_ref(&self.hash),
+			self.version.0,
+			self.lock_time.to_consensus_u32(),
+			self.input_count,
+			self.output_count,
+			self.is_coinbase
+		])?;
 
 		Ok(())
 	}
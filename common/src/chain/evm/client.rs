@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use ethers::providers::{
+	Http, HttpRateLimitRetryPolicy, JsonRpcClient, ProviderError, RetryClient, RetryClientBuilder,
+	Ws,
+};
+use eyre::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt::Debug, time::Duration};
+use url::Url;
+
+// how many times a dropped websocket gets re-established before requests
+// start failing
+const WS_RECONNECTS: usize = 10;
+
+const RATE_LIMIT_RETRIES: u32 = 10;
+const TIMEOUT_RETRIES: u32 = 3;
+const INITIAL_BACKOFF: u64 = 1_000;
+
+// @NOTE the transport is picked by the rpc endpoint's scheme: `ws://` and
+// `wss://` get a websocket that reconnects on its own, anything else goes
+// over http with retries on rate limits
+#[derive(Debug)]
+pub enum EvmClient {
+	Http(RetryClient<Http>),
+	Ws(Ws),
+}
+
+impl EvmClient {
+	pub async fn new(rpc_endpoint: &str) -> Result<Self> {
+		let url = Url::parse(rpc_endpoint)?;
+
+		Ok(match url.scheme() {
+			"ws" | "wss" => Self::Ws(Ws::connect_with_reconnects(url, WS_RECONNECTS).await?),
+			_ => Self::Http(
+				RetryClientBuilder::default()
+					.rate_limit_retries(RATE_LIMIT_RETRIES)
+					.timeout_retries(TIMEOUT_RETRIES)
+					.initial_backoff(Duration::from_millis(INITIAL_BACKOFF))
+					.build(Http::new(url), Box::<HttpRateLimitRetryPolicy>::default()),
+			),
+		})
+	}
+}
+
+#[async_trait]
+impl JsonRpcClient for EvmClient {
+	type Error = ProviderError;
+
+	async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+	where
+		T: Debug + Serialize + Send + Sync,
+		R: DeserializeOwned + Send,
+	{
+		match self {
+			Self::Http(client) => Ok(client.request(method, params).await?),
+			Self::Ws(client) => Ok(client.request(method, params).await?),
+		}
+	}
+}
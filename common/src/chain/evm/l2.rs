@@ -0,0 +1,88 @@
+use ethers::types::{Address, Transaction, TransactionReceipt, H256, U256};
+use serde_json::Value as JsonValue;
+
+// optimism (op stack) deposit transactions, sent from l1
+pub const OPTIMISM_DEPOSIT_TX_TYPE: u64 = 0x7e;
+
+// arbitrum eth deposits from l1 and internal (system) transactions
+pub const ARBITRUM_DEPOSIT_TX_TYPE: u64 = 0x64;
+pub const ARBITRUM_INTERNAL_TX_TYPE: u64 = 0x6a;
+
+// senders of per-block system transactions
+static OPTIMISM_L1_ATTRIBUTES_DEPOSITOR: &str = "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001";
+static ARBITRUM_OS: &str = "0x00000000000000000000000000000000000a4b05";
+
+// @NOTE some rollup nodes leave out signature fields (and a gas price) on
+// transactions that were never signed on l2, which makes them fail to parse.
+// they're filled in with zeroes, which is what newer nodes return anyway
+pub fn normalize_transaction(tx: &mut JsonValue) {
+	if let Some(tx) = tx.as_object_mut() {
+		for field in ["v", "r", "s", "nonce"] {
+			tx.entry(field).or_insert_with(|| JsonValue::String("0x0".to_string()));
+		}
+	}
+}
+
+pub fn get_type(tx: &Transaction) -> Option<u64> {
+	tx.transaction_type.map(|t| t.as_u64())
+}
+
+// system transactions only update chain state and don't move any value
+pub fn is_system_transaction(tx: &Transaction) -> bool {
+	match get_type(tx) {
+		Some(OPTIMISM_DEPOSIT_TX_TYPE) => {
+			tx.other.get_deserialized::<bool>("isSystemTx").and_then(|v| v.ok()) == Some(true) ||
+				is_address(&tx.from, OPTIMISM_L1_ATTRIBUTES_DEPOSITOR)
+		}
+		Some(ARBITRUM_INTERNAL_TX_TYPE) => true,
+		_ => is_address(&tx.from, ARBITRUM_OS),
+	}
+}
+
+// eth bridged over from l1 in this transaction (it didn't exist on l2 before)
+pub fn get_minted(tx: &Transaction) -> Option<U256> {
+	match get_type(tx) {
+		Some(OPTIMISM_DEPOSIT_TX_TYPE) => {
+			tx.other.get_deserialized::<U256>("mint").and_then(|v| v.ok())
+		}
+		Some(ARBITRUM_DEPOSIT_TX_TYPE) => Some(tx.value),
+		_ => None,
+	}
+	.filter(|v| !v.is_zero())
+}
+
+pub fn get_source_hash(tx: &Transaction) -> Option<H256> {
+	tx.other.get_deserialized::<H256>("sourceHash").and_then(|v| v.ok())
+}
+
+// @NOTE op stack charges for l1 data availability separately from l2 gas, so
+// it's not part of `gas_used * effective_gas_price`. arbitrum folds it into
+// `gas_used` instead, so there's nothing extra to add
+pub fn get_l1_fee(receipt: &TransactionReceipt) -> Option<U256> {
+	get_receipt_field(receipt, "l1Fee")
+}
+
+pub fn get_receipt_field(receipt: &TransactionReceipt, field: &str) -> Option<U256> {
+	receipt.other.get_deserialized::<U256>(field).and_then(|v| v.ok())
+}
+
+fn is_address(address: &Address, other: &str) -> bool {
+	format!("{address:?}") == other
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn test_normalize_transaction() {
+		let mut tx = json!({ "v": "0x1b", "type": "0x7e" });
+		normalize_transaction(&mut tx);
+
+		assert_eq!(
+			tx,
+			json!({ "v": "0x1b", "r": "0x0", "s": "0x0", "nonce": "0x0", "type": "0x7e" })
+		);
+	}
+}
@@ -1,78 +1,188 @@
 use async_trait::async_trait;
 use ethers::{
 	self,
-	abi::AbiDecode,
+	abi::{self, Abi as ContractAbi, AbiDecode, ParamType, Token as AbiToken},
 	prelude::*,
-	types::{Address, Log, Transaction, TransactionReceipt, U256, U64},
+	types::{
+		transaction::eip2718::TypedTransaction, Address, Block as EvmBlock, BlockNumber, Log,
+		Transaction, TransactionReceipt, Withdrawal, H256, U256, U64,
+	},
 	utils::hex::ToHex,
 };
 use eyre::Result;
-use std::sync::Arc;
+use futures::future;
+use serde_json::Value as JsonValue;
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+};
+use tokio::sync::{RwLock, Semaphore};
 
 use crate::{
-	chain::{ChainTrait, ModuleId, ModuleTrait, WarehouseData},
-	models::Network,
-	utils, BlockHeight, RateLimiter, Storage,
+	chain::{
+		self, BlockHashes, ChainTrait, ModuleId, ModuleTrait, PluginModule, TokenMetadata,
+		WarehouseData, MAX_CONCURRENT_TRANSACTIONS,
+	},
+	metrics::Counter,
+	models::{Abi, Block, Network},
+	producers, utils, BlockHeight, RateLimiter, Storage,
+};
+use client::EvmClient;
+pub use modules::EvmModuleTrait;
+use modules::{
+	EvmApproval, EvmBalance, EvmDecodedCall, EvmFee, EvmFeeTransfer, EvmStakingDeposit, EvmSwap,
+	EvmTokenBalance, EvmTokenTransfer, EvmTransfer, EvmUserOperation, EvmWithdrawal,
 };
-use modules::{EvmBalance, EvmModuleTrait, EvmTokenBalance, EvmTokenTransfer, EvmTransfer};
 use schema::{
 	Block as ParquetBlock, Log as ParquetLog, ParquetFile, Receipt as ParquetReceipt,
-	Transaction as ParquetTransaction,
+	Transaction as ParquetTransaction, Withdrawal as ParquetWithdrawal,
 };
 
+mod client;
+pub mod l2;
 mod modules;
 mod schema;
 
 static TRANSFER_FROM_TO_AMOUNT: &str =
 	"ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+static APPROVAL_OWNER_SPENDER_AMOUNT: &str =
+	"8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
+
+// `Swap` events of uniswap v2 and v3 pools (and their forks); both have the
+// recipient as the second indexed topic
+static SWAP_V2: &str = "d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822";
+static SWAP_V3: &str = "c42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67";
+
+// weth9-style `Deposit` and `Withdrawal` events, which wrapping contracts
+// emit instead of minting and burning with `Transfer`
+static DEPOSIT_DST_AMOUNT: &str =
+	"e1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c";
+static WITHDRAWAL_SRC_AMOUNT: &str =
+	"7fcf532c15f0a6db0bd6d0e038bea71d30d808c7d98cb3bf7268a95bf5081b65";
+
+// erc-20 metadata function selectors
+static SELECTOR_NAME: [u8; 4] = [0x06, 0xfd, 0xde, 0x03];
+static SELECTOR_SYMBOL: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+static SELECTOR_DECIMALS: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+// erc-721 `tokenURI(uint256)` and erc-1155 `uri(uint256)` selectors
+static SELECTOR_TOKEN_URI: [u8; 4] = [0xc8, 0x7b, 0x56, 0xdd];
+static SELECTOR_URI: [u8; 4] = [0x0e, 0x89, 0x34, 0x1c];
+
+// a block rebuilt from extracted files, with receipts of its txs by tx hash
+type StoredBlock = (EvmBlock<Transaction>, HashMap<H256, TransactionReceipt>);
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum EvmTopic {
 	Unknown,
 	TokenTransfer(Address, Address, U256),
+	NftTransfer(Address, Address, U256),
+	TokenApproval(Address, Address, U256),
+	Swap(Address),
+	Wrap(Address, U256),
+	Unwrap(Address, U256),
+}
+
+// fee-related fields of a block that's being processed, since txs don't
+// carry them
+#[derive(Debug, Copy, Clone)]
+pub struct BlockFees {
+	pub coinbase: Address,
+	pub base_fee: U256,
 }
 
 pub struct Evm {
 	network: Network,
 	rpc: Option<String>,
-	provider: Option<Arc<Provider<RetryClient<Http>>>>,
+	provider: Option<Arc<Provider<EvmClient>>>,
 	rate_limiter: Option<Arc<RateLimiter>>,
+	rpc_counter: Option<Counter>,
+	token_allowlist: Option<HashSet<Address>>,
+	token_denylist: HashSet<Address>,
+	wrapped_native_tokens: HashSet<Address>,
+	abis: HashMap<Address, ContractAbi>,
+	block_fees: RwLock<HashMap<BlockHeight, BlockFees>>,
 	modules: Vec<Box<dyn EvmModuleTrait>>,
 }
 
+pub fn is_valid_address(address: &str) -> bool {
+	address.parse::<Address>().is_ok()
+}
+
 impl Evm {
 	pub fn new(network: Network) -> Self {
 		let rps = network.rps as u32;
 		let network_id = network.network_id;
 
+		let parse_addresses = |v: Vec<String>| -> HashSet<Address> {
+			v.iter().filter_map(|a| a.parse().ok()).collect()
+		};
+		let token_allowlist = network.get_token_allowlist().map(parse_addresses);
+		let token_denylist = parse_addresses(network.get_token_denylist());
+		let wrapped_native_tokens = parse_addresses(network.get_wrapped_native_tokens());
+
 		Self {
 			network,
 			rpc: None,
 			provider: None,
 			rate_limiter: utils::get_rate_limiter(rps),
+			rpc_counter: None,
+			token_allowlist,
+			token_denylist,
+			wrapped_native_tokens,
+			abis: HashMap::new(),
+			block_fees: RwLock::new(HashMap::new()),
 			modules: vec![
 				Box::new(EvmTransfer::new(network_id)),
 				Box::new(EvmBalance::new(network_id)),
 				Box::new(EvmTokenTransfer::new(network_id)),
 				Box::new(EvmTokenBalance::new(network_id)),
+				Box::new(EvmDecodedCall::new(network_id)),
+				Box::new(EvmUserOperation::new(network_id)),
+				Box::new(EvmFee::new(network_id)),
+				Box::new(EvmWithdrawal::new(network_id)),
+				Box::new(EvmFeeTransfer::new(network_id)),
+				Box::new(EvmStakingDeposit::new(network_id)),
+				Box::new(EvmApproval::new(network_id)),
+				Box::new(EvmSwap::new(network_id)),
 			],
 		}
 	}
+
+	pub fn with_plugin_modules(mut self, plugin_modules: Vec<PluginModule>) -> Self {
+		for plugin_module in plugin_modules.into_iter() {
+			#[allow(irrefutable_let_patterns)]
+			if let PluginModule::Evm(module) = plugin_module {
+				self.modules.push(module);
+			}
+		}
+
+		self
+	}
 }
 
 #[async_trait]
 impl ChainTrait for Evm {
+	// @NOTE connecting again starts over with a new client, so a websocket
+	// that dropped for good (after running out of reconnects) gets replaced
+	// instead of failing every request
 	async fn connect(&mut self) -> Result<bool> {
-		if let Ok(provider) =
-			Provider::<RetryClient<Http>>::new_client(&self.network.rpc_endpoint, 10, 1_000)
-		{
-			if let Some(rate_limiter) = &self.rate_limiter {
-				rate_limiter.until_ready().await;
-			}
+		self.rpc = None;
+		self.provider = None;
+
+		for rpc_endpoint in self.network.get_rpc_endpoints().into_iter() {
+			if let Ok(client) = EvmClient::new(&rpc_endpoint).await {
+				let provider = Provider::new(client);
 
-			if provider.get_block_number().await.is_ok() {
-				self.rpc = Some(self.network.rpc_endpoint.clone());
-				self.provider = Some(Arc::new(provider));
+				if let Some(rate_limiter) = &self.rate_limiter {
+					rate_limiter.until_ready().await;
+				}
+
+				if provider.get_block_number().await.is_ok() {
+					self.rpc = Some(rpc_endpoint);
+					self.provider = Some(Arc::new(provider));
+					break;
+				}
 			}
 		}
 
@@ -99,6 +209,14 @@ impl ChainTrait for Evm {
 		self.rate_limiter.clone()
 	}
 
+	fn set_rpc_counter(&mut self, counter: Counter) {
+		self.rpc_counter = Some(counter);
+	}
+
+	fn get_rpc_counter(&self) -> Option<Counter> {
+		self.rpc_counter.clone()
+	}
+
 	fn format_address(&self, address: &str) -> String {
 		if address.len() > 2 {
 			if let Ok(parsed_address) = address[2..].parse() {
@@ -114,47 +232,142 @@ impl ChainTrait for Evm {
 		Ok(self.provider.as_ref().unwrap().get_block_number().await?.as_u64())
 	}
 
+	async fn get_balance(
+		&self,
+		address: &str,
+		block_height: BlockHeight,
+	) -> Result<Option<(BlockHeight, chain::U256)>> {
+		self.rate_limit().await;
+		let balance = self
+			.provider
+			.as_ref()
+			.unwrap()
+			.get_balance(address.parse::<Address>()?, Some(block_height.into()))
+			.await?;
+
+		Ok(Some((block_height, chain::U256::from_str_radix(&balance.to_string(), 10)?)))
+	}
+
+	async fn get_block_hash(&self, block_height: BlockHeight) -> Result<Option<String>> {
+		self.rate_limit().await;
+		Ok(self
+			.provider
+			.as_ref()
+			.unwrap()
+			.get_block(block_height)
+			.await?
+			.and_then(|block| block.hash)
+			.map(|block_hash| format!("{block_hash:?}")))
+	}
+
+	async fn get_block_producer_tag(&self, block_height: BlockHeight) -> Result<Option<String>> {
+		self.rate_limit().await;
+		Ok(self
+			.provider
+			.as_ref()
+			.unwrap()
+			.get_block(block_height)
+			.await?
+			.and_then(|block| producers::get_tag(&block.extra_data)))
+	}
+
 	async fn process_block(
 		&self,
-		_storage: Arc<Storage>,
+		storage: Arc<Storage>,
 		block_height: BlockHeight,
 		module_ids: Vec<ModuleId>,
 	) -> Result<Option<WarehouseData>> {
 		let mut ret = None;
-		let provider = self.provider.as_ref().unwrap();
 
-		self.rate_limit().await;
-		match provider.get_block_with_txs(block_height).await? {
+		// @NOTE without an rpc connection (offline mode), blocks are read back from
+		// extracted files instead, receipts included
+		let (block, stored_receipts) = match &self.provider {
+			Some(_) => {
+				self.rate_limit().await;
+				(self.get_block_with_txs(block_height).await?, None)
+			}
+			None => match self.get_stored_block(&storage, block_height)? {
+				Some((block, receipts)) => (Some(block), Some(receipts)),
+				None => (None, None),
+			},
+		};
+
+		match block {
 			Some(block) if block.number.is_some() => {
 				let mut warehouse_data = WarehouseData::new();
+				let block_time = block.timestamp.as_u32();
+
+				// record block hash, so it can be checked for canonicality later
+				if let Some(block_hash) = block.hash {
+					warehouse_data.blocks.insert(Block::new(
+						self.network.network_id,
+						block_height,
+						&format!("{block_hash:?}"),
+						&format!("{:?}", block.parent_hash),
+						block_time,
+					));
+				}
 
-				for tx in block.transactions.into_iter() {
-					// skip if pending
-					if tx.block_hash.is_none() {
-						continue;
-					}
+				// kept around while the block's txs are processed (see `get_block_fees()`)
+				if let Some(coinbase) = block.author {
+					let base_fee = block.base_fee_per_gas.unwrap_or_default();
+					self.block_fees
+						.write()
+						.await
+						.insert(block_height, BlockFees { coinbase, base_fee });
+				}
 
-					// process tx only if receipt exists
-					self.rate_limit().await;
-					if let Some(receipt) = provider.get_transaction_receipt(tx.hash()).await? {
-						// skip if tx reverted
-						if let Some(status) = receipt.status {
-							if status == U64::zero() {
-								continue;
+				// block-level data (eg: withdrawals)
+				for module in self.modules.iter().filter(|m| module_ids.contains(&m.get_id())) {
+					warehouse_data +=
+						module.run_block(self, block_height, block_time, &block).await?;
+				}
+
+				// process txs concurrently (receipt fetching dominates)
+				let semaphore = &Semaphore::new(MAX_CONCURRENT_TRANSACTIONS);
+				let futures = block
+					.transactions
+					.into_iter()
+					.filter(|tx| tx.block_hash.is_some()) // skip if pending
+					.map(|tx| {
+						let module_ids = module_ids.clone();
+						let stored_receipts = &stored_receipts;
+
+						async move {
+							let _permit = semaphore.acquire().await?;
+
+							// process tx only if receipt exists
+							let receipt = match (stored_receipts, &self.provider) {
+								(Some(receipts), _) => receipts.get(&tx.hash).cloned(),
+								(None, Some(provider)) => {
+									self.rate_limit().await;
+									provider.get_transaction_receipt(tx.hash()).await?
+								}
+								_ => None,
+							};
+
+							match receipt {
+								// skip if tx reverted
+								Some(receipt) if receipt.status != Some(U64::zero()) => {
+									self.process_transaction(
+										block_height,
+										block_time,
+										tx,
+										receipt,
+										module_ids,
+									)
+									.await
+								}
+								_ => Ok(WarehouseData::new()),
 							}
 						}
+					});
 
-						// process tx
-						warehouse_data += self
-							.process_transaction(
-								block_height,
-								block.timestamp.as_u32(),
-								tx,
-								receipt,
-								module_ids.clone(),
-							)
-							.await?;
-					}
+				let all_tx_warehouse_data = future::try_join_all(futures).await;
+				self.block_fees.write().await.remove(&block_height);
+
+				for tx_warehouse_data in all_tx_warehouse_data?.into_iter() {
+					warehouse_data += tx_warehouse_data;
 				}
 
 				ret = Some(warehouse_data);
@@ -169,13 +382,24 @@ impl ChainTrait for Evm {
 		&self,
 		storage: Arc<Storage>,
 		block_height: BlockHeight,
-	) -> Result<bool> {
+	) -> Result<Option<BlockHashes>> {
+		let mut ret = None;
 		let storage_db = storage.get(self.network.network_id, block_height)?;
 		let provider = self.provider.as_ref().unwrap();
 
+		// already extracted (eg: by a chunk worker before it restarted)
+		if let Some(block_hashes) = storage_db.get_manifest()? {
+			return Ok(Some(block_hashes));
+		}
+
 		self.rate_limit().await;
-		match provider.get_block_with_txs(block_height).await? {
+		match self.get_block_with_txs(block_height).await? {
 			Some(block) if block.number.is_some() => {
+				ret = block.hash.map(|hash| BlockHashes {
+					hash: format!("{hash:?}"),
+					parent_hash: format!("{:?}", block.parent_hash),
+				});
+
 				storage_db.insert(ParquetBlock {
 					hash: block.hash,
 					parent_hash: block.parent_hash,
@@ -190,6 +414,15 @@ impl ChainTrait for Evm {
 					base_fee_per_gas: block.base_fee_per_gas,
 				})?;
 
+				for withdrawal in block.withdrawals.unwrap_or_default().into_iter() {
+					storage_db.insert(ParquetWithdrawal {
+						index: withdrawal.index.as_u64(),
+						validator_index: withdrawal.validator_index.as_u64(),
+						address: withdrawal.address,
+						amount: withdrawal.amount,
+					})?;
+				}
+
 				for tx in block.transactions.into_iter() {
 					// skip if pending
 					if tx.block_hash.is_none() {
@@ -217,6 +450,10 @@ impl ChainTrait for Evm {
 							gas: tx.gas,
 							transaction_type: tx.transaction_type.map(|v| v.as_u64()),
 							chain_id: tx.chain_id,
+							source_hash: l2::get_source_hash(&tx),
+							mint: l2::get_minted(&tx),
+							is_system_tx: l2::is_system_transaction(&tx),
+							input: tx.input.clone(),
 						})?;
 
 						storage_db.insert(ParquetReceipt {
@@ -234,6 +471,9 @@ impl ChainTrait for Evm {
 							root: receipt.root,
 							transaction_type: receipt.transaction_type.map(|v| v.as_u64()),
 							effective_gas_price: receipt.effective_gas_price,
+							l1_fee: l2::get_l1_fee(&receipt),
+							l1_gas_price: l2::get_receipt_field(&receipt, "l1GasPrice"),
+							l1_gas_used: l2::get_receipt_field(&receipt, "l1GasUsed"),
 						})?;
 
 						for log in receipt.logs.into_iter() {
@@ -255,18 +495,96 @@ impl ChainTrait for Evm {
 			_ => {}
 		};
 
-		storage_db.commit(vec![
+		let files = vec![
 			ParquetFile::Blocks.to_string(),
 			ParquetFile::Transactions.to_string(),
 			ParquetFile::Receipts.to_string(),
 			ParquetFile::Logs.to_string(),
-		])?;
+			ParquetFile::Withdrawals.to_string(),
+		];
+
+		storage_db.commit(files.clone())?;
+		if let Some(block_hashes) = &ret {
+			storage_db.save_manifest(&files, block_hashes)?;
+		}
+
+		Ok(ret)
+	}
+
+	async fn get_token_metadata(&self, address: &str) -> Result<Option<TokenMetadata>> {
+		let address = match address.parse::<Address>() {
+			Ok(address) => address,
+			_ => return Ok(None),
+		};
+
+		let name = self.call_contract(address, &SELECTOR_NAME).await?.and_then(decode_string);
+		let symbol = self.call_contract(address, &SELECTOR_SYMBOL).await?.and_then(decode_string);
+		let decimals = self.call_contract(address, &SELECTOR_DECIMALS).await?.and_then(|b| {
+			abi::decode(&[ParamType::Uint(8)], &b)
+				.ok()
+				.and_then(|t| t.into_iter().next())
+				.and_then(AbiToken::into_uint)
+				.map(|v| v.low_u32() as u16)
+		});
+
+		Ok(match (name, symbol, decimals) {
+			(Some(name), Some(symbol), Some(decimals)) => {
+				Some(TokenMetadata { name, symbol, decimals })
+			}
+			_ => None,
+		})
+	}
+
+	async fn get_collection_name(&self, address: &str) -> Result<Option<String>> {
+		Ok(match address.parse::<Address>() {
+			Ok(address) => {
+				self.call_contract(address, &SELECTOR_NAME).await?.and_then(decode_string)
+			}
+			_ => None,
+		})
+	}
 
-		Ok(true)
+	async fn get_nft_uri(&self, address: &str, token_id: &str) -> Result<Option<String>> {
+		let (address, token_id) = match (address.parse::<Address>(), U256::from_dec_str(token_id))
+		{
+			(Ok(address), Ok(token_id)) => (address, token_id),
+			_ => return Ok(None),
+		};
+
+		for selector in [SELECTOR_TOKEN_URI, SELECTOR_URI] {
+			let mut data = selector.to_vec();
+			data.extend(abi::encode(&[AbiToken::Uint(token_id)]));
+
+			if let Some(uri) = self.call_contract(address, &data).await?.and_then(decode_string) {
+				// erc-1155 uris use a hex-encoded `{id}` placeholder
+				return Ok(Some(uri.replace("{id}", &format!("{:064x}", token_id))));
+			}
+		}
+
+		Ok(None)
+	}
+
+	fn set_abis(&mut self, abis: Vec<Abi>) {
+		self.abis = abis
+			.into_iter()
+			.filter_map(|a| match (a.address.parse(), serde_json::from_value(a.abi)) {
+				(Ok(address), Ok(abi)) => Some((address, abi)),
+				_ => None,
+			})
+			.collect();
 	}
 }
 
 impl Evm {
+	async fn call_contract(&self, address: Address, selector: &[u8]) -> Result<Option<Bytes>> {
+		let tx: TypedTransaction =
+			TransactionRequest::new().to(address).data(Bytes::from(selector.to_vec())).into();
+
+		// reverts just mean the contract doesn't implement the function
+		self.rate_limit().await;
+		Ok(self.provider.as_ref().unwrap().call(&tx, None).await.ok().filter(|b| !b.is_empty()))
+	}
+
 	async fn process_transaction(
 		&self,
 		block_height: BlockHeight,
@@ -284,6 +602,183 @@ impl Evm {
 		Ok(ret)
 	}
 
+	// @NOTE rebuilds a block (and receipts of its txs, by tx hash) from extracted
+	// files. only what modules read is restored; reverted txs were never
+	// extracted, which is fine since they're skipped when processing anyway
+	fn get_stored_block(
+		&self,
+		storage: &Storage,
+		block_height: BlockHeight,
+	) -> Result<Option<StoredBlock>> {
+		let storage_db = storage.get(self.network.network_id, block_height)?;
+
+		let Some(block) = ParquetBlock::get(&storage_db)? else {
+			return Ok(None);
+		};
+		let block_number = block.number.map(U64::from);
+
+		let mut logs = HashMap::<H256, Vec<Log>>::new();
+		for log in ParquetLog::get_all(&storage_db)?.into_iter() {
+			let Some(tx_hash) = log.transaction_hash else {
+				continue;
+			};
+
+			logs.entry(tx_hash).or_default().push(Log {
+				address: log.address,
+				topics: log.topics,
+				data: log.data,
+				block_hash: block.hash,
+				block_number,
+				transaction_hash: log.transaction_hash,
+				transaction_index: log.transaction_index.map(U64::from),
+				log_index: log.log_index,
+				transaction_log_index: log.transaction_log_index,
+				log_type: log.log_type,
+				removed: log.removed,
+			});
+		}
+
+		let mut receipts = HashMap::new();
+		for r in ParquetReceipt::get_all(&storage_db)?.into_iter() {
+			let mut receipt = TransactionReceipt {
+				transaction_hash: r.transaction_hash,
+				transaction_index: r.transaction_index.into(),
+				block_hash: r.block_hash,
+				block_number: r.block_number.map(U64::from),
+				from: r.from_address,
+				to: r.to_address,
+				cumulative_gas_used: r.cumulative_gas_used,
+				gas_used: r.gas_used,
+				contract_address: r.contract_address,
+				logs: logs.remove(&r.transaction_hash).unwrap_or_default(),
+				status: r.status.map(U64::from),
+				root: r.root,
+				transaction_type: r.transaction_type.map(U64::from),
+				effective_gas_price: r.effective_gas_price,
+				..Default::default()
+			};
+
+			// rollup fields (see `l2::get_receipt_field()`)
+			for (field, value) in
+				[("l1Fee", r.l1_fee), ("l1GasPrice", r.l1_gas_price), ("l1GasUsed", r.l1_gas_used)]
+			{
+				if let Some(value) = value {
+					receipt.other.insert(field.to_string(), serde_json::to_value(value)?);
+				}
+			}
+
+			receipts.insert(r.transaction_hash, receipt);
+		}
+
+		let mut transactions = vec![];
+		for t in ParquetTransaction::get_all(&storage_db)?.into_iter() {
+			let mut tx = Transaction {
+				hash: t.hash,
+				nonce: t.nonce,
+				block_hash: block.hash,
+				block_number,
+				transaction_index: t.transaction_index.map(U64::from),
+				from: t.from_address,
+				to: t.to_address,
+				value: t.value,
+				gas_price: t.gas_price,
+				gas: t.gas,
+				input: t.input,
+				transaction_type: t.transaction_type.map(U64::from),
+				chain_id: t.chain_id,
+				..Default::default()
+			};
+
+			// rollup deposit fields (see `l2`)
+			if let Some(source_hash) = t.source_hash {
+				tx.other.insert("sourceHash".to_string(), serde_json::to_value(source_hash)?);
+			}
+			if let Some(mint) = t.mint {
+				tx.other.insert("mint".to_string(), serde_json::to_value(mint)?);
+			}
+			tx.other.insert("isSystemTx".to_string(), JsonValue::Bool(t.is_system_tx));
+
+			transactions.push(tx);
+		}
+
+		let withdrawals = ParquetWithdrawal::get_all(&storage_db)?
+			.into_iter()
+			.map(|w| Withdrawal {
+				index: w.index.into(),
+				validator_index: w.validator_index.into(),
+				address: w.address,
+				amount: w.amount,
+			})
+			.collect::<Vec<_>>();
+
+		let block = EvmBlock {
+			hash: block.hash,
+			parent_hash: block.parent_hash,
+			author: block.author,
+			state_root: block.state_root,
+			transactions_root: block.transactions_root,
+			receipts_root: block.receipts_root,
+			number: block_number,
+			gas_used: block.gas_used,
+			timestamp: block.timestamp.into(),
+			total_difficulty: block.total_difficulty,
+			base_fee_per_gas: block.base_fee_per_gas,
+			withdrawals: Some(withdrawals).filter(|w| !w.is_empty()),
+			transactions,
+			..Default::default()
+		};
+
+		Ok(Some((block, receipts)))
+	}
+
+	// same as the provider's `get_block_with_txs()`, but tolerant of rollup
+	// transactions (see `l2::normalize_transaction()`)
+	async fn get_block_with_txs(
+		&self,
+		block_height: BlockHeight,
+	) -> Result<Option<EvmBlock<Transaction>>> {
+		let provider = self.provider.as_ref().unwrap();
+
+		let mut block: JsonValue = provider
+			.request("eth_getBlockByNumber", (BlockNumber::from(block_height), true))
+			.await?;
+		if block.is_null() {
+			return Ok(None);
+		}
+
+		if let Some(txs) = block.get_mut("transactions").and_then(|t| t.as_array_mut()) {
+			txs.iter_mut().for_each(l2::normalize_transaction);
+		}
+
+		Ok(Some(serde_json::from_value(block)?))
+	}
+
+	// whether transfers of this token contract should be indexed
+	pub fn is_indexed_token(&self, address: &Address) -> bool {
+		if self.token_denylist.contains(address) {
+			return false;
+		}
+
+		match &self.token_allowlist {
+			Some(token_allowlist) => token_allowlist.contains(address),
+			_ => true,
+		}
+	}
+
+	// whether this contract wraps the native asset 1:1 (eg: weth)
+	pub fn is_wrapped_native_token(&self, address: &Address) -> bool {
+		self.wrapped_native_tokens.contains(address)
+	}
+
+	pub fn get_abi(&self, address: &Address) -> Option<&ContractAbi> {
+		self.abis.get(address)
+	}
+
+	// producer and base fee of a block, while its txs are being processed
+	pub async fn get_block_fees(&self, block_height: BlockHeight) -> Option<BlockFees> {
+		self.block_fees.read().await.get(&block_height).copied()
+	}
+
 	fn get_topic(&self, log: &Log) -> Result<EvmTopic> {
 		if log.topics.len() == 3 && log.topics[0].encode_hex::<String>() == *TRANSFER_FROM_TO_AMOUNT
 		{
@@ -294,6 +789,60 @@ impl Evm {
 			return Ok(EvmTopic::TokenTransfer(from, to, amount));
 		}
 
+		if log.topics.len() == 4 && log.topics[0].encode_hex::<String>() == *TRANSFER_FROM_TO_AMOUNT
+		{
+			let from = Address::from(log.topics[1]);
+			let to = Address::from(log.topics[2]);
+			let token_id = U256::from_big_endian(log.topics[3].as_bytes());
+
+			return Ok(EvmTopic::NftTransfer(from, to, token_id));
+		}
+
+		// erc-721 approvals have a fourth (token id) topic, so they're left out
+		if log.topics.len() == 3 &&
+			log.topics[0].encode_hex::<String>() == *APPROVAL_OWNER_SPENDER_AMOUNT
+		{
+			let owner = Address::from(log.topics[1]);
+			let spender = Address::from(log.topics[2]);
+			let amount = U256::decode(log.data.clone()).unwrap_or_default();
+
+			return Ok(EvmTopic::TokenApproval(owner, spender, amount));
+		}
+
+		if log.topics.len() == 3 &&
+			[SWAP_V2, SWAP_V3].contains(&log.topics[0].encode_hex::<String>().as_str())
+		{
+			return Ok(EvmTopic::Swap(Address::from(log.topics[2])));
+		}
+
+		if log.topics.len() == 2 && log.topics[0].encode_hex::<String>() == *DEPOSIT_DST_AMOUNT {
+			let dst = Address::from(log.topics[1]);
+			let amount = U256::decode(log.data.clone()).unwrap_or_default();
+
+			return Ok(EvmTopic::Wrap(dst, amount));
+		}
+
+		if log.topics.len() == 2 && log.topics[0].encode_hex::<String>() == *WITHDRAWAL_SRC_AMOUNT
+		{
+			let src = Address::from(log.topics[1]);
+			let amount = U256::decode(log.data.clone()).unwrap_or_default();
+
+			return Ok(EvmTopic::Unwrap(src, amount));
+		}
+
 		Ok(EvmTopic::Unknown)
 	}
 }
+
+// strings are either abi-encoded or (in older tokens) a null-padded bytes32
+fn decode_string(bytes: Bytes) -> Option<String> {
+	let ret = match abi::decode(&[ParamType::String], &bytes) {
+		Ok(tokens) => tokens.into_iter().next().and_then(AbiToken::into_string),
+		_ if bytes.len() == 32 => {
+			String::from_utf8(bytes.iter().take_while(|b| **b != 0).cloned().collect()).ok()
+		}
+		_ => None,
+	};
+
+	ret.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
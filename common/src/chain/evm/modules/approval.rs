@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use ethers::{
+	abi::AbiEncode,
+	types::{Transaction, TransactionReceipt},
+	utils,
+};
+use eyre::Result;
+
+use crate::{
+	chain::{
+		evm::{modules::EvmModuleTrait, EvmTopic},
+		Evm, ModuleId, ModuleTrait, WarehouseData,
+	},
+	models::{Approval, PrimaryId},
+	BlockHeight,
+};
+
+pub struct EvmApproval {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for EvmApproval {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::EvmApproval
+	}
+}
+
+#[async_trait]
+impl EvmModuleTrait for EvmApproval {
+	async fn run(
+		&self,
+		evm: &Evm,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: Transaction,
+		receipt: TransactionReceipt,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+		let tx_hash = tx.hash.encode_hex();
+
+		for log in receipt.logs.into_iter() {
+			// if log was removed, it's not valid
+			if log.removed == Some(true) {
+				continue;
+			}
+
+			// skip tokens excluded for this network
+			if !evm.is_indexed_token(&log.address) {
+				continue;
+			}
+
+			// @NOTE zero amounts are kept too, since that's how approvals are
+			// revoked (the latest approval is the current allowance)
+			if let EvmTopic::TokenApproval(owner, spender, amount) = evm.get_topic(&log)? {
+				ret.approvals.insert(Approval::new(
+					self.network_id,
+					block_height,
+					&tx_hash,
+					log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+					&utils::to_checksum(&log.address, None),
+					&utils::to_checksum(&owner, None),
+					&utils::to_checksum(&spender, None),
+					amount,
+					block_time,
+				));
+			}
+		}
+
+		Ok(ret)
+	}
+}
@@ -1,13 +1,21 @@
 use async_trait::async_trait;
 use ethers::{
 	abi::AbiEncode,
-	types::{Transaction, TransactionReceipt},
+	types::{Address, Transaction, TransactionReceipt},
 	utils,
 };
 use eyre::Result;
+use std::collections::HashMap;
 
 use crate::{
-	chain::{evm::modules::EvmModuleTrait, Evm, ModuleId, ModuleTrait, WarehouseData, U256},
+	chain::{
+		evm::{
+			l2,
+			modules::{fee::get_fee_split, EvmModuleTrait},
+			EvmTopic,
+		},
+		Evm, ModuleId, ModuleTrait, WarehouseData, U256,
+	},
 	models::{Amount, PrimaryId},
 	BlockHeight,
 };
@@ -30,51 +38,72 @@ impl ModuleTrait for EvmBalance {
 impl EvmModuleTrait for EvmBalance {
 	async fn run(
 		&self,
-		_evm: &Evm,
+		evm: &Evm,
 		block_height: BlockHeight,
 		block_time: u32,
 		tx: Transaction,
-		_receipt: TransactionReceipt,
+		receipt: TransactionReceipt,
 	) -> Result<WarehouseData> {
 		let mut ret = WarehouseData::new();
+		let tx_hash = tx.hash.encode_hex();
 
-		// skip if no asset transfer
-		if tx.value.is_zero() {
-			return Ok(ret);
+		// @NOTE amounts are unique per (tx, address), so everything an address
+		// gains and spends in a tx has to be in the same record
+		let mut balance_map = HashMap::<Address, (U256, U256)>::new();
+
+		// l2 deposits first mint bridged eth to the sender
+		if let Some(minted) = l2::get_minted(&tx) {
+			balance_map.entry(tx.from).or_default().0 +=
+				U256::from_str_radix(&minted.to_string(), 10)?;
+		}
+
+		// skip if no asset transfer, contract deploy call or sending to self
+		if let Some(to) = tx.to.filter(|to| !tx.value.is_zero() && *to != tx.from) {
+			let value = U256::from_str_radix(&tx.value.to_string(), 10)?;
+			balance_map.entry(tx.from).or_default().1 += value;
+			balance_map.entry(to).or_default().0 += value;
 		}
 
-		// skip if contract deploy call
-		if tx.to.is_none() {
-			return Ok(ret);
+		// fees are spent by the sender, and the priority part goes to the
+		// block's producer
+		if let Some((coinbase, tip, burnt)) = get_fee_split(evm, block_height, &tx, &receipt).await
+		{
+			balance_map.entry(tx.from).or_default().1 +=
+				U256::from_str_radix(&tip.saturating_add(burnt).to_string(), 10)?;
+			balance_map.entry(coinbase).or_default().0 +=
+				U256::from_str_radix(&tip.to_string(), 10)?;
 		}
 
-		// skip if sending to self
-		if tx.from == tx.to.unwrap() {
-			return Ok(ret);
+		// unwrapping pays the native asset back out with an internal call,
+		// which isn't indexed otherwise
+		for log in receipt.logs.iter().filter(|log| log.removed != Some(true)) {
+			if !evm.is_wrapped_native_token(&log.address) {
+				continue;
+			}
+
+			if let EvmTopic::Unwrap(src, amount) = evm.get_topic(log)? {
+				balance_map.entry(log.address).or_default().1 += amount;
+				balance_map.entry(src).or_default().0 += amount;
+			}
 		}
 
-		ret.amounts.insert(Amount::new(
-			self.get_id(),
-			self.network_id,
-			block_height,
-			&tx.hash.encode_hex(),
-			&utils::to_checksum(&tx.from, None),
-			None,
-			U256::zero(),
-			U256::from_str_radix(&tx.value.to_string(), 10)?,
-			block_time,
-		));
-		ret.amounts.insert(Amount::new(
-			self.get_id(),
-			self.network_id,
-			block_height,
-			&tx.hash.encode_hex(),
-			&utils::to_checksum(&tx.to.unwrap(), None),
-			None,
-			U256::from_str_radix(&tx.value.to_string(), 10)?,
-			U256::zero(),
-			block_time,
-		));
+		for (address, (amount_in, amount_out)) in balance_map.into_iter() {
+			if amount_in.is_zero() && amount_out.is_zero() {
+				continue;
+			}
+
+			ret.amounts.insert(Amount::new(
+				self.get_id(),
+				self.network_id,
+				block_height,
+				&tx_hash,
+				&utils::to_checksum(&address, None),
+				None,
+				amount_in,
+				amount_out,
+				block_time,
+			));
+		}
 
 		Ok(ret)
 	}
@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use ethers::{
+	abi::{AbiEncode, ParamType, RawLog, Token as AbiToken},
+	types::{Transaction, TransactionReceipt, I256},
+	utils,
+};
+use eyre::Result;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+use crate::{
+	chain::{evm::modules::EvmModuleTrait, Evm, ModuleId, ModuleTrait, WarehouseData},
+	models::{DecodedCall, PrimaryId},
+	BlockHeight,
+};
+
+pub struct EvmDecodedCall {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for EvmDecodedCall {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::EvmDecodedCall
+	}
+}
+
+#[async_trait]
+impl EvmModuleTrait for EvmDecodedCall {
+	async fn run(
+		&self,
+		evm: &Evm,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: Transaction,
+		receipt: TransactionReceipt,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+		let tx_hash = tx.hash.encode_hex();
+
+		// decode function call (first 4 bytes of calldata are the selector)
+		if let Some(to) = tx.to {
+			if let Some(abi) = evm.get_abi(&to).filter(|_| tx.input.len() >= 4) {
+				let function = abi.functions().find(|f| f.short_signature() == tx.input[..4]);
+				if let Some(function) = function {
+					if let Ok(tokens) = function.decode_input(&tx.input[4..]) {
+						let inputs = &function.inputs;
+
+						ret.decoded_calls.insert(DecodedCall::new(
+							self.get_id(),
+							self.network_id,
+							block_height,
+							&tx_hash,
+							None,
+							&utils::to_checksum(&to, None),
+							&function.name,
+							&get_signature(&function.name, inputs.iter().map(|p| &p.kind)),
+							get_args(inputs.iter().map(|p| p.name.clone()), tokens),
+							block_time,
+						));
+					}
+				}
+			}
+		}
+
+		// decode events emitted by contracts with known abis
+		for log in receipt.logs.into_iter() {
+			// if log was removed, it's not valid
+			if log.removed == Some(true) || log.topics.is_empty() {
+				continue;
+			}
+
+			let Some(abi) = evm.get_abi(&log.address) else {
+				continue;
+			};
+
+			let event = abi.events().find(|e| !e.anonymous && e.signature() == log.topics[0]);
+			if let Some(event) = event {
+				let raw_log = RawLog { topics: log.topics.clone(), data: log.data.to_vec() };
+				if let Ok(parsed_log) = event.parse_log(raw_log) {
+					let (names, tokens): (Vec<_>, Vec<_>) =
+						parsed_log.params.into_iter().map(|p| (p.name, p.value)).unzip();
+
+					ret.decoded_calls.insert(DecodedCall::new(
+						self.get_id(),
+						self.network_id,
+						block_height,
+						&tx_hash,
+						Some(log.log_index.unwrap_or_default().as_u64()),
+						&utils::to_checksum(&log.address, None),
+						&event.name,
+						&get_signature(&event.name, event.inputs.iter().map(|p| &p.kind)),
+						get_args(names, tokens),
+						block_time,
+					));
+				}
+			}
+		}
+
+		Ok(ret)
+	}
+}
+
+fn get_signature<'a>(name: &str, kinds: impl Iterator<Item = &'a ParamType>) -> String {
+	format!("{name}({})", kinds.map(|k| k.to_string()).collect::<Vec<_>>().join(","))
+}
+
+// unnamed arguments are keyed by position
+fn get_args(names: impl IntoIterator<Item = String>, tokens: Vec<AbiToken>) -> String {
+	let args = names
+		.into_iter()
+		.zip(tokens)
+		.enumerate()
+		.map(|(i, (name, token))| {
+			(if name.is_empty() { i.to_string() } else { name }, to_json(token))
+		})
+		.collect::<JsonMap<_, _>>();
+
+	JsonValue::Object(args).to_string()
+}
+
+fn to_json(token: AbiToken) -> JsonValue {
+	match token {
+		AbiToken::Address(a) => json!(utils::to_checksum(&a, None)),
+		AbiToken::FixedBytes(b) | AbiToken::Bytes(b) => json!(format!("0x{}", hex::encode(b))),
+		AbiToken::Int(i) => json!(I256::from_raw(i).to_string()),
+		AbiToken::Uint(u) => json!(u.to_string()),
+		AbiToken::Bool(b) => json!(b),
+		AbiToken::String(s) => json!(s),
+		AbiToken::FixedArray(v) | AbiToken::Array(v) | AbiToken::Tuple(v) => {
+			JsonValue::Array(v.into_iter().map(to_json).collect())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethers::types::{Address, U256};
+
+	#[test]
+	fn test_get_args() {
+		let args = get_args(
+			vec!["to".to_string(), "".to_string()],
+			vec![AbiToken::Address(Address::zero()), AbiToken::Uint(U256::from(42))],
+		);
+
+		assert_eq!(args, r#"{"1":"42","to":"0x0000000000000000000000000000000000000000"}"#);
+	}
+}
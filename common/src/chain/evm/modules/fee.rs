@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use ethers::{
+	abi::AbiEncode,
+	types::{Address, Transaction, TransactionReceipt, U256},
+	utils,
+};
+use eyre::Result;
+use std::cmp;
+
+use crate::{
+	chain::{
+		evm::{l2, modules::EvmModuleTrait},
+		Evm, ModuleId, ModuleTrait, WarehouseData,
+	},
+	models::{Fee, PrimaryId},
+	BlockHeight,
+};
+
+pub struct EvmFee {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for EvmFee {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::EvmFee
+	}
+}
+
+#[async_trait]
+impl EvmModuleTrait for EvmFee {
+	async fn run(
+		&self,
+		_evm: &Evm,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: Transaction,
+		receipt: TransactionReceipt,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+
+		// rollup system transactions are free
+		if l2::is_system_transaction(&tx) {
+			return Ok(ret);
+		}
+
+		// pre-london receipts don't have an effective gas price
+		let gas_price = receipt.effective_gas_price.or(tx.gas_price);
+
+		// skip if fee is unknown or zero (eg: system transactions)
+		match (receipt.gas_used, gas_price) {
+			(Some(gas_used), Some(gas_price)) if !gas_used.is_zero() && !gas_price.is_zero() => {
+				ret.fees.insert(Fee::new(
+					self.get_id(),
+					self.network_id,
+					block_height,
+					&tx.hash.encode_hex(),
+					&utils::to_checksum(&tx.from, None),
+					gas_used.as_u64(),
+					gas_price,
+					l2::get_l1_fee(&receipt).unwrap_or_default(),
+					block_time,
+				));
+			}
+			_ => {}
+		}
+
+		Ok(ret)
+	}
+}
+
+// @NOTE splits what a tx paid into the part that went to the block's producer
+// (the priority fee) and the part that didn't (the burnt base fee, and l1 data
+// fees on rollups). returns `(coinbase, tip, burnt)`, or `None` if the tx was
+// free or the block's producer isn't known
+pub async fn get_fee_split(
+	evm: &Evm,
+	block_height: BlockHeight,
+	tx: &Transaction,
+	receipt: &TransactionReceipt,
+) -> Option<(Address, U256, U256)> {
+	if l2::is_system_transaction(tx) {
+		return None;
+	}
+
+	let block_fees = evm.get_block_fees(block_height).await?;
+	let gas_used = receipt.gas_used?;
+	let gas_price = receipt.effective_gas_price.or(tx.gas_price)?;
+
+	let tip = gas_used.saturating_mul(gas_price.saturating_sub(block_fees.base_fee));
+	let burnt = gas_used
+		.saturating_mul(cmp::min(gas_price, block_fees.base_fee))
+		.saturating_add(l2::get_l1_fee(receipt).unwrap_or_default());
+
+	match tip.is_zero() && burnt.is_zero() {
+		true => None,
+		false => Some((block_fees.coinbase, tip, burnt)),
+	}
+}
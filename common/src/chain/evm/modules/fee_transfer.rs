@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use ethers::{
+	abi::AbiEncode,
+	types::{Transaction, TransactionReceipt},
+	utils,
+};
+use eyre::Result;
+
+use crate::{
+	chain::{
+		evm::modules::{fee::get_fee_split, EvmModuleTrait},
+		Evm, ModuleId, ModuleTrait, WarehouseData, U256,
+	},
+	models::{PrimaryId, Transfer},
+	BlockHeight,
+};
+
+pub struct EvmFeeTransfer {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for EvmFeeTransfer {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::EvmFeeTransfer
+	}
+}
+
+#[async_trait]
+impl EvmModuleTrait for EvmFeeTransfer {
+	// @NOTE the priority fee is sent to the block's producer (coinbase), and
+	// the rest of the fee to "" (it's burnt or paid to the protocol), so an
+	// address's outgoing transfers add up to everything it spent
+	async fn run(
+		&self,
+		evm: &Evm,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: Transaction,
+		receipt: TransactionReceipt,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+
+		let Some((coinbase, tip, burnt)) = get_fee_split(evm, block_height, &tx, &receipt).await
+		else {
+			return Ok(ret);
+		};
+
+		let tx_hash = tx.hash.encode_hex();
+		let from = utils::to_checksum(&tx.from, None);
+		let batch_amount = U256::from_str_radix(&tip.saturating_add(burnt).to_string(), 10)?;
+
+		// skip if the producer paid for its own tx
+		if !tip.is_zero() && coinbase != tx.from {
+			ret.transfers.insert(Transfer::new(
+				self.get_id(),
+				self.network_id,
+				block_height,
+				&tx_hash,
+				0,
+				&from,
+				&utils::to_checksum(&coinbase, None),
+				None,
+				U256::from_str_radix(&tip.to_string(), 10)?,
+				batch_amount,
+				block_time,
+			));
+		}
+
+		if !burnt.is_zero() {
+			ret.transfers.insert(Transfer::new(
+				self.get_id(),
+				self.network_id,
+				block_height,
+				&tx_hash,
+				0,
+				&from,
+				"",
+				None,
+				U256::from_str_radix(&burnt.to_string(), 10)?,
+				batch_amount,
+				block_time,
+			));
+		}
+
+		Ok(ret)
+	}
+}
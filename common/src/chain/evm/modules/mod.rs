@@ -1,20 +1,36 @@
 use async_trait::async_trait;
-use ethers::types::{Transaction, TransactionReceipt};
+use ethers::types::{Block, Transaction, TransactionReceipt};
 use eyre::Result;
 
 use crate::{
 	chain::{Evm, ModuleTrait, WarehouseData},
 	BlockHeight,
 };
+pub use approval::EvmApproval;
 pub use balance::EvmBalance;
+pub use decoded_call::EvmDecodedCall;
+pub use fee::EvmFee;
+pub use fee_transfer::EvmFeeTransfer;
+pub use staking_deposit::EvmStakingDeposit;
+pub use swap::EvmSwap;
 pub use token_balance::EvmTokenBalance;
 pub use token_transfer::EvmTokenTransfer;
 pub use transfer::EvmTransfer;
+pub use user_operation::EvmUserOperation;
+pub use withdrawal::EvmWithdrawal;
 
+mod approval;
 mod balance;
+mod decoded_call;
+mod fee;
+mod fee_transfer;
+mod staking_deposit;
+mod swap;
 mod token_balance;
 mod token_transfer;
 mod transfer;
+mod user_operation;
+mod withdrawal;
 
 #[async_trait]
 pub trait EvmModuleTrait: ModuleTrait + Send + Sync {
@@ -26,4 +42,15 @@ pub trait EvmModuleTrait: ModuleTrait + Send + Sync {
 		tx: Transaction,
 		receipt: TransactionReceipt,
 	) -> Result<WarehouseData>;
+
+	// runs once per block, for data that's not part of any transaction
+	async fn run_block(
+		&self,
+		_evm: &Evm,
+		_block_height: BlockHeight,
+		_block_time: u32,
+		_block: &Block<Transaction>,
+	) -> Result<WarehouseData> {
+		Ok(WarehouseData::new())
+	}
 }
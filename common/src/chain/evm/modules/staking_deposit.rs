@@ -0,0 +1,145 @@
+use async_trait::async_trait;
+use ethers::{
+	abi::{self, AbiEncode, ParamType},
+	types::{Address, Transaction, TransactionReceipt, H256},
+	utils,
+};
+use eyre::Result;
+
+use crate::{
+	chain::{evm::modules::EvmModuleTrait, Evm, ModuleId, ModuleTrait, WarehouseData, U256},
+	models::{PrimaryId, StakingDeposit, Transfer},
+	BlockHeight,
+};
+
+// beacon chain deposit contracts (mainnet, holesky and sepolia)
+static DEPOSIT_CONTRACTS: [&str; 3] = [
+	"0x00000000219ab540356cbb839cbe05303d7705fa",
+	"0x4242424242424242424242424242424242424242",
+	"0x7f02c3e3c98b133055b8b348b2ac625669ed295d",
+];
+
+static DEPOSIT_EVENT: &str = "DepositEvent(bytes,bytes,bytes,bytes,bytes)";
+
+// deposit amounts are denominated in gwei
+const GWEI: u64 = 1_000_000_000;
+
+pub struct EvmStakingDeposit {
+	network_id: PrimaryId,
+	event_topic: H256,
+}
+
+impl ModuleTrait for EvmStakingDeposit {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id, event_topic: H256::from(utils::keccak256(DEPOSIT_EVENT)) }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::EvmStakingDeposit
+	}
+}
+
+#[async_trait]
+impl EvmModuleTrait for EvmStakingDeposit {
+	async fn run(
+		&self,
+		_evm: &Evm,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: Transaction,
+		receipt: TransactionReceipt,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+		let tx_hash = tx.hash.encode_hex();
+
+		for log in receipt.logs.into_iter() {
+			// if log was removed, it's not valid
+			if log.removed == Some(true) {
+				continue;
+			}
+
+			// skip anything that's not a deposit
+			if !is_deposit_contract(&log.address) ||
+				log.topics.len() != 1 ||
+				log.topics[0] != self.event_topic
+			{
+				continue;
+			}
+
+			// pubkey, withdrawal_credentials, amount, signature & index
+			let params = abi::decode(
+				&[
+					ParamType::Bytes,
+					ParamType::Bytes,
+					ParamType::Bytes,
+					ParamType::Bytes,
+					ParamType::Bytes,
+				],
+				&log.data,
+			)?;
+			let (Some(pubkey), Some(withdrawal_credentials), Some(amount), Some(index)) = (
+				params[0].clone().into_bytes(),
+				params[1].clone().into_bytes(),
+				params[2].clone().into_bytes().and_then(|v| to_u64(&v)),
+				params[4].clone().into_bytes().and_then(|v| to_u64(&v)),
+			) else {
+				continue;
+			};
+
+			// @NOTE 0x01 (and 0x02, compounding) credentials end with the
+			// execution address that withdrawals go to
+			let withdrawal_address = Some(&withdrawal_credentials)
+				.filter(|c| c.len() == 32 && [1, 2].contains(&c[0]))
+				.map(|c| utils::to_checksum(&Address::from_slice(&c[12..]), None));
+
+			// the depositor is whoever sent the funds, not the contract that
+			// forwarded them (eg: batch deposit contracts)
+			let depositor = utils::to_checksum(&tx.from, None);
+			let amount = U256::from(amount) * GWEI;
+
+			ret.staking_deposits.insert(StakingDeposit::new(
+				self.network_id,
+				block_height,
+				&tx_hash,
+				index,
+				&depositor,
+				&format!("0x{}", hex::encode(pubkey)),
+				&format!("0x{}", hex::encode(withdrawal_credentials)),
+				withdrawal_address.clone(),
+				amount,
+				block_time,
+			));
+
+			// @NOTE funds sent to the deposit contract are otherwise a dead end,
+			// since withdrawals come back from the consensus layer. this keeps
+			// the trail going from the depositor to whoever can withdraw (no
+			// amounts, so balances aren't affected)
+			if let Some(withdrawal_address) = withdrawal_address.filter(|a| *a != depositor) {
+				ret.transfers.insert(Transfer::new(
+					self.get_id(),
+					self.network_id,
+					block_height,
+					&tx_hash,
+					log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+					&depositor,
+					&withdrawal_address,
+					None,
+					amount,
+					amount,
+					block_time,
+				));
+			}
+		}
+
+		Ok(ret)
+	}
+}
+
+fn is_deposit_contract(address: &Address) -> bool {
+	DEPOSIT_CONTRACTS.contains(&format!("{address:?}").as_str())
+}
+
+// amounts and indexes are little-endian uint64s
+fn to_u64(bytes: &[u8]) -> Option<u64> {
+	Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use ethers::{
+	abi::AbiEncode,
+	types::{Transaction, TransactionReceipt},
+	utils,
+};
+use eyre::Result;
+use std::collections::HashSet;
+
+use crate::{
+	chain::{
+		evm::{modules::EvmModuleTrait, EvmTopic},
+		Evm, ModuleId, ModuleTrait, WarehouseData, U256,
+	},
+	models::{PrimaryId, Transfer},
+	BlockHeight,
+};
+
+pub struct EvmSwap {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for EvmSwap {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::EvmSwap
+	}
+}
+
+#[async_trait]
+impl EvmModuleTrait for EvmSwap {
+	// @NOTE a swap shows up as tokens going into a pool and other tokens coming
+	// out of it, so tracing back from the recipient stops at the pool. this
+	// pairs the two legs: the tx sender is linked to the recipient with the
+	// token that came out. pools paying other pools are hops of a multi-hop
+	// swap, so only the last hop is paired
+	async fn run(
+		&self,
+		evm: &Evm,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: Transaction,
+		receipt: TransactionReceipt,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+		let tx_hash = tx.hash.encode_hex();
+
+		let mut swaps = vec![];
+		let mut token_transfers = vec![];
+		for log in receipt.logs.into_iter() {
+			// if log was removed, it's not valid
+			if log.removed == Some(true) {
+				continue;
+			}
+
+			match evm.get_topic(&log)? {
+				EvmTopic::Swap(recipient) => swaps.push((log.address, recipient)),
+				EvmTopic::TokenTransfer(from, to, amount) if amount > U256::zero() => {
+					let log_index = log.log_index.map(|i| i.as_u64()).unwrap_or_default();
+					token_transfers.push((log.address, from, to, amount, log_index))
+				}
+				_ => {}
+			}
+		}
+
+		let pools = swaps.iter().map(|(pool, _)| *pool).collect::<HashSet<_>>();
+		for (pool, recipient) in swaps.into_iter().filter(|(_, r)| !pools.contains(r)) {
+			let swapped_out = token_transfers
+				.iter()
+				.filter(|(_, from, to, _, _)| *from == pool && *to == recipient)
+				.filter(|(token, _, _, _, _)| evm.is_indexed_token(token))
+				.collect::<Vec<_>>();
+			if swapped_out.is_empty() {
+				continue;
+			}
+
+			let recipient = utils::to_checksum(&recipient, None);
+			ret.swaps.insert((self.network_id, recipient.clone(), utils::to_checksum(&pool, None)));
+
+			// swapping for oneself doesn't need linking
+			if recipient == utils::to_checksum(&tx.from, None) {
+				continue;
+			}
+
+			for (token, _, _, amount, log_index) in swapped_out.into_iter() {
+				ret.transfers.insert(Transfer::new(
+					self.get_id(),
+					self.network_id,
+					block_height,
+					&tx_hash,
+					*log_index,
+					&utils::to_checksum(&tx.from, None),
+					&recipient,
+					Some(utils::to_checksum(token, None)),
+					*amount,
+					*amount,
+					block_time,
+				));
+			}
+		}
+
+		Ok(ret)
+	}
+}
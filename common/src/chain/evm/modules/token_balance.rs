@@ -1,10 +1,11 @@
 use async_trait::async_trait;
 use ethers::{
 	abi::AbiEncode,
-	types::{Transaction, TransactionReceipt},
+	types::{Address, Transaction, TransactionReceipt},
 	utils,
 };
 use eyre::Result;
+use std::collections::HashMap;
 
 use crate::{
 	chain::{
@@ -41,6 +42,11 @@ impl EvmModuleTrait for EvmTokenBalance {
 	) -> Result<WarehouseData> {
 		let mut ret = WarehouseData::new();
 
+		// @NOTE amounts are unique per (tx, address, token), so everything an
+		// address gains and spends of a token in a tx has to be in the same
+		// record
+		let mut balance_map = HashMap::<(Address, Address), (U256, U256)>::new();
+
 		for log in receipt.logs.into_iter() {
 			// if log was removed, it's not valid
 			if let Some(removed) = log.removed {
@@ -49,36 +55,46 @@ impl EvmModuleTrait for EvmTokenBalance {
 				}
 			}
 
-			// process token `transfer` event
+			// skip tokens excluded for this network
+			if !evm.is_indexed_token(&log.address) {
+				continue;
+			}
+
+			// process token `transfer` event, and wrapping contracts minting and
+			// burning (they don't emit `transfer` for it)
 			match evm.get_topic(&log)? {
 				EvmTopic::TokenTransfer(from, to, amount) if amount > U256::zero() => {
-					ret.amounts.insert(Amount::new(
-						self.get_id(),
-						self.network_id,
-						block_height,
-						&tx.hash.encode_hex(),
-						&utils::to_checksum(&from, None),
-						Some(utils::to_checksum(&log.address, None)),
-						U256::zero(),
-						amount,
-						block_time,
-					));
-					ret.amounts.insert(Amount::new(
-						self.get_id(),
-						self.network_id,
-						block_height,
-						&tx.hash.encode_hex(),
-						&utils::to_checksum(&to, None),
-						Some(utils::to_checksum(&log.address, None)),
-						amount,
-						U256::zero(),
-						block_time,
-					));
+					balance_map.entry((from, log.address)).or_default().1 += amount;
+					balance_map.entry((to, log.address)).or_default().0 += amount;
+				}
+				EvmTopic::Wrap(dst, amount) if evm.is_wrapped_native_token(&log.address) => {
+					balance_map.entry((dst, log.address)).or_default().0 += amount;
+				}
+				EvmTopic::Unwrap(src, amount) if evm.is_wrapped_native_token(&log.address) => {
+					balance_map.entry((src, log.address)).or_default().1 += amount;
 				}
 				_ => {}
 			}
 		}
 
+		for ((address, token), (amount_in, amount_out)) in balance_map.into_iter() {
+			if amount_in.is_zero() && amount_out.is_zero() {
+				continue;
+			}
+
+			ret.amounts.insert(Amount::new(
+				self.get_id(),
+				self.network_id,
+				block_height,
+				&tx.hash.encode_hex(),
+				&utils::to_checksum(&address, None),
+				Some(utils::to_checksum(&token, None)),
+				amount_in,
+				amount_out,
+				block_time,
+			));
+		}
+
 		Ok(ret)
 	}
 }
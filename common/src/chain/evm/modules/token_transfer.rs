@@ -49,6 +49,11 @@ impl EvmModuleTrait for EvmTokenTransfer {
 				}
 			}
 
+			// skip tokens excluded for this network
+			if !evm.is_indexed_token(&log.address) {
+				continue;
+			}
+
 			// process token `transfer` event
 			match evm.get_topic(&log)? {
 				EvmTopic::TokenTransfer(from, to, amount) if amount > U256::zero() => {
@@ -57,6 +62,7 @@ impl EvmModuleTrait for EvmTokenTransfer {
 						self.network_id,
 						block_height,
 						&tx.hash.encode_hex(),
+						log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
 						&utils::to_checksum(&from, None),
 						&utils::to_checksum(&to, None),
 						Some(utils::to_checksum(&log.address, None)),
@@ -65,6 +71,13 @@ impl EvmModuleTrait for EvmTokenTransfer {
 						block_time,
 					));
 				}
+				EvmTopic::NftTransfer(_, _, token_id) => {
+					ret.nfts.insert((
+						self.network_id,
+						utils::to_checksum(&log.address, None),
+						token_id.to_string(),
+					));
+				}
 				_ => {}
 			}
 		}
@@ -7,7 +7,10 @@ use ethers::{
 use eyre::Result;
 
 use crate::{
-	chain::{evm::modules::EvmModuleTrait, Evm, ModuleId, ModuleTrait, WarehouseData, U256},
+	chain::{
+		evm::{l2, modules::EvmModuleTrait},
+		Evm, ModuleId, ModuleTrait, WarehouseData, U256,
+	},
 	models::{PrimaryId, Transfer},
 	BlockHeight,
 };
@@ -38,6 +41,25 @@ impl EvmModuleTrait for EvmTransfer {
 	) -> Result<WarehouseData> {
 		let mut ret = WarehouseData::new();
 
+		// l2 deposits first mint bridged eth to the sender
+		if let Some(minted) = l2::get_minted(&tx) {
+			let amount = U256::from_str_radix(&minted.to_string(), 10)?;
+
+			ret.transfers.insert(Transfer::new(
+				self.get_id(),
+				self.network_id,
+				block_height,
+				&tx.hash.encode_hex(),
+				0,
+				"",
+				&utils::to_checksum(&tx.from, None),
+				None,
+				amount,
+				amount,
+				block_time,
+			));
+		}
+
 		// skip if no asset transfer
 		if tx.value.is_zero() {
 			return Ok(ret);
@@ -58,6 +80,7 @@ impl EvmModuleTrait for EvmTransfer {
 			self.network_id,
 			block_height,
 			&tx.hash.encode_hex(),
+			0,
 			&utils::to_checksum(&tx.from, None),
 			&utils::to_checksum(&tx.to.unwrap(), None),
 			None,
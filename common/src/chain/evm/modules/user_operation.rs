@@ -0,0 +1,133 @@
+use async_trait::async_trait;
+use ethers::{
+	abi::{self, AbiEncode, ParamType},
+	types::{Address, Transaction, TransactionReceipt, H256},
+	utils,
+};
+use eyre::Result;
+
+use crate::{
+	chain::{evm::modules::EvmModuleTrait, Evm, ModuleId, ModuleTrait, WarehouseData},
+	models::{PrimaryId, Transfer, UserOperation},
+	BlockHeight,
+};
+
+// erc-4337 entry points (v0.6 and v0.7)
+static ENTRY_POINTS: [&str; 2] =
+	["0x5ff137d4b0fdcd49dca30c7cf57e578a026d2789", "0x0000000071727de22e5e9d8baf0edac6f37da032"];
+
+static USER_OPERATION_EVENT: &str =
+	"UserOperationEvent(bytes32,address,address,uint256,bool,uint256,uint256)";
+
+pub struct EvmUserOperation {
+	network_id: PrimaryId,
+	event_topic: H256,
+}
+
+impl ModuleTrait for EvmUserOperation {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id, event_topic: H256::from(utils::keccak256(USER_OPERATION_EVENT)) }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::EvmUserOperation
+	}
+}
+
+#[async_trait]
+impl EvmModuleTrait for EvmUserOperation {
+	async fn run(
+		&self,
+		_evm: &Evm,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: Transaction,
+		receipt: TransactionReceipt,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+		let tx_hash = tx.hash.encode_hex();
+
+		// @NOTE both `handleOps` and `handleAggregatedOps` take the beneficiary
+		// (who gets refunded for gas) as the second argument. it's only known
+		// when the bundler calls the entry point directly
+		let beneficiary = tx
+			.to
+			.filter(|to| is_entry_point(to) && tx.input.len() >= 68)
+			.map(|_| Address::from_slice(&tx.input[48..68]));
+
+		for log in receipt.logs.into_iter() {
+			// if log was removed, it's not valid
+			if log.removed == Some(true) {
+				continue;
+			}
+
+			// skip anything that's not a user operation
+			if !is_entry_point(&log.address) ||
+				log.topics.len() != 4 ||
+				log.topics[0] != self.event_topic
+			{
+				continue;
+			}
+
+			// nonce, success, actualGasCost & actualGasUsed
+			let params = abi::decode(
+				&[
+					ParamType::Uint(256),
+					ParamType::Bool,
+					ParamType::Uint(256),
+					ParamType::Uint(256),
+				],
+				&log.data,
+			)?;
+			let (Some(nonce), Some(success), Some(actual_gas_cost)) = (
+				params[0].clone().into_uint(),
+				params[1].clone().into_bool(),
+				params[2].clone().into_uint(),
+			) else {
+				continue;
+			};
+
+			let sender = Address::from(log.topics[2]);
+			let paymaster = Some(Address::from(log.topics[3])).filter(|p| !p.is_zero());
+
+			ret.user_operations.insert(UserOperation::new(
+				self.network_id,
+				block_height,
+				&tx_hash,
+				&log.topics[1].encode_hex(),
+				&utils::to_checksum(&log.address, None),
+				&utils::to_checksum(&sender, None),
+				paymaster.map(|p| utils::to_checksum(&p, None)),
+				&utils::to_checksum(&tx.from, None),
+				nonce,
+				success,
+				actual_gas_cost,
+				block_time,
+			));
+
+			// gas is paid by the paymaster if there is one, otherwise by the
+			// smart account itself (not by whoever submitted the bundle)
+			if let Some(beneficiary) = beneficiary.filter(|_| !actual_gas_cost.is_zero()) {
+				ret.transfers.insert(Transfer::new(
+					self.get_id(),
+					self.network_id,
+					block_height,
+					&tx_hash,
+					log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+					&utils::to_checksum(&paymaster.unwrap_or(sender), None),
+					&utils::to_checksum(&beneficiary, None),
+					None,
+					actual_gas_cost,
+					actual_gas_cost,
+					block_time,
+				));
+			}
+		}
+
+		Ok(ret)
+	}
+}
+
+fn is_entry_point(address: &Address) -> bool {
+	ENTRY_POINTS.contains(&format!("{address:?}").as_str())
+}
@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use ethers::{
+	types::{Block, Transaction, TransactionReceipt},
+	utils,
+};
+use eyre::Result;
+use std::collections::HashMap;
+
+use crate::{
+	chain::{evm::modules::EvmModuleTrait, Evm, ModuleId, ModuleTrait, WarehouseData, U256},
+	models::{Amount, PrimaryId, Transfer},
+	BlockHeight,
+};
+
+// withdrawal amounts are denominated in gwei
+const GWEI: u64 = 1_000_000_000;
+
+pub struct EvmWithdrawal {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for EvmWithdrawal {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::EvmWithdrawal
+	}
+}
+
+#[async_trait]
+impl EvmModuleTrait for EvmWithdrawal {
+	async fn run(
+		&self,
+		_evm: &Evm,
+		_block_height: BlockHeight,
+		_block_time: u32,
+		_tx: Transaction,
+		_receipt: TransactionReceipt,
+	) -> Result<WarehouseData> {
+		Ok(WarehouseData::new())
+	}
+
+	// @NOTE post-shanghai blocks carry beacon-chain withdrawals (validator
+	// rewards and exits) in their body. they are not transactions, so they're
+	// recorded as transfers without a source (coming from the consensus layer)
+	// and keyed by block hash. withdrawals to the same address within a block
+	// are summed up, since they would otherwise collapse in the warehouse
+	async fn run_block(
+		&self,
+		_evm: &Evm,
+		block_height: BlockHeight,
+		block_time: u32,
+		block: &Block<Transaction>,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+
+		let (Some(block_hash), Some(withdrawals)) = (block.hash, block.withdrawals.as_ref()) else {
+			return Ok(ret);
+		};
+
+		let mut amounts = HashMap::<String, U256>::new();
+		for withdrawal in withdrawals.iter().filter(|w| !w.amount.is_zero()) {
+			let amount = U256::from_str_radix(&withdrawal.amount.to_string(), 10)? * GWEI;
+			let to = utils::to_checksum(&withdrawal.address, None);
+			*amounts.entry(to).or_insert(U256::zero()) += amount;
+		}
+
+		let tx_hash = format!("{block_hash:?}");
+		let batch_amount = amounts.values().fold(U256::zero(), |acc, a| acc + *a);
+
+		for (to, amount) in amounts.into_iter() {
+			ret.transfers.insert(Transfer::new(
+				self.get_id(),
+				self.network_id,
+				block_height,
+				&tx_hash,
+				0,
+				"",
+				&to,
+				None,
+				amount,
+				batch_amount,
+				block_time,
+			));
+			ret.amounts.insert(Amount::new(
+				self.get_id(),
+				self.network_id,
+				block_height,
+				&tx_hash,
+				&to,
+				None,
+				amount,
+				U256::zero(),
+				block_time,
+			));
+		}
+
+		Ok(ret)
+	}
+}
@@ -1,12 +1,12 @@
-use duckdb::{params, Connection};
+use duckdb::{params, Appender, Connection};
 use ethers::{
 	abi::AbiEncode,
 	types::{H160, H256, U256},
 };
 use eyre::Result;
 
-use super::ParquetFile;
-use crate::storage::StorageModelTrait;
+use super::{decode_hex, decode_hex_opt, parse_u256, parse_u256_opt, read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
 
 #[derive(Debug)]
 pub struct Block {
@@ -23,10 +23,36 @@ pub struct Block {
 	pub base_fee_per_gas: Option<U256>,
 }
 
+impl Block {
+	pub fn get(storage_db: &StorageDb) -> Result<Option<Block>> {
+		let blocks = read_file(storage_db, ParquetFile::Blocks, "*", |row| {
+			Ok(Block {
+				hash: decode_hex_opt(row.get(0)?)?,
+				parent_hash: decode_hex(&row.get::<_, String>(1)?)?,
+				author: decode_hex_opt(row.get(2)?)?,
+				state_root: decode_hex(&row.get::<_, String>(3)?)?,
+				transactions_root: decode_hex(&row.get::<_, String>(4)?)?,
+				receipts_root: decode_hex(&row.get::<_, String>(5)?)?,
+				number: row.get(6)?,
+				gas_used: parse_u256(&row.get::<_, String>(7)?)?,
+				timestamp: row.get(8)?,
+				total_difficulty: parse_u256_opt(row.get(9)?)?,
+				base_fee_per_gas: parse_u256_opt(row.get(10)?)?,
+			})
+		})?;
+
+		Ok(blocks.into_iter().next())
+	}
+}
+
 impl StorageModelTrait for Block {
+	fn get_table(&self) -> String {
+		ParquetFile::Blocks.to_string()
+	}
+
 	fn create_table(&self, db: &Connection) -> Result<()> {
 		db.execute_batch(&format!(
-			r#"CREATE TEMP TABLE IF NOT EXISTS {} (
+			r#"CREATE TABLE IF NOT EXISTS {} (
                 hash VARCHAR,
                 parent_hash VARCHAR NOT NULL,
                 author VARCHAR,
@@ -45,42 +71,20 @@ impl StorageModelTrait for Block {
 		Ok(())
 	}
 
-	fn insert(&self, db: &Connection) -> Result<()> {
-		self.create_table(db)?;
-
-		db.execute(
-			&format!(
-				r#"INSERT INTO {} (
-                    hash,
-                    parent_hash,
-                    author,
-                    state_root,
-                    transactions_root,
-                    receipts_root,
-                    number,
-                    gas_used,
-                    timestamp,
-                    total_difficulty,
-                    base_fee_per_gas
-                ) VALUES (
-                    ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
-                );"#,
-				ParquetFile::Blocks
-			),
-			params![
-				self.hash.map(|v| v.encode_hex()),
-				self.parent_hash.encode_hex(),
-				self.author.map(|v| v.encode_hex()),
-				self.state_root.encode_hex(),
-				self.transactions_root.encode_hex(),
-				self.receipts_root.encode_hex(),
-				self.number,
-				self.gas_used.to_string(),
-				self.timestamp,
-				self.total_difficulty.map(|v| v.to_string()),
-				self.base_fee_per_gas.map(|v| v.to_string()),
-			],
-		)?;
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.hash.map(|v| v.encode_hex()),
+			self.parent_hash.encode_hex(),
+			self.author.map(|v| v.encode_hex()),
+			self.state_root.encode_hex(),
+			self.transactions_root.encode_hex(),
+			self.receipts_root.encode_hex(),
+			self.number,
+			self.gas_used.to_string(),
+			self.timestamp,
+			self.total_difficulty.map(|v| v.to_string()),
+			self.base_fee_per_gas.map(|v| v.to_string()),
+		])?;
 
 		Ok(())
 	}
@@ -1,12 +1,12 @@
-use duckdb::{params, Connection};
+use duckdb::{params, Appender, Connection};
 use ethers::{
-	abi::AbiEncode,
+	abi::{AbiDecode, AbiEncode},
 	types::{Bytes, H160, H256, U256},
 };
 use eyre::Result;
 
-use super::ParquetFile;
-use crate::storage::StorageModelTrait;
+use super::{decode_hex, decode_hex_opt, parse_u256_opt, read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
 
 #[derive(Debug)]
 pub struct Log {
@@ -22,10 +22,43 @@ pub struct Log {
 	pub removed: Option<bool>,
 }
 
+impl Log {
+	// @NOTE `address` and `data` went into varchar columns as raw bytes, which
+	// duckdb escapes (eg: `\x00`); casting back to a blob reverses that
+	pub fn get_all(storage_db: &StorageDb) -> Result<Vec<Log>> {
+		let columns = "address::BLOB, topics, data::BLOB, transaction_hash, transaction_index, \
+		               log_index, transaction_log_index, log_type, removed";
+
+		read_file(storage_db, ParquetFile::Logs, columns, |row| {
+			let topics = row.get::<_, Option<String>>(1)?.unwrap_or_default();
+
+			Ok(Log {
+				address: H160::decode(row.get::<_, Vec<u8>>(0)?)?,
+				topics: topics
+					.split(',')
+					.filter(|t| !t.is_empty())
+					.map(decode_hex)
+					.collect::<Result<Vec<_>>>()?,
+				data: row.get::<_, Option<Vec<u8>>>(2)?.unwrap_or_default().into(),
+				transaction_hash: decode_hex_opt(row.get(3)?)?,
+				transaction_index: row.get(4)?,
+				log_index: parse_u256_opt(row.get(5)?)?,
+				transaction_log_index: parse_u256_opt(row.get(6)?)?,
+				log_type: row.get(7)?,
+				removed: row.get(8)?,
+			})
+		})
+	}
+}
+
 impl StorageModelTrait for Log {
+	fn get_table(&self) -> String {
+		ParquetFile::Logs.to_string()
+	}
+
 	fn create_table(&self, db: &Connection) -> Result<()> {
 		db.execute_batch(&format!(
-			r#"CREATE TEMP TABLE IF NOT EXISTS {} (
+			r#"CREATE TABLE IF NOT EXISTS {} (
                 address VARCHAR,
                 topics VARCHAR,
                 data VARCHAR,
@@ -42,38 +75,18 @@ impl StorageModelTrait for Log {
 		Ok(())
 	}
 
-	fn insert(&self, db: &Connection) -> Result<()> {
-		self.create_table(db)?;
-
-		db.execute(
-			&format!(
-				r#"INSERT INTO {} (
-                    address,
-                    topics,
-                    data,
-                    transaction_hash,
-                    transaction_index,
-                    log_index,
-                    transaction_log_index,
-                    log_type,
-                    removed
-                ) VALUES (
-                    ?, ?, ?, ?, ?, ?, ?, ?, ?
-                );"#,
-				ParquetFile::Logs
-			),
-			params![
-				self.address.encode(),
-				self.topics.iter().map(|v| v.encode_hex()).collect::<Vec<String>>().join(","),
-				self.data.to_vec(),
-				self.transaction_hash.map(|v| v.encode_hex()),
-				self.transaction_index,
-				self.log_index.map(|v| v.to_string()),
-				self.transaction_log_index.map(|v| v.to_string()),
-				self.log_type,
-				self.removed,
-			],
-		)?;
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.address.encode(),
+			self.topics.iter().map(|v| v.encode_hex()).collect::<Vec<String>>().join(","),
+			self.data.to_vec(),
+			self.transaction_hash.map(|v| v.encode_hex()),
+			self.transaction_index,
+			self.log_index.map(|v| v.to_string()),
+			self.transaction_log_index.map(|v| v.to_string()),
+			self.log_type,
+			self.removed,
+		])?;
 
 		Ok(())
 	}
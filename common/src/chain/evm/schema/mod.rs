@@ -1,9 +1,15 @@
 use derive_more::Display;
+use duckdb::Row;
+use ethers::{abi::AbiDecode, types::U256};
+use eyre::Result;
+
+use crate::storage::StorageDb;
 
 pub use self::log::Log;
 pub use block::Block;
 pub use receipt::Receipt;
 pub use transaction::Transaction;
+pub use withdrawal::Withdrawal;
 
 #[derive(Display, Debug)]
 pub enum ParquetFile {
@@ -15,9 +21,76 @@ pub enum ParquetFile {
 	Receipts,
 	#[display("logs")]
 	Logs,
+	#[display("withdrawals")]
+	Withdrawals,
 }
 
 mod block;
 mod log;
 mod receipt;
 mod transaction;
+mod withdrawal;
+
+// @NOTE rows of a stored file, mapped with `f`. files are only written when
+// there's something in them (eg: blocks without logs have no logs file), so a
+// missing one has no rows
+fn read_file<T>(
+	storage_db: &StorageDb,
+	file: ParquetFile,
+	columns: &str,
+	mut f: impl FnMut(&Row) -> Result<T>,
+) -> Result<Vec<T>> {
+	let mut ret = vec![];
+
+	if let Some(source) = storage_db.get_source(&file.to_string())? {
+		let mut statement = storage_db.db.prepare(&format!("SELECT {columns} FROM {source}"))?;
+		let mut rows = statement.query([])?;
+
+		while let Some(row) = rows.next()? {
+			ret.push(f(row)?);
+		}
+	}
+
+	Ok(ret)
+}
+
+// hashes and addresses are stored abi-encoded (see `AbiEncode::encode_hex()`)
+fn decode_hex<T: AbiDecode>(value: &str) -> Result<T> {
+	Ok(T::decode_hex(value)?)
+}
+
+fn decode_hex_opt<T: AbiDecode>(value: Option<String>) -> Result<Option<T>> {
+	value.map(|v| decode_hex(&v)).transpose()
+}
+
+// amounts are stored as decimal strings
+fn parse_u256(value: &str) -> Result<U256> {
+	Ok(U256::from_dec_str(value)?)
+}
+
+fn parse_u256_opt(value: Option<String>) -> Result<Option<U256>> {
+	value.map(|v| parse_u256(&v)).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethers::{
+		abi::AbiEncode,
+		types::{H160, H256},
+	};
+
+	#[test]
+	fn test_decode_stored_values() {
+		let address = H160::random();
+		assert_eq!(decode_hex::<H160>(&address.encode_hex()).unwrap(), address);
+
+		let hash = H256::random();
+		assert_eq!(decode_hex_opt::<H256>(Some(hash.encode_hex())).unwrap(), Some(hash));
+		assert_eq!(decode_hex_opt::<H256>(None).unwrap(), None);
+
+		let amount = U256::from(10).pow(U256::from(24));
+		assert_eq!(parse_u256(&amount.to_string()).unwrap(), amount);
+		assert!(parse_u256("0x1").is_err());
+	}
+}
@@ -1,12 +1,12 @@
-use duckdb::{params, Connection};
+use duckdb::{params, Appender, Connection};
 use ethers::{
 	abi::AbiEncode,
 	types::{H160, H256, U256},
 };
 use eyre::Result;
 
-use super::ParquetFile;
-use crate::storage::StorageModelTrait;
+use super::{decode_hex, decode_hex_opt, parse_u256, parse_u256_opt, read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
 
 #[derive(Debug)]
 pub struct Receipt {
@@ -24,12 +24,46 @@ pub struct Receipt {
 	pub root: Option<H256>,
 	pub transaction_type: Option<u64>,
 	pub effective_gas_price: Option<U256>,
+	// l2 data availability costs
+	pub l1_fee: Option<U256>,
+	pub l1_gas_price: Option<U256>,
+	pub l1_gas_used: Option<U256>,
+}
+
+impl Receipt {
+	pub fn get_all(storage_db: &StorageDb) -> Result<Vec<Receipt>> {
+		read_file(storage_db, ParquetFile::Receipts, "*", |row| {
+			Ok(Receipt {
+				transaction_hash: decode_hex(&row.get::<_, String>(0)?)?,
+				transaction_index: row.get(1)?,
+				block_hash: decode_hex_opt(row.get(2)?)?,
+				block_number: row.get(3)?,
+				from_address: decode_hex(&row.get::<_, String>(4)?)?,
+				to_address: decode_hex_opt(row.get(5)?)?,
+				cumulative_gas_used: parse_u256(&row.get::<_, String>(6)?)?,
+				gas_used: parse_u256_opt(row.get(7)?)?,
+				contract_address: decode_hex_opt(row.get(8)?)?,
+				logs: row.get(9)?,
+				status: row.get(10)?,
+				root: decode_hex_opt(row.get(11)?)?,
+				transaction_type: row.get(12)?,
+				effective_gas_price: parse_u256_opt(row.get(13)?)?,
+				l1_fee: parse_u256_opt(row.get(14)?)?,
+				l1_gas_price: parse_u256_opt(row.get(15)?)?,
+				l1_gas_used: parse_u256_opt(row.get(16)?)?,
+			})
+		})
+	}
 }
 
 impl StorageModelTrait for Receipt {
+	fn get_table(&self) -> String {
+		ParquetFile::Receipts.to_string()
+	}
+
 	fn create_table(&self, db: &Connection) -> Result<()> {
 		db.execute_batch(&format!(
-			r#"CREATE TEMP TABLE IF NOT EXISTS {} (
+			r#"CREATE TABLE IF NOT EXISTS {} (
                 transaction_hash VARCHAR,
                 transaction_index UINT64 NOT NULL,
                 block_hash VARCHAR,
@@ -43,7 +77,10 @@ impl StorageModelTrait for Receipt {
                 status UINT64,
                 root VARCHAR,
                 transaction_type UINT64,
-                effective_gas_price VARCHAR
+                effective_gas_price VARCHAR,
+                l1_fee VARCHAR,
+                l1_gas_price VARCHAR,
+                l1_gas_used VARCHAR
             );"#,
 			ParquetFile::Receipts
 		))?;
@@ -51,48 +88,26 @@ impl StorageModelTrait for Receipt {
 		Ok(())
 	}
 
-	fn insert(&self, db: &Connection) -> Result<()> {
-		self.create_table(db)?;
-
-		db.execute(
-			&format!(
-				r#"INSERT INTO {} (
-                    transaction_hash,
-                    transaction_index,
-                    block_hash,
-                    block_number,
-                    from_address,
-                    to_address,
-                    cumulative_gas_used,
-                    gas_used,
-                    contract_address,
-                    logs,
-                    status,
-                    root,
-                    transaction_type,
-                    effective_gas_price
-                ) VALUES (
-                    ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
-                );"#,
-				ParquetFile::Receipts
-			),
-			params![
-				self.transaction_hash.encode_hex(),
-				self.transaction_index,
-				self.block_hash.map(|v| v.encode_hex()),
-				self.block_number,
-				self.from_address.encode_hex(),
-				self.to_address.map(|v| v.encode_hex()),
-				self.cumulative_gas_used.to_string(),
-				self.gas_used.map(|v| v.to_string()),
-				self.contract_address.map(|v| v.encode_hex()),
-				self.logs,
-				self.status,
-				self.root.map(|v| v.encode_hex()),
-				self.transaction_type,
-				self.effective_gas_price.map(|v| v.to_string()),
-			],
-		)?;
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.transaction_hash.encode_hex(),
+			self.transaction_index,
+			self.block_hash.map(|v| v.encode_hex()),
+			self.block_number,
+			self.from_address.encode_hex(),
+			self.to_address.map(|v| v.encode_hex()),
+			self.cumulative_gas_used.to_string(),
+			self.gas_used.map(|v| v.to_string()),
+			self.contract_address.map(|v| v.encode_hex()),
+			self.logs,
+			self.status,
+			self.root.map(|v| v.encode_hex()),
+			self.transaction_type,
+			self.effective_gas_price.map(|v| v.to_string()),
+			self.l1_fee.map(|v| v.to_string()),
+			self.l1_gas_price.map(|v| v.to_string()),
+			self.l1_gas_used.map(|v| v.to_string()),
+		])?;
 
 		Ok(())
 	}
@@ -1,12 +1,12 @@
-use duckdb::{params, Connection};
+use duckdb::{params, Appender, Connection};
 use ethers::{
 	abi::AbiEncode,
-	types::{H160, H256, U256},
+	types::{Bytes, H160, H256, U256},
 };
 use eyre::Result;
 
-use super::ParquetFile;
-use crate::storage::StorageModelTrait;
+use super::{decode_hex, decode_hex_opt, parse_u256, parse_u256_opt, read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
 
 #[derive(Debug)]
 pub struct Transaction {
@@ -20,12 +20,48 @@ pub struct Transaction {
 	pub gas: U256,
 	pub transaction_type: Option<u64>,
 	pub chain_id: Option<U256>,
+	// l2 deposit fields
+	pub source_hash: Option<H256>,
+	pub mint: Option<U256>,
+	pub is_system_tx: bool,
+	pub input: Bytes,
+}
+
+impl Transaction {
+	pub fn get_all(storage_db: &StorageDb) -> Result<Vec<Transaction>> {
+		read_file(storage_db, ParquetFile::Transactions, "*", |row| {
+			Ok(Transaction {
+				hash: decode_hex(&row.get::<_, String>(0)?)?,
+				nonce: parse_u256(&row.get::<_, String>(1)?)?,
+				transaction_index: row
+					.get::<_, Option<String>>(2)?
+					.map(|v| v.parse::<u64>())
+					.transpose()?,
+				from_address: decode_hex(&row.get::<_, String>(3)?)?,
+				to_address: decode_hex_opt(row.get(4)?)?,
+				value: parse_u256(&row.get::<_, String>(5)?)?,
+				gas_price: parse_u256_opt(row.get(6)?)?,
+				gas: parse_u256(&row.get::<_, String>(7)?)?,
+				transaction_type: row.get(8)?,
+				chain_id: parse_u256_opt(row.get(9)?)?,
+				source_hash: decode_hex_opt(row.get(10)?)?,
+				mint: parse_u256_opt(row.get(11)?)?,
+				is_system_tx: row.get(12)?,
+				// not extracted by earlier versions
+				input: row.get::<_, Option<Vec<u8>>>(13).ok().flatten().unwrap_or_default().into(),
+			})
+		})
+	}
 }
 
 impl StorageModelTrait for Transaction {
+	fn get_table(&self) -> String {
+		ParquetFile::Transactions.to_string()
+	}
+
 	fn create_table(&self, db: &Connection) -> Result<()> {
 		db.execute_batch(&format!(
-			r#"CREATE TEMP TABLE IF NOT EXISTS {} (
+			r#"CREATE TABLE IF NOT EXISTS {} (
                 hash VARCHAR NOT NULL,
                 nonce VARCHAR NOT NULL,
                 transaction_index VARCHAR,
@@ -35,7 +71,11 @@ impl StorageModelTrait for Transaction {
                 gas_price VARCHAR,
                 gas VARCHAR NOT NULL,
                 transaction_type UINT64,
-                chain_id VARCHAR
+                chain_id VARCHAR,
+                source_hash VARCHAR,
+                mint VARCHAR,
+                is_system_tx BOOLEAN NOT NULL,
+                input BLOB
             );"#,
 			ParquetFile::Transactions
 		))?;
@@ -43,40 +83,23 @@ impl StorageModelTrait for Transaction {
 		Ok(())
 	}
 
-	fn insert(&self, db: &Connection) -> Result<()> {
-		self.create_table(db)?;
-
-		db.execute(
-			&format!(
-				r#"INSERT INTO {} (
-                    hash,
-                    nonce,
-                    transaction_index,
-                    from_address,
-                    to_address,
-                    value,
-                    gas_price,
-                    gas,
-                    transaction_type,
-                    chain_id
-                ) VALUES (
-                    ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
-                );"#,
-				ParquetFile::Transactions
-			),
-			params![
-				self.hash.encode_hex(),
-				self.nonce.to_string(),
-				self.transaction_index,
-				self.from_address.encode_hex(),
-				self.to_address.map(|v| v.encode_hex()),
-				self.value.to_string(),
-				self.gas_price.map(|v| v.to_string()),
-				self.gas.to_string(),
-				self.transaction_type,
-				self.chain_id.map(|v| v.to_string()),
-			],
-		)?;
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.hash.encode_hex(),
+			self.nonce.to_string(),
+			self.transaction_index,
+			self.from_address.encode_hex(),
+			self.to_address.map(|v| v.encode_hex()),
+			self.value.to_string(),
+			self.gas_price.map(|v| v.to_string()),
+			self.gas.to_string(),
+			self.transaction_type,
+			self.chain_id.map(|v| v.to_string()),
+			self.source_hash.map(|v| v.encode_hex()),
+			self.mint.map(|v| v.to_string()),
+			self.is_system_tx,
+			self.input.to_vec(),
+		])?;
 
 		Ok(())
 	}
@@ -0,0 +1,62 @@
+use duckdb::{params, Appender, Connection};
+use ethers::{
+	abi::AbiEncode,
+	types::{H160, U256},
+};
+use eyre::Result;
+
+use super::{decode_hex, parse_u256, read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
+
+// beacon-chain withdrawals in post-shanghai blocks (`amount` is in gwei)
+#[derive(Debug)]
+pub struct Withdrawal {
+	pub index: u64,
+	pub validator_index: u64,
+	pub address: H160,
+	pub amount: U256,
+}
+
+impl Withdrawal {
+	pub fn get_all(storage_db: &StorageDb) -> Result<Vec<Withdrawal>> {
+		read_file(storage_db, ParquetFile::Withdrawals, "*", |row| {
+			Ok(Withdrawal {
+				index: row.get(0)?,
+				validator_index: row.get(1)?,
+				address: decode_hex(&row.get::<_, String>(2)?)?,
+				amount: parse_u256(&row.get::<_, String>(3)?)?,
+			})
+		})
+	}
+}
+
+impl StorageModelTrait for Withdrawal {
+	fn get_table(&self) -> String {
+		ParquetFile::Withdrawals.to_string()
+	}
+
+	fn create_table(&self, db: &Connection) -> Result<()> {
+		db.execute_batch(&format!(
+			r#"CREATE TABLE IF NOT EXISTS {} (
+                withdrawal_index UINT64 NOT NULL,
+                validator_index UINT64 NOT NULL,
+                address VARCHAR NOT NULL,
+                amount VARCHAR NOT NULL
+            );"#,
+			ParquetFile::Withdrawals
+		))?;
+
+		Ok(())
+	}
+
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.index,
+			self.validator_index,
+			self.address.encode_hex(),
+			self.amount.to_string(),
+		])?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::{
+	chain::{ModuleId, WarehouseData, U256},
+	models::{Amount, PrimaryId, Transfer},
+};
+
+// stands in for a tx hash, since genesis balances weren't sent by anyone
+pub static GENESIS_TX_HASH: &str = "genesis";
+
+// @NOTE balances that exist from the very start of a chain (eg: evm `alloc`
+// in genesis.json, or a premine) never show up in any transaction, so computed
+// balances would be off by that much. they're imported as synthetic block-0
+// records instead: a transfer without a source (for tracing) and an amount
+// (for balances) per address
+pub fn get_warehouse_data(
+	network_id: PrimaryId,
+	alloc: HashMap<String, U256>,
+	created_at: u32,
+) -> WarehouseData {
+	let mut ret = WarehouseData::new();
+
+	let batch_amount = alloc.values().fold(U256::zero(), |acc, a| acc.saturating_add(*a));
+	for (address, balance) in alloc.into_iter().filter(|(_, b)| !b.is_zero()) {
+		ret.transfers.insert(Transfer::new(
+			ModuleId::Genesis,
+			network_id,
+			0,
+			GENESIS_TX_HASH,
+			0,
+			"",
+			&address,
+			None,
+			balance,
+			batch_amount,
+			created_at,
+		));
+		ret.amounts.insert(Amount::new(
+			ModuleId::Genesis,
+			network_id,
+			0,
+			GENESIS_TX_HASH,
+			&address,
+			None,
+			balance,
+			U256::zero(),
+			created_at,
+		));
+	}
+
+	ret
+}
+
+// genesis files use either hex ("0x..") or decimal numbers
+pub fn parse_quantity(v: &str) -> Option<U256> {
+	match v.strip_prefix("0x") {
+		Some(hex) => U256::from_str_radix(hex, 16).ok(),
+		_ => U256::from_dec_str(v).ok(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_quantity() {
+		assert_eq!(parse_quantity("0x3e8"), Some(U256::from(1_000)));
+		assert_eq!(parse_quantity("1000"), Some(U256::from(1_000)));
+		assert_eq!(parse_quantity("abc"), None);
+	}
+}
@@ -1,34 +1,173 @@
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
 use derive_more::Display;
-use eyre::Result;
-use std::{collections::HashSet, ops::AddAssign, sync::Arc};
+use eyre::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::HashSet,
+	fs,
+	ops::AddAssign,
+	path::Path,
+	sync::{atomic::Ordering, Arc},
+};
 use tokio::task::JoinSet;
+use tracing::warn;
+use uuid::Uuid;
 
+#[cfg(feature = "bitcoin")]
 pub use crate::chain::bitcoin::Bitcoin;
 use crate::{
-	models::{Amount, AmountTable, Link, LinkTable, Network, Transfer, TransferTable},
-	utils, BlockHeight, PrimaryId, RateLimiter, Storage, Warehouse,
+	metrics::Counter,
+	models::{
+		Abi, Amount, AmountTable, Approval, ApprovalTable, Block, BlockTable, DecodedCall,
+		DecodedCallTable, Fee, FeeTable, Link, LinkTable, Network, StakingDeposit,
+		StakingDepositTable, Transfer, TransferTable, UserOperation, UserOperationTable,
+	},
+	utils, Architecture, BlockHeight, PrimaryId, RateLimiter, Storage, Warehouse,
 };
+#[cfg(feature = "evm")]
 pub use evm::Evm;
+pub use plugin::{PluginModule, PluginTrait, Plugins};
+#[cfg(feature = "solana")]
+pub use solana::Solana;
+#[cfg(feature = "tron")]
+pub use tron::Tron;
 pub use u256::U256;
 
+#[cfg(feature = "bitcoin")]
 pub mod bitcoin;
+#[cfg(feature = "evm")]
 pub mod evm;
+pub mod genesis;
+pub mod plugin;
+#[cfg(feature = "solana")]
+pub mod solana;
+#[cfg(feature = "tron")]
+pub mod tron;
 pub mod u256;
 
 pub type BoxedChain = Box<dyn ChainTrait>;
 
-#[repr(u16)]
+// chains are behind cargo features, so not every architecture is available
+pub fn new_boxed_chain(network: Network, plugins: &Plugins) -> Result<BoxedChain> {
+	let plugin_modules = plugin::get_modules(plugins, &network)?;
+
+	Ok(match network.architecture {
+		#[cfg(feature = "bitcoin")]
+		Architecture::Bitcoin => Box::new(Bitcoin::new(network).with_plugin_modules(plugin_modules)),
+		#[cfg(feature = "evm")]
+		Architecture::Evm => Box::new(Evm::new(network).with_plugin_modules(plugin_modules)),
+		#[cfg(feature = "solana")]
+		Architecture::Solana => Box::new(Solana::new(network).with_plugin_modules(plugin_modules)),
+		#[cfg(feature = "tron")]
+		Architecture::Tron => Box::new(Tron::new(network).with_plugin_modules(plugin_modules)),
+		#[allow(unreachable_patterns)]
+		architecture => bail!("support for `{architecture:?}` was not compiled in"),
+	})
+}
+
+pub fn is_supported(architecture: Architecture) -> bool {
+	match architecture {
+		Architecture::Bitcoin => cfg!(feature = "bitcoin"),
+		Architecture::Evm => cfg!(feature = "evm"),
+		Architecture::Solana => cfg!(feature = "solana"),
+		Architecture::Tron => cfg!(feature = "tron"),
+	}
+}
+
+// entries of a token allowlist or denylist that aren't valid contract
+// addresses (lists are only applied to evm networks)
+pub fn get_invalid_token_addresses(
+	architecture: Architecture,
+	addresses: &[String],
+) -> Vec<String> {
+	addresses
+		.iter()
+		.filter(|address| match architecture {
+			#[cfg(feature = "evm")]
+			Architecture::Evm => !evm::is_valid_address(address),
+			_ => false,
+		})
+		.cloned()
+		.collect()
+}
+
+// max number of transactions processed concurrently within a single block
+pub const MAX_CONCURRENT_TRANSACTIONS: usize = 16;
+
 #[derive(Display, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ModuleId {
-	BitcoinCoinbase = 101,
-	BitcoinTransfer = 102,
-	BitcoinBalance = 103,
-	EvmTransfer = 201,
-	EvmBalance = 202,
-	EvmTokenTransfer = 203,
-	EvmTokenBalance = 204,
+	Genesis,
+	BitcoinCoinbase,
+	BitcoinTransfer,
+	BitcoinBalance,
+	BitcoinFeeTransfer,
+	EvmTransfer,
+	EvmBalance,
+	EvmTokenTransfer,
+	EvmTokenBalance,
+	EvmDecodedCall,
+	EvmUserOperation,
+	EvmFee,
+	EvmWithdrawal,
+	EvmFeeTransfer,
+	EvmStakingDeposit,
+	EvmApproval,
+	EvmSwap,
+	SolanaTransfer,
+	SolanaBalance,
+	TronTransfer,
+	TronBalance,
+	TronTokenTransfer,
+	TronTokenBalance,
+	#[display("Plugin{_0}")]
+	Plugin(u16),
+}
+
+// ids are stored in the warehouse, so they must never change
+impl From<ModuleId> for u16 {
+	fn from(module_id: ModuleId) -> Self {
+		match module_id {
+			ModuleId::Genesis => 1,
+			ModuleId::BitcoinCoinbase => 101,
+			ModuleId::BitcoinTransfer => 102,
+			ModuleId::BitcoinBalance => 103,
+			ModuleId::BitcoinFeeTransfer => 104,
+			ModuleId::EvmTransfer => 201,
+			ModuleId::EvmBalance => 202,
+			ModuleId::EvmTokenTransfer => 203,
+			ModuleId::EvmTokenBalance => 204,
+			ModuleId::EvmDecodedCall => 205,
+			ModuleId::EvmUserOperation => 206,
+			ModuleId::EvmFee => 207,
+			ModuleId::EvmWithdrawal => 208,
+			ModuleId::EvmFeeTransfer => 209,
+			ModuleId::EvmStakingDeposit => 210,
+			ModuleId::EvmApproval => 211,
+			ModuleId::EvmSwap => 212,
+			ModuleId::SolanaTransfer => 301,
+			ModuleId::SolanaBalance => 302,
+			ModuleId::TronTransfer => 401,
+			ModuleId::TronBalance => 402,
+			ModuleId::TronTokenTransfer => 403,
+			ModuleId::TronTokenBalance => 404,
+			ModuleId::Plugin(id) => id,
+		}
+	}
+}
+
+// identifies an extracted block and the one it builds on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHashes {
+	pub hash: String,
+	pub parent_hash: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenMetadata {
+	pub name: String,
+	pub symbol: String,
+	pub decimals: u16,
 }
 
 #[async_trait]
@@ -51,13 +190,61 @@ pub trait ChainTrait: Send + Sync {
 		modules: Vec<ModuleId>,
 	) -> Result<Option<WarehouseData>>;
 
-	async fn extract_block(&self, storage: Arc<Storage>, block_height: BlockHeight)
-		-> Result<bool>;
+	// returns `None` if the block doesn't exist (yet)
+	async fn extract_block(
+		&self,
+		storage: Arc<Storage>,
+		block_height: BlockHeight,
+	) -> Result<Option<BlockHashes>>;
+
+	async fn get_token_metadata(&self, _address: &str) -> Result<Option<TokenMetadata>> {
+		Ok(None)
+	}
+
+	async fn get_collection_name(&self, _address: &str) -> Result<Option<String>> {
+		Ok(None)
+	}
+
+	async fn get_nft_uri(&self, _address: &str, _token_id: &str) -> Result<Option<String>> {
+		Ok(None)
+	}
+
+	// hash of the canonical block at this height (if chain supports it)
+	async fn get_block_hash(&self, _block_height: BlockHeight) -> Result<Option<String>> {
+		Ok(None)
+	}
+
+	// text the producer put into the block at this height, eg: a mining pool's
+	// coinbase tag (if chain supports it)
+	async fn get_block_producer_tag(&self, _block_height: BlockHeight) -> Result<Option<String>> {
+		Ok(None)
+	}
+
+	// native balance of `address` as the node reports it, along with the height
+	// it's as of. nodes that can't look back report it at their tip (if chain
+	// supports it)
+	async fn get_balance(
+		&self,
+		_address: &str,
+		_block_height: BlockHeight,
+	) -> Result<Option<(BlockHeight, U256)>> {
+		Ok(None)
+	}
+
+	// uploaded contract abis, for chains that can decode calls
+	fn set_abis(&mut self, _abis: Vec<Abi>) {}
+
+	// bumped on every rpc request, for metrics
+	fn set_rpc_counter(&mut self, counter: Counter);
+	fn get_rpc_counter(&self) -> Option<Counter>;
 
 	async fn rate_limit(&self) {
 		if let Some(rate_limiter) = &self.get_rate_limiter() {
 			rate_limiter.until_ready().await;
 		}
+		if let Some(rpc_counter) = &self.get_rpc_counter() {
+			rpc_counter.fetch_add(1, Ordering::Relaxed);
+		}
 	}
 }
 
@@ -75,6 +262,18 @@ pub struct WarehouseData {
 	pub transfers: HashSet<Transfer>,
 	pub amounts: HashSet<Amount>,
 	pub links: HashSet<Link>,
+	pub decoded_calls: HashSet<DecodedCall>,
+	pub user_operations: HashSet<UserOperation>,
+	pub fees: HashSet<Fee>,
+	pub blocks: HashSet<Block>,
+	pub staking_deposits: HashSet<StakingDeposit>,
+	pub approvals: HashSet<Approval>,
+	// (network_id, contract address, token id) of transferred nfts; these are
+	// not warehouse records, only passed along for metadata resolution
+	pub nfts: HashSet<(PrimaryId, String, String)>,
+	// (network_id, recipient, pool address) of dex swaps, for annotating
+	// recipients; not warehouse records either
+	pub swaps: HashSet<(PrimaryId, String, String)>,
 }
 
 impl WarehouseData {
@@ -83,7 +282,15 @@ impl WarehouseData {
 	}
 
 	pub fn len(&self) -> usize {
-		self.transfers.len() + self.amounts.len() + self.links.len()
+		self.transfers.len() +
+			self.amounts.len() +
+			self.links.len() +
+			self.decoded_calls.len() +
+			self.user_operations.len() +
+			self.fees.len() +
+			self.blocks.len() +
+			self.staking_deposits.len() +
+			self.approvals.len()
 	}
 
 	pub fn is_empty(&self) -> bool {
@@ -136,6 +343,72 @@ impl WarehouseData {
 				}
 			});
 		}
+		if !self.decoded_calls.is_empty() {
+			set.spawn({
+				let w = warehouse.clone();
+				let d: Vec<_> = self.decoded_calls.clone().into_iter().collect();
+
+				async move {
+					w.insert(DecodedCallTable, &d).await?;
+					Ok::<_, eyre::Error>(())
+				}
+			});
+		}
+		if !self.user_operations.is_empty() {
+			set.spawn({
+				let w = warehouse.clone();
+				let u: Vec<_> = self.user_operations.clone().into_iter().collect();
+
+				async move {
+					w.insert(UserOperationTable, &u).await?;
+					Ok::<_, eyre::Error>(())
+				}
+			});
+		}
+		if !self.fees.is_empty() {
+			set.spawn({
+				let w = warehouse.clone();
+				let f: Vec<_> = self.fees.clone().into_iter().collect();
+
+				async move {
+					w.insert(FeeTable, &f).await?;
+					Ok::<_, eyre::Error>(())
+				}
+			});
+		}
+		if !self.blocks.is_empty() {
+			set.spawn({
+				let w = warehouse.clone();
+				let b: Vec<_> = self.blocks.clone().into_iter().collect();
+
+				async move {
+					w.insert(BlockTable, &b).await?;
+					Ok::<_, eyre::Error>(())
+				}
+			});
+		}
+		if !self.staking_deposits.is_empty() {
+			set.spawn({
+				let w = warehouse.clone();
+				let s: Vec<_> = self.staking_deposits.clone().into_iter().collect();
+
+				async move {
+					w.insert(StakingDepositTable, &s).await?;
+					Ok::<_, eyre::Error>(())
+				}
+			});
+		}
+		if !self.approvals.is_empty() {
+			set.spawn({
+				let w = warehouse.clone();
+				let a: Vec<_> = self.approvals.clone().into_iter().collect();
+
+				async move {
+					w.insert(ApprovalTable, &a).await?;
+					Ok::<_, eyre::Error>(())
+				}
+			});
+		}
 
 		while let Some(res) = set.join_next().await {
 			res??;
@@ -151,6 +424,128 @@ impl WarehouseData {
 		self.transfers.clear();
 		self.amounts.clear();
 		self.links.clear();
+		self.decoded_calls.clear();
+		self.user_operations.clear();
+		self.fees.clear();
+		self.blocks.clear();
+		self.staking_deposits.clear();
+		self.approvals.clear();
+		self.nfts.clear();
+		self.swaps.clear();
+	}
+}
+
+// on-disk format for data that could not be pushed to the warehouse in time
+#[derive(Serialize, Deserialize)]
+struct SpilledWarehouseData {
+	transfers: Vec<Transfer>,
+	amounts: Vec<Amount>,
+	links: Vec<Link>,
+	#[serde(default)]
+	decoded_calls: Vec<DecodedCall>,
+	#[serde(default)]
+	user_operations: Vec<UserOperation>,
+	#[serde(default)]
+	fees: Vec<Fee>,
+	#[serde(default)]
+	blocks: Vec<Block>,
+	#[serde(default)]
+	staking_deposits: Vec<StakingDeposit>,
+	#[serde(default)]
+	approvals: Vec<Approval>,
+}
+
+impl WarehouseData {
+	pub fn spill(&mut self, path: &Path) -> Result<()> {
+		fs::create_dir_all(path)?;
+
+		let data = SpilledWarehouseData {
+			transfers: self.transfers.drain().collect(),
+			amounts: self.amounts.drain().collect(),
+			links: self.links.drain().collect(),
+			decoded_calls: self.decoded_calls.drain().collect(),
+			user_operations: self.user_operations.drain().collect(),
+			fees: self.fees.drain().collect(),
+			blocks: self.blocks.drain().collect(),
+			staking_deposits: self.staking_deposits.drain().collect(),
+			approvals: self.approvals.drain().collect(),
+		};
+
+		// prefix with timestamp so files get replayed in order
+		let timestamp = utils::now().and_utc().timestamp_millis();
+		let file = path.join(format!("{timestamp}_{}.json", Uuid::new_v4()));
+
+		// write under a temporary name first, so a crash mid-write never
+		// leaves a partial file for `replay` to pick up
+		let tmp_file = file.with_extension("json.tmp");
+		fs::write(&tmp_file, serde_json::to_vec(&data)?)?;
+		fs::rename(tmp_file, file)?;
+
+		self.clear();
+		Ok(())
+	}
+
+	// total size of spilled files, in bytes
+	pub fn get_spilled_size(path: &Path) -> Result<u64> {
+		if !path.exists() {
+			return Ok(0);
+		}
+
+		// only files that are still to be replayed count
+		let mut ret = 0;
+		for entry in fs::read_dir(path)? {
+			let entry = entry?;
+			if entry.path().extension().is_some_and(|ext| ext == "json") {
+				ret += entry.metadata()?.len();
+			}
+		}
+
+		Ok(ret)
+	}
+
+	pub async fn replay(warehouse: Arc<Warehouse>, path: &Path) -> Result<()> {
+		if !path.exists() {
+			return Ok(());
+		}
+
+		let mut files = fs::read_dir(path)?
+			.filter_map(|entry| entry.ok().map(|e| e.path()))
+			.filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+			.collect::<Vec<_>>();
+		files.sort();
+
+		for file in files.into_iter() {
+			// @NOTE a file that can't be parsed would block every later push, so
+			// it's moved aside (and kept around for inspection) instead
+			let data: SpilledWarehouseData = match serde_json::from_slice(&fs::read(&file)?) {
+				Ok(data) => data,
+				Err(e) => {
+					warn!(
+						file = file.display().to_string(),
+						error = e.to_string(),
+						"Skipping corrupt spill file"
+					);
+					fs::rename(&file, file.with_extension("json.corrupt"))?;
+					continue;
+				}
+			};
+
+			let mut warehouse_data = WarehouseData::new();
+			warehouse_data.transfers.extend(data.transfers);
+			warehouse_data.amounts.extend(data.amounts);
+			warehouse_data.links.extend(data.links);
+			warehouse_data.decoded_calls.extend(data.decoded_calls);
+			warehouse_data.user_operations.extend(data.user_operations);
+			warehouse_data.fees.extend(data.fees);
+			warehouse_data.blocks.extend(data.blocks);
+			warehouse_data.staking_deposits.extend(data.staking_deposits);
+			warehouse_data.approvals.extend(data.approvals);
+			warehouse_data.commit(warehouse.clone()).await?;
+
+			fs::remove_file(file)?;
+		}
+
+		Ok(())
 	}
 }
 
@@ -159,5 +554,13 @@ impl AddAssign for WarehouseData {
 		self.transfers.extend(rhs.transfers);
 		self.amounts.extend(rhs.amounts);
 		self.links.extend(rhs.links);
+		self.decoded_calls.extend(rhs.decoded_calls);
+		self.user_operations.extend(rhs.user_operations);
+		self.fees.extend(rhs.fees);
+		self.blocks.extend(rhs.blocks);
+		self.staking_deposits.extend(rhs.staking_deposits);
+		self.approvals.extend(rhs.approvals);
+		self.nfts.extend(rhs.nfts);
+		self.swaps.extend(rhs.swaps);
 	}
 }
@@ -0,0 +1,89 @@
+use eyre::{bail, Result};
+use std::{collections::HashSet, sync::Arc};
+
+#[cfg(feature = "bitcoin")]
+use crate::chain::bitcoin::BitcoinModuleTrait;
+#[cfg(feature = "evm")]
+use crate::chain::evm::EvmModuleTrait;
+#[cfg(feature = "solana")]
+use crate::chain::solana::SolanaModuleTrait;
+#[cfg(feature = "tron")]
+use crate::chain::tron::TronModuleTrait;
+use crate::{chain::ModuleId, models::Network};
+
+// plugin module ids start here, so they never collide with built-in modules
+pub const MIN_PLUGIN_MODULE_ID: u16 = 1_000;
+
+pub type Plugins = Vec<Arc<dyn PluginTrait>>;
+
+pub enum PluginModule {
+	#[cfg(feature = "bitcoin")]
+	Bitcoin(Box<dyn BitcoinModuleTrait>),
+	#[cfg(feature = "evm")]
+	Evm(Box<dyn EvmModuleTrait>),
+	#[cfg(feature = "solana")]
+	Solana(Box<dyn SolanaModuleTrait>),
+	#[cfg(feature = "tron")]
+	Tron(Box<dyn TronModuleTrait>),
+}
+
+impl PluginModule {
+	pub fn get_id(&self) -> ModuleId {
+		match self {
+			#[cfg(feature = "bitcoin")]
+			PluginModule::Bitcoin(module) => module.get_id(),
+			#[cfg(feature = "evm")]
+			PluginModule::Evm(module) => module.get_id(),
+			#[cfg(feature = "solana")]
+			PluginModule::Solana(module) => module.get_id(),
+			#[cfg(feature = "tron")]
+			PluginModule::Tron(module) => module.get_id(),
+		}
+	}
+}
+
+// @NOTE plugins add custom processing modules without forking barreleye.
+// they're registered with `BarreleyeBuilder::plugin()` and enabled by name
+// with `--plugins`. whenever a network is connected, every enabled plugin is
+// asked for its modules, which then run alongside the built-in ones and have
+// their own per-module sync (so adding a plugin later backfills its data)
+pub trait PluginTrait: Send + Sync {
+	fn get_name(&self) -> String;
+
+	// modules to run for this network; modules whose architecture doesn't
+	// match the network's are ignored
+	fn get_modules(&self, network: &Network) -> Vec<PluginModule>;
+}
+
+// filters registered plugins down to the ones named in settings (all of them
+// if none are named)
+pub fn get_enabled(registered: Plugins, names: &[String]) -> Result<Plugins> {
+	if names.is_empty() {
+		return Ok(registered);
+	}
+
+	for name in names.iter() {
+		if !registered.iter().any(|p| p.get_name() == *name) {
+			bail!("plugin `{name}` is not registered");
+		}
+	}
+
+	Ok(registered.into_iter().filter(|p| names.contains(&p.get_name())).collect())
+}
+
+pub fn get_modules(plugins: &Plugins, network: &Network) -> Result<Vec<PluginModule>> {
+	let modules = plugins.iter().flat_map(|p| p.get_modules(network)).collect::<Vec<_>>();
+
+	let mut ids = HashSet::new();
+	for module in modules.iter() {
+		match module.get_id() {
+			ModuleId::Plugin(id) if id >= MIN_PLUGIN_MODULE_ID && ids.insert(id) => {}
+			module_id => bail!(
+				"plugin module id {} must be unique and at least {MIN_PLUGIN_MODULE_ID}",
+				u16::from(module_id)
+			),
+		}
+	}
+
+	Ok(modules)
+}
@@ -0,0 +1,251 @@
+use derive_more::{Display, Error};
+use eyre::Result;
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::time::{sleep, Duration};
+
+// source: `https://github.com/anza-xyz/agave/blob/master/rpc-client-api/src/custom_error.rs`
+const RPC_BLOCK_NOT_AVAILABLE: i64 = -32004;
+const RPC_SLOT_SKIPPED: i64 = -32007;
+const RPC_LONG_TERM_STORAGE_SLOT_SKIPPED: i64 = -32009;
+
+const RETRY_ATTEMPTS: u32 = 13;
+const RPC_TIMEOUT: u64 = 250;
+
+// `processed` blocks can still be dropped, and `getBlock` doesn't take it
+const COMMITMENT: &str = "confirmed";
+
+#[derive(Debug, Display, Error)]
+pub enum ClientError {
+	#[display("{message}")]
+	General { message: String },
+	#[display("Could not connect to rpc endpoint")]
+	Connection,
+	#[display("RPC error: {message}")]
+	Rpc { code: i64, message: String },
+	#[display("Nonce mismatch")]
+	NonceMismatch,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+	code: i64,
+	message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+	result: Option<JsonValue>,
+	error: Option<RpcError>,
+	id: Option<String>,
+}
+
+// what's at a slot: leaders don't always produce a block for theirs
+pub enum SlotBlock {
+	Produced(Box<Block>),
+	Skipped,
+	Unavailable,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+	pub blockhash: String,
+	pub previous_blockhash: String,
+	pub parent_slot: u64,
+	pub block_height: Option<u64>,
+	pub block_time: Option<i64>,
+	#[serde(default)]
+	pub transactions: Vec<TransactionWithMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionWithMeta {
+	pub transaction: Transaction,
+	pub meta: Option<Meta>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Transaction {
+	pub signatures: Vec<String>,
+	pub message: Message,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+	pub account_keys: Vec<String>,
+	pub instructions: Vec<Instruction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Instruction {
+	pub program_id_index: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Meta {
+	pub err: Option<JsonValue>,
+	pub fee: u64,
+	pub pre_balances: Vec<u64>,
+	pub post_balances: Vec<u64>,
+	#[serde(default)]
+	pub pre_token_balances: Option<Vec<TokenBalance>>,
+	#[serde(default)]
+	pub post_token_balances: Option<Vec<TokenBalance>>,
+	#[serde(default)]
+	pub loaded_addresses: Option<LoadedAddresses>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalance {
+	pub account_index: usize,
+	pub mint: String,
+	pub owner: Option<String>,
+	pub ui_token_amount: UiTokenAmount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UiTokenAmount {
+	pub amount: String,
+	pub decimals: u8,
+}
+
+// accounts that versioned transactions pull in from lookup tables
+#[derive(Debug, Default, Deserialize)]
+pub struct LoadedAddresses {
+	pub writable: Vec<String>,
+	pub readonly: Vec<String>,
+}
+
+impl TransactionWithMeta {
+	// static keys, then writable and readonly lookup table accounts; balances
+	// are listed in this order
+	pub fn get_account_keys(&self) -> Vec<String> {
+		let mut ret = self.transaction.message.account_keys.clone();
+
+		if let Some(loaded_addresses) = self.meta.as_ref().and_then(|m| m.loaded_addresses.as_ref())
+		{
+			ret.extend(loaded_addresses.writable.iter().cloned());
+			ret.extend(loaded_addresses.readonly.iter().cloned());
+		}
+
+		ret
+	}
+}
+
+pub struct Client {
+	url: String,
+	id: AtomicUsize,
+	with_retry: bool,
+}
+
+impl Client {
+	pub fn new(url: &str) -> Self {
+		Self { url: url.to_string(), id: AtomicUsize::new(1), with_retry: true }
+	}
+
+	pub fn new_without_retry(url: &str) -> Self {
+		Self { url: url.to_string(), id: AtomicUsize::new(1), with_retry: false }
+	}
+
+	pub async fn get_slot(&self) -> Result<u64> {
+		let result = self.request("getSlot", &[json!({ "commitment": COMMITMENT })]).await?;
+		Ok(serde_json::from_value(result)?)
+	}
+
+	pub async fn get_block(&self, slot: u64) -> Result<SlotBlock> {
+		self.fetch_block(slot, "full").await
+	}
+
+	// hashes and times only, without transactions
+	pub async fn get_block_header(&self, slot: u64) -> Result<SlotBlock> {
+		self.fetch_block(slot, "none").await
+	}
+
+	async fn fetch_block(&self, slot: u64, transaction_details: &str) -> Result<SlotBlock> {
+		let params = [
+			JsonValue::from(slot),
+			json!({
+				"commitment": COMMITMENT,
+				"encoding": "json",
+				"transactionDetails": transaction_details,
+				"maxSupportedTransactionVersion": 0,
+				"rewards": false,
+			}),
+		];
+
+		match self.request("getBlock", &params).await {
+			Ok(JsonValue::Null) => Ok(SlotBlock::Unavailable),
+			Ok(result) => Ok(SlotBlock::Produced(Box::new(serde_json::from_value(result)?))),
+			Err(e) => match e.downcast_ref::<ClientError>() {
+				Some(ClientError::Rpc { code, .. })
+					if [RPC_SLOT_SKIPPED, RPC_LONG_TERM_STORAGE_SLOT_SKIPPED].contains(code) =>
+				{
+					Ok(SlotBlock::Skipped)
+				}
+				Some(ClientError::Rpc { code, .. }) if *code == RPC_BLOCK_NOT_AVAILABLE => {
+					Ok(SlotBlock::Unavailable)
+				}
+				_ => Err(e),
+			},
+		}
+	}
+
+	// first slot from `start_slot` on that has a block
+	pub async fn get_next_produced_slot(&self, start_slot: u64) -> Result<Option<u64>> {
+		let params = [JsonValue::from(start_slot), 1.into(), json!({ "commitment": COMMITMENT })];
+		let result = self.request("getBlocksWithLimit", &params).await?;
+		Ok(serde_json::from_value::<Vec<u64>>(result)?.into_iter().next())
+	}
+
+	async fn request(&self, method: &str, params: &[JsonValue]) -> Result<JsonValue> {
+		let client = reqwest::Client::new();
+		let req = client.post(&self.url);
+
+		let retry_attempts = if self.with_retry { RETRY_ATTEMPTS } else { 1 };
+
+		for attempt in 0..retry_attempts {
+			let id = self.id.fetch_add(1, Ordering::Relaxed).to_string();
+			let timeout = Duration::from_millis(RPC_TIMEOUT * 2_i32.pow(attempt) as u64);
+
+			let body = json!({
+				"jsonrpc": "2.0",
+				"method": method,
+				"params": params,
+				"id": id,
+			});
+
+			match req.try_clone().unwrap().json(&body).send().await {
+				// public endpoints rate limit aggressively
+				Ok(response) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+					sleep(timeout).await;
+					continue;
+				}
+				Ok(response) => {
+					let json = response.json::<Response>().await?;
+					match json.error {
+						Some(RpcError { code, message }) => {
+							return Err(ClientError::Rpc { code, message }.into())
+						}
+						None if json.id.is_none() || json.id.unwrap() != id => {
+							return Err(ClientError::NonceMismatch.into())
+						}
+						None => return Ok(json.result.unwrap_or_default()),
+					}
+				}
+				Err(e) if e.is_connect() => {
+					sleep(timeout).await;
+					continue;
+				}
+				Err(e) => return Err(ClientError::General { message: e.to_string() }.into()),
+			}
+		}
+
+		Err(ClientError::Connection.into())
+	}
+}
@@ -0,0 +1,429 @@
+use async_trait::async_trait;
+use eyre::Result;
+use futures::future;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::Semaphore;
+
+use crate::{
+	chain::{
+		BlockHashes, ChainTrait, ModuleId, ModuleTrait, PluginModule, WarehouseData,
+		MAX_CONCURRENT_TRANSACTIONS,
+	},
+	metrics::Counter,
+	models::Network,
+	utils, BlockHeight, RateLimiter, Storage,
+};
+use client::{Client, SlotBlock, TransactionWithMeta};
+pub use modules::{Holding, SolanaModuleTrait};
+use modules::{SolanaBalance, SolanaTransfer};
+use schema::{
+	Balance as ParquetBalance, Block as ParquetBlock, ParquetFile,
+	TokenBalance as ParquetTokenBalance, Transaction as ParquetTransaction,
+};
+
+mod client;
+mod modules;
+mod schema;
+
+static VOTE_PROGRAM: &str = "Vote111111111111111111111111111111111111111";
+
+pub struct Solana {
+	network: Network,
+	rpc: Option<String>,
+	client: Option<Arc<Client>>,
+	rate_limiter: Option<Arc<RateLimiter>>,
+	rpc_counter: Option<Counter>,
+	modules: Vec<Box<dyn SolanaModuleTrait>>,
+}
+
+impl Solana {
+	pub fn new(network: Network) -> Self {
+		let rps = network.rps as u32;
+		let network_id = network.network_id;
+
+		Self {
+			network,
+			rpc: None,
+			client: None,
+			rate_limiter: utils::get_rate_limiter(rps),
+			rpc_counter: None,
+			modules: vec![
+				Box::new(SolanaTransfer::new(network_id)),
+				Box::new(SolanaBalance::new(network_id)),
+			],
+		}
+	}
+
+	pub fn with_plugin_modules(mut self, plugin_modules: Vec<PluginModule>) -> Self {
+		for plugin_module in plugin_modules.into_iter() {
+			#[allow(irrefutable_let_patterns)]
+			if let PluginModule::Solana(module) = plugin_module {
+				self.modules.push(module);
+			}
+		}
+
+		self
+	}
+}
+
+#[async_trait]
+impl ChainTrait for Solana {
+	async fn connect(&mut self) -> Result<bool> {
+		for rpc_endpoint in self.network.get_rpc_endpoints().into_iter() {
+			if let Some(rate_limiter) = &self.rate_limiter {
+				rate_limiter.until_ready().await;
+			}
+
+			let client = Client::new_without_retry(&rpc_endpoint);
+			if client.get_slot().await.is_ok() {
+				self.client = Some(Arc::new(Client::new(&rpc_endpoint)));
+				self.rpc = Some(rpc_endpoint);
+				break;
+			}
+		}
+
+		Ok(self.is_connected())
+	}
+
+	fn is_connected(&self) -> bool {
+		self.client.is_some()
+	}
+
+	fn get_network(&self) -> Network {
+		self.network.clone()
+	}
+
+	fn get_rpc(&self) -> Option<String> {
+		self.rpc.clone()
+	}
+
+	fn get_module_ids(&self) -> Vec<ModuleId> {
+		self.modules.iter().map(|m| m.get_id()).collect()
+	}
+
+	fn get_rate_limiter(&self) -> Option<Arc<RateLimiter>> {
+		self.rate_limiter.clone()
+	}
+
+	fn set_rpc_counter(&mut self, counter: Counter) {
+		self.rpc_counter = Some(counter);
+	}
+
+	fn get_rpc_counter(&self) -> Option<Counter> {
+		self.rpc_counter.clone()
+	}
+
+	// base58 is case-sensitive, so there's nothing to normalize
+	fn format_address(&self, address: &str) -> String {
+		address.trim().to_string()
+	}
+
+	// @NOTE block heights are slots here (`blockHeight` skips empty slots, and
+	// rpc methods are keyed by slot)
+	async fn get_block_height(&self) -> Result<BlockHeight> {
+		self.rate_limit().await;
+		self.client.as_ref().unwrap().get_slot().await
+	}
+
+	async fn get_block_hash(&self, block_height: BlockHeight) -> Result<Option<String>> {
+		self.rate_limit().await;
+		Ok(match self.client.as_ref().unwrap().get_block_header(block_height).await? {
+			SlotBlock::Produced(block) => Some(block.blockhash),
+			_ => None,
+		})
+	}
+
+	async fn process_block(
+		&self,
+		storage: Arc<Storage>,
+		block_height: BlockHeight,
+		module_ids: Vec<ModuleId>,
+	) -> Result<Option<WarehouseData>> {
+		let mut warehouse_data = WarehouseData::new();
+		let storage_db = storage.get(self.network.network_id, block_height)?;
+
+		let block = match ParquetBlock::get(&storage_db)? {
+			Some(block) if block.is_skipped => return Ok(Some(warehouse_data)),
+			Some(block) => block,
+			_ => return Ok(None),
+		};
+		let block_time = block.block_time.unwrap_or_default();
+
+		// group balance changes by their tx
+		let mut all_tx_balances = HashMap::<_, Vec<ParquetBalance>>::new();
+		for balance in ParquetBalance::get_all(&storage_db)?.into_iter() {
+			all_tx_balances.entry(balance.signature.clone()).or_default().push(balance);
+		}
+		let mut all_tx_token_balances = HashMap::<_, Vec<ParquetTokenBalance>>::new();
+		for token_balance in ParquetTokenBalance::get_all(&storage_db)?.into_iter() {
+			all_tx_token_balances
+				.entry(token_balance.signature.clone())
+				.or_default()
+				.push(token_balance);
+		}
+
+		// process txs concurrently
+		let semaphore = &Semaphore::new(MAX_CONCURRENT_TRANSACTIONS);
+		let futures = ParquetTransaction::get_all(&storage_db)?.into_iter().map(|tx| {
+			let changes = get_changes(
+				&all_tx_balances.remove(&tx.signature).unwrap_or_default(),
+				&all_tx_token_balances.remove(&tx.signature).unwrap_or_default(),
+			);
+			let module_ids = module_ids.clone();
+
+			async move {
+				let _permit = semaphore.acquire().await?;
+
+				let mut ret = WarehouseData::new();
+				for module in self.modules.iter().filter(|m| module_ids.contains(&m.get_id())) {
+					ret +=
+						module.run(block_height, block_time, tx.clone(), changes.clone()).await?;
+				}
+
+				Ok::<_, eyre::Error>(ret)
+			}
+		});
+
+		for tx_warehouse_data in future::try_join_all(futures).await?.into_iter() {
+			warehouse_data += tx_warehouse_data;
+		}
+
+		Ok(Some(warehouse_data))
+	}
+
+	async fn extract_block(
+		&self,
+		storage: Arc<Storage>,
+		block_height: BlockHeight,
+	) -> Result<Option<BlockHashes>> {
+		let mut ret = None;
+		let storage_db = storage.get(self.network.network_id, block_height)?;
+		let client = self.client.as_ref().unwrap();
+
+		// already extracted (eg: by a chunk worker before it restarted)
+		if let Some(block_hashes) = storage_db.get_manifest()? {
+			return Ok(Some(block_hashes));
+		}
+
+		self.rate_limit().await;
+		match client.get_block(block_height).await? {
+			SlotBlock::Produced(block) => {
+				ret = Some(BlockHashes {
+					hash: block.blockhash.clone(),
+					parent_hash: block.previous_blockhash.clone(),
+				});
+
+				storage_db.insert(ParquetBlock {
+					slot: block_height,
+					hash: block.blockhash,
+					parent_hash: block.previous_blockhash,
+					parent_slot: block.parent_slot,
+					block_height: block.block_height,
+					block_time: block.block_time.map(|t| t as u32),
+					is_skipped: false,
+				})?;
+
+				for (i, tx) in block.transactions.into_iter().enumerate() {
+					let Some(meta) = &tx.meta else {
+						continue;
+					};
+
+					// votes are most of the traffic, and only ever move fees
+					if is_vote(&tx) {
+						continue;
+					}
+
+					let account_keys = tx.get_account_keys();
+					let signature = tx.transaction.signatures.first().cloned().unwrap_or_default();
+
+					storage_db.insert(ParquetTransaction {
+						signature: signature.clone(),
+						transaction_index: i as u32,
+						fee_payer: account_keys.first().cloned().unwrap_or_default(),
+						fee: meta.fee,
+						is_successful: meta.err.is_none(),
+					})?;
+
+					// only balances that changed are kept
+					for (j, account) in account_keys.iter().enumerate() {
+						match (meta.pre_balances.get(j), meta.post_balances.get(j)) {
+							(Some(pre_balance), Some(post_balance))
+								if pre_balance != post_balance =>
+							{
+								storage_db.insert(ParquetBalance {
+									signature: signature.clone(),
+									account: account.clone(),
+									pre_balance: *pre_balance,
+									post_balance: *post_balance,
+								})?;
+							}
+							_ => {}
+						}
+					}
+
+					// token accounts can be opened or closed in the tx, in which
+					// case they're missing from one of the lists
+					let mut token_balances = HashMap::new();
+					for (token_balances_list, is_post) in [
+						(meta.pre_token_balances.clone().unwrap_or_default(), false),
+						(meta.post_token_balances.clone().unwrap_or_default(), true),
+					] {
+						for token_balance in token_balances_list.into_iter() {
+							let Some(account) = account_keys.get(token_balance.account_index)
+							else {
+								continue;
+							};
+							let amount = token_balance.ui_token_amount.amount.parse::<u64>()?;
+
+							let entry = token_balances.entry(account.clone()).or_insert(
+								ParquetTokenBalance {
+									signature: signature.clone(),
+									account: account.clone(),
+									owner: token_balance
+										.owner
+										.clone()
+										.unwrap_or_else(|| account.clone()),
+									mint: token_balance.mint.clone(),
+									decimals: token_balance.ui_token_amount.decimals,
+									pre_amount: 0,
+									post_amount: 0,
+								},
+							);
+							if is_post {
+								entry.post_amount = amount;
+							} else {
+								entry.pre_amount = amount;
+							}
+						}
+					}
+					for token_balance in token_balances.into_values() {
+						if token_balance.pre_amount != token_balance.post_amount {
+							storage_db.insert(token_balance)?;
+						}
+					}
+				}
+			}
+			SlotBlock::Skipped => {
+				// the hash is carried over from the last produced block, which the
+				// next produced one names as its parent
+				self.rate_limit().await;
+				if let Some(next_slot) = client.get_next_produced_slot(block_height + 1).await? {
+					self.rate_limit().await;
+					if let SlotBlock::Produced(next_block) =
+						client.get_block_header(next_slot).await?
+					{
+						ret = Some(BlockHashes {
+							hash: next_block.previous_blockhash.clone(),
+							parent_hash: next_block.previous_blockhash.clone(),
+						});
+
+						storage_db.insert(ParquetBlock {
+							slot: block_height,
+							hash: next_block.previous_blockhash.clone(),
+							parent_hash: next_block.previous_blockhash,
+							parent_slot: next_block.parent_slot,
+							block_height: None,
+							block_time: None,
+							is_skipped: true,
+						})?;
+					}
+				}
+			}
+			SlotBlock::Unavailable => {}
+		}
+
+		let files = vec![
+			ParquetFile::Blocks.to_string(),
+			ParquetFile::Transactions.to_string(),
+			ParquetFile::Balances.to_string(),
+			ParquetFile::TokenBalances.to_string(),
+		];
+
+		storage_db.commit(files.clone())?;
+		if let Some(block_hashes) = &ret {
+			storage_db.save_manifest(&files, block_hashes)?;
+		}
+
+		Ok(ret)
+	}
+}
+
+// @NOTE what each holding gained and spent in a tx, from balances before and
+// after. token accounts count towards their owner, so moving tokens between
+// accounts of the same wallet nets out
+fn get_changes(
+	balances: &[ParquetBalance],
+	token_balances: &[ParquetTokenBalance],
+) -> HashMap<Holding, (u64, u64)> {
+	let mut ret = HashMap::<Holding, (u64, u64)>::new();
+
+	let mut add = |holding: Holding, pre: u64, post: u64| {
+		let entry = ret.entry(holding).or_default();
+		if post > pre {
+			entry.0 += post - pre;
+		} else {
+			entry.1 += pre - post;
+		}
+	};
+
+	for balance in balances.iter() {
+		add((balance.account.clone(), None), balance.pre_balance, balance.post_balance);
+	}
+	for token_balance in token_balances.iter() {
+		add(
+			(token_balance.owner.clone(), Some(token_balance.mint.clone())),
+			token_balance.pre_amount,
+			token_balance.post_amount,
+		);
+	}
+
+	ret
+}
+
+// only calls the vote program (validators voting on forks)
+fn is_vote(tx: &TransactionWithMeta) -> bool {
+	let account_keys = &tx.transaction.message.account_keys;
+
+	!tx.transaction.message.instructions.is_empty() &&
+		tx.transaction.message.instructions.iter().all(|instruction| {
+			account_keys.get(instruction.program_id_index).map(|k| k.as_str()) == Some(VOTE_PROGRAM)
+		})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_get_changes() {
+		let balances = vec![
+			ParquetBalance {
+				signature: "sig".to_string(),
+				account: "a".to_string(),
+				pre_balance: 100,
+				post_balance: 40,
+			},
+			ParquetBalance {
+				signature: "sig".to_string(),
+				account: "b".to_string(),
+				pre_balance: 0,
+				post_balance: 55,
+			},
+		];
+		let token_balance = |account: &str, pre_amount, post_amount| ParquetTokenBalance {
+			signature: "sig".to_string(),
+			account: account.to_string(),
+			owner: "a".to_string(),
+			mint: "mint".to_string(),
+			decimals: 6,
+			pre_amount,
+			post_amount,
+		};
+		let token_balances = vec![token_balance("ta1", 10, 0), token_balance("ta2", 0, 10)];
+
+		let changes = get_changes(&balances, &token_balances);
+		assert_eq!(changes[&("a".to_string(), None)], (0, 60));
+		assert_eq!(changes[&("b".to_string(), None)], (55, 0));
+		assert_eq!(changes[&("a".to_string(), Some("mint".to_string()))], (10, 10));
+	}
+}
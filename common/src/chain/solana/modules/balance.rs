@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use eyre::Result;
+use std::collections::HashMap;
+
+use crate::{
+	chain::{
+		solana::{
+			modules::{Holding, SolanaModuleTrait},
+			schema::Transaction as ParquetTransaction,
+		},
+		ModuleId, ModuleTrait, WarehouseData, U256,
+	},
+	models::{Amount, PrimaryId},
+	BlockHeight,
+};
+
+pub struct SolanaBalance {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for SolanaBalance {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::SolanaBalance
+	}
+}
+
+#[async_trait]
+impl SolanaModuleTrait for SolanaBalance {
+	async fn run(
+		&self,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: ParquetTransaction,
+		changes: HashMap<Holding, (u64, u64)>,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+
+		// fees are part of the fee payer's change, failed transactions included
+		for ((address, mint), (amount_in, amount_out)) in changes.into_iter() {
+			if amount_in == 0 && amount_out == 0 {
+				continue;
+			}
+
+			ret.amounts.insert(Amount::new(
+				self.get_id(),
+				self.network_id,
+				block_height,
+				&tx.signature,
+				&address,
+				mint,
+				U256::from(amount_in),
+				U256::from(amount_out),
+				block_time,
+			));
+		}
+
+		Ok(ret)
+	}
+}
@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+use eyre::Result;
+use std::collections::HashMap;
+
+use crate::{
+	chain::{solana::schema::Transaction as ParquetTransaction, ModuleTrait, WarehouseData},
+	BlockHeight,
+};
+pub use balance::SolanaBalance;
+pub use transfer::SolanaTransfer;
+
+mod balance;
+mod transfer;
+
+// an address and the mint of what it holds (`None` for native sol)
+pub type Holding = (String, Option<String>);
+
+#[async_trait]
+pub trait SolanaModuleTrait: ModuleTrait + Send + Sync {
+	// `changes` is what each holding gained and spent in the transaction
+	async fn run(
+		&self,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: ParquetTransaction,
+		changes: HashMap<Holding, (u64, u64)>,
+	) -> Result<WarehouseData>;
+}
@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+use eyre::Result;
+use std::collections::HashMap;
+
+use crate::{
+	chain::{
+		solana::{
+			modules::{Holding, SolanaModuleTrait},
+			schema::Transaction as ParquetTransaction,
+		},
+		ModuleId, ModuleTrait, WarehouseData, U256,
+	},
+	models::{PrimaryId, Transfer},
+	BlockHeight,
+};
+
+pub struct SolanaTransfer {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for SolanaTransfer {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::SolanaTransfer
+	}
+}
+
+#[async_trait]
+impl SolanaModuleTrait for SolanaTransfer {
+	async fn run(
+		&self,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: ParquetTransaction,
+		changes: HashMap<Holding, (u64, u64)>,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+
+		if !tx.is_successful {
+			return Ok(ret);
+		}
+
+		// @NOTE instructions aren't decoded, so transfers come from balance
+		// changes instead: per asset, whatever senders lost is split between
+		// receivers proportionally (the same way bitcoin inputs are). the fee
+		// isn't a transfer, so it's taken out of the fee payer's change
+		let mut assets = HashMap::<Option<String>, (Vec<(String, u64)>, Vec<(String, u64)>)>::new();
+		for ((address, mint), (amount_in, amount_out)) in changes.into_iter() {
+			let mut change = amount_in as i128 - amount_out as i128;
+			if mint.is_none() && address == tx.fee_payer {
+				change += tx.fee as i128;
+			}
+
+			let (senders, receivers) = assets.entry(mint).or_default();
+			match change {
+				c if c < 0 => senders.push((address, c.unsigned_abs() as u64)),
+				c if c > 0 => receivers.push((address, c as u64)),
+				_ => {}
+			}
+		}
+
+		for (mint, (senders, receivers)) in assets.into_iter() {
+			let sent_total: u64 = senders.iter().map(|(_, amount)| amount).sum();
+			let received_total: u64 = receivers.iter().map(|(_, amount)| amount).sum();
+			if sent_total == 0 {
+				continue;
+			}
+
+			for (from, sent) in senders.iter() {
+				for (to, received) in receivers.iter() {
+					let amount = ((*sent as f64 / sent_total as f64) * *received as f64).round();
+
+					ret.transfers.insert(Transfer::new(
+						self.get_id(),
+						self.network_id,
+						block_height,
+						&tx.signature,
+						0,
+						from,
+						to,
+						mint.clone(),
+						U256::from(amount as u64),
+						U256::from(received_total),
+						block_time,
+					));
+				}
+			}
+		}
+
+		Ok(ret)
+	}
+}
@@ -0,0 +1,63 @@
+use duckdb::{params, Appender, Connection};
+use eyre::Result;
+
+use super::{read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
+
+// native (lamport) balance of an account that changed in a transaction
+#[derive(Debug, Clone)]
+pub struct Balance {
+	pub signature: String,
+	pub account: String,
+	pub pre_balance: u64,
+	pub post_balance: u64,
+}
+
+impl Balance {
+	pub fn get_all(storage_db: &StorageDb) -> Result<Vec<Balance>> {
+		read_file(
+			storage_db,
+			ParquetFile::Balances,
+			"signature, account, pre_balance, post_balance",
+			|row| {
+				Ok(Balance {
+					signature: row.get(0)?,
+					account: row.get(1)?,
+					pre_balance: row.get(2)?,
+					post_balance: row.get(3)?,
+				})
+			},
+		)
+	}
+}
+
+impl StorageModelTrait for Balance {
+	fn get_table(&self) -> String {
+		ParquetFile::Balances.to_string()
+	}
+
+	fn create_table(&self, db: &Connection) -> Result<()> {
+		db.execute_batch(&format!(
+			r#"CREATE TABLE IF NOT EXISTS {} (
+                signature VARCHAR NOT NULL,
+                account VARCHAR NOT NULL,
+                pre_balance UINT64 NOT NULL,
+                post_balance UINT64 NOT NULL
+            );"#,
+			ParquetFile::Balances
+		))?;
+
+		Ok(())
+	}
+
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.signature,
+			self.account,
+			self.pre_balance,
+			self.post_balance,
+		])?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,79 @@
+use duckdb::{params, Appender, Connection};
+use eyre::Result;
+
+use super::{read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
+
+// @NOTE skipped slots (no block from their leader) get a row too, so their
+// manifest carries hashes: both are those of the last produced block, which
+// is what the next one builds on
+#[derive(Debug, Clone)]
+pub struct Block {
+	pub slot: u64,
+	pub hash: String,
+	pub parent_hash: String,
+	pub parent_slot: u64,
+	pub block_height: Option<u64>,
+	pub block_time: Option<u32>,
+	pub is_skipped: bool,
+}
+
+impl Block {
+	pub fn get(storage_db: &StorageDb) -> Result<Option<Block>> {
+		Ok(read_file(
+			storage_db,
+			ParquetFile::Blocks,
+			"slot, hash, parent_hash, parent_slot, block_height, block_time, is_skipped",
+			|row| {
+				Ok(Block {
+					slot: row.get(0)?,
+					hash: row.get(1)?,
+					parent_hash: row.get(2)?,
+					parent_slot: row.get(3)?,
+					block_height: row.get(4)?,
+					block_time: row.get(5)?,
+					is_skipped: row.get(6)?,
+				})
+			},
+		)?
+		.into_iter()
+		.next())
+	}
+}
+
+impl StorageModelTrait for Block {
+	fn get_table(&self) -> String {
+		ParquetFile::Blocks.to_string()
+	}
+
+	fn create_table(&self, db: &Connection) -> Result<()> {
+		db.execute_batch(&format!(
+			r#"CREATE TABLE IF NOT EXISTS {} (
+                slot UINT64 NOT NULL,
+                hash VARCHAR NOT NULL,
+                parent_hash VARCHAR NOT NULL,
+                parent_slot UINT64 NOT NULL,
+                block_height UINT64 NULL,
+                block_time UINT32 NULL,
+                is_skipped BOOLEAN NOT NULL
+            );"#,
+			ParquetFile::Blocks
+		))?;
+
+		Ok(())
+	}
+
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.slot,
+			self.hash,
+			self.parent_hash,
+			self.parent_slot,
+			self.block_height,
+			self.block_time,
+			self.is_skipped,
+		])?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,76 @@
+use duckdb::{params, Appender, Connection};
+use eyre::Result;
+
+use super::{read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
+
+// spl token account balance that changed in a transaction; `owner` is the
+// wallet behind the token account
+#[derive(Debug, Clone)]
+pub struct TokenBalance {
+	pub signature: String,
+	pub account: String,
+	pub owner: String,
+	pub mint: String,
+	pub decimals: u8,
+	pub pre_amount: u64,
+	pub post_amount: u64,
+}
+
+impl TokenBalance {
+	pub fn get_all(storage_db: &StorageDb) -> Result<Vec<TokenBalance>> {
+		read_file(
+			storage_db,
+			ParquetFile::TokenBalances,
+			"signature, account, owner, mint, decimals, pre_amount, post_amount",
+			|row| {
+				Ok(TokenBalance {
+					signature: row.get(0)?,
+					account: row.get(1)?,
+					owner: row.get(2)?,
+					mint: row.get(3)?,
+					decimals: row.get(4)?,
+					pre_amount: row.get(5)?,
+					post_amount: row.get(6)?,
+				})
+			},
+		)
+	}
+}
+
+impl StorageModelTrait for TokenBalance {
+	fn get_table(&self) -> String {
+		ParquetFile::TokenBalances.to_string()
+	}
+
+	fn create_table(&self, db: &Connection) -> Result<()> {
+		db.execute_batch(&format!(
+			r#"CREATE TABLE IF NOT EXISTS {} (
+                signature VARCHAR NOT NULL,
+                account VARCHAR NOT NULL,
+                owner VARCHAR NOT NULL,
+                mint VARCHAR NOT NULL,
+                decimals UINT8 NOT NULL,
+                pre_amount UINT64 NOT NULL,
+                post_amount UINT64 NOT NULL
+            );"#,
+			ParquetFile::TokenBalances
+		))?;
+
+		Ok(())
+	}
+
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.signature,
+			self.account,
+			self.owner,
+			self.mint,
+			self.decimals,
+			self.pre_amount,
+			self.post_amount,
+		])?;
+
+		Ok(())
+	}
+}
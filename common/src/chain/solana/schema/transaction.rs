@@ -0,0 +1,67 @@
+use duckdb::{params, Appender, Connection};
+use eyre::Result;
+
+use super::{read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
+
+#[derive(Debug, Clone)]
+pub struct Transaction {
+	pub signature: String,
+	pub transaction_index: u32,
+	pub fee_payer: String,
+	pub fee: u64,
+	// failed transactions only charge the fee
+	pub is_successful: bool,
+}
+
+impl Transaction {
+	pub fn get_all(storage_db: &StorageDb) -> Result<Vec<Transaction>> {
+		read_file(
+			storage_db,
+			ParquetFile::Transactions,
+			"signature, transaction_index, fee_payer, fee, is_successful",
+			|row| {
+				Ok(Transaction {
+					signature: row.get(0)?,
+					transaction_index: row.get(1)?,
+					fee_payer: row.get(2)?,
+					fee: row.get(3)?,
+					is_successful: row.get(4)?,
+				})
+			},
+		)
+	}
+}
+
+impl StorageModelTrait for Transaction {
+	fn get_table(&self) -> String {
+		ParquetFile::Transactions.to_string()
+	}
+
+	fn create_table(&self, db: &Connection) -> Result<()> {
+		db.execute_batch(&format!(
+			r#"CREATE TABLE IF NOT EXISTS {} (
+                signature VARCHAR NOT NULL,
+                transaction_index UINT32 NOT NULL,
+                fee_payer VARCHAR NOT NULL,
+                fee UINT64 NOT NULL,
+                is_successful BOOLEAN NOT NULL
+            );"#,
+			ParquetFile::Transactions
+		))?;
+
+		Ok(())
+	}
+
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.signature,
+			self.transaction_index,
+			self.fee_payer,
+			self.fee,
+			self.is_successful,
+		])?;
+
+		Ok(())
+	}
+}
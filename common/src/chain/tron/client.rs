@@ -0,0 +1,224 @@
+use derive_more::{Display, Error};
+use eyre::Result;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value as JsonValue};
+use tokio::time::{sleep, Duration};
+
+const RETRY_ATTEMPTS: u32 = 13;
+const RPC_TIMEOUT: u64 = 250;
+
+#[derive(Debug, Display, Error)]
+pub enum ClientError {
+	#[display("{message}")]
+	General { message: String },
+	#[display("Could not connect to rpc endpoint")]
+	Connection,
+}
+
+// @NOTE blocks come from `getblockbynum` with `visible` on, so addresses in
+// contract parameters are base58 already (eg: `TR7NHq...`)
+#[derive(Debug, Deserialize)]
+pub struct Block {
+	#[serde(rename = "blockID")]
+	pub block_id: String,
+	pub block_header: BlockHeader,
+	#[serde(default)]
+	pub transactions: Vec<Transaction>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BlockHeader {
+	pub raw_data: BlockRawData,
+}
+
+// protobuf's json mapping leaves out zero values, eg: the genesis block's
+// number
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockRawData {
+	#[serde(default)]
+	pub number: u64,
+	#[serde(default)]
+	pub timestamp: u64,
+	#[serde(default)]
+	pub parent_hash: String,
+	#[serde(default)]
+	pub witness_address: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Transaction {
+	#[serde(rename = "txID")]
+	pub tx_id: String,
+	#[serde(default)]
+	pub ret: Vec<TransactionResult>,
+	pub raw_data: TransactionRawData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionResult {
+	pub contract_ret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionRawData {
+	#[serde(default)]
+	pub contract: Vec<Contract>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Contract {
+	#[serde(rename = "type")]
+	pub contract_type: String,
+	pub parameter: ContractParameter,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContractParameter {
+	pub value: ContractValue,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ContractValue {
+	pub owner_address: Option<String>,
+	pub to_address: Option<String>,
+	pub amount: Option<u64>,
+	pub contract_address: Option<String>,
+	pub call_value: Option<u64>,
+}
+
+// log addresses are hex without the `41` prefix, and fees are in sun
+#[derive(Debug, Deserialize)]
+pub struct TransactionInfo {
+	pub id: String,
+	#[serde(default)]
+	pub fee: u64,
+	#[serde(default)]
+	pub log: Vec<Log>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Log {
+	pub address: String,
+	#[serde(default)]
+	pub topics: Vec<String>,
+	#[serde(default)]
+	pub data: String,
+}
+
+impl Transaction {
+	// a transaction has a single contract in practice
+	pub fn get_contract(&self) -> Option<&Contract> {
+		self.raw_data.contract.first()
+	}
+
+	pub fn is_successful(&self) -> bool {
+		self.ret.first().and_then(|r| r.contract_ret.as_deref()).unwrap_or("SUCCESS") == "SUCCESS"
+	}
+}
+
+pub struct Client {
+	url: String,
+	with_retry: bool,
+}
+
+impl Client {
+	pub fn new(url: &str) -> Self {
+		Self { url: url.trim_end_matches('/').to_string(), with_retry: true }
+	}
+
+	pub fn new_without_retry(url: &str) -> Self {
+		Self { url: url.trim_end_matches('/').to_string(), with_retry: false }
+	}
+
+	pub async fn get_block_height(&self) -> Result<u64> {
+		let block = self.request::<Block>("wallet/getnowblock", json!({})).await?;
+		Ok(block.block_header.raw_data.number)
+	}
+
+	// missing blocks come back as an empty object
+	pub async fn get_block(&self, block_height: u64) -> Result<Option<Block>> {
+		let result = self
+			.request::<JsonValue>(
+				"wallet/getblockbynum",
+				json!({ "num": block_height, "visible": true }),
+			)
+			.await?;
+
+		Ok(match result.get("blockID") {
+			Some(_) => Some(serde_json::from_value(result)?),
+			None => None,
+		})
+	}
+
+	// receipts of a block's transactions (fees and logs); empty blocks don't
+	// return a list
+	pub async fn get_transaction_infos(&self, block_height: u64) -> Result<Vec<TransactionInfo>> {
+		let result = self
+			.request::<JsonValue>(
+				"wallet/gettransactioninfobyblocknum",
+				json!({ "num": block_height }),
+			)
+			.await?;
+
+		Ok(match result {
+			JsonValue::Array(_) => serde_json::from_value(result)?,
+			_ => vec![],
+		})
+	}
+
+	// return data of a read-only contract call, eg: `decimals()`
+	pub async fn call_contract(&self, address: &str, function: &str) -> Result<Option<Vec<u8>>> {
+		let result = self
+			.request::<JsonValue>(
+				"wallet/triggerconstantcontract",
+				json!({
+					"owner_address": address,
+					"contract_address": address,
+					"function_selector": function,
+					"visible": true,
+				}),
+			)
+			.await?;
+
+		Ok(result
+			.get("constant_result")
+			.and_then(|r| r.get(0))
+			.and_then(|r| r.as_str())
+			.and_then(|r| hex::decode(r).ok())
+			.filter(|r| !r.is_empty()))
+	}
+
+	async fn request<T: DeserializeOwned>(&self, path: &str, body: JsonValue) -> Result<T> {
+		let client = reqwest::Client::new();
+		let req = client.post(format!("{}/{path}", self.url));
+
+		let retry_attempts = if self.with_retry { RETRY_ATTEMPTS } else { 1 };
+
+		for attempt in 0..retry_attempts {
+			let timeout = Duration::from_millis(RPC_TIMEOUT * 2_i32.pow(attempt) as u64);
+
+			match req.try_clone().unwrap().json(&body).send().await {
+				// public endpoints rate limit aggressively
+				Ok(response) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+					sleep(timeout).await;
+					continue;
+				}
+				Ok(response) if !response.status().is_success() => {
+					return Err(
+						ClientError::General { message: response.status().to_string() }.into()
+					)
+				}
+				Ok(response) => return Ok(response.json::<T>().await?),
+				Err(e) if e.is_connect() => {
+					sleep(timeout).await;
+					continue;
+				}
+				Err(e) => return Err(ClientError::General { message: e.to_string() }.into()),
+			}
+		}
+
+		Err(ClientError::Connection.into())
+	}
+}
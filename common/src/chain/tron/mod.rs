@@ -0,0 +1,371 @@
+use async_trait::async_trait;
+use base58::{FromBase58, ToBase58};
+use eyre::Result;
+use futures::future;
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::Semaphore;
+
+use crate::{
+	chain::{
+		BlockHashes, ChainTrait, ModuleId, ModuleTrait, PluginModule, TokenMetadata, WarehouseData,
+		MAX_CONCURRENT_TRANSACTIONS, U256,
+	},
+	metrics::Counter,
+	models::Network,
+	utils, BlockHeight, RateLimiter, Storage,
+};
+use client::Client;
+pub use modules::TronModuleTrait;
+use modules::{TronBalance, TronTokenBalance, TronTokenTransfer, TronTransfer};
+use schema::{
+	Block as ParquetBlock, Log as ParquetLog, ParquetFile, Transaction as ParquetTransaction,
+};
+
+mod client;
+mod modules;
+mod schema;
+
+// mainnet addresses are a version byte followed by the 20-byte evm-style one
+const ADDRESS_PREFIX: u8 = 0x41;
+
+static TRANSFER_FROM_TO_AMOUNT: &str =
+	"ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+pub struct Tron {
+	network: Network,
+	rpc: Option<String>,
+	client: Option<Arc<Client>>,
+	rate_limiter: Option<Arc<RateLimiter>>,
+	rpc_counter: Option<Counter>,
+	modules: Vec<Box<dyn TronModuleTrait>>,
+}
+
+impl Tron {
+	pub fn new(network: Network) -> Self {
+		let rps = network.rps as u32;
+		let network_id = network.network_id;
+
+		Self {
+			network,
+			rpc: None,
+			client: None,
+			rate_limiter: utils::get_rate_limiter(rps),
+			rpc_counter: None,
+			modules: vec![
+				Box::new(TronTransfer::new(network_id)),
+				Box::new(TronBalance::new(network_id)),
+				Box::new(TronTokenTransfer::new(network_id)),
+				Box::new(TronTokenBalance::new(network_id)),
+			],
+		}
+	}
+
+	pub fn with_plugin_modules(mut self, plugin_modules: Vec<PluginModule>) -> Self {
+		for plugin_module in plugin_modules.into_iter() {
+			#[allow(irrefutable_let_patterns)]
+			if let PluginModule::Tron(module) = plugin_module {
+				self.modules.push(module);
+			}
+		}
+
+		self
+	}
+
+	async fn call_contract(&self, address: &str, function: &str) -> Result<Option<Vec<u8>>> {
+		self.rate_limit().await;
+		self.client.as_ref().unwrap().call_contract(address, function).await
+	}
+}
+
+#[async_trait]
+impl ChainTrait for Tron {
+	async fn connect(&mut self) -> Result<bool> {
+		for rpc_endpoint in self.network.get_rpc_endpoints().into_iter() {
+			if let Some(rate_limiter) = &self.rate_limiter {
+				rate_limiter.until_ready().await;
+			}
+
+			let client = Client::new_without_retry(&rpc_endpoint);
+			if client.get_block_height().await.is_ok() {
+				self.client = Some(Arc::new(Client::new(&rpc_endpoint)));
+				self.rpc = Some(rpc_endpoint);
+				break;
+			}
+		}
+
+		Ok(self.is_connected())
+	}
+
+	fn is_connected(&self) -> bool {
+		self.client.is_some()
+	}
+
+	fn get_network(&self) -> Network {
+		self.network.clone()
+	}
+
+	fn get_rpc(&self) -> Option<String> {
+		self.rpc.clone()
+	}
+
+	fn get_module_ids(&self) -> Vec<ModuleId> {
+		self.modules.iter().map(|m| m.get_id()).collect()
+	}
+
+	fn get_rate_limiter(&self) -> Option<Arc<RateLimiter>> {
+		self.rate_limiter.clone()
+	}
+
+	fn set_rpc_counter(&mut self, counter: Counter) {
+		self.rpc_counter = Some(counter);
+	}
+
+	fn get_rpc_counter(&self) -> Option<Counter> {
+		self.rpc_counter.clone()
+	}
+
+	// hex addresses (`41...` or evm-style `0x...`) are stored as base58check
+	fn format_address(&self, address: &str) -> String {
+		let address = address.trim();
+		to_base58_address(address).unwrap_or_else(|| address.to_string())
+	}
+
+	async fn get_block_height(&self) -> Result<BlockHeight> {
+		self.rate_limit().await;
+		self.client.as_ref().unwrap().get_block_height().await
+	}
+
+	async fn get_block_hash(&self, block_height: BlockHeight) -> Result<Option<String>> {
+		self.rate_limit().await;
+		Ok(self.client.as_ref().unwrap().get_block(block_height).await?.map(|b| b.block_id))
+	}
+
+	async fn get_token_metadata(&self, address: &str) -> Result<Option<TokenMetadata>> {
+		let name = self.call_contract(address, "name()").await?.and_then(decode_string);
+		let symbol = self.call_contract(address, "symbol()").await?.and_then(decode_string);
+		let decimals = self
+			.call_contract(address, "decimals()")
+			.await?
+			.filter(|b| b.len() == 32)
+			.map(|b| U256::from_big_endian(&b).low_u32() as u16);
+
+		Ok(match (name, symbol, decimals) {
+			(Some(name), Some(symbol), Some(decimals)) => {
+				Some(TokenMetadata { name, symbol, decimals })
+			}
+			_ => None,
+		})
+	}
+
+	async fn process_block(
+		&self,
+		storage: Arc<Storage>,
+		block_height: BlockHeight,
+		module_ids: Vec<ModuleId>,
+	) -> Result<Option<WarehouseData>> {
+		let mut warehouse_data = WarehouseData::new();
+		let storage_db = storage.get(self.network.network_id, block_height)?;
+
+		let Some(block) = ParquetBlock::get(&storage_db)? else {
+			return Ok(None);
+		};
+
+		// group logs by their tx
+		let mut all_tx_logs = HashMap::<_, Vec<ParquetLog>>::new();
+		for log in ParquetLog::get_all(&storage_db)?.into_iter() {
+			all_tx_logs.entry(log.transaction_hash.clone()).or_default().push(log);
+		}
+
+		// process txs concurrently
+		let semaphore = &Semaphore::new(MAX_CONCURRENT_TRANSACTIONS);
+		let futures = ParquetTransaction::get_all(&storage_db)?.into_iter().map(|tx| {
+			let logs = all_tx_logs.remove(&tx.hash).unwrap_or_default();
+			let module_ids = module_ids.clone();
+
+			async move {
+				let _permit = semaphore.acquire().await?;
+
+				let mut ret = WarehouseData::new();
+				for module in self.modules.iter().filter(|m| module_ids.contains(&m.get_id())) {
+					ret += module
+						.run(block_height, block.block_time, tx.clone(), logs.clone())
+						.await?;
+				}
+
+				Ok::<_, eyre::Error>(ret)
+			}
+		});
+
+		for tx_warehouse_data in future::try_join_all(futures).await?.into_iter() {
+			warehouse_data += tx_warehouse_data;
+		}
+
+		Ok(Some(warehouse_data))
+	}
+
+	async fn extract_block(
+		&self,
+		storage: Arc<Storage>,
+		block_height: BlockHeight,
+	) -> Result<Option<BlockHashes>> {
+		let storage_db = storage.get(self.network.network_id, block_height)?;
+		let client = self.client.as_ref().unwrap();
+
+		// already extracted (eg: by a chunk worker before it restarted)
+		if let Some(block_hashes) = storage_db.get_manifest()? {
+			return Ok(Some(block_hashes));
+		}
+
+		self.rate_limit().await;
+		let Some(block) = client.get_block(block_height).await? else {
+			return Ok(None);
+		};
+
+		self.rate_limit().await;
+		let mut tx_infos = client
+			.get_transaction_infos(block_height)
+			.await?
+			.into_iter()
+			.map(|info| (info.id.clone(), info))
+			.collect::<HashMap<_, _>>();
+
+		let ret = BlockHashes {
+			hash: block.block_id.clone(),
+			parent_hash: block.block_header.raw_data.parent_hash.clone(),
+		};
+
+		storage_db.insert(ParquetBlock {
+			block_height,
+			hash: block.block_id,
+			parent_hash: block.block_header.raw_data.parent_hash,
+			block_time: (block.block_header.raw_data.timestamp / 1_000) as u32,
+			witness_address: block.block_header.raw_data.witness_address,
+		})?;
+
+		for (i, tx) in block.transactions.iter().enumerate() {
+			let Some(contract) = tx.get_contract() else {
+				continue;
+			};
+			let value = &contract.parameter.value;
+			let tx_info = tx_infos.remove(&tx.tx_id);
+
+			// contract calls can send trx along too
+			let (to_address, amount) = match contract.contract_type.as_str() {
+				"TransferContract" => (value.to_address.clone(), value.amount),
+				"TriggerSmartContract" => (value.contract_address.clone(), value.call_value),
+				_ => (None, None),
+			};
+
+			storage_db.insert(ParquetTransaction {
+				hash: tx.tx_id.clone(),
+				transaction_index: i as u32,
+				contract_type: contract.contract_type.clone(),
+				from_address: value.owner_address.clone().unwrap_or_default(),
+				to_address,
+				value: amount.unwrap_or_default(),
+				fee: tx_info.as_ref().map(|info| info.fee).unwrap_or_default(),
+				is_successful: tx.is_successful(),
+			})?;
+
+			for (j, log) in tx_info.map(|info| info.log).unwrap_or_default().into_iter().enumerate()
+			{
+				storage_db.insert(ParquetLog {
+					transaction_hash: tx.tx_id.clone(),
+					log_index: j as u32,
+					address: to_base58_address(&log.address).unwrap_or(log.address),
+					topics: log.topics,
+					data: log.data,
+				})?;
+			}
+		}
+
+		let files = vec![
+			ParquetFile::Blocks.to_string(),
+			ParquetFile::Transactions.to_string(),
+			ParquetFile::Logs.to_string(),
+		];
+
+		storage_db.commit(files.clone())?;
+		storage_db.save_manifest(&files, &ret)?;
+
+		Ok(Some(ret))
+	}
+}
+
+// @NOTE base58check of the address bytes with the `41` version prefix (eg:
+// `TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t`); base58 input is only validated.
+// anything that's not an address returns `None`
+pub fn to_base58_address(address: &str) -> Option<String> {
+	if let Ok(bytes) = address.from_base58() {
+		if bytes.len() == 25 && bytes[0] == ADDRESS_PREFIX && checksum(&bytes[..21]) == bytes[21..]
+		{
+			return Some(address.to_string());
+		}
+	}
+
+	let hex = address.strip_prefix("0x").unwrap_or(address);
+	let mut bytes = match hex::decode(hex).ok()? {
+		bytes if bytes.len() == 20 => [vec![ADDRESS_PREFIX], bytes].concat(),
+		bytes if bytes.len() == 21 && bytes[0] == ADDRESS_PREFIX => bytes,
+		_ => return None,
+	};
+
+	bytes.extend(checksum(&bytes));
+	Some(bytes.to_base58())
+}
+
+// first 4 bytes of a double sha256
+fn checksum(bytes: &[u8]) -> Vec<u8> {
+	Sha256::digest(Sha256::digest(bytes))[..4].to_vec()
+}
+
+// trc-20 `Transfer` events (erc-721 style ones have a fourth topic instead of
+// data); the first topics are 32 bytes, with the address in the last 20
+fn get_token_transfer(log: &ParquetLog) -> Option<(String, String, U256)> {
+	if log.topics.len() != 3 || log.topics[0] != TRANSFER_FROM_TO_AMOUNT {
+		return None;
+	}
+
+	let from = to_base58_address(log.topics[1].get(24..)?)?;
+	let to = to_base58_address(log.topics[2].get(24..)?)?;
+	let amount = U256::from_str_radix(&log.data, 16).ok().filter(|a| !a.is_zero())?;
+
+	Some((from, to, amount))
+}
+
+// strings are either abi-encoded or (in older tokens) a null-padded bytes32
+fn decode_string(bytes: Vec<u8>) -> Option<String> {
+	let ret = if bytes.len() >= 64 {
+		let length = U256::from_big_endian(&bytes[32..64]).low_u64() as usize;
+		bytes.get(64..64 + length).and_then(|b| String::from_utf8(b.to_vec()).ok())
+	} else if bytes.len() == 32 {
+		String::from_utf8(bytes.iter().take_while(|b| **b != 0).cloned().collect()).ok()
+	} else {
+		None
+	};
+
+	ret.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_to_base58_address() {
+		let usdt = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+
+		assert_eq!(to_base58_address(usdt), Some(usdt.to_string()));
+		assert_eq!(
+			to_base58_address("41a614f803b6fd780986a42c78ec9c7f77e6ded13c"),
+			Some(usdt.to_string())
+		);
+		assert_eq!(
+			to_base58_address("0xa614f803b6fd780986a42c78ec9c7f77e6ded13c"),
+			Some(usdt.to_string())
+		);
+		assert_eq!(to_base58_address("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6u"), None);
+		assert_eq!(to_base58_address("0x1234"), None);
+	}
+}
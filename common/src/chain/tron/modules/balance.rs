@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use eyre::Result;
+use std::collections::HashMap;
+
+use crate::{
+	chain::{
+		tron::{
+			modules::TronModuleTrait,
+			schema::{Log as ParquetLog, Transaction as ParquetTransaction},
+		},
+		ModuleId, ModuleTrait, WarehouseData, U256,
+	},
+	models::{Amount, PrimaryId},
+	BlockHeight,
+};
+
+pub struct TronBalance {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for TronBalance {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::TronBalance
+	}
+}
+
+#[async_trait]
+impl TronModuleTrait for TronBalance {
+	async fn run(
+		&self,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: ParquetTransaction,
+		_logs: Vec<ParquetLog>,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+
+		// @NOTE amounts are unique per (tx, address), so everything an address
+		// gains and spends in a tx has to be in the same record
+		let mut balance_map = HashMap::<String, (u64, u64)>::new();
+
+		if let Some(to) = tx
+			.to_address
+			.as_ref()
+			.filter(|to| tx.is_successful && tx.value > 0 && **to != tx.from_address)
+		{
+			balance_map.entry(tx.from_address.clone()).or_default().1 += tx.value;
+			balance_map.entry(to.clone()).or_default().0 += tx.value;
+		}
+
+		// fees are burnt (failed transactions included), unless they're paid
+		// with staked energy and bandwidth
+		balance_map.entry(tx.from_address.clone()).or_default().1 += tx.fee;
+
+		for (address, (amount_in, amount_out)) in balance_map.into_iter() {
+			if amount_in == 0 && amount_out == 0 {
+				continue;
+			}
+
+			ret.amounts.insert(Amount::new(
+				self.get_id(),
+				self.network_id,
+				block_height,
+				&tx.hash,
+				&address,
+				None,
+				U256::from(amount_in),
+				U256::from(amount_out),
+				block_time,
+			));
+		}
+
+		Ok(ret)
+	}
+}
@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use eyre::Result;
+
+use crate::{
+	chain::{
+		tron::schema::{Log as ParquetLog, Transaction as ParquetTransaction},
+		ModuleTrait, WarehouseData,
+	},
+	BlockHeight,
+};
+pub use balance::TronBalance;
+pub use token_balance::TronTokenBalance;
+pub use token_transfer::TronTokenTransfer;
+pub use transfer::TronTransfer;
+
+mod balance;
+mod token_balance;
+mod token_transfer;
+mod transfer;
+
+#[async_trait]
+pub trait TronModuleTrait: ModuleTrait + Send + Sync {
+	// `logs` are the transaction's own
+	async fn run(
+		&self,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: ParquetTransaction,
+		logs: Vec<ParquetLog>,
+	) -> Result<WarehouseData>;
+}
@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use eyre::Result;
+
+use crate::{
+	chain::{
+		tron::{
+			self,
+			modules::TronModuleTrait,
+			schema::{Log as ParquetLog, Transaction as ParquetTransaction},
+		},
+		ModuleId, ModuleTrait, WarehouseData, U256,
+	},
+	models::{Amount, PrimaryId},
+	BlockHeight,
+};
+
+pub struct TronTokenBalance {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for TronTokenBalance {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::TronTokenBalance
+	}
+}
+
+#[async_trait]
+impl TronModuleTrait for TronTokenBalance {
+	async fn run(
+		&self,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: ParquetTransaction,
+		logs: Vec<ParquetLog>,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+
+		// process trc-20 `transfer` events
+		for log in logs.into_iter() {
+			if let Some((from, to, amount)) = tron::get_token_transfer(&log) {
+				ret.amounts.insert(Amount::new(
+					self.get_id(),
+					self.network_id,
+					block_height,
+					&tx.hash,
+					&from,
+					Some(log.address.clone()),
+					U256::zero(),
+					amount,
+					block_time,
+				));
+				ret.amounts.insert(Amount::new(
+					self.get_id(),
+					self.network_id,
+					block_height,
+					&tx.hash,
+					&to,
+					Some(log.address.clone()),
+					amount,
+					U256::zero(),
+					block_time,
+				));
+			}
+		}
+
+		Ok(ret)
+	}
+}
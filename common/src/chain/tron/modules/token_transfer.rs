@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use eyre::Result;
+
+use crate::{
+	chain::{
+		tron::{
+			self,
+			modules::TronModuleTrait,
+			schema::{Log as ParquetLog, Transaction as ParquetTransaction},
+		},
+		ModuleId, ModuleTrait, WarehouseData,
+	},
+	models::{PrimaryId, Transfer},
+	BlockHeight,
+};
+
+pub struct TronTokenTransfer {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for TronTokenTransfer {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::TronTokenTransfer
+	}
+}
+
+#[async_trait]
+impl TronModuleTrait for TronTokenTransfer {
+	async fn run(
+		&self,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: ParquetTransaction,
+		logs: Vec<ParquetLog>,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+
+		// process trc-20 `transfer` events
+		for log in logs.into_iter() {
+			if let Some((from, to, amount)) = tron::get_token_transfer(&log) {
+				ret.transfers.insert(Transfer::new(
+					self.get_id(),
+					self.network_id,
+					block_height,
+					&tx.hash,
+					log.log_index as u64,
+					&from,
+					&to,
+					Some(log.address.clone()),
+					amount,
+					amount,
+					block_time,
+				));
+			}
+		}
+
+		Ok(ret)
+	}
+}
@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use eyre::Result;
+
+use crate::{
+	chain::{
+		tron::{
+			modules::TronModuleTrait,
+			schema::{Log as ParquetLog, Transaction as ParquetTransaction},
+		},
+		ModuleId, ModuleTrait, WarehouseData, U256,
+	},
+	models::{PrimaryId, Transfer},
+	BlockHeight,
+};
+
+pub struct TronTransfer {
+	network_id: PrimaryId,
+}
+
+impl ModuleTrait for TronTransfer {
+	fn new(network_id: PrimaryId) -> Self {
+		Self { network_id }
+	}
+
+	fn get_id(&self) -> ModuleId {
+		ModuleId::TronTransfer
+	}
+}
+
+#[async_trait]
+impl TronModuleTrait for TronTransfer {
+	async fn run(
+		&self,
+		block_height: BlockHeight,
+		block_time: u32,
+		tx: ParquetTransaction,
+		_logs: Vec<ParquetLog>,
+	) -> Result<WarehouseData> {
+		let mut ret = WarehouseData::new();
+
+		// skip if failed, no asset transfer or sending to self
+		let Some(to) = tx
+			.to_address
+			.as_ref()
+			.filter(|to| tx.is_successful && tx.value > 0 && **to != tx.from_address)
+		else {
+			return Ok(ret);
+		};
+
+		ret.transfers.insert(Transfer::new(
+			self.get_id(),
+			self.network_id,
+			block_height,
+			&tx.hash,
+			0,
+			&tx.from_address,
+			to,
+			None,
+			U256::from(tx.value),
+			U256::from(tx.value),
+			block_time,
+		));
+
+		Ok(ret)
+	}
+}
@@ -0,0 +1,68 @@
+use duckdb::{params, Appender, Connection};
+use eyre::Result;
+
+use super::{read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
+
+#[derive(Debug, Clone)]
+pub struct Block {
+	pub block_height: u64,
+	pub hash: String,
+	pub parent_hash: String,
+	pub block_time: u32,
+	pub witness_address: String,
+}
+
+impl Block {
+	pub fn get(storage_db: &StorageDb) -> Result<Option<Block>> {
+		Ok(read_file(
+			storage_db,
+			ParquetFile::Blocks,
+			"block_height, hash, parent_hash, block_time, witness_address",
+			|row| {
+				Ok(Block {
+					block_height: row.get(0)?,
+					hash: row.get(1)?,
+					parent_hash: row.get(2)?,
+					block_time: row.get(3)?,
+					witness_address: row.get(4)?,
+				})
+			},
+		)?
+		.into_iter()
+		.next())
+	}
+}
+
+impl StorageModelTrait for Block {
+	fn get_table(&self) -> String {
+		ParquetFile::Blocks.to_string()
+	}
+
+	fn create_table(&self, db: &Connection) -> Result<()> {
+		db.execute_batch(&format!(
+			r#"CREATE TABLE IF NOT EXISTS {} (
+                block_height UINT64 NOT NULL,
+                hash VARCHAR NOT NULL,
+                parent_hash VARCHAR NOT NULL,
+                block_time UINT32 NOT NULL,
+                witness_address VARCHAR NOT NULL
+            );"#,
+			ParquetFile::Blocks
+		))?;
+
+		Ok(())
+	}
+
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.block_height,
+			self.hash,
+			self.parent_hash,
+			self.block_time,
+			self.witness_address,
+		])?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,74 @@
+use duckdb::{params, Appender, Connection};
+use eyre::Result;
+
+use super::{read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
+
+// @NOTE `address` is base58 like the rest, while topics (comma separated)
+// and data stay hex, as returned by the node
+#[derive(Debug, Clone)]
+pub struct Log {
+	pub transaction_hash: String,
+	pub log_index: u32,
+	pub address: String,
+	pub topics: Vec<String>,
+	pub data: String,
+}
+
+impl Log {
+	pub fn get_all(storage_db: &StorageDb) -> Result<Vec<Log>> {
+		read_file(
+			storage_db,
+			ParquetFile::Logs,
+			"transaction_hash, log_index, address, topics, data",
+			|row| {
+				let topics = row.get::<_, String>(3)?;
+
+				Ok(Log {
+					transaction_hash: row.get(0)?,
+					log_index: row.get(1)?,
+					address: row.get(2)?,
+					topics: topics
+						.split(',')
+						.filter(|t| !t.is_empty())
+						.map(|t| t.to_string())
+						.collect(),
+					data: row.get(4)?,
+				})
+			},
+		)
+	}
+}
+
+impl StorageModelTrait for Log {
+	fn get_table(&self) -> String {
+		ParquetFile::Logs.to_string()
+	}
+
+	fn create_table(&self, db: &Connection) -> Result<()> {
+		db.execute_batch(&format!(
+			r#"CREATE TABLE IF NOT EXISTS {} (
+                transaction_hash VARCHAR NOT NULL,
+                log_index UINT32 NOT NULL,
+                address VARCHAR NOT NULL,
+                topics VARCHAR NOT NULL,
+                data VARCHAR NOT NULL
+            );"#,
+			ParquetFile::Logs
+		))?;
+
+		Ok(())
+	}
+
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.transaction_hash,
+			self.log_index,
+			self.address,
+			self.topics.join(","),
+			self.data,
+		])?;
+
+		Ok(())
+	}
+}
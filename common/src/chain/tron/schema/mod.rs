@@ -0,0 +1,44 @@
+use derive_more::Display;
+use duckdb::Row;
+use eyre::Result;
+
+use crate::storage::StorageDb;
+
+pub use block::Block;
+pub use log::Log;
+pub use transaction::Transaction;
+
+#[derive(Display, Debug)]
+pub enum ParquetFile {
+	#[display("blocks")]
+	Blocks,
+	#[display("transactions")]
+	Transactions,
+	#[display("logs")]
+	Logs,
+}
+
+mod block;
+mod log;
+mod transaction;
+
+// rows of a stored file, mapped with `f`; a missing file has no rows
+fn read_file<T>(
+	storage_db: &StorageDb,
+	file: ParquetFile,
+	columns: &str,
+	mut f: impl FnMut(&Row) -> Result<T>,
+) -> Result<Vec<T>> {
+	let mut ret = vec![];
+
+	if let Some(source) = storage_db.get_source(&file.to_string())? {
+		let mut statement = storage_db.db.prepare(&format!("SELECT {columns} FROM {source}"))?;
+		let mut rows = statement.query([])?;
+
+		while let Some(row) = rows.next()? {
+			ret.push(f(row)?);
+		}
+	}
+
+	Ok(ret)
+}
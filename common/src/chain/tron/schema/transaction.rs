@@ -0,0 +1,82 @@
+use duckdb::{params, Appender, Connection};
+use eyre::Result;
+
+use super::{read_file, ParquetFile};
+use crate::storage::{StorageDb, StorageModelTrait};
+
+// @NOTE `to_address` and `value` are the recipient and amount of trx
+// transfers, and the contract and call value of contract calls
+#[derive(Debug, Clone)]
+pub struct Transaction {
+	pub hash: String,
+	pub transaction_index: u32,
+	pub contract_type: String,
+	pub from_address: String,
+	pub to_address: Option<String>,
+	pub value: u64,
+	pub fee: u64,
+	// failed transactions only charge the fee
+	pub is_successful: bool,
+}
+
+impl Transaction {
+	pub fn get_all(storage_db: &StorageDb) -> Result<Vec<Transaction>> {
+		read_file(
+			storage_db,
+			ParquetFile::Transactions,
+			"hash, transaction_index, contract_type, from_address, to_address, value, fee, \
+			 is_successful",
+			|row| {
+				Ok(Transaction {
+					hash: row.get(0)?,
+					transaction_index: row.get(1)?,
+					contract_type: row.get(2)?,
+					from_address: row.get(3)?,
+					to_address: row.get(4)?,
+					value: row.get(5)?,
+					fee: row.get(6)?,
+					is_successful: row.get(7)?,
+				})
+			},
+		)
+	}
+}
+
+impl StorageModelTrait for Transaction {
+	fn get_table(&self) -> String {
+		ParquetFile::Transactions.to_string()
+	}
+
+	fn create_table(&self, db: &Connection) -> Result<()> {
+		db.execute_batch(&format!(
+			r#"CREATE TABLE IF NOT EXISTS {} (
+                hash VARCHAR NOT NULL,
+                transaction_index UINT32 NOT NULL,
+                contract_type VARCHAR NOT NULL,
+                from_address VARCHAR NOT NULL,
+                to_address VARCHAR NULL,
+                value UINT64 NOT NULL,
+                fee UINT64 NOT NULL,
+                is_successful BOOLEAN NOT NULL
+            );"#,
+			ParquetFile::Transactions
+		))?;
+
+		Ok(())
+	}
+
+	fn append(&self, appender: &mut Appender) -> Result<()> {
+		appender.append_row(params![
+			self.hash,
+			self.transaction_index,
+			self.contract_type,
+			self.from_address,
+			self.to_address,
+			self.value,
+			self.fee,
+			self.is_successful,
+		])?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter()
+					.table(Tokens::Table)
+					.add_column(
+						ColumnDef::new(Tokens::IsPlaceholder).boolean().not_null().default(false),
+					)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter().table(Tokens::Table).drop_column(Tokens::IsPlaceholder).to_owned(),
+			)
+			.await
+	}
+}
+
+#[derive(Iden)]
+enum Tokens {
+	#[iden = "tokens"]
+	Table,
+	IsPlaceholder,
+}
@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		// @NOTE sqlite can only add one column per statement
+		manager
+			.alter_table(
+				Table::alter()
+					.table(Networks::Table)
+					.add_column(ColumnDef::new(Networks::TokenAllowlist).json().null())
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.alter_table(
+				Table::alter()
+					.table(Networks::Table)
+					.add_column(ColumnDef::new(Networks::TokenDenylist).json().null())
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter().table(Networks::Table).drop_column(Networks::TokenAllowlist).to_owned(),
+			)
+			.await?;
+
+		manager
+			.alter_table(
+				Table::alter().table(Networks::Table).drop_column(Networks::TokenDenylist).to_owned(),
+			)
+			.await
+	}
+}
+
+#[derive(Iden)]
+enum Networks {
+	#[iden = "networks"]
+	Table,
+	TokenAllowlist,
+	TokenDenylist,
+}
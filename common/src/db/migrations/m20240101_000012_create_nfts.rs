@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Nfts::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(Nfts::NftId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(Nfts::NetworkId).big_integer().not_null())
+					.col(ColumnDef::new(Nfts::Id).unique_key().string().not_null())
+					.col(ColumnDef::new(Nfts::Address).string().not_null())
+					.col(ColumnDef::new(Nfts::TokenId).string().not_null())
+					.col(ColumnDef::new(Nfts::CollectionName).string().null())
+					.col(ColumnDef::new(Nfts::Name).string().null())
+					.col(ColumnDef::new(Nfts::Description).text().null())
+					.col(ColumnDef::new(Nfts::Image).text().null())
+					.col(ColumnDef::new(Nfts::Uri).text().null())
+					.col(ColumnDef::new(Nfts::IsResolved).boolean().not_null())
+					.col(ColumnDef::new(Nfts::Attempts).integer().not_null())
+					.col(ColumnDef::new(Nfts::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(Nfts::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.foreign_key(
+						&mut sea_query::ForeignKey::create()
+							.name("fk_nfts_network_id")
+							.from(Nfts::Table, Nfts::NetworkId)
+							.to(Alias::new("networks"), Alias::new("network_id"))
+							.on_delete(ForeignKeyAction::Cascade)
+							.to_owned(),
+					)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ux_nfts_network_id_address_token_id")
+					.table(Nfts::Table)
+					.unique()
+					.col(Nfts::NetworkId)
+					.col(Nfts::Address)
+					.col(Nfts::TokenId)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ix_nfts_is_resolved")
+					.table(Nfts::Table)
+					.col(Nfts::IsResolved)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(Nfts::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum Nfts {
+	#[iden = "nfts"]
+	Table,
+	NftId,
+	NetworkId,
+	Id,
+	Address,
+	TokenId,
+	CollectionName,
+	Name,
+	Description,
+	Image,
+	Uri,
+	IsResolved,
+	Attempts,
+	UpdatedAt,
+	CreatedAt,
+}
@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Alerts::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(Alerts::AlertId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(Alerts::NetworkId).big_integer().not_null())
+					.col(ColumnDef::new(Alerts::Id).unique_key().string().not_null())
+					.col(ColumnDef::new(Alerts::Kind).small_integer().not_null())
+					.col(ColumnDef::new(Alerts::BlockHeight).big_integer().not_null())
+					.col(ColumnDef::new(Alerts::TxHash).string().not_null())
+					.col(ColumnDef::new(Alerts::Address).string().not_null())
+					.col(ColumnDef::new(Alerts::AssetAddress).string().not_null())
+					.col(ColumnDef::new(Alerts::Amount).string().not_null())
+					.col(ColumnDef::new(Alerts::Reason).text().not_null())
+					.col(ColumnDef::new(Alerts::IsAcknowledged).boolean().not_null())
+					.col(ColumnDef::new(Alerts::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(Alerts::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.foreign_key(
+						&mut sea_query::ForeignKey::create()
+							.name("fk_alerts_network_id")
+							.from(Alerts::Table, Alerts::NetworkId)
+							.to(Alias::new("networks"), Alias::new("network_id"))
+							.on_delete(ForeignKeyAction::Cascade)
+							.to_owned(),
+					)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ux_alerts_network_id_kind_tx_hash_address_asset_address")
+					.table(Alerts::Table)
+					.unique()
+					.col(Alerts::NetworkId)
+					.col(Alerts::Kind)
+					.col(Alerts::TxHash)
+					.col(Alerts::Address)
+					.col(Alerts::AssetAddress)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(Alerts::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum Alerts {
+	#[iden = "alerts"]
+	Table,
+	AlertId,
+	NetworkId,
+	Id,
+	Kind,
+	BlockHeight,
+	TxHash,
+	Address,
+	AssetAddress,
+	Amount,
+	Reason,
+	IsAcknowledged,
+	UpdatedAt,
+	CreatedAt,
+}
@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Annotations::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(Annotations::AnnotationId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(Annotations::NetworkId).big_integer().not_null())
+					.col(ColumnDef::new(Annotations::Id).unique_key().string().not_null())
+					.col(ColumnDef::new(Annotations::Address).string().not_null())
+					.col(ColumnDef::new(Annotations::Kind).small_integer().not_null())
+					.col(ColumnDef::new(Annotations::Reference).string().not_null())
+					.col(ColumnDef::new(Annotations::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(Annotations::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.foreign_key(
+						&mut sea_query::ForeignKey::create()
+							.name("fk_annotations_network_id")
+							.from(Annotations::Table, Annotations::NetworkId)
+							.to(Alias::new("networks"), Alias::new("network_id"))
+							.on_delete(ForeignKeyAction::Cascade)
+							.to_owned(),
+					)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ux_annotations_network_id_address_kind")
+					.table(Annotations::Table)
+					.unique()
+					.col(Annotations::NetworkId)
+					.col(Annotations::Address)
+					.col(Annotations::Kind)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(Annotations::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum Annotations {
+	#[iden = "annotations"]
+	Table,
+	AnnotationId,
+	NetworkId,
+	Id,
+	Address,
+	Kind,
+	Reference,
+	UpdatedAt,
+	CreatedAt,
+}
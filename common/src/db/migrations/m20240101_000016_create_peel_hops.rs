@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(PeelHops::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(PeelHops::PeelHopId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(PeelHops::NetworkId).big_integer().not_null())
+					.col(ColumnDef::new(PeelHops::BlockHeight).big_integer().not_null())
+					.col(ColumnDef::new(PeelHops::TxHash).string().not_null())
+					.col(ColumnDef::new(PeelHops::RootTxHash).string().not_null())
+					.col(ColumnDef::new(PeelHops::Length).integer().not_null())
+					.col(ColumnDef::new(PeelHops::InputAddress).string().not_null())
+					.col(ColumnDef::new(PeelHops::ChangeAddress).string().not_null())
+					.col(ColumnDef::new(PeelHops::PeelAddress).string().not_null())
+					.col(ColumnDef::new(PeelHops::PeelAmount).string().not_null())
+					.col(ColumnDef::new(PeelHops::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(PeelHops::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.foreign_key(
+						&mut sea_query::ForeignKey::create()
+							.name("fk_peel_hops_network_id")
+							.from(PeelHops::Table, PeelHops::NetworkId)
+							.to(Alias::new("networks"), Alias::new("network_id"))
+							.on_delete(ForeignKeyAction::Cascade)
+							.to_owned(),
+					)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ux_peel_hops_network_id_tx_hash")
+					.table(PeelHops::Table)
+					.unique()
+					.col(PeelHops::NetworkId)
+					.col(PeelHops::TxHash)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ix_peel_hops_network_id_change_address")
+					.table(PeelHops::Table)
+					.col(PeelHops::NetworkId)
+					.col(PeelHops::ChangeAddress)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ix_peel_hops_network_id_root_tx_hash")
+					.table(PeelHops::Table)
+					.col(PeelHops::NetworkId)
+					.col(PeelHops::RootTxHash)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(PeelHops::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum PeelHops {
+	#[iden = "peel_hops"]
+	Table,
+	PeelHopId,
+	NetworkId,
+	BlockHeight,
+	TxHash,
+	RootTxHash,
+	Length,
+	InputAddress,
+	ChangeAddress,
+	PeelAddress,
+	PeelAmount,
+	UpdatedAt,
+	CreatedAt,
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter()
+					.table(Tags::Table)
+					.add_column(
+						ColumnDef::new(Tags::IsMixer).boolean().not_null().default(false),
+					)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter().table(Tags::Table).drop_column(Tags::IsMixer).to_owned(),
+			)
+			.await
+	}
+}
+
+#[derive(Iden)]
+enum Tags {
+	#[iden = "tags"]
+	Table,
+	IsMixer,
+}
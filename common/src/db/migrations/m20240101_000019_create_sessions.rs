@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Sessions::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(Sessions::SessionId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(Sessions::Id).unique_key().string().not_null())
+					.col(ColumnDef::new(Sessions::Subject).string().not_null())
+					.col(ColumnDef::new(Sessions::TokenHash).unique_key().binary().not_null())
+					.col(ColumnDef::new(Sessions::ExpiresAt).date_time().not_null())
+					.col(ColumnDef::new(Sessions::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(Sessions::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(Sessions::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum Sessions {
+	#[iden = "sessions"]
+	Table,
+	SessionId,
+	Id,
+	Subject,
+	TokenHash,
+	ExpiresAt,
+	UpdatedAt,
+	CreatedAt,
+}
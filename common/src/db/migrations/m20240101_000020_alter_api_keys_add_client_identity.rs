@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter()
+					.table(ApiKeys::Table)
+					.add_column(
+						ColumnDef::new(ApiKeys::ClientIdentity).unique_key().string().null(),
+					)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter()
+					.table(ApiKeys::Table)
+					.drop_column(ApiKeys::ClientIdentity)
+					.to_owned(),
+			)
+			.await
+	}
+}
+
+#[derive(Iden)]
+enum ApiKeys {
+	#[iden = "api_keys"]
+	Table,
+	ClientIdentity,
+}
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(ApiKeyUsages::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(ApiKeyUsages::ApiKeyUsageId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(ApiKeyUsages::ApiKeyId).big_integer().not_null())
+					.col(ColumnDef::new(ApiKeyUsages::Endpoint).string().not_null())
+					.col(ColumnDef::new(ApiKeyUsages::RequestCount).big_integer().not_null())
+					.col(ColumnDef::new(ApiKeyUsages::LastUsedAt).date_time().not_null())
+					.col(ColumnDef::new(ApiKeyUsages::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(ApiKeyUsages::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.foreign_key(
+						&mut sea_query::ForeignKey::create()
+							.name("fk_api_key_usage_api_key_id")
+							.from(ApiKeyUsages::Table, ApiKeyUsages::ApiKeyId)
+							.to(Alias::new("api_keys"), Alias::new("api_key_id"))
+							.on_delete(ForeignKeyAction::Cascade)
+							.to_owned(),
+					)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ux_api_key_usage_api_key_id_endpoint")
+					.table(ApiKeyUsages::Table)
+					.unique()
+					.col(ApiKeyUsages::ApiKeyId)
+					.col(ApiKeyUsages::Endpoint)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(ApiKeyUsages::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum ApiKeyUsages {
+	#[iden = "api_key_usage"]
+	Table,
+	ApiKeyUsageId,
+	ApiKeyId,
+	Endpoint,
+	RequestCount,
+	LastUsedAt,
+	UpdatedAt,
+	CreatedAt,
+}
@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Abis::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(Abis::AbiId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(Abis::NetworkId).big_integer().not_null())
+					.col(ColumnDef::new(Abis::Id).unique_key().string().not_null())
+					.col(ColumnDef::new(Abis::Name).string().not_null())
+					.col(ColumnDef::new(Abis::Address).string().not_null())
+					.col(ColumnDef::new(Abis::Abi).json().not_null())
+					.col(ColumnDef::new(Abis::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(Abis::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.foreign_key(
+						&mut sea_query::ForeignKey::create()
+							.name("fk_abis_network_id")
+							.from(Abis::Table, Abis::NetworkId)
+							.to(Alias::new("networks"), Alias::new("network_id"))
+							.on_delete(ForeignKeyAction::Cascade)
+							.to_owned(),
+					)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ux_abis_network_id_address")
+					.table(Abis::Table)
+					.unique()
+					.col(Abis::NetworkId)
+					.col(Abis::Address)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(Abis::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum Abis {
+	#[iden = "abis"]
+	Table,
+	AbiId,
+	NetworkId,
+	Id,
+	Name,
+	Address,
+	Abi,
+	UpdatedAt,
+	CreatedAt,
+}
@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(BridgeTransfers::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(BridgeTransfers::BridgeTransferId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(BridgeTransfers::EntityId).big_integer().not_null())
+					.col(ColumnDef::new(BridgeTransfers::FromNetworkId).big_integer().not_null())
+					.col(ColumnDef::new(BridgeTransfers::FromBlockHeight).big_integer().not_null())
+					.col(ColumnDef::new(BridgeTransfers::FromTxHash).string().not_null())
+					.col(ColumnDef::new(BridgeTransfers::FromAddress).string().not_null())
+					.col(ColumnDef::new(BridgeTransfers::FromAmount).string().not_null())
+					.col(ColumnDef::new(BridgeTransfers::ToNetworkId).big_integer().not_null())
+					.col(ColumnDef::new(BridgeTransfers::ToBlockHeight).big_integer().not_null())
+					.col(ColumnDef::new(BridgeTransfers::ToTxHash).string().not_null())
+					.col(ColumnDef::new(BridgeTransfers::ToAddress).string().not_null())
+					.col(ColumnDef::new(BridgeTransfers::ToAmount).string().not_null())
+					.col(ColumnDef::new(BridgeTransfers::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(BridgeTransfers::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.foreign_key(
+						&mut sea_query::ForeignKey::create()
+							.name("fk_bridge_transfers_entity_id")
+							.from(BridgeTransfers::Table, BridgeTransfers::EntityId)
+							.to(Alias::new("entities"), Alias::new("entity_id"))
+							.on_delete(ForeignKeyAction::Cascade)
+							.to_owned(),
+					)
+					.foreign_key(
+						&mut sea_query::ForeignKey::create()
+							.name("fk_bridge_transfers_from_network_id")
+							.from(BridgeTransfers::Table, BridgeTransfers::FromNetworkId)
+							.to(Alias::new("networks"), Alias::new("network_id"))
+							.on_delete(ForeignKeyAction::Cascade)
+							.to_owned(),
+					)
+					.foreign_key(
+						&mut sea_query::ForeignKey::create()
+							.name("fk_bridge_transfers_to_network_id")
+							.from(BridgeTransfers::Table, BridgeTransfers::ToNetworkId)
+							.to(Alias::new("networks"), Alias::new("network_id"))
+							.on_delete(ForeignKeyAction::Cascade)
+							.to_owned(),
+					)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ux_bridge_transfers_to_network_id_to_tx_hash_to_address")
+					.table(BridgeTransfers::Table)
+					.unique()
+					.col(BridgeTransfers::ToNetworkId)
+					.col(BridgeTransfers::ToTxHash)
+					.col(BridgeTransfers::ToAddress)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ix_bridge_transfers_to_network_id_to_address")
+					.table(BridgeTransfers::Table)
+					.col(BridgeTransfers::ToNetworkId)
+					.col(BridgeTransfers::ToAddress)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(BridgeTransfers::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum BridgeTransfers {
+	#[iden = "bridge_transfers"]
+	Table,
+	BridgeTransferId,
+	EntityId,
+	FromNetworkId,
+	FromBlockHeight,
+	FromTxHash,
+	FromAddress,
+	FromAmount,
+	ToNetworkId,
+	ToBlockHeight,
+	ToTxHash,
+	ToAddress,
+	ToAmount,
+	UpdatedAt,
+	CreatedAt,
+}
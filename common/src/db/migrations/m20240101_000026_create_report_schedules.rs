@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(ReportSchedules::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(ReportSchedules::ReportScheduleId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(ReportSchedules::Id).unique_key().string().not_null())
+					.col(ColumnDef::new(ReportSchedules::Name).string().not_null())
+					.col(ColumnDef::new(ReportSchedules::Watchlist).json().not_null())
+					.col(ColumnDef::new(ReportSchedules::IntervalSeconds).big_integer().not_null())
+					.col(ColumnDef::new(ReportSchedules::NextRunAt).date_time().not_null())
+					.col(ColumnDef::new(ReportSchedules::LastRunAt).date_time().null())
+					.col(ColumnDef::new(ReportSchedules::LastReportIds).json().null())
+					.col(ColumnDef::new(ReportSchedules::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(ReportSchedules::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("ix_report_schedules_next_run_at")
+					.table(ReportSchedules::Table)
+					.col(ReportSchedules::NextRunAt)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(ReportSchedules::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum ReportSchedules {
+	#[iden = "report_schedules"]
+	Table,
+	ReportScheduleId,
+	Id,
+	Name,
+	Watchlist,
+	IntervalSeconds,
+	NextRunAt,
+	LastRunAt,
+	LastReportIds,
+	UpdatedAt,
+	CreatedAt,
+}
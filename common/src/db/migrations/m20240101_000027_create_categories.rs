@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Categories::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(Categories::CategoryId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(Categories::Id).unique_key().string().not_null())
+					.col(ColumnDef::new(Categories::Name).unique_key().string().not_null())
+					.col(ColumnDef::new(Categories::Severity).small_integer().not_null())
+					.col(ColumnDef::new(Categories::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(Categories::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(Categories::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum Categories {
+	#[iden = "categories"]
+	Table,
+	CategoryId,
+	Id,
+	Name,
+	Severity,
+	UpdatedAt,
+	CreatedAt,
+}
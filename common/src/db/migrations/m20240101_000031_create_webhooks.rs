@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Webhooks::Table)
+					.if_not_exists()
+					.col(
+						ColumnDef::new(Webhooks::WebhookId)
+							.big_integer()
+							.not_null()
+							.auto_increment()
+							.primary_key(),
+					)
+					.col(ColumnDef::new(Webhooks::Id).unique_key().string().not_null())
+					.col(ColumnDef::new(Webhooks::Url).string().not_null())
+					.col(ColumnDef::new(Webhooks::UpdatedAt).date_time().null())
+					.col(
+						ColumnDef::new(Webhooks::CreatedAt)
+							.date_time()
+							.not_null()
+							.extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+					)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(Webhooks::Table).to_owned()).await
+	}
+}
+
+#[derive(Iden)]
+enum Webhooks {
+	#[iden = "webhooks"]
+	Table,
+	WebhookId,
+	Id,
+	Url,
+	UpdatedAt,
+	CreatedAt,
+}
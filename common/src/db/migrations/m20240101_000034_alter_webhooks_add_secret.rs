@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter()
+					.table(Webhooks::Table)
+					.add_column(ColumnDef::new(Webhooks::Secret).string().null())
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter().table(Webhooks::Table).drop_column(Webhooks::Secret).to_owned(),
+			)
+			.await
+	}
+}
+
+#[derive(Iden)]
+enum Webhooks {
+	#[iden = "webhooks"]
+	Table,
+	Secret,
+}
@@ -9,6 +9,31 @@ mod m20240101_000006_create_addresses;
 mod m20240101_000007_create_tags;
 mod m20240101_000008_create_entity_tags;
 mod m20240101_000009_create_tokens;
+mod m20240101_000010_alter_tokens_add_is_placeholder;
+mod m20240101_000011_alter_networks_add_token_lists;
+mod m20240101_000012_create_nfts;
+mod m20240101_000013_create_alerts;
+mod m20240101_000014_alter_networks_add_large_transfer_threshold;
+mod m20240101_000015_create_annotations;
+mod m20240101_000016_create_peel_hops;
+mod m20240101_000017_alter_tags_add_is_mixer;
+mod m20240101_000018_alter_tags_add_is_exchange;
+mod m20240101_000019_create_sessions;
+mod m20240101_000020_alter_api_keys_add_client_identity;
+mod m20240101_000021_create_api_key_usage;
+mod m20240101_000022_create_abis;
+mod m20240101_000023_alter_networks_add_confirmations;
+mod m20240101_000024_alter_tags_add_is_bridge;
+mod m20240101_000025_create_bridge_transfers;
+mod m20240101_000026_create_report_schedules;
+mod m20240101_000027_create_categories;
+mod m20240101_000028_alter_tags_add_category_id;
+mod m20240101_000029_alter_networks_add_wrapped_native_tokens;
+mod m20240101_000030_alter_networks_add_dormancy_days;
+mod m20240101_000031_create_webhooks;
+mod m20240101_000032_alter_networks_add_rpc_endpoints;
+mod m20240101_000033_alter_networks_add_price_id;
+mod m20240101_000034_alter_webhooks_add_secret;
 
 pub struct Migrator;
 
@@ -24,6 +49,31 @@ impl MigratorTrait for Migrator {
 			Box::new(m20240101_000007_create_tags::Migration),
 			Box::new(m20240101_000008_create_entity_tags::Migration),
 			Box::new(m20240101_000009_create_tokens::Migration),
+			Box::new(m20240101_000010_alter_tokens_add_is_placeholder::Migration),
+			Box::new(m20240101_000011_alter_networks_add_token_lists::Migration),
+			Box::new(m20240101_000012_create_nfts::Migration),
+			Box::new(m20240101_000013_create_alerts::Migration),
+			Box::new(m20240101_000014_alter_networks_add_large_transfer_threshold::Migration),
+			Box::new(m20240101_000015_create_annotations::Migration),
+			Box::new(m20240101_000016_create_peel_hops::Migration),
+			Box::new(m20240101_000017_alter_tags_add_is_mixer::Migration),
+			Box::new(m20240101_000018_alter_tags_add_is_exchange::Migration),
+			Box::new(m20240101_000019_create_sessions::Migration),
+			Box::new(m20240101_000020_alter_api_keys_add_client_identity::Migration),
+			Box::new(m20240101_000021_create_api_key_usage::Migration),
+			Box::new(m20240101_000022_create_abis::Migration),
+			Box::new(m20240101_000023_alter_networks_add_confirmations::Migration),
+			Box::new(m20240101_000024_alter_tags_add_is_bridge::Migration),
+			Box::new(m20240101_000025_create_bridge_transfers::Migration),
+			Box::new(m20240101_000026_create_report_schedules::Migration),
+			Box::new(m20240101_000027_create_categories::Migration),
+			Box::new(m20240101_000028_alter_tags_add_category_id::Migration),
+			Box::new(m20240101_000029_alter_networks_add_wrapped_native_tokens::Migration),
+			Box::new(m20240101_000030_alter_networks_add_dormancy_days::Migration),
+			Box::new(m20240101_000031_create_webhooks::Migration),
+			Box::new(m20240101_000032_alter_networks_add_rpc_endpoints::Migration),
+			Box::new(m20240101_000033_alter_networks_add_price_id::Migration),
+			Box::new(m20240101_000034_alter_webhooks_add_secret::Migration),
 		]
 	}
 }
@@ -2,18 +2,28 @@ use derive_more::Display;
 use eyre::{Result, WrapErr};
 use log::LevelFilter;
 use sea_orm::{
+	sqlx::{
+		self,
+		sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
+		ConnectOptions as _,
+	},
 	ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DatabaseTransaction, DbBackend,
-	Statement, TransactionTrait,
+	SqlxSqliteConnector, Statement, TransactionTrait,
 };
 use serde::{Deserialize, Serialize};
-use std::{str::FromStr, sync::Arc, time::Duration};
+use std::{
+	str::FromStr,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+use url::Url;
 
 use crate::{utils, Settings};
 use migrations::{Migrator, MigratorTrait};
 
 mod migrations;
 
-#[derive(Display, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Display, Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub enum Driver {
 	#[default]
 	#[serde(rename = "sqlite")]
@@ -40,24 +50,35 @@ impl FromStr for Driver {
 	}
 }
 
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStats {
+	pub size: u32,
+	pub idle: u32,
+	pub in_use: u32,
+	pub max: u32,
+	pub wait_time_ms: u128,
+}
+
 pub struct Db {
 	db: DatabaseConnection,
+	max_connections: u32,
 }
 
 impl Db {
 	pub async fn new(settings: Arc<Settings>) -> Result<Self> {
 		let url = settings.database.clone();
 
+		// @TODO for sqlite, max out at 1 connection otherwise
+		// writes are not guaranteed to be executed serially
+		let (min_connections, max_connections) = match settings.database_driver {
+			Driver::SQLite => (1, 1),
+			_ => (settings.database_min_connections, settings.database_max_connections),
+		};
+
 		let with_options = |url: String| -> ConnectOptions {
 			let mut opt = ConnectOptions::new(url);
 
-			// @TODO for sqlite, max out at 1 connection otherwise
-			// writes are not guaranteed to be executed serially
-			let (min_connections, max_connections) = match settings.database_driver {
-				Driver::SQLite => (1, 1),
-				_ => (settings.database_min_connections, settings.database_max_connections),
-			};
-
 			opt.max_connections(max_connections)
 				.min_connections(min_connections)
 				.connect_timeout(Duration::from_secs(settings.database_connect_timeout))
@@ -73,11 +94,28 @@ impl Db {
 			Driver::SQLite => (url.clone(), "".to_string()),
 			_ => utils::without_pathname(&url),
 		};
-		let url_with_database = url;
+		let mut url_with_database = url;
+
+		// set statement timeout for every connection in the pool
+		if settings.database_driver == Driver::PostgreSQL && settings.database_statement_timeout > 0
+		{
+			if let Ok(mut parsed_url) = Url::parse(&url_with_database) {
+				let timeout = settings.database_statement_timeout * 1_000;
+				parsed_url
+					.query_pairs_mut()
+					.append_pair("options", &format!("-c statement_timeout={timeout}"));
+				url_with_database = parsed_url.to_string();
+			}
+		}
 
-		let conn = Database::connect(with_options(url_without_database.clone()))
-			.await
-			.wrap_err(url_without_database.clone())?;
+		let conn = match settings.database_driver {
+			Driver::SQLite => Self::connect_sqlite(&settings, &url_without_database)
+				.await
+				.wrap_err(url_without_database.clone())?,
+			_ => Database::connect(with_options(url_without_database.clone()))
+				.await
+				.wrap_err(url_without_database.clone())?,
+		};
 
 		let db = match conn.get_database_backend() {
 			DbBackend::MySql => {
@@ -119,7 +157,31 @@ impl Db {
 			DbBackend::Sqlite => conn,
 		};
 
-		Ok(Self { db })
+		Ok(Self { db, max_connections })
+	}
+
+	// sqlite-specific pragmas are not exposed through sea-orm's options, so
+	// the pool is built with sqlx directly
+	async fn connect_sqlite(settings: &Settings, url: &str) -> Result<DatabaseConnection> {
+		let mut options = SqliteConnectOptions::from_str(url)?
+			.busy_timeout(Duration::from_secs(settings.database_sqlite_busy_timeout))
+			.synchronous(SqliteSynchronous::from_str(&settings.database_sqlite_synchronous)?)
+			.disable_statement_logging();
+
+		if settings.database_sqlite_wal {
+			options = options.journal_mode(SqliteJournalMode::Wal);
+		}
+
+		let pool = SqlitePoolOptions::new()
+			.max_connections(1)
+			.min_connections(1)
+			.acquire_timeout(Duration::from_secs(settings.database_connect_timeout))
+			.idle_timeout(Duration::from_secs(settings.database_idle_timeout))
+			.max_lifetime(Duration::from_secs(settings.database_max_lifetime))
+			.connect_with(options)
+			.await?;
+
+		Ok(SqlxSqliteConnector::from_sqlx_sqlite_pool(pool))
 	}
 
 	pub async fn run_migrations(&self) -> Result<()> {
@@ -134,4 +196,30 @@ impl Db {
 	pub async fn get_tx(&self) -> Result<DatabaseTransaction> {
 		Ok(self.db.begin().await?)
 	}
+
+	// @NOTE wait time is measured by acquiring (and immediately releasing) a
+	// connection, so it reflects current pool contention
+	pub async fn get_pool_stats(&self) -> Result<PoolStats> {
+		async fn probe<DB: sqlx::Database>(pool: &sqlx::Pool<DB>) -> Result<(u32, u32, u128)> {
+			let started_at = Instant::now();
+			drop(pool.acquire().await?);
+			let wait_time_ms = started_at.elapsed().as_millis();
+
+			Ok((pool.size(), pool.num_idle() as u32, wait_time_ms))
+		}
+
+		let (size, idle, wait_time_ms) = match self.db.get_database_backend() {
+			DbBackend::MySql => probe(self.db.get_mysql_connection_pool()).await?,
+			DbBackend::Postgres => probe(self.db.get_postgres_connection_pool()).await?,
+			DbBackend::Sqlite => probe(self.db.get_sqlite_connection_pool()).await?,
+		};
+
+		Ok(PoolStats {
+			size,
+			idle,
+			in_use: size.saturating_sub(idle),
+			max: self.max_connections,
+			wait_time_ms,
+		})
+	}
 }
@@ -0,0 +1,474 @@
+use clap::{builder::PossibleValue, ValueEnum};
+use duckdb::Connection;
+use eyre::{bail, Result};
+use sea_orm::{ColumnTrait, Set};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+	chain::new_boxed_chain,
+	models::{
+		set, Address, AddressActiveModel, AddressColumn, BasicModel, Config, ConfigKey, Entity,
+		EntityTag, Network, PrimaryId, Tag, TagActiveModel,
+	},
+	App, RiskLevel,
+};
+
+// addresses per insert/lookup
+const BATCH_SIZE: usize = 1_000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LabelFormat {
+	// etherscan label dumps (address, name tag & labels)
+	Etherscan,
+	// graphsense tagpacks (yaml)
+	TagPack,
+}
+
+impl ValueEnum for LabelFormat {
+	fn value_variants<'a>() -> &'a [Self] {
+		&[Self::Etherscan, Self::TagPack]
+	}
+
+	fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+		match self {
+			Self::Etherscan => Some(PossibleValue::new("etherscan")),
+			Self::TagPack => Some(PossibleValue::new("tagpack")),
+		}
+	}
+}
+
+// what to do with addresses that are already labeled
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum OnConflict {
+	#[default]
+	Skip,
+	Replace,
+}
+
+impl ValueEnum for OnConflict {
+	fn value_variants<'a>() -> &'a [Self] {
+		&[Self::Skip, Self::Replace]
+	}
+
+	fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+		match self {
+			Self::Skip => Some(PossibleValue::new("skip")),
+			Self::Replace => Some(PossibleValue::new("replace")),
+		}
+	}
+}
+
+// a labeled address, in whatever format it was read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+	pub address: String,
+	pub entity: String,
+	pub description: String,
+	pub tags: Vec<String>,
+	pub data: JsonValue,
+	// currency it was labeled on (eg: `BTC`), when the format has one
+	pub currency: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportStats {
+	pub entities_created: usize,
+	pub tags_created: usize,
+	pub addresses_created: usize,
+	pub addresses_replaced: usize,
+	pub addresses_skipped: usize,
+}
+
+pub fn read(format: LabelFormat, path: &Path) -> Result<Vec<Label>> {
+	match format {
+		LabelFormat::Etherscan => read_etherscan_csv(path),
+		LabelFormat::TagPack => read_tagpack(&fs::read_to_string(path)?),
+	}
+}
+
+// @NOTE the shared dumps don't agree on headers (eg: `Name Tag` vs `name`, or
+// `Labels` vs `label`), so columns are matched loosely. labels within a row
+// are separated by `;`, `|` or `,`
+fn read_etherscan_csv(path: &Path) -> Result<Vec<Label>> {
+	let db = Connection::open_in_memory()?;
+	let mut statement = db.prepare(&format!(
+		"SELECT * FROM read_csv('{}', header = true, all_varchar = true)",
+		path.display().to_string().replace('\'', "''")
+	))?;
+
+	let mut rows = statement.query([])?;
+	let columns = rows
+		.as_ref()
+		.map(|s| s.column_names())
+		.unwrap_or_default()
+		.into_iter()
+		.map(|c| c.to_lowercase().chars().filter(|c| c.is_ascii_alphanumeric()).collect())
+		.collect::<Vec<String>>();
+	let find_column = |names: &[&str]| columns.iter().position(|c| names.contains(&c.as_str()));
+
+	let Some(address_column) = find_column(&["address"]) else {
+		bail!("no `address` column in {}", path.display());
+	};
+	let Some(name_column) = find_column(&["nametag", "name", "labelname"]) else {
+		bail!("no `name tag` column in {}", path.display());
+	};
+	let tags_column = find_column(&["labels", "label", "tags"]);
+
+	let mut ret = vec![];
+	while let Some(row) = rows.next()? {
+		let address = row.get::<_, Option<String>>(address_column)?.unwrap_or_default();
+		let name_tag = row.get::<_, Option<String>>(name_column)?.unwrap_or_default();
+		let (address, name_tag) = (address.trim(), name_tag.trim());
+		if address.is_empty() || name_tag.is_empty() {
+			continue;
+		}
+
+		let tags = match tags_column {
+			Some(i) => row
+				.get::<_, Option<String>>(i)?
+				.unwrap_or_default()
+				.split([';', '|', ','])
+				.map(|t| t.trim().to_string())
+				.filter(|t| !t.is_empty())
+				.collect::<Vec<String>>(),
+			_ => vec![],
+		};
+
+		ret.push(Label {
+			address: address.to_string(),
+			entity: to_entity_name(name_tag),
+			description: name_tag.to_string(),
+			data: json!({ "source": "etherscan", "nameTag": name_tag, "labels": tags }),
+			tags,
+			currency: None,
+		});
+	}
+
+	Ok(ret)
+}
+
+// fields that can be set on a tagpack's header (as defaults) or on each tag
+#[derive(Debug, Clone, Default, Deserialize)]
+struct TagPackTag {
+	address: Option<String>,
+	label: Option<String>,
+	actor: Option<String>,
+	source: Option<String>,
+	currency: Option<String>,
+	category: Option<String>,
+	abuse: Option<String>,
+	confidence: Option<JsonValue>,
+	context: Option<String>,
+}
+
+impl TagPackTag {
+	fn with_defaults(self, defaults: &TagPackTag) -> Self {
+		Self {
+			address: self.address,
+			label: self.label.or(defaults.label.clone()),
+			actor: self.actor.or(defaults.actor.clone()),
+			source: self.source.or(defaults.source.clone()),
+			currency: self.currency.or(defaults.currency.clone()),
+			category: self.category.or(defaults.category.clone()),
+			abuse: self.abuse.or(defaults.abuse.clone()),
+			confidence: self.confidence.or(defaults.confidence.clone()),
+			context: self.context.or(defaults.context.clone()),
+		}
+	}
+}
+
+#[derive(Debug, Deserialize)]
+struct TagPack {
+	title: Option<String>,
+	creator: Option<String>,
+	#[serde(flatten)]
+	defaults: TagPackTag,
+	#[serde(default)]
+	tags: Vec<TagPackTag>,
+}
+
+// @NOTE entities are the tag's actor when there is one, otherwise its label.
+// categories and abuse types become tags, and the attribution itself (source,
+// confidence and where it came from) is kept on the address
+fn read_tagpack(contents: &str) -> Result<Vec<Label>> {
+	let tagpack = serde_yaml::from_str::<TagPack>(contents)?;
+
+	let mut ret = vec![];
+	for tag in tagpack.tags.into_iter() {
+		let tag = tag.with_defaults(&tagpack.defaults);
+		let (Some(address), Some(label)) = (tag.address, tag.label) else {
+			continue;
+		};
+		let (address, label) = (address.trim(), label.trim());
+		if address.is_empty() || label.is_empty() {
+			continue;
+		}
+
+		ret.push(Label {
+			address: address.to_string(),
+			entity: tag.actor.filter(|a| !a.trim().is_empty()).unwrap_or(label.to_string()),
+			description: label.to_string(),
+			tags: [&tag.category, &tag.abuse]
+				.into_iter()
+				.flatten()
+				.map(|t| t.trim().to_string())
+				.filter(|t| !t.is_empty())
+				.collect(),
+			data: json!({
+				"source": tag.source,
+				"confidence": tag.confidence,
+				"context": tag.context,
+				"tagpack": tagpack.title,
+				"creator": tagpack.creator,
+			}),
+			currency: tag.currency.map(|c| c.trim().to_uppercase()),
+		});
+	}
+
+	Ok(ret)
+}
+
+// name tags are per address (eg: `Binance 14` or `Uniswap V3: Router`), while
+// entities are per owner (`Binance` and `Uniswap V3`)
+fn to_entity_name(name_tag: &str) -> String {
+	let name = name_tag.split(':').next().unwrap_or(name_tag).trim();
+
+	match name.rsplit_once(' ') {
+		Some((prefix, suffix)) if suffix.chars().all(|c| c.is_ascii_digit()) => {
+			prefix.trim().to_string()
+		}
+		_ => name.to_string(),
+	}
+}
+
+// tag flags and risk levels for the usual label names
+fn to_tag_model(name: &str) -> TagActiveModel {
+	let lower = name.to_lowercase();
+	let risk_level = if ["phish", "hack", "heist", "exploit", "scam", "sanction", "ransom"]
+		.iter()
+		.any(|k| lower.contains(k))
+	{
+		RiskLevel::High
+	} else {
+		RiskLevel::Low
+	};
+
+	let mut model = Tag::new_model(None, name, risk_level);
+	model.is_exchange = Set(lower == "exchange" || lower.ends_with(" exchange"));
+	model.is_bridge = Set(lower == "bridge" || lower.ends_with(" bridge"));
+	model.is_mixer = Set(lower == "mixer" || lower.contains("tornado"));
+
+	model
+}
+
+// @NOTE maps labels onto entities (matched by name, created if missing),
+// tags (same) and addresses on `network`. addresses that are already labeled
+// on the network are skipped, or moved over to the imported entity
+pub async fn import(
+	app: &App,
+	network: &Network,
+	labels: Vec<Label>,
+	on_conflict: OnConflict,
+) -> Result<ImportStats> {
+	let mut stats = ImportStats::default();
+
+	// addresses are stored formatted (eg: checksummed), which doesn't need a
+	// connection. last one wins when an address is listed more than once
+	let chain = new_boxed_chain(network.clone(), &app.plugins)?;
+	let labels = labels
+		.into_iter()
+		.map(|mut l| {
+			l.address = chain.format_address(&l.address);
+			(l.address.clone(), l)
+		})
+		.collect::<HashMap<String, Label>>();
+
+	let mut entity_ids = HashMap::<String, PrimaryId>::new();
+	let mut tag_ids = HashMap::<String, PrimaryId>::new();
+	let mut new_addresses = vec![];
+
+	let labels = labels.into_values().collect::<Vec<Label>>();
+	for chunk in labels.chunks(BATCH_SIZE) {
+		let existing = Address::get_all_by_addresses(
+			app.db(),
+			chunk.iter().map(|l| l.address.clone()).collect(),
+			None,
+		)
+		.await?
+		.into_iter()
+		.filter(|a| a.network_id == network.network_id)
+		.map(|a| (a.address, a.address_id))
+		.collect::<HashMap<String, PrimaryId>>();
+
+		let mut data = vec![];
+		for label in chunk.iter() {
+			let existing_address_id = existing.get(&label.address).copied();
+			if existing_address_id.is_some() && on_conflict == OnConflict::Skip {
+				stats.addresses_skipped += 1;
+				continue;
+			}
+
+			let entity_id = match entity_ids.get(&label.entity.to_lowercase()) {
+				Some(entity_id) => *entity_id,
+				_ => {
+					let entity_id = get_or_create_entity(app, &label.entity, &mut stats).await?;
+					entity_ids.insert(label.entity.to_lowercase(), entity_id);
+					entity_id
+				}
+			};
+
+			let mut entity_tags = vec![];
+			for tag in label.tags.iter() {
+				let tag_id = match tag_ids.get(&tag.to_lowercase()) {
+					Some(tag_id) => *tag_id,
+					_ => {
+						let tag_id = get_or_create_tag(app, tag, &mut stats).await?;
+						tag_ids.insert(tag.to_lowercase(), tag_id);
+						tag_id
+					}
+				};
+
+				entity_tags.push(EntityTag::new_model(entity_id, tag_id));
+			}
+			if !entity_tags.is_empty() {
+				EntityTag::create_many(app.db(), entity_tags).await?;
+			}
+
+			match existing_address_id {
+				Some(address_id) => {
+					stats.addresses_replaced += 1;
+					Address::update_all_where(
+						app.db(),
+						AddressColumn::AddressId.eq(address_id),
+						AddressActiveModel {
+							entity_id: set(entity_id),
+							description: set(label.description.clone()),
+							data: set(label.data.clone()),
+							is_deleted: set(false),
+							..Default::default()
+						},
+					)
+					.await?;
+				}
+				_ => {
+					stats.addresses_created += 1;
+					new_addresses.push(label.address.clone());
+					data.push(Address::new_model(
+						None,
+						entity_id,
+						network.network_id,
+						&network.id,
+						&label.address,
+						&label.description,
+						Some(label.data.clone()),
+					));
+				}
+			}
+		}
+
+		if !data.is_empty() {
+			Address::create_many(app.db(), data).await?;
+		}
+	}
+
+	// tell the link step about newly created addresses
+	for chunk in new_addresses.chunks(BATCH_SIZE) {
+		Config::set_many::<_, PrimaryId>(
+			app.db(),
+			Address::get_all_by_addresses(app.db(), chunk.to_vec(), Some(false))
+				.await?
+				.into_iter()
+				.filter(|a| a.network_id == network.network_id)
+				.map(|a| (ConfigKey::NewlyAddedAddress(a.network_id, a.address_id), a.address_id))
+				.collect::<HashMap<ConfigKey, PrimaryId>>(),
+		)
+		.await?;
+	}
+
+	// invalidate cached labels
+	if stats.addresses_created + stats.addresses_replaced > 0 {
+		Config::set::<_, u8>(app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+	}
+
+	Ok(stats)
+}
+
+async fn get_or_create_entity(app: &App, name: &str, stats: &mut ImportStats) -> Result<PrimaryId> {
+	if let Some(entity) = Entity::get_by_name(app.db(), name, Some(false)).await? {
+		return Ok(entity.entity_id);
+	}
+
+	stats.entities_created += 1;
+	Entity::create(
+		app.db(),
+		Entity::new_model(None, Some(name.to_string()), "Imported label", None),
+	)
+	.await
+}
+
+async fn get_or_create_tag(app: &App, name: &str, stats: &mut ImportStats) -> Result<PrimaryId> {
+	if let Some(tag) = Tag::get_by_name(app.db(), name).await? {
+		return Ok(tag.tag_id);
+	}
+
+	stats.tags_created += 1;
+	Tag::create(app.db(), to_tag_model(name)).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_to_entity_name() {
+		let data = HashMap::from([
+			("Binance 14", "Binance"),
+			("Uniswap V3: Router", "Uniswap V3"),
+			("Uniswap V3: Router 2", "Uniswap V3"),
+			("Tornado.Cash: 100 ETH", "Tornado.Cash"),
+			("Vitalik Buterin", "Vitalik Buterin"),
+			("0x Protocol", "0x Protocol"),
+		]);
+
+		for (name_tag, entity) in data.into_iter() {
+			assert_eq!(to_entity_name(name_tag), entity);
+		}
+	}
+
+	#[test]
+	fn test_read_tagpack() {
+		let labels = read_tagpack(
+			r#"
+title: Donation addresses
+creator: Someone
+source: https://example.com/donate
+currency: BTC
+category: organization
+confidence: web_crawl
+lastmod: 2021-04-21
+tags:
+  - address: 1Archive1n2C579dMsAu3iC6tWzuQJz8dN
+    label: Internet Archive
+  - address: 0xFA8E3920daF271daB92Be9B87d9998DDd94FEF08
+    label: Internet Archive
+    currency: eth
+    abuse: scam
+    confidence: 50
+  - address: 1NoLabe1
+"#,
+		)
+		.unwrap();
+
+		assert_eq!(labels.len(), 2);
+		assert_eq!(labels[0].entity, "Internet Archive");
+		assert_eq!(labels[0].currency.as_deref(), Some("BTC"));
+		assert_eq!(labels[0].tags, vec!["organization"]);
+		assert_eq!(labels[0].data["source"], "https://example.com/donate");
+		assert_eq!(labels[0].data["confidence"], "web_crawl");
+		assert_eq!(labels[1].currency.as_deref(), Some("ETH"));
+		assert_eq!(labels[1].tags, vec!["organization", "scam"]);
+		assert_eq!(labels[1].data["confidence"], 50);
+	}
+}
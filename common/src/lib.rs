@@ -2,7 +2,7 @@ use chrono::NaiveDateTime;
 use clap::{builder::PossibleValue, ValueEnum};
 use console::{style, Emoji};
 use derive_more::Display;
-use eyre::{bail, eyre, Result};
+use eyre::Result;
 use futures::future::join_all;
 use governor::{
 	clock::DefaultClock,
@@ -21,29 +21,42 @@ use std::{
 		Arc,
 	},
 };
-use tokio::{sync::RwLock, time::Duration};
+use tokio::{
+	sync::{broadcast, RwLock},
+	time::Duration,
+};
 
 use crate::{
-	chain::{Bitcoin, BoxedChain, Evm},
-	models::{Config, ConfigKey, Network, PrimaryId, SoftDeleteModel},
+	cache::{BalanceCache, EntityCache, ResponseCache},
+	chain::{new_boxed_chain, BoxedChain, Plugins},
+	models::{Abi, BasicModel, Config, ConfigKey, Network, PrimaryId, SoftDeleteModel},
 };
-pub use db::Db;
+pub use cache::Cache;
+pub use db::{Db, PoolStats as DbPoolStats};
 pub use errors::AppError;
+pub use metrics::Metrics;
 pub use progress::{Progress, ReadyType as ProgressReadyType, Step as ProgressStep};
 pub use s3::{Service as S3Service, S3};
 pub use settings::Settings;
-pub use storage::Storage;
+pub use storage::{Storage, StoredReport};
 pub use warehouse::Warehouse;
 
+pub mod cache;
 pub mod chain;
 pub mod db;
 pub mod errors;
+pub mod labels;
+pub mod metrics;
 pub mod models;
+pub mod prices;
+pub mod producers;
 pub mod progress;
 pub mod s3;
 pub mod settings;
+pub mod snapshot;
 pub mod storage;
 pub mod utils;
+pub mod verify;
 pub mod warehouse;
 
 mod banner;
@@ -57,6 +70,9 @@ static EMOJI_QUIT: Emoji<'_, '_> = Emoji("🛑  ", "");
 pub const INDEXER_PROMOTION_TIMEOUT: u64 = 20;
 pub const INDEXER_HEARTBEAT_INTERVAL: u64 = 2;
 
+// how long an rpc endpoint that stopped responding is tried last
+const RPC_FAILURE_PERIOD: u64 = 10 * 60;
+
 pub type Warnings = Vec<String>;
 pub type BlockHeight = u64;
 pub type RateLimiter = GovernorRateLimiter<NotKeyed, InMemoryState, DefaultClock>;
@@ -65,13 +81,21 @@ pub type RateLimiter = GovernorRateLimiter<NotKeyed, InMemoryState, DefaultClock
 pub struct App {
 	pub uuid: Uuid,
 	pub networks: Arc<RwLock<HashMap<PrimaryId, Arc<BoxedChain>>>>,
+	disconnected_networks: Arc<RwLock<HashMap<PrimaryId, String>>>,
+	reconnected_networks: broadcast::Sender<PrimaryId>,
+	pub plugins: Arc<Plugins>,
 	pub settings: Arc<Settings>,
 	pub storage: Arc<Storage>,
 	db: Arc<Db>,
 	pub warehouse: Arc<Warehouse>,
+	pub entity_cache: Arc<EntityCache>,
+	pub balance_cache: Arc<BalanceCache>,
+	pub info_cache: Arc<ResponseCache>,
+	pub metrics: Arc<Metrics>,
 	is_ready: Arc<AtomicBool>,
 	is_primary: Arc<AtomicBool>,
 	connected_at: Arc<RwLock<Option<NaiveDateTime>>>,
+	pub started_at: NaiveDateTime,
 	pub cpu_count: usize,
 }
 
@@ -81,17 +105,45 @@ impl App {
 		storage: Arc<Storage>,
 		db: Arc<Db>,
 		warehouse: Arc<Warehouse>,
+		plugins: Plugins,
 	) -> Result<Self> {
+		let entity_cache = Arc::new(EntityCache::new(
+			ConfigKey::EntitiesUpdated,
+			settings.cache_ttl,
+			settings.cache_capacity,
+		));
+
+		// historical balances only change when networks get resynced or removed
+		let balance_cache = Arc::new(BalanceCache::new(
+			ConfigKey::NetworksUpdated,
+			settings.cache_ttl,
+			settings.cache_capacity,
+		));
+
+		let info_cache = Arc::new(ResponseCache::new(
+			ConfigKey::EntitiesUpdated,
+			settings.info_cache_ttl,
+			settings.cache_capacity,
+		));
+
 		let mut app = App {
 			uuid: utils::new_uuid(),
 			networks: Arc::new(RwLock::new(HashMap::new())),
+			disconnected_networks: Arc::new(RwLock::new(HashMap::new())),
+			reconnected_networks: broadcast::channel(16).0,
+			plugins: Arc::new(plugins),
 			settings,
 			storage,
 			db,
 			warehouse,
+			entity_cache,
+			balance_cache,
+			info_cache,
+			metrics: Arc::new(Metrics::default()),
 			is_ready: Arc::new(AtomicBool::new(false)),
 			is_primary: Arc::new(AtomicBool::new(false)),
 			connected_at: Arc::new(RwLock::new(None)),
+			started_at: utils::now(),
 			cpu_count: num_cpus::get(),
 		};
 
@@ -108,16 +160,18 @@ impl App {
 		Ok(self.db().begin().await?)
 	}
 
+	pub async fn db_pool_stats(&self) -> Result<DbPoolStats> {
+		self.db.get_pool_stats().await
+	}
+
 	pub async fn get_networks(&self) -> Result<HashMap<PrimaryId, Arc<BoxedChain>>> {
 		let mut ret = HashMap::new();
 
 		for n in Network::get_all_existing(self.db(), Some(false)).await?.into_iter() {
 			let network_id = n.network_id;
 
-			let boxed_chain: BoxedChain = match n.architecture {
-				Architecture::Bitcoin => Box::new(Bitcoin::new(n)),
-				Architecture::Evm => Box::new(Evm::new(n)),
-			};
+			let mut boxed_chain = new_boxed_chain(n, &self.plugins)?;
+			boxed_chain.set_rpc_counter(self.metrics.rpc_counter(network_id));
 
 			ret.insert(network_id, Arc::new(boxed_chain));
 		}
@@ -157,19 +211,32 @@ impl App {
 			pb.set_prefix(n.name.clone());
 			pb.enable_steady_tick(Duration::from_millis(50));
 
+			let abis = Abi::get_all_by_network_id(self.db(), n.network_id).await?;
+			let n = self.with_failing_rpc_endpoints(n).await?;
+
 			threads.push({
 				tokio::spawn({
-					let mut boxed_chain: BoxedChain = match n.architecture {
-						Architecture::Bitcoin => Box::new(Bitcoin::new(n.clone())),
-						Architecture::Evm => Box::new(Evm::new(n.clone())),
-					};
+					let mut boxed_chain = new_boxed_chain(n.clone(), &self.plugins)?;
+					boxed_chain.set_abis(abis);
+					boxed_chain.set_rpc_counter(self.metrics.rpc_counter(n.network_id));
+
+					let is_offline = self.settings.offline;
 
 					async move {
+						// offline, chains only process what's already been extracted
+						if is_offline {
+							if !silent {
+								pb.finish_with_message("offline");
+							}
+
+							return Ok(Arc::new(boxed_chain));
+						}
+
 						if !silent {
 							pb.set_message("connecting…");
 						}
 
-						if boxed_chain.connect().await? {
+						if boxed_chain.connect().await.unwrap_or(false) {
 							if !silent {
 								pb.finish_with_message(format!(
 									"connected to {}",
@@ -183,25 +250,44 @@ impl App {
 								pb.finish_with_message("could not connect");
 							}
 
-							Err(eyre!("{}: Could not connect to an RPC endpoint.", n.name))
+							Err(n)
 						}
 					}
 				})
 			});
 		}
 
+		let mut results = vec![];
+		for thread in join_all(threads).await.into_iter() {
+			results.push(thread?);
+		}
+
+		// @NOTE networks that can't be reached are left out (and marked as
+		// disconnected) instead of failing everything, so a bad endpoint can still
+		// be fixed through the api. the indexer retries them in the background
 		let (connected_networks, failures): (HashMap<_, _>, Vec<_>) =
-			join_all(threads).await.into_iter().partition_map(|r| match r.unwrap() {
+			results.into_iter().partition_map(|r| match r {
 				Ok(chain) => {
 					let network_id = chain.get_network().network_id;
 					Either::Left((network_id, chain))
 				}
-				Err(e) => Either::Right(e),
+				Err(n) => Either::Right(n),
 			});
 
-		if !failures.is_empty() {
-			bail!(failures.iter().map(|e| format!("- {e}")).join("\n"));
+		for network_id in connected_networks.keys() {
+			Config::delete(self.db(), ConfigKey::NetworkDisconnected(*network_id)).await?;
 		}
+		for n in failures.iter() {
+			Config::set::<_, String>(
+				self.db(),
+				ConfigKey::NetworkDisconnected(n.network_id),
+				"Could not connect to an RPC endpoint".to_string(),
+			)
+			.await?;
+		}
+
+		let mut disconnected_networks = self.disconnected_networks.write().await;
+		*disconnected_networks = failures.into_iter().map(|n| (n.network_id, n.name)).collect();
 
 		let mut networks = self.networks.write().await;
 		*networks = connected_networks;
@@ -212,6 +298,104 @@ impl App {
 		Ok(())
 	}
 
+	// @NOTE rebuilds a single network's chain (and with it, its rate limiter)
+	// after its rpc endpoint or rps changed, and swaps it in without touching
+	// the others. threads holding the old one are told to restart through
+	// `subscribe_reconnected_networks()`
+	pub async fn reconnect_network(&self, network_id: PrimaryId) -> Result<bool> {
+		let Some(n) =
+			<Network as BasicModel>::get(self.db(), network_id).await?.filter(|n| !n.is_deleted)
+		else {
+			return Ok(false);
+		};
+		let n = self.with_failing_rpc_endpoints(n).await?;
+
+		let mut boxed_chain = new_boxed_chain(n.clone(), &self.plugins)?;
+		boxed_chain.set_abis(Abi::get_all_by_network_id(self.db(), network_id).await?);
+		boxed_chain.set_rpc_counter(self.metrics.rpc_counter(network_id));
+
+		let is_connected = boxed_chain.connect().await.unwrap_or(false);
+		if is_connected {
+			Config::delete(self.db(), ConfigKey::NetworkDisconnected(network_id)).await?;
+			self.disconnected_networks.write().await.remove(&network_id);
+			self.networks.write().await.insert(network_id, Arc::new(boxed_chain));
+		} else {
+			Config::set::<_, String>(
+				self.db(),
+				ConfigKey::NetworkDisconnected(network_id),
+				"Could not connect to an RPC endpoint".to_string(),
+			)
+			.await?;
+			self.disconnected_networks.write().await.insert(network_id, n.name);
+			self.networks.write().await.remove(&network_id);
+		}
+
+		// no subscribers is fine, nothing is running for this network yet
+		self.reconnected_networks.send(network_id).ok();
+
+		Ok(is_connected)
+	}
+
+	// ids of networks whose chain got swapped out (or removed, if it could not
+	// reconnect) by `reconnect_network()`
+	pub fn subscribe_reconnected_networks(&self) -> broadcast::Receiver<PrimaryId> {
+		self.reconnected_networks.subscribe()
+	}
+
+	// @NOTE called when a network's requests fail. if reconnecting ends up on a
+	// different rpc endpoint, the previous one stopped responding: it's
+	// recorded as failing (so it's tried last for a while) and the network
+	// carries on with the next one. returns whether that happened
+	pub async fn failover_network(&self, network_id: PrimaryId) -> Result<bool> {
+		let Some(previous_rpc) =
+			self.networks.read().await.get(&network_id).and_then(|chain| chain.get_rpc())
+		else {
+			return Ok(false);
+		};
+
+		self.reconnect_network(network_id).await?;
+		let rpc = self.networks.read().await.get(&network_id).and_then(|chain| chain.get_rpc());
+		if rpc == Some(previous_rpc.clone()) {
+			return Ok(false);
+		}
+
+		let config_key = ConfigKey::NetworkRpcFailures(network_id);
+		let mut failures = Config::get::<_, HashMap<String, u32>>(self.db(), config_key)
+			.await?
+			.map(|v| v.value)
+			.unwrap_or_default();
+		failures.insert(
+			utils::with_masked_auth(&previous_rpc),
+			utils::now().and_utc().timestamp() as u32,
+		);
+		Config::set::<_, HashMap<String, u32>>(self.db(), config_key, failures).await?;
+
+		Ok(true)
+	}
+
+	async fn with_failing_rpc_endpoints(&self, mut n: Network) -> Result<Network> {
+		let failed_since = utils::ago_in_seconds(RPC_FAILURE_PERIOD).and_utc().timestamp() as u32;
+
+		n.failing_rpc_endpoints = Config::get::<_, HashMap<String, u32>>(
+			self.db(),
+			ConfigKey::NetworkRpcFailures(n.network_id),
+		)
+		.await?
+		.map(|v| v.value)
+		.unwrap_or_default()
+		.into_iter()
+		.filter(|(_, failed_at)| *failed_at >= failed_since)
+		.map(|(rpc_endpoint, _)| rpc_endpoint)
+		.collect();
+
+		Ok(n)
+	}
+
+	// names of networks that could not be connected to on the last attempt
+	pub async fn get_disconnected_networks(&self) -> HashMap<PrimaryId, String> {
+		self.disconnected_networks.read().await.clone()
+	}
+
 	pub async fn get_warnings(&self) -> Result<Warnings> {
 		let mut warnings = Warnings::new();
 
@@ -291,6 +475,24 @@ pub enum IdPrefix {
 	Tag,
 	#[display("tok")]
 	Token,
+	#[display("nft")]
+	Nft,
+	#[display("alr")]
+	Alert,
+	#[display("ann")]
+	Annotation,
+	#[display("ses")]
+	Session,
+	#[display("abi")]
+	Abi,
+	#[display("rpt")]
+	Report,
+	#[display("sch")]
+	ReportSchedule,
+	#[display("cat")]
+	Category,
+	#[display("whk")]
+	Webhook,
 }
 
 #[derive(
@@ -324,11 +526,75 @@ impl strum::IntoEnumIterator for RiskLevel {
 	}
 }
 
+#[derive(Debug, DeriveActiveEnum, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
+#[serde(rename_all = "camelCase")]
+pub enum AlertKind {
+	// worth more (in usd) than the network's fixed threshold
+	LargeTransfer = 1,
+	// far above what the address usually sends
+	UnusualTransfer = 2,
+	// first outgoing transfer after the network's dormancy period
+	DormantReactivated = 3,
+	// warehouse balance doesn't match what the node reports
+	BalanceDrift = 4,
+}
+
+// @TODO for some reason `EnumIter` in sea-orm v1.0.0 doesn't work
+impl strum::IntoEnumIterator for AlertKind {
+	type Iterator = std::array::IntoIter<AlertKind, 4>;
+
+	fn iter() -> Self::Iterator {
+		[
+			AlertKind::LargeTransfer,
+			AlertKind::UnusualTransfer,
+			AlertKind::DormantReactivated,
+			AlertKind::BalanceDrift,
+		]
+		.into_iter()
+	}
+}
+
+#[derive(Debug, DeriveActiveEnum, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
+#[serde(rename_all = "camelCase")]
+pub enum AnnotationKind {
+	// part of a peel chain (input or change address of a hop)
+	PeelChain = 1,
+	// sent funds into a mixer (reference is the mixer address)
+	MixerDeposit = 2,
+	// received funds from a mixer (reference is the likely depositor if
+	// denomination and timing single one out, otherwise the mixer address)
+	MixerWithdrawal = 3,
+	// swept its whole balance into an exchange (reference is the hot wallet)
+	ExchangeDeposit = 4,
+	// received the output of a dex swap (reference is the pool)
+	Swap = 5,
+}
+
+// @TODO for some reason `EnumIter` in sea-orm v1.0.0 doesn't work
+impl strum::IntoEnumIterator for AnnotationKind {
+	type Iterator = std::array::IntoIter<AnnotationKind, 5>;
+
+	fn iter() -> Self::Iterator {
+		[
+			AnnotationKind::PeelChain,
+			AnnotationKind::MixerDeposit,
+			AnnotationKind::MixerWithdrawal,
+			AnnotationKind::ExchangeDeposit,
+			AnnotationKind::Swap,
+		]
+		.into_iter()
+	}
+}
+
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum RiskReason {
 	Entity,
 	Source,
+	Mixer,
+	Approval,
 }
 
 #[derive(Default, Debug, DeriveActiveEnum, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -338,14 +604,29 @@ pub enum Architecture {
 	#[default]
 	Bitcoin = 1,
 	Evm = 2,
+	Solana = 3,
+	Tron = 4,
+}
+
+impl Architecture {
+	// decimals of the network's native asset
+	pub fn native_decimals(&self) -> u16 {
+		match self {
+			Architecture::Bitcoin => 8,
+			Architecture::Evm => 18,
+			Architecture::Solana => 9,
+			Architecture::Tron => 6,
+		}
+	}
 }
 
 // @TODO for some reason `EnumIter` in sea-orm v1.0.0 doesn't work
 impl strum::IntoEnumIterator for Architecture {
-	type Iterator = std::array::IntoIter<Architecture, 2>;
+	type Iterator = std::array::IntoIter<Architecture, 4>;
 
 	fn iter() -> Self::Iterator {
-		[Architecture::Bitcoin, Architecture::Evm].into_iter()
+		[Architecture::Bitcoin, Architecture::Evm, Architecture::Solana, Architecture::Tron]
+			.into_iter()
 	}
 }
 
@@ -0,0 +1,230 @@
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt::Write,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc, Mutex,
+	},
+	time::Duration,
+};
+
+use crate::models::PrimaryId;
+
+// upper bounds of histogram buckets, in seconds
+const BUCKETS: [f64; 12] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+pub type Counter = Arc<AtomicU64>;
+
+#[derive(Default, Clone)]
+struct Histogram {
+	buckets: [u64; BUCKETS.len()],
+	count: u64,
+	sum: f64,
+}
+
+impl Histogram {
+	fn observe(&mut self, duration: Duration) {
+		let seconds = duration.as_secs_f64();
+		for (i, le) in BUCKETS.iter().enumerate() {
+			if seconds <= *le {
+				self.buckets[i] += 1;
+			}
+		}
+
+		self.count += 1;
+		self.sum += seconds;
+	}
+
+	fn render(&self, out: &mut String, name: &str, labels: &str) {
+		let (sep, suffix) =
+			if labels.is_empty() { ("", String::new()) } else { (",", format!("{{{labels}}}")) };
+
+		for (le, count) in BUCKETS.iter().zip(self.buckets.iter()) {
+			let _ = writeln!(out, "{name}_bucket{{{labels}{sep}le=\"{le}\"}} {count}");
+		}
+		let _ = writeln!(out, "{name}_bucket{{{labels}{sep}le=\"+Inf\"}} {}", self.count);
+		let _ = writeln!(out, "{name}_sum{suffix} {}", self.sum);
+		let _ = writeln!(out, "{name}_count{suffix} {}", self.count);
+	}
+}
+
+// indexing progress of a network, as stored by the indexer
+pub struct NetworkProgress {
+	pub network_id: PrimaryId,
+	pub name: String,
+	pub synced: f64,
+	pub processed: f64,
+}
+
+// @NOTE process-local metrics, rendered in prometheus' text format. counters
+// start over on restart (which prometheus handles), and with several
+// instances each one only reports what it did itself, so all of them need to
+// be scraped
+#[derive(Default)]
+pub struct Metrics {
+	rpc_requests: Mutex<HashMap<PrimaryId, Counter>>,
+	warehouse_commits: Mutex<Histogram>,
+	warehouse_commit_failures: AtomicU64,
+	http_requests: Mutex<BTreeMap<(String, String, u16), Histogram>>,
+}
+
+impl Metrics {
+	// counter that a network's chain bumps on every rpc request. it's shared
+	// across reconnects, so counts don't reset when a network is rebuilt
+	pub fn rpc_counter(&self, network_id: PrimaryId) -> Counter {
+		self.rpc_requests
+			.lock()
+			.unwrap_or_else(|e| e.into_inner())
+			.entry(network_id)
+			.or_default()
+			.clone()
+	}
+
+	pub fn observe_warehouse_commit(&self, duration: Duration, is_success: bool) {
+		if is_success {
+			self.warehouse_commits.lock().unwrap_or_else(|e| e.into_inner()).observe(duration);
+		} else {
+			self.warehouse_commit_failures.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	pub fn observe_http_request(&self, method: &str, route: &str, status: u16, duration: Duration) {
+		self.http_requests
+			.lock()
+			.unwrap_or_else(|e| e.into_inner())
+			.entry((method.to_string(), route.to_string(), status))
+			.or_default()
+			.observe(duration);
+	}
+
+	pub fn render(&self, networks: Vec<NetworkProgress>) -> String {
+		let mut out = String::new();
+
+		let header = |out: &mut String, name: &str, kind: &str, help: &str| {
+			let _ = writeln!(out, "# HELP {name} {help}");
+			let _ = writeln!(out, "# TYPE {name} {kind}");
+		};
+
+		header(&mut out, "barreleye_sync_progress", "gauge", "Share of blocks extracted (0 to 1)");
+		for n in networks.iter() {
+			let _ = writeln!(
+				out,
+				"barreleye_sync_progress{{network=\"{}\"}} {}",
+				escape(&n.name),
+				n.synced
+			);
+		}
+
+		header(
+			&mut out,
+			"barreleye_process_progress",
+			"gauge",
+			"Share of blocks processed (0 to 1)",
+		);
+		for n in networks.iter() {
+			let _ = writeln!(
+				out,
+				"barreleye_process_progress{{network=\"{}\"}} {}",
+				escape(&n.name),
+				n.processed
+			);
+		}
+
+		header(&mut out, "barreleye_rpc_requests_total", "counter", "RPC requests made");
+		let rpc_requests = self.rpc_requests.lock().unwrap_or_else(|e| e.into_inner()).clone();
+		for n in networks.iter() {
+			if let Some(counter) = rpc_requests.get(&n.network_id) {
+				let _ = writeln!(
+					out,
+					"barreleye_rpc_requests_total{{network=\"{}\"}} {}",
+					escape(&n.name),
+					counter.load(Ordering::Relaxed)
+				);
+			}
+		}
+
+		header(
+			&mut out,
+			"barreleye_warehouse_commit_duration_seconds",
+			"histogram",
+			"Time taken by successful warehouse commits",
+		);
+		self.warehouse_commits.lock().unwrap_or_else(|e| e.into_inner()).clone().render(
+			&mut out,
+			"barreleye_warehouse_commit_duration_seconds",
+			"",
+		);
+
+		header(
+			&mut out,
+			"barreleye_warehouse_commit_failures_total",
+			"counter",
+			"Warehouse commits that failed",
+		);
+		let _ = writeln!(
+			out,
+			"barreleye_warehouse_commit_failures_total {}",
+			self.warehouse_commit_failures.load(Ordering::Relaxed)
+		);
+
+		header(
+			&mut out,
+			"barreleye_http_request_duration_seconds",
+			"histogram",
+			"Time taken to handle HTTP requests",
+		);
+		let http_requests = self.http_requests.lock().unwrap_or_else(|e| e.into_inner()).clone();
+		for ((method, route, status), histogram) in http_requests.iter() {
+			histogram.render(
+				&mut out,
+				"barreleye_http_request_duration_seconds",
+				&format!(
+					"method=\"{}\",route=\"{}\",status=\"{status}\"",
+					escape(method),
+					escape(route)
+				),
+			);
+		}
+
+		out
+	}
+}
+
+fn escape(value: &str) -> String {
+	value.replace('\\', r"\\").replace('"', "\\\"").replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_render() {
+		let metrics = Metrics::default();
+		metrics.rpc_counter(1).fetch_add(3, Ordering::Relaxed);
+		metrics.observe_warehouse_commit(Duration::from_millis(200), true);
+		metrics.observe_warehouse_commit(Duration::from_millis(200), false);
+		metrics.observe_http_request("GET", "/v1/stats", 200, Duration::from_millis(20));
+
+		let out = metrics.render(vec![NetworkProgress {
+			network_id: 1,
+			name: "Ethereum \"Mainnet\"".to_string(),
+			synced: 1.0,
+			processed: 0.5,
+		}]);
+
+		assert!(
+			out.contains("barreleye_process_progress{network=\"Ethereum \\\"Mainnet\\\"\"} 0.5")
+		);
+		assert!(
+			out.contains("barreleye_rpc_requests_total{network=\"Ethereum \\\"Mainnet\\\"\"} 3")
+		);
+		assert!(out.contains("barreleye_warehouse_commit_duration_seconds_bucket{le=\"0.1\"} 0"));
+		assert!(out.contains("barreleye_warehouse_commit_duration_seconds_bucket{le=\"0.25\"} 1"));
+		assert!(out.contains("barreleye_warehouse_commit_failures_total 1"));
+		assert!(out.contains(
+			"barreleye_http_request_duration_seconds_count{method=\"GET\",route=\"/v1/stats\",\
+			 status=\"200\"} 1"
+		));
+	}
+}
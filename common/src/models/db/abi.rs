@@ -0,0 +1,90 @@
+use eyre::Result;
+use sea_orm::{
+	entity::{prelude::*, *},
+	ConnectionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::{
+	models::{BasicModel, PrimaryId, PrimaryIds},
+	utils, IdPrefix,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "abis")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub abi_id: PrimaryId,
+	#[serde(skip_serializing)]
+	pub network_id: PrimaryId,
+	pub id: String,
+	pub name: String,
+	pub address: String,
+	pub abi: Json,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+}
+
+impl From<Vec<Model>> for PrimaryIds {
+	fn from(m: Vec<Model>) -> PrimaryIds {
+		let ids: HashSet<PrimaryId> = m.iter().map(|m| m.abi_id).collect();
+		PrimaryIds(ids.into_iter().collect())
+	}
+}
+
+pub use ActiveModel as AbiActiveModel;
+pub use Model as Abi;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+impl Model {
+	pub fn new_model(
+		id: Option<String>,
+		network_id: PrimaryId,
+		name: &str,
+		address: &str,
+		abi: Json,
+	) -> ActiveModel {
+		ActiveModel {
+			id: Set(id.unwrap_or(utils::new_unique_id(IdPrefix::Abi))),
+			network_id: Set(network_id),
+			name: Set(name.to_string()),
+			address: Set(address.to_string()),
+			abi: Set(abi),
+			..Default::default()
+		}
+	}
+
+	// only evm contract abis are supported
+	#[cfg_attr(not(feature = "evm"), allow(unused_variables))]
+	pub fn is_valid_abi(abi: &Json) -> bool {
+		#[cfg(feature = "evm")]
+		{
+			serde_json::from_value::<ethers::abi::Abi>(abi.clone()).is_ok()
+		}
+
+		#[cfg(not(feature = "evm"))]
+		{
+			false
+		}
+	}
+
+	pub async fn get_all_by_network_id<C>(c: &C, network_id: PrimaryId) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find().filter(Column::NetworkId.eq(network_id)).all(c).await?)
+	}
+}
@@ -0,0 +1,128 @@
+use eyre::Result;
+use sea_orm::{
+	entity::{prelude::*, *},
+	ConnectionTrait, QueryOrder, QuerySelect,
+};
+use sea_orm_migration::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::{BasicModel, PrimaryId},
+	utils, AlertKind, BlockHeight, IdPrefix,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "alerts")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub alert_id: PrimaryId,
+	#[serde(skip_serializing)]
+	pub network_id: PrimaryId,
+	pub id: String,
+	pub kind: AlertKind,
+	pub block_height: i64,
+	pub tx_hash: String,
+	pub address: String,
+	pub asset_address: String,
+	pub amount: String,
+	pub reason: String,
+	pub is_acknowledged: bool,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+}
+
+pub use ActiveModel as AlertActiveModel;
+pub use Model as Alert;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+impl Model {
+	#[allow(clippy::too_many_arguments)]
+	pub fn new_model(
+		network_id: PrimaryId,
+		kind: AlertKind,
+		block_height: BlockHeight,
+		tx_hash: &str,
+		address: &str,
+		asset_address: &str,
+		amount: String,
+		reason: String,
+	) -> ActiveModel {
+		ActiveModel {
+			id: Set(utils::new_unique_id(IdPrefix::Alert)),
+			network_id: Set(network_id),
+			kind: Set(kind),
+			block_height: Set(block_height as i64),
+			tx_hash: Set(tx_hash.to_string()),
+			address: Set(address.to_string()),
+			asset_address: Set(asset_address.to_string()),
+			amount: Set(amount),
+			reason: Set(reason),
+			is_acknowledged: Set(false),
+			..Default::default()
+		}
+	}
+
+	// re-processed blocks don't raise the same alert twice
+	pub async fn create_many<C>(c: &C, data: Vec<ActiveModel>) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		Entity::insert_many(data)
+			.on_conflict(
+				OnConflict::columns([
+					Column::NetworkId,
+					Column::Kind,
+					Column::TxHash,
+					Column::Address,
+					Column::AssetAddress,
+				])
+				.do_nothing()
+				.to_owned(),
+			)
+			.do_nothing()
+			.exec(c)
+			.await?;
+
+		Ok(())
+	}
+
+	pub async fn get_all_paginated_by_network_id<C>(
+		c: &C,
+		network_id: Option<PrimaryId>,
+		is_acknowledged: Option<bool>,
+		offset: Option<u64>,
+		limit: Option<u64>,
+	) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		let mut q = Entity::find().order_by_desc(Column::AlertId);
+
+		if let Some(network_id) = network_id {
+			q = q.filter(Column::NetworkId.eq(network_id));
+		}
+		if let Some(is_acknowledged) = is_acknowledged {
+			q = q.filter(Column::IsAcknowledged.eq(is_acknowledged));
+		}
+		if let Some(v) = offset {
+			q = q.offset(v);
+		}
+		if let Some(v) = limit {
+			q = q.limit(v);
+		}
+
+		Ok(q.all(c).await?)
+	}
+}
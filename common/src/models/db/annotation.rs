@@ -0,0 +1,100 @@
+use eyre::Result;
+use sea_orm::{
+	entity::{prelude::*, *},
+	ConnectionTrait,
+};
+use sea_orm_migration::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::{BasicModel, PrimaryId},
+	utils, AnnotationKind, IdPrefix,
+};
+
+// @NOTE heuristic labels derived from on-chain behavior (as opposed to
+// user-defined entities and tags)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "annotations")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub annotation_id: PrimaryId,
+	#[serde(skip_serializing)]
+	pub network_id: PrimaryId,
+	pub id: String,
+	pub address: String,
+	pub kind: AnnotationKind,
+	pub reference: String,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+}
+
+pub use ActiveModel as AnnotationActiveModel;
+pub use Model as Annotation;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+impl Model {
+	pub fn new_model(
+		network_id: PrimaryId,
+		address: &str,
+		kind: AnnotationKind,
+		reference: &str,
+	) -> ActiveModel {
+		ActiveModel {
+			id: Set(utils::new_unique_id(IdPrefix::Annotation)),
+			network_id: Set(network_id),
+			address: Set(address.to_string()),
+			kind: Set(kind),
+			reference: Set(reference.to_string()),
+			..Default::default()
+		}
+	}
+
+	// an address keeps the first reference it was annotated with
+	pub async fn create_many<C>(c: &C, data: Vec<ActiveModel>) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		Entity::insert_many(data)
+			.on_conflict(
+				OnConflict::columns([Column::NetworkId, Column::Address, Column::Kind])
+					.do_nothing()
+					.to_owned(),
+			)
+			.do_nothing()
+			.exec(c)
+			.await?;
+
+		Ok(())
+	}
+
+	pub async fn get_all_by_addresses<C>(
+		c: &C,
+		network_id: Option<PrimaryId>,
+		mut addresses: Vec<String>,
+	) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		addresses.sort_unstable();
+		addresses.dedup();
+
+		let mut q = Entity::find().filter(Column::Address.is_in(addresses));
+		if let Some(network_id) = network_id {
+			q = q.filter(Column::NetworkId.eq(network_id));
+		}
+
+		Ok(q.all(c).await?)
+	}
+}
@@ -7,7 +7,7 @@ use sea_orm::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-	models::{BasicModel, PrimaryId},
+	models::{ApiKeyUsage, BasicModel, PrimaryId, UsageSummary},
 	utils, IdPrefix,
 };
 
@@ -25,12 +25,17 @@ pub struct Model {
 	pub secret_key_hash: Vec<u8>,
 	pub is_active: bool,
 	#[sea_orm(nullable)]
+	pub client_identity: Option<String>,
+	#[sea_orm(nullable)]
 	#[serde(skip_serializing)]
 	pub updated_at: Option<DateTime>,
 	pub created_at: DateTime,
 
 	#[sea_orm(ignore)]
 	pub key: Option<String>,
+	#[sea_orm(ignore)]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub usage: Option<UsageSummary>,
 }
 
 pub use ActiveModel as ApiKeyActiveModel;
@@ -46,7 +51,7 @@ impl BasicModel for Model {
 }
 
 impl Model {
-	pub fn new_model(id: Option<String>) -> ActiveModel {
+	pub fn new_model(id: Option<String>, client_identity: Option<String>) -> ActiveModel {
 		let (secret_key, secret_key_hash) = Self::generate_key();
 
 		ActiveModel {
@@ -54,6 +59,7 @@ impl Model {
 			secret_key: Set(Some(format!("sk_{secret_key}"))),
 			secret_key_hash: Set(secret_key_hash),
 			is_active: Set(true),
+			client_identity: Set(client_identity),
 			..Default::default()
 		}
 	}
@@ -79,6 +85,28 @@ impl Model {
 		Ok(Entity::find().filter(Column::SecretKeyHash.eq(secret_key_hash)).one(c).await?)
 	}
 
+	// identity of a client certificate (eg: its common name) mapped to a key
+	pub async fn get_by_client_identity<C>(c: &C, client_identity: &str) -> Result<Option<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find().filter(Column::ClientIdentity.eq(client_identity)).one(c).await?)
+	}
+
+	// adds request counts, last-used time and top endpoints to each key
+	pub async fn with_usage<C>(c: &C, api_keys: Vec<Self>) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		let mut summaries =
+			ApiKeyUsage::get_summaries(c, api_keys.iter().map(|k| k.api_key_id).collect()).await?;
+
+		Ok(api_keys
+			.into_iter()
+			.map(|k| Self { usage: Some(summaries.remove(&k.api_key_id).unwrap_or_default()), ..k })
+			.collect())
+	}
+
 	pub fn format(&self) -> Self {
 		let mut key = None;
 		if let Some(secret_key) = self.secret_key.clone() {
@@ -0,0 +1,129 @@
+use eyre::Result;
+use sea_orm::{
+	entity::{prelude::*, *},
+	ConnectionTrait, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+	models::{BasicModel, PrimaryId},
+	utils,
+};
+
+// how many endpoints are listed in a key's usage summary
+const TOP_ENDPOINTS: usize = 5;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "api_key_usage")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub api_key_usage_id: PrimaryId,
+	#[serde(skip_serializing)]
+	pub api_key_id: PrimaryId,
+	pub endpoint: String,
+	pub request_count: i64,
+	pub last_used_at: DateTime,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+}
+
+pub use ActiveModel as ApiKeyUsageActiveModel;
+pub use Model as ApiKeyUsage;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointUsage {
+	pub endpoint: String,
+	pub request_count: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+	pub request_count: i64,
+	pub last_used_at: Option<DateTime>,
+	pub top_endpoints: Vec<EndpointUsage>,
+}
+
+impl Model {
+	pub async fn increment<C>(
+		c: &C,
+		api_key_id: PrimaryId,
+		endpoint: &str,
+		request_count: i64,
+		last_used_at: DateTime,
+	) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		let update_result = Entity::update_many()
+			.col_expr(Column::RequestCount, Expr::col(Column::RequestCount).add(request_count))
+			.col_expr(Column::LastUsedAt, Expr::value(last_used_at))
+			.col_expr(Column::UpdatedAt, Expr::value(utils::now()))
+			.filter(Column::ApiKeyId.eq(api_key_id))
+			.filter(Column::Endpoint.eq(endpoint))
+			.exec(c)
+			.await?;
+
+		if update_result.rows_affected == 0 {
+			Self::create(
+				c,
+				ActiveModel {
+					api_key_id: Set(api_key_id),
+					endpoint: Set(endpoint.to_string()),
+					request_count: Set(request_count),
+					last_used_at: Set(last_used_at),
+					..Default::default()
+				},
+			)
+			.await?;
+		}
+
+		Ok(())
+	}
+
+	pub async fn get_summaries<C>(
+		c: &C,
+		api_key_ids: Vec<PrimaryId>,
+	) -> Result<HashMap<PrimaryId, UsageSummary>>
+	where
+		C: ConnectionTrait,
+	{
+		let mut ret = HashMap::<PrimaryId, UsageSummary>::new();
+
+		let rows = Entity::find()
+			.filter(Column::ApiKeyId.is_in(api_key_ids))
+			.order_by_desc(Column::RequestCount)
+			.all(c)
+			.await?;
+
+		for row in rows.into_iter() {
+			let summary = ret.entry(row.api_key_id).or_default();
+
+			summary.request_count += row.request_count;
+			summary.last_used_at = summary.last_used_at.max(Some(row.last_used_at));
+			if summary.top_endpoints.len() < TOP_ENDPOINTS {
+				summary.top_endpoints.push(EndpointUsage {
+					endpoint: row.endpoint,
+					request_count: row.request_count,
+				});
+			}
+		}
+
+		Ok(ret)
+	}
+}
@@ -0,0 +1,92 @@
+use eyre::Result;
+use sea_orm::{entity::prelude::*, Condition, ConnectionTrait};
+use sea_orm_migration::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{BasicModel, PrimaryId};
+
+// @NOTE funds that went into a bridge on one network and came out of the same
+// bridge on another. `entity_id` is the bridge, and amounts are kept as they
+// were on each side (assets and decimals don't have to match)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "bridge_transfers")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub bridge_transfer_id: PrimaryId,
+	#[serde(skip_serializing)]
+	pub entity_id: PrimaryId,
+	#[serde(skip_serializing)]
+	pub from_network_id: PrimaryId,
+	pub from_block_height: i64,
+	pub from_tx_hash: String,
+	pub from_address: String,
+	pub from_amount: String,
+	#[serde(skip_serializing)]
+	pub to_network_id: PrimaryId,
+	pub to_block_height: i64,
+	pub to_tx_hash: String,
+	pub to_address: String,
+	pub to_amount: String,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+}
+
+pub use ActiveModel as BridgeTransferActiveModel;
+pub use Model as BridgeTransfer;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+impl Model {
+	pub async fn create_many<C>(c: &C, data: Vec<ActiveModel>) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		Entity::insert_many(data)
+			.on_conflict(
+				OnConflict::columns([Column::ToNetworkId, Column::ToTxHash, Column::ToAddress])
+					.do_nothing()
+					.to_owned(),
+			)
+			.do_nothing()
+			.exec(c)
+			.await?;
+
+		Ok(())
+	}
+
+	// bridge transfers by where they came out: (network_id, tx_hash, address)
+	pub async fn get_all_by_releases<C>(
+		c: &C,
+		releases: Vec<(PrimaryId, String, String)>,
+	) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		if releases.is_empty() {
+			return Ok(vec![]);
+		}
+
+		let mut condition = Condition::any();
+		for (network_id, tx_hash, address) in releases.into_iter() {
+			condition = condition.add(
+				Condition::all()
+					.add(Column::ToNetworkId.eq(network_id))
+					.add(Column::ToTxHash.eq(tx_hash))
+					.add(Column::ToAddress.eq(address)),
+			);
+		}
+
+		Ok(Entity::find().filter(condition).all(c).await?)
+	}
+}
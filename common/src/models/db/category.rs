@@ -0,0 +1,86 @@
+use eyre::Result;
+use sea_orm::{
+	entity::{prelude::*, *},
+	ConnectionTrait,
+};
+use sea_orm_migration::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::{
+	models::{BasicModel, PrimaryId, PrimaryIds},
+	utils, IdPrefix,
+};
+
+// highest severity a category can have
+pub const MAX_SEVERITY: i16 = 100;
+
+// @NOTE reporting categories are defined per deployment (eg: "sanctions",
+// "darknet market", "gambling") and tags are mapped to them. a category's
+// `severity` (0-100) is what the risk engine uses to rank exposure, on top of
+// the fixed `RiskLevel` each tag has
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "categories")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub category_id: PrimaryId,
+	pub id: String,
+	pub name: String,
+	pub severity: i16,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+
+	#[sea_orm(ignore)]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub tags: Option<Vec<String>>,
+}
+
+impl From<Vec<Model>> for PrimaryIds {
+	fn from(m: Vec<Model>) -> PrimaryIds {
+		let ids: HashSet<PrimaryId> = m.iter().map(|m| m.category_id).collect();
+		PrimaryIds(ids.into_iter().collect())
+	}
+}
+
+pub use ActiveModel as CategoryActiveModel;
+pub use Model as Category;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+impl Model {
+	pub fn new_model(id: Option<String>, name: &str, severity: i16) -> ActiveModel {
+		ActiveModel {
+			id: Set(id.unwrap_or(utils::new_unique_id(IdPrefix::Category))),
+			name: Set(name.to_string()),
+			severity: Set(severity),
+			..Default::default()
+		}
+	}
+
+	pub fn is_valid_severity(severity: i16) -> bool {
+		(0..=MAX_SEVERITY).contains(&severity)
+	}
+
+	pub async fn get_by_name<C>(c: &C, name: &str) -> Result<Option<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find()
+			.filter(Condition::all().add(
+				Expr::expr(Func::lower(Expr::col(Column::Name))).eq(name.trim().to_lowercase()),
+			))
+			.one(c)
+			.await?)
+	}
+}
@@ -1,11 +1,14 @@
 use derive_more::Display;
-use eyre::Result;
+use eyre::{bail, Result, WrapErr};
 use regex::Regex;
-use sea_orm::{entity::prelude::*, Condition, ConnectionTrait, Set};
+use sea_orm::{entity::prelude::*, Condition, ConnectionTrait, QueryOrder, Set};
 use sea_orm_migration::prelude::{Expr, OnConflict};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::{
+	collections::{BTreeMap, HashMap},
+	str::FromStr,
+};
 
 use crate::{models::PrimaryId, utils, BlockHeight};
 
@@ -24,6 +27,8 @@ pub enum ConfigKey {
 	IndexerSyncChunk(PrimaryId, BlockHeight),
 	#[display("indexer_sync_progress_n{_0}")]
 	IndexerSyncProgress(PrimaryId),
+	#[display("indexer_sync_block_hashes_n{_0}")]
+	IndexerSyncBlockHashes(PrimaryId),
 	#[display("indexer_process_tail_n{_0}")]
 	IndexerProcessTail(PrimaryId),
 	#[display("indexer_process_chunk_n{_0}_b{_1}")]
@@ -34,30 +39,65 @@ pub enum ConfigKey {
 	IndexerProcessModuleDone(PrimaryId, u16),
 	#[display("indexer_process_progress_n{_0}")]
 	IndexerProcessProgress(PrimaryId),
+	#[display("indexer_process_backfill_n{_0}_b{_1}")]
+	IndexerProcessBackfill(PrimaryId, BlockHeight),
 	#[display("indexer_link_n{_0}_a{_1}")]
 	IndexerLink(PrimaryId, PrimaryId),
+	#[display("indexer_peel_n{_0}")]
+	IndexerPeel(PrimaryId),
+	#[display("indexer_mixer_n{_0}")]
+	IndexerMixer(PrimaryId),
+	#[display("indexer_deposit_n{_0}")]
+	IndexerDeposit(PrimaryId),
+	#[display("indexer_producer_n{_0}")]
+	IndexerProducer(PrimaryId),
+	#[display("indexer_bridge_n{_0}")]
+	IndexerBridge(PrimaryId),
+	#[display("indexer_compact_n{_0}")]
+	IndexerCompact(PrimaryId),
+	#[display("indexer_reconcile_n{_0}")]
+	IndexerReconcile(PrimaryId),
+	#[display("indexer_prune")]
+	IndexerPrune,
+	#[display("indexer_optimize")]
+	IndexerOptimize,
+	#[display("indexer_process_batch")]
+	IndexerProcessBatch,
+	#[display("indexer_link_batch")]
+	IndexerLinkBatch,
 	#[display("block_height_n{_0}")]
 	BlockHeight(PrimaryId),
 	#[display("networks_updated")]
 	NetworksUpdated,
+	#[display("network_disconnected_n{_0}")]
+	NetworkDisconnected(PrimaryId),
+	#[display("network_rpc_updated_n{_0}")]
+	NetworkRpcUpdated(PrimaryId),
+	#[display("network_rpc_failures_n{_0}")]
+	NetworkRpcFailures(PrimaryId),
+	#[display("entities_updated")]
+	EntitiesUpdated,
 	#[display("newly_added_address_n{_0}_a{_1}")]
 	NewlyAddedAddress(PrimaryId, PrimaryId),
 }
 
-impl From<String> for ConfigKey {
-	fn from(s: String) -> Self {
+impl FromStr for ConfigKey {
+	type Err = eyre::Report;
+
+	fn from_str(s: &str) -> Result<Self> {
 		let re = Regex::new(r"(\d+)").unwrap();
 
-		let template = re.replace_all(&s, "{}");
-		let n = re.find_iter(&s).filter_map(|n| n.as_str().parse().ok()).collect::<Vec<i64>>();
+		let template = re.replace_all(s, "{}");
+		let n = re.find_iter(s).filter_map(|n| n.as_str().parse().ok()).collect::<Vec<i64>>();
 
-		match template.to_string().as_str() {
+		Ok(match template.to_string().as_str() {
 			"primary" => Self::Primary,
 			"indexer_sync_tail_n{}" if n.len() == 1 => Self::IndexerSyncTail(n[0]),
 			"indexer_sync_chunk_n{}_b{}" if n.len() == 2 => {
 				Self::IndexerSyncChunk(n[0], n[1] as BlockHeight)
 			}
 			"indexer_sync_progress_n{}" if n.len() == 1 => Self::IndexerSyncProgress(n[0]),
+			"indexer_sync_block_hashes_n{}" if n.len() == 1 => Self::IndexerSyncBlockHashes(n[0]),
 			"indexer_process_tail_n{}" if n.len() == 1 => Self::IndexerProcessTail(n[0]),
 			"indexer_process_chunk_n{}_b{}" if n.len() == 2 => {
 				Self::IndexerProcessChunk(n[0], n[1] as BlockHeight)
@@ -69,12 +109,82 @@ impl From<String> for ConfigKey {
 				Self::IndexerProcessModuleDone(n[0], n[1] as u16)
 			}
 			"indexer_process_progress_n{}" if n.len() == 1 => Self::IndexerProcessProgress(n[0]),
+			"indexer_process_backfill_n{}_b{}" if n.len() == 2 => {
+				Self::IndexerProcessBackfill(n[0], n[1] as BlockHeight)
+			}
 			"indexer_link_n{}_a{}" if n.len() == 2 => Self::IndexerLink(n[0], n[1]),
+			"indexer_peel_n{}" if n.len() == 1 => Self::IndexerPeel(n[0]),
+			"indexer_mixer_n{}" if n.len() == 1 => Self::IndexerMixer(n[0]),
+			"indexer_deposit_n{}" if n.len() == 1 => Self::IndexerDeposit(n[0]),
+			"indexer_producer_n{}" if n.len() == 1 => Self::IndexerProducer(n[0]),
+			"indexer_bridge_n{}" if n.len() == 1 => Self::IndexerBridge(n[0]),
+			"indexer_compact_n{}" if n.len() == 1 => Self::IndexerCompact(n[0]),
+			"indexer_reconcile_n{}" if n.len() == 1 => Self::IndexerReconcile(n[0]),
+			"indexer_prune" => Self::IndexerPrune,
+			"indexer_optimize" => Self::IndexerOptimize,
+			"indexer_process_batch" => Self::IndexerProcessBatch,
+			"indexer_link_batch" => Self::IndexerLinkBatch,
 			"block_height_n{}" if n.len() == 1 => Self::BlockHeight(n[0]),
 			"networks_updated" => Self::NetworksUpdated,
+			"network_disconnected_n{}" if n.len() == 1 => Self::NetworkDisconnected(n[0]),
+			"network_rpc_updated_n{}" if n.len() == 1 => Self::NetworkRpcUpdated(n[0]),
+			"network_rpc_failures_n{}" if n.len() == 1 => Self::NetworkRpcFailures(n[0]),
+			"entities_updated" => Self::EntitiesUpdated,
 			"newly_added_address_n{}_a{}" if n.len() == 2 => Self::NewlyAddedAddress(n[0], n[1]),
-			_ => panic!("no match in From<String> for ConfigKey: {s:?}"),
+			_ => bail!("unknown config key: {s:?}"),
+		})
+	}
+}
+
+impl From<String> for ConfigKey {
+	fn from(s: String) -> Self {
+		s.parse().unwrap_or_else(|_| panic!("no match in From<String> for ConfigKey: {s:?}"))
+	}
+}
+
+impl ConfigKey {
+	// @NOTE values are stored as json; this checks that a raw value decodes
+	// into the type that's read back for this key, so a hand-edited value
+	// can't leave the indexer stuck on a parse error
+	pub fn validate_value(&self, value: &str) -> Result<()> {
+		fn check<T: for<'a> Deserialize<'a>>(value: &str) -> Result<()> {
+			serde_json::from_str::<T>(value)?;
+			Ok(())
 		}
+
+		match self {
+			Self::Primary => check::<Uuid>(value),
+			Self::IndexerSyncTail(_) |
+			Self::IndexerProcessTail(_) |
+			Self::IndexerLink(_, _) |
+			Self::IndexerPeel(_) |
+			Self::IndexerMixer(_) |
+			Self::IndexerDeposit(_) |
+			Self::IndexerProducer(_) |
+			Self::IndexerBridge(_) |
+			Self::IndexerCompact(_) |
+			Self::IndexerReconcile(_) |
+			Self::BlockHeight(_) => check::<BlockHeight>(value),
+			Self::IndexerSyncChunk(_, _) |
+			Self::IndexerProcessChunk(_, _) |
+			Self::IndexerProcessModule(_, _) => check::<(BlockHeight, BlockHeight)>(value),
+			Self::IndexerProcessBackfill(_, _) => {
+				check::<(BlockHeight, BlockHeight, Vec<u16>)>(value)
+			}
+			Self::IndexerSyncProgress(_) | Self::IndexerProcessProgress(_) => check::<f64>(value),
+			Self::IndexerSyncBlockHashes(_) => check::<Vec<(BlockHeight, String)>>(value),
+			Self::IndexerProcessModuleDone(_, _) |
+			Self::NetworksUpdated |
+			Self::NetworkRpcUpdated(_) |
+			Self::EntitiesUpdated => check::<u8>(value),
+			Self::IndexerPrune => check::<PruneStats>(value),
+			Self::IndexerOptimize => check::<u64>(value),
+			Self::IndexerProcessBatch | Self::IndexerLinkBatch => check::<StagedBatch>(value),
+			Self::NewlyAddedAddress(_, _) => check::<PrimaryId>(value),
+			Self::NetworkDisconnected(_) => check::<String>(value),
+			Self::NetworkRpcFailures(_) => check::<HashMap<String, u32>>(value),
+		}
+		.wrap_err(format!("invalid value for {self}: {value}"))
 	}
 }
 
@@ -89,6 +199,7 @@ mod tests {
 			(ConfigKey::IndexerSyncTail(123), "indexer_sync_tail_n123"),
 			(ConfigKey::IndexerSyncChunk(123, 456), "indexer_sync_chunk_n123_b456"),
 			(ConfigKey::IndexerSyncProgress(123), "indexer_sync_progress_n123"),
+			(ConfigKey::IndexerSyncBlockHashes(123), "indexer_sync_block_hashes_n123"),
 			(ConfigKey::IndexerProcessTail(123), "indexer_process_tail_n123"),
 			(ConfigKey::IndexerProcessChunk(123, 456), "indexer_process_chunk_n123_b456"),
 			(ConfigKey::IndexerProcessModule(123, 456), "indexer_process_module_n123_m456"),
@@ -97,9 +208,25 @@ mod tests {
 				"indexer_process_module_done_n123_m456",
 			),
 			(ConfigKey::IndexerProcessProgress(123), "indexer_process_progress_n123"),
+			(ConfigKey::IndexerProcessBackfill(123, 456), "indexer_process_backfill_n123_b456"),
 			(ConfigKey::IndexerLink(123, 456), "indexer_link_n123_a456"),
+			(ConfigKey::IndexerPeel(123), "indexer_peel_n123"),
+			(ConfigKey::IndexerMixer(123), "indexer_mixer_n123"),
+			(ConfigKey::IndexerDeposit(123), "indexer_deposit_n123"),
+			(ConfigKey::IndexerProducer(123), "indexer_producer_n123"),
+			(ConfigKey::IndexerBridge(123), "indexer_bridge_n123"),
+			(ConfigKey::IndexerCompact(123), "indexer_compact_n123"),
+			(ConfigKey::IndexerReconcile(123), "indexer_reconcile_n123"),
+			(ConfigKey::IndexerPrune, "indexer_prune"),
+			(ConfigKey::IndexerOptimize, "indexer_optimize"),
+			(ConfigKey::IndexerProcessBatch, "indexer_process_batch"),
+			(ConfigKey::IndexerLinkBatch, "indexer_link_batch"),
 			(ConfigKey::BlockHeight(123), "block_height_n123"),
 			(ConfigKey::NetworksUpdated, "networks_updated"),
+			(ConfigKey::NetworkDisconnected(123), "network_disconnected_n123"),
+			(ConfigKey::NetworkRpcUpdated(123), "network_rpc_updated_n123"),
+			(ConfigKey::NetworkRpcFailures(123), "network_rpc_failures_n123"),
+			(ConfigKey::EntitiesUpdated, "entities_updated"),
 			(ConfigKey::NewlyAddedAddress(123, 456), "newly_added_address_n123_a456"),
 		]);
 
@@ -107,6 +234,18 @@ mod tests {
 			assert_eq!(config_key.to_string(), config_key_str);
 			assert_eq!(Into::<ConfigKey>::into(config_key_str.to_string()), config_key);
 		}
+
+		assert!("indexer_unknown_n123".parse::<ConfigKey>().is_err());
+	}
+
+	#[test]
+	fn test_config_key_validate_value() {
+		assert!(ConfigKey::IndexerSyncTail(123).validate_value("456").is_ok());
+		assert!(ConfigKey::IndexerSyncTail(123).validate_value("\"456\"").is_err());
+		assert!(ConfigKey::IndexerSyncChunk(123, 456).validate_value("[456,789]").is_ok());
+		assert!(ConfigKey::IndexerSyncChunk(123, 456).validate_value("[456]").is_err());
+		assert!(ConfigKey::NetworksUpdated.validate_value("1").is_ok());
+		assert!(ConfigKey::IndexerPrune.validate_value(r#"{"runs":1,"addresses":2}"#).is_ok());
 	}
 }
 
@@ -131,6 +270,28 @@ pub struct Value<T: for<'a> Deserialize<'a>> {
 	pub created_at: DateTime,
 }
 
+// running totals of what pruning has removed, kept under `IndexerPrune` (its
+// `updated_at` doubles as the time of the last run)
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PruneStats {
+	pub runs: u64,
+	pub addresses: u64,
+	pub entities: u64,
+	pub networks: u64,
+	pub duration_ms: u64,
+}
+
+// manifest of a batch on its way to the warehouse: the markers it moves
+// forward, applied only once the warehouse has acked the batch
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct StagedBatch {
+	pub id: String,
+	pub records: usize,
+	pub markers: BTreeMap<String, serde_json::Value>,
+}
+
 pub use Model as Config;
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -251,6 +412,14 @@ impl Model {
 			.collect())
 	}
 
+	// everything that's stored, with values left as raw json
+	pub async fn get_all<C>(c: &C) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find().order_by_asc(Column::Key).all(c).await?)
+	}
+
 	pub async fn delete<C>(c: &C, key: ConfigKey) -> Result<()>
 	where
 		C: ConnectionTrait,
@@ -284,11 +453,11 @@ impl Model {
 	fn adjust_filter(keys: Vec<ConfigKey>) -> Condition {
 		let mut condition = Condition::any();
 
-		// only match zeros: `example_a100_b0_c123` => `example_a100_b%_c123`
+		// only match zeros: `example_a100_b0_c0` => `example_a100_b%_c%`
 		let r = Regex::new(r"_([a-z])0").unwrap();
 
 		for key in keys.into_iter().map(|k| k.to_string()) {
-			let adjusted_key = r.replace(&key, "_$1%");
+			let adjusted_key = r.replace_all(&key, "_$1%");
 			condition = condition.add(if adjusted_key.contains('%') {
 				Column::Key.like(adjusted_key.clone())
 			} else {
@@ -63,6 +63,13 @@ impl Model {
 		Ok(insert_result.last_insert_id)
 	}
 
+	pub async fn get_all_by_tag_ids<C>(c: &C, tag_ids: PrimaryIds) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find().filter(Column::TagId.is_in(tag_ids)).all(c).await?)
+	}
+
 	pub async fn delete_not_included_tags<C>(
 		c: &C,
 		entity_id: PrimaryId,
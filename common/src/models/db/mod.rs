@@ -1,20 +1,48 @@
+pub use abi::{Abi, AbiActiveModel, Column as AbiColumn};
 pub use address::{Address, AddressActiveModel, Column as AddressColumn};
+pub use alert::{Alert, AlertActiveModel, Column as AlertColumn};
+pub use annotation::{Annotation, AnnotationActiveModel, Column as AnnotationColumn};
 pub use api_key::{ApiKey, ApiKeyActiveModel, Column as ApiKeyColumn};
-pub use config::{Config, ConfigKey};
+pub use api_key_usage::{
+	ApiKeyUsage, ApiKeyUsageActiveModel, Column as ApiKeyUsageColumn, EndpointUsage, UsageSummary,
+};
+pub use bridge_transfer::{
+	BridgeTransfer, BridgeTransferActiveModel, Column as BridgeTransferColumn,
+};
+pub use category::{Category, CategoryActiveModel, Column as CategoryColumn};
+pub use config::{Config, ConfigKey, PruneStats, StagedBatch};
 pub use entity::{
 	Column as EntityColumn, JoinedEntity, LabeledEntity as Entity,
 	LabeledEntityActiveModel as EntityActiveModel, SanitizedEntity,
 };
 pub use entity_tag::{Column as EntityTagColumn, EntityTag};
 pub use network::{Column as NetworkColumn, Network, NetworkActiveModel, SanitizedNetwork};
+pub use nft::{Column as NftColumn, Nft, NftActiveModel};
+pub use peel_hop::{Column as PeelHopColumn, PeelHop, PeelHopActiveModel};
+pub use report_schedule::{
+	Column as ReportScheduleColumn, ReportSchedule, ReportScheduleActiveModel,
+};
+pub use session::{Column as SessionColumn, Session, SessionActiveModel};
 pub use tag::{Column as TagColumn, JoinedTag, SanitizedTag, Tag, TagActiveModel};
 pub use token::{Column as TokenColumn, Token, TokenActiveModel};
+pub use webhook::{Column as WebhookColumn, Webhook, WebhookActiveModel};
 
+mod abi;
 mod address;
+mod alert;
+mod annotation;
 mod api_key;
+mod api_key_usage;
+mod bridge_transfer;
+mod category;
 mod config;
 mod entity;
 mod entity_tag;
 mod network;
+mod nft;
+mod peel_hop;
+mod report_schedule;
+mod session;
 mod tag;
 mod token;
+mod webhook;
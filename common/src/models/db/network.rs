@@ -9,7 +9,7 @@ use std::collections::HashSet;
 
 use crate::{
 	models::{BasicModel, PrimaryId, PrimaryIds, SoftDeleteModel},
-	utils, Architecture, IdPrefix,
+	prices, utils, Architecture, BlockHeight, IdPrefix,
 };
 
 #[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
@@ -25,13 +25,35 @@ pub struct Model {
 	pub chain_id: i64,
 	pub block_time: i64,
 	pub rpc_endpoint: String,
+	#[sea_orm(nullable)]
+	pub rpc_endpoints: Option<Json>,
 	pub rps: i32,
+	#[sea_orm(nullable)]
+	pub token_allowlist: Option<Json>,
+	#[sea_orm(nullable)]
+	pub token_denylist: Option<Json>,
+	#[sea_orm(nullable)]
+	pub large_transfer_threshold: Option<String>,
+	pub confirmations: i32,
+	#[sea_orm(nullable)]
+	pub wrapped_native_tokens: Option<Json>,
+	#[sea_orm(nullable)]
+	pub dormancy_days: Option<i32>,
+	// coingecko id of the native asset (eg: `ethereum`)
+	#[sea_orm(nullable)]
+	pub price_id: Option<String>,
 	#[serde(skip_serializing)]
 	pub is_deleted: bool,
 	#[sea_orm(nullable)]
 	#[serde(skip_serializing)]
 	pub updated_at: Option<DateTime>,
 	pub created_at: DateTime,
+
+	// endpoints that recently stopped responding (not stored, set before
+	// connecting)
+	#[sea_orm(ignore)]
+	#[serde(skip)]
+	pub failing_rpc_endpoints: Vec<String>,
 }
 
 impl From<Vec<Model>> for PrimaryIds {
@@ -94,6 +116,82 @@ impl Model {
 		}
 	}
 
+	// empty lists are stored as null
+	pub fn to_token_list(addresses: Vec<String>) -> Option<Json> {
+		match addresses.is_empty() {
+			true => None,
+			_ => Some(Json::from(addresses)),
+		}
+	}
+
+	pub fn get_token_allowlist(&self) -> Option<Vec<String>> {
+		self.token_allowlist.clone().and_then(|v| serde_json::from_value(v).ok())
+	}
+
+	pub fn get_token_denylist(&self) -> Vec<String> {
+		self.token_denylist.clone().and_then(|v| serde_json::from_value(v).ok()).unwrap_or_default()
+	}
+
+	// @NOTE endpoints to connect to, in order: `rpc_endpoint` first, then the
+	// fallbacks. ones that recently stopped responding are moved to the back
+	pub fn get_rpc_endpoints(&self) -> Vec<String> {
+		let mut rpc_endpoints = vec![self.rpc_endpoint.clone()];
+		for rpc_endpoint in self
+			.rpc_endpoints
+			.clone()
+			.and_then(|v| serde_json::from_value::<Vec<String>>(v).ok())
+			.unwrap_or_default()
+			.into_iter()
+		{
+			if !rpc_endpoints.contains(&rpc_endpoint) {
+				rpc_endpoints.push(rpc_endpoint);
+			}
+		}
+
+		rpc_endpoints
+			.sort_by_key(|e| self.failing_rpc_endpoints.contains(&utils::with_masked_auth(e)));
+		rpc_endpoints
+	}
+
+	// hides passwords in rpc endpoints before they're returned
+	pub fn mask_rpc_endpoints(&mut self) {
+		self.rpc_endpoint = utils::with_masked_auth(&self.rpc_endpoint);
+		self.rpc_endpoints = self.rpc_endpoints.take().map(|v| {
+			match serde_json::from_value::<Vec<String>>(v.clone()) {
+				Ok(rpc_endpoints) => Json::from(
+					rpc_endpoints.iter().map(|e| utils::with_masked_auth(e)).collect::<Vec<_>>(),
+				),
+				_ => v,
+			}
+		});
+	}
+
+	// contracts that wrap the native asset 1:1 (eg: weth)
+	pub fn get_wrapped_native_tokens(&self) -> Vec<String> {
+		self.wrapped_native_tokens
+			.clone()
+			.and_then(|v| serde_json::from_value(v).ok())
+			.unwrap_or_default()
+	}
+
+	// threshold in usd, which transfers of the native asset (and tokens that
+	// wrap it) are checked against
+	pub fn get_large_transfer_threshold(&self) -> Option<f64> {
+		self.large_transfer_threshold.as_deref().and_then(prices::parse_usd)
+	}
+
+	// how long an address has to go without sending before its next outgoing
+	// transfer raises an alert (in seconds)
+	pub fn get_dormancy_period(&self) -> Option<u32> {
+		self.dormancy_days.filter(|days| *days > 0).map(|days| days as u32 * 86_400)
+	}
+
+	// how far behind the chain's tip syncing stays, so shallow reorgs never
+	// get indexed in the first place
+	pub fn get_confirmations(&self) -> BlockHeight {
+		self.confirmations.max(0) as BlockHeight
+	}
+
 	pub async fn get_all_by_network_ids<C>(
 		c: &C,
 		network_ids: PrimaryIds,
@@ -0,0 +1,125 @@
+use eyre::Result;
+use sea_orm::{
+	entity::{prelude::*, *},
+	ConnectionTrait, QuerySelect,
+};
+use sea_orm_migration::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::{BasicModel, PrimaryId},
+	utils, IdPrefix,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "nfts")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub nft_id: PrimaryId,
+	#[serde(skip_serializing)]
+	pub network_id: PrimaryId,
+	pub id: String,
+	pub address: String,
+	pub token_id: String,
+	pub collection_name: Option<String>,
+	pub name: Option<String>,
+	pub description: Option<String>,
+	pub image: Option<String>,
+	pub uri: Option<String>,
+	pub is_resolved: bool,
+	#[serde(skip_serializing)]
+	pub attempts: i32,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+}
+
+pub use ActiveModel as NftActiveModel;
+pub use Model as Nft;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+impl Model {
+	pub fn new_model(network_id: PrimaryId, address: &str, token_id: &str) -> ActiveModel {
+		ActiveModel {
+			id: Set(utils::new_unique_id(IdPrefix::Nft)),
+			network_id: Set(network_id),
+			address: Set(address.to_string()),
+			token_id: Set(token_id.to_string()),
+			is_resolved: Set(false),
+			attempts: Set(0),
+			..Default::default()
+		}
+	}
+
+	pub async fn create_many<C>(c: &C, data: Vec<ActiveModel>) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		Entity::insert_many(data)
+			.on_conflict(
+				OnConflict::columns([Column::NetworkId, Column::Address, Column::TokenId])
+					.do_nothing()
+					.to_owned(),
+			)
+			.do_nothing()
+			.exec(c)
+			.await?;
+
+		Ok(())
+	}
+
+	// unresolved nfts that haven't failed too many times, and weren't attempted
+	// since `attempted_before`
+	pub async fn get_all_unresolved<C>(
+		c: &C,
+		max_attempts: i32,
+		attempted_before: DateTime,
+		limit: u64,
+	) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find()
+			.filter(Column::IsResolved.eq(false))
+			.filter(Column::Attempts.lt(max_attempts))
+			.filter(
+				Condition::any()
+					.add(Column::UpdatedAt.is_null())
+					.add(Column::UpdatedAt.lt(attempted_before)),
+			)
+			.limit(limit)
+			.all(c)
+			.await?)
+	}
+
+	pub async fn get_all_by_network_id_and_address<C>(
+		c: &C,
+		network_id: PrimaryId,
+		address: &str,
+		token_id: Option<String>,
+	) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		let mut q = Entity::find()
+			.filter(Column::NetworkId.eq(network_id))
+			.filter(Column::Address.eq(address));
+
+		if let Some(token_id) = token_id {
+			q = q.filter(Column::TokenId.eq(token_id));
+		}
+
+		Ok(q.all(c).await?)
+	}
+}
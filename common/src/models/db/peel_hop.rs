@@ -0,0 +1,97 @@
+use eyre::Result;
+use sea_orm::{entity::prelude::*, ConnectionTrait};
+use sea_orm_migration::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::{BasicModel, PrimaryId},
+	BlockHeight,
+};
+
+// @NOTE a one-input/two-output transaction that peels a small amount off and
+// sends the rest to a new change address. hops whose input is the previous
+// hop's change address make up a chain (sharing `root_tx_hash`)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "peel_hops")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub peel_hop_id: PrimaryId,
+	#[serde(skip_serializing)]
+	pub network_id: PrimaryId,
+	pub block_height: i64,
+	pub tx_hash: String,
+	pub root_tx_hash: String,
+	pub length: i32,
+	pub input_address: String,
+	pub change_address: String,
+	pub peel_address: String,
+	pub peel_amount: String,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+}
+
+pub use ActiveModel as PeelHopActiveModel;
+pub use Model as PeelHop;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+impl Model {
+	pub async fn create_many<C>(c: &C, data: Vec<ActiveModel>) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		Entity::insert_many(data)
+			.on_conflict(
+				OnConflict::columns([Column::NetworkId, Column::TxHash]).do_nothing().to_owned(),
+			)
+			.do_nothing()
+			.exec(c)
+			.await?;
+
+		Ok(())
+	}
+
+	// latest hops that sent change to any of `addresses` before `block_height`
+	pub async fn get_all_by_change_addresses<C>(
+		c: &C,
+		network_id: PrimaryId,
+		addresses: Vec<String>,
+		block_height: BlockHeight,
+	) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find()
+			.filter(Column::NetworkId.eq(network_id))
+			.filter(Column::ChangeAddress.is_in(addresses))
+			.filter(Column::BlockHeight.lt(block_height as i64))
+			.all(c)
+			.await?)
+	}
+
+	pub async fn get_all_by_root_tx_hash<C>(
+		c: &C,
+		network_id: PrimaryId,
+		root_tx_hash: &str,
+	) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find()
+			.filter(Column::NetworkId.eq(network_id))
+			.filter(Column::RootTxHash.eq(root_tx_hash))
+			.all(c)
+			.await?)
+	}
+}
@@ -0,0 +1,147 @@
+use eyre::Result;
+use sea_orm::{
+	entity::{prelude::*, *},
+	Condition, ConnectionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::{
+	models::{BasicModel, PrimaryId},
+	utils, IdPrefix,
+};
+
+// @NOTE reports compiled every `interval_seconds` for each subject on the
+// watchlist (addresses or entity ids), and archived to storage like any other
+// report. `last_report_ids` are the ones compiled on the latest run
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "report_schedules")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub report_schedule_id: PrimaryId,
+	pub id: String,
+	pub name: String,
+	pub watchlist: Json,
+	pub interval_seconds: i64,
+	pub next_run_at: DateTime,
+	#[sea_orm(nullable)]
+	pub last_run_at: Option<DateTime>,
+	#[sea_orm(nullable)]
+	pub last_report_ids: Option<Json>,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+}
+
+pub use ActiveModel as ReportScheduleActiveModel;
+pub use Model as ReportSchedule;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+impl Model {
+	// first run is right away
+	pub fn new_model(name: &str, watchlist: Vec<String>, interval_seconds: u64) -> ActiveModel {
+		ActiveModel {
+			id: Set(utils::new_unique_id(IdPrefix::ReportSchedule)),
+			name: Set(name.to_string()),
+			watchlist: Set(watchlist.into()),
+			interval_seconds: Set(interval_seconds as i64),
+			next_run_at: Set(utils::now()),
+			..Default::default()
+		}
+	}
+
+	pub fn get_watchlist(&self) -> Vec<String> {
+		serde_json::from_value(self.watchlist.clone()).unwrap_or_default()
+	}
+
+	pub async fn get_all_due<C>(c: &C) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find().filter(Column::NextRunAt.lte(utils::now())).all(c).await?)
+	}
+
+	// @NOTE moves the next run forward, but only if no one else has since, so
+	// with several servers only the one that claims a run delivers it
+	pub async fn claim<C>(c: &C, schedule: &Self) -> Result<bool>
+	where
+		C: ConnectionTrait,
+	{
+		let now = utils::now();
+		let res = Entity::update_many()
+			.col_expr(
+				Column::NextRunAt,
+				Expr::value(utils::in_seconds(schedule.interval_seconds as u64)),
+			)
+			.col_expr(Column::LastRunAt, Expr::value(now))
+			.col_expr(Column::UpdatedAt, Expr::value(now))
+			.filter(
+				Condition::all()
+					.add(Column::ReportScheduleId.eq(schedule.report_schedule_id))
+					.add(Column::NextRunAt.eq(schedule.next_run_at)),
+			)
+			.exec(c)
+			.await?;
+
+		Ok(res.rows_affected == 1)
+	}
+
+	// @NOTE moves the next run of schedules watching any of `subjects` up to
+	// now, eg: when a risk level their reports depend on changed. watchlists
+	// hold whatever was submitted, so they're matched case-insensitively.
+	// returns how many schedules were moved
+	pub async fn run_now_by_subjects<C>(c: &C, subjects: HashSet<String>) -> Result<u64>
+	where
+		C: ConnectionTrait,
+	{
+		let subjects = subjects.into_iter().map(|s| s.to_lowercase()).collect::<HashSet<_>>();
+		let report_schedule_ids = Entity::find()
+			.filter(Column::NextRunAt.gt(utils::now()))
+			.all(c)
+			.await?
+			.into_iter()
+			.filter(|s| s.get_watchlist().iter().any(|q| subjects.contains(&q.to_lowercase())))
+			.map(|s| s.report_schedule_id)
+			.collect::<Vec<PrimaryId>>();
+
+		if report_schedule_ids.is_empty() {
+			return Ok(0);
+		}
+
+		let res = Entity::update_many()
+			.col_expr(Column::NextRunAt, Expr::value(utils::now()))
+			.filter(Column::ReportScheduleId.is_in(report_schedule_ids))
+			.exec(c)
+			.await?;
+
+		Ok(res.rows_affected)
+	}
+
+	pub async fn set_last_report_ids<C>(
+		c: &C,
+		report_schedule_id: PrimaryId,
+		report_ids: Vec<String>,
+	) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		Entity::update_many()
+			.col_expr(Column::LastReportIds, Expr::value(Json::from(report_ids)))
+			.filter(Column::ReportScheduleId.eq(report_schedule_id))
+			.exec(c)
+			.await?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,99 @@
+use base58::ToBase58;
+use eyre::Result;
+use sea_orm::{
+	entity::{prelude::*, *},
+	ConnectionTrait,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::{BasicModel, PrimaryId},
+	utils, IdPrefix,
+};
+
+// prefix of session tokens, so they're distinguishable from api keys
+pub static TOKEN_PREFIX: &str = "st_";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "sessions")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub session_id: PrimaryId,
+	pub id: String,
+	pub subject: String,
+	#[serde(skip_serializing, skip_deserializing)]
+	pub token_hash: Vec<u8>,
+	pub expires_at: DateTime,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+}
+
+pub use ActiveModel as SessionActiveModel;
+pub use Model as Session;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+impl Model {
+	// returns the model along with its token, which is only known at this point
+	pub fn new_model(subject: &str, expires_at: DateTime) -> (ActiveModel, String) {
+		let secret = utils::sha256(&utils::new_uuid().to_string()).to_base58();
+
+		(
+			ActiveModel {
+				id: Set(utils::new_unique_id(IdPrefix::Session)),
+				subject: Set(subject.to_string()),
+				token_hash: Set(utils::sha256(&secret)),
+				expires_at: Set(expires_at),
+				..Default::default()
+			},
+			format!("{TOKEN_PREFIX}{secret}"),
+		)
+	}
+
+	pub fn is_token(token: &str) -> bool {
+		token.starts_with(TOKEN_PREFIX)
+	}
+
+	pub async fn get_active_by_token<C>(c: &C, token: &str) -> Result<Option<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		let secret = token.trim_start_matches(TOKEN_PREFIX);
+
+		Ok(Entity::find()
+			.filter(Column::TokenHash.eq(utils::sha256(secret)))
+			.filter(Column::ExpiresAt.gt(utils::now()))
+			.one(c)
+			.await?)
+	}
+
+	pub async fn delete_by_token<C>(c: &C, token: &str) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		let secret = token.trim_start_matches(TOKEN_PREFIX);
+		Entity::delete_many().filter(Column::TokenHash.eq(utils::sha256(secret))).exec(c).await?;
+
+		Ok(())
+	}
+
+	pub async fn delete_all_expired<C>(c: &C) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		Entity::delete_many().filter(Column::ExpiresAt.lte(utils::now())).exec(c).await?;
+
+		Ok(())
+	}
+}
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 use crate::{
-	models::{db::entity_tag, BasicModel, EntityTagColumn, PrimaryId, PrimaryIds},
+	models::{db::entity_tag, Address, BasicModel, EntityTagColumn, PrimaryId, PrimaryIds},
 	utils, IdPrefix, RiskLevel,
 };
 
@@ -22,11 +22,20 @@ pub struct Model {
 	pub id: String,
 	pub name: String,
 	pub risk_level: RiskLevel,
+	pub is_mixer: bool,
+	pub is_exchange: bool,
+	pub is_bridge: bool,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub category_id: Option<PrimaryId>,
 	#[sea_orm(nullable)]
 	#[serde(skip_serializing)]
 	pub updated_at: Option<DateTime>,
 	pub created_at: DateTime,
 
+	#[sea_orm(ignore)]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub category: Option<String>,
 	#[sea_orm(ignore)]
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub entities: Option<Vec<String>>,
@@ -45,6 +54,10 @@ pub struct JoinedModel {
 	pub id: String,
 	pub name: String,
 	pub risk_level: RiskLevel,
+	pub is_mixer: bool,
+	pub is_exchange: bool,
+	pub is_bridge: bool,
+	pub category_id: Option<PrimaryId>,
 	pub updated_at: Option<DateTime>,
 	pub created_at: DateTime,
 	pub entity_id: PrimaryId,
@@ -68,8 +81,13 @@ impl From<JoinedModel> for Model {
 			id: m.id,
 			name: m.name,
 			risk_level: m.risk_level,
+			is_mixer: m.is_mixer,
+			is_exchange: m.is_exchange,
+			is_bridge: m.is_bridge,
+			category_id: m.category_id,
 			updated_at: m.updated_at,
 			created_at: m.created_at,
+			category: None,
 			entities: None,
 		}
 	}
@@ -115,6 +133,10 @@ impl Model {
 			id: Set(id.unwrap_or(utils::new_unique_id(IdPrefix::Tag))),
 			name: Set(name.to_string()),
 			risk_level: Set(risk_level),
+			is_mixer: Set(false),
+			is_exchange: Set(false),
+			is_bridge: Set(false),
+			category_id: Set(None),
 			..Default::default()
 		}
 	}
@@ -131,6 +153,59 @@ impl Model {
 			.await?)
 	}
 
+	// addresses of entities tagged as mixers
+	pub async fn get_all_mixer_addresses<C>(c: &C) -> Result<Vec<Address>>
+	where
+		C: ConnectionTrait,
+	{
+		Self::get_all_addresses_where(c, Column::IsMixer.eq(true)).await
+	}
+
+	// addresses of entities tagged as exchanges (eg: hot wallets)
+	pub async fn get_all_exchange_addresses<C>(c: &C) -> Result<Vec<Address>>
+	where
+		C: ConnectionTrait,
+	{
+		Self::get_all_addresses_where(c, Column::IsExchange.eq(true)).await
+	}
+
+	// addresses of entities tagged as bridges (contracts and escrows, on every
+	// network the bridge connects)
+	pub async fn get_all_bridge_addresses<C>(c: &C) -> Result<Vec<Address>>
+	where
+		C: ConnectionTrait,
+	{
+		Self::get_all_addresses_where(c, Column::IsBridge.eq(true)).await
+	}
+
+	async fn get_all_addresses_where<C, F>(c: &C, filter: F) -> Result<Vec<Address>>
+	where
+		C: ConnectionTrait,
+		F: IntoCondition,
+	{
+		let tag_ids = Entity::find().filter(filter).all(c).await?.into_iter().map(|t| t.tag_id);
+		let entity_ids = entity_tag::Entity::find()
+			.filter(EntityTagColumn::TagId.is_in(tag_ids))
+			.all(c)
+			.await?
+			.into_iter()
+			.map(|et| et.entity_id)
+			.collect::<Vec<PrimaryId>>();
+
+		if entity_ids.is_empty() {
+			return Ok(vec![]);
+		}
+
+		Address::get_all_by_entity_ids(c, entity_ids.into(), Some(false)).await
+	}
+
+	pub async fn get_all_by_category_ids<C>(c: &C, category_ids: PrimaryIds) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find().filter(Column::CategoryId.is_in(category_ids)).all(c).await?)
+	}
+
 	pub async fn get_all_by_entity_ids<C>(c: &C, entity_ids: PrimaryIds) -> Result<Vec<JoinedModel>>
 	where
 		C: ConnectionTrait,
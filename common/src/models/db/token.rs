@@ -1,7 +1,7 @@
 use eyre::Result;
 use sea_orm::{
 	entity::{prelude::*, *},
-	ConnectionTrait,
+	ConnectionTrait, QuerySelect,
 };
 use sea_orm_migration::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -26,6 +26,8 @@ pub struct Model {
 	pub symbol: String,
 	pub address: String,
 	pub decimals: i16,
+	#[serde(skip_serializing)]
+	pub is_placeholder: bool,
 	#[sea_orm(nullable)]
 	#[serde(skip_serializing)]
 	pub updated_at: Option<DateTime>,
@@ -71,6 +73,23 @@ impl Model {
 		}
 	}
 
+	// tokens seen in transfers but not registered yet; name & symbol are
+	// unique, so they default to the address until metadata is discovered
+	pub fn new_placeholder_model(network_id: PrimaryId, address: &str) -> ActiveModel {
+		let placeholder = format!("{network_id}:{address}");
+
+		ActiveModel {
+			id: Set(utils::new_unique_id(IdPrefix::Token)),
+			network_id: Set(network_id),
+			name: Set(placeholder.clone()),
+			symbol: Set(placeholder),
+			address: Set(address.to_string()),
+			decimals: Set(0),
+			is_placeholder: Set(true),
+			..Default::default()
+		}
+	}
+
 	pub async fn create_many<C>(c: &C, data: Vec<ActiveModel>) -> Result<PrimaryId>
 	where
 		C: ConnectionTrait,
@@ -85,10 +104,46 @@ impl Model {
 		Ok(insert_result.last_insert_id)
 	}
 
+	pub async fn create_placeholders<C>(c: &C, data: Vec<ActiveModel>) -> Result<()>
+	where
+		C: ConnectionTrait,
+	{
+		Entity::insert_many(data)
+			.on_conflict(
+				OnConflict::columns([Column::NetworkId, Column::Address]).do_nothing().to_owned(),
+			)
+			.do_nothing()
+			.exec(c)
+			.await?;
+
+		Ok(())
+	}
+
 	pub async fn get_all_by_network_ids<C>(c: &C, network_ids: PrimaryIds) -> Result<Vec<Self>>
 	where
 		C: ConnectionTrait,
 	{
 		Ok(Entity::find().filter(Column::NetworkId.is_in(network_ids)).all(c).await?)
 	}
+
+	// placeholders that haven't been attempted since `attempted_before`
+	pub async fn get_all_placeholders<C>(
+		c: &C,
+		attempted_before: DateTime,
+		limit: u64,
+	) -> Result<Vec<Self>>
+	where
+		C: ConnectionTrait,
+	{
+		Ok(Entity::find()
+			.filter(Column::IsPlaceholder.eq(true))
+			.filter(
+				Condition::any()
+					.add(Column::UpdatedAt.is_null())
+					.add(Column::UpdatedAt.lt(attempted_before)),
+			)
+			.limit(limit)
+			.all(c)
+			.await?)
+	}
 }
@@ -0,0 +1,74 @@
+use base58::ToBase58;
+use sea_orm::entity::{prelude::*, *};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::{BasicModel, PrimaryId},
+	utils, IdPrefix,
+};
+
+// @NOTE endpoints that get transfers and links touching tagged entities'
+// addresses POSTed to them as they're indexed
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DeriveEntityModel)]
+#[sea_orm(table_name = "webhooks")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+	#[sea_orm(primary_key)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub webhook_id: PrimaryId,
+	pub id: String,
+	pub url: String,
+	// signs deliveries; webhooks created before secrets existed have none
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing, skip_deserializing)]
+	pub secret: Option<String>,
+	#[sea_orm(nullable)]
+	#[serde(skip_serializing)]
+	pub updated_at: Option<DateTime>,
+	pub created_at: DateTime,
+
+	// only returned once, when the webhook is created
+	#[sea_orm(ignore)]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub signing_secret: Option<String>,
+}
+
+pub use ActiveModel as WebhookActiveModel;
+pub use Model as Webhook;
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl BasicModel for Model {
+	type ActiveModel = ActiveModel;
+}
+
+impl Model {
+	pub fn new_model(url: &str) -> ActiveModel {
+		ActiveModel {
+			id: Set(utils::new_unique_id(IdPrefix::Webhook)),
+			url: Set(url.to_string()),
+			secret: Set(Some(Self::generate_secret())),
+			..Default::default()
+		}
+	}
+
+	pub fn generate_secret() -> String {
+		let secret = utils::sha256(&utils::new_uuid().to_string()).to_base58();
+		format!("whsec_{secret}")
+	}
+
+	pub fn with_signing_secret(self) -> Self {
+		Self { signing_secret: self.secret.clone(), ..self }
+	}
+
+	// hmac-sha256 of `{timestamp}.{body}`, so receivers can reject replays
+	pub fn get_signature(&self, timestamp: u64, body: &str) -> Option<String> {
+		self.secret.as_ref().map(|secret| {
+			let signature = utils::hmac_sha256(secret, &format!("{timestamp}.{body}"));
+			format!("sha256={}", hex::encode(signature))
+		})
+	}
+}
@@ -0,0 +1,92 @@
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::{PrimaryId, PrimaryIds},
+	warehouse::Warehouse,
+	BlockHeight,
+};
+
+pub static TABLE: &str = "address_activity";
+
+// first and last block an address sent or received anything in, per network
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	pub network_id: u64,
+	pub address: String,
+	pub first_block_height: u64,
+	pub last_block_height: u64,
+}
+
+pub use Model as AddressActivity;
+
+impl Model {
+	pub async fn get_all_by_addresses(
+		warehouse: &Warehouse,
+		mut addresses: Vec<String>,
+	) -> Result<Vec<Self>> {
+		addresses.sort_unstable();
+		addresses.dedup();
+		if addresses.is_empty() {
+			return Ok(vec![]);
+		}
+
+		let formatted_addresses =
+			addresses.iter().map(|addr| format!("'{}'", addr)).collect::<Vec<_>>().join(", ");
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT
+					    network_id,
+					    address,
+					    min(min_block_height) as first_block_height,
+					    max(max_block_height) as last_block_height
+					FROM {TABLE}
+					WHERE address IN ({formatted_addresses})
+					GROUP BY (network_id, address)
+					ORDER BY (network_id, address)
+                "#
+			))
+			.await
+	}
+
+	// @NOTE addresses first active in blocks that are no longer canonical. ones
+	// active before those keep their row, though their last block might be
+	// too high until they're active again
+	pub async fn rollback(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+	) -> Result<()> {
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						min_block_height >= {block_height}
+				"#
+			))
+			.await
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+}
@@ -1,11 +1,14 @@
 use clickhouse::Row;
 use eyre::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 
+use super::block::get_module_filter;
 use crate::{
 	chain::{u256, ModuleId, U256},
 	models::{PrimaryId, PrimaryIds},
 	warehouse::Warehouse,
+	BlockHeight,
 };
 
 pub static TABLE: &str = "amounts";
@@ -25,6 +28,64 @@ pub struct Model {
 	pub created_at: u32,
 }
 
+// what an address moved of an asset within a day
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct DailyAmount {
+	pub network_id: u64,
+	pub address: String,
+	pub asset_address: String,
+	// days since unix epoch
+	pub date: u16,
+	#[serde(with = "u256")]
+	pub amount_in: U256,
+	#[serde(with = "u256")]
+	pub amount_out: U256,
+	// zero when nothing was sent that day
+	pub last_sent_at: u32,
+}
+
+impl DailyAmount {
+	// @NOTE value-weighted age (in days) of what's currently held, assuming the
+	// oldest coins are spent first. `amounts` must be sorted by date and belong
+	// to a single address and asset. nothing held means there's no age
+	pub fn get_coin_age(amounts: &[DailyAmount], today: u16) -> Option<f64> {
+		let mut held = VecDeque::<(u16, U256)>::new();
+		for amount in amounts.iter() {
+			if !amount.amount_in.is_zero() {
+				held.push_back((amount.date, amount.amount_in));
+			}
+
+			let mut amount_out = amount.amount_out;
+			while !amount_out.is_zero() {
+				match held.front_mut() {
+					Some((_, value)) if *value > amount_out => {
+						*value -= amount_out;
+						amount_out = U256::zero();
+					}
+					Some((_, value)) => {
+						amount_out -= *value;
+						held.pop_front();
+					}
+					None => break,
+				}
+			}
+		}
+
+		let total = held.iter().fold(U256::zero(), |acc, (_, v)| acc.saturating_add(*v));
+		if total.is_zero() {
+			return None;
+		}
+
+		// in hundredths of a day, to keep two decimals
+		let weighted = held.iter().fold(U256::zero(), |acc, (date, v)| {
+			let age = today.saturating_sub(*date) as u64 * 100;
+			acc.saturating_add(v.saturating_mul(U256::from(age)))
+		});
+
+		Some((weighted / total).low_u64() as f64 / 100.0)
+	}
+}
+
 pub use Model as Amount;
 
 impl Model {
@@ -40,7 +101,7 @@ impl Model {
 		created_at: u32,
 	) -> Self {
 		Self {
-			module_id: module_id as u16,
+			module_id: module_id.into(),
 			network_id: network_id as u64,
 			block_height,
 			tx_hash: tx_hash.to_string(),
@@ -82,6 +143,177 @@ impl Model {
 			.into())
 	}
 
+	// (address, network id) pairs for networks each address has activity on
+	pub async fn get_all_network_ids_by_address(
+		warehouse: &Warehouse,
+		mut addresses: Vec<String>,
+	) -> Result<Vec<(String, PrimaryId)>> {
+		#[derive(Debug, Clone, Serialize, Deserialize)]
+		struct Data {
+			address: String,
+			network_id: u64,
+		}
+
+		addresses.sort_unstable();
+		addresses.dedup();
+
+		let formatted_addresses =
+			addresses.iter().map(|addr| format!("'{}'", addr)).collect::<Vec<_>>().join(", ");
+
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT DISTINCT address, network_id
+					FROM {TABLE}
+					WHERE address IN ({formatted_addresses})
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d: Data| (d.address, d.network_id as PrimaryId))
+			.collect())
+	}
+
+	// per-day totals for each address and asset, oldest first
+	pub async fn get_all_daily_by_addresses(
+		warehouse: &Warehouse,
+		mut addresses: Vec<String>,
+	) -> Result<Vec<DailyAmount>> {
+		addresses.sort_unstable();
+		addresses.dedup();
+
+		let formatted_addresses =
+			addresses.iter().map(|addr| format!("'{}'", addr)).collect::<Vec<_>>().join(", ");
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT
+					    network_id,
+					    address,
+					    asset_address,
+					    toUInt16(toDate(created_at)) as date,
+					    sum(amount_in) as amount_in,
+					    sum(amount_out) as amount_out,
+					    maxIf(created_at, amount_out > 0) as last_sent_at
+					FROM {TABLE} FINAL
+					WHERE address IN ({formatted_addresses})
+					GROUP BY (network_id, address, asset_address, date)
+					ORDER BY (network_id, address, asset_address, date)
+                "#
+			))
+			.await
+	}
+
+	// when each address last sent anything before `block_height`, for the ones
+	// that ever did
+	pub async fn get_last_sent_by_addresses(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		mut addresses: Vec<String>,
+		block_height: BlockHeight,
+	) -> Result<HashMap<String, u32>> {
+		#[derive(Row, Deserialize)]
+		struct Data {
+			address: String,
+			last_sent_at: u32,
+		}
+
+		addresses.sort_unstable();
+		addresses.dedup();
+
+		let formatted_addresses =
+			addresses.iter().map(|addr| format!("'{}'", addr)).collect::<Vec<_>>().join(", ");
+
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT
+						address,
+						max(created_at) as last_sent_at
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						address IN ({formatted_addresses}) AND
+						amount_out > 0 AND
+						block_height < {block_height}
+					GROUP BY address
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d: Data| (d.address, d.last_sent_at))
+			.collect())
+	}
+
+	// addresses whose native balance changed within a block range, eg: to
+	// sample from
+	pub async fn get_all_native_addresses_by_block_range(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		block_height_min: BlockHeight,
+		block_height_max: BlockHeight,
+		limit: u64,
+	) -> Result<Vec<String>> {
+		#[derive(Row, Deserialize)]
+		struct Data {
+			address: String,
+		}
+
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT DISTINCT address
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						asset_address = '' AND
+						block_height >= {block_height_min} AND
+						block_height <= {block_height_max}
+					LIMIT {limit}
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d: Data| d.address)
+			.collect())
+	}
+
+	// addresses whose balance `module_ids` (any module when empty) changed
+	// within a block range, eg: to rebuild the balances of
+	pub async fn get_all_addresses_by_block_range(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		(block_height_min, block_height_max): (BlockHeight, BlockHeight),
+		module_ids: &[u16],
+		limit: u64,
+	) -> Result<Vec<String>> {
+		let module_filter = get_module_filter(module_ids);
+
+		#[derive(Row, Deserialize)]
+		struct Data {
+			address: String,
+		}
+
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT DISTINCT address
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						block_height >= {block_height_min} AND
+						block_height <= {block_height_max}
+						{module_filter}
+					LIMIT {limit}
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d: Data| d.address)
+			.collect())
+	}
+
 	pub async fn delete_all_by_network_id(
 		warehouse: &Warehouse,
 		network_ids: PrimaryIds,
@@ -98,4 +330,66 @@ impl Model {
 			))
 			.await
 	}
+
+	// rows `module_ids` (any module when empty) wrote within a block range, eg:
+	// before it gets processed again
+	pub async fn delete_all_by_block_range(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		(block_height_min, block_height_max): (BlockHeight, BlockHeight),
+		module_ids: &[u16],
+	) -> Result<()> {
+		let module_filter = get_module_filter(module_ids);
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						block_height >= {block_height_min} AND
+						block_height <= {block_height_max}
+						{module_filter}
+				"#
+			))
+			.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn daily_amount(date: u16, amount_in: u64, amount_out: u64) -> DailyAmount {
+		DailyAmount {
+			network_id: 1,
+			address: "address".to_string(),
+			asset_address: "".to_string(),
+			date,
+			amount_in: U256::from(amount_in),
+			amount_out: U256::from(amount_out),
+			last_sent_at: 0,
+		}
+	}
+
+	#[test]
+	fn test_get_coin_age() {
+		// nothing held
+		assert_eq!(DailyAmount::get_coin_age(&[], 10), None);
+		assert_eq!(DailyAmount::get_coin_age(&[daily_amount(0, 5, 5)], 10), None);
+
+		// equal parts 10 and 0 days old
+		let amounts = vec![daily_amount(0, 100, 0), daily_amount(10, 100, 0)];
+		assert_eq!(DailyAmount::get_coin_age(&amounts, 10), Some(5.0));
+
+		// oldest coins are spent first
+		let amounts =
+			vec![daily_amount(0, 100, 0), daily_amount(4, 100, 0), daily_amount(6, 0, 150)];
+		assert_eq!(DailyAmount::get_coin_age(&amounts, 10), Some(6.0));
+
+		// sending more than was seen received doesn't go negative
+		let amounts = vec![daily_amount(0, 100, 200), daily_amount(8, 50, 0)];
+		assert_eq!(DailyAmount::get_coin_age(&amounts, 10), Some(2.0));
+	}
 }
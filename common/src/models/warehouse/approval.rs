@@ -0,0 +1,149 @@
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	chain::{u256, U256},
+	models::{PrimaryId, PrimaryIds},
+	warehouse::Warehouse,
+	BlockHeight,
+};
+
+pub static TABLE: &str = "approvals";
+
+// erc-20 `Approval` events; zero amounts are revocations
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	pub network_id: u64,
+	pub block_height: u64,
+	pub tx_hash: String,
+	pub log_index: u64,
+	pub token_address: String,
+	pub owner: String,
+	pub spender: String,
+	#[serde(with = "u256")]
+	pub amount: U256,
+	pub created_at: u32,
+}
+
+pub use Model as Approval;
+
+// current allowance of `spender` over `owner`'s tokens, as of the latest
+// approval
+#[derive(Debug, Clone, Row, Deserialize)]
+pub struct Allowance {
+	pub network_id: u64,
+	pub token_address: String,
+	pub owner: String,
+	pub spender: String,
+	#[serde(with = "u256")]
+	pub amount: U256,
+}
+
+impl Allowance {
+	// @NOTE wallets approve `type(uint256).max` for "unlimited", but some tokens
+	// cap allowances lower (eg: uint96), so anything from there up counts
+	pub fn is_unlimited(&self) -> bool {
+		self.amount >= (U256::one() << 96) - 1
+	}
+}
+
+impl Model {
+	#[allow(clippy::too_many_arguments)]
+	pub fn new(
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+		tx_hash: &str,
+		log_index: u64,
+		token_address: &str,
+		owner: &str,
+		spender: &str,
+		amount: U256,
+		created_at: u32,
+	) -> Self {
+		Self {
+			network_id: network_id as u64,
+			block_height,
+			tx_hash: tx_hash.to_string(),
+			log_index,
+			token_address: token_address.to_string(),
+			owner: owner.to_string(),
+			spender: spender.to_string(),
+			amount,
+			created_at,
+		}
+	}
+
+	// non-zero allowances granted by `owners`
+	pub async fn get_all_allowances_by_owners(
+		warehouse: &Warehouse,
+		mut owners: Vec<String>,
+	) -> Result<Vec<Allowance>> {
+		owners.sort_unstable();
+		owners.dedup();
+
+		if owners.is_empty() {
+			return Ok(vec![]);
+		}
+
+		let formatted_owners =
+			owners.iter().map(|addr| format!("'{}'", addr)).collect::<Vec<_>>().join(", ");
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM (
+	                    SELECT
+	                        network_id,
+	                        token_address,
+	                        owner,
+	                        spender,
+	                        argMax(amount, (block_height, log_index)) as amount
+	                    FROM {TABLE}
+	                    WHERE owner IN ({formatted_owners})
+	                    GROUP BY (network_id, token_address, owner, spender)
+					)
+					WHERE amount > 0
+                "#
+			))
+			.await
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_is_unlimited() {
+		let allowance = |amount: U256| Allowance {
+			network_id: 1,
+			token_address: String::new(),
+			owner: String::new(),
+			spender: String::new(),
+			amount,
+		};
+
+		assert!(allowance(U256::MAX).is_unlimited());
+		assert!(allowance((U256::one() << 96) - 1).is_unlimited());
+		assert!(!allowance(U256::from(1_000_000u64)).is_unlimited());
+	}
+}
@@ -4,12 +4,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
 	chain::{u256, U256},
-	models::PrimaryIds,
+	models::{AmountTable, ExcludedRows, PrimaryId, PrimaryIds},
+	utils,
 	warehouse::Warehouse,
+	BlockHeight,
 };
 
 pub static TABLE: &str = "balances";
 
+// addresses rebuilt per statement
+pub(crate) const REBUILD_CHUNK_SIZE: usize = 1_000;
+
 #[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
 pub struct Model {
 	pub network_id: u64,
@@ -55,6 +60,126 @@ impl Model {
 			.await
 	}
 
+	// balances as of `block_height` (inclusive), summed up from raw amounts
+	// since the `balances` view only holds the latest state
+	pub async fn get_all_by_address_at_block_height(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		address: &str,
+		block_height: BlockHeight,
+	) -> Result<Vec<Model>> {
+		let escaped_address = utils::escape_sql_string(address);
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM (
+	                    SELECT
+	                        network_id,
+	                        address,
+	                        asset_address,
+	                        SUM(amount_in) - SUM(amount_out) as balance
+	                    FROM {AmountTable}
+	                    WHERE
+	                        network_id = {network_id} AND
+	                        address = '{escaped_address}' AND
+	                        block_height <= {block_height}
+	                    GROUP BY (network_id, address, asset_address)
+					)
+					WHERE balance > 0
+                "#
+			))
+			.await
+	}
+
+	// largest holders of `asset_address` (empty for the native asset),
+	// paginated by keyset (`cursor` being the last row's balance and address)
+	pub async fn get_top_by_asset_address(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		asset_address: &str,
+		cursor: Option<(U256, String)>,
+		limit: u64,
+	) -> Result<Vec<Model>> {
+		let cursor_filter = match cursor {
+			Some((balance, address)) => format!(
+				"AND (balance < {balance} OR (balance = {balance} AND address > '{address}'))"
+			),
+			None => "".to_string(),
+		};
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM (
+	                    SELECT
+	                        network_id,
+	                        address,
+	                        asset_address,
+	                        SUM(balance) as balance
+	                    FROM {TABLE}
+	                    WHERE network_id = {network_id} AND asset_address = '{asset_address}'
+	                    GROUP BY (network_id, address, asset_address)
+					)
+					WHERE balance > 0 {cursor_filter}
+					ORDER BY balance DESC, address ASC
+					LIMIT {limit}
+                "#
+			))
+			.await
+	}
+
+	// @NOTE the view only ever adds up new amounts, so once amounts are removed
+	// (eg: after a rollback) the balances of `addresses` (all of the network's
+	// when `None`) are replaced with ones summed up from the amounts that are
+	// not `excluded`. running it again gives the same result
+	pub async fn rebuild(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		addresses: Option<&[String]>,
+		excluded: &ExcludedRows,
+	) -> Result<()> {
+		let excluded_filter = excluded.to_filter();
+		let filters = match addresses {
+			Some(addresses) => utils::get_in_filters("address", addresses, REBUILD_CHUNK_SIZE),
+			None => vec!["true".to_string()],
+		};
+
+		for filter in filters.into_iter() {
+			warehouse
+				.delete(&format!(
+					r#"
+						SET allow_experimental_lightweight_delete = true;
+						DELETE FROM {TABLE} WHERE network_id = {network_id} AND {filter}
+					"#
+				))
+				.await?;
+
+			warehouse
+				.execute(&format!(
+					r#"
+						INSERT INTO {TABLE} (network_id, address, asset_address, balance)
+						SELECT
+						    network_id,
+						    address,
+						    asset_address,
+						    SUM(amount_in) - SUM(amount_out) as balance
+						FROM {AmountTable} FINAL
+						WHERE
+						    network_id = {network_id} AND
+						    {filter} AND
+						    {excluded_filter}
+						GROUP BY (network_id, address, asset_address)
+					"#
+				))
+				.await?;
+		}
+
+		Ok(())
+	}
+
 	pub async fn delete_all_by_network_id(
 		warehouse: &Warehouse,
 		network_ids: PrimaryIds,
@@ -0,0 +1,167 @@
+use chrono::NaiveDate;
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use super::balance::REBUILD_CHUNK_SIZE;
+use crate::{
+	chain::{u256, U256},
+	models::{AmountTable, ExcludedRows, PrimaryId, PrimaryIds},
+	utils,
+	warehouse::Warehouse,
+};
+
+pub static TABLE: &str = "balance_snapshots";
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	pub network_id: u64,
+	pub address: String,
+	pub asset_address: String,
+	// days since unix epoch
+	pub date: u16,
+	#[serde(with = "u256")]
+	pub balance: U256,
+}
+
+pub use Model as BalanceSnapshot;
+
+impl Model {
+	// end-of-day balances for every day the address had activity in
+	// `[from, to]`; days without activity carry over the previous balance
+	pub async fn get_all_by_address(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		address: &str,
+		from: NaiveDate,
+		to: NaiveDate,
+	) -> Result<Vec<Model>> {
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM (
+	                    SELECT
+	                        network_id,
+	                        address,
+	                        asset_address,
+	                        date,
+	                        SUM(SUM(balance_change)) OVER (
+	                            PARTITION BY asset_address
+	                            ORDER BY date
+	                        ) as balance
+	                    FROM {TABLE} FINAL
+	                    WHERE
+	                        network_id = {network_id} AND
+	                        address = '{address}' AND
+	                        date <= '{to}'
+	                    GROUP BY (network_id, address, asset_address, date)
+					)
+					WHERE date >= '{from}'
+					ORDER BY (asset_address, date)
+                "#
+			))
+			.await
+	}
+
+	// last activity date per asset, for dormancy checks
+	pub async fn get_last_active_dates(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		address: &str,
+	) -> Result<Vec<(String, u16)>> {
+		#[derive(Row, Deserialize)]
+		struct Data {
+			asset_address: String,
+			date: u16,
+		}
+
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT asset_address, MAX(date) as date
+					FROM {TABLE}
+					WHERE network_id = {network_id} AND address = '{address}'
+					GROUP BY asset_address
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d: Data| (d.asset_address, d.date))
+			.collect())
+	}
+
+	// balance changes of `addresses` (all of the network's when `None`) from
+	// the amounts that are not `excluded`; see `Balance::rebuild`
+	pub async fn rebuild(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		addresses: Option<&[String]>,
+		excluded: &ExcludedRows,
+	) -> Result<()> {
+		let excluded_filter = excluded.to_filter();
+		let filters = match addresses {
+			Some(addresses) => utils::get_in_filters("address", addresses, REBUILD_CHUNK_SIZE),
+			None => vec!["true".to_string()],
+		};
+
+		for filter in filters.into_iter() {
+			warehouse
+				.delete(&format!(
+					r#"
+						SET allow_experimental_lightweight_delete = true;
+						DELETE FROM {TABLE} WHERE network_id = {network_id} AND {filter}
+					"#
+				))
+				.await?;
+
+			warehouse
+				.execute(&format!(
+					r#"
+						INSERT INTO {TABLE} (
+						    network_id,
+						    address,
+						    asset_address,
+						    date,
+						    block_height,
+						    tx_hash,
+						    balance_change
+						)
+						SELECT
+						    network_id,
+						    address,
+						    asset_address,
+						    toDate(created_at) as date,
+						    block_height,
+						    tx_hash,
+						    (amount_in - amount_out) as balance_change
+						FROM {AmountTable} FINAL
+						WHERE
+						    network_id = {network_id} AND
+						    {filter} AND
+						    {excluded_filter}
+					"#
+				))
+				.await?;
+		}
+
+		Ok(())
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+}
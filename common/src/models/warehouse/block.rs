@@ -0,0 +1,373 @@
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::{
+		AddressActivity, Amount, AmountTable, ApprovalTable, Balance, BalanceSnapshot,
+		DecodedCallTable, FeeTable, Funder, LinkTable, NetworkStats, PrimaryId, PrimaryIds,
+		StakingDepositTable, Transfer, TransferTable, UserOperationTable,
+	},
+	warehouse::Warehouse,
+	BlockHeight,
+};
+
+pub static TABLE: &str = "blocks";
+
+// balances are rebuilt address by address up to this many of them, past which
+// all of the network's are
+const MAX_REBUILT_ADDRESSES: u64 = 100_000;
+
+// hashes of processed blocks, so non-canonical ones can be detected later
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	pub network_id: u64,
+	pub block_height: u64,
+	pub block_hash: String,
+	pub parent_hash: String,
+	pub created_at: u32,
+}
+
+pub use Model as Block;
+
+// raw rows that derived views get rebuilt without (eg: ones that are about to
+// be removed): the ones within `block_range` (inclusive) that were written by
+// `module_ids`, or by any module when empty
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExcludedRows {
+	pub block_range: Option<(BlockHeight, BlockHeight)>,
+	pub module_ids: Vec<u16>,
+}
+
+impl ExcludedRows {
+	pub fn new(block_range: (BlockHeight, BlockHeight), module_ids: Vec<u16>) -> Self {
+		Self { block_range: Some(block_range), module_ids }
+	}
+
+	pub fn to_filter(&self) -> String {
+		let Some((block_height_min, block_height_max)) = self.block_range else {
+			return "true".to_string();
+		};
+
+		let filter = format!(
+			"block_height >= {block_height_min} AND block_height <= {block_height_max} {}",
+			get_module_filter(&self.module_ids)
+		);
+
+		format!("NOT ({})", filter.trim_end())
+	}
+}
+
+// `AND module_id IN (..)`, or nothing for any module
+pub(crate) fn get_module_filter(module_ids: &[u16]) -> String {
+	match module_ids.is_empty() {
+		true => "".to_string(),
+		false => format!(
+			"AND module_id IN ({})",
+			module_ids.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(",")
+		),
+	}
+}
+
+// @NOTE rebuilds what the views summed up from the rows in `block_range`
+// (inclusive) that `module_ids` (any module when empty) wrote, without them
+async fn rebuild_views_without(
+	warehouse: &Warehouse,
+	network_id: PrimaryId,
+	block_range: (BlockHeight, BlockHeight),
+	module_ids: &[u16],
+) -> Result<()> {
+	let excluded = ExcludedRows::new(block_range, module_ids.to_vec());
+
+	let addresses = Amount::get_all_addresses_by_block_range(
+		warehouse,
+		network_id,
+		block_range,
+		module_ids,
+		MAX_REBUILT_ADDRESSES + 1,
+	)
+	.await?;
+	let addresses = match addresses.len() as u64 > MAX_REBUILT_ADDRESSES {
+		true => None,
+		false => Some(addresses),
+	};
+
+	let dates =
+		Transfer::get_all_dates_by_block_range(warehouse, network_id, block_range, module_ids)
+			.await?;
+
+	Balance::rebuild(warehouse, network_id, addresses.as_deref(), &excluded).await?;
+	BalanceSnapshot::rebuild(warehouse, network_id, addresses.as_deref(), &excluded).await?;
+	NetworkStats::rebuild(warehouse, network_id, Some(&dates), &excluded).await?;
+
+	Ok(())
+}
+
+impl Model {
+	pub fn new(
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+		block_hash: &str,
+		parent_hash: &str,
+		created_at: u32,
+	) -> Self {
+		Self {
+			network_id: network_id as u64,
+			block_height,
+			block_hash: block_hash.to_string(),
+			parent_hash: parent_hash.to_string(),
+			created_at,
+		}
+	}
+
+	// most recently processed blocks, highest first
+	pub async fn get_latest(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		limit: u64,
+	) -> Result<Vec<Self>> {
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT DISTINCT ON (block_height) *
+					FROM {TABLE}
+					WHERE network_id = {network_id}
+					ORDER BY block_height DESC, created_at DESC
+					LIMIT {limit}
+				"#
+			))
+			.await
+	}
+
+	// @NOTE removes everything that was processed from `block_height` onwards,
+	// so those blocks can be processed again. the views built from those rows
+	// (`balances`, `balance_snapshots` and `network_stats`) only see inserts,
+	// so the keys the removed rows touched are rebuilt from the rows below
+	// `block_height` first. that way a rollback that fails halfway can simply
+	// be run again
+	pub async fn rollback(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+	) -> Result<()> {
+		rebuild_views_without(warehouse, network_id, (block_height, BlockHeight::MAX), &[]).await?;
+
+		for table in [
+			TransferTable,
+			AmountTable,
+			LinkTable,
+			DecodedCallTable,
+			UserOperationTable,
+			FeeTable,
+			StakingDepositTable,
+			ApprovalTable,
+			TABLE,
+		] {
+			warehouse
+				.delete(&format!(
+					r#"
+						SET allow_experimental_lightweight_delete = true;
+						DELETE FROM {table}
+						WHERE network_id = {network_id} AND block_height >= {block_height}
+					"#
+				))
+				.await?;
+		}
+
+		Funder::rollback(warehouse, network_id, block_height).await?;
+		AddressActivity::rollback(warehouse, network_id, block_height).await?;
+
+		Ok(())
+	}
+
+	// @NOTE removes what `module_ids` wrote within `block_range` (inclusive), so
+	// those blocks can be processed again for them. like with a rollback, the
+	// views are rebuilt without those rows before they're removed. only
+	// `amounts` and `transfers` have views summed up from them; re-processed
+	// rows of the other tables replace the existing ones
+	pub async fn rewind_modules(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		block_range: (BlockHeight, BlockHeight),
+		module_ids: &[u16],
+	) -> Result<()> {
+		rebuild_views_without(warehouse, network_id, block_range, module_ids).await?;
+
+		Amount::delete_all_by_block_range(warehouse, network_id, block_range, module_ids).await?;
+		Transfer::delete_all_by_block_range(warehouse, network_id, block_range, module_ids).await?;
+
+		Ok(())
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		chain::{ModuleId, U256},
+		utils, Settings,
+	};
+	use chrono::Days;
+	use std::{collections::HashSet, sync::Arc};
+
+	#[test]
+	fn test_excluded_rows() {
+		assert_eq!(ExcludedRows::default().to_filter(), "true");
+		assert_eq!(
+			ExcludedRows::new((10, 20), vec![]).to_filter(),
+			"NOT (block_height >= 10 AND block_height <= 20)"
+		);
+		assert_eq!(
+			ExcludedRows::new((10, 20), vec![102, 103]).to_filter(),
+			"NOT (block_height >= 10 AND block_height <= 20 AND module_id IN (102,103))"
+		);
+	}
+
+	type State = (HashSet<Balance>, HashSet<BalanceSnapshot>, HashSet<NetworkStats>);
+
+	async fn get_state(warehouse: &Warehouse, network_id: PrimaryId) -> Result<State> {
+		let addresses = vec!["rollback_a".to_string(), "rollback_b".to_string()];
+		let today = utils::now().date();
+
+		let mut snapshots = HashSet::new();
+		for address in addresses.iter() {
+			snapshots.extend(
+				BalanceSnapshot::get_all_by_address(
+					warehouse,
+					network_id,
+					address,
+					today - Days::new(7),
+					today,
+				)
+				.await?,
+			);
+		}
+
+		Ok((
+			Balance::get_all_by_addresses(warehouse, addresses)
+				.await?
+				.into_iter()
+				.filter(|b| b.network_id == network_id as u64 && !b.balance.is_zero())
+				.collect(),
+			snapshots,
+			NetworkStats::get_all_by_network_id(warehouse, network_id, 7)
+				.await?
+				.into_iter()
+				.collect(),
+		))
+	}
+
+	async fn process_block(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+		created_at: u32,
+		(from, to, amount): (&str, &str, u64),
+	) -> Result<()> {
+		let amount = U256::from(amount);
+		let module_id = ModuleId::BitcoinTransfer;
+
+		warehouse
+			.insert(
+				TransferTable,
+				&[Transfer::new(
+					module_id,
+					network_id,
+					block_height,
+					"tx",
+					0,
+					from,
+					to,
+					None,
+					amount,
+					amount,
+					created_at,
+				)],
+			)
+			.await?;
+
+		let mut amounts = vec![Amount::new(
+			module_id,
+			network_id,
+			block_height,
+			"tx",
+			to,
+			None,
+			amount,
+			U256::zero(),
+			created_at,
+		)];
+		if !from.is_empty() {
+			amounts.push(Amount::new(
+				module_id,
+				network_id,
+				block_height,
+				"tx",
+				from,
+				None,
+				U256::zero(),
+				amount,
+				created_at,
+			));
+		}
+		warehouse.insert(AmountTable, &amounts).await?;
+
+		warehouse
+			.insert(TABLE, &[Block::new(network_id, block_height, "hash", "parent", created_at)])
+			.await
+	}
+
+	// @NOTE needs a clickhouse warehouse to write to, eg:
+	// `BARRELEYE_TEST_WAREHOUSE=http://localhost:8123/barreleye_test cargo test -- --ignored`
+	#[tokio::test]
+	#[ignore]
+	async fn test_rollback_and_replay() -> Result<()> {
+		let url = std::env::var("BARRELEYE_TEST_WAREHOUSE")?;
+		let (settings, _) = Settings::from_args(["barreleye", "--warehouse", &url]).await?;
+		let warehouse = Warehouse::new(Arc::new(settings)).await?;
+		warehouse.run_migrations().await?;
+
+		let network_id = 1_000_000;
+		let now = utils::now().and_utc().timestamp() as u32;
+		let yesterday = now - 86_400;
+
+		Block::delete_all_by_network_id(&warehouse, vec![network_id].into()).await?;
+		Transfer::delete_all_by_network_id(&warehouse, vec![network_id].into()).await?;
+		Amount::delete_all_by_network_id(&warehouse, vec![network_id].into()).await?;
+		Balance::delete_all_by_network_id(&warehouse, vec![network_id].into()).await?;
+		BalanceSnapshot::delete_all_by_network_id(&warehouse, vec![network_id].into()).await?;
+		NetworkStats::delete_all_by_network_id(&warehouse, vec![network_id].into()).await?;
+
+		process_block(&warehouse, network_id, 1, yesterday, ("", "rollback_a", 100)).await?;
+		let before = get_state(&warehouse, network_id).await?;
+
+		process_block(&warehouse, network_id, 2, now, ("rollback_a", "rollback_b", 40)).await?;
+		let after = get_state(&warehouse, network_id).await?;
+		assert_ne!(before, after);
+
+		Block::rollback(&warehouse, network_id, 2).await?;
+		assert_eq!(get_state(&warehouse, network_id).await?, before);
+
+		process_block(&warehouse, network_id, 2, now, ("rollback_a", "rollback_b", 40)).await?;
+		assert_eq!(get_state(&warehouse, network_id).await?, after);
+
+		Ok(())
+	}
+}
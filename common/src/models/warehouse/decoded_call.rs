@@ -0,0 +1,99 @@
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	chain::ModuleId,
+	models::{PrimaryId, PrimaryIds},
+	warehouse::Warehouse,
+	BlockHeight,
+};
+
+pub static TABLE: &str = "decoded_calls";
+
+// contract function calls & emitted events, decoded with uploaded abis
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	pub module_id: u16,
+	pub network_id: u64,
+	pub block_height: u64,
+	pub tx_hash: String,
+	// 0 for function calls
+	pub log_index: u64,
+	pub contract_address: String,
+	pub kind: String,
+	pub name: String,
+	pub signature: String,
+	// json object of argument name -> value
+	pub args: String,
+	pub created_at: u32,
+}
+
+pub use Model as DecodedCall;
+
+impl Model {
+	pub const KIND_FUNCTION: &'static str = "function";
+	pub const KIND_EVENT: &'static str = "event";
+
+	pub fn new(
+		module_id: ModuleId,
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+		tx_hash: &str,
+		log_index: Option<u64>,
+		contract_address: &str,
+		name: &str,
+		signature: &str,
+		args: String,
+		created_at: u32,
+	) -> Self {
+		Self {
+			module_id: module_id.into(),
+			network_id: network_id as u64,
+			block_height,
+			tx_hash: tx_hash.to_string(),
+			log_index: log_index.unwrap_or_default(),
+			contract_address: contract_address.to_string(),
+			kind: if log_index.is_some() { Self::KIND_EVENT } else { Self::KIND_FUNCTION }
+				.to_string(),
+			name: name.to_string(),
+			signature: signature.to_string(),
+			args,
+			created_at,
+		}
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+
+	// rows written by a single module, eg: before it gets resynced
+	pub async fn delete_all_by_module_id(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		module_id: ModuleId,
+	) -> Result<()> {
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id = {network_id} AND module_id = {}
+				"#,
+				u16::from(module_id)
+			))
+			.await
+	}
+}
@@ -0,0 +1,138 @@
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	chain::{u256, ModuleId, U256},
+	models::{PrimaryId, PrimaryIds},
+	utils,
+	warehouse::Warehouse,
+	BlockHeight,
+};
+
+pub static TABLE: &str = "fees";
+
+// gas paid per transaction (incl l1 data fees on rollups)
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	pub module_id: u16,
+	pub network_id: u64,
+	pub block_height: u64,
+	pub tx_hash: String,
+	pub address: String,
+	pub gas_used: u64,
+	#[serde(with = "u256")]
+	pub effective_gas_price: U256,
+	#[serde(with = "u256")]
+	pub l1_fee: U256,
+	#[serde(with = "u256")]
+	pub fee: U256,
+	pub created_at: u32,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct FeeSummary {
+	pub network_id: u64,
+	pub transactions: u64,
+	pub gas_used: u64,
+	#[serde(with = "u256")]
+	pub total_fee: U256,
+}
+
+pub use Model as Fee;
+
+impl Model {
+	pub fn new(
+		module_id: ModuleId,
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+		tx_hash: &str,
+		address: &str,
+		gas_used: u64,
+		effective_gas_price: U256,
+		l1_fee: U256,
+		created_at: u32,
+	) -> Self {
+		Self {
+			module_id: module_id.into(),
+			network_id: network_id as u64,
+			block_height,
+			tx_hash: tx_hash.to_string(),
+			address: address.to_string(),
+			gas_used,
+			effective_gas_price,
+			l1_fee,
+			fee: effective_gas_price * gas_used + l1_fee,
+			created_at,
+		}
+	}
+
+	// totals per network, optionally only for fees paid by `address`
+	pub async fn get_summaries(
+		warehouse: &Warehouse,
+		network_ids: Option<PrimaryIds>,
+		address: Option<String>,
+	) -> Result<Vec<FeeSummary>> {
+		let mut conditions = vec!["1 = 1".to_string()];
+		if let Some(network_ids) = network_ids {
+			let network_ids_string =
+				network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+			conditions.push(format!("network_id IN ({network_ids_string})"));
+		}
+		if let Some(address) = address {
+			let escaped_address = utils::escape_sql_string(&address);
+			conditions.push(format!("address = '{escaped_address}'"));
+		}
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT
+					    network_id,
+					    count() as transactions,
+					    sum(gas_used) as gas_used,
+					    sum(fee) as total_fee
+					FROM {TABLE}
+					WHERE {}
+					GROUP BY network_id
+					ORDER BY network_id
+                "#,
+				conditions.join(" AND ")
+			))
+			.await
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+
+	// rows written by a single module, eg: before it gets resynced
+	pub async fn delete_all_by_module_id(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		module_id: ModuleId,
+	) -> Result<()> {
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id = {network_id} AND module_id = {}
+				"#,
+				u16::from(module_id)
+			))
+			.await
+	}
+}
@@ -0,0 +1,96 @@
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::{PrimaryId, PrimaryIds},
+	warehouse::Warehouse,
+	BlockHeight,
+};
+
+pub static TABLE: &str = "funders";
+
+// first transfer an address ever received (and who sent it)
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	pub network_id: u64,
+	pub address: String,
+	pub funder: String,
+	pub block_height: u64,
+	pub tx_hash: String,
+	pub created_at: u32,
+}
+
+pub use Model as Funder;
+
+impl Model {
+	pub async fn get_all_by_addresses(
+		warehouse: &Warehouse,
+		mut addresses: Vec<String>,
+	) -> Result<Vec<Self>> {
+		addresses.sort_unstable();
+		addresses.dedup();
+
+		let formatted_addresses =
+			addresses.iter().map(|addr| format!("'{}'", addr)).collect::<Vec<_>>().join(", ");
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT
+					    network_id,
+					    address,
+					    tupleElement(first_transfer, 3) as funder,
+					    tupleElement(first_transfer, 1) as block_height,
+					    tupleElement(first_transfer, 2) as tx_hash,
+					    toUInt32(tupleElement(first_transfer, 4)) as created_at
+					FROM (
+					    SELECT
+					        network_id,
+					        address,
+					        min(first_transfer) as first_transfer
+					    FROM {TABLE}
+					    WHERE address IN ({formatted_addresses})
+					    GROUP BY (network_id, address)
+					)
+                "#
+			))
+			.await
+	}
+
+	// funded in blocks that are no longer canonical
+	pub async fn rollback(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+	) -> Result<()> {
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						tupleElement(first_transfer, 1) >= {block_height}
+				"#
+			))
+			.await
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+}
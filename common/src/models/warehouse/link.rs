@@ -6,6 +6,7 @@ use uuid::Uuid;
 
 use crate::{
 	models::{warehouse::transfer::TABLE as TRANSFERS_TABLE, PrimaryId, PrimaryIds},
+	utils,
 	warehouse::Warehouse,
 	BlockHeight,
 };
@@ -208,7 +209,7 @@ impl Model {
 			.map(|(network_id, addresses)| {
 				let escaped_addresses = addresses
 					.into_iter()
-					.map(|a| format!("'{}'", a.replace('\\', "\\\\").replace('\'', "\\'")))
+					.map(|a| format!("'{}'", utils::escape_sql_string(&a)))
 					.collect::<Vec<String>>()
 					.join(",");
 
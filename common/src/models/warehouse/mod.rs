@@ -1,9 +1,35 @@
-pub use amount::{Amount, TABLE as AmountTable};
+pub use address_activity::{AddressActivity, TABLE as AddressActivityTable};
+pub use amount::{Amount, DailyAmount, TABLE as AmountTable};
+pub use approval::{Allowance, Approval, TABLE as ApprovalTable};
 pub use balance::{Balance, TABLE as BalanceTable};
+pub use balance_snapshot::{BalanceSnapshot, TABLE as BalanceSnapshotTable};
+pub use block::{Block, ExcludedRows, TABLE as BlockTable};
+pub use decoded_call::{DecodedCall, TABLE as DecodedCallTable};
+pub use fee::{Fee, FeeSummary, TABLE as FeeTable};
+pub use funder::{Funder, TABLE as FunderTable};
 pub use link::{Link, LinkUuid, TABLE as LinkTable};
-pub use transfer::{Transfer, TABLE as TransferTable};
+pub use network_stats::{
+	NetworkStats, StatsInterval, StatsPeriod, ValueMoved, TABLE as NetworkStatsTable,
+};
+pub use price::{Price, TABLE as PriceTable};
+pub use staking_deposit::{StakingDeposit, TABLE as StakingDepositTable};
+pub use transfer::{FeePeriod, Transfer, TABLE as TransferTable};
+pub use transfer_filter::{FilterCondition, FilterField, FilterOp, TransferFilter};
+pub use user_operation::{UserOperation, TABLE as UserOperationTable};
 
+mod address_activity;
 mod amount;
+mod approval;
 mod balance;
+mod balance_snapshot;
+mod block;
+mod decoded_call;
+mod fee;
+mod funder;
 mod link;
+mod network_stats;
+mod price;
+mod staking_deposit;
 mod transfer;
+mod transfer_filter;
+mod user_operation;
@@ -0,0 +1,220 @@
+use chrono::NaiveDate;
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	chain::{u256, U256},
+	models::{ExcludedRows, PrimaryId, PrimaryIds, TransferTable},
+	utils,
+	warehouse::Warehouse,
+};
+
+pub static TABLE: &str = "network_stats";
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	// days since unix epoch
+	pub date: u16,
+	pub transactions: u64,
+	pub transfers: u64,
+	pub active_addresses: u64,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct ValueMoved {
+	pub date: u16,
+	pub asset_address: String,
+	#[serde(with = "u256")]
+	pub value_moved: U256,
+}
+
+// totals of a single asset within a day, week or month
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct StatsPeriod {
+	// days since unix epoch (first day of the period)
+	pub period: u16,
+	pub transactions: u64,
+	pub transfers: u64,
+	pub active_addresses: u64,
+	#[serde(with = "u256")]
+	pub value_moved: U256,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StatsInterval {
+	#[default]
+	Day,
+	Week,
+	Month,
+}
+
+pub use Model as NetworkStats;
+
+impl Model {
+	// daily totals across all assets for the last `days` days
+	pub async fn get_all_by_network_id(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		days: u16,
+	) -> Result<Vec<Model>> {
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT
+					    date,
+					    uniq(tx_hash) as transactions,
+					    count() as transfers,
+					    uniqArray([from_address, to_address]) as active_addresses
+					FROM {TABLE} FINAL
+					WHERE network_id = {network_id} AND date > today() - {days}
+					GROUP BY date
+					ORDER BY date
+                "#
+			))
+			.await
+	}
+
+	// daily value moved per asset for the last `days` days
+	pub async fn get_all_values_by_network_id(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		days: u16,
+	) -> Result<Vec<ValueMoved>> {
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT
+					    date,
+					    asset_address,
+					    sum(relative_amount) as value_moved
+					FROM {TABLE} FINAL
+					WHERE network_id = {network_id} AND date > today() - {days}
+					GROUP BY (date, asset_address)
+					ORDER BY (date, asset_address)
+                "#
+			))
+			.await
+	}
+
+	// @NOTE rolls up the daily stats of one asset (empty `asset_address` for the
+	// native one) into periods of `interval`. weeks start on monday, and
+	// `from`/`to` are inclusive
+	pub async fn get_periods_by_asset_address(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		asset_address: &str,
+		interval: StatsInterval,
+		from: Option<NaiveDate>,
+		to: Option<NaiveDate>,
+	) -> Result<Vec<StatsPeriod>> {
+		let mut filters = vec![
+			format!("network_id = {network_id}"),
+			format!("asset_address = '{asset_address}'"),
+		];
+		if let Some(from) = from {
+			filters.push(format!("date >= '{from}'"));
+		}
+		if let Some(to) = to {
+			filters.push(format!("date <= '{to}'"));
+		}
+		let filters = filters.join(" AND ");
+
+		let period = match interval {
+			StatsInterval::Day => "date",
+			StatsInterval::Week => "toMonday(date)",
+			StatsInterval::Month => "toStartOfMonth(date)",
+		};
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT
+					    toUInt16({period}) as period,
+					    uniq(tx_hash) as transactions,
+					    count() as transfers,
+					    uniqArray([from_address, to_address]) as active_addresses,
+					    sum(relative_amount) as value_moved
+					FROM {TABLE} FINAL
+					WHERE {filters}
+					GROUP BY period
+					ORDER BY period
+                "#
+			))
+			.await
+	}
+
+	// @NOTE the rows of `dates` (days since unix epoch, all of the network's
+	// when `None`) are replaced with the transfers that are not `excluded`
+	pub async fn rebuild(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		dates: Option<&[u16]>,
+		excluded: &ExcludedRows,
+	) -> Result<()> {
+		let excluded_filter = excluded.to_filter();
+		let (date_filter, created_at_filter) = match dates {
+			Some([]) => return Ok(()),
+			Some(dates) => {
+				let dates = dates
+					.iter()
+					.map(|d| format!("'{}'", utils::date_from_days(*d)))
+					.collect::<Vec<String>>()
+					.join(",");
+				(format!("date IN ({dates})"), format!("toDate(created_at) IN ({dates})"))
+			}
+			None => ("true".to_string(), "true".to_string()),
+		};
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id = {network_id} AND {date_filter}
+				"#
+			))
+			.await?;
+
+		warehouse
+			.execute(&format!(
+				r#"
+					INSERT INTO {TABLE}
+					SELECT
+					    network_id,
+					    toDate(created_at) as date,
+					    asset_address,
+					    uuid,
+					    tx_hash,
+					    from_address,
+					    to_address,
+					    relative_amount
+					FROM {TransferTable} FINAL
+					WHERE
+					    network_id = {network_id} AND
+					    {created_at_filter} AND
+					    {excluded_filter}
+				"#
+			))
+			.await?;
+
+		Ok(())
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+}
@@ -0,0 +1,169 @@
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+	models::{PrimaryId, PrimaryIds},
+	utils,
+	warehouse::Warehouse,
+};
+
+pub static TABLE: &str = "prices";
+
+// a native price older than this is not reported as the current one
+const MAX_LATEST_AGE_DAYS: u16 = 7;
+
+// usd price of an asset on a day, as of `updated_at`
+#[derive(PartialEq, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	pub network_id: u64,
+	// empty for the native asset
+	pub asset_address: String,
+	// days since unix epoch
+	pub date: u16,
+	pub price: f64,
+	pub updated_at: u32,
+}
+
+pub use Model as Price;
+
+impl Model {
+	pub fn new(
+		network_id: PrimaryId,
+		asset_address: &str,
+		date: u16,
+		price: f64,
+		updated_at: u32,
+	) -> Self {
+		Self {
+			network_id: network_id as u64,
+			asset_address: asset_address.to_string(),
+			date,
+			price,
+			updated_at,
+		}
+	}
+
+	pub async fn create_many(warehouse: &Warehouse, models: Vec<Self>) -> Result<()> {
+		warehouse.insert(TABLE, &models).await
+	}
+
+	// most recently fetched native price of a network
+	pub async fn get_last_native_by_network_id(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+	) -> Result<Option<Self>> {
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM {TABLE}
+					WHERE network_id = {network_id} AND asset_address = ''
+					ORDER BY date DESC, updated_at DESC
+					LIMIT 1
+                "#
+			))
+			.await?
+			.pop())
+	}
+
+	// current native price of each network that has a recent one
+	pub async fn get_latest_native_by_network_ids(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<HashMap<PrimaryId, f64>> {
+		#[derive(Row, Deserialize)]
+		struct Data {
+			network_id: u64,
+			latest_price: f64,
+		}
+
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+		if network_ids_string.is_empty() {
+			return Ok(HashMap::new());
+		}
+
+		Ok(warehouse
+			.select::<Data>(&format!(
+				r#"
+					SELECT
+					    network_id,
+					    argMax(price, (date, updated_at)) as latest_price
+					FROM {TABLE}
+					WHERE
+					    network_id IN ({network_ids_string}) AND
+					    asset_address = '' AND
+					    date > today() - {MAX_LATEST_AGE_DAYS}
+					GROUP BY network_id
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d| (d.network_id as PrimaryId, d.latest_price))
+			.collect())
+	}
+
+	// native price of each day between `from` and `to` (inclusive, in days
+	// since unix epoch), keyed by network and day
+	pub async fn get_all_native_by_dates(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+		from: u16,
+		to: u16,
+	) -> Result<HashMap<(PrimaryId, u16), f64>> {
+		#[derive(Row, Deserialize)]
+		struct Data {
+			network_id: u64,
+			date: u16,
+			day_price: f64,
+		}
+
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+		if network_ids_string.is_empty() {
+			return Ok(HashMap::new());
+		}
+
+		let (from, to) = (utils::date_from_days(from), utils::date_from_days(to));
+
+		Ok(warehouse
+			.select::<Data>(&format!(
+				r#"
+					SELECT
+					    network_id,
+					    date,
+					    argMax(price, updated_at) as day_price
+					FROM {TABLE}
+					WHERE
+					    network_id IN ({network_ids_string}) AND
+					    asset_address = '' AND
+					    date >= '{from}' AND
+					    date <= '{to}'
+					GROUP BY (network_id, date)
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d| ((d.network_id as PrimaryId, d.date), d.day_price))
+			.collect())
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+}
@@ -0,0 +1,113 @@
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	chain::{u256, U256},
+	models::{PrimaryId, PrimaryIds},
+	utils,
+	warehouse::Warehouse,
+	BlockHeight,
+};
+
+pub static TABLE: &str = "staking_deposits";
+
+// deposits into the beacon chain deposit contract
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	pub network_id: u64,
+	pub block_height: u64,
+	pub tx_hash: String,
+	pub deposit_index: u64,
+	pub depositor: String,
+	pub pubkey: String,
+	pub withdrawal_credentials: String,
+	// empty for bls (0x00) credentials, which don't point to an address
+	pub withdrawal_address: String,
+	#[serde(with = "u256")]
+	pub amount: U256,
+	pub created_at: u32,
+}
+
+pub use Model as StakingDeposit;
+
+impl Model {
+	#[allow(clippy::too_many_arguments)]
+	pub fn new(
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+		tx_hash: &str,
+		deposit_index: u64,
+		depositor: &str,
+		pubkey: &str,
+		withdrawal_credentials: &str,
+		withdrawal_address: Option<String>,
+		amount: U256,
+		created_at: u32,
+	) -> Self {
+		Self {
+			network_id: network_id as u64,
+			block_height,
+			tx_hash: tx_hash.to_string(),
+			deposit_index,
+			depositor: depositor.to_string(),
+			pubkey: pubkey.to_string(),
+			withdrawal_credentials: withdrawal_credentials.to_string(),
+			withdrawal_address: withdrawal_address.unwrap_or_default(),
+			amount,
+			created_at,
+		}
+	}
+
+	// total staked on behalf of `address` as of `block_height` (inclusive).
+	// deposits count towards whoever can withdraw them, or the depositor if
+	// that's not an address
+	pub async fn get_staked_at_block_height(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		address: &str,
+		block_height: BlockHeight,
+	) -> Result<U256> {
+		#[derive(Row, Deserialize)]
+		struct Data {
+			#[serde(with = "u256")]
+			amount: U256,
+		}
+
+		let escaped_address = utils::escape_sql_string(address);
+
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT SUM(amount) as amount
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						if(withdrawal_address != '', withdrawal_address, depositor) = '{escaped_address}' AND
+						block_height <= {block_height}
+                "#
+			))
+			.await?
+			.into_iter()
+			.next()
+			.map(|d: Data| d.amount)
+			.unwrap_or_default())
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+}
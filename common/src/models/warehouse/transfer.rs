@@ -1,8 +1,11 @@
+use chrono::NaiveDate;
 use clickhouse::Row;
 use eyre::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use super::block::get_module_filter;
 use crate::{
 	chain::{u256, ModuleId, U256},
 	models::{PrimaryId, PrimaryIds},
@@ -21,6 +24,8 @@ pub struct Model {
 	pub network_id: u64,
 	pub block_height: u64,
 	pub tx_hash: String,
+	// 0 for transfers that don't come from an event log
+	pub log_index: u64,
 	pub from_address: String,
 	pub to_address: String,
 	pub asset_address: String,
@@ -31,14 +36,27 @@ pub struct Model {
 	pub created_at: u32,
 }
 
+// fees paid by an address within a day or month
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct FeePeriod {
+	pub network_id: u64,
+	// days since unix epoch (first day of the period)
+	pub date: u16,
+	pub transactions: u64,
+	#[serde(with = "u256")]
+	pub total_fee: U256,
+}
+
 pub use Model as Transfer;
 
 impl Model {
+	#[allow(clippy::too_many_arguments)]
 	pub fn new(
 		module_id: ModuleId,
 		network_id: PrimaryId,
 		block_height: u64,
 		tx_hash: &str,
+		log_index: u64,
 		from_address: &str,
 		to_address: &str,
 		asset_address: Option<String>,
@@ -46,19 +64,43 @@ impl Model {
 		batch_amount: U256,
 		created_at: u32,
 	) -> Self {
-		Self {
-			uuid: utils::new_uuid(),
-			module_id: module_id as u16,
+		let mut ret = Self {
+			uuid: Uuid::nil(),
+			module_id: module_id.into(),
 			network_id: network_id as u64,
 			block_height,
 			tx_hash: tx_hash.to_string(),
+			log_index,
 			from_address: from_address.to_string(),
 			to_address: to_address.to_string(),
 			asset_address: asset_address.unwrap_or_default(),
 			relative_amount,
 			batch_amount,
 			created_at,
-		}
+		};
+
+		ret.uuid = ret.get_deterministic_uuid();
+		ret
+	}
+
+	// @NOTE derived from the same columns the warehouse deduplicates rows by
+	// (`ReplacingMergeTree` sort key), so re-processing a block after a crash
+	// produces identical rows that collapse into one, and links keep pointing
+	// at the uuid of whichever row survives
+	fn get_deterministic_uuid(&self) -> Uuid {
+		utils::new_deterministic_uuid(&format!(
+			"transfer:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}",
+			self.module_id,
+			self.network_id,
+			self.block_height,
+			self.tx_hash,
+			self.log_index,
+			self.from_address,
+			self.to_address,
+			self.asset_address,
+			self.relative_amount,
+			self.batch_amount,
+		))
 	}
 
 	pub async fn get_first_by_source(
@@ -104,6 +146,483 @@ impl Model {
 			.await
 	}
 
+	// @NOTE same as `get_all_by_block_range`, but without fee transfers. those
+	// have no recipient (`to_address` is empty), so anything that follows
+	// funds from one address to another should skip them
+	pub async fn get_all_by_block_range_excluding_fees(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		(block_height_min, block_height_max): (BlockHeight, BlockHeight),
+	) -> Result<Vec<Self>> {
+		let fee_module_ids_string = [ModuleId::BitcoinFeeTransfer, ModuleId::EvmFeeTransfer]
+			.into_iter()
+			.map(|m| u16::from(m).to_string())
+			.collect::<Vec<String>>()
+			.join(",");
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						to_address != '' AND
+						module_id NOT IN ({fee_module_ids_string}) AND
+						block_height >= {block_height_min} AND
+						block_height <= {block_height_max}
+					ORDER BY block_height ASC
+                "#
+			))
+			.await
+	}
+
+	// days (since unix epoch) that a block range's transfers (of `module_ids`, or
+	// any module when empty) happened on
+	pub async fn get_all_dates_by_block_range(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		(block_height_min, block_height_max): (BlockHeight, BlockHeight),
+		module_ids: &[u16],
+	) -> Result<Vec<u16>> {
+		let module_filter = get_module_filter(module_ids);
+
+		#[derive(Row, Deserialize)]
+		struct Data {
+			date: u16,
+		}
+
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT DISTINCT toDate(created_at) as date
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						block_height >= {block_height_min} AND
+						block_height <= {block_height_max}
+						{module_filter}
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d: Data| d.date)
+			.collect())
+	}
+
+	pub async fn get_all_by_tx_hash(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		tx_hash: &str,
+	) -> Result<Vec<Self>> {
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						tx_hash = '{tx_hash}'
+                "#
+			))
+			.await
+	}
+
+	pub async fn get_all_by_uuids(warehouse: &Warehouse, uuids: Vec<Uuid>) -> Result<Vec<Self>> {
+		if uuids.is_empty() {
+			return Ok(vec![]);
+		}
+
+		let uuids_string =
+			uuids.into_iter().map(|uuid| format!("'{uuid}'")).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM {TABLE}
+					WHERE uuid IN ({uuids_string})
+                "#
+			))
+			.await
+	}
+
+	// latest transfers sent or received by any of `addresses`, across networks
+	pub async fn get_latest_by_addresses(
+		warehouse: &Warehouse,
+		mut addresses: Vec<String>,
+		limit: u64,
+	) -> Result<Vec<Self>> {
+		addresses.sort_unstable();
+		addresses.dedup();
+		if addresses.is_empty() {
+			return Ok(vec![]);
+		}
+
+		let formatted_addresses =
+			addresses.iter().map(|addr| format!("'{}'", addr)).collect::<Vec<_>>().join(", ");
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM {TABLE}
+					WHERE
+						from_address IN ({formatted_addresses}) OR
+						to_address IN ({formatted_addresses})
+					ORDER BY created_at DESC, uuid ASC
+					LIMIT {limit}
+                "#
+			))
+			.await
+	}
+
+	// transfers sent by `from_address` into any of `to_addresses` (network_id ->
+	// addresses) within `created_at_range`, latest first. fee transfers are
+	// left out, since they're never sent to the addresses themselves
+	pub async fn get_all_into_addresses(
+		warehouse: &Warehouse,
+		from_address: &str,
+		to_addresses: HashMap<PrimaryId, HashSet<String>>,
+		(created_at_min, created_at_max): (u32, u32),
+	) -> Result<Vec<Self>> {
+		if to_addresses.is_empty() {
+			return Ok(vec![]);
+		}
+
+		let to_addresses_condition = to_addresses
+			.into_iter()
+			.map(|(network_id, addresses)| {
+				let escaped_addresses = addresses
+					.into_iter()
+					.map(|a| format!("'{}'", utils::escape_sql_string(&a)))
+					.collect::<Vec<String>>()
+					.join(",");
+
+				format!("(network_id = {network_id} AND to_address IN ({escaped_addresses}))")
+			})
+			.collect::<Vec<String>>()
+			.join(" OR ");
+		let fee_module_ids_string = [ModuleId::BitcoinFeeTransfer, ModuleId::EvmFeeTransfer]
+			.into_iter()
+			.map(|m| u16::from(m).to_string())
+			.collect::<Vec<String>>()
+			.join(",");
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM {TABLE}
+					WHERE
+						from_address = '{from_address}' AND
+						({to_addresses_condition}) AND
+						module_id NOT IN ({fee_module_ids_string}) AND
+						created_at >= toDateTime({created_at_min}) AND
+						created_at <= toDateTime({created_at_max})
+					ORDER BY created_at DESC
+                "#
+			))
+			.await
+	}
+
+	// @NOTE address each block's reward went to, per block. that's the largest
+	// coinbase output on bitcoin (pools like ocean pay out many miners straight
+	// from the coinbase), and the recipient of priority fees on evm chains
+	pub async fn get_producers_by_block_range(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		block_range: (BlockHeight, BlockHeight),
+	) -> Result<Vec<(BlockHeight, String)>> {
+		#[derive(Row, Deserialize)]
+		struct Data {
+			block_height: u64,
+			address: String,
+		}
+
+		let (block_height_min, block_height_max) = block_range;
+		let module_ids_string = [ModuleId::BitcoinCoinbase, ModuleId::EvmFeeTransfer]
+			.into_iter()
+			.map(|m| u16::from(m).to_string())
+			.collect::<Vec<String>>()
+			.join(",");
+
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT
+						block_height,
+						argMax(to_address, relative_amount) as address
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						module_id IN ({module_ids_string}) AND
+						to_address != '' AND
+						block_height >= {block_height_min} AND
+						block_height <= {block_height_max}
+					GROUP BY block_height
+					ORDER BY block_height ASC
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d: Data| (d.block_height, d.address))
+			.collect())
+	}
+
+	// total sent and number of transfers per address before `block_height`
+	pub async fn get_sent_totals_by_addresses(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		asset_address: &str,
+		mut addresses: Vec<String>,
+		block_height: BlockHeight,
+	) -> Result<HashMap<String, (U256, u64)>> {
+		#[derive(Row, Deserialize)]
+		struct Data {
+			from_address: String,
+			#[serde(with = "u256")]
+			total: U256,
+			count: u64,
+		}
+
+		addresses.sort_unstable();
+		addresses.dedup();
+
+		let formatted_addresses =
+			addresses.iter().map(|addr| format!("'{}'", addr)).collect::<Vec<_>>().join(", ");
+
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT
+						from_address,
+						SUM(relative_amount) as total,
+						COUNT() as count
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						asset_address = '{asset_address}' AND
+						from_address IN ({formatted_addresses}) AND
+						block_height < {block_height}
+					GROUP BY from_address
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d: Data| (d.from_address, (d.total, d.count)))
+			.collect())
+	}
+
+	// number of distinct addresses that sent to each of `addresses` up to (and
+	// including) `block_height`, not counting fee transfers
+	pub async fn get_sender_counts_by_addresses(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		mut addresses: Vec<String>,
+		block_height: BlockHeight,
+	) -> Result<HashMap<String, u64>> {
+		#[derive(Row, Deserialize)]
+		struct Data {
+			to_address: String,
+			senders: u64,
+		}
+
+		addresses.sort_unstable();
+		addresses.dedup();
+		if addresses.is_empty() {
+			return Ok(HashMap::new());
+		}
+
+		let formatted_addresses = addresses
+			.iter()
+			.map(|a| format!("'{}'", utils::escape_sql_string(a)))
+			.collect::<Vec<String>>()
+			.join(",");
+		let fee_module_ids_string = [ModuleId::BitcoinFeeTransfer, ModuleId::EvmFeeTransfer]
+			.into_iter()
+			.map(|m| u16::from(m).to_string())
+			.collect::<Vec<String>>()
+			.join(",");
+
+		Ok(warehouse
+			.select(&format!(
+				r#"
+					SELECT
+						to_address,
+						uniqExact(from_address) as senders
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						to_address IN ({formatted_addresses}) AND
+						from_address != '' AND
+						module_id NOT IN ({fee_module_ids_string}) AND
+						block_height <= {block_height}
+					GROUP BY to_address
+                "#
+			))
+			.await?
+			.into_iter()
+			.map(|d: Data| (d.to_address, d.senders))
+			.collect())
+	}
+
+	// transfers into `to_address` of at least `amount` within a block range
+	pub async fn get_all_by_to_address_and_min_amount(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		to_address: &str,
+		amount: U256,
+		(block_height_min, block_height_max): (BlockHeight, BlockHeight),
+	) -> Result<Vec<Self>> {
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						to_address = '{to_address}' AND
+						relative_amount >= {amount} AND
+						block_height >= {block_height_min} AND
+						block_height <= {block_height_max}
+					ORDER BY block_height ASC
+                "#
+			))
+			.await
+	}
+
+	// @NOTE transfers sent by any of `from_addresses` (address -> lowest block
+	// height to consider, eg: when funds arrived there), earliest first. meant
+	// for walking the transfer graph one hop at a time, so fee transfers and
+	// burns are left out since funds don't move on from there
+	pub async fn get_all_from_addresses(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		from_addresses: HashMap<String, BlockHeight>,
+		limit: u64,
+	) -> Result<Vec<Self>> {
+		let Some(block_height_min) = from_addresses.values().min().copied() else {
+			return Ok(vec![]);
+		};
+
+		let formatted_addresses = from_addresses
+			.keys()
+			.map(|a| format!("'{}'", utils::escape_sql_string(a)))
+			.collect::<Vec<String>>()
+			.join(",");
+		let fee_module_ids_string = [ModuleId::BitcoinFeeTransfer, ModuleId::EvmFeeTransfer]
+			.into_iter()
+			.map(|m| u16::from(m).to_string())
+			.collect::<Vec<String>>()
+			.join(",");
+
+		let transfers: Vec<Self> = warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						from_address IN ({formatted_addresses}) AND
+						to_address != '' AND
+						module_id NOT IN ({fee_module_ids_string}) AND
+						block_height >= {block_height_min}
+					ORDER BY block_height ASC, uuid ASC
+					LIMIT {limit}
+                "#
+			))
+			.await?;
+
+		Ok(transfers
+			.into_iter()
+			.filter(|t| {
+				from_addresses.get(&t.from_address).is_some_and(|min| t.block_height >= *min)
+			})
+			.collect())
+	}
+
+	// latest transfers of a network, optionally only those from or to `address`
+	// and matching `conditions` (eg: sql built by `TransferFilter`). paginated
+	// by keyset (`cursor` being the last row's block height and uuid), so
+	// pages stay stable while new blocks are inserted
+	pub async fn get_all_by_address_paginated(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		address: Option<&str>,
+		conditions: Option<String>,
+		cursor: Option<(BlockHeight, Uuid)>,
+		limit: u64,
+	) -> Result<Vec<Self>> {
+		let filters = get_paginated_filters(network_id, address, conditions, cursor);
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT *
+					FROM {TABLE}
+					WHERE {filters}
+					ORDER BY block_height DESC, uuid ASC
+					LIMIT {limit}
+                "#
+			))
+			.await
+	}
+
+	// @NOTE totals of what `address` paid in fees per network and period, from
+	// the transfers that fee modules record. `monthly` groups by calendar month
+	// instead of by day, and `from`/`to` are inclusive
+	pub async fn get_fee_periods(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+		address: &str,
+		monthly: bool,
+		from: Option<NaiveDate>,
+		to: Option<NaiveDate>,
+	) -> Result<Vec<FeePeriod>> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+		let module_ids_string = [ModuleId::BitcoinFeeTransfer, ModuleId::EvmFeeTransfer]
+			.into_iter()
+			.map(|m| u16::from(m).to_string())
+			.collect::<Vec<String>>()
+			.join(",");
+
+		let mut filters = vec![
+			format!("network_id IN ({network_ids_string})"),
+			format!("module_id IN ({module_ids_string})"),
+			format!("from_address = '{address}'"),
+		];
+		if let Some(from) = from {
+			filters.push(format!("toDate(created_at) >= '{from}'"));
+		}
+		if let Some(to) = to {
+			filters.push(format!("toDate(created_at) <= '{to}'"));
+		}
+		let filters = filters.join(" AND ");
+
+		let period = match monthly {
+			true => "toStartOfMonth(created_at)",
+			false => "toDate(created_at)",
+		};
+
+		warehouse
+			.select(&format!(
+				r#"
+					SELECT
+					    network_id,
+					    toUInt16({period}) as date,
+					    uniqExact(tx_hash) as transactions,
+					    sum(relative_amount) as total_fee
+					FROM {TABLE}
+					WHERE {filters}
+					GROUP BY (network_id, date)
+					ORDER BY (network_id, date)
+                "#
+			))
+			.await
+	}
+
 	pub async fn delete_all_by_network_id(
 		warehouse: &Warehouse,
 		network_ids: PrimaryIds,
@@ -120,4 +639,168 @@ impl Model {
 			))
 			.await
 	}
+
+	// rows `module_ids` (any module when empty) wrote within a block range, eg:
+	// before it gets processed again
+	pub async fn delete_all_by_block_range(
+		warehouse: &Warehouse,
+		network_id: PrimaryId,
+		(block_height_min, block_height_max): (BlockHeight, BlockHeight),
+		module_ids: &[u16],
+	) -> Result<()> {
+		let module_filter = get_module_filter(module_ids);
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE}
+					WHERE
+						network_id = {network_id} AND
+						block_height >= {block_height_min} AND
+						block_height <= {block_height_max}
+						{module_filter}
+				"#
+			))
+			.await
+	}
+}
+
+// rows of `get_all_by_address_paginated()` that come after `cursor`
+fn get_paginated_filters(
+	network_id: PrimaryId,
+	address: Option<&str>,
+	conditions: Option<String>,
+	cursor: Option<(BlockHeight, Uuid)>,
+) -> String {
+	let mut filters = vec![format!("network_id = {network_id}")];
+	if let Some(address) = address {
+		filters.push(format!("(from_address = '{address}' OR to_address = '{address}')"));
+	}
+	if let Some(conditions) = conditions {
+		filters.push(format!("({conditions})"));
+	}
+	if let Some((block_height, uuid)) = cursor {
+		filters.push(format!(
+			"(block_height < {block_height} OR (block_height = {block_height} AND uuid > \
+			 '{uuid}'))"
+		));
+	}
+
+	filters.join(" AND ")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Settings;
+	use std::sync::Arc;
+
+	#[test]
+	fn test_deterministic_uuid() {
+		let new_transfer = |log_index: u64, to_address: &str| {
+			Transfer::new(
+				ModuleId::EvmTokenTransfer,
+				1,
+				100,
+				"0xabc",
+				log_index,
+				"0x1",
+				to_address,
+				None,
+				U256::from(10),
+				U256::from(10),
+				0,
+			)
+		};
+
+		assert_eq!(new_transfer(0, "0x2").uuid, new_transfer(0, "0x2").uuid);
+		assert_ne!(new_transfer(0, "0x2").uuid, new_transfer(0, "0x3").uuid);
+
+		// identical transfers from separate logs of the same tx are kept apart
+		assert_ne!(new_transfer(0, "0x2").uuid, new_transfer(1, "0x2").uuid);
+	}
+
+	#[test]
+	fn test_paginated_filters() {
+		assert_eq!(get_paginated_filters(1, None, None, None), "network_id = 1");
+		assert_eq!(
+			get_paginated_filters(1, Some("0x1"), None, Some((100, Uuid::nil()))),
+			"network_id = 1 AND (from_address = '0x1' OR to_address = '0x1') AND (block_height < \
+			 100 OR (block_height = 100 AND uuid > '00000000-0000-0000-0000-000000000000'))"
+		);
+	}
+
+	// @NOTE needs a clickhouse warehouse to write to, eg:
+	// `BARRELEYE_TEST_WAREHOUSE=http://localhost:8123/barreleye_test cargo test -- --ignored`
+	#[tokio::test]
+	#[ignore]
+	async fn test_get_all_by_address_paginated() -> Result<()> {
+		let url = std::env::var("BARRELEYE_TEST_WAREHOUSE")?;
+		let (settings, _) = Settings::from_args(["barreleye", "--warehouse", &url]).await?;
+		let warehouse = Warehouse::new(Arc::new(settings)).await?;
+		warehouse.run_migrations().await?;
+
+		let network_id = 1_000_001;
+		Transfer::delete_all_by_network_id(&warehouse, vec![network_id].into()).await?;
+
+		let transfers = [
+			(1, "paginated_c", "paginated_a"),
+			(2, "paginated_a", "paginated_b"),
+			(2, "paginated_b", "paginated_a"),
+			(2, "paginated_a", "paginated_c"),
+			(3, "paginated_a", "paginated_b"),
+			(3, "paginated_b", "paginated_c"),
+		]
+		.into_iter()
+		.map(|(block_height, from, to)| {
+			Transfer::new(
+				ModuleId::BitcoinTransfer,
+				network_id,
+				block_height,
+				"tx",
+				0,
+				from,
+				to,
+				None,
+				U256::from(1),
+				U256::from(1),
+				0,
+			)
+		})
+		.collect::<Vec<_>>();
+		warehouse.insert(TABLE, &transfers).await?;
+
+		let get_page = |cursor| {
+			Transfer::get_all_by_address_paginated(
+				&warehouse,
+				network_id,
+				Some("paginated_a"),
+				None,
+				cursor,
+				3,
+			)
+		};
+
+		// the first page ends halfway through block 2, so the second one has
+		// to pick up at the right uuid within the same block
+		let page_1 = get_page(None).await?;
+		let page_2 = get_page(page_1.last().map(|t| (t.block_height, t.uuid))).await?;
+		assert_eq!(page_1.iter().map(|t| t.block_height).collect::<Vec<_>>(), vec![3, 2, 2]);
+		assert_eq!(page_2.iter().map(|t| t.block_height).collect::<Vec<_>>(), vec![2, 1]);
+
+		// no transfer shows up twice, and none of the address' are skipped
+		let uuids = page_1.iter().chain(page_2.iter()).map(|t| t.uuid).collect::<Vec<_>>();
+		assert_eq!(uuids.iter().collect::<HashSet<_>>().len(), uuids.len());
+		assert_eq!(
+			uuids.into_iter().collect::<HashSet<_>>(),
+			transfers
+				.into_iter()
+				.filter(|t| t.from_address == "paginated_a" || t.to_address == "paginated_a")
+				.map(|t| t.uuid)
+				.collect::<HashSet<_>>()
+		);
+
+		Transfer::delete_all_by_network_id(&warehouse, vec![network_id].into()).await
+	}
 }
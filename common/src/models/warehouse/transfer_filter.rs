@@ -0,0 +1,266 @@
+use derive_more::Display;
+use eyre::{bail, eyre, Result};
+use std::collections::HashMap;
+
+use crate::utils;
+
+#[derive(Display, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FilterField {
+	#[display("amount")]
+	Amount,
+	#[display("asset")]
+	Asset,
+	#[display("direction")]
+	Direction,
+	#[display("from")]
+	From,
+	#[display("to")]
+	To,
+	#[display("block")]
+	Block,
+	#[display("tx")]
+	Tx,
+}
+
+#[derive(Display, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FilterOp {
+	#[display("=")]
+	Eq,
+	#[display("!=")]
+	Ne,
+	#[display(">")]
+	Gt,
+	#[display(">=")]
+	Gte,
+	#[display("<")]
+	Lt,
+	#[display("<=")]
+	Lte,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterCondition {
+	pub field: FilterField,
+	pub op: FilterOp,
+	pub value: String,
+}
+
+// @NOTE compact filter syntax for transfers, eg:
+// `amount>1e18 AND asset=USDT AND direction=in`. values are restricted to
+// alphanumerics (plus `.` for amounts), so they're safe to inline into sql
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TransferFilter {
+	pub conditions: Vec<FilterCondition>,
+}
+
+impl TransferFilter {
+	pub fn parse(input: &str) -> Result<Self> {
+		let mut conditions = vec![];
+
+		let mut parts = vec![vec![]];
+		for word in input.split_whitespace() {
+			if word.eq_ignore_ascii_case("and") {
+				parts.push(vec![]);
+			} else if let Some(part) = parts.last_mut() {
+				part.push(word);
+			}
+		}
+
+		for part in parts.into_iter() {
+			let condition = part.concat();
+			if condition.is_empty() {
+				bail!("empty condition");
+			}
+
+			let position = condition
+				.find(['=', '!', '<', '>'])
+				.ok_or(eyre!("missing operator in `{condition}`"))?;
+			let (field, rest) = condition.split_at(position);
+			let (op, value) = match rest {
+				r if r.starts_with("!=") => (FilterOp::Ne, &r[2..]),
+				r if r.starts_with(">=") => (FilterOp::Gte, &r[2..]),
+				r if r.starts_with("<=") => (FilterOp::Lte, &r[2..]),
+				r if r.starts_with('=') => (FilterOp::Eq, &r[1..]),
+				r if r.starts_with('>') => (FilterOp::Gt, &r[1..]),
+				r if r.starts_with('<') => (FilterOp::Lt, &r[1..]),
+				_ => bail!("invalid operator in `{condition}`"),
+			};
+
+			let field = match field.to_lowercase().as_str() {
+				"amount" => FilterField::Amount,
+				"asset" => FilterField::Asset,
+				"direction" => FilterField::Direction,
+				"from" => FilterField::From,
+				"to" => FilterField::To,
+				"block" => FilterField::Block,
+				"tx" => FilterField::Tx,
+				_ => bail!("unknown field `{field}`"),
+			};
+
+			let is_comparable = matches!(field, FilterField::Amount | FilterField::Block);
+			if !is_comparable && !matches!(op, FilterOp::Eq | FilterOp::Ne) {
+				bail!("`{field}` only supports `=` and `!=`");
+			}
+
+			let value = match field {
+				FilterField::Amount => {
+					Self::parse_number(value).ok_or(eyre!("invalid amount `{value}`"))?
+				}
+				FilterField::Block => {
+					value.parse::<u64>().map_err(|_| eyre!("invalid block `{value}`"))?.to_string()
+				}
+				FilterField::Direction => match value.to_lowercase().as_str() {
+					"in" | "out" if op == FilterOp::Eq => value.to_lowercase(),
+					_ => bail!("`direction` has to be `=in` or `=out`"),
+				},
+				_ if !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric()) => {
+					value.to_string()
+				}
+				_ => bail!("invalid value `{value}` for `{field}`"),
+			};
+
+			conditions.push(FilterCondition { field, op, value });
+		}
+
+		Ok(Self { conditions })
+	}
+
+	// raw integer amounts, optionally in scientific notation (eg: `1.5e18`)
+	fn parse_number(value: &str) -> Option<String> {
+		let (mantissa, exponent) = match value.to_lowercase().split_once('e') {
+			Some((m, e)) => (m.to_string(), e.parse::<u16>().ok()?),
+			None => (value.to_string(), 0),
+		};
+
+		utils::parse_amount(&mantissa, exponent).map(|v| v.to_string())
+	}
+
+	// asset values that still need to be resolved (eg: token symbols)
+	pub fn get_assets(&self) -> Vec<String> {
+		self.conditions
+			.iter()
+			.filter(|c| c.field == FilterField::Asset)
+			.map(|c| c.value.clone())
+			.collect()
+	}
+
+	// `address` is the address whose transfers are listed (needed for
+	// `direction`); `assets` maps symbols to asset addresses, anything not in
+	// there is treated as an address (`native` being the chain's own coin)
+	pub fn to_sql(
+		&self,
+		address: Option<&str>,
+		assets: &HashMap<String, Vec<String>>,
+	) -> Result<Option<String>> {
+		let mut ret = vec![];
+
+		for condition in self.conditions.iter() {
+			let FilterCondition { field, op, value } = condition;
+
+			ret.push(match field {
+				FilterField::Amount => format!("relative_amount {op} {value}"),
+				FilterField::Block => format!("block_height {op} {value}"),
+				FilterField::From => format!("from_address {op} '{value}'"),
+				FilterField::To => format!("to_address {op} '{value}'"),
+				FilterField::Tx => format!("tx_hash {op} '{value}'"),
+				FilterField::Direction => {
+					let address = address.ok_or(eyre!("`direction` requires an address"))?;
+					match value.as_str() {
+						"in" => format!("to_address = '{address}'"),
+						_ => format!("from_address = '{address}'"),
+					}
+				}
+				FilterField::Asset => {
+					let asset_addresses = match assets.get(value) {
+						Some(asset_addresses) => asset_addresses.clone(),
+						None if value.eq_ignore_ascii_case("native") => vec!["".to_string()],
+						None => vec![value.clone()],
+					};
+
+					let formatted_addresses = asset_addresses
+						.iter()
+						.map(|a| format!("'{a}'"))
+						.collect::<Vec<_>>()
+						.join(", ");
+
+					match op {
+						FilterOp::Ne => format!("asset_address NOT IN ({formatted_addresses})"),
+						_ => format!("asset_address IN ({formatted_addresses})"),
+					}
+				}
+			});
+		}
+
+		Ok(if ret.is_empty() { None } else { Some(ret.join(" AND ")) })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse() {
+		let filter =
+			TransferFilter::parse("amount>1e18 AND asset=USDT and direction = in").unwrap();
+		assert_eq!(
+			filter.conditions,
+			vec![
+				FilterCondition {
+					field: FilterField::Amount,
+					op: FilterOp::Gt,
+					value: "1000000000000000000".to_string()
+				},
+				FilterCondition {
+					field: FilterField::Asset,
+					op: FilterOp::Eq,
+					value: "USDT".to_string()
+				},
+				FilterCondition {
+					field: FilterField::Direction,
+					op: FilterOp::Eq,
+					value: "in".to_string()
+				},
+			]
+		);
+
+		for input in [
+			"",
+			"amount",
+			"amount>1e18 AND",
+			"amount>abc",
+			"amount>1.5",
+			"weight=1",
+			"asset>USDT",
+			"direction=sideways",
+			"to='; DROP TABLE transfers",
+		] {
+			assert!(TransferFilter::parse(input).is_err(), "{input}");
+		}
+	}
+
+	#[test]
+	fn test_to_sql() {
+		let assets = HashMap::from([("USDT".to_string(), vec!["0xdac17f".to_string()])]);
+
+		let filter =
+			TransferFilter::parse("amount>=1.5e3 AND asset=USDT AND direction=out").unwrap();
+		assert_eq!(
+			filter.to_sql(Some("0xabc"), &assets).unwrap(),
+			Some(
+				"relative_amount >= 1500 AND asset_address IN ('0xdac17f') AND from_address = \
+				 '0xabc'"
+					.to_string()
+			)
+		);
+
+		let filter = TransferFilter::parse("asset!=native AND block<100").unwrap();
+		assert_eq!(
+			filter.to_sql(None, &assets).unwrap(),
+			Some("asset_address NOT IN ('') AND block_height < 100".to_string())
+		);
+
+		let filter = TransferFilter::parse("direction=in").unwrap();
+		assert!(filter.to_sql(None, &assets).is_err());
+	}
+}
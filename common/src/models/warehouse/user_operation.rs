@@ -0,0 +1,84 @@
+use clickhouse::Row;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	chain::{u256, U256},
+	models::{PrimaryId, PrimaryIds},
+	warehouse::Warehouse,
+	BlockHeight,
+};
+
+pub static TABLE: &str = "user_operations";
+
+// erc-4337 user operations, unpacked from entry point bundles
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Row, Serialize, Deserialize)]
+pub struct Model {
+	pub network_id: u64,
+	pub block_height: u64,
+	pub tx_hash: String,
+	pub user_op_hash: String,
+	pub entry_point: String,
+	pub sender: String,
+	// empty if the sender paid for gas
+	pub paymaster: String,
+	pub bundler: String,
+	#[serde(with = "u256")]
+	pub nonce: U256,
+	pub success: bool,
+	#[serde(with = "u256")]
+	pub actual_gas_cost: U256,
+	pub created_at: u32,
+}
+
+pub use Model as UserOperation;
+
+impl Model {
+	#[allow(clippy::too_many_arguments)]
+	pub fn new(
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+		tx_hash: &str,
+		user_op_hash: &str,
+		entry_point: &str,
+		sender: &str,
+		paymaster: Option<String>,
+		bundler: &str,
+		nonce: U256,
+		success: bool,
+		actual_gas_cost: U256,
+		created_at: u32,
+	) -> Self {
+		Self {
+			network_id: network_id as u64,
+			block_height,
+			tx_hash: tx_hash.to_string(),
+			user_op_hash: user_op_hash.to_string(),
+			entry_point: entry_point.to_string(),
+			sender: sender.to_string(),
+			paymaster: paymaster.unwrap_or_default(),
+			bundler: bundler.to_string(),
+			nonce,
+			success,
+			actual_gas_cost,
+			created_at,
+		}
+	}
+
+	pub async fn delete_all_by_network_id(
+		warehouse: &Warehouse,
+		network_ids: PrimaryIds,
+	) -> Result<()> {
+		let network_ids_string =
+			network_ids.into_iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",");
+
+		warehouse
+			.delete(&format!(
+				r#"
+					SET allow_experimental_lightweight_delete = true;
+					DELETE FROM {TABLE} WHERE network_id IN ({network_ids_string})
+                "#
+			))
+			.await
+	}
+}
@@ -0,0 +1,95 @@
+use eyre::Result;
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::DailyPrice;
+use crate::Settings;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct MarketChart {
+	// (unix timestamp in ms, price)
+	prices: Vec<(f64, f64)>,
+}
+
+// client for coingecko's api, or anything that serves the same responses
+pub struct CoinGecko {
+	client: reqwest::Client,
+	url: String,
+	api_key: Option<String>,
+}
+
+impl CoinGecko {
+	pub fn new(settings: &Settings) -> Result<Self> {
+		Ok(Self {
+			client: reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?,
+			url: settings.prices_url.trim_end_matches('/').to_string(),
+			api_key: settings.prices_api_key.clone(),
+		})
+	}
+
+	// usd prices of the last `days` days (including today), oldest first
+	pub async fn get_daily_prices(&self, price_id: &str, days: u16) -> Result<Vec<DailyPrice>> {
+		let mut request = self
+			.client
+			.get(format!("{}/coins/{price_id}/market_chart", self.url))
+			.query(&[("vs_currency", "usd"), ("days", &days.to_string()), ("interval", "daily")]);
+
+		if let Some(api_key) = &self.api_key {
+			let header = match self.url.contains("pro-api.coingecko.com") {
+				true => "x-cg-pro-api-key",
+				false => "x-cg-demo-api-key",
+			};
+			request = request.header(header, api_key);
+		}
+
+		let market_chart = request.send().await?.error_for_status()?.json::<MarketChart>().await?;
+
+		Ok(to_daily_prices(market_chart.prices))
+	}
+}
+
+// @NOTE daily charts have a price for 00:00 utc of every day, followed by the
+// current one. later prices of a day replace earlier ones, so today's keeps
+// moving until the day is over
+fn to_daily_prices(prices: Vec<(f64, f64)>) -> Vec<DailyPrice> {
+	let mut ret: Vec<DailyPrice> = vec![];
+	for (timestamp, price) in prices.into_iter() {
+		if timestamp < 0.0 || !price.is_finite() || price < 0.0 {
+			continue;
+		}
+
+		let date = (timestamp / 86_400_000.0) as u16;
+		match ret.last_mut() {
+			Some(last) if last.date == date => last.price = price,
+			_ => ret.push(DailyPrice { date, price }),
+		}
+	}
+
+	ret
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_to_daily_prices() {
+		let day = 86_400_000.0;
+		let prices = vec![
+			(19_000.0 * day, 1_000.0),
+			(19_001.0 * day, 1_100.0),
+			(19_001.0 * day + 3_600_000.0, 1_150.0),
+			(19_002.0 * day, f64::NAN),
+		];
+
+		assert_eq!(
+			to_daily_prices(prices),
+			vec![
+				DailyPrice { date: 19_000, price: 1_000.0 },
+				DailyPrice { date: 19_001, price: 1_150.0 },
+			]
+		);
+	}
+}
@@ -0,0 +1,73 @@
+use crate::{chain::U256, models::Network, utils};
+
+pub use coingecko::CoinGecko;
+
+mod coingecko;
+
+// usd price of an asset on a day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyPrice {
+	// days since unix epoch
+	pub date: u16,
+	pub price: f64,
+}
+
+// coingecko ids are slugs (eg: `ethereum` or `matic-network`), and they end
+// up in request paths
+pub fn is_valid_price_id(price_id: &str) -> bool {
+	!price_id.is_empty() &&
+		price_id.len() <= 100 &&
+		price_id
+			.chars()
+			.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+// usd amounts (eg: thresholds) have to be positive
+pub fn parse_usd(value: &str) -> Option<f64> {
+	value.trim().parse::<f64>().ok().filter(|v| v.is_finite() && *v > 0.0)
+}
+
+// only native assets are priced, which includes tokens that wrap them 1:1
+pub fn is_priced(network: &Network, asset_address: &str) -> bool {
+	network.price_id.is_some() &&
+		(asset_address.is_empty() ||
+			network.get_wrapped_native_tokens().iter().any(|a| a == asset_address))
+}
+
+// usd value of a raw amount, rounded to cents
+pub fn to_usd(amount: U256, decimals: u16, price: f64) -> Option<f64> {
+	let amount = utils::format_amount(amount, decimals).parse::<f64>().ok()?;
+	Some((amount * price * 100.0).round() / 100.0).filter(|v| v.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_to_usd() {
+		let amount = U256::from(1_500_000_000_000_000_000u64);
+		assert_eq!(to_usd(amount, 18, 2_000.0), Some(3_000.0));
+		assert_eq!(to_usd(U256::from(1u64), 18, 2_000.0), Some(0.0));
+		assert_eq!(to_usd(U256::from(123_456u64), 2, 1.0), Some(1_234.56));
+	}
+
+	#[test]
+	fn test_parse_usd() {
+		assert_eq!(parse_usd("100000"), Some(100_000.0));
+		assert_eq!(parse_usd("2500.50"), Some(2_500.5));
+		assert_eq!(parse_usd("0"), None);
+		assert_eq!(parse_usd("-1"), None);
+		assert_eq!(parse_usd("inf"), None);
+		assert_eq!(parse_usd("1 eth"), None);
+	}
+
+	#[test]
+	fn test_is_valid_price_id() {
+		assert!(is_valid_price_id("ethereum"));
+		assert!(is_valid_price_id("matic-network"));
+		assert!(!is_valid_price_id(""));
+		assert!(!is_valid_price_id("Ethereum"));
+		assert!(!is_valid_price_id("../ethereum"));
+	}
+}
@@ -0,0 +1,93 @@
+// @NOTE seed dataset of well-known block producers (mining pools and block
+// builders). they're recognized either by a payout address, or by the text
+// they put into the blocks they produce (the coinbase script on bitcoin, the
+// extra data on evm chains). tags are matched case-insensitively as substrings
+pub struct KnownProducer {
+	pub name: &'static str,
+	pub tags: &'static [&'static str],
+	pub addresses: &'static [&'static str],
+}
+
+pub static KNOWN_PRODUCERS: [KnownProducer; 19] = [
+	// bitcoin mining pools
+	KnownProducer { name: "Foundry USA", tags: &["Foundry USA"], addresses: &[] },
+	KnownProducer { name: "AntPool", tags: &["AntPool"], addresses: &[] },
+	KnownProducer { name: "F2Pool", tags: &["F2Pool", "七彩神仙鱼"], addresses: &[] },
+	KnownProducer { name: "ViaBTC", tags: &["/ViaBTC/"], addresses: &[] },
+	KnownProducer { name: "Binance Pool", tags: &["/Binance/"], addresses: &[] },
+	KnownProducer { name: "MARA Pool", tags: &["MARA Pool"], addresses: &[] },
+	KnownProducer { name: "Luxor", tags: &["Luxor"], addresses: &[] },
+	KnownProducer { name: "SpiderPool", tags: &["SpiderPool"], addresses: &[] },
+	KnownProducer { name: "BTC.com", tags: &["/BTC.COM/"], addresses: &[] },
+	KnownProducer { name: "Braiins Pool", tags: &["/slush/"], addresses: &[] },
+	KnownProducer { name: "SECPOOL", tags: &["SecPool"], addresses: &[] },
+	KnownProducer { name: "OCEAN", tags: &["OCEAN.XYZ"], addresses: &[] },
+	// evm block builders and (pre-merge) mining pools
+	KnownProducer {
+		name: "beaverbuild",
+		tags: &["beaverbuild"],
+		addresses: &["0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfe5"],
+	},
+	KnownProducer {
+		name: "Titan Builder",
+		tags: &["titanbuilder"],
+		addresses: &["0x4838B106FCe9647Bdf1E7877BF73cE8B0BAD5f97"],
+	},
+	KnownProducer {
+		name: "rsync-builder",
+		tags: &["rsync-builder"],
+		addresses: &["0x1f9090aaE28b8a3dCeaDf281B0F12828e676c326"],
+	},
+	KnownProducer {
+		name: "Flashbots Builder",
+		tags: &["Illuminate Dmocratize Dstribute"],
+		addresses: &["0xDAFEA492D9c6733ae3d56b7Ed1ADB60692c98Bc5"],
+	},
+	KnownProducer { name: "bloXroute", tags: &["bloXroute"], addresses: &[] },
+	KnownProducer { name: "BuilderNet", tags: &["BuilderNet"], addresses: &[] },
+	KnownProducer {
+		name: "Ethermine",
+		tags: &["ethermine"],
+		addresses: &["0xEA674fdDe714fd979de3EdF0F56AA9716B898ec8"],
+	},
+];
+
+// known producer by payout address first, then by block text
+pub fn find_known_producer(address: &str, tag: Option<&str>) -> Option<&'static KnownProducer> {
+	KNOWN_PRODUCERS
+		.iter()
+		.find(|p| p.addresses.iter().any(|a| a.eq_ignore_ascii_case(address)))
+		.or_else(|| {
+			let tag = tag?.to_lowercase();
+			KNOWN_PRODUCERS.iter().find(|p| p.tags.iter().any(|t| tag.contains(&t.to_lowercase())))
+		})
+}
+
+// printable text embedded in raw block bytes (eg: "/ViaBTC/Mined by x/")
+pub fn get_tag(data: &[u8]) -> Option<String> {
+	let tag = String::from_utf8_lossy(data)
+		.chars()
+		.filter(|c| !c.is_control() && *c != char::REPLACEMENT_CHARACTER)
+		.collect::<String>()
+		.trim()
+		.to_string();
+
+	(!tag.is_empty()).then_some(tag)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_find_known_producer() {
+		let titan = "0x4838b106fce9647bdf1e7877bf73ce8b0bad5f97";
+		assert_eq!(find_known_producer(titan, None).map(|p| p.name), Some("Titan Builder"));
+
+		let tag = get_tag(b"\x03\xa0\x8f\x0c/ViaBTC/Mined by someone/,\xfa\xbe");
+		assert_eq!(find_known_producer("bc1q", tag.as_deref()).map(|p| p.name), Some("ViaBTC"));
+
+		assert!(find_known_producer("bc1q", Some("unknown")).is_none());
+		assert!(get_tag(b"\x00\x01").is_none());
+	}
+}
@@ -19,14 +19,30 @@ pub enum Step {
 #[derive(Clone)]
 pub struct Progress {
 	with_indexer: bool,
+	is_silent: bool,
 }
 
 impl Progress {
 	pub fn new(with_indexer: bool) -> Self {
-		Self { with_indexer }
+		Self { with_indexer, is_silent: false }
+	}
+
+	// for embedding, where stdout belongs to the host program
+	pub fn silent() -> Self {
+		Self { with_indexer: false, is_silent: true }
 	}
 
 	pub fn show(&self, step: Step) {
+		if self.is_silent {
+			if let Step::Ready(_, warnings) = step {
+				for warning in warnings.into_iter() {
+					warn!(warning);
+				}
+			}
+
+			return;
+		}
+
 		let total_steps = if self.with_indexer { 4 } else { 3 };
 
 		let out = |step, emoji, text| {
@@ -1,8 +1,26 @@
+use chrono::{DateTime, NaiveDateTime};
 use derive_more::Display;
-use eyre::{ErrReport, Result};
-use std::str::FromStr;
+use eyre::{bail, ErrReport, Result};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, env, fs, str::FromStr};
+use tokio::time::Duration;
 use url::Url;
 
+use crate::utils;
+
+// ec2 instance metadata and ecs task credentials endpoints
+const IMDS_URL: &str = "http://169.254.169.254/latest";
+const ECS_CREDENTIALS_URL: &str = "http://169.254.170.2";
+const STS_URL: &str = "https://sts.amazonaws.com/";
+
+// region requests are signed for when the storage url doesn't have one
+pub const DEFAULT_REGION: &str = "us-east-1";
+
+// metadata endpoints only exist on aws, so don't wait long for them
+const METADATA_TIMEOUT: Duration = Duration::from_secs(2);
+
 #[derive(Display, Debug, Clone, Default, PartialEq, Eq)]
 pub enum Service {
 	#[default]
@@ -21,6 +39,11 @@ pub struct S3 {
 	pub region: Option<String>,
 	pub domain: Option<String>,
 	pub bucket: Option<String>,
+	// host (and port) requests go to; aws' is derived from the region
+	pub endpoint: Option<String>,
+	pub use_ssl: bool,
+	pub path_style: bool,
+	pub sse_kms_key_id: Option<String>,
 }
 
 impl FromStr for S3 {
@@ -31,7 +54,8 @@ impl FromStr for S3 {
 
 		let parsed_url = Url::parse(s)?;
 		ret.url = parsed_url.to_string();
-		if let Some(domain) = parsed_url.domain() {
+		ret.use_ssl = parsed_url.scheme() == "https";
+		if let Some(domain) = parsed_url.host_str() {
 			let parts: Vec<String> = domain.split('.').map(|v| v.to_string()).collect();
 			if parts.len() >= 3 && parts[parts.len() - 2] == "amazonaws" {
 				ret.service = Service::S3;
@@ -39,6 +63,10 @@ impl FromStr for S3 {
 			} else {
 				ret.service = Service::S3Compatible;
 				ret.domain = Some(domain.to_string());
+				ret.endpoint = Some(match parsed_url.port() {
+					Some(port) => format!("{domain}:{port}"),
+					None => domain.to_string(),
+				});
 			}
 
 			if let Some(mut segments) = parsed_url.path_segments() {
@@ -54,6 +82,305 @@ impl FromStr for S3 {
 	}
 }
 
+impl S3 {
+	// path-style bucket url (aws' own urls are used as-is, unless an endpoint
+	// has been set)
+	pub fn get_bucket_url(&self) -> String {
+		match (&self.endpoint, &self.bucket) {
+			(Some(endpoint), Some(bucket)) => {
+				let scheme = if self.use_ssl { "https" } else { "http" };
+				format!("{scheme}://{endpoint}/{bucket}")
+			}
+			_ => self.url.trim_end_matches('/').to_string(),
+		}
+	}
+
+	// @NOTE uploads `body` under `key` only if nothing's there yet (a
+	// conditional write with `If-None-Match`), so concurrent writers can't
+	// overwrite each other. returns whether it was written
+	pub async fn put_if_absent(
+		&self,
+		credentials: &Credentials,
+		key: &str,
+		body: Vec<u8>,
+	) -> Result<bool> {
+		let url = Url::parse(&format!("{}/{}", self.get_bucket_url(), uri_encode(key)))?;
+		let host = match (url.host_str(), url.port()) {
+			(Some(host), Some(port)) => format!("{host}:{port}"),
+			(host, _) => host.unwrap_or_default().to_string(),
+		};
+
+		let mut headers = BTreeMap::from([
+			("host".to_string(), host),
+			("if-none-match".to_string(), "*".to_string()),
+			("x-amz-content-sha256".to_string(), hex::encode(Sha256::digest(&body))),
+			("x-amz-date".to_string(), utils::now().format("%Y%m%dT%H%M%SZ").to_string()),
+		]);
+		if let Some(session_token) = &credentials.session_token {
+			headers.insert("x-amz-security-token".to_string(), session_token.clone());
+		}
+		if let Some(sse_kms_key_id) = &self.sse_kms_key_id {
+			headers.insert("x-amz-server-side-encryption".to_string(), "aws:kms".to_string());
+			headers.insert(
+				"x-amz-server-side-encryption-aws-kms-key-id".to_string(),
+				sse_kms_key_id.clone(),
+			);
+		}
+
+		let region = self.region.clone().unwrap_or_else(|| DEFAULT_REGION.to_string());
+		let request = SignedRequest {
+			method: "PUT",
+			path: url.path(),
+			query: "",
+			headers: &headers,
+			body: &body,
+		};
+		let authorization = credentials.sign(&request, &region, "s3");
+
+		let mut builder = reqwest::Client::new().put(url.clone());
+		for (k, v) in headers.iter().filter(|(k, _)| *k != "host") {
+			builder = builder.header(k, v);
+		}
+		let status =
+			builder.header("authorization", authorization).body(body).send().await?.status();
+
+		match status {
+			s if s.is_success() => Ok(true),
+			StatusCode::PRECONDITION_FAILED | StatusCode::CONFLICT => Ok(false),
+			s => bail!("Unexpected response from storage: {s}"),
+		}
+	}
+}
+
+// percent-encodes everything but unreserved characters and slashes, the way
+// s3 expects object keys in signed urls
+fn uri_encode(path: &str) -> String {
+	path.bytes()
+		.map(|b| match b {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+				(b as char).to_string()
+			}
+			_ => format!("%{b:02X}"),
+		})
+		.collect()
+}
+
+// credentials resolved through the aws provider chain; temporary ones come
+// with an expiration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+	pub access_key_id: String,
+	pub secret_access_key: String,
+	pub session_token: Option<String>,
+	pub expires_at: Option<NaiveDateTime>,
+}
+
+// shape of both ecs and ec2 instance profile responses
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MetadataCredentials {
+	access_key_id: String,
+	secret_access_key: String,
+	token: Option<String>,
+	expiration: Option<String>,
+}
+
+impl From<MetadataCredentials> for Credentials {
+	fn from(c: MetadataCredentials) -> Self {
+		Credentials {
+			access_key_id: c.access_key_id,
+			secret_access_key: c.secret_access_key,
+			session_token: c.token,
+			expires_at: c.expiration.as_deref().and_then(parse_expiration),
+		}
+	}
+}
+
+impl Credentials {
+	// @NOTE same order as aws sdks: environment variables, web identity (eg:
+	// eks service accounts), ecs task role and finally the ec2 instance profile
+	// (imdsv2). returns `None` when none of them are available
+	pub async fn from_provider_chain() -> Result<Option<Self>> {
+		if let (Ok(access_key_id), Ok(secret_access_key)) =
+			(env::var("AWS_ACCESS_KEY_ID"), env::var("AWS_SECRET_ACCESS_KEY"))
+		{
+			return Ok(Some(Credentials {
+				access_key_id,
+				secret_access_key,
+				session_token: env::var("AWS_SESSION_TOKEN").ok(),
+				expires_at: None,
+			}));
+		}
+
+		if let (Ok(token_file), Ok(role_arn)) =
+			(env::var("AWS_WEB_IDENTITY_TOKEN_FILE"), env::var("AWS_ROLE_ARN"))
+		{
+			return Ok(Some(Self::from_web_identity(&token_file, &role_arn).await?));
+		}
+
+		if let Ok(relative_uri) = env::var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI") {
+			let url = format!("{ECS_CREDENTIALS_URL}{relative_uri}");
+			let credentials = reqwest::Client::new()
+				.get(url)
+				.timeout(METADATA_TIMEOUT)
+				.send()
+				.await?
+				.error_for_status()?
+				.json::<MetadataCredentials>()
+				.await?;
+
+			return Ok(Some(credentials.into()));
+		}
+
+		Ok(Self::from_instance_profile().await.ok())
+	}
+
+	async fn from_web_identity(token_file: &str, role_arn: &str) -> Result<Self> {
+		let token = fs::read_to_string(token_file)?;
+		let session_name =
+			env::var("AWS_ROLE_SESSION_NAME").unwrap_or_else(|_| "barreleye".to_string());
+
+		let body = reqwest::Client::new()
+			.get(STS_URL)
+			.query(&[
+				("Action", "AssumeRoleWithWebIdentity"),
+				("Version", "2011-06-15"),
+				("RoleArn", role_arn),
+				("RoleSessionName", &session_name),
+				("WebIdentityToken", token.trim()),
+			])
+			.send()
+			.await?
+			.error_for_status()?
+			.text()
+			.await?;
+
+		let expires_at = get_xml_value(&body, "Expiration").as_deref().and_then(parse_expiration);
+		match (
+			get_xml_value(&body, "AccessKeyId"),
+			get_xml_value(&body, "SecretAccessKey"),
+			get_xml_value(&body, "SessionToken"),
+		) {
+			(Some(access_key_id), Some(secret_access_key), session_token) => {
+				Ok(Credentials { access_key_id, secret_access_key, session_token, expires_at })
+			}
+			_ => bail!("Unexpected response from sts"),
+		}
+	}
+
+	async fn from_instance_profile() -> Result<Self> {
+		let client = reqwest::Client::new();
+
+		let token = client
+			.put(format!("{IMDS_URL}/api/token"))
+			.header("X-aws-ec2-metadata-token-ttl-seconds", "21600")
+			.timeout(METADATA_TIMEOUT)
+			.send()
+			.await?
+			.error_for_status()?
+			.text()
+			.await?;
+
+		let url = format!("{IMDS_URL}/meta-data/iam/security-credentials/");
+		let get = |url: String| {
+			client
+				.get(url)
+				.header("X-aws-ec2-metadata-token", &token)
+				.timeout(METADATA_TIMEOUT)
+				.send()
+		};
+
+		let role = get(url.clone()).await?.error_for_status()?.text().await?;
+		let Some(role) = role.lines().next().filter(|r| !r.is_empty()) else {
+			bail!("No instance profile attached");
+		};
+
+		let credentials = get(format!("{url}{role}"))
+			.await?
+			.error_for_status()?
+			.json::<MetadataCredentials>()
+			.await?;
+
+		Ok(credentials.into())
+	}
+}
+
+// a request to an aws api, as far as signing is concerned. `headers` are
+// lowercased and have to include `host` and `x-amz-date`
+pub struct SignedRequest<'a> {
+	pub method: &'a str,
+	pub path: &'a str,
+	pub query: &'a str,
+	pub headers: &'a BTreeMap<String, String>,
+	pub body: &'a [u8],
+}
+
+impl Credentials {
+	// @NOTE aws signature version 4, for the few aws apis that are called
+	// directly. returns the `authorization` header; a session token has to be
+	// sent (and signed) as `x-amz-security-token`
+	pub fn sign(&self, request: &SignedRequest, region: &str, service: &str) -> String {
+		let amz_date = request.headers.get("x-amz-date").cloned().unwrap_or_default();
+		let date = &amz_date[..amz_date.len().min(8)];
+
+		let signed_headers = request.headers.keys().cloned().collect::<Vec<_>>().join(";");
+		let canonical_request = [
+			request.method.to_string(),
+			request.path.to_string(),
+			request.query.to_string(),
+			request.headers.iter().map(|(k, v)| format!("{k}:{}\n", v.trim())).collect(),
+			signed_headers.clone(),
+			hex::encode(Sha256::digest(request.body)),
+		]
+		.join("\n");
+
+		let scope = format!("{date}/{region}/{service}/aws4_request");
+		let string_to_sign = format!(
+			"AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+			hex::encode(Sha256::digest(canonical_request.as_bytes()))
+		);
+
+		let mut key = format!("AWS4{}", self.secret_access_key).into_bytes();
+		for part in [date, region, service, "aws4_request"] {
+			key = hmac_sha256(&key, part.as_bytes());
+		}
+		let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+		format!(
+			"AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, \
+			 Signature={signature}",
+			self.access_key_id
+		)
+	}
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+	const BLOCK_SIZE: usize = 64;
+
+	let mut block = [0u8; BLOCK_SIZE];
+	if key.len() > BLOCK_SIZE {
+		block[..32].copy_from_slice(&Sha256::digest(key));
+	} else {
+		block[..key.len()].copy_from_slice(key);
+	}
+
+	let inner = Sha256::new().chain_update(block.map(|b| b ^ 0x36)).chain_update(data).finalize();
+
+	Sha256::new().chain_update(block.map(|b| b ^ 0x5c)).chain_update(inner).finalize().to_vec()
+}
+
+fn parse_expiration(expiration: &str) -> Option<NaiveDateTime> {
+	DateTime::parse_from_rfc3339(expiration).ok().map(|d| d.naive_utc())
+}
+
+// sts responds in xml, but only a handful of flat values are needed
+fn get_xml_value(xml: &str, tag: &str) -> Option<String> {
+	let start = xml.find(&format!("<{tag}>"))? + tag.len() + 2;
+	let end = start + xml[start..].find(&format!("</{tag}>"))?;
+
+	Some(xml[start..end].trim().to_string())
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -70,6 +397,7 @@ mod tests {
 					region: Some("us-east-1".to_string()),
 					domain: None,
 					bucket: Some("bucket_name".to_string()),
+					..Default::default()
 				},
 			),
 			(
@@ -80,6 +408,8 @@ mod tests {
 					region: None,
 					domain: Some("storage.googleapis.com".to_string()),
 					bucket: Some("bucket_name".to_string()),
+					endpoint: Some("storage.googleapis.com".to_string()),
+					..Default::default()
 				},
 			),
 			(
@@ -90,6 +420,8 @@ mod tests {
 					region: None,
 					domain: Some("example.com".to_string()),
 					bucket: Some("bucket_name".to_string()),
+					endpoint: Some("example.com".to_string()),
+					..Default::default()
 				},
 			),
 			(
@@ -100,6 +432,33 @@ mod tests {
 					region: None,
 					domain: Some("example.com".to_string()),
 					bucket: Some("one".to_string()),
+					endpoint: Some("example.com".to_string()),
+					..Default::default()
+				},
+			),
+			(
+				"http://localhost:9000/bucket_name",
+				S3 {
+					service: Service::S3Compatible,
+					url: "http://localhost:9000/bucket_name".to_string(),
+					region: None,
+					domain: Some("localhost".to_string()),
+					bucket: Some("bucket_name".to_string()),
+					endpoint: Some("localhost:9000".to_string()),
+					..Default::default()
+				},
+			),
+			(
+				"https://127.0.0.1:9000/bucket_name",
+				S3 {
+					service: Service::S3Compatible,
+					url: "https://127.0.0.1:9000/bucket_name".to_string(),
+					region: None,
+					domain: Some("127.0.0.1".to_string()),
+					bucket: Some("bucket_name".to_string()),
+					endpoint: Some("127.0.0.1:9000".to_string()),
+					use_ssl: true,
+					..Default::default()
 				},
 			),
 			(
@@ -110,6 +469,7 @@ mod tests {
 					region: Some("us-east-1".to_string()),
 					domain: None,
 					bucket: None,
+					..Default::default()
 				},
 			),
 		]);
@@ -118,4 +478,65 @@ mod tests {
 			assert_eq!(S3::from_str(&url).unwrap(), s3);
 		}
 	}
+
+	#[test]
+	fn test_get_xml_value() {
+		let xml = "<Credentials><AccessKeyId>AKIA</AccessKeyId><SessionToken>\n\ttoken\n\
+		           </SessionToken></Credentials>";
+
+		assert_eq!(get_xml_value(xml, "AccessKeyId"), Some("AKIA".to_string()));
+		assert_eq!(get_xml_value(xml, "SessionToken"), Some("token".to_string()));
+		assert_eq!(get_xml_value(xml, "Expiration"), None);
+	}
+
+	#[test]
+	fn test_uri_encode() {
+		assert_eq!(
+			uri_encode("_delta/network_id=1/logs/_delta_log/00000000000000000000.json"),
+			"_delta/network_id%3D1/logs/_delta_log/00000000000000000000.json"
+		);
+		assert_eq!(uri_encode("a b+c"), "a%20b%2Bc");
+	}
+
+	#[test]
+	fn test_hmac_sha256() {
+		// rfc 4231, test case 2
+		assert_eq!(
+			hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+			"5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+		);
+	}
+
+	#[test]
+	fn test_sign() {
+		// example request from aws' signature version 4 documentation
+		let credentials = Credentials {
+			access_key_id: "AKIDEXAMPLE".to_string(),
+			secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+			session_token: None,
+			expires_at: None,
+		};
+		let headers = BTreeMap::from([
+			(
+				"content-type".to_string(),
+				"application/x-www-form-urlencoded; charset=utf-8".to_string(),
+			),
+			("host".to_string(), "iam.amazonaws.com".to_string()),
+			("x-amz-date".to_string(), "20150830T123600Z".to_string()),
+		]);
+		let request = SignedRequest {
+			method: "GET",
+			path: "/",
+			query: "Action=ListUsers&Version=2010-05-08",
+			headers: &headers,
+			body: b"",
+		};
+
+		assert_eq!(
+			credentials.sign(&request, "us-east-1", "iam"),
+			"AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+			 SignedHeaders=content-type;host;x-amz-date, \
+			 Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+		);
+	}
 }
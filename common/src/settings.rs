@@ -1,6 +1,8 @@
-use clap::{Parser, ValueHint};
+use chrono::NaiveTime;
+use clap::{ArgAction, Parser, Subcommand, ValueHint};
 use eyre::Result;
 use std::{
+	ffi::OsString,
 	fs,
 	net::IpAddr,
 	path::{Path, PathBuf},
@@ -9,8 +11,13 @@ use std::{
 use url::Url;
 
 use crate::{
-	banner, db::Driver as DatabaseDriver, utils, warehouse::Driver as WarehouseDriver, AppError,
-	Mode, S3Service, Warnings, S3,
+	banner,
+	db::Driver as DatabaseDriver,
+	labels::{LabelFormat, OnConflict},
+	storage::TableFormat,
+	utils,
+	warehouse::Driver as WarehouseDriver,
+	AppError, Mode, S3Service, Warnings, S3,
 };
 
 #[derive(Parser, Debug)]
@@ -21,6 +28,9 @@ use crate::{
 	long_about = None
 )]
 pub struct Settings {
+	#[command(subcommand)]
+	pub command: Option<Command>,
+
 	/// Mode can be used to run either the server or the indexer. By default
 	/// both are run in parallel.
 	#[arg(help_heading = "Runtime options", long, num_args = 1.., value_delimiter = ',')]
@@ -30,6 +40,12 @@ pub struct Settings {
 	#[arg(skip)]
 	pub is_server: bool,
 
+	/// Process and link from extracted files only, without connecting to any
+	/// RPC node (eg: to replay module changes over history in an air-gapped
+	/// environment). No new blocks are synced.
+	#[arg(help_heading = "Runtime options", long, env = "BARRELEYE_OFFLINE")]
+	pub offline: bool,
+
 	/// Where to store extracted blockchain data.
 	/// Can be either a folder or S3-compatible storage.
 	///
@@ -55,6 +71,9 @@ pub struct Settings {
 	#[arg(skip)]
 	pub storage_url: Option<S3>,
 
+	/// Without static keys, AWS storage gets credentials from the standard
+	/// provider chain (`AWS_*` env vars, web identity, ECS task role or EC2
+	/// instance profile).
 	#[arg(
 		help_heading = "Storage options",
 		long,
@@ -71,6 +90,65 @@ pub struct Settings {
 	)]
 	pub s3_secret_access_key: Option<String>,
 
+	/// Host (and port) to send S3 requests to, instead of the one in the
+	/// storage URL (eg: `minio:9000`).
+	#[arg(
+		help_heading = "Storage options",
+		long,
+		env = "BARRELEYE_S3_ENDPOINT",
+		value_name = "HOST:PORT"
+	)]
+	pub s3_endpoint: Option<String>,
+
+	/// Address buckets by path (`endpoint/bucket`) rather than by subdomain,
+	/// as MinIO and most self-hosted services expect.
+	#[arg(
+		help_heading = "Storage options",
+		long,
+		env = "BARRELEYE_S3_PATH_STYLE",
+		default_value_t = false,
+		action = ArgAction::Set,
+		value_name = "BOOL"
+	)]
+	pub s3_path_style: bool,
+
+	/// KMS key to encrypt stored files with (SSE-KMS), for buckets that
+	/// require it.
+	#[arg(
+		help_heading = "Storage options",
+		long,
+		env = "BARRELEYE_S3_SSE_KMS_KEY_ID",
+		value_name = "KEY_ID"
+	)]
+	pub s3_sse_kms_key_id: Option<String>,
+
+	/// Register extracted files as tables in this AWS Glue database, so they
+	/// can be queried with Athena. Requires Amazon S3 storage.
+	#[arg(
+		help_heading = "Storage options",
+		long,
+		env = "BARRELEYE_GLUE_DATABASE",
+		value_name = "NAME"
+	)]
+	pub glue_database: Option<String>,
+
+	/// Also keep a Delta Lake log per network and file (under `_delta/`), so
+	/// Spark and Trino can read extracted files as tables.
+	#[arg(
+		help_heading = "Storage options",
+		long,
+		env = "BARRELEYE_TABLE_FORMAT",
+		default_value = "parquet",
+		value_name = "FORMAT"
+	)]
+	pub table_format: TableFormat,
+
+	/// Merge extracted blocks into larger files (a file per 1,000 blocks) once
+	/// they're well behind the chain's tip, which makes reprocessing much
+	/// faster on S3.
+	#[arg(help_heading = "Storage options", long, env = "BARRELEYE_COMPACT_STORAGE")]
+	pub compact_storage: bool,
+
 	/// Database to connect to. Supports SQLite, PostgreSQL and MySQL.
 	///
 	/// SQLite eg: sqlite://database_path?mode=rwc
@@ -108,6 +186,35 @@ pub struct Settings {
 	#[arg(help_heading = "Database options", long, default_value_t = 8, value_name = "SECONDS")]
 	pub database_max_lifetime: u64,
 
+	/// Max time a single statement may run before it's cancelled. Set to 0
+	/// to disable. Only applies to PostgreSQL.
+	#[arg(help_heading = "Database options", long, default_value_t = 0, value_name = "SECONDS")]
+	pub database_statement_timeout: u64,
+
+	/// Use write-ahead logging with SQLite, so readers don't block writers.
+	#[arg(
+		help_heading = "Database options",
+		long,
+		default_value_t = true,
+		action = ArgAction::Set,
+		value_name = "BOOL"
+	)]
+	pub database_sqlite_wal: bool,
+
+	/// How long SQLite waits on a locked database before giving up.
+	#[arg(help_heading = "Database options", long, default_value_t = 5, value_name = "SECONDS")]
+	pub database_sqlite_busy_timeout: u64,
+
+	/// SQLite's `synchronous` setting.
+	#[arg(
+		help_heading = "Database options",
+		long,
+		default_value = "normal",
+		value_parser = ["off", "normal", "full", "extra"],
+		value_name = "MODE"
+	)]
+	pub database_sqlite_synchronous: String,
+
 	/// Warehouse for storing analytical data. Supports DuckDB and ClickHouse.
 	///
 	/// DuckDB eg: /path/to/your/database.db
@@ -122,9 +229,140 @@ pub struct Settings {
 		value_name = "URI"
 	)]
 	pub warehouse: String,
+
+	/// Drop ClickHouse rows once their monthly partition is this many months
+	/// old, using table `TTL`s.
+	#[arg(help_heading = "Warehouse options", long, value_name = "MONTHS")]
+	pub warehouse_ttl: Option<u32>,
+
+	/// Move ClickHouse partitions older than this many months into storage as
+	/// parquet (under `archive/`), and detach them from the warehouse.
+	/// Requires S3 storage.
+	#[arg(help_heading = "Warehouse options", long, value_name = "MONTHS")]
+	pub warehouse_archive_after: Option<u32>,
 	#[arg(skip)]
 	pub warehouse_driver: WarehouseDriver,
 
+	/// Memory DuckDB can use per connection (eg: `2GB`), for both extraction
+	/// and the DuckDB warehouse. Past it, work spills to `duckdb_temp_directory`.
+	#[arg(help_heading = "Warehouse options", long, value_name = "SIZE")]
+	pub duckdb_memory_limit: Option<String>,
+
+	/// Threads DuckDB can use per connection (defaults to all cores).
+	#[arg(help_heading = "Warehouse options", long, value_name = "NUMBER")]
+	pub duckdb_threads: Option<u32>,
+
+	/// Where DuckDB spills data that doesn't fit in memory.
+	#[arg(help_heading = "Warehouse options", long, value_name = "FOLDER")]
+	pub duckdb_temp_directory: Option<PathBuf>,
+
+	/// Max number of records to keep in memory when the warehouse can't
+	/// keep up. Anything above is spilled to disk and replayed later.
+	#[arg(
+		help_heading = "Indexer options",
+		long,
+		default_value_t = 1_000_000,
+		value_name = "NUMBER"
+	)]
+	pub buffer_max_records: usize,
+
+	/// Max size of data spilled to disk while the warehouse is unreachable.
+	/// Once it's reached, extraction waits for the warehouse to come back.
+	#[arg(help_heading = "Indexer options", long, default_value_t = 10_240, value_name = "MB")]
+	pub buffer_max_disk_size: u64,
+
+	/// Gateway used to resolve `ipfs://` NFT metadata.
+	#[arg(
+		help_heading = "Indexer options",
+		long,
+		default_value = "https://ipfs.io/ipfs/",
+		value_name = "URL"
+	)]
+	pub ipfs_gateway: String,
+
+	/// Max requests per second when fetching NFT metadata.
+	#[arg(help_heading = "Indexer options", long, default_value_t = 5, value_name = "NUMBER")]
+	pub nft_metadata_rps: u32,
+
+	/// Plugins to enable, by name. Plugins are registered by programs that
+	/// embed Barreleye; if none are named, all registered plugins are enabled.
+	#[arg(
+		help_heading = "Indexer options",
+		long,
+		env = "BARRELEYE_PLUGINS",
+		value_delimiter = ',',
+		value_name = "NAMES"
+	)]
+	pub plugins: Vec<String>,
+
+	/// How often soft-deleted addresses, entities and networks are pruned.
+	#[arg(help_heading = "Indexer options", long, default_value_t = 3600, value_name = "SECONDS")]
+	pub prune_interval: u64,
+
+	/// UTC windows that pruning and warehouse optimization are limited to, so
+	/// heavy warehouse work runs off-peak (eg: `01:00-05:00`). If none are set,
+	/// they run any time.
+	#[arg(
+		help_heading = "Indexer options",
+		long,
+		value_delimiter = ',',
+		value_name = "HH:MM-HH:MM"
+	)]
+	maintenance_windows: Vec<String>,
+	#[arg(skip)]
+	pub maintenance_windows_utc: Vec<(NaiveTime, NaiveTime)>,
+
+	/// How often ClickHouse tables are merged with `OPTIMIZE TABLE … FINAL`,
+	/// so duplicate rows don't pile up between background merges (`0` disables
+	/// it).
+	#[arg(
+		help_heading = "Indexer options",
+		long,
+		default_value_t = 86400,
+		value_name = "SECONDS"
+	)]
+	pub warehouse_optimize_interval: u64,
+
+	/// How often a sample of warehouse balances is checked against what the
+	/// node reports, raising `balanceDrift` alerts on mismatches (`0` disables
+	/// it).
+	#[arg(
+		help_heading = "Indexer options",
+		long,
+		default_value_t = 86400,
+		value_name = "SECONDS"
+	)]
+	pub reconcile_interval: u64,
+
+	/// How often daily USD prices are fetched for networks with a `priceId`
+	/// (`0` disables it).
+	#[arg(help_heading = "Indexer options", long, default_value_t = 3600, value_name = "SECONDS")]
+	pub prices_interval: u64,
+
+	/// How many days of prices to backfill the first time a network's are
+	/// fetched.
+	#[arg(help_heading = "Indexer options", long, default_value_t = 365, value_name = "DAYS")]
+	pub prices_backfill_days: u16,
+
+	/// CoinGecko-compatible API that prices are fetched from.
+	#[arg(
+		help_heading = "Indexer options",
+		long,
+		default_value = "https://api.coingecko.com/api/v3",
+		value_name = "URL"
+	)]
+	pub prices_url: String,
+
+	/// API key for `prices_url`. It's sent as a pro key when the URL is
+	/// CoinGecko's pro API, and as a demo key otherwise.
+	#[arg(
+		help_heading = "Indexer options",
+		long,
+		env = "BARRELEYE_PRICES_API_KEY",
+		value_name = "KEY"
+	)]
+	pub prices_api_key: Option<String>,
+
 	#[arg(
 		help_heading = "Server options",
 		long,
@@ -137,11 +375,210 @@ pub struct Settings {
 
 	#[arg(help_heading = "Server options", long, default_value_t = 80, value_name = "PORT")]
 	pub port: u16,
+
+	/// How long to cache address labels and historical balances. Set to 0 to
+	/// disable caching.
+	#[arg(help_heading = "Server options", long, default_value_t = 60, value_name = "SECONDS")]
+	pub cache_ttl: u64,
+
+	#[arg(help_heading = "Server options", long, default_value_t = 10_000, value_name = "NUMBER")]
+	pub cache_capacity: usize,
+
+	/// How long to cache `/v1/info` responses, for hot addresses that get
+	/// screened over and over. Responses are dropped as soon as labels change
+	/// or new blocks are processed. Set to 0 to disable.
+	#[arg(help_heading = "Server options", long, default_value_t = 0, value_name = "SECONDS")]
+	pub info_cache_ttl: u64,
+
+	/// Max requests per minute for each API key. Responses carry
+	/// `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset`
+	/// headers; requests above the limit get a 429 with a `Retry-After`
+	/// header. Set to 0 to disable.
+	#[arg(help_heading = "Server options", long, default_value_t = 0, value_name = "NUMBER")]
+	pub api_rate_limit: u32,
+
+	/// JWKS endpoint used to validate externally issued JWTs, which are then
+	/// accepted as bearer tokens alongside API keys.
+	#[arg(help_heading = "Server options", long, value_name = "URL")]
+	pub jwt_jwks_url: Option<String>,
+
+	/// Required `iss` claim of JWTs.
+	#[arg(help_heading = "Server options", long, value_name = "ISSUER")]
+	pub jwt_issuer: Option<String>,
+
+	/// Required `aud` claim of JWTs.
+	#[arg(help_heading = "Server options", long, value_name = "AUDIENCE")]
+	pub jwt_audience: Option<String>,
+
+	/// OpenID Connect issuer used to log into admin endpoints (eg: networks,
+	/// entities, keys and anything that writes). When set, those endpoints
+	/// only accept sessions from `/auth/login`, while API keys keep working
+	/// everywhere else.
+	#[arg(help_heading = "Server options", long, value_name = "URL")]
+	pub oidc_issuer: Option<String>,
+
+	#[arg(help_heading = "Server options", long, value_name = "CLIENT_ID")]
+	pub oidc_client_id: Option<String>,
+
+	#[arg(
+		help_heading = "Server options",
+		long,
+		env = "BARRELEYE_OIDC_CLIENT_SECRET",
+		value_name = "SECRET"
+	)]
+	pub oidc_client_secret: Option<String>,
+
+	/// Where the identity provider sends users back to, eg:
+	/// https://barreleye.example.com/auth/callback
+	#[arg(help_heading = "Server options", long, value_name = "URL")]
+	pub oidc_redirect_url: Option<String>,
+
+	/// Subjects (`sub` claims) allowed to log in through OpenID Connect.
+	#[arg(help_heading = "Server options", long, value_delimiter = ',', value_name = "SUBJECTS")]
+	pub oidc_allowed_subjects: Vec<String>,
+
+	/// Verified emails allowed to log in through OpenID Connect.
+	#[arg(help_heading = "Server options", long, value_delimiter = ',', value_name = "EMAILS")]
+	pub oidc_allowed_emails: Vec<String>,
+
+	/// PEM certificate chain to serve HTTPS with (requires `tls_key`).
+	#[arg(help_heading = "Server options", long, value_name = "FILE")]
+	pub tls_cert: Option<PathBuf>,
+
+	/// PEM private key matching `tls_cert`.
+	#[arg(help_heading = "Server options", long, value_name = "FILE")]
+	pub tls_key: Option<PathBuf>,
+
+	/// PEM bundle of CAs that client certificates are validated against. When
+	/// set, clients have to present a certificate, and ones whose common name
+	/// matches an API key's `clientIdentity` are authenticated as that key.
+	#[arg(help_heading = "Server options", long, value_name = "FILE")]
+	pub tls_client_ca: Option<PathBuf>,
+
+	/// Header carrying the client certificate's subject when TLS is terminated
+	/// by a trusted proxy (eg: `x-ssl-client-s-dn`). Requires
+	/// `tls_client_header_proxies`, and the proxy should always overwrite it.
+	#[arg(help_heading = "Server options", long, value_name = "HEADER")]
+	pub tls_client_header: Option<String>,
+
+	/// IP addresses of the proxies trusted to set `tls_client_header`. The
+	/// header is ignored on requests coming from anywhere else.
+	#[arg(
+		help_heading = "Server options",
+		long,
+		value_delimiter = ',',
+		value_name = "IP_ADDRESSES"
+	)]
+	pub tls_client_header_proxies: Vec<IpAddr>,
+
+	/// How long admin sessions last.
+	#[arg(help_heading = "Server options", long, default_value_t = 3_600, value_name = "SECONDS")]
+	pub session_ttl: u64,
+
+	/// Max number of rows returned by `/v1/query`.
+	#[arg(help_heading = "Server options", long, default_value_t = 1_000, value_name = "NUMBER")]
+	pub query_max_rows: usize,
+
+	/// Max time a `/v1/query` statement may run before it's cancelled.
+	#[arg(help_heading = "Server options", long, default_value_t = 10, value_name = "SECONDS")]
+	pub query_timeout: u64,
+}
+
+// @NOTE one-off commands that run against the configured database, warehouse
+// and storage and then exit, instead of starting the indexer or the server
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+	/// Export or import a full dataset snapshot.
+	#[command(subcommand)]
+	Snapshot(SnapshotCommand),
+	/// Inspect or repair indexer state (eg: a stuck chunk marker).
+	#[command(subcommand)]
+	Config(ConfigCommand),
+	/// Bulk-import address labels into entities, addresses and tags.
+	#[command(subcommand)]
+	Labels(LabelsCommand),
+	/// Fetch random processed blocks from the RPC again and compare them
+	/// against stored files and warehouse rows.
+	Verify {
+		/// Network id to verify.
+		#[arg(long)]
+		network: String,
+		/// How many blocks to check.
+		#[arg(long, default_value_t = 10)]
+		sample: usize,
+	},
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SnapshotCommand {
+	/// Write the database, warehouse tables and a storage manifest into an
+	/// empty folder.
+	Export {
+		#[arg(value_hint = ValueHint::DirPath, value_name = "FOLDER")]
+		path: PathBuf,
+	},
+	/// Restore a snapshot into a new deployment, so it doesn't have to
+	/// re-index from genesis. Extracted files are not part of the snapshot
+	/// and have to be copied into storage separately.
+	Import {
+		#[arg(value_hint = ValueHint::DirPath, value_name = "FOLDER")]
+		path: PathBuf,
+	},
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+	/// List stored configs, optionally only the ones containing `filter`.
+	List { filter: Option<String> },
+	/// Show a config, eg: `indexer_sync_tail_n1`.
+	Get { key: String },
+	/// Set a config to a JSON value, eg: `indexer_sync_chunk_n1_b100 '[100,200]'`.
+	/// The value has to match the type the key is read as.
+	Set { key: String, value: String },
+	/// Delete a config.
+	Delete { key: String },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum LabelsCommand {
+	/// Import labels from a file for one network, eg: an etherscan label dump
+	/// (`address`, `name tag` and `labels` columns) for an ethereum network, or
+	/// a GraphSense TagPack.
+	Import {
+		#[arg(value_hint = ValueHint::FilePath, value_name = "FILE")]
+		path: PathBuf,
+		/// Network id the addresses are on.
+		#[arg(long)]
+		network: String,
+		#[arg(long, default_value = "etherscan")]
+		format: LabelFormat,
+		/// What to do with addresses that are already labeled: `skip` them,
+		/// or `replace` their labels.
+		#[arg(long, default_value = "skip")]
+		on_conflict: OnConflict,
+		/// Only import labels for this currency (eg: `BTC`), for formats that
+		/// have one (eg: `tagpack`).
+		#[arg(long)]
+		currency: Option<String>,
+	},
 }
 
 impl Settings {
 	pub async fn new() -> Result<(Self, Warnings)> {
-		let mut settings = Self::parse();
+		Self::init(Self::parse(), true).await
+	}
+
+	// for embedding, where settings come from `args` (eg: `["barreleye",
+	// "--mode", "http"]`) instead of the process' own command line
+	pub async fn from_args<I, T>(args: I) -> Result<(Self, Warnings)>
+	where
+		I: IntoIterator<Item = T>,
+		T: Into<OsString> + Clone,
+	{
+		Self::init(Self::try_parse_from(args)?, false).await
+	}
+
+	async fn init(mut settings: Self, show_banner: bool) -> Result<(Self, Warnings)> {
 		let warnings = Warnings::new();
 
 		// set is_indexer and is_server
@@ -158,7 +595,9 @@ impl Settings {
 		}
 
 		// show banner
-		banner::show(settings.is_indexer, settings.is_server)?;
+		if show_banner && settings.command.is_none() {
+			banner::show(settings.is_indexer, settings.is_server)?;
+		}
 
 		// set driver for db
 		let test_scheme = settings.database.split(':').next().unwrap_or_default();
@@ -221,6 +660,85 @@ impl Settings {
 				error: "could not parse IP v4.",
 			})?));
 
+		// parse maintenance windows
+		for window in settings.maintenance_windows.iter() {
+			let times = window
+				.split_once('-')
+				.and_then(|(start, end)| {
+					Some((
+						NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?,
+						NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?,
+					))
+				})
+				.ok_or(AppError::Config {
+					config: "maintenance_windows",
+					error: "could not parse window; expected `HH:MM-HH:MM`",
+				})?;
+			settings.maintenance_windows_utc.push(times);
+		}
+
+		// test jwks url
+		if let Some(jwks_url) = &settings.jwt_jwks_url {
+			if Url::parse(jwks_url).is_err() {
+				return Err(AppError::Config {
+					config: "jwt_jwks_url",
+					error: "could not parse URL",
+				}
+				.into());
+			}
+		}
+
+		// test oidc config
+		if settings.oidc_issuer.is_some() &&
+			(settings.oidc_client_id.is_none() || settings.oidc_redirect_url.is_none())
+		{
+			return Err(AppError::Config {
+				config: "oidc_issuer",
+				error: "requires `oidc_client_id` and `oidc_redirect_url`",
+			}
+			.into());
+		}
+
+		// an identity provider is often shared (eg: google or a company-wide
+		// okta), so who gets in has to be spelled out
+		if settings.oidc_issuer.is_some() &&
+			settings.oidc_allowed_subjects.is_empty() &&
+			settings.oidc_allowed_emails.is_empty()
+		{
+			return Err(AppError::Config {
+				config: "oidc_issuer",
+				error: "requires `oidc_allowed_subjects` or `oidc_allowed_emails`",
+			}
+			.into());
+		}
+
+		// test tls config
+		if settings.tls_cert.is_some() != settings.tls_key.is_some() {
+			return Err(AppError::Config {
+				config: "tls_cert",
+				error: "`tls_cert` and `tls_key` have to be set together",
+			}
+			.into());
+		}
+		if settings.tls_client_ca.is_some() && settings.tls_cert.is_none() {
+			return Err(AppError::Config {
+				config: "tls_client_ca",
+				error: "requires `tls_cert` and `tls_key`",
+			}
+			.into());
+		}
+		settings.tls_client_header =
+			settings.tls_client_header.take().map(|h| h.trim().to_lowercase());
+		if settings.tls_client_header.is_some() && settings.tls_client_header_proxies.is_empty() {
+			return Err(AppError::Config {
+				config: "tls_client_header",
+				error: "requires `tls_client_header_proxies`",
+			}
+			.into());
+		}
+		settings.tls_client_header_proxies =
+			settings.tls_client_header_proxies.iter().map(|ip| ip.to_canonical()).collect();
+
 		// test storage
 		let folder_prefix = "file://";
 		if settings.storage.starts_with('/') ||
@@ -255,14 +773,60 @@ impl Settings {
 			}
 
 			// check that service is known
-			let storage_url = S3::from_str(&settings.storage)?;
+			let mut storage_url = S3::from_str(&settings.storage)?;
 			if storage_url.service == S3Service::Unknown || storage_url.bucket.is_none() {
 				return Err(err.into());
 			}
 
+			if let Some(endpoint) = settings.s3_endpoint.clone() {
+				storage_url.endpoint = Some(endpoint);
+			}
+			storage_url.path_style = settings.s3_path_style;
+			storage_url.sse_kms_key_id = settings.s3_sse_kms_key_id.clone();
+
 			settings.storage_url = Some(storage_url);
 		}
 
+		// test glue catalog
+		if settings.glue_database.is_some() &&
+			!settings.storage_url.as_ref().is_some_and(|s3| s3.service == S3Service::S3)
+		{
+			return Err(AppError::Config {
+				config: "glue_database",
+				error: "registering tables requires Amazon S3 storage",
+			}
+			.into());
+		}
+
+		// test warehouse retention
+		if (settings.warehouse_ttl.is_some() || settings.warehouse_archive_after.is_some()) &&
+			settings.warehouse_driver != WarehouseDriver::ClickHouse
+		{
+			return Err(AppError::Config {
+				config: "warehouse_ttl",
+				error: "`warehouse_ttl` and `warehouse_archive_after` require ClickHouse",
+			}
+			.into());
+		}
+		if settings.warehouse_archive_after.is_some() && settings.storage_url.is_none() {
+			return Err(AppError::Config {
+				config: "warehouse_archive_after",
+				error: "archiving requires S3 storage",
+			}
+			.into());
+		}
+		if let (Some(ttl), Some(archive_after)) =
+			(settings.warehouse_ttl, settings.warehouse_archive_after)
+		{
+			if archive_after >= ttl {
+				return Err(AppError::Config {
+					config: "warehouse_archive_after",
+					error: "has to be less than `warehouse_ttl`, or partitions expire first",
+				}
+				.into());
+			}
+		}
+
 		Ok((settings, warnings))
 	}
 }
@@ -0,0 +1,418 @@
+use chrono::{NaiveDateTime, Utc};
+use eyre::{bail, Result};
+use sea_orm::{
+	sea_query::{Alias, Keyword, Query, SimpleExpr},
+	ConnectionTrait, DbBackend, FromQueryResult, Statement, Value,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::{
+	collections::HashSet,
+	fs::{self, File},
+	io::{BufRead, BufReader, BufWriter, Write},
+	path::Path,
+};
+
+use crate::{
+	db::Driver as DatabaseDriver,
+	models::{
+		AmountTable, ApprovalTable, BlockTable, DecodedCallTable, FeeTable, LinkTable, PriceTable,
+		StakingDepositTable, TransferTable, UserOperationTable,
+	},
+	App,
+};
+
+// bumped whenever the bundle layout changes
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+static MANIFEST_FILE: &str = "manifest.json";
+
+// rows per insert when restoring
+const BATCH_SIZE: usize = 1_000;
+
+// blocks per select when exporting warehouse tables
+const BLOCK_RANGE: u64 = 10_000;
+
+// @NOTE relational tables in the order they're restored in (so foreign keys
+// resolve), along with their auto-incrementing primary key. sessions are left
+// out on purpose, since they're only valid on the machine that issued them
+static DB_TABLES: &[(&str, Option<&str>)] = &[
+	("configs", Some("config_id")),
+	("networks", Some("network_id")),
+	("api_keys", Some("api_key_id")),
+	("entities", Some("entity_id")),
+	("addresses", Some("address_id")),
+	("categories", Some("category_id")),
+	("tags", Some("tag_id")),
+	("entity_tags", None),
+	("tokens", Some("token_id")),
+	("nfts", Some("nft_id")),
+	("alerts", Some("alert_id")),
+	("annotations", Some("annotation_id")),
+	("peel_hops", Some("peel_hop_id")),
+	("api_key_usage", Some("api_key_usage_id")),
+	("abis", Some("abi_id")),
+	("bridge_transfers", Some("bridge_transfer_id")),
+	("report_schedules", Some("report_schedule_id")),
+	("webhooks", Some("webhook_id")),
+];
+
+// balances, balance snapshots and network stats are materialized from these
+// on insert, so they're rebuilt as part of the restore
+static WAREHOUSE_TABLES: &[&str] = &[
+	TransferTable,
+	AmountTable,
+	LinkTable,
+	DecodedCallTable,
+	UserOperationTable,
+	FeeTable,
+	BlockTable,
+	StakingDepositTable,
+	ApprovalTable,
+];
+
+// warehouse tables that aren't keyed by block height. they only have a row
+// per day, so they're selected a network at a time
+static DAILY_WAREHOUSE_TABLES: &[&str] = &[PriceTable];
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+	pub version: u32,
+	pub barreleye_version: String,
+	pub created_at: NaiveDateTime,
+	pub database_driver: DatabaseDriver,
+	pub db_tables: Vec<TableManifest>,
+	pub warehouse_tables: Vec<TableManifest>,
+	pub storage: StorageManifest,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableManifest {
+	pub table: String,
+	pub rows: u64,
+}
+
+// extracted files aren't copied into the bundle (they can be terabytes);
+// they're listed instead, so a restore can check they're all in place
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageManifest {
+	pub root: Option<String>,
+	pub files: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MaxBlockHeight {
+	block_height: Option<u64>,
+}
+
+// @NOTE writes a versioned bundle into `path`:
+//
+// manifest.json
+// db/<table>.jsonl
+// warehouse/<table>.jsonl
+pub async fn export(app: &App, path: &Path) -> Result<Manifest> {
+	if path.exists() && fs::read_dir(path)?.next().is_some() {
+		bail!("snapshot folder is not empty: {}", path.display());
+	}
+	fs::create_dir_all(path.join("db"))?;
+	fs::create_dir_all(path.join("warehouse"))?;
+
+	// relational db
+	let mut db_tables = vec![];
+	for (table, _) in DB_TABLES.iter() {
+		let rows = JsonValue::find_by_statement(Statement::from_string(
+			app.db().get_database_backend(),
+			format!("SELECT * FROM {table}"),
+		))
+		.all(app.db())
+		.await?;
+
+		write_rows(&path.join("db").join(format!("{table}.jsonl")), &rows)?;
+		db_tables.push(TableManifest { table: table.to_string(), rows: rows.len() as u64 });
+	}
+
+	// warehouse (selected in block ranges, so tables don't have to fit in memory)
+	let network_ids = JsonValue::find_by_statement(Statement::from_string(
+		app.db().get_database_backend(),
+		"SELECT network_id FROM networks".to_string(),
+	))
+	.all(app.db())
+	.await?
+	.into_iter()
+	.filter_map(|row| row["network_id"].as_i64())
+	.collect::<Vec<_>>();
+
+	let mut warehouse_tables = vec![];
+	for table in WAREHOUSE_TABLES.iter() {
+		let mut file =
+			BufWriter::new(File::create(path.join("warehouse").join(format!("{table}.jsonl")))?);
+
+		let mut total = 0;
+		for network_id in network_ids.iter() {
+			let max_block_height = app
+				.warehouse
+				.select::<MaxBlockHeight>(&format!(
+					r#"
+						SELECT MAX(block_height) AS block_height
+						FROM {table}
+						WHERE network_id = {network_id}
+					"#
+				))
+				.await?
+				.first()
+				.and_then(|m| m.block_height);
+
+			let Some(max_block_height) = max_block_height else {
+				continue;
+			};
+
+			let mut block_height = 0;
+			while block_height <= max_block_height {
+				let rows = app
+					.warehouse
+					.select::<JsonValue>(&format!(
+						r#"
+							SELECT *
+							FROM {table}
+							WHERE
+								network_id = {network_id} AND
+								block_height >= {block_height} AND
+								block_height < {}
+						"#,
+						block_height + BLOCK_RANGE
+					))
+					.await?;
+
+				for row in rows.iter() {
+					serde_json::to_writer(&mut file, row)?;
+					file.write_all(b"\n")?;
+				}
+
+				total += rows.len() as u64;
+				block_height += BLOCK_RANGE;
+			}
+		}
+
+		file.flush()?;
+		warehouse_tables.push(TableManifest { table: table.to_string(), rows: total });
+	}
+
+	for table in DAILY_WAREHOUSE_TABLES.iter() {
+		let mut file =
+			BufWriter::new(File::create(path.join("warehouse").join(format!("{table}.jsonl")))?);
+
+		let mut total = 0;
+		for network_id in network_ids.iter() {
+			let rows = app
+				.warehouse
+				.select::<JsonValue>(&format!(
+					r#"
+						SELECT *
+						FROM {table}
+						WHERE network_id = {network_id}
+					"#
+				))
+				.await?;
+
+			for row in rows.iter() {
+				serde_json::to_writer(&mut file, row)?;
+				file.write_all(b"\n")?;
+			}
+
+			total += rows.len() as u64;
+		}
+
+		file.flush()?;
+		warehouse_tables.push(TableManifest { table: table.to_string(), rows: total });
+	}
+
+	// storage
+	let storage =
+		StorageManifest { root: app.storage.get_root(), files: app.storage.list_files()? };
+
+	let manifest = Manifest {
+		version: SNAPSHOT_VERSION,
+		barreleye_version: env!("CARGO_PKG_VERSION").to_string(),
+		created_at: Utc::now().naive_utc(),
+		database_driver: app.settings.database_driver.clone(),
+		db_tables,
+		warehouse_tables,
+		storage,
+	};
+	fs::write(path.join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)?;
+
+	Ok(manifest)
+}
+
+// @NOTE restores a bundle made by `export` into a fresh deployment. the
+// warehouse goes first and the relational db last (in one transaction), so if
+// anything fails the db is still empty and the import can be re-run. returns
+// the manifest along with any extracted files that are missing from storage
+pub async fn import(app: &App, path: &Path) -> Result<(Manifest, Vec<String>)> {
+	let manifest: Manifest = serde_json::from_str(&fs::read_to_string(path.join(MANIFEST_FILE))?)?;
+
+	if manifest.version != SNAPSHOT_VERSION {
+		bail!("unsupported snapshot version: {}", manifest.version);
+	}
+
+	// values are dumped as-is, so they only go back into the same kind of db
+	if manifest.database_driver != app.settings.database_driver {
+		bail!(
+			"snapshot was taken from {}, but the database is {}",
+			manifest.database_driver,
+			app.settings.database_driver
+		);
+	}
+
+	let backend = app.db().get_database_backend();
+	if JsonValue::find_by_statement(Statement::from_string(
+		backend,
+		"SELECT network_id FROM networks LIMIT 1".to_string(),
+	))
+	.one(app.db())
+	.await?
+	.is_some()
+	{
+		bail!("database is not empty; snapshots can only be imported into a new deployment");
+	}
+
+	// warehouse
+	for t in manifest.warehouse_tables.iter() {
+		let mut rows = vec![];
+		for row in read_rows(&path.join("warehouse").join(format!("{}.jsonl", t.table)))? {
+			rows.push(row?);
+			if rows.len() >= BATCH_SIZE {
+				app.warehouse.insert(&t.table, &rows).await?;
+				rows.clear();
+			}
+		}
+		if !rows.is_empty() {
+			app.warehouse.insert(&t.table, &rows).await?;
+		}
+	}
+
+	// relational db (configs written on startup are replaced by the snapshot's)
+	let db_tx = app.db_tx().await?;
+	db_tx.execute(Statement::from_string(backend, "DELETE FROM configs".to_string())).await?;
+
+	for (table, primary_key) in DB_TABLES.iter() {
+		let rows = read_rows(&path.join("db").join(format!("{table}.jsonl")))?
+			.collect::<Result<Vec<_>>>()?;
+		for chunk in rows.chunks(BATCH_SIZE) {
+			let Some(columns) = chunk
+				.first()
+				.and_then(|row| row.as_object())
+				.map(|row| row.keys().cloned().collect::<Vec<_>>())
+			else {
+				continue;
+			};
+
+			let mut insert = Query::insert();
+			insert.into_table(Alias::new(*table)).columns(columns.iter().map(Alias::new));
+			for row in chunk.iter() {
+				insert.values(columns.iter().map(|c| to_db_value(&row[c])))?;
+			}
+
+			db_tx.execute(backend.build(&insert)).await?;
+		}
+
+		// postgres sequences don't move when ids are inserted explicitly
+		if let (DbBackend::Postgres, Some(primary_key)) = (backend, primary_key) {
+			db_tx
+				.execute(Statement::from_string(
+					backend,
+					format!(
+						r#"
+							SELECT setval(
+								pg_get_serial_sequence('{table}', '{primary_key}'),
+								COALESCE(MAX({primary_key}), 0) + 1,
+								false
+							)
+							FROM {table}
+						"#
+					),
+				))
+				.await?;
+		}
+	}
+
+	db_tx.commit().await?;
+
+	// storage
+	let existing_files = app.storage.list_files()?.into_iter().collect::<HashSet<_>>();
+	let missing_files = manifest
+		.storage
+		.files
+		.iter()
+		.filter(|f| !existing_files.contains(*f))
+		.cloned()
+		.collect::<Vec<_>>();
+
+	Ok((manifest, missing_files))
+}
+
+fn write_rows(path: &Path, rows: &[JsonValue]) -> Result<()> {
+	let mut file = BufWriter::new(File::create(path)?);
+
+	for row in rows.iter() {
+		serde_json::to_writer(&mut file, row)?;
+		file.write_all(b"\n")?;
+	}
+
+	Ok(file.flush()?)
+}
+
+// rows are read one line at a time, since warehouse tables can be large
+fn read_rows(path: &Path) -> Result<impl Iterator<Item = Result<JsonValue>>> {
+	let lines = match path.exists() {
+		true => Some(BufReader::new(File::open(path)?).lines()),
+		false => None,
+	};
+
+	Ok(lines.into_iter().flatten().filter_map(|line| match line {
+		Ok(line) if line.is_empty() => None,
+		Ok(line) => Some(serde_json::from_str(&line).map_err(|e| e.into())),
+		Err(e) => Some(Err(e.into())),
+	}))
+}
+
+// @NOTE dumped rows lose their column types, so they're inferred back from
+// json. timestamps come out as strings and have to be bound as datetimes
+// (postgres won't cast text into a timestamp column by itself)
+fn to_db_value(v: &JsonValue) -> SimpleExpr {
+	match v {
+		JsonValue::Null => SimpleExpr::Keyword(Keyword::Null),
+		JsonValue::Bool(b) => (*b).into(),
+		JsonValue::Number(n) => match n.as_i64() {
+			Some(n) => n.into(),
+			_ => n.as_f64().unwrap_or_default().into(),
+		},
+		JsonValue::String(s) => match to_datetime(s) {
+			Some(datetime) => datetime.into(),
+			_ => s.clone().into(),
+		},
+		_ => Value::Json(Some(Box::new(v.clone()))).into(),
+	}
+}
+
+fn to_datetime(s: &str) -> Option<NaiveDateTime> {
+	["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+		.iter()
+		.find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_to_datetime() {
+		assert!(to_datetime("2024-01-01T10:00:00").is_some());
+		assert!(to_datetime("2024-01-01 10:00:00.123").is_some());
+		assert!(to_datetime("0x2024-01-01").is_none());
+		assert!(to_datetime("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq").is_none());
+	}
+}
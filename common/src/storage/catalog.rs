@@ -0,0 +1,265 @@
+use eyre::{bail, Result};
+use serde_json::{json, Value};
+use std::{
+	collections::{BTreeMap, HashMap, HashSet},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex,
+	},
+};
+
+use crate::{
+	models::PrimaryId,
+	s3::{Credentials, SignedRequest, DEFAULT_REGION},
+	utils, Settings,
+};
+
+// folder (next to extracted blocks) with one symlink file per block and file
+pub const SYMLINKS_FOLDER: &str = "_symlinks";
+
+// upper bound of projected partitions; athena only lists the ones filtered on
+const MAX_BLOCK_HEIGHT: u64 = 100_000_000_000;
+
+#[derive(Debug, Clone)]
+struct Table {
+	network_id: PrimaryId,
+	file: String,
+	columns: Vec<(String, String)>,
+}
+
+// @NOTE registers extracted files as aws glue tables (one per network and
+// file, eg: `network_1_transactions`), so athena can query them without any
+// ddl. a block's folder mixes different files, so tables point at symlink
+// files instead (`_symlinks/network_id=1/transactions/block_height=100/`),
+// and `block_height` partitions are projected rather than added one by one
+pub struct Catalog {
+	settings: Arc<Settings>,
+	pending: Mutex<HashMap<String, Table>>,
+	registered: Mutex<HashSet<String>>,
+	has_database: AtomicBool,
+}
+
+impl Catalog {
+	pub fn new(settings: Arc<Settings>) -> Self {
+		Self {
+			settings,
+			pending: Mutex::new(HashMap::new()),
+			registered: Mutex::new(HashSet::new()),
+			has_database: AtomicBool::new(false),
+		}
+	}
+
+	pub fn get_table_name(network_id: PrimaryId, file: &str) -> String {
+		format!("network_{network_id}_{file}")
+	}
+
+	// whether the table for `file` is registered already (or about to be)
+	pub fn is_tracked(&self, network_id: PrimaryId, file: &str) -> bool {
+		let name = Self::get_table_name(network_id, file);
+		self.registered.lock().unwrap().contains(&name) ||
+			self.pending.lock().unwrap().contains_key(&name)
+	}
+
+	// queues a table for registration; `columns` are duckdb names and types
+	pub fn track(&self, network_id: PrimaryId, file: &str, columns: Vec<(String, String)>) {
+		let name = Self::get_table_name(network_id, file);
+		if !self.registered.lock().unwrap().contains(&name) {
+			self.pending
+				.lock()
+				.unwrap()
+				.insert(name, Table { network_id, file: file.to_string(), columns });
+		}
+	}
+
+	pub async fn register_pending(&self, credentials: &Credentials) -> Result<()> {
+		let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+		if pending.is_empty() {
+			return Ok(());
+		}
+
+		if !self.has_database.load(Ordering::SeqCst) {
+			if let Err(e) = self.create_database(credentials).await {
+				self.pending.lock().unwrap().extend(pending);
+				return Err(e);
+			}
+			self.has_database.store(true, Ordering::SeqCst);
+		}
+
+		let mut pending = pending.into_iter();
+		while let Some((name, table)) = pending.next() {
+			if let Err(e) = self.create_table(credentials, &name, &table).await {
+				let mut queue = self.pending.lock().unwrap();
+				queue.insert(name, table);
+				queue.extend(pending);
+
+				return Err(e);
+			}
+
+			self.registered.lock().unwrap().insert(name);
+		}
+
+		Ok(())
+	}
+
+	async fn create_database(&self, credentials: &Credentials) -> Result<()> {
+		let body = json!({ "DatabaseInput": { "Name": self.get_database() } });
+
+		match self.call(credentials, "CreateDatabase", &body).await? {
+			Some(e) if !e.ends_with("AlreadyExistsException") => bail!("Glue: {e}"),
+			_ => Ok(()),
+		}
+	}
+
+	// tables are updated when they exist already, in case columns were added
+	async fn create_table(
+		&self,
+		credentials: &Credentials,
+		name: &str,
+		table: &Table,
+	) -> Result<()> {
+		let body = json!({
+			"DatabaseName": self.get_database(),
+			"TableInput": self.get_table_input(name, table),
+		});
+
+		match self.call(credentials, "CreateTable", &body).await? {
+			Some(e) if e.ends_with("AlreadyExistsException") => {
+				if let Some(e) = self.call(credentials, "UpdateTable", &body).await? {
+					bail!("Glue: {e}");
+				}
+			}
+			Some(e) => bail!("Glue: {e}"),
+			None => {}
+		}
+
+		Ok(())
+	}
+
+	fn get_table_input(&self, name: &str, table: &Table) -> Value {
+		let bucket =
+			self.settings.storage_url.as_ref().and_then(|s3| s3.bucket.clone()).unwrap_or_default();
+		let location = format!(
+			"s3://{bucket}/{SYMLINKS_FOLDER}/network_id={}/{}",
+			table.network_id, table.file
+		);
+
+		let columns = table
+			.columns
+			.iter()
+			.map(|(name, data_type)| json!({ "Name": name, "Type": get_hive_type(data_type) }))
+			.collect::<Vec<_>>();
+
+		json!({
+			"Name": name,
+			"TableType": "EXTERNAL_TABLE",
+			"PartitionKeys": [{ "Name": "block_height", "Type": "bigint" }],
+			"Parameters": {
+				"EXTERNAL": "TRUE",
+				"classification": "parquet",
+				"projection.enabled": "true",
+				"projection.block_height.type": "integer",
+				"projection.block_height.range": format!("0,{MAX_BLOCK_HEIGHT}"),
+				"storage.location.template": format!("{location}/block_height=${{block_height}}/"),
+			},
+			"StorageDescriptor": {
+				"Columns": columns,
+				"Location": format!("{location}/"),
+				"InputFormat": "org.apache.hadoop.hive.ql.io.SymlinkTextInputFormat",
+				"OutputFormat": "org.apache.hadoop.hive.ql.io.HiveIgnoreKeyTextOutputFormat",
+				"SerdeInfo": {
+					"SerializationLibrary":
+						"org.apache.hadoop.hive.ql.io.parquet.serde.ParquetHiveSerDe",
+				},
+			},
+		})
+	}
+
+	// @NOTE glue's json api: returns the error type (eg:
+	// `AlreadyExistsException`) if glue rejected the request, and an error if it
+	// couldn't be reached at all
+	async fn call(
+		&self,
+		credentials: &Credentials,
+		action: &str,
+		body: &Value,
+	) -> Result<Option<String>> {
+		let region = self.get_region();
+		let host = format!("glue.{region}.amazonaws.com");
+		let body = serde_json::to_vec(body)?;
+
+		let mut headers = BTreeMap::from([
+			("content-type".to_string(), "application/x-amz-json-1.1".to_string()),
+			("host".to_string(), host.clone()),
+			("x-amz-date".to_string(), utils::now().format("%Y%m%dT%H%M%SZ").to_string()),
+			("x-amz-target".to_string(), format!("AWSGlue.{action}")),
+		]);
+		if let Some(session_token) = &credentials.session_token {
+			headers.insert("x-amz-security-token".to_string(), session_token.clone());
+		}
+
+		let request =
+			SignedRequest { method: "POST", path: "/", query: "", headers: &headers, body: &body };
+		let authorization = credentials.sign(&request, &region, "glue");
+
+		let mut builder = reqwest::Client::new().post(format!("https://{host}/"));
+		for (k, v) in headers.iter().filter(|(k, _)| *k != "host") {
+			builder = builder.header(k, v);
+		}
+		let response = builder.header("authorization", authorization).body(body).send().await?;
+
+		if response.status().is_success() {
+			return Ok(None);
+		}
+
+		let status = response.status();
+		let error = response
+			.json::<Value>()
+			.await
+			.ok()
+			.and_then(|v| v.get("__type").and_then(|t| t.as_str()).map(|t| t.to_string()));
+
+		Ok(Some(error.unwrap_or_else(|| status.to_string())))
+	}
+
+	fn get_database(&self) -> String {
+		self.settings.glue_database.clone().unwrap_or_default()
+	}
+
+	fn get_region(&self) -> String {
+		self.settings
+			.storage_url
+			.as_ref()
+			.and_then(|s3| s3.region.clone())
+			.unwrap_or_else(|| DEFAULT_REGION.to_string())
+	}
+}
+
+// closest hive type for a duckdb column type
+fn get_hive_type(data_type: &str) -> &'static str {
+	match data_type.to_uppercase().as_str() {
+		"VARCHAR" => "string",
+		"BLOB" => "binary",
+		"BOOLEAN" => "boolean",
+		"TINYINT" | "SMALLINT" | "INTEGER" | "UTINYINT" | "USMALLINT" => "int",
+		"BIGINT" | "UINTEGER" | "UBIGINT" => "bigint",
+		"FLOAT" => "float",
+		"DOUBLE" | "HUGEINT" | "UHUGEINT" => "double",
+		"DATE" => "date",
+		"TIMESTAMP" => "timestamp",
+		_ => "string",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_get_hive_type() {
+		assert_eq!(get_hive_type("VARCHAR"), "string");
+		assert_eq!(get_hive_type("BLOB"), "binary");
+		assert_eq!(get_hive_type("UBIGINT"), "bigint");
+		assert_eq!(get_hive_type("integer"), "int");
+		assert_eq!(get_hive_type("DECIMAL(38,0)"), "string");
+	}
+}
@@ -0,0 +1,226 @@
+use duckdb::params;
+use eyre::{bail, Result};
+use std::{
+	collections::{BTreeMap, HashMap},
+	fs,
+	sync::Arc,
+};
+
+use super::{Storage, TableFormat, MANIFEST_FILE};
+use crate::{chain::BlockHashes, models::PrimaryId, BlockHeight};
+
+// blocks per compacted file; ranges always start at a multiple of it, so the
+// range a block is in doesn't have to be looked up
+pub const COMPACTION_RANGE: BlockHeight = 1_000;
+
+// a block's rows in a compacted range
+#[derive(Debug, Clone)]
+pub struct CompactedBlock {
+	pub path: String,
+	pub row_counts: BTreeMap<String, u64>,
+	pub block_hashes: BlockHashes,
+}
+
+#[derive(Debug, Default)]
+pub struct CompactedRange {
+	blocks: HashMap<BlockHeight, CompactedBlock>,
+}
+
+impl Storage {
+	// first block of the range `block_height` is in
+	pub fn get_range_start(block_height: BlockHeight) -> BlockHeight {
+		block_height - block_height % COMPACTION_RANGE
+	}
+
+	// folder of a compacted range (eg: `network_id=1/block_range=1000-1999`)
+	fn get_range_path(&self, network_id: PrimaryId, range_start: BlockHeight) -> Option<String> {
+		self.get_root().map(|root| {
+			format!(
+				"{root}/network_id={network_id}/block_range={range_start}-{}",
+				range_start + COMPACTION_RANGE - 1
+			)
+		})
+	}
+
+	// @NOTE ranges are looked up once and kept in memory, compacted or not:
+	// only ranges well below the processed tail are compacted, and it's this
+	// process that does it (see `compact_range`)
+	pub(crate) fn get_compacted(
+		&self,
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+	) -> Result<Option<CompactedBlock>> {
+		if self.scratch_path.is_some() {
+			return Ok(None);
+		}
+
+		let range_start = Self::get_range_start(block_height);
+		let cached = self.compacted.read().unwrap().get(&(network_id, range_start)).cloned();
+		let range = match cached {
+			Some(range) => range,
+			None => {
+				let range = Arc::new(self.read_compacted_range(network_id, range_start)?);
+				self.compacted.write().unwrap().insert((network_id, range_start), range.clone());
+				range
+			}
+		};
+
+		Ok(range.blocks.get(&block_height).cloned())
+	}
+
+	fn read_compacted_range(
+		&self,
+		network_id: PrimaryId,
+		range_start: BlockHeight,
+	) -> Result<CompactedRange> {
+		let mut ret = CompactedRange::default();
+		let Some(path) = self.get_range_path(network_id, range_start) else {
+			return Ok(ret);
+		};
+
+		// reading a missing file errors out, so check with a glob first
+		let db = self.get_db()?;
+		let manifest_path = format!("{path}/{MANIFEST_FILE}.parquet");
+		let mut statement = db.prepare(&format!("SELECT count(*) FROM glob('{manifest_path}')"))?;
+		if statement.query_row([], |row| row.get::<_, u64>(0))? == 0 {
+			return Ok(ret);
+		}
+
+		let mut statement = db.prepare(&format!(
+			"SELECT block_height, file, row_count, hash, parent_hash FROM \
+			 read_parquet('{manifest_path}')"
+		))?;
+		let mut rows = statement.query([])?;
+		while let Some(row) = rows.next()? {
+			let block = ret.blocks.entry(row.get(0)?).or_insert_with(|| CompactedBlock {
+				path: path.clone(),
+				row_counts: BTreeMap::new(),
+				block_hashes: BlockHashes { hash: String::new(), parent_hash: String::new() },
+			});
+			block.row_counts.insert(row.get(1)?, row.get(2)?);
+			block.block_hashes = BlockHashes { hash: row.get(3)?, parent_hash: row.get(4)? };
+		}
+
+		Ok(ret)
+	}
+
+	// @NOTE merges every extracted block of a range into a file per type, with a
+	// `block_height` column, followed by the range's manifest (what readers
+	// look for). row counts are checked against the blocks' own manifests
+	// before it's written, so a bad merge is never read from. blocks without a
+	// valid manifest are left out. returns how many blocks were compacted
+	pub fn compact_range(&self, network_id: PrimaryId, range_start: BlockHeight) -> Result<usize> {
+		let Some(path) = self.get_range_path(network_id, range_start) else {
+			return Ok(0);
+		};
+
+		// blocks' manifests, and the files they list
+		let mut entries = vec![];
+		let mut files = BTreeMap::<String, Vec<String>>::new();
+		for block_height in range_start..range_start + COMPACTION_RANGE {
+			let storage_db = self.get(network_id, block_height)?;
+			if storage_db.compacted.is_some() {
+				return Ok(0);
+			}
+
+			for (file, row_count, block_hashes) in storage_db.read_manifest()?.into_iter() {
+				if let Some(file_path) = storage_db.get_path(&file)? {
+					files.entry(file.clone()).or_default().push(file_path);
+				}
+				entries.push((block_height, file, row_count, block_hashes));
+			}
+		}
+		if entries.is_empty() {
+			return Ok(0);
+		}
+
+		if let Some(storage_path) = &self.settings.storage_path {
+			fs::create_dir_all(
+				storage_path
+					.join(format!("network_id={network_id}"))
+					.join(path.rsplit('/').next().unwrap_or_default()),
+			)?;
+		}
+
+		// files are listed in block order, and insertion order is preserved
+		let db = self.get_db()?;
+		for (file, paths) in files.iter() {
+			let paths = paths.iter().map(|p| format!("'{p}'")).collect::<Vec<_>>().join(", ");
+			db.execute_batch(&format!(
+				"COPY (SELECT * EXCLUDE (network_id) FROM read_parquet([{paths}], \
+				 hive_partitioning = true, union_by_name = true)) TO '{path}/{file}.parquet' \
+				 (FORMAT PARQUET, COMPRESSION GZIP);"
+			))?;
+		}
+
+		let mut row_counts = HashMap::new();
+		for file in files.keys() {
+			let mut statement = db.prepare(&format!(
+				"SELECT block_height, count(*) FROM read_parquet('{path}/{file}.parquet') GROUP \
+				 BY block_height"
+			))?;
+			let mut rows = statement.query([])?;
+			while let Some(row) = rows.next()? {
+				row_counts.insert((row.get::<_, BlockHeight>(0)?, file.clone()), row.get(1)?);
+			}
+		}
+
+		db.execute_batch(&format!(
+			"CREATE TABLE {MANIFEST_FILE} (block_height UBIGINT NOT NULL, file VARCHAR NOT NULL, \
+			 row_count UBIGINT NOT NULL, hash VARCHAR NOT NULL, parent_hash VARCHAR NOT NULL);"
+		))?;
+		for (block_height, file, row_count, block_hashes) in entries.iter() {
+			let compacted_count =
+				row_counts.get(&(*block_height, file.clone())).copied().unwrap_or(0);
+			if compacted_count != *row_count {
+				bail!(
+					"Block {block_height} has {row_count} rows in {file}, compacted \
+					 {compacted_count}"
+				);
+			}
+
+			db.execute(
+				&format!("INSERT INTO {MANIFEST_FILE} VALUES (?, ?, ?, ?, ?)"),
+				params![block_height, file, row_count, block_hashes.hash, block_hashes.parent_hash],
+			)?;
+		}
+		db.execute_batch(&format!(
+			"COPY {MANIFEST_FILE} TO '{path}/{MANIFEST_FILE}.parquet' (FORMAT PARQUET, \
+			 COMPRESSION GZIP);"
+		))?;
+
+		let range = self.read_compacted_range(network_id, range_start)?;
+		let block_heights = range.blocks.keys().copied().collect::<Vec<_>>();
+		self.compacted.write().unwrap().insert((network_id, range_start), Arc::new(range));
+
+		// local block folders aren't needed anymore, unless delta logs point at
+		// them (there's no deleting through duckdb on s3, so they stay there)
+		if let Some(storage_path) = &self.settings.storage_path {
+			if self.settings.table_format != TableFormat::Delta {
+				for block_height in block_heights.iter() {
+					fs::remove_dir_all(
+						storage_path
+							.join(format!("network_id={network_id}"))
+							.join(format!("block_height={block_height}")),
+					)
+					.ok();
+				}
+			}
+		}
+
+		Ok(block_heights.len())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_get_range_start() {
+		assert_eq!(Storage::get_range_start(0), 0);
+		assert_eq!(Storage::get_range_start(999), 0);
+		assert_eq!(Storage::get_range_start(1_000), 1_000);
+		assert_eq!(Storage::get_range_start(123_456), 123_000);
+	}
+}
@@ -0,0 +1,335 @@
+use eyre::{bail, Result};
+use serde_json::{json, Value};
+use std::{
+	collections::{BTreeMap, HashMap, HashSet},
+	fs::{self, OpenOptions},
+	io::{ErrorKind, Write},
+	sync::Mutex,
+};
+use uuid::Uuid;
+
+use super::Storage;
+use crate::{models::PrimaryId, utils, BlockHeight};
+
+// folder (next to extracted blocks) with a delta table per network and file
+pub const DELTA_FOLDER: &str = "_delta";
+
+// how many times a commit is retried when another writer took its version
+const MAX_COMMIT_ATTEMPTS: usize = 5;
+
+// a stored parquet file, as delta's `add` action sees it
+#[derive(Debug, Clone)]
+pub struct DataFile {
+	pub path: String,
+	pub block_height: BlockHeight,
+	pub size: u64,
+	pub num_records: u64,
+	pub columns: Vec<(String, String)>,
+}
+
+#[derive(Debug, Default)]
+struct TableState {
+	// latest committed version
+	version: Option<u64>,
+	// columns of the latest `metaData` action written by this process
+	columns: Vec<(String, String)>,
+}
+
+// @NOTE keeps a delta lake transaction log for each network and file (eg:
+// `_delta/network_id=1/transactions/_delta_log/`), pointing at parquet files
+// where they're extracted to already (delta allows absolute paths), with
+// `block_height` as the partition. files committed since the last round go
+// into a single commit per table, and columns that show up later are added
+// to the table's schema
+pub struct DeltaLog {
+	pending: Mutex<HashMap<(PrimaryId, String), Vec<DataFile>>>,
+	tables: Mutex<HashMap<(PrimaryId, String), TableState>>,
+}
+
+impl DeltaLog {
+	pub fn new() -> Self {
+		Self { pending: Mutex::new(HashMap::new()), tables: Mutex::new(HashMap::new()) }
+	}
+
+	pub fn track(&self, network_id: PrimaryId, file: &str, data_file: DataFile) {
+		self.pending
+			.lock()
+			.unwrap()
+			.entry((network_id, file.to_string()))
+			.or_default()
+			.push(data_file);
+	}
+
+	pub async fn commit_pending(&self, storage: &Storage) -> Result<()> {
+		let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+
+		let mut pending = pending.into_iter();
+		while let Some((key, data_files)) = pending.next() {
+			if let Err(e) = self.commit(storage, &key, &data_files).await {
+				let mut queue = self.pending.lock().unwrap();
+				queue.entry(key).or_default().extend(data_files);
+				queue.extend(pending);
+
+				return Err(e);
+			}
+		}
+
+		Ok(())
+	}
+
+	async fn commit(
+		&self,
+		storage: &Storage,
+		key: &(PrimaryId, String),
+		data_files: &[DataFile],
+	) -> Result<()> {
+		let (network_id, file) = key;
+		let Some(root) = storage.get_root() else {
+			return Ok(());
+		};
+		let table_path = format!("{DELTA_FOLDER}/network_id={network_id}/{file}");
+
+		for _ in 0..MAX_COMMIT_ATTEMPTS {
+			let (version, columns) = {
+				let tables = self.tables.lock().unwrap();
+				let state = tables.get(key);
+				(
+					state.and_then(|s| s.version),
+					state.map(|s| s.columns.clone()).unwrap_or_default(),
+				)
+			};
+			let version = match version {
+				Some(version) => Some(version),
+				None => get_latest_version(storage, &format!("{root}/{table_path}"))?,
+			};
+			let next_version = version.map_or(0, |v| v + 1);
+
+			let merged_columns = merge_columns(&columns, data_files);
+			let actions = get_actions(
+				&format!("{root}/{table_path}"),
+				next_version,
+				(merged_columns != columns).then_some(&merged_columns),
+				data_files,
+			);
+			let body = actions.iter().map(|a| format!("{a}\n")).collect::<String>().into_bytes();
+
+			let log_file = format!("{table_path}/_delta_log/{next_version:020}.json");
+			let is_written = if let Some(storage_path) = &storage.settings.storage_path {
+				let path = storage_path.join(&log_file);
+				if let Some(parent) = path.parent() {
+					fs::create_dir_all(parent)?;
+				}
+
+				match OpenOptions::new().write(true).create_new(true).open(&path) {
+					Ok(mut f) => {
+						f.write_all(&body)?;
+						true
+					}
+					Err(e) if e.kind() == ErrorKind::AlreadyExists => false,
+					Err(e) => return Err(e.into()),
+				}
+			} else if let Some(storage_url) = &storage.settings.storage_url {
+				let Some(credentials) = storage.get_credentials() else {
+					bail!("No credentials to write the delta log with");
+				};
+
+				storage_url.put_if_absent(&credentials, &log_file, body).await?
+			} else {
+				return Ok(());
+			};
+
+			let mut tables = self.tables.lock().unwrap();
+			let state = tables.entry(key.clone()).or_default();
+			if is_written {
+				state.version = Some(next_version);
+				state.columns = merged_columns;
+				return Ok(());
+			}
+
+			// another writer got there first, so look up the latest version again
+			state.version = None;
+		}
+
+		bail!("Could not commit to the delta log of {table_path}")
+	}
+}
+
+impl Default for DeltaLog {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+// highest version in a table's `_delta_log`, if there are any
+fn get_latest_version(storage: &Storage, table_path: &str) -> Result<Option<u64>> {
+	let db = storage.get_db()?;
+	let mut statement =
+		db.prepare(&format!("SELECT file FROM glob('{table_path}/_delta_log/*.json')"))?;
+	let files =
+		statement.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?;
+
+	Ok(files
+		.iter()
+		.filter_map(|f| f.rsplit('/').next()?.strip_suffix(".json")?.parse::<u64>().ok())
+		.max())
+}
+
+// columns of the table so far, followed by any new ones from `data_files`
+fn merge_columns(columns: &[(String, String)], data_files: &[DataFile]) -> Vec<(String, String)> {
+	let mut ret = columns.to_vec();
+	let mut names = ret.iter().map(|(name, _)| name.clone()).collect::<HashSet<_>>();
+
+	for column in data_files.iter().flat_map(|f| f.columns.iter()) {
+		if names.insert(column.0.clone()) {
+			ret.push(column.clone());
+		}
+	}
+
+	ret
+}
+
+// @NOTE one json action per line: `protocol` when the table is created,
+// `metaData` when the schema is new to this process (it's not kept across
+// restarts, and the table id stays the same), then an `add` per file. a block
+// that's extracted again is added again, replacing the earlier entry
+fn get_actions(
+	table_path: &str,
+	version: u64,
+	columns: Option<&Vec<(String, String)>>,
+	data_files: &[DataFile],
+) -> Vec<Value> {
+	let now = utils::now().and_utc().timestamp_millis();
+	let mut ret = vec![json!({
+		"commitInfo": {
+			"timestamp": now,
+			"operation": "WRITE",
+			"operationParameters": { "mode": "Append", "partitionBy": "[\"block_height\"]" },
+			"engineInfo": format!("barreleye/{}", env!("CARGO_PKG_VERSION")),
+		}
+	})];
+
+	if version == 0 {
+		ret.push(json!({ "protocol": { "minReaderVersion": 1, "minWriterVersion": 2 } }));
+	}
+
+	if let Some(columns) = columns {
+		let mut fields = columns
+			.iter()
+			.map(|(name, data_type)| {
+				json!({
+					"name": name,
+					"type": get_delta_type(data_type),
+					"nullable": true,
+					"metadata": {},
+				})
+			})
+			.collect::<Vec<_>>();
+		fields.push(
+			json!({ "name": "block_height", "type": "long", "nullable": false, "metadata": {} }),
+		);
+
+		ret.push(json!({
+			"metaData": {
+				"id": Uuid::new_v5(&Uuid::NAMESPACE_URL, table_path.as_bytes()).to_string(),
+				"format": { "provider": "parquet", "options": {} },
+				"schemaString": json!({ "type": "struct", "fields": fields }).to_string(),
+				"partitionColumns": ["block_height"],
+				"configuration": {},
+				"createdTime": now,
+			}
+		}));
+	}
+
+	let data_files = data_files.iter().map(|f| (f.path.clone(), f)).collect::<BTreeMap<_, _>>();
+	for data_file in data_files.values() {
+		let path = if data_file.path.starts_with("s3://") {
+			data_file.path.clone()
+		} else {
+			format!("file://{}", data_file.path)
+		};
+
+		ret.push(json!({
+			"add": {
+				"path": path,
+				"partitionValues": { "block_height": data_file.block_height.to_string() },
+				"size": data_file.size,
+				"modificationTime": now,
+				"dataChange": true,
+				"stats": json!({ "numRecords": data_file.num_records }).to_string(),
+			}
+		}));
+	}
+
+	ret
+}
+
+// closest delta (spark) type for a duckdb column type; unsigned 64-bit
+// integers don't fit a `long`, so they're read as decimals
+fn get_delta_type(data_type: &str) -> &'static str {
+	match data_type.to_uppercase().as_str() {
+		"VARCHAR" => "string",
+		"BLOB" => "binary",
+		"BOOLEAN" => "boolean",
+		"TINYINT" => "byte",
+		"SMALLINT" | "UTINYINT" => "short",
+		"INTEGER" | "USMALLINT" => "integer",
+		"BIGINT" | "UINTEGER" => "long",
+		"UBIGINT" => "decimal(20,0)",
+		"FLOAT" => "float",
+		"DOUBLE" => "double",
+		"DATE" => "date",
+		"TIMESTAMP" => "timestamp",
+		_ => "string",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn data_file(path: &str, block_height: BlockHeight, columns: &[&str]) -> DataFile {
+		DataFile {
+			path: path.to_string(),
+			block_height,
+			size: 100,
+			num_records: 2,
+			columns: columns.iter().map(|c| (c.to_string(), "VARCHAR".to_string())).collect(),
+		}
+	}
+
+	#[test]
+	fn test_merge_columns() {
+		let columns = vec![("hash".to_string(), "VARCHAR".to_string())];
+		let data_files =
+			vec![data_file("a", 1, &["hash"]), data_file("b", 2, &["hash", "input", "from"])];
+
+		let names = merge_columns(&columns, &data_files)
+			.into_iter()
+			.map(|(name, _)| name)
+			.collect::<Vec<_>>();
+		assert_eq!(names, vec!["hash", "input", "from"]);
+	}
+
+	#[test]
+	fn test_get_actions() {
+		let data_files = vec![
+			data_file("/storage/network_id=1/block_height=5/logs.parquet", 5, &["data"]),
+			data_file("/storage/network_id=1/block_height=5/logs.parquet", 5, &["data"]),
+		];
+		let columns = merge_columns(&[], &data_files);
+
+		let actions =
+			get_actions("/storage/_delta/network_id=1/logs", 0, Some(&columns), &data_files);
+		assert_eq!(actions.len(), 4);
+		assert!(actions[1].get("protocol").is_some());
+		assert!(actions[2].get("metaData").is_some());
+		assert_eq!(
+			actions[3]["add"]["path"],
+			"file:///storage/network_id=1/block_height=5/logs.parquet"
+		);
+		assert_eq!(actions[3]["add"]["partitionValues"]["block_height"], "5");
+
+		let actions = get_actions("/storage/_delta/network_id=1/logs", 1, None, &data_files);
+		assert_eq!(actions.len(), 2);
+	}
+}
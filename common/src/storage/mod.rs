@@ -1,29 +1,344 @@
-use duckdb::Connection;
-use eyre::Result;
-use std::{fs, sync::Arc};
+use chrono::NaiveDateTime;
+use clap::{builder::PossibleValue, ValueEnum};
+use duckdb::{params, Appender, Config as DuckDbConfig, Connection};
+use eyre::{bail, eyre, Result};
+use std::{
+	collections::{BTreeMap, HashMap},
+	fs,
+	path::PathBuf,
+	sync::{Arc, Mutex, RwLock},
+};
+use tokio::time::{sleep, Duration};
+use tracing::warn;
 
-use crate::{models::PrimaryId, BlockHeight, Settings};
+use self::{
+	catalog::{Catalog, SYMLINKS_FOLDER},
+	compaction::{CompactedBlock, CompactedRange},
+	delta::{DataFile, DeltaLog},
+};
+use crate::{
+	chain::BlockHashes, models::PrimaryId, s3::Credentials, utils, BlockHeight, S3Service, Settings,
+};
 
-pub trait StorageModelTrait {
+// max number of buffered rows before they're appended to in-memory tables
+const MAX_BUFFERED_ROWS: usize = 10_000;
+
+// written after a block's files, marking it as fully extracted
+const MANIFEST_FILE: &str = "manifest";
+
+// temporary credentials are refreshed this long before they expire
+const CREDENTIALS_REFRESH_MARGIN: i64 = 5 * 60;
+
+// how long to wait before trying the provider chain again after a failure
+const CREDENTIALS_RETRY_INTERVAL: u64 = 30;
+
+// how often newly seen files are registered in the glue catalog
+const CATALOG_REGISTRATION_INTERVAL: u64 = 10;
+
+// how often newly written files are committed to delta logs (each round is a
+// new table version)
+const DELTA_COMMIT_INTERVAL: u64 = 60;
+
+pub mod catalog;
+pub mod compaction;
+pub mod delta;
+
+// how extracted files are laid out for other query engines
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TableFormat {
+	// bare parquet files, partitioned by folder
+	Parquet,
+	// parquet files, plus a delta lake log per network and file
+	Delta,
+}
+
+impl ValueEnum for TableFormat {
+	fn value_variants<'a>() -> &'a [Self] {
+		&[Self::Parquet, Self::Delta]
+	}
+
+	fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+		match self {
+			Self::Parquet => Some(PossibleValue::new("parquet")),
+			Self::Delta => Some(PossibleValue::new("delta")),
+		}
+	}
+}
+
+pub trait StorageModelTrait: Send {
+	fn get_table(&self) -> String;
 	fn create_table(&self, db: &Connection) -> Result<()>;
-	fn insert(&self, db: &Connection) -> Result<()>;
+	fn append(&self, appender: &mut Appender) -> Result<()>;
+}
+
+// report as archived: `report` is the rendered json
+#[derive(Debug, Clone)]
+pub struct StoredReport {
+	pub id: String,
+	pub subject: String,
+	pub created_at: u64,
+	pub report: String,
 }
 
 pub struct Storage {
 	settings: Arc<Settings>,
+	credentials: RwLock<Option<Credentials>>,
+	scratch_path: Option<PathBuf>,
+	catalog: Option<Arc<Catalog>>,
+	delta_log: Option<Arc<DeltaLog>>,
+	compacted: RwLock<HashMap<(PrimaryId, BlockHeight), Arc<CompactedRange>>>,
 }
 
 impl Storage {
 	pub fn new(settings: Arc<Settings>) -> Result<Self> {
-		Ok(Self { settings })
+		let catalog =
+			settings.glue_database.is_some().then(|| Arc::new(Catalog::new(settings.clone())));
+		let delta_log =
+			(settings.table_format == TableFormat::Delta).then(|| Arc::new(DeltaLog::new()));
+
+		Ok(Self {
+			settings,
+			credentials: RwLock::new(None),
+			scratch_path: None,
+			catalog,
+			delta_log,
+			compacted: RwLock::new(HashMap::new()),
+		})
+	}
+
+	// local storage under `path` instead of the configured one, for blocks that
+	// are extracted only to be looked at (eg: when verifying)
+	pub fn new_scratch(settings: Arc<Settings>, path: PathBuf) -> Self {
+		Self {
+			settings,
+			credentials: RwLock::new(None),
+			scratch_path: Some(path),
+			catalog: None,
+			delta_log: None,
+			compacted: RwLock::new(HashMap::new()),
+		}
+	}
+
+	// aws storage without static keys uses the standard provider chain
+	pub fn uses_provider_chain(&self) -> bool {
+		self.settings.storage_url.as_ref().is_some_and(|s3| s3.service == S3Service::S3) &&
+			self.settings.s3_access_key_id.is_none()
+	}
+
+	// @NOTE resolves credentials through the provider chain right away (so a
+	// misconfigured role shows up on startup), then keeps temporary ones fresh
+	// in the background, a few minutes ahead of their expiration
+	pub async fn start_credentials_refresh(self: &Arc<Self>) -> Result<()> {
+		if !self.uses_provider_chain() {
+			return Ok(());
+		}
+
+		let mut expires_at = self.refresh_credentials().await?;
+
+		tokio::spawn({
+			let storage = self.clone();
+
+			async move {
+				while let Some(at) = expires_at {
+					let secs = (at - utils::now()).num_seconds() - CREDENTIALS_REFRESH_MARGIN;
+					sleep(Duration::from_secs(secs.max(0) as u64)).await;
+
+					expires_at = match storage.refresh_credentials().await {
+						Ok(expires_at) => expires_at,
+						Err(e) => {
+							warn!(credentials = "not refreshed", error = e.to_string());
+							sleep(Duration::from_secs(CREDENTIALS_RETRY_INTERVAL)).await;
+							Some(at)
+						}
+					};
+				}
+			}
+		});
+
+		Ok(())
+	}
+
+	// registers tables for newly extracted files in the background (see
+	// `Catalog`), retrying on the next round if glue can't be reached
+	pub fn start_catalog_registration(self: &Arc<Self>) {
+		let Some(catalog) = self.catalog.clone() else {
+			return;
+		};
+
+		tokio::spawn({
+			let storage = self.clone();
+
+			async move {
+				loop {
+					sleep(Duration::from_secs(CATALOG_REGISTRATION_INTERVAL)).await;
+
+					let result = match storage.get_credentials() {
+						Some(credentials) => catalog.register_pending(&credentials).await,
+						None => Err(eyre!("no aws credentials")),
+					};
+					if let Err(e) = result {
+						warn!(catalog = "not registered", error = e.to_string());
+					}
+				}
+			}
+		});
+	}
+
+	// commits newly written files to their delta logs in the background,
+	// retrying on the next round if that fails
+	pub fn start_delta_commits(self: &Arc<Self>) {
+		let Some(delta_log) = self.delta_log.clone() else {
+			return;
+		};
+
+		tokio::spawn({
+			let storage = self.clone();
+
+			async move {
+				loop {
+					sleep(Duration::from_secs(DELTA_COMMIT_INTERVAL)).await;
+
+					if let Err(e) = delta_log.commit_pending(&storage).await {
+						warn!(delta_log = "not committed", error = e.to_string());
+					}
+				}
+			}
+		});
+	}
+
+	// static keys if they're set, otherwise whatever the provider chain gave
+	pub fn get_credentials(&self) -> Option<Credentials> {
+		match (&self.settings.s3_access_key_id, &self.settings.s3_secret_access_key) {
+			(Some(access_key_id), Some(secret_access_key)) => Some(Credentials {
+				access_key_id: access_key_id.clone(),
+				secret_access_key: secret_access_key.clone(),
+				session_token: None,
+				expires_at: None,
+			}),
+			_ => self.credentials.read().unwrap().clone(),
+		}
+	}
+
+	async fn refresh_credentials(&self) -> Result<Option<NaiveDateTime>> {
+		let credentials = Credentials::from_provider_chain().await?;
+		let expires_at = credentials.as_ref().and_then(|c| c.expires_at);
+		*self.credentials.write().unwrap() = credentials;
+
+		Ok(expires_at)
 	}
 
 	pub fn get(&self, network_id: PrimaryId, block_height: BlockHeight) -> Result<StorageDb> {
-		Ok(StorageDb::new(self.settings.clone(), self.get_db()?, network_id, block_height))
+		Ok(StorageDb::new(self.settings.clone(), self.get_db()?, network_id, block_height)
+			.with_scratch_path(self.scratch_path.clone())
+			.with_catalog(self.catalog.clone())
+			.with_delta_log(self.delta_log.clone())
+			.with_compacted(self.get_compacted(network_id, block_height)?))
+	}
+
+	// folder or s3 bucket that everything is stored under
+	pub fn get_root(&self) -> Option<String> {
+		if let Some(storage_path) = &self.settings.storage_path {
+			Some(storage_path.display().to_string())
+		} else {
+			self.settings
+				.storage_url
+				.as_ref()
+				.and_then(|s3| s3.bucket.as_ref())
+				.map(|bucket| format!("s3://{bucket}"))
+		}
+	}
+
+	// all extracted files, relative to the root (eg:
+	// `network_id=1/block_height=100/transactions.parquet`), compacted ones
+	// included
+	pub fn list_files(&self) -> Result<Vec<String>> {
+		let Some(root) = self.get_root() else {
+			return Ok(vec![]);
+		};
+
+		let db = self.get_db()?;
+		let mut statement = db.prepare(&format!(
+			"SELECT file FROM glob('{root}/network_id=*/block_*=*/*.parquet') ORDER BY file"
+		))?;
+		let files = statement
+			.query_map([], |row| row.get::<_, String>(0))?
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let prefix = format!("{root}/");
+		Ok(files
+			.into_iter()
+			.filter_map(|f| f.strip_prefix(&prefix).map(|f| f.to_string()))
+			.collect())
+	}
+
+	// @NOTE reports are archived next to extracted files, as single-row parquet
+	// files under `reports/` (so they go wherever extracted data goes, including
+	// s3). `id` is expected to be a valid report id
+	pub fn put_report(&self, report: &StoredReport) -> Result<()> {
+		let Some(root) = self.get_root() else {
+			bail!("Reports require storage (`--storage-path` or `--storage-url`)");
+		};
+
+		if let Some(storage_path) = &self.settings.storage_path {
+			fs::create_dir_all(storage_path.join("reports"))?;
+		}
+
+		let db = self.get_db()?;
+		db.execute(
+			"CREATE TABLE report AS SELECT ?::VARCHAR AS id, ?::VARCHAR AS subject, ?::UBIGINT \
+			 AS created_at, ?::VARCHAR AS report",
+			params![report.id, report.subject, report.created_at, report.report],
+		)?;
+		db.execute_batch(&format!(
+			"COPY report TO '{root}/reports/{}.parquet' (FORMAT PARQUET, COMPRESSION GZIP);",
+			report.id
+		))?;
+
+		Ok(())
+	}
+
+	pub fn get_report(&self, id: &str) -> Result<Option<StoredReport>> {
+		Ok(self.select_reports(&format!("{id}.parquet"))?.into_iter().next())
+	}
+
+	// all archived reports, latest first
+	pub fn list_reports(&self) -> Result<Vec<StoredReport>> {
+		self.select_reports("*.parquet")
+	}
+
+	fn select_reports(&self, pattern: &str) -> Result<Vec<StoredReport>> {
+		let Some(root) = self.get_root() else {
+			return Ok(vec![]);
+		};
+
+		// reading a missing file errors out, so check with a glob first
+		let db = self.get_db()?;
+		let path = format!("{root}/reports/{pattern}");
+		let mut statement = db.prepare(&format!("SELECT count(*) FROM glob('{path}')"))?;
+		if statement.query_row([], |row| row.get::<_, u64>(0))? == 0 {
+			return Ok(vec![]);
+		}
+
+		let mut statement = db.prepare(&format!(
+			"SELECT id, subject, created_at, report FROM read_parquet('{path}') ORDER BY \
+			 created_at DESC"
+		))?;
+		let reports = statement
+			.query_map([], |row| {
+				Ok(StoredReport {
+					id: row.get(0)?,
+					subject: row.get(1)?,
+					created_at: row.get(2)?,
+					report: row.get(3)?,
+				})
+			})?
+			.collect::<Result<Vec<_>, _>>()?;
+
+		Ok(reports)
 	}
 
 	fn get_db(&self) -> Result<Connection> {
-		let db = Connection::open_in_memory()?;
+		let db = Connection::open_in_memory_with_flags(get_duckdb_config(&self.settings)?)?;
 
 		if self.settings.storage_url.is_some() {
 			self.set_credentials(&db)?;
@@ -32,37 +347,76 @@ impl Storage {
 		Ok(db)
 	}
 
+	// @NOTE a single s3 secret covers everything httpfs needs: where requests
+	// go (`ENDPOINT`, `URL_STYLE` and `USE_SSL` for self-hosted services like
+	// minio), credentials (static or from the provider chain), and the kms key
+	// uploads are encrypted with
 	fn set_credentials(&self, db: &Connection) -> Result<()> {
 		if let Some(s3) = self.settings.storage_url.clone() {
-			let mut commands = vec![];
+			let mut options = vec!["TYPE S3".to_string()];
 
 			if let Some(region) = s3.region {
-				commands.push(format!("SET s3_region='{region}';"));
-			} else if let Some(domain) = s3.domain {
-				commands.push(format!("SET s3_endpoint='{domain}';"));
+				options.push(format!("REGION '{region}'"));
 			}
+			if let Some(endpoint) = s3.endpoint {
+				options.push(format!("ENDPOINT '{endpoint}'"));
 
-			if let Some(s3_access_key_id) = self.settings.s3_access_key_id.clone() {
-				commands.push(format!("SET s3_access_key_id='{}';", s3_access_key_id));
+				if !s3.use_ssl {
+					options.push("USE_SSL false".to_string());
+				}
+			}
+			if s3.path_style {
+				options.push("URL_STYLE 'path'".to_string());
 			}
-			if let Some(s3_secret_access_key) = self.settings.s3_secret_access_key.clone() {
-				commands.push(format!("SET s3_secret_access_key='{}';", s3_secret_access_key));
+
+			if let Some(credentials) = self.get_credentials() {
+				options.push(format!("KEY_ID '{}'", credentials.access_key_id));
+				options.push(format!("SECRET '{}'", credentials.secret_access_key));
+				if let Some(session_token) = credentials.session_token {
+					options.push(format!("SESSION_TOKEN '{session_token}'"));
+				}
 			}
 
-			if !commands.is_empty() {
-				db.execute_batch(&commands.join(""))?;
+			if let Some(sse_kms_key_id) = s3.sse_kms_key_id {
+				options.push(format!("KMS_KEY_ID '{sse_kms_key_id}'"));
 			}
+
+			db.execute_batch(&format!("CREATE SECRET storage ({});", options.join(", ")))?;
 		}
 
 		Ok(())
 	}
 }
 
+// @NOTE resource limits shared by every duckdb connection, so big `COPY`s
+// spill to disk instead of getting the process oom-killed on small machines
+pub fn get_duckdb_config(settings: &Settings) -> Result<DuckDbConfig> {
+	let mut config = DuckDbConfig::default();
+
+	if let Some(memory_limit) = &settings.duckdb_memory_limit {
+		config = config.max_memory(memory_limit)?;
+	}
+	if let Some(threads) = settings.duckdb_threads {
+		config = config.threads(threads as i64)?;
+	}
+
+	let temp_directory =
+		settings.duckdb_temp_directory.clone().unwrap_or_else(|| utils::project_dir(Some("tmp")));
+	config = config.with("temp_directory", temp_directory.display().to_string())?;
+
+	Ok(config)
+}
+
 pub struct StorageDb {
 	settings: Arc<Settings>,
 	pub db: Connection,
 	network_id: PrimaryId,
 	block_height: BlockHeight,
+	buffer: Mutex<HashMap<String, Vec<Box<dyn StorageModelTrait>>>>,
+	scratch_path: Option<PathBuf>,
+	catalog: Option<Arc<Catalog>>,
+	delta_log: Option<Arc<DeltaLog>>,
+	compacted: Option<CompactedBlock>,
 }
 
 impl StorageDb {
@@ -72,25 +426,114 @@ impl StorageDb {
 		network_id: PrimaryId,
 		block_height: BlockHeight,
 	) -> Self {
-		Self { settings, db, network_id, block_height }
+		Self {
+			settings,
+			db,
+			network_id,
+			block_height,
+			buffer: Mutex::new(HashMap::new()),
+			scratch_path: None,
+			catalog: None,
+			delta_log: None,
+			compacted: None,
+		}
+	}
+
+	pub fn with_scratch_path(mut self, scratch_path: Option<PathBuf>) -> Self {
+		self.scratch_path = scratch_path;
+		self
+	}
+
+	pub fn with_catalog(mut self, catalog: Option<Arc<Catalog>>) -> Self {
+		self.catalog = catalog;
+		self
+	}
+
+	pub fn with_delta_log(mut self, delta_log: Option<Arc<DeltaLog>>) -> Self {
+		self.delta_log = delta_log;
+		self
+	}
+
+	pub fn with_compacted(mut self, compacted: Option<CompactedBlock>) -> Self {
+		self.compacted = compacted;
+		self
+	}
+
+	// @NOTE what to select a stored file's rows from (eg: `SELECT * FROM
+	// {source}`): the block's own file, or its rows in a compacted range.
+	// `None` if the block doesn't have the file
+	pub fn get_source(&self, file: &str) -> Result<Option<String>> {
+		if let Some(compacted) = &self.compacted {
+			return Ok(compacted.row_counts.contains_key(file).then(|| {
+				format!(
+					"(SELECT * EXCLUDE (block_height) FROM read_parquet('{}/{file}.parquet') 					 WHERE block_height = {})",
+					compacted.path, self.block_height
+				)
+			}));
+		}
+
+		if !self.has_file(file)? {
+			return Ok(None);
+		}
+
+		Ok(self.get_path(file)?.map(|path| format!("read_parquet('{path}')")))
 	}
 
 	pub fn insert<T>(&self, model: T) -> Result<()>
 	where
-		T: StorageModelTrait,
+		T: StorageModelTrait + 'static,
 	{
-		model.insert(&self.db)
+		let is_full = {
+			let mut buffer = self.buffer.lock().unwrap();
+			let rows = buffer.entry(model.get_table()).or_default();
+			rows.push(Box::new(model));
+			rows.len() >= MAX_BUFFERED_ROWS
+		};
+
+		if is_full {
+			self.flush()?;
+		}
+
+		Ok(())
+	}
+
+	// appends buffered rows in bulk (much cheaper than an insert per row)
+	pub fn flush(&self) -> Result<()> {
+		let buffer = std::mem::take(&mut *self.buffer.lock().unwrap());
+
+		for (table, rows) in buffer.into_iter() {
+			if let Some(row) = rows.first() {
+				row.create_table(&self.db)?;
+
+				let mut appender = self.db.appender(&table)?;
+				for row in rows.iter() {
+					row.append(&mut appender)?;
+				}
+				appender.flush()?;
+			}
+		}
+
+		Ok(())
 	}
 
 	pub fn commit(&self, files: Vec<String>) -> Result<()> {
+		self.flush()?;
+
 		let mut commands = vec![];
+		let mut written = vec![];
 
 		for file in files.into_iter() {
+			// nothing was inserted (eg: a block without logs), so there's no file
+			if !self.has_table(&file)? {
+				continue;
+			}
+
 			if let Some(path) = self.get_path(&file)? {
 				commands
 					.push(
 						format!("COPY {file} TO '{}' (FORMAT PARQUET, COMPRESSION GZIP);", path,),
 					);
+				written.push((file, path));
 			}
 		}
 
@@ -98,13 +541,242 @@ impl StorageDb {
 			self.db.execute_batch(&commands.join(""))?;
 		}
 
+		if self.scratch_path.is_none() {
+			for (file, path) in written.into_iter() {
+				if self.catalog.is_some() {
+					self.register_file(&file, &path)?;
+				}
+				if self.delta_log.is_some() {
+					self.add_to_delta_log(&file, &path)?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	// queues a written file for the next commit to its delta log
+	fn add_to_delta_log(&self, file: &str, path: &str) -> Result<()> {
+		let Some(delta_log) = &self.delta_log else {
+			return Ok(());
+		};
+
+		let size = if self.settings.storage_path.is_some() {
+			fs::metadata(path)?.len()
+		} else {
+			// only the size is selected, so the file isn't downloaded
+			self.db.query_row(&format!("SELECT size FROM read_blob('{path}')"), [], |row| {
+				row.get::<_, u64>(0)
+			})?
+		};
+		let num_records =
+			self.db.query_row(&format!("SELECT count(*) FROM {file}"), [], |row| row.get(0))?;
+
+		delta_log.track(
+			self.network_id,
+			file,
+			DataFile {
+				path: path.to_string(),
+				block_height: self.block_height,
+				size,
+				num_records,
+				columns: self.describe(file)?,
+			},
+		);
+
+		Ok(())
+	}
+
+	// @NOTE points the file's glue table at the parquet file that was just
+	// written, with a symlink file (a text file listing the actual path) in the
+	// block's partition of that table
+	fn register_file(&self, file: &str, path: &str) -> Result<()> {
+		let (Some(catalog), Some(storage_url)) = (&self.catalog, &self.settings.storage_url) else {
+			return Ok(());
+		};
+
+		let symlink_path = format!(
+			"s3://{}/{SYMLINKS_FOLDER}/network_id={}/{file}/block_height={}/symlink.txt",
+			storage_url.bucket.as_ref().unwrap(),
+			self.network_id,
+			self.block_height,
+		);
+		self.db.execute_batch(&format!(
+			"COPY (SELECT '{path}') TO '{symlink_path}' (FORMAT CSV, HEADER false, QUOTE '');"
+		))?;
+
+		if !catalog.is_tracked(self.network_id, file) {
+			catalog.track(self.network_id, file, self.describe(file)?);
+		}
+
+		Ok(())
+	}
+
+	// names and types of a table's columns
+	fn describe(&self, table: &str) -> Result<Vec<(String, String)>> {
+		let mut statement = self.db.prepare(&format!("DESCRIBE {table}"))?;
+		let columns = statement
+			.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+			.collect::<Result<Vec<_>, _>>()?;
+
+		Ok(columns)
+	}
+
+	// @NOTE the manifest lists every file of an extracted block with its row
+	// count (and the block's hashes, so they don't have to be fetched again). it's
+	// only written once all files are, so a worker that died halfway through a
+	// block leaves no manifest behind
+	pub fn save_manifest(&self, files: &[String], block_hashes: &BlockHashes) -> Result<()> {
+		let Some(path) = self.get_path(MANIFEST_FILE)? else {
+			return Ok(());
+		};
+
+		self.create_manifest_table()?;
+		for file in files.iter() {
+			if !self.has_table(file)? {
+				continue;
+			}
+
+			self.db.execute(
+				&format!("INSERT INTO {MANIFEST_FILE} SELECT ?, count(*), ?, ? FROM {file}"),
+				params![file, block_hashes.hash, block_hashes.parent_hash],
+			)?;
+		}
+
+		self.db.execute_batch(&format!(
+			"COPY {MANIFEST_FILE} TO '{path}' (FORMAT PARQUET, COMPRESSION GZIP);"
+		))?;
+
+		Ok(())
+	}
+
+	// @NOTE hashes of the block if it's been fully extracted before: the manifest
+	// exists and every file it lists is readable and has as many rows as were
+	// written. anything else (including errors) means the block has to be
+	// extracted again
+	pub fn get_manifest(&self) -> Result<Option<BlockHashes>> {
+		if let Some(compacted) = &self.compacted {
+			return Ok(Some(compacted.block_hashes.clone()));
+		}
+
+		let mut ret = None;
+
+		for (file, row_count, block_hashes) in self.read_manifest()?.into_iter() {
+			if self.count_rows(&file)? != Some(row_count) {
+				return Ok(None);
+			}
+
+			ret = Some(block_hashes);
+		}
+
+		Ok(ret)
+	}
+
+	// rows per file as recorded in the manifest when the block was extracted
+	pub fn get_row_counts(&self) -> Result<BTreeMap<String, u64>> {
+		if let Some(compacted) = &self.compacted {
+			return Ok(compacted.row_counts.clone());
+		}
+
+		Ok(self
+			.read_manifest()?
+			.into_iter()
+			.map(|(file, row_count, _)| (file, row_count))
+			.collect())
+	}
+
+	// rows in a stored file, or `None` if it's missing or unreadable
+	pub fn count_rows(&self, file: &str) -> Result<Option<u64>> {
+		let source = match &self.compacted {
+			Some(_) => self.get_source(file)?,
+			None => self.get_path(file)?.map(|path| format!("read_parquet('{path}')")),
+		};
+		let Some(source) = source else {
+			return Ok(None);
+		};
+
+		Ok(self
+			.db
+			.prepare(&format!("SELECT count(*) FROM {source}"))
+			.and_then(|mut s| s.query_row([], |row| row.get::<_, u64>(0)))
+			.ok())
+	}
+
+	fn read_manifest(&self) -> Result<Vec<(String, u64, BlockHashes)>> {
+		let Some(path) = self.get_path(MANIFEST_FILE)? else {
+			return Ok(vec![]);
+		};
+
+		// reading a missing file errors out, so check first
+		if !self.has_file(MANIFEST_FILE)? {
+			return Ok(vec![]);
+		}
+
+		let mut statement = self.db.prepare(&format!(
+			"SELECT file, row_count, hash, parent_hash FROM read_parquet('{path}')"
+		))?;
+		let entries = statement
+			.query_map([], |row| {
+				Ok((
+					row.get::<_, String>(0)?,
+					row.get::<_, u64>(1)?,
+					BlockHashes { hash: row.get(2)?, parent_hash: row.get(3)? },
+				))
+			})?
+			.collect::<Result<Vec<_>, _>>()?;
+
+		Ok(entries)
+	}
+
+	// overwrites the manifest with an empty one (there's no deleting through
+	// duckdb on s3), so the block is extracted again next time
+	pub fn invalidate_manifest(&self) -> Result<()> {
+		if let Some(path) = self.get_path(MANIFEST_FILE)? {
+			self.create_manifest_table()?;
+			self.db.execute_batch(&format!(
+				"DELETE FROM {MANIFEST_FILE}; COPY {MANIFEST_FILE} TO '{path}' (FORMAT PARQUET, \
+				 COMPRESSION GZIP);"
+			))?;
+		}
+
+		Ok(())
+	}
+
+	// whether this block has a stored `file`
+	pub fn has_file(&self, file: &str) -> Result<bool> {
+		if let Some(compacted) = &self.compacted {
+			return Ok(compacted.row_counts.contains_key(file));
+		}
+
+		let Some(path) = self.get_path(file)? else {
+			return Ok(false);
+		};
+
+		let mut statement = self.db.prepare(&format!("SELECT count(*) FROM glob('{path}')"))?;
+		Ok(statement.query_row([], |row| row.get::<_, u64>(0))? > 0)
+	}
+
+	fn has_table(&self, table: &str) -> Result<bool> {
+		let mut statement = self
+			.db
+			.prepare("SELECT count(*) FROM information_schema.tables WHERE table_name = ?")?;
+		Ok(statement.query_row(params![table], |row| row.get::<_, u64>(0))? > 0)
+	}
+
+	fn create_manifest_table(&self) -> Result<()> {
+		self.db.execute_batch(&format!(
+			"CREATE TABLE IF NOT EXISTS {MANIFEST_FILE} (file VARCHAR NOT NULL, row_count UBIGINT \
+			 NOT NULL, hash VARCHAR NOT NULL, parent_hash VARCHAR NOT NULL);"
+		))?;
+
 		Ok(())
 	}
 
 	pub fn get_path(&self, file: &str) -> Result<Option<String>> {
 		let mut ret = None;
 
-		if let Some(storage_path) = &self.settings.storage_path {
+		let storage_path = self.scratch_path.as_ref().or(self.settings.storage_path.as_ref());
+		if let Some(storage_path) = storage_path {
 			let absolute_path = storage_path
 				.join(format!("network_id={}", self.network_id))
 				.join(format!("block_height={}", self.block_height));
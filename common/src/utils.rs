@@ -1,13 +1,16 @@
-use chrono::{offset::Utc, Duration, NaiveDateTime};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{offset::Utc, Duration, NaiveDate, NaiveDateTime};
 use directories::ProjectDirs;
 use governor::Quota;
+use hmac::{Hmac, Mac};
 use nanoid::nanoid;
+use serde::{de::DeserializeOwned, Serialize};
 use sha2::{Digest, Sha256};
 use std::{num::NonZeroU32, path::PathBuf, sync::Arc};
 use url::Url;
 use uuid::Uuid;
 
-use crate::{GovernorRateLimiter, IdPrefix, RateLimiter};
+use crate::{chain::U256, GovernorRateLimiter, IdPrefix, RateLimiter};
 
 pub fn sha256(input: &str) -> Vec<u8> {
 	let mut hasher = Sha256::new();
@@ -15,6 +18,12 @@ pub fn sha256(input: &str) -> Vec<u8> {
 	hasher.finalize().to_vec()
 }
 
+pub fn hmac_sha256(key: &str, input: &str) -> Vec<u8> {
+	let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("any key size works");
+	mac.update(input.as_bytes());
+	mac.finalize().into_bytes().to_vec()
+}
+
 pub fn project_dir(folder: Option<&str>) -> PathBuf {
 	// @TODO will panic on systems with no home directory
 	ProjectDirs::from("org", "barreleye", "barreleye")
@@ -55,6 +64,11 @@ pub fn new_uuid() -> uuid::Uuid {
 	Uuid::new_v4()
 }
 
+// same name always gives the same uuid
+pub fn new_deterministic_uuid(name: &str) -> uuid::Uuid {
+	Uuid::new_v5(&Uuid::NAMESPACE_OID, name.as_bytes())
+}
+
 pub fn now() -> NaiveDateTime {
 	Utc::now().naive_utc()
 }
@@ -63,6 +77,20 @@ pub fn ago_in_seconds(secs: u64) -> NaiveDateTime {
 	now() - Duration::try_seconds(secs as i64).unwrap()
 }
 
+pub fn in_seconds(secs: u64) -> NaiveDateTime {
+	now() + Duration::try_seconds(secs as i64).unwrap()
+}
+
+// warehouse dates are stored as days since unix epoch
+pub fn date_from_days(days: u16) -> NaiveDate {
+	NaiveDate::default() + Duration::try_days(days as i64).unwrap()
+}
+
+// dates in api params, eg: `2024-01-31`
+pub fn parse_date(date: &str) -> Option<NaiveDate> {
+	NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
 pub fn with_masked_auth(url: &str) -> String {
 	match Url::parse(url) {
 		Ok(mut parsed_url) => {
@@ -108,6 +136,73 @@ pub fn has_pathname(url: &str) -> bool {
 	}
 }
 
+// human-readable decimal string of a raw amount (eg: `1500000` with 6
+// decimals is `1.5`)
+pub fn format_amount(amount: U256, decimals: u16) -> String {
+	let digits = amount.to_string();
+	let decimals = decimals as usize;
+	if decimals == 0 {
+		return digits;
+	}
+
+	let digits = format!("{digits:0>width$}", width = decimals + 1);
+	let (whole, fraction) = digits.split_at(digits.len() - decimals);
+	let fraction = fraction.trim_end_matches('0');
+
+	if fraction.is_empty() {
+		whole.to_string()
+	} else {
+		format!("{whole}.{fraction}")
+	}
+}
+
+// raw amount from a human-readable decimal string (inverse of
+// `format_amount`)
+pub fn parse_amount(amount: &str, decimals: u16) -> Option<U256> {
+	let (whole, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+	let decimals = decimals as usize;
+
+	let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+	if whole.is_empty() || fraction.len() > decimals || !is_digits(whole) || !is_digits(fraction) {
+		return None;
+	}
+
+	U256::from_dec_str(&format!("{whole}{fraction:0<decimals$}")).ok()
+}
+
+// opaque pagination cursor holding the sort keys of the last returned row
+pub fn encode_cursor<T: Serialize>(keys: &T) -> String {
+	general_purpose::URL_SAFE_NO_PAD.encode(serde_json::to_vec(keys).unwrap_or_default())
+}
+
+pub fn decode_cursor<T: DeserializeOwned>(cursor: &str) -> Option<T> {
+	let bytes = general_purpose::URL_SAFE_NO_PAD.decode(cursor).ok()?;
+	serde_json::from_slice(&bytes).ok()
+}
+
+// @NOTE escapes a value for a single-quoted warehouse string. doubled quotes
+// work on both drivers, and backslashes are escaped since clickhouse would
+// otherwise treat them as escapes (duckdb has none)
+pub fn escape_sql_string(value: &str) -> String {
+	value.replace('\\', "\\\\").replace('\'', "''")
+}
+
+// `column IN (..)` filters of at most `chunk_size` values each, so long lists
+// are split across statements
+pub fn get_in_filters(column: &str, values: &[String], chunk_size: usize) -> Vec<String> {
+	values
+		.chunks(chunk_size.max(1))
+		.map(|chunk| {
+			let values = chunk
+				.iter()
+				.map(|v| format!("'{}'", escape_sql_string(v)))
+				.collect::<Vec<_>>()
+				.join(",");
+			format!("{column} IN ({values})")
+		})
+		.collect()
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -157,4 +252,77 @@ mod tests {
 			assert_eq!(get_db_path(&from), path.to_string())
 		}
 	}
+
+	#[test]
+	fn test_format_amount() {
+		let data = vec![
+			((0, 0), "0"),
+			((0, 18), "0"),
+			((123, 0), "123"),
+			((1_500_000, 6), "1.5"),
+			((1_000_000, 6), "1"),
+			((1, 8), "0.00000001"),
+			((123_456_789, 2), "1234567.89"),
+		];
+
+		for ((amount, decimals), formatted) in data.into_iter() {
+			assert_eq!(format_amount(U256::from(amount as u64), decimals), formatted)
+		}
+	}
+
+	#[test]
+	fn test_parse_amount() {
+		let data = vec![
+			(("0", 0), Some(0)),
+			(("123", 0), Some(123)),
+			(("1.5", 6), Some(1_500_000)),
+			(("1", 6), Some(1_000_000)),
+			(("0.00000001", 8), Some(1)),
+			(("1.5", 0), None),
+			(("1.", 2), Some(100)),
+			((".5", 2), None),
+			(("abc", 2), None),
+			(("-1", 2), None),
+		];
+
+		for ((amount, decimals), parsed) in data.into_iter() {
+			assert_eq!(parse_amount(amount, decimals), parsed.map(|v: u64| U256::from(v)))
+		}
+	}
+
+	#[test]
+	fn test_escape_sql_string() {
+		assert_eq!(escape_sql_string("0xabc"), "0xabc");
+		assert_eq!(escape_sql_string("a' OR 1=1 --"), "a'' OR 1=1 --");
+		assert_eq!(escape_sql_string("a\\' OR 1=1 --"), "a\\\\'' OR 1=1 --");
+	}
+
+	#[test]
+	fn test_hmac_sha256() {
+		// rfc 4231, test case 2
+		assert_eq!(
+			hex::encode(hmac_sha256("Jefe", "what do ya want for nothing?")),
+			"5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+		);
+	}
+
+	#[test]
+	fn test_get_in_filters() {
+		let values = vec!["a".to_string(), "b'".to_string(), "c".to_string()];
+		assert_eq!(
+			get_in_filters("address", &values, 2),
+			vec!["address IN ('a','b''')".to_string(), "address IN ('c')".to_string()]
+		);
+		assert!(get_in_filters("address", &[], 2).is_empty());
+	}
+
+	#[test]
+	fn test_cursor() {
+		let keys = (123u64, "0xabc".to_string());
+		let cursor = encode_cursor(&keys);
+
+		assert_eq!(decode_cursor::<(u64, String)>(&cursor), Some(keys));
+		assert_eq!(decode_cursor::<(u64, String)>("not a cursor"), None);
+		assert_eq!(decode_cursor::<(u64, u64)>(&cursor), None);
+	}
 }
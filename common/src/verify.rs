@@ -0,0 +1,138 @@
+use eyre::{bail, Result};
+use std::{collections::HashSet, fs, sync::Arc};
+use uuid::Uuid;
+
+use crate::{
+	chain::BoxedChain,
+	models::{Config, ConfigKey, Network, PrimaryId, Transfer},
+	utils, App, BlockHeight, Storage,
+};
+
+// what a sampled block looks like on the chain versus what's been stored
+#[derive(Debug, Clone)]
+pub struct BlockReport {
+	pub block_height: BlockHeight,
+	pub discrepancies: Vec<String>,
+}
+
+// @NOTE picks `sample` random blocks out of the processed range, fetches them
+// from the rpc again (into a scratch folder, so stored files are left alone)
+// and compares them against what's stored: the block hash, row counts of the
+// extracted parquet files, and transfers in the warehouse
+pub async fn verify(app: &App, network: &Network, sample: usize) -> Result<Vec<BlockReport>> {
+	let nid = network.network_id;
+
+	let Some(chain) = app.networks.read().await.get(&nid).filter(|c| c.is_connected()).cloned()
+	else {
+		bail!("{} is not connected", network.name);
+	};
+
+	let processed_height =
+		Config::get::<_, BlockHeight>(app.db(), ConfigKey::IndexerProcessTail(nid))
+			.await?
+			.map(|h| h.value)
+			.unwrap_or(0);
+	if processed_height == 0 {
+		bail!("{} has not been processed yet", network.name);
+	}
+
+	let scratch_path = utils::project_dir(Some("tmp")).join(format!("verify-{}", Uuid::new_v4()));
+	let scratch = Arc::new(Storage::new_scratch(app.settings.clone(), scratch_path.clone()));
+
+	let mut ret = vec![];
+	for block_height in get_sample(processed_height, sample).into_iter() {
+		let result = verify_block(app, &chain, &scratch, nid, block_height).await;
+		fs::remove_dir_all(&scratch_path).ok();
+
+		ret.push(BlockReport { block_height, discrepancies: result? });
+	}
+
+	Ok(ret)
+}
+
+async fn verify_block(
+	app: &App,
+	chain: &Arc<BoxedChain>,
+	scratch: &Arc<Storage>,
+	nid: PrimaryId,
+	block_height: BlockHeight,
+) -> Result<Vec<String>> {
+	let mut ret = vec![];
+
+	let Some(block_hashes) = chain.extract_block(scratch.clone(), block_height).await? else {
+		return Ok(vec!["block not found on the rpc".to_string()]);
+	};
+
+	// extracted files
+	let stored_db = app.storage.get(nid, block_height)?;
+	match stored_db.get_manifest()? {
+		Some(stored) if stored.hash != block_hashes.hash => {
+			ret.push(format!("block hash is {}, stored {}", block_hashes.hash, stored.hash));
+		}
+		Some(_) => {}
+		None => ret.push("no valid manifest in storage".to_string()),
+	}
+
+	for (file, row_count) in scratch.get(nid, block_height)?.get_row_counts()?.into_iter() {
+		match stored_db.count_rows(&file)? {
+			Some(stored) if stored != row_count => {
+				ret.push(format!("{file}: {row_count} rows, stored {stored}"));
+			}
+			Some(_) => {}
+			None => ret.push(format!("{file}: missing from storage")),
+		}
+	}
+
+	// warehouse rows
+	let expected = chain
+		.process_block(scratch.clone(), block_height, chain.get_module_ids())
+		.await?
+		.map(|data| data.transfers.into_iter().map(|t| t.uuid).collect::<HashSet<_>>())
+		.unwrap_or_default();
+	let stored =
+		Transfer::get_all_by_block_range(&app.warehouse, nid, (block_height, block_height))
+			.await?
+			.into_iter()
+			.map(|t| t.uuid)
+			.collect::<HashSet<_>>();
+
+	let missing = expected.difference(&stored).count();
+	if missing > 0 {
+		ret.push(format!("transfers: {missing} missing from the warehouse"));
+	}
+	let unexpected = stored.difference(&expected).count();
+	if unexpected > 0 {
+		ret.push(format!("transfers: {unexpected} in the warehouse that aren't on the chain"));
+	}
+
+	Ok(ret)
+}
+
+// distinct random block heights up to `max_height`, in order
+fn get_sample(max_height: BlockHeight, sample: usize) -> Vec<BlockHeight> {
+	let count = (sample as u64).min(max_height + 1);
+
+	let mut ret = HashSet::new();
+	while (ret.len() as u64) < count {
+		ret.insert((Uuid::new_v4().as_u128() % (max_height as u128 + 1)) as BlockHeight);
+	}
+
+	let mut ret = ret.into_iter().collect::<Vec<_>>();
+	ret.sort();
+	ret
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_get_sample() {
+		let sample = get_sample(100, 10);
+		assert_eq!(sample.len(), 10);
+		assert!(sample.windows(2).all(|w| w[0] < w[1]));
+		assert!(sample.iter().all(|h| *h <= 100));
+
+		assert_eq!(get_sample(4, 10), vec![0, 1, 2, 3, 4]);
+	}
+}
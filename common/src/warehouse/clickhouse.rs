@@ -5,9 +5,23 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use super::DriverTrait;
-use crate::{utils, Settings};
+use crate::{chain::ModuleId, s3::Credentials, utils, Settings};
+
+// tables partitioned by month, which retention and archiving apply to
+static PARTITIONED_TABLES: &[&str] = &[
+	"transfers",
+	"amounts",
+	"links",
+	"decoded_calls",
+	"user_operations",
+	"fees",
+	"blocks",
+	"staking_deposits",
+	"approvals",
+];
 
 pub struct ClickHouse {
+	settings: Arc<Settings>,
 	url_without_database: String,
 	db_name: String,
 	client: ClickHouseClient,
@@ -37,6 +51,7 @@ impl DriverTrait for ClickHouse {
 			.wrap_err(url_without_database.clone())?;
 
 		Ok(Self {
+			settings,
 			url_without_database: url_without_database.clone(),
 			db_name: db_name.clone(),
 			client: ClickHouseClient::default()
@@ -56,6 +71,7 @@ impl DriverTrait for ClickHouse {
                         network_id UInt64,
                         block_height UInt64,
                         tx_hash String,
+                        log_index UInt64,
                         from_address String,
                         to_address String,
                         asset_address String,
@@ -73,7 +89,8 @@ impl DriverTrait for ClickHouse {
                         to_address,
                         asset_address,
                         relative_amount,
-                        batch_amount
+                        batch_amount,
+                        log_index
                     )
                     PARTITION BY toYYYYMM(created_at);
                 "#,
@@ -83,6 +100,49 @@ impl DriverTrait for ClickHouse {
 			.await
 			.wrap_err(self.url_without_database.clone())?;
 
+		// @NOTE `log_index` came after `transfers` was first released, so tables
+		// created before it get the column (0 for rows already there). a sorting
+		// key can only change in place by appending columns added in the same
+		// `ALTER`, which is why `log_index` goes last (for new tables too)
+		let log_index_columns = self
+			.client
+			.query(&format!(
+				r#"
+                    SELECT count()
+                    FROM system.columns
+                    WHERE database = '{}' AND table = 'transfers' AND name = 'log_index'
+                "#,
+				self.db_name
+			))
+			.fetch_one::<u64>()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+		if log_index_columns == 0 {
+			self.client
+				.query(&format!(
+					r#"
+                        ALTER TABLE {}.transfers
+                        ADD COLUMN log_index UInt64 AFTER tx_hash,
+                        MODIFY ORDER BY (
+                            module_id,
+                            network_id,
+                            block_height,
+                            tx_hash,
+                            from_address,
+                            to_address,
+                            asset_address,
+                            relative_amount,
+                            batch_amount,
+                            log_index
+                        )
+                    "#,
+					self.db_name
+				))
+				.execute()
+				.await
+				.wrap_err(self.url_without_database.clone())?;
+		}
+
 		self.client
 			.query(&format!(
 				r#"
@@ -136,6 +196,62 @@ impl DriverTrait for ClickHouse {
 			.await
 			.wrap_err(self.url_without_database.clone())?;
 
+		// balance changes by date, so historical balances don't require scanning
+		// the full `amounts` history. rows are keyed like `amounts` (and summed
+		// up when queried), so re-inserted ones replace the previous ones instead
+		// of being counted twice
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE MATERIALIZED VIEW IF NOT EXISTS {}.balance_snapshots
+                    ENGINE = ReplacingMergeTree
+                    PARTITION BY network_id
+                    ORDER BY (network_id, address, asset_address, date, block_height, tx_hash)
+                    POPULATE AS
+                    SELECT
+                        network_id,
+                        address,
+                        asset_address,
+                        toDate(created_at) as date,
+                        block_height,
+                        tx_hash,
+                        (amount_in - amount_out) as balance_change
+                    FROM {}.amounts
+                "#,
+				self.db_name, self.db_name,
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		// transfers by date for per-network dashboards. rows are keyed on the
+		// transfer's uuid (and aggregated when queried), so re-inserted ones
+		// replace the previous ones instead of being counted twice
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE MATERIALIZED VIEW IF NOT EXISTS {}.network_stats
+                    ENGINE = ReplacingMergeTree
+                    PARTITION BY network_id
+                    ORDER BY (network_id, date, asset_address, uuid)
+                    POPULATE AS
+                    SELECT
+                        network_id,
+                        toDate(created_at) as date,
+                        asset_address,
+                        uuid,
+                        tx_hash,
+                        from_address,
+                        to_address,
+                        relative_amount
+                    FROM {}.transfers
+                "#,
+				self.db_name, self.db_name,
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
 		self.client
 			.query(&format!(
 				r#"
@@ -164,6 +280,293 @@ impl DriverTrait for ClickHouse {
 			.await
 			.wrap_err(self.url_without_database.clone())?;
 
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE TABLE IF NOT EXISTS {}.decoded_calls
+                    (
+                        module_id UInt16,
+                        network_id UInt64,
+                        block_height UInt64,
+                        tx_hash String,
+                        log_index UInt64,
+                        contract_address String,
+                        kind LowCardinality(String),
+                        name String,
+                        signature String,
+                        args String,
+                        created_at DateTime
+                    )
+                    ENGINE = ReplacingMergeTree
+                    ORDER BY (
+                        network_id,
+                        block_height,
+                        tx_hash,
+                        kind,
+                        log_index
+                    )
+                    PARTITION BY toYYYYMM(created_at);
+                "#,
+				self.db_name
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE TABLE IF NOT EXISTS {}.user_operations
+                    (
+                        network_id UInt64,
+                        block_height UInt64,
+                        tx_hash String,
+                        user_op_hash String,
+                        entry_point String,
+                        sender String,
+                        paymaster String,
+                        bundler String,
+                        nonce UInt256,
+                        success Bool,
+                        actual_gas_cost UInt256,
+                        created_at DateTime
+                    )
+                    ENGINE = ReplacingMergeTree
+                    ORDER BY (
+                        network_id,
+                        block_height,
+                        tx_hash,
+                        user_op_hash
+                    )
+                    PARTITION BY toYYYYMM(created_at);
+                "#,
+				self.db_name
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE TABLE IF NOT EXISTS {}.fees
+                    (
+                        module_id UInt16,
+                        network_id UInt64,
+                        block_height UInt64,
+                        tx_hash String,
+                        address String,
+                        gas_used UInt64,
+                        effective_gas_price UInt256,
+                        l1_fee UInt256,
+                        fee UInt256,
+                        created_at DateTime
+                    )
+                    ENGINE = ReplacingMergeTree
+                    ORDER BY (
+                        network_id,
+                        address,
+                        block_height,
+                        tx_hash
+                    )
+                    PARTITION BY toYYYYMM(created_at);
+                "#,
+				self.db_name
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE TABLE IF NOT EXISTS {}.blocks
+                    (
+                        network_id UInt64,
+                        block_height UInt64,
+                        block_hash String,
+                        parent_hash String,
+                        created_at DateTime
+                    )
+                    ENGINE = ReplacingMergeTree
+                    ORDER BY (
+                        network_id,
+                        block_height,
+                        block_hash
+                    )
+                    PARTITION BY toYYYYMM(created_at);
+                "#,
+				self.db_name
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE TABLE IF NOT EXISTS {}.staking_deposits
+                    (
+                        network_id UInt64,
+                        block_height UInt64,
+                        tx_hash String,
+                        deposit_index UInt64,
+                        depositor String,
+                        pubkey String,
+                        withdrawal_credentials String,
+                        withdrawal_address String,
+                        amount UInt256,
+                        created_at DateTime
+                    )
+                    ENGINE = ReplacingMergeTree
+                    ORDER BY (
+                        network_id,
+                        deposit_index
+                    )
+                    PARTITION BY toYYYYMM(created_at);
+                "#,
+				self.db_name
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE TABLE IF NOT EXISTS {}.approvals
+                    (
+                        network_id UInt64,
+                        block_height UInt64,
+                        tx_hash String,
+                        log_index UInt64,
+                        token_address String,
+                        owner String,
+                        spender String,
+                        amount UInt256,
+                        created_at DateTime
+                    )
+                    ENGINE = ReplacingMergeTree
+                    ORDER BY (
+                        network_id,
+                        token_address,
+                        owner,
+                        spender,
+                        block_height,
+                        tx_hash,
+                        log_index
+                    )
+                    PARTITION BY toYYYYMM(created_at);
+                "#,
+				self.db_name
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		// daily usd prices, refetched until the day is over
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE TABLE IF NOT EXISTS {}.prices
+                    (
+                        network_id UInt64,
+                        asset_address String,
+                        date Date,
+                        price Float64,
+                        updated_at DateTime
+                    )
+                    ENGINE = ReplacingMergeTree(updated_at)
+                    ORDER BY (
+                        network_id,
+                        asset_address,
+                        date
+                    );
+                "#,
+				self.db_name
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		// @NOTE the earliest transfer into each address (by block height, then tx
+		// hash), kept as a plain tuple so that rolled back blocks can be deleted
+		// by it. mints and burns have no counterparty, and fees paid to block
+		// producers aren't funding
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE MATERIALIZED VIEW IF NOT EXISTS {}.funders
+                    ENGINE = AggregatingMergeTree
+                    PARTITION BY network_id
+                    ORDER BY (network_id, address)
+                    POPULATE AS
+                    SELECT
+                        network_id,
+                        to_address as address,
+                        minSimpleState(
+                            (block_height, tx_hash, from_address, created_at)
+                        ) as first_transfer
+                    FROM {}.transfers
+                    WHERE
+                        from_address != '' AND
+                        to_address != '' AND
+                        from_address != to_address AND
+                        module_id NOT IN ({}, {})
+                    GROUP BY (network_id, address)
+                "#,
+				self.db_name,
+				self.db_name,
+				u16::from(ModuleId::BitcoinFeeTransfer),
+				u16::from(ModuleId::EvmFeeTransfer),
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		// @NOTE every address that sent or received anything, per network. it's
+		// ordered by address first, so looking one up across all networks only
+		// reads a few granules instead of scanning `transfers`
+		self.client
+			.query(&format!(
+				r#"
+                    CREATE MATERIALIZED VIEW IF NOT EXISTS {}.address_activity
+                    ENGINE = AggregatingMergeTree
+                    PARTITION BY network_id
+                    ORDER BY (address, network_id)
+                    POPULATE AS
+                    SELECT
+                        address,
+                        network_id,
+                        minSimpleState(block_height) as min_block_height,
+                        maxSimpleState(block_height) as max_block_height
+                    FROM {}.transfers
+                    ARRAY JOIN [from_address, to_address] as address
+                    WHERE address != ''
+                    GROUP BY (address, network_id)
+                "#,
+				self.db_name, self.db_name,
+			))
+			.execute()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		// @NOTE `created_at` is when rows were indexed, so that's what
+		// partitions (and their expiry) go by. unsetting `warehouse_ttl` leaves
+		// existing ttls in place; they have to be removed with `REMOVE TTL`
+		if let Some(months) = self.settings.warehouse_ttl {
+			for table in PARTITIONED_TABLES.iter() {
+				self.client
+					.query(&format!(
+						"ALTER TABLE {}.{table} MODIFY TTL created_at + INTERVAL {months} MONTH",
+						self.db_name
+					))
+					.execute()
+					.await
+					.wrap_err(self.url_without_database.clone())?;
+			}
+		}
+
 		Ok(())
 	}
 
@@ -188,6 +591,33 @@ impl DriverTrait for ClickHouse {
 		Ok(rows.into_iter().map(|row| row.network_id.to_string()).collect())
 	}
 
+	// @NOTE ad-hoc queries can return any columns, which the typed client
+	// can't decode, so they go through the http interface as `JSONEachRow`
+	// (one json object per line). the caller restricts them with `SETTINGS
+	// readonly = 1`, which clickhouse enforces for the query itself
+	async fn select_read_only(&self, query: &str) -> Result<Vec<String>> {
+		let response = reqwest::Client::new()
+			.post(&self.url_without_database)
+			.query(&[("database", self.db_name.as_str()), ("default_format", "JSONEachRow")])
+			.body(query.to_string())
+			.send()
+			.await
+			.wrap_err(self.url_without_database.clone())?;
+
+		let status = response.status();
+		let body = response.text().await?;
+		if !status.is_success() {
+			return Err(eyre!("Failed to execute read-only query: {}", body.trim()));
+		}
+
+		Ok(body.lines().filter(|line| !line.is_empty()).map(|line| line.to_string()).collect())
+	}
+
+	async fn ping(&self) -> Result<()> {
+		self.client.query("SELECT 1").execute().await?;
+		Ok(())
+	}
+
 	async fn delete(&self, query: &str) -> Result<()> {
 		self.client
 			.query(query)
@@ -196,4 +626,101 @@ impl DriverTrait for ClickHouse {
 			.map_err(|e| eyre!("Failed to execute delete query: {}", e))?;
 		Ok(())
 	}
+
+	async fn execute(&self, query: &str) -> Result<()> {
+		self.client.query(query).execute().await?;
+		Ok(())
+	}
+
+	async fn get_old_partitions(&self, table: &str, months: u32) -> Result<Vec<String>> {
+		if !PARTITIONED_TABLES.contains(&table) {
+			return Ok(vec![]);
+		}
+
+		// partition ids are `YYYYMM`, so they compare as strings
+		Ok(self
+			.client
+			.query(&format!(
+				r#"
+					SELECT DISTINCT partition_id
+					FROM system.parts
+					WHERE
+						database = '{}' AND
+						table = '{table}' AND
+						active AND
+						partition_id < toString(toYYYYMM(now() - INTERVAL {months} MONTH))
+					ORDER BY partition_id
+				"#,
+				self.db_name
+			))
+			.fetch_all::<String>()
+			.await?)
+	}
+
+	// @NOTE copies the partition into storage first and only detaches it once
+	// that succeeds. re-running overwrites the same file, so a failure in
+	// between is safe to retry. credentials are the ones storage uses (static
+	// or from the provider chain); without any, clickhouse falls back to its own
+	async fn archive_partition(
+		&self,
+		table: &str,
+		partition: &str,
+		credentials: Option<Credentials>,
+	) -> Result<()> {
+		let Some(s3) = &self.settings.storage_url else {
+			return Err(eyre!("Archiving requires S3 storage"));
+		};
+
+		let url = format!("{}/archive/{table}/{partition}.parquet", s3.get_bucket_url());
+		let credentials = match credentials {
+			Some(Credentials { access_key_id, secret_access_key, session_token, .. }) => {
+				match session_token {
+					Some(token) => format!("'{access_key_id}', '{secret_access_key}', '{token}', "),
+					None => format!("'{access_key_id}', '{secret_access_key}', "),
+				}
+			}
+			None => "".to_string(),
+		};
+		let headers = match &s3.sse_kms_key_id {
+			Some(key_id) => format!(
+				", headers('x-amz-server-side-encryption' = 'aws:kms', \
+				 'x-amz-server-side-encryption-aws-kms-key-id' = '{key_id}')"
+			),
+			None => "".to_string(),
+		};
+
+		self.client
+			.query(&format!(
+				r#"
+					INSERT INTO FUNCTION s3('{url}', {credentials}'Parquet'{headers})
+					SELECT *
+					FROM {table}
+					WHERE _partition_id = '{partition}'
+					SETTINGS s3_truncate_on_insert = 1
+				"#
+			))
+			.execute()
+			.await
+			.map_err(|e| eyre!("Failed to archive {} partition {}: {}", table, partition, e))?;
+
+		self.client
+			.query(&format!("ALTER TABLE {table} DETACH PARTITION ID '{partition}'"))
+			.execute()
+			.await
+			.map_err(|e| eyre!("Failed to detach {} partition {}: {}", table, partition, e))?;
+
+		Ok(())
+	}
+
+	// @NOTE forces an unscheduled merge of all parts, which is when replacing
+	// and summing engines collapse duplicate rows. it rewrites the whole table,
+	// so it's meant to run rarely and off-peak
+	async fn optimize(&self, table: &str) -> Result<()> {
+		self.client
+			.query(&format!("OPTIMIZE TABLE {table} FINAL"))
+			.execute()
+			.await
+			.map_err(|e| eyre!("Failed to optimize {}: {}", table, e))?;
+		Ok(())
+	}
 }
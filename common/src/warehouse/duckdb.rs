@@ -1,14 +1,15 @@
 use async_trait::async_trait;
-use duckdb::{Connection, ToSql};
+use duckdb::{AccessMode, Connection, ToSql};
 use eyre::{eyre, Result};
 use serde_json::Value as JsonValue;
 use std::sync::{Arc, Mutex};
 use tokio::task::spawn_blocking;
 
 use super::DriverTrait;
-use crate::Settings;
+use crate::{s3::Credentials, storage::get_duckdb_config, Settings};
 
 pub struct DuckDB {
+	settings: Arc<Settings>,
 	connection: Arc<Mutex<duckdb::Connection>>,
 }
 
@@ -30,16 +31,55 @@ fn json_value_to_sql(value: &JsonValue) -> Box<dyn ToSql> {
 	}
 }
 
+fn select_rows(connection: &Connection, query: &str) -> Result<Vec<String>> {
+	let mut statement = connection.prepare(query)?;
+
+	let column_count = statement.column_count();
+	let column_names = statement.column_names();
+
+	let mut rows = statement.query([])?;
+	let mut results = Vec::new();
+
+	while let Some(row) = rows.next()? {
+		let mut json_map = serde_json::Map::new();
+
+		for (i, col_name) in column_names.iter().enumerate().take(column_count) {
+			let value: JsonValue = match row.get::<usize, Option<i64>>(i) {
+				Ok(Some(val)) => JsonValue::from(val),
+				Ok(None) => JsonValue::Null,
+				Err(_) => match row.get::<usize, Option<f64>>(i) {
+					Ok(Some(val)) => JsonValue::from(val),
+					Ok(None) => JsonValue::Null,
+					Err(_) => match row.get::<usize, Option<String>>(i) {
+						Ok(Some(val)) => JsonValue::from(val),
+						Ok(None) => JsonValue::Null,
+						Err(_) => JsonValue::Null,
+					},
+				},
+			};
+
+			json_map.insert(col_name.to_string(), value);
+		}
+
+		results.push(JsonValue::Object(json_map).to_string());
+	}
+
+	Ok(results)
+}
+
 #[async_trait]
 impl DriverTrait for DuckDB {
 	async fn new(settings: Arc<Settings>) -> Result<Self> {
-		let connection = spawn_blocking(move || {
-			Connection::open(&settings.warehouse)
-				.map_err(|e| eyre!("Failed to open DuckDB connection: {}", e))
+		let connection = spawn_blocking({
+			let settings = settings.clone();
+			move || {
+				Connection::open_with_flags(&settings.warehouse, get_duckdb_config(&settings)?)
+					.map_err(|e| eyre!("Failed to open DuckDB connection: {}", e))
+			}
 		})
 		.await??;
 
-		Ok(Self { connection: Arc::new(Mutex::new(connection)) })
+		Ok(Self { settings, connection: Arc::new(Mutex::new(connection)) })
 	}
 
 	async fn run_migrations(&self) -> Result<()> {
@@ -86,40 +126,37 @@ impl DriverTrait for DuckDB {
 
 	async fn select(&self, query: &str) -> Result<Vec<String>> {
 		let connection = self.connection.lock().map_err(|e| eyre::eyre!("Lock poisoned: {}", e))?;
+		select_rows(&connection, query)
+	}
 
-		let mut statement = connection.prepare(query)?;
-
-		let column_count = statement.column_count();
-		let column_names = statement.column_names();
-
-		let mut rows = statement.query([])?;
-		let mut results = Vec::new();
-
-		while let Some(row) = rows.next()? {
-			let mut json_map = serde_json::Map::new();
+	// @NOTE ad-hoc queries get a connection of their own, opened read-only and
+	// without access to files, urls or extensions, so even a statement that
+	// slips past the sanitizer can't write or read outside of the warehouse
+	async fn select_read_only(&self, query: &str) -> Result<Vec<String>> {
+		let settings = self.settings.clone();
+		let query = query.to_string();
 
-			for (i, col_name) in column_names.iter().enumerate().take(column_count) {
-				let value: JsonValue = match row.get::<usize, Option<i64>>(i) {
-					Ok(Some(val)) => JsonValue::from(val),
-					Ok(None) => JsonValue::Null,
-					Err(_) => match row.get::<usize, Option<f64>>(i) {
-						Ok(Some(val)) => JsonValue::from(val),
-						Ok(None) => JsonValue::Null,
-						Err(_) => match row.get::<usize, Option<String>>(i) {
-							Ok(Some(val)) => JsonValue::from(val),
-							Ok(None) => JsonValue::Null,
-							Err(_) => JsonValue::Null,
-						},
-					},
-				};
+		spawn_blocking(move || {
+			let config = get_duckdb_config(&settings)?
+				.access_mode(AccessMode::ReadOnly)?
+				.enable_external_access(false)?;
+			let connection = Connection::open_with_flags(&settings.warehouse, config)
+				.map_err(|e| eyre!("Failed to open read-only DuckDB connection: {}", e))?;
 
-				json_map.insert(col_name.to_string(), value);
-			}
+			select_rows(&connection, &query)
+		})
+		.await?
+	}
 
-			results.push(JsonValue::Object(json_map).to_string());
-		}
+	async fn ping(&self) -> Result<()> {
+		let conn = self.connection.clone();
 
-		Ok(results)
+		spawn_blocking(move || -> Result<()> {
+			let conn = conn.lock().map_err(|e| eyre!("Failed to acquire lock: {}", e))?;
+			conn.execute_batch("SELECT 1")?;
+			Ok(())
+		})
+		.await?
 	}
 
 	async fn delete(&self, query: &str) -> Result<()> {
@@ -133,4 +170,35 @@ impl DriverTrait for DuckDB {
 		})
 		.await?
 	}
+
+	async fn execute(&self, query: &str) -> Result<()> {
+		let query = query.to_string();
+		let conn = self.connection.clone();
+
+		spawn_blocking(move || -> Result<()> {
+			let conn = conn.lock().map_err(|e| eyre!("Failed to acquire lock: {}", e))?;
+			conn.execute_batch(&query)?;
+			Ok(())
+		})
+		.await?
+	}
+
+	// duckdb has no background merges, so there's nothing to collapse
+	async fn optimize(&self, _table: &str) -> Result<()> {
+		Ok(())
+	}
+
+	// tables aren't partitioned in duckdb
+	async fn get_old_partitions(&self, _table: &str, _months: u32) -> Result<Vec<String>> {
+		Ok(vec![])
+	}
+
+	async fn archive_partition(
+		&self,
+		_table: &str,
+		_partition: &str,
+		_credentials: Option<Credentials>,
+	) -> Result<()> {
+		Err(eyre!("Archiving is not supported by DuckDB"))
+	}
 }
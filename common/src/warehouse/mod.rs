@@ -2,15 +2,28 @@ use async_trait::async_trait;
 use derive_more::Display;
 use eyre::{eyre, Result};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{
+	future::Future,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+};
+use tokio::{
+	sync::RwLock,
+	time::{sleep, Duration},
+};
+use tracing::warn;
 
 use crate::{
+	s3::Credentials,
 	warehouse::{clickhouse::ClickHouse, duckdb::DuckDB},
 	Settings,
 };
 
 pub mod clickhouse;
 pub mod duckdb;
+pub mod query;
 
 #[derive(Display, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
 pub enum Driver {
@@ -23,6 +36,9 @@ pub enum Driver {
 	ClickHouse,
 }
 
+// how often the warehouse connection is checked (and re-established if needed)
+const HEALTH_CHECK_INTERVAL: u64 = 10;
+
 #[async_trait]
 pub trait DriverTrait: Send + Sync {
 	async fn new(settings: Arc<Settings>) -> Result<Self>
@@ -31,25 +47,100 @@ pub trait DriverTrait: Send + Sync {
 	async fn run_migrations(&self) -> Result<()>;
 	async fn insert(&self, table: &str, serialized_data: &[String]) -> Result<()>;
 	async fn select(&self, query: &str) -> Result<Vec<String>>;
+	async fn select_read_only(&self, query: &str) -> Result<Vec<String>>;
+	async fn ping(&self) -> Result<()>;
 	async fn delete(&self, query: &str) -> Result<()>;
+	async fn execute(&self, query: &str) -> Result<()>;
+	async fn optimize(&self, table: &str) -> Result<()>;
+	async fn get_old_partitions(&self, table: &str, months: u32) -> Result<Vec<String>>;
+	async fn archive_partition(
+		&self,
+		table: &str,
+		partition: &str,
+		credentials: Option<Credentials>,
+	) -> Result<()>;
 }
 
 pub struct Warehouse {
-	driver: Box<dyn DriverTrait>,
+	settings: Arc<Settings>,
+	driver: RwLock<Box<dyn DriverTrait>>,
+	is_healthy: AtomicBool,
 }
 
 impl Warehouse {
 	pub async fn new(settings: Arc<Settings>) -> Result<Self> {
-		let driver: Box<dyn DriverTrait> = match settings.warehouse_driver {
+		let driver = Self::new_driver(settings.clone()).await?;
+		Ok(Self { settings, driver: RwLock::new(driver), is_healthy: AtomicBool::new(true) })
+	}
+
+	async fn new_driver(settings: Arc<Settings>) -> Result<Box<dyn DriverTrait>> {
+		Ok(match settings.warehouse_driver {
 			Driver::DuckDB => Box::new(DuckDB::new(settings).await?),
 			Driver::ClickHouse => Box::new(ClickHouse::new(settings).await?),
-		};
+		})
+	}
+
+	// whether the last health check (or operation) reached the warehouse
+	pub fn is_healthy(&self) -> bool {
+		self.is_healthy.load(Ordering::SeqCst)
+	}
+
+	// @NOTE pings the warehouse every few seconds and, when that fails, swaps
+	// in a fresh connection (eg: after a clickhouse restart) so the next
+	// operation doesn't have to fail first
+	pub fn start_health_checks(self: &Arc<Self>) {
+		tokio::spawn({
+			let warehouse = self.clone();
 
-		Ok(Self { driver })
+			async move {
+				loop {
+					sleep(Duration::from_secs(HEALTH_CHECK_INTERVAL)).await;
+					warehouse.check_health().await;
+				}
+			}
+		});
+	}
+
+	pub async fn check_health(&self) -> bool {
+		let is_healthy = self.driver.read().await.ping().await.is_ok() || self.reconnect().await;
+		self.is_healthy.store(is_healthy, Ordering::SeqCst);
+
+		is_healthy
+	}
+
+	async fn reconnect(&self) -> bool {
+		match Self::new_driver(self.settings.clone()).await {
+			Ok(driver) => {
+				*self.driver.write().await = driver;
+				warn!(warehouse = "reconnected");
+				true
+			}
+			Err(e) => {
+				warn!(warehouse = "unreachable", error = e.to_string());
+				false
+			}
+		}
+	}
+
+	// @NOTE a failed operation is retried once if the warehouse turns out to be
+	// reachable, since the failure was most likely a connection that went stale
+	// (writes are safe to repeat: rows are deterministic and get deduplicated)
+	async fn retry_once<T, F, Fut>(&self, f: F) -> Result<T>
+	where
+		F: Fn() -> Fut,
+		Fut: Future<Output = Result<T>>,
+	{
+		match f().await {
+			Ok(v) => Ok(v),
+			Err(e) => match self.check_health().await {
+				true => f().await,
+				false => Err(e),
+			},
+		}
 	}
 
 	pub async fn run_migrations(&self) -> Result<()> {
-		self.driver.run_migrations().await
+		self.driver.read().await.run_migrations().await
 	}
 
 	pub async fn insert<T: Serialize>(&self, table: &str, data: &[T]) -> Result<()> {
@@ -59,11 +150,30 @@ impl Warehouse {
 			.collect::<Result<Vec<_>, _>>()
 			.map_err(|e| eyre!(e))?;
 
-		self.driver.insert(table, &serialized_data).await
+		self.retry_once(|| async { self.driver.read().await.insert(table, &serialized_data).await })
+			.await
 	}
 
 	pub async fn select<T: for<'de> Deserialize<'de>>(&self, query: &str) -> Result<Vec<T>> {
-		let serialized_rows = self.driver.select(query).await?;
+		let serialized_rows =
+			self.retry_once(|| async { self.driver.read().await.select(query).await }).await?;
+		let deserialized_rows: Vec<T> = serialized_rows
+			.iter()
+			.map(|row| serde_json::from_str(row))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| eyre!(e))?;
+
+		Ok(deserialized_rows)
+	}
+
+	// for ad-hoc queries, which the driver runs without write or external access
+	pub async fn select_read_only<T: for<'de> Deserialize<'de>>(
+		&self,
+		query: &str,
+	) -> Result<Vec<T>> {
+		let serialized_rows = self
+			.retry_once(|| async { self.driver.read().await.select_read_only(query).await })
+			.await?;
 		let deserialized_rows: Vec<T> = serialized_rows
 			.iter()
 			.map(|row| serde_json::from_str(row))
@@ -74,6 +184,31 @@ impl Warehouse {
 	}
 
 	pub async fn delete(&self, query: &str) -> Result<()> {
-		self.driver.delete(query).await
+		self.retry_once(|| async { self.driver.read().await.delete(query).await }).await
+	}
+
+	// @NOTE for statements that write derived rows (eg: `INSERT INTO .. SELECT`).
+	// unlike deletes they're not retried, since running one twice would write
+	// its rows twice
+	pub async fn execute(&self, query: &str) -> Result<()> {
+		self.driver.read().await.execute(query).await
+	}
+
+	pub async fn optimize(&self, table: &str) -> Result<()> {
+		self.driver.read().await.optimize(table).await
+	}
+
+	// partitions with rows indexed more than `months` months ago
+	pub async fn get_old_partitions(&self, table: &str, months: u32) -> Result<Vec<String>> {
+		self.driver.read().await.get_old_partitions(table, months).await
+	}
+
+	pub async fn archive_partition(
+		&self,
+		table: &str,
+		partition: &str,
+		credentials: Option<Credentials>,
+	) -> Result<()> {
+		self.driver.read().await.archive_partition(table, partition, credentials).await
 	}
 }
@@ -0,0 +1,319 @@
+use eyre::{bail, eyre, Result};
+use sqlparser::{
+	ast::{Expr, Query, SetExpr, Statement, TableFactor, Visit, Visitor},
+	dialect::{ClickHouseDialect, DuckDbDialect},
+	parser::Parser,
+};
+use std::{collections::HashSet, ops::ControlFlow};
+
+use super::Driver;
+
+// functions that read from outside of the whitelisted tables
+const FORBIDDEN_FUNCTIONS: &[&str] = &[
+	"cluster",
+	"clusterallreplicas",
+	"executable",
+	"file",
+	"getenv",
+	"glob",
+	"globalin",
+	"globalnotin",
+	"globalnotnullin",
+	"globalnullin",
+	"hascolumnintable",
+	"hdfs",
+	"in",
+	"input",
+	"jdbc",
+	"joinget",
+	"merge",
+	"mysql",
+	"notin",
+	"notnullin",
+	"nullin",
+	"odbc",
+	"postgres_scan",
+	"postgresql",
+	"query",
+	"query_table",
+	"remote",
+	"remotesecure",
+	"s3",
+	"s3cluster",
+	"sqlite",
+	"sqlite_scan",
+	"url",
+];
+
+// @NOTE the statement is parsed by `sqlparser`, but it's the original text
+// that runs, so it has to be split into tokens exactly the way the warehouse
+// splits it, or a string could hide a table function from the checks below.
+// clickhouse strings have backslash escapes and duckdb ones don't, so
+// anything where the two differ (comments, escaped identifiers, prefixed
+// strings like duckdb's `E'...'`, and dollar-quoted strings) is refused
+fn check_tokens(query: &str, driver: &Driver) -> Result<()> {
+	let chars = query.chars().collect::<Vec<char>>();
+	let mut i = 0;
+	while i < chars.len() {
+		let c = chars[i];
+
+		if (c == '-' && chars.get(i + 1) == Some(&'-')) ||
+			(c == '/' && chars.get(i + 1) == Some(&'*')) ||
+			c == '#'
+		{
+			bail!("comments are not allowed");
+		} else if c == '\'' {
+			if i > 0 && (chars[i - 1].is_alphanumeric() || chars[i - 1] == '_') {
+				bail!("prefixed strings are not allowed");
+			}
+
+			i += 1;
+			loop {
+				match chars.get(i) {
+					None => bail!("unterminated string"),
+					Some('\\') if *driver == Driver::ClickHouse => i += 2,
+					Some('\'') if chars.get(i + 1) == Some(&'\'') => i += 2,
+					Some('\'') => break,
+					_ => i += 1,
+				}
+			}
+			i += 1;
+		} else if c == '"' || c == '`' {
+			i += 1;
+			while chars.get(i).is_some_and(|&ch| ch != c) {
+				if chars[i] == '\\' {
+					bail!("escapes in identifiers are not allowed");
+				}
+				i += 1;
+			}
+			if i >= chars.len() {
+				bail!("unterminated identifier");
+			}
+			if chars.get(i + 1) == Some(&c) {
+				bail!("escapes in identifiers are not allowed");
+			}
+			i += 1;
+		} else if c == '$' {
+			bail!("`$` is not allowed");
+		} else {
+			i += 1;
+		}
+	}
+
+	Ok(())
+}
+
+// walks every query, table reference and expression of a statement, however
+// deep it's nested (eg: `ARRAY(SELECT ... FROM ...)`)
+struct Sandbox<'a> {
+	tables: &'a [&'a str],
+	ctes: HashSet<String>,
+}
+
+impl Sandbox<'_> {
+	fn check_body(body: &SetExpr) -> ControlFlow<String> {
+		match body {
+			SetExpr::Select(select) if select.into.is_some() => {
+				ControlFlow::Break("`INTO` is not allowed".to_string())
+			}
+			SetExpr::SetOperation { left, right, .. } => {
+				Self::check_body(left)?;
+				Self::check_body(right)
+			}
+			SetExpr::Select(_) | SetExpr::Query(_) | SetExpr::Values(_) => {
+				ControlFlow::Continue(())
+			}
+			_ => ControlFlow::Break("only `SELECT` statements are allowed".to_string()),
+		}
+	}
+}
+
+impl Visitor for Sandbox<'_> {
+	type Break = String;
+
+	fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+		if query.settings.is_some() {
+			return ControlFlow::Break("`SETTINGS` is not allowed".to_string());
+		}
+		if query.format_clause.is_some() {
+			return ControlFlow::Break("`FORMAT` is not allowed".to_string());
+		}
+		if query.for_clause.is_some() || !query.locks.is_empty() {
+			return ControlFlow::Break("`FOR` is not allowed".to_string());
+		}
+
+		Self::check_body(&query.body)
+	}
+
+	fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<Self::Break> {
+		match table_factor {
+			TableFactor::Table { name, args: None, .. } => {
+				let table = match name.0.as_slice() {
+					[table] => table.value.to_lowercase(),
+					_ => return ControlFlow::Break("qualified table names are not allowed".into()),
+				};
+				if !self.tables.contains(&table.as_str()) && !self.ctes.contains(&table) {
+					return ControlFlow::Break(format!("table `{table}` is not allowed"));
+				}
+
+				ControlFlow::Continue(())
+			}
+			TableFactor::Derived { .. } | TableFactor::NestedJoin { .. } => {
+				ControlFlow::Continue(())
+			}
+			_ => ControlFlow::Break("table functions are not allowed".to_string()),
+		}
+	}
+
+	fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+		match expr {
+			Expr::Function(function) => {
+				let name = match function.name.0.as_slice() {
+					[name] => name.value.to_lowercase(),
+					_ => return ControlFlow::Break("qualified functions are not allowed".into()),
+				};
+				if FORBIDDEN_FUNCTIONS.contains(&name.as_str()) ||
+					name.starts_with("dict") ||
+					name.starts_with("read_")
+				{
+					return ControlFlow::Break(format!("function `{name}` is not allowed"));
+				}
+			}
+			// clickhouse reads a table when it's the only thing in the list
+			Expr::InList { list, .. }
+				if list
+					.iter()
+					.any(|e| matches!(e, Expr::Identifier(_) | Expr::CompoundIdentifier(_))) =>
+			{
+				return ControlFlow::Break("`IN` lists can only hold values".to_string());
+			}
+			_ => {}
+		}
+
+		ControlFlow::Continue(())
+	}
+}
+
+// @NOTE validates an ad-hoc, read-only statement: a single `SELECT` (or
+// `WITH ... SELECT`) that only reads from `tables`, without comments, table
+// functions, qualified names or anything that writes or changes settings.
+// returns the statement without trailing semicolons, ready to be wrapped
+pub fn sanitize(query: &str, tables: &[&str], driver: &Driver) -> Result<String> {
+	let query = query.trim().trim_end_matches(|c: char| c == ';' || c.is_whitespace());
+	if query.is_empty() {
+		bail!("empty statement");
+	}
+
+	check_tokens(query, driver)?;
+
+	let statements = match driver {
+		Driver::DuckDB => Parser::parse_sql(&DuckDbDialect {}, query),
+		Driver::ClickHouse => Parser::parse_sql(&ClickHouseDialect {}, query),
+	}
+	.map_err(|e| eyre!("invalid statement: {e}"))?;
+
+	let query_ast = match statements.as_slice() {
+		[Statement::Query(query_ast)] => query_ast,
+		[_] => bail!("only `SELECT` statements are allowed"),
+		_ => bail!("only a single statement is allowed"),
+	};
+
+	// common table expressions can be referenced like tables, but only the
+	// ones from the leading `WITH` clause
+	let ctes = match &query_ast.with {
+		Some(with) => {
+			with.cte_tables.iter().map(|cte| cte.alias.name.value.to_lowercase()).collect()
+		}
+		None => HashSet::new(),
+	};
+
+	if let ControlFlow::Break(e) = query_ast.visit(&mut Sandbox { tables, ctes }) {
+		bail!(e);
+	}
+
+	Ok(query.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TABLES: &[&str] = &["transfers", "balances"];
+
+	#[test]
+	fn test_sanitize() {
+		let allowed = [
+			("SELECT * FROM transfers;", "SELECT * FROM transfers"),
+			("select count(*) from transfers t where t.network_id = 1", ""),
+			("SELECT * FROM transfers AS t JOIN balances b ON t.to_address = b.address", ""),
+			("SELECT * FROM transfers, balances", ""),
+			("WITH x AS (SELECT * FROM transfers) SELECT * FROM x", ""),
+			("SELECT EXTRACT(year FROM created_at) FROM transfers", ""),
+			("SELECT * FROM transfers WHERE tx_hash IN (SELECT tx_hash FROM transfers)", ""),
+			("SELECT 'DROP TABLE transfers; --' FROM transfers", ""),
+			("SELECT * FROM (SELECT * FROM balances) WHERE balance > 0", ""),
+			("SELECT format('{}', tx_hash) FROM transfers", ""),
+			("SELECT substring(tx_hash FROM 1 FOR 4) FROM transfers", ""),
+			("SELECT * FROM transfers JOIN (SELECT * FROM balances) b ON 1 = 1", ""),
+			("SELECT * FROM transfers FINAL WHERE network_id IN (1, 2)", ""),
+			("SELECT * FROM (transfers CROSS JOIN balances)", ""),
+		];
+		for driver in [Driver::DuckDB, Driver::ClickHouse] {
+			for (query, expected) in allowed.into_iter() {
+				let expected = if expected.is_empty() { query } else { expected };
+				assert_eq!(sanitize(query, TABLES, &driver).unwrap(), expected);
+			}
+		}
+
+		let denied = [
+			"",
+			"DROP TABLE transfers",
+			"INSERT INTO transfers SELECT * FROM transfers",
+			"SELECT * FROM transfers; DROP TABLE transfers",
+			"SELECT * FROM transfers -- comment",
+			"SELECT * FROM transfers /* comment */",
+			"SELECT * FROM amounts",
+			"SELECT * FROM transfers, amounts",
+			"SELECT * FROM transfers JOIN links ON 1 = 1",
+			"SELECT * FROM system.tables",
+			"SELECT * FROM `system.tables`",
+			"SELECT * FROM url('http://localhost')",
+			"SELECT * FROM read_csv('/etc/passwd')",
+			"SELECT * FROM '/etc/passwd'",
+			"SELECT file('/etc/passwd') FROM transfers",
+			"SELECT * FROM transfers WHERE address IN links",
+			"SELECT * FROM transfers SETTINGS max_threads = 1",
+			"SELECT * FROM transfers INTO OUTFILE 'out.csv'",
+			"SELECT * FROM transfers WHERE tx_hash = 'unterminated",
+			r"SELECT '\' AS a, * FROM read_text($$/etc/passwd$$) WHERE $$'$$ IS NOT NULL",
+			"SELECT $$x$$ FROM transfers",
+			r"SELECT E'\'' AS a, * FROM read_text('/etc/passwd') WHERE '' = ''",
+			r#"SELECT "\" '" FROM url('http://localhost') WHERE 'a' = 'a'"#,
+			r#"SELECT "a""b" FROM transfers"#,
+			"SELECT array(SELECT table_name FROM information_schema.tables) FROM transfers",
+			"SELECT * FROM (amounts CROSS JOIN transfers)",
+			"SELECT * FROM transfers, (amounts CROSS JOIN balances)",
+			"SELECT * FROM transfers t(a), '/etc/passwd'",
+			"SELECT * FROM transfers SAMPLE 1, amounts",
+			"SELECT * FROM amounts WINDOW amounts AS (ORDER BY 1)",
+			"SELECT * FROM transfers, amounts WINDOW amounts AS (ORDER BY 1)",
+			"SELECT * FROM transfers FINAL, otherdb.x",
+			"SELECT * FROM transfers WHERE address IN (links)",
+			"SELECT * FROM transfers WHERE notIn(address, links)",
+			"SELECT * FROM transfers, (WITH amounts AS (SELECT 1) SELECT * FROM amounts)",
+		];
+		for driver in [Driver::DuckDB, Driver::ClickHouse] {
+			for query in denied.into_iter() {
+				assert!(sanitize(query, TABLES, &driver).is_err(), "{driver}: {query}");
+			}
+		}
+
+		// strings only have backslash escapes on clickhouse
+		let query = r"SELECT '\' FROM transfers";
+		assert!(sanitize(query, TABLES, &Driver::DuckDB).is_ok());
+		assert!(sanitize(query, TABLES, &Driver::ClickHouse).is_err());
+		let query = r"SELECT 'a\'b' FROM transfers";
+		assert!(sanitize(query, TABLES, &Driver::DuckDB).is_err());
+		assert!(sanitize(query, TABLES, &Driver::ClickHouse).is_ok());
+	}
+}
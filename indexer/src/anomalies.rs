@@ -0,0 +1,195 @@
+use eyre::Result;
+use std::collections::HashMap;
+
+use crate::Indexer;
+use barreleye_common::{
+	chain::{WarehouseData, U256},
+	models::{Alert, Amount, Price, PrimaryId, Transfer},
+	prices, AlertKind, BlockHeight,
+};
+
+// how many times above its average an address has to send to be flagged
+const DEVIATION_FACTOR: u64 = 20;
+
+// addresses with less history than this are not checked for deviations
+const MIN_HISTORY: u64 = 10;
+
+impl Indexer {
+	// @NOTE flags transfers whose usd value is above the network's fixed
+	// threshold (native asset only, at its current price), far above what the
+	// sending address usually moves, or sent
+	// by an address that's been dormant for longer than the network allows.
+	// only meant for live blocks, since historical checks query the warehouse
+	pub async fn detect_anomalies(
+		&self,
+		network_id: PrimaryId,
+		warehouse_data: &WarehouseData,
+	) -> Result<()> {
+		let network = match self.app.networks.read().await.get(&network_id) {
+			Some(chain) => chain.get_network(),
+			_ => return Ok(()),
+		};
+		let dormancy_period = network.get_dormancy_period();
+
+		// sum up amounts per (sender, asset, tx)
+		let mut sent = HashMap::<(String, String, String), (BlockHeight, u32, U256)>::new();
+		for transfer in warehouse_data.transfers.iter() {
+			if transfer.network_id as PrimaryId != network_id || transfer.from_address.is_empty() {
+				continue;
+			}
+
+			let key = (
+				transfer.from_address.clone(),
+				transfer.asset_address.clone(),
+				transfer.tx_hash.clone(),
+			);
+			let entry = sent.entry(key).or_insert((
+				transfer.block_height,
+				transfer.created_at,
+				U256::zero(),
+			));
+			entry.2 = entry.2.saturating_add(transfer.relative_amount);
+		}
+
+		if sent.is_empty() {
+			return Ok(());
+		}
+
+		let mut alerts = vec![];
+		let block_height_min =
+			sent.values().map(|(block_height, _, _)| *block_height).min().unwrap_or_default();
+
+		// fixed threshold, in usd (skipped until the network has a recent price)
+		if let Some(threshold) = network.get_large_transfer_threshold() {
+			let price = match network.price_id.is_some() {
+				true => {
+					Price::get_latest_native_by_network_ids(&self.app.warehouse, network_id.into())
+						.await?
+						.remove(&network_id)
+				}
+				false => None,
+			};
+
+			if let Some(price) = price {
+				let decimals = network.architecture.native_decimals();
+				for ((address, asset_address, tx_hash), (block_height, _, amount)) in sent.iter() {
+					if !prices::is_priced(&network, asset_address) {
+						continue;
+					}
+
+					if let Some(value) =
+						prices::to_usd(*amount, decimals, price).filter(|v| *v >= threshold)
+					{
+						alerts.push(Alert::new_model(
+							network_id,
+							AlertKind::LargeTransfer,
+							*block_height,
+							tx_hash,
+							address,
+							asset_address,
+							amount.to_string(),
+							format!(
+								"value of ${value} is above the network threshold of ${threshold}"
+							),
+						));
+					}
+				}
+			}
+		}
+
+		// deviation from history, one lookup per asset
+		let mut by_asset = HashMap::<String, Vec<String>>::new();
+		for (address, asset_address, _) in sent.keys() {
+			by_asset.entry(asset_address.clone()).or_default().push(address.clone());
+		}
+
+		for (asset_address, addresses) in by_asset.into_iter() {
+			let totals = Transfer::get_sent_totals_by_addresses(
+				&self.app.warehouse,
+				network_id,
+				&asset_address,
+				addresses,
+				block_height_min,
+			)
+			.await?;
+
+			for ((address, asset, tx_hash), (block_height, _, amount)) in sent.iter() {
+				if *asset != asset_address {
+					continue;
+				}
+
+				if let Some((total, count)) = totals.get(address) {
+					if *count < MIN_HISTORY {
+						continue;
+					}
+
+					let average = *total / U256::from(*count);
+					if *amount > average.saturating_mul(U256::from(DEVIATION_FACTOR)) {
+						alerts.push(Alert::new_model(
+							network_id,
+							AlertKind::UnusualTransfer,
+							*block_height,
+							tx_hash,
+							address,
+							asset,
+							amount.to_string(),
+							format!(
+								"amount is over {DEVIATION_FACTOR}x the average of {average} across \
+								 {count} previous transfers"
+							),
+						));
+					}
+				}
+			}
+		}
+
+		// dormant addresses becoming active again, flagged on their earliest
+		// transfer out of these blocks
+		if let Some(dormancy_period) = dormancy_period {
+			let mut first_sent = HashMap::new();
+			for (key, value) in sent.iter() {
+				let entry = first_sent.entry(&key.0).or_insert((key, value));
+				if value.0 < entry.1 .0 {
+					*entry = (key, value);
+				}
+			}
+
+			let last_sent = Amount::get_last_sent_by_addresses(
+				&self.app.warehouse,
+				network_id,
+				first_sent.keys().map(|a| a.to_string()).collect(),
+				block_height_min,
+			)
+			.await?;
+
+			for ((address, asset, tx_hash), (block_height, created_at, amount)) in
+				first_sent.into_values()
+			{
+				if let Some(last_sent_at) = last_sent.get(address) {
+					let dormant_for = created_at.saturating_sub(*last_sent_at);
+					if dormant_for >= dormancy_period {
+						alerts.push(Alert::new_model(
+							network_id,
+							AlertKind::DormantReactivated,
+							*block_height,
+							tx_hash,
+							address,
+							asset,
+							amount.to_string(),
+							format!(
+								"first transfer out after {} days without any",
+								dormant_for / 86_400
+							),
+						));
+					}
+				}
+			}
+		}
+
+		if !alerts.is_empty() {
+			Alert::create_many(self.app.db(), alerts).await?;
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,82 @@
+use eyre::Result;
+use std::time::SystemTime;
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::{debug, info, warn};
+
+use crate::Indexer;
+use barreleye_common::models::{
+	AmountTable, ApprovalTable, BlockTable, DecodedCallTable, FeeTable, LinkTable,
+	StakingDepositTable, TransferTable, UserOperationTable,
+};
+
+// materialized views are left alone, since they hold running totals that
+// archived partitions still count towards
+static TABLES: &[&str] = &[
+	TransferTable,
+	AmountTable,
+	LinkTable,
+	DecodedCallTable,
+	UserOperationTable,
+	FeeTable,
+	BlockTable,
+	StakingDepositTable,
+	ApprovalTable,
+];
+
+impl Indexer {
+	// @NOTE moves monthly partitions older than `warehouse_archive_after` into
+	// storage, so history is kept in cold storage while the warehouse stays
+	// small. detached partitions are what mark progress, so there's nothing to
+	// keep track of in between runs
+	pub async fn archive_warehouse(
+		&self,
+		mut networks_updated: Receiver<SystemTime>,
+	) -> Result<()> {
+		let archive_after = self.app.settings.warehouse_archive_after;
+
+		loop {
+			if let Some(months) = archive_after {
+				if self.app.is_leading() && self.is_in_maintenance_window() {
+					// failures are retried on the next run
+					for table in TABLES.iter() {
+						let partitions =
+							match self.app.warehouse.get_old_partitions(table, months).await {
+								Ok(partitions) => partitions,
+								Err(e) => {
+									warn!(table, error = e.to_string());
+									continue;
+								}
+							};
+
+						for partition in partitions.into_iter() {
+							let credentials = self.app.storage.get_credentials();
+							match self
+								.app
+								.warehouse
+								.archive_partition(table, &partition, credentials)
+								.await
+							{
+								Ok(()) => info!(table, partition, "Archived partition"),
+								Err(e) => {
+									warn!(table, partition, error = e.to_string());
+									break;
+								}
+							}
+						}
+					}
+				}
+			}
+
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(3600)) => {}
+			}
+		}
+	}
+}
@@ -0,0 +1,206 @@
+use eyre::Result;
+use sea_orm::Set;
+use std::{
+	cmp,
+	collections::{HashMap, HashSet},
+	time::SystemTime,
+};
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::{debug, warn};
+
+use crate::Indexer;
+use barreleye_common::{
+	models::{
+		BridgeTransfer, BridgeTransferActiveModel, Config, ConfigKey, PrimaryId, Tag, Transfer,
+	},
+	BlockHeight,
+};
+
+const BLOCKS_PER_LOOP: BlockHeight = 100;
+
+// how long (in seconds) a bridge can take to release funds; optimistic
+// rollups hold withdrawals for 7 days
+const MAX_BRIDGE_DELAY: u32 = 8 * 24 * 60 * 60;
+
+impl Indexer {
+	// @NOTE bridges are entities tagged with `isBridge`, whose addresses are the
+	// bridge's contracts (or escrows) on each network. every release (a bridge
+	// address sending funds out) is matched with the latest deposit the same
+	// address made into that bridge on another network, before the release.
+	// networks are processed independently, so a release is only matched if
+	// its deposit has been processed by then
+	pub async fn detect_bridge_transfers(
+		&self,
+		mut networks_updated: Receiver<SystemTime>,
+	) -> Result<()> {
+		loop {
+			if !self.app.is_leading() {
+				sleep(Duration::from_secs(1)).await;
+				continue;
+			}
+
+			// failures (eg: the warehouse being unreachable) are retried on the
+			// next run, without holding up other networks
+
+			// bridge address -> entity, per network
+			let mut bridges = HashMap::<PrimaryId, HashMap<String, PrimaryId>>::new();
+			match Tag::get_all_bridge_addresses(self.app.db()).await {
+				Ok(addresses) => {
+					for address in addresses.into_iter() {
+						bridges
+							.entry(address.network_id)
+							.or_default()
+							.insert(address.address, address.entity_id);
+					}
+				}
+				Err(e) => warn!(bridge_transfers = "failed", error = e.to_string()),
+			}
+
+			let mut is_caught_up = true;
+			for nid in bridges.keys().copied() {
+				match self.detect_network_bridge_transfers(nid, &bridges).await {
+					Ok(is_network_caught_up) => is_caught_up &= is_network_caught_up,
+					Err(e) => {
+						warn!(network_id = nid, bridge_transfers = "failed", error = e.to_string())
+					}
+				}
+			}
+
+			let pause = if is_caught_up { 10 } else { 0 };
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(pause)) => {}
+			}
+		}
+	}
+
+	async fn detect_network_bridge_transfers(
+		&self,
+		nid: PrimaryId,
+		bridges: &HashMap<PrimaryId, HashMap<String, PrimaryId>>,
+	) -> Result<bool> {
+		// skip network if "process" step is not done yet
+		let processed_block_height =
+			Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerProcessTail(nid))
+				.await?
+				.map(|v| v.value)
+				.unwrap_or(0);
+		let process_step_synced = Config::get_many::<_, (BlockHeight, BlockHeight)>(
+			self.app.db(),
+			vec![ConfigKey::IndexerProcessChunk(nid, 0), ConfigKey::IndexerProcessModule(nid, 0)],
+		)
+		.await?
+		.is_empty();
+		if processed_block_height == 0 || !process_step_synced {
+			return Ok(true);
+		}
+
+		let block_height =
+			Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerBridge(nid))
+				.await?
+				.map(|v| v.value)
+				.unwrap_or(0);
+		if block_height >= processed_block_height {
+			return Ok(true);
+		}
+
+		let block_height_min = block_height + 1;
+		let block_height_max = cmp::min(block_height + BLOCKS_PER_LOOP, processed_block_height);
+
+		let transfers = Transfer::get_all_by_block_range_excluding_fees(
+			&self.app.warehouse,
+			nid,
+			(block_height_min, block_height_max),
+		)
+		.await?;
+
+		self.save_bridge_transfers(nid, bridges, transfers).await?;
+
+		Config::set::<_, BlockHeight>(
+			self.app.db(),
+			ConfigKey::IndexerBridge(nid),
+			block_height_max,
+		)
+		.await?;
+
+		Ok(block_height_max >= processed_block_height)
+	}
+
+	async fn save_bridge_transfers(
+		&self,
+		network_id: PrimaryId,
+		bridges: &HashMap<PrimaryId, HashMap<String, PrimaryId>>,
+		transfers: Vec<Transfer>,
+	) -> Result<()> {
+		let bridge_addresses = &bridges[&network_id];
+
+		let mut data = vec![];
+		for t in transfers.into_iter() {
+			// only releases to regular addresses
+			let Some(entity_id) = bridge_addresses.get(&t.from_address).copied() else {
+				continue;
+			};
+			if t.to_address.is_empty() ||
+				bridge_addresses.contains_key(&t.to_address) ||
+				t.relative_amount.is_zero()
+			{
+				continue;
+			}
+
+			// the same bridge on every other network
+			let deposit_addresses = bridges
+				.iter()
+				.filter(|(nid, _)| **nid != network_id)
+				.map(|(nid, addresses)| {
+					let addresses = addresses
+						.iter()
+						.filter(|(_, e)| **e == entity_id)
+						.map(|(a, _)| a.clone())
+						.collect::<HashSet<String>>();
+
+					(*nid, addresses)
+				})
+				.filter(|(_, addresses)| !addresses.is_empty())
+				.collect::<HashMap<PrimaryId, HashSet<String>>>();
+
+			let Some(deposit) = Transfer::get_all_into_addresses(
+				&self.app.warehouse,
+				&t.to_address,
+				deposit_addresses,
+				(t.created_at.saturating_sub(MAX_BRIDGE_DELAY), t.created_at),
+			)
+			.await?
+			.into_iter()
+			.next() else {
+				continue;
+			};
+
+			data.push(BridgeTransferActiveModel {
+				entity_id: Set(entity_id),
+				from_network_id: Set(deposit.network_id as PrimaryId),
+				from_block_height: Set(deposit.block_height as i64),
+				from_tx_hash: Set(deposit.tx_hash),
+				from_address: Set(deposit.from_address),
+				from_amount: Set(deposit.relative_amount.to_string()),
+				to_network_id: Set(network_id),
+				to_block_height: Set(t.block_height as i64),
+				to_tx_hash: Set(t.tx_hash),
+				to_address: Set(t.to_address),
+				to_amount: Set(t.relative_amount.to_string()),
+				..Default::default()
+			});
+		}
+
+		if !data.is_empty() {
+			BridgeTransfer::create_many(self.app.db(), data).await?;
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,86 @@
+use eyre::Result;
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::Indexer;
+use barreleye_common::{
+	models::{Block, Config, ConfigKey, PrimaryId},
+	BlockHeight,
+};
+
+// how many of the latest processed blocks get re-checked
+const CANONICAL_CHECK_DEPTH: u64 = 128;
+
+impl Indexer {
+	// @NOTE compares hashes of recently processed blocks against the chain,
+	// walking down from the latest one until they match again. returns the
+	// lowest block that's no longer canonical (eg: uncled or reorged out).
+	// chains that can't look up block hashes are never flagged, and neither
+	// are blocks whose hash couldn't be fetched (they're checked again later)
+	pub async fn find_non_canonical_block(
+		&self,
+		network_id: PrimaryId,
+	) -> Result<Option<BlockHeight>> {
+		let Some(chain) = self.app.networks.read().await.get(&network_id).cloned() else {
+			return Ok(None);
+		};
+
+		let mut ret = None;
+		for block in Block::get_latest(&self.app.warehouse, network_id, CANONICAL_CHECK_DEPTH)
+			.await?
+			.into_iter()
+		{
+			match chain.get_block_hash(block.block_height).await {
+				Ok(Some(block_hash)) if block_hash != block.block_hash => {
+					ret = Some(block.block_height);
+				}
+				Ok(_) => break,
+				Err(e) => {
+					warn!(network_id, block_height = block.block_height, error = e.to_string());
+					return Ok(None);
+				}
+			}
+		}
+
+		Ok(ret)
+	}
+
+	// @NOTE drops processed data from `block_height` onwards and rewinds every
+	// marker that's past it (the processed tail, each address' link marker and
+	// the detectors'), so those blocks get processed, linked and scanned again
+	// from the canonical chain
+	pub async fn rollback_processed_blocks(
+		&self,
+		network_id: PrimaryId,
+		block_height: BlockHeight,
+	) -> Result<()> {
+		warn!(network_id, block_height, rollback = "non-canonical block");
+
+		Block::rollback(&self.app.warehouse, network_id, block_height).await?;
+
+		let db_tx = self.app.db_tx().await?;
+		let markers = Config::get_many::<_, BlockHeight>(
+			&db_tx,
+			vec![
+				ConfigKey::IndexerProcessTail(network_id),
+				ConfigKey::IndexerLink(network_id, 0),
+				ConfigKey::IndexerPeel(network_id),
+				ConfigKey::IndexerMixer(network_id),
+				ConfigKey::IndexerDeposit(network_id),
+				ConfigKey::IndexerProducer(network_id),
+				ConfigKey::IndexerBridge(network_id),
+			],
+		)
+		.await?
+		.into_iter()
+		.filter(|(_, hit)| hit.value >= block_height)
+		.map(|(config_key, _)| (config_key, block_height.saturating_sub(1)))
+		.collect::<HashMap<ConfigKey, BlockHeight>>();
+		if !markers.is_empty() {
+			Config::set_many::<_, BlockHeight>(&db_tx, markers).await?;
+		}
+		db_tx.commit().await?;
+
+		Ok(())
+	}
+}
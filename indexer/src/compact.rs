@@ -0,0 +1,91 @@
+use eyre::Result;
+use std::time::{Instant, SystemTime};
+use tokio::{
+	sync::watch::Receiver,
+	task::spawn_blocking,
+	time::{sleep, Duration},
+};
+use tracing::{debug, info};
+
+use crate::Indexer;
+use barreleye_common::{
+	models::{Config, ConfigKey, Network, SoftDeleteModel},
+	storage::compaction::COMPACTION_RANGE,
+	BlockHeight,
+};
+
+// ranges are only compacted once they end this far below the processed tail,
+// so blocks that could still be re-extracted after a reorg aren't touched
+const COMPACTION_MARGIN: BlockHeight = 1_000;
+
+impl Indexer {
+	// @NOTE merges per-block parquet files into a file per range of
+	// `COMPACTION_RANGE` blocks, oldest ranges first, so reprocessing reads a
+	// few large files instead of thousands of tiny ones (see
+	// `Storage::compact_range`)
+	pub async fn compact_storage(&self, mut networks_updated: Receiver<SystemTime>) -> Result<()> {
+		loop {
+			let mut is_caught_up = true;
+
+			if self.app.settings.compact_storage && self.app.is_leading() {
+				for network in Network::get_all_existing(self.app.db(), Some(false)).await? {
+					let nid = network.network_id;
+
+					let processed_block_height = Config::get::<_, BlockHeight>(
+						self.app.db(),
+						ConfigKey::IndexerProcessTail(nid),
+					)
+					.await?
+					.map(|v| v.value)
+					.unwrap_or(0);
+
+					let range_start = Config::get::<_, BlockHeight>(
+						self.app.db(),
+						ConfigKey::IndexerCompact(nid),
+					)
+					.await?
+					.map(|v| v.value)
+					.unwrap_or(0);
+					if range_start + COMPACTION_RANGE + COMPACTION_MARGIN > processed_block_height {
+						continue;
+					}
+
+					let started_at = Instant::now();
+					let blocks = spawn_blocking({
+						let storage = self.app.storage.clone();
+						move || storage.compact_range(nid, range_start)
+					})
+					.await??;
+
+					if blocks > 0 {
+						info!(
+							network = %network.name,
+							range_start,
+							blocks,
+							duration_ms = started_at.elapsed().as_millis() as u64,
+							"Compacted storage"
+						);
+					}
+
+					Config::set::<_, BlockHeight>(
+						self.app.db(),
+						ConfigKey::IndexerCompact(nid),
+						range_start + COMPACTION_RANGE,
+					)
+					.await?;
+
+					is_caught_up = false;
+				}
+			}
+
+			let pause = if is_caught_up { 60 } else { 0 };
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(pause)) => {}
+			}
+		}
+	}
+}
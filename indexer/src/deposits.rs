@@ -0,0 +1,265 @@
+use eyre::Result;
+use serde_json::json;
+use std::{
+	cmp,
+	collections::{HashMap, HashSet},
+	time::SystemTime,
+};
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::{debug, warn};
+
+use crate::Indexer;
+use barreleye_common::{
+	chain::U256,
+	models::{
+		Address, Annotation, Balance, BasicModel, Config, ConfigKey, Network, PrimaryId, Tag,
+		Transfer,
+	},
+	AnnotationKind, BlockHeight,
+};
+
+const BLOCKS_PER_LOOP: BlockHeight = 100;
+
+// whatever is left after a sweep has to be at most 1/DUST_RATIO of it
+const DUST_RATIO: u64 = 100;
+
+// distinct addresses that have to pay into an address before it's swept
+const MIN_SENDERS: u64 = 3;
+
+impl Indexer {
+	// @NOTE detects deposit addresses by their sweep pattern: an unlabeled
+	// address that's been paid by several others forwards (nearly) its whole
+	// balance to a known exchange hot wallet. a single sender emptying its
+	// own address into an exchange is just a user withdrawing everything, so
+	// it doesn't count. such addresses are added to the exchange's entity
+	pub async fn detect_exchange_deposits(
+		&self,
+		mut networks_updated: Receiver<SystemTime>,
+	) -> Result<()> {
+		loop {
+			if !self.app.is_leading() {
+				sleep(Duration::from_secs(1)).await;
+				continue;
+			}
+
+			// failures (eg: the warehouse being unreachable) are retried on the
+			// next run, without holding up other networks
+
+			// hot wallet -> entity, per network
+			let mut exchanges = HashMap::<PrimaryId, HashMap<String, PrimaryId>>::new();
+			match Tag::get_all_exchange_addresses(self.app.db()).await {
+				Ok(addresses) => {
+					for address in addresses.into_iter() {
+						exchanges
+							.entry(address.network_id)
+							.or_default()
+							.insert(address.address, address.entity_id);
+					}
+				}
+				Err(e) => warn!(exchange_deposits = "failed", error = e.to_string()),
+			}
+
+			let mut is_caught_up = true;
+			for (nid, hot_wallets) in exchanges.into_iter() {
+				match self.detect_network_exchange_deposits(nid, &hot_wallets).await {
+					Ok(is_network_caught_up) => is_caught_up &= is_network_caught_up,
+					Err(e) => {
+						warn!(network_id = nid, exchange_deposits = "failed", error = e.to_string())
+					}
+				}
+			}
+
+			let pause = if is_caught_up { 10 } else { 0 };
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(pause)) => {}
+			}
+		}
+	}
+
+	async fn detect_network_exchange_deposits(
+		&self,
+		nid: PrimaryId,
+		hot_wallets: &HashMap<String, PrimaryId>,
+	) -> Result<bool> {
+		// skip network if "process" step is not done yet
+		let processed_block_height =
+			Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerProcessTail(nid))
+				.await?
+				.map(|v| v.value)
+				.unwrap_or(0);
+		let process_step_synced = Config::get_many::<_, (BlockHeight, BlockHeight)>(
+			self.app.db(),
+			vec![ConfigKey::IndexerProcessChunk(nid, 0), ConfigKey::IndexerProcessModule(nid, 0)],
+		)
+		.await?
+		.is_empty();
+		if processed_block_height == 0 || !process_step_synced {
+			return Ok(true);
+		}
+
+		let block_height =
+			Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerDeposit(nid))
+				.await?
+				.map(|v| v.value)
+				.unwrap_or(0);
+		if block_height >= processed_block_height {
+			return Ok(true);
+		}
+
+		let block_height_min = block_height + 1;
+		let block_height_max = cmp::min(block_height + BLOCKS_PER_LOOP, processed_block_height);
+
+		let transfers = Transfer::get_all_by_block_range_excluding_fees(
+			&self.app.warehouse,
+			nid,
+			(block_height_min, block_height_max),
+		)
+		.await?;
+
+		self.save_exchange_deposits(nid, hot_wallets, transfers).await?;
+
+		Config::set::<_, BlockHeight>(
+			self.app.db(),
+			ConfigKey::IndexerDeposit(nid),
+			block_height_max,
+		)
+		.await?;
+
+		Ok(block_height_max >= processed_block_height)
+	}
+
+	async fn save_exchange_deposits(
+		&self,
+		network_id: PrimaryId,
+		hot_wallets: &HashMap<String, PrimaryId>,
+		transfers: Vec<Transfer>,
+	) -> Result<()> {
+		// amounts swept into hot wallets: (from, hot wallet, asset) -> (block, amount)
+		let mut sweeps = HashMap::<(String, String, String), (BlockHeight, String, U256)>::new();
+		for t in transfers.into_iter() {
+			if !hot_wallets.contains_key(&t.to_address) || hot_wallets.contains_key(&t.from_address)
+			{
+				continue;
+			}
+
+			let key = (t.from_address, t.to_address, t.asset_address);
+			let entry = sweeps.entry(key).or_insert((t.block_height, t.tx_hash, U256::zero()));
+			entry.0 = cmp::max(entry.0, t.block_height);
+			entry.2 = entry.2.saturating_add(t.relative_amount);
+		}
+
+		if sweeps.is_empty() {
+			return Ok(());
+		}
+
+		// already labeled addresses are left alone
+		let labeled = Address::get_all_by_addresses(
+			self.app.db(),
+			sweeps.keys().map(|(from, _, _)| from.clone()).collect(),
+			None,
+		)
+		.await?
+		.into_iter()
+		.filter(|a| a.network_id == network_id)
+		.map(|a| a.address)
+		.collect::<HashSet<String>>();
+
+		let network = match Network::get(self.app.db(), network_id).await? {
+			Some(network) => network,
+			_ => return Ok(()),
+		};
+
+		// deposit addresses collect from many customers before being swept
+		let sender_counts = Transfer::get_sender_counts_by_addresses(
+			&self.app.warehouse,
+			network_id,
+			sweeps
+				.keys()
+				.map(|(from, _, _)| from.clone())
+				.filter(|a| !labeled.contains(a))
+				.collect(),
+			sweeps.values().map(|(block_height, _, _)| *block_height).max().unwrap_or_default(),
+		)
+		.await?;
+
+		let mut addresses = HashMap::new();
+		let mut annotations = vec![];
+		for ((from, hot_wallet, asset_address), (block_height, tx_hash, amount)) in sweeps {
+			if labeled.contains(&from) || addresses.contains_key(&from) {
+				continue;
+			}
+
+			if sender_counts.get(&from).copied().unwrap_or_default() < MIN_SENDERS {
+				continue;
+			}
+
+			// has to forward (nearly) everything
+			let leftover = Balance::get_all_by_address_at_block_height(
+				&self.app.warehouse,
+				network_id,
+				&from,
+				block_height,
+			)
+			.await?
+			.into_iter()
+			.find(|b| b.asset_address == asset_address)
+			.map(|b| b.balance)
+			.unwrap_or_default();
+			if leftover.saturating_mul(U256::from(DUST_RATIO)) > amount {
+				continue;
+			}
+
+			let entity_id = hot_wallets[&hot_wallet];
+			addresses.insert(
+				from.clone(),
+				Address::new_model(
+					None,
+					entity_id,
+					network_id,
+					&network.id,
+					&from,
+					"Detected deposit address",
+					Some(json!({ "sweptTo": hot_wallet, "txHash": tx_hash })),
+				),
+			);
+			annotations.push(Annotation::new_model(
+				network_id,
+				&from,
+				AnnotationKind::ExchangeDeposit,
+				&hot_wallet,
+			));
+		}
+
+		if addresses.is_empty() {
+			return Ok(());
+		}
+
+		let new_addresses = addresses.keys().cloned().collect::<Vec<String>>();
+		Address::create_many(self.app.db(), addresses.into_values().collect()).await?;
+		Annotation::create_many(self.app.db(), annotations).await?;
+
+		// tell the link step about newly created addresses
+		Config::set_many::<_, PrimaryId>(
+			self.app.db(),
+			Address::get_all_by_addresses(self.app.db(), new_addresses, Some(false))
+				.await?
+				.into_iter()
+				.filter(|a| a.network_id == network_id)
+				.map(|a| (ConfigKey::NewlyAddedAddress(a.network_id, a.address_id), a.address_id))
+				.collect::<HashMap<ConfigKey, PrimaryId>>(),
+		)
+		.await?;
+
+		// invalidate cached labels
+		Config::set::<_, u8>(self.app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,63 @@
+use eyre::Result;
+use sea_orm::{prelude::DateTime, ColumnTrait, ConnectionTrait};
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+};
+
+use barreleye_common::models::{
+	Address, AddressColumn, BasicModel, Config, ConfigKey, PrimaryId, PrimaryIds,
+};
+
+// @NOTE in-memory index of all labeled addresses, so hot loops can check
+// whether an address is "interesting" without hitting the database. it's
+// reloaded only when addresses change (`ConfigKey::EntitiesUpdated`)
+#[derive(Default)]
+pub struct AddressIndex {
+	addresses: Vec<Address>,
+	data: HashMap<PrimaryId, Arc<HashSet<String>>>,
+	synced_at: Option<DateTime>,
+	is_loaded: bool,
+}
+
+impl AddressIndex {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub async fn refresh<C>(&mut self, c: &C) -> Result<bool>
+	where
+		C: ConnectionTrait,
+	{
+		let updated_at =
+			Config::get::<_, u8>(c, ConfigKey::EntitiesUpdated).await?.map(|v| v.updated_at);
+		if self.is_loaded && updated_at <= self.synced_at {
+			return Ok(false);
+		}
+
+		self.addresses = Address::get_all_where(c, AddressColumn::IsDeleted.eq(false)).await?;
+
+		let mut data = HashMap::<PrimaryId, HashSet<String>>::new();
+		for address in self.addresses.iter() {
+			data.entry(address.network_id).or_default().insert(address.address.clone());
+		}
+		self.data = data.into_iter().map(|(k, v)| (k, Arc::new(v))).collect();
+
+		self.synced_at = updated_at;
+		self.is_loaded = true;
+
+		Ok(true)
+	}
+
+	pub fn invalidate(&mut self) {
+		self.is_loaded = false;
+	}
+
+	pub fn get_addresses(&self, network_ids: &PrimaryIds) -> Vec<Address> {
+		self.addresses.iter().filter(|a| network_ids.contains(&a.network_id)).cloned().collect()
+	}
+
+	pub fn get(&self, network_id: PrimaryId) -> Arc<HashSet<String>> {
+		self.data.get(&network_id).cloned().unwrap_or_default()
+	}
+}
@@ -1,9 +1,12 @@
 use eyre::Result;
-use sea_orm::ColumnTrait;
+use sea_orm::prelude::DateTime;
+use serde::Serialize;
+use serde_json::{from_value as json_parse, json, Value as JsonValue};
 use std::{
-	collections::{HashMap, HashSet},
+	collections::HashMap,
+	str::FromStr,
 	sync::Arc,
-	time::SystemTime,
+	time::{Instant, SystemTime},
 };
 use tokio::{
 	signal,
@@ -11,21 +14,43 @@ use tokio::{
 	task::JoinSet,
 	time::{sleep, Duration},
 };
-use tracing::debug;
+use tracing::{debug, trace, warn};
 use uuid::Uuid;
 
 use barreleye_common::{
-	models::{
-		Address, AddressColumn, Amount, Balance, Config, ConfigKey, Entity, Link, Network,
-		NetworkColumn, PrimaryId, PrimaryIds, SoftDeleteModel, Transfer,
-	},
+	chain::WarehouseData,
+	models::{Block, Config, ConfigKey, PrimaryId, StagedBatch},
 	utils, App, AppError, BlockHeight, Progress, ProgressReadyType, ProgressStep, Warnings,
 	INDEXER_HEARTBEAT_INTERVAL, INDEXER_PROMOTION_TIMEOUT,
 };
 
+mod anomalies;
+mod archive;
+mod bridges;
+mod canonical;
+mod compact;
+mod deposits;
+mod index;
 mod link;
+mod mixer;
+mod nfts;
+mod notify;
+mod optimize;
+mod peel;
+mod prices;
 mod process;
+mod producers;
+mod prune;
+mod reconcile;
+mod swaps;
 mod sync;
+mod tokens;
+
+// how long to wait before retrying the warehouse once the disk buffer is full
+const WAREHOUSE_RETRY_INTERVAL: u64 = 5;
+
+// how often networks that could not be connected to are retried
+const RECONNECT_INTERVAL: u64 = 30;
 
 #[derive(Clone)]
 pub struct Indexer {
@@ -43,27 +68,120 @@ impl Indexer {
 		}
 
 		loop {
-			self.prune_data().await?;
-
 			let mut set = JoinSet::new();
 			let (tx, rx) = watch::channel(SystemTime::now());
 
+			// @NOTE offline, only extracted files are processed; nothing that needs
+			// an rpc node or the internet (syncing, token and nft metadata,
+			// producer tags, prices) runs
+			let is_offline = self.app.settings.offline;
+
+			if !is_offline {
+				set.spawn({
+					let s = self.clone();
+					let r = rx.clone();
+					async move { s.sync(r).await }
+				});
+			}
+
 			set.spawn({
 				let s = self.clone();
 				let r = rx.clone();
-				async move { s.sync(r).await }
+				async move { s.process(r).await }
 			});
 
 			set.spawn({
 				let s = self.clone();
 				let r = rx.clone();
-				async move { s.process(r).await }
+				async move { s.link(r).await }
+			});
+
+			if !is_offline {
+				set.spawn({
+					let s = self.clone();
+					let r = rx.clone();
+					async move { s.discover_tokens(r).await }
+				});
+			}
+
+			if !is_offline {
+				set.spawn({
+					let s = self.clone();
+					let r = rx.clone();
+					async move { s.resolve_nfts(r).await }
+				});
+			}
+
+			set.spawn({
+				let s = self.clone();
+				let r = rx.clone();
+				async move { s.detect_peel_chains(r).await }
 			});
 
 			set.spawn({
 				let s = self.clone();
 				let r = rx.clone();
-				async move { s.link(r).await }
+				async move { s.detect_mixer_interactions(r).await }
+			});
+
+			set.spawn({
+				let s = self.clone();
+				let r = rx.clone();
+				async move { s.detect_exchange_deposits(r).await }
+			});
+
+			if !is_offline {
+				set.spawn({
+					let s = self.clone();
+					let r = rx.clone();
+					async move { s.label_block_producers(r).await }
+				});
+			}
+
+			if !is_offline {
+				set.spawn({
+					let s = self.clone();
+					let r = rx.clone();
+					async move { s.reconcile_balances(r).await }
+				});
+			}
+
+			if !is_offline {
+				set.spawn({
+					let s = self.clone();
+					let r = rx.clone();
+					async move { s.update_prices(r).await }
+				});
+			}
+
+			set.spawn({
+				let s = self.clone();
+				let r = rx.clone();
+				async move { s.detect_bridge_transfers(r).await }
+			});
+
+			set.spawn({
+				let s = self.clone();
+				let r = rx.clone();
+				async move { s.prune(r).await }
+			});
+
+			set.spawn({
+				let s = self.clone();
+				let r = rx.clone();
+				async move { s.optimize_warehouse(r).await }
+			});
+
+			set.spawn({
+				let s = self.clone();
+				let r = rx.clone();
+				async move { s.archive_warehouse(r).await }
+			});
+
+			set.spawn({
+				let s = self.clone();
+				let r = rx.clone();
+				async move { s.compact_storage(r).await }
 			});
 
 			let ret = tokio::select! {
@@ -128,6 +246,8 @@ impl Indexer {
 				.await?
 				.map(|v| v.updated_at)
 				.unwrap_or_else(utils::now);
+		let mut reconnected_at = utils::now();
+		let mut rpc_updated_at = self.get_rpc_updates().await?;
 
 		loop {
 			match Config::get::<_, u8>(self.app.db(), ConfigKey::NetworksUpdated).await? {
@@ -138,10 +258,50 @@ impl Indexer {
 				_ => {}
 			}
 
+			if self.app.settings.offline {
+				sleep(Duration::from_secs(1)).await;
+				continue;
+			}
+
+			// networks whose rpc endpoint or rps changed are reconnected on their own
+			let latest_rpc_updated_at = self.get_rpc_updates().await?;
+			for (network_id, updated_at) in latest_rpc_updated_at.iter() {
+				if rpc_updated_at.get(network_id) != Some(updated_at) {
+					debug!("Reconnecting… (network {network_id} rpc updated)");
+					self.app.reconnect_network(*network_id).await?;
+				}
+			}
+			rpc_updated_at = latest_rpc_updated_at;
+
+			// retry unreachable networks, and restart once any of them connects
+			if self.app.is_leading() && utils::ago_in_seconds(RECONNECT_INTERVAL) > reconnected_at {
+				let disconnected_count = self.app.get_disconnected_networks().await.len();
+				if disconnected_count > 0 {
+					self.app.connect_networks(true).await?;
+					if self.app.get_disconnected_networks().await.len() < disconnected_count {
+						debug!("Restarting… (networks reconnected)");
+						tx.send(SystemTime::now())?;
+					}
+				}
+
+				reconnected_at = utils::now();
+			}
+
 			sleep(Duration::from_secs(1)).await;
 		}
 	}
 
+	async fn get_rpc_updates(&self) -> Result<HashMap<PrimaryId, DateTime>> {
+		Ok(Config::get_many::<_, u8>(self.app.db(), vec![ConfigKey::NetworkRpcUpdated(0)])
+			.await?
+			.into_iter()
+			.filter_map(|(key, value)| match key {
+				ConfigKey::NetworkRpcUpdated(network_id) => Some((network_id, value.updated_at)),
+				_ => None,
+			})
+			.collect())
+	}
+
 	async fn show_progress(&self) -> Result<()> {
 		let mut started_indexing = false;
 
@@ -171,79 +331,155 @@ impl Indexer {
 		}
 	}
 
-	async fn prune_data(&self) -> Result<()> {
-		// prune all soft-deleted addresses
-		let addresses = Address::get_all_deleted(self.app.db()).await?;
-		if !addresses.is_empty() {
-			// delete all upstream configs
-			Config::delete_many(
-				self.app.db(),
-				addresses
-					.iter()
-					.map(|a| ConfigKey::IndexerLink(a.network_id, a.address_id))
-					.collect(),
-			)
-			.await?;
-
-			// delete all addresses
-			Address::prune_all_where(
-				self.app.db(),
-				AddressColumn::AddressId.is_in(Into::<PrimaryIds>::into(addresses.clone())),
-			)
-			.await?;
-
-			// delete links from warehouse
-			let mut sources: HashMap<PrimaryId, HashSet<String>> = HashMap::new();
-			for address in addresses.into_iter() {
-				if let Some(set) = sources.get_mut(&address.network_id) {
-					set.insert(address.address);
-				} else {
-					sources.insert(address.network_id, HashSet::from([address.address]));
-				}
+	// whether heavy warehouse work is allowed to run right now. windows can
+	// wrap around midnight (eg: 22:00-04:00)
+	fn is_in_maintenance_window(&self) -> bool {
+		let windows = &self.app.settings.maintenance_windows_utc;
+		let now = utils::now().time();
+
+		windows.is_empty() ||
+			windows.iter().any(|(start, end)| match start <= end {
+				true => *start <= now && now < *end,
+				false => *start <= now || now < *end,
+			})
+	}
+
+	// @NOTE push buffered data to the warehouse. if that fails, data stays in
+	// memory up to `buffer_max_records`, after which it's spilled to disk and
+	// replayed (oldest first) on the next successful push. spilled data is
+	// capped at `buffer_max_disk_size`; past that, this keeps retrying, which
+	// holds off extraction until the warehouse is back. returns whether data
+	// is persisted
+	async fn push_warehouse_data(&self, warehouse_data: &mut WarehouseData) -> Result<bool> {
+		let spill_path = utils::project_dir(Some("spill"));
+		let max_spilled_size = self.app.settings.buffer_max_disk_size * 1024 * 1024;
+		trace!(warehouse = "pushing", records = warehouse_data.len());
+
+		loop {
+			let started_at = Instant::now();
+			let result = async {
+				WarehouseData::replay(self.app.warehouse.clone(), &spill_path).await?;
+				warehouse_data.commit(self.app.warehouse.clone()).await
 			}
-			Link::delete_all_by_sources(&self.app.warehouse, sources).await?;
+			.await;
+			self.app.metrics.observe_warehouse_commit(started_at.elapsed(), result.is_ok());
+
+			let Err(e) = result else {
+				return Ok(true);
+			};
+
+			warn!(warehouse = "unavailable", error = e.to_string());
+
+			if warehouse_data.len() <= self.app.settings.buffer_max_records {
+				return Ok(false);
+			}
+
+			if WarehouseData::get_spilled_size(&spill_path)? < max_spilled_size {
+				warn!(warehouse = "spilling", records = warehouse_data.len());
+				warehouse_data.spill(&spill_path)?;
+				return Ok(true);
+			}
+
+			warn!(warehouse = "buffer full", retry_in = WAREHOUSE_RETRY_INTERVAL);
+			sleep(Duration::from_secs(WAREHOUSE_RETRY_INTERVAL)).await;
 		}
+	}
+
+	// @NOTE first phase of a batch commit: a manifest with the markers the
+	// batch moves forward is saved before its data goes to the warehouse, and
+	// callers apply those markers (removing the manifest in the same db
+	// transaction) only once this returns true. a crash in between leaves the
+	// manifest behind with markers untouched, so the blocks are processed again
+	// and some of their rows might get written twice (see `recover_batch`)
+	async fn commit_batch<T: Serialize>(
+		&self,
+		batch_key: ConfigKey,
+		warehouse_data: &mut WarehouseData,
+		markers: &HashMap<ConfigKey, T>,
+	) -> Result<bool> {
+		let batch = StagedBatch {
+			id: Uuid::new_v4().to_string(),
+			records: warehouse_data.len(),
+			markers: markers.iter().map(|(k, v)| (k.to_string(), json!(v))).collect(),
+		};
+		Config::set::<_, StagedBatch>(self.app.db(), batch_key, batch).await?;
+
+		self.push_warehouse_data(warehouse_data).await
+	}
 
-		// prune all soft-deleted entities
-		Entity::prune_all(self.app.db()).await?;
-
-		// prune all soft-deleted networks
-		let deleted_networks = Network::get_all_existing(self.app.db(), Some(true)).await?;
-		if !deleted_networks.is_empty() {
-			let network_ids: PrimaryIds = deleted_networks.clone().into();
-
-			// delete all associated configs
-			Config::delete_all_by_keywords(
-				self.app.db(),
-				deleted_networks.clone().iter().map(|n| format!("n{}", n.network_id)).collect(),
-			)
-			.await?;
-
-			// delete all addresses
-			Address::prune_all_where(
-				self.app.db(),
-				AddressColumn::NetworkId.is_in(network_ids.clone()),
-			)
-			.await?;
-
-			// delete from warehouse
-			let (transfers_deleted, balances_deleted, amounts_deleted, links_deleted) = tokio::join!(
-				Transfer::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
-				Balance::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
-				Amount::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
-				Link::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+	// @NOTE a manifest still around on startup is a batch that was never
+	// confirmed, so any part of it might have landed. rows written again
+	// replace the ones already there, but the views summed up from them would
+	// count them twice. so the blocks its markers would have moved past are
+	// rewound (for the modules they were processed for) before they're
+	// processed again
+	async fn recover_batch(&self, batch_key: ConfigKey) -> Result<()> {
+		if let Some(batch) = Config::get::<_, StagedBatch>(self.app.db(), batch_key).await? {
+			warn!(
+				batch = "unconfirmed",
+				id = %batch.value.id,
+				records = batch.value.records,
+				markers = batch.value.markers.len(),
 			);
 
-			transfers_deleted.and(balances_deleted).and(amounts_deleted).and(links_deleted)?;
+			for (key, value) in batch.value.markers.into_iter() {
+				let Ok(config_key) = ConfigKey::from_str(&key) else {
+					continue;
+				};
 
-			// finally delete only the networks we grabbed earlier
-			Network::prune_all_where(self.app.db(), NetworkColumn::NetworkId.is_in(network_ids))
-				.await?;
+				if let Some((nid, block_range, mids)) =
+					self.get_unconfirmed_range(config_key, value).await?
+				{
+					Block::rewind_modules(&self.app.warehouse, nid, block_range, &mids).await?;
+				}
+			}
+
+			Config::delete(self.app.db(), batch_key).await?;
 		}
 
 		Ok(())
 	}
 
+	// blocks a staged marker moves past (from where its confirmed value is),
+	// and the modules they're processed for (all of them when empty)
+	async fn get_unconfirmed_range(
+		&self,
+		config_key: ConfigKey,
+		value: JsonValue,
+	) -> Result<Option<(PrimaryId, (BlockHeight, BlockHeight), Vec<u16>)>> {
+		let db = self.app.db();
+
+		let (nid, confirmed, staged, mids) = match config_key {
+			ConfigKey::IndexerProcessTail(nid) => {
+				let confirmed = Config::get::<_, BlockHeight>(db, config_key).await?;
+				let staged = json_parse::<BlockHeight>(value)?;
+				(nid, Some(confirmed.map(|h| h.value).unwrap_or(0)), staged, vec![])
+			}
+			ConfigKey::IndexerProcessChunk(nid, _) | ConfigKey::IndexerProcessModule(nid, _) => {
+				let confirmed =
+					Config::get::<_, (BlockHeight, BlockHeight)>(db, config_key).await?;
+				let mids = match config_key {
+					ConfigKey::IndexerProcessModule(_, mid) => vec![mid],
+					_ => vec![],
+				};
+				let (staged, _) = json_parse::<(BlockHeight, BlockHeight)>(value)?;
+				(nid, confirmed.map(|h| h.value.0), staged, mids)
+			}
+			ConfigKey::IndexerProcessBackfill(nid, _) => {
+				let confirmed =
+					Config::get::<_, (BlockHeight, BlockHeight, Vec<u16>)>(db, config_key).await?;
+				let (staged, _, mids) = json_parse::<(BlockHeight, BlockHeight, Vec<u16>)>(value)?;
+				(nid, confirmed.map(|h| h.value.0), staged, mids)
+			}
+			_ => return Ok(None),
+		};
+
+		Ok(match confirmed {
+			Some(confirmed) if confirmed < staged => Some((nid, (confirmed + 1, staged), mids)),
+			_ => None,
+		})
+	}
+
 	async fn get_updated_block_height(
 		&self,
 		network_id: PrimaryId,
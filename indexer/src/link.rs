@@ -10,9 +10,9 @@ use tokio::{
 	task::JoinSet,
 	time::{sleep, Duration},
 };
-use tracing::{debug, trace};
+use tracing::{debug, warn};
 
-use crate::Indexer;
+use crate::{index::AddressIndex, Indexer};
 use barreleye_common::{
 	chain::WarehouseData,
 	models::{
@@ -59,8 +59,11 @@ impl IndexedLinks {
 impl Indexer {
 	pub async fn link(&self, mut networks_updated: Receiver<SystemTime>) -> Result<()> {
 		let mut warehouse_data = WarehouseData::new();
+		let mut address_index = AddressIndex::new();
 		let mut config_key_map = HashMap::<ConfigKey, BlockHeight>::new();
+		let mut webhook_events = vec![];
 		let mut blocked_and_notified = false;
+		let mut is_recovered = false;
 
 		'indexing: loop {
 			if !self.app.is_leading() {
@@ -68,6 +71,11 @@ impl Indexer {
 				continue;
 			}
 
+			if !is_recovered {
+				self.recover_batch(ConfigKey::IndexerLinkBatch).await?;
+				is_recovered = true;
+			}
+
 			// skip network if "process" step is not done yet
 			let mut networks = vec![];
 			for network in Network::get_all_existing(self.app.db(), Some(false)).await?.into_iter()
@@ -90,6 +98,13 @@ impl Indexer {
 				}
 			}
 
+			// wrapping contracts hold funds on behalf of whoever wrapped them, so
+			// links don't go through them
+			let wrapped_native_tokens_map = networks
+				.iter()
+				.map(|n| (n.network_id, n.get_wrapped_native_tokens().into_iter().collect()))
+				.collect::<HashMap<PrimaryId, HashSet<String>>>();
+
 			// create a map of `network_id` -> `latest_processed_block`
 			let block_height_map = {
 				let map = networks
@@ -117,19 +132,33 @@ impl Indexer {
 				blocked_and_notified = false;
 			}
 
+			// a rollback moves the processed tail back (see
+			// `rollback_processed_blocks`), so links past it are dropped and
+			// those blocks get linked again
+			for (config_key, block_height) in config_key_map.iter_mut() {
+				if let ConfigKey::IndexerLink(network_id, _) = config_key {
+					if let Some(&latest_block_height) = block_height_map.get(network_id) {
+						*block_height = cmp::min(*block_height, latest_block_height);
+					}
+				}
+			}
+			warehouse_data.links.retain(|l| {
+				block_height_map
+					.get(&(l.network_id as PrimaryId))
+					.is_none_or(|&latest_block_height| l.block_height <= latest_block_height)
+			});
+
 			// break the link chains that contain newly added addresses in the
 			// middle
 			let network_ids: PrimaryIds =
 				block_height_map.clone().into_keys().collect::<Vec<PrimaryId>>().into();
-			self.break_in_new_addresses(network_ids.clone()).await?;
+			if self.break_in_new_addresses(network_ids.clone()).await? {
+				address_index.invalidate();
+			}
 
-			// fetch all addresses
-			let addresses =
-				Address::get_all_by_network_ids(self.app.db(), network_ids, Some(false)).await?;
-			let all_entity_addresses = addresses
-				.iter()
-				.map(|a| (a.network_id, a.address.clone()))
-				.collect::<HashSet<(PrimaryId, String)>>();
+			// refresh labeled addresses (only reloads if they changed)
+			address_index.refresh(self.app.db()).await?;
+			let addresses = address_index.get_addresses(&network_ids);
 			if addresses.is_empty() {
 				debug!("No addresses to link");
 				sleep(Duration::from_secs(10)).await;
@@ -183,17 +212,9 @@ impl Indexer {
 						is_caught_up = false;
 					}
 
-					let network_entity_addresses =
-						all_entity_addresses
-							.iter()
-							.filter_map(|(nid, address)| {
-								if *nid == network_id {
-									Some(address.clone())
-								} else {
-									None
-								}
-							})
-							.collect::<HashSet<String>>();
+					let network_entity_addresses = address_index.get(network_id);
+					let wrapped_native_tokens =
+						wrapped_native_tokens_map.get(&network_id).cloned().unwrap_or_default();
 
 					futures.spawn({
 						let uncommitted_links = warehouse_data
@@ -221,14 +242,17 @@ impl Indexer {
 							);
 
 							// process transfers for a range of blocks
-							for transfer in Transfer::get_all_by_block_range(
+							for transfer in Transfer::get_all_by_block_range_excluding_fees(
 								&warehouse,
 								network_id,
 								(min_block_height, max_block_height),
 							)
 							.await?
 							.into_iter()
-							{
+							.filter(|t| {
+								!wrapped_native_tokens.contains(&t.from_address) &&
+									!wrapped_native_tokens.contains(&t.to_address)
+							}) {
 								if indexed_links.contains(&transfer.from_address) {
 									let mut new_links = vec![];
 
@@ -302,6 +326,12 @@ impl Indexer {
 					result = futures.join_next() => {
 						if let Some(res) = result {
 							if let Ok((config_key, block_height, new_warehouse_data)) = res? {
+								// activity on tagged entities, for webhooks once it's committed
+								match self.get_webhook_events(&new_warehouse_data).await {
+									Ok(events) => webhook_events.extend(events),
+									Err(e) => warn!(webhooks = "skipped", error = e.to_string()),
+								}
+
 								warehouse_data += new_warehouse_data;
 								config_key_map.insert(config_key, block_height);
 							}
@@ -315,15 +345,31 @@ impl Indexer {
 			// commit if collected enough
 			if self.app.is_leading() {
 				// push to warehouse
+				let mut is_pushed = true;
 				if warehouse_data.should_commit(is_caught_up) {
-					trace!(warehouse = "pushing", records = warehouse_data.len());
-					warehouse_data.commit(self.app.warehouse.clone()).await?;
+					is_pushed = self
+						.commit_batch(
+							ConfigKey::IndexerLinkBatch,
+							&mut warehouse_data,
+							&config_key_map,
+						)
+						.await?;
+
+					// let webhooks know about activity on tagged entities
+					if is_pushed {
+						if let Err(e) = self.notify_webhooks(&webhook_events).await {
+							warn!(webhooks = "skipped", error = e.to_string());
+						}
+						webhook_events.clear();
+					}
 				}
 
-				// commit config marker updates
-				if is_caught_up {
-					Config::set_many::<_, BlockHeight>(self.app.db(), config_key_map.clone())
-						.await?;
+				// commit config marker updates (and confirm batch)
+				if is_caught_up && is_pushed {
+					let db_tx = self.app.db_tx().await?;
+					Config::set_many::<_, BlockHeight>(&db_tx, config_key_map.clone()).await?;
+					Config::delete(&db_tx, ConfigKey::IndexerLinkBatch).await?;
+					db_tx.commit().await?;
 					config_key_map.clear();
 				}
 			}
@@ -335,7 +381,7 @@ impl Indexer {
 		}
 	}
 
-	async fn break_in_new_addresses(&self, network_ids: PrimaryIds) -> Result<()> {
+	async fn break_in_new_addresses(&self, network_ids: PrimaryIds) -> Result<bool> {
 		// get all newly added addresses for the provided networks
 		let address_ids = Config::get_many::<_, PrimaryId>(
 			self.app.db(),
@@ -375,8 +421,10 @@ impl Indexer {
 					.collect(),
 			)
 			.await?;
+
+			return Ok(true);
 		}
 
-		Ok(())
+		Ok(false)
 	}
 }
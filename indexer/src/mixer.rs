@@ -0,0 +1,183 @@
+use eyre::Result;
+use std::{
+	cmp,
+	collections::{HashMap, HashSet},
+	time::SystemTime,
+};
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::{debug, warn};
+
+use crate::Indexer;
+use barreleye_common::{
+	chain::U256,
+	models::{Annotation, Config, ConfigKey, PrimaryId, Tag, Transfer},
+	AnnotationKind, BlockHeight,
+};
+
+const BLOCKS_PER_LOOP: BlockHeight = 100;
+
+// how far back to look for a deposit matching a withdrawal
+const MATCH_WINDOW_BLOCKS: BlockHeight = 50_000;
+
+// max share of a deposit that relayer fees could take from a withdrawal
+const MAX_FEE_PERCENT: u64 = 5;
+
+impl Indexer {
+	// @NOTE annotates addresses that deposit into or withdraw from addresses
+	// of entities tagged as mixers. a withdrawal is attributed to a depositor
+	// only when exactly one deposit in the window matches its denomination
+	pub async fn detect_mixer_interactions(
+		&self,
+		mut networks_updated: Receiver<SystemTime>,
+	) -> Result<()> {
+		loop {
+			if !self.app.is_leading() {
+				sleep(Duration::from_secs(1)).await;
+				continue;
+			}
+
+			// failures (eg: the warehouse being unreachable) are retried on the
+			// next run, without holding up other networks
+			let mut mixers = HashMap::<PrimaryId, HashSet<String>>::new();
+			match Tag::get_all_mixer_addresses(self.app.db()).await {
+				Ok(addresses) => {
+					for address in addresses.into_iter() {
+						mixers.entry(address.network_id).or_default().insert(address.address);
+					}
+				}
+				Err(e) => warn!(mixer_interactions = "failed", error = e.to_string()),
+			}
+
+			let mut is_caught_up = true;
+			for (nid, mixer_addresses) in mixers.into_iter() {
+				match self.detect_network_mixer_interactions(nid, &mixer_addresses).await {
+					Ok(is_network_caught_up) => is_caught_up &= is_network_caught_up,
+					Err(e) => warn!(
+						network_id = nid,
+						mixer_interactions = "failed",
+						error = e.to_string()
+					),
+				}
+			}
+
+			let pause = if is_caught_up { 10 } else { 0 };
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(pause)) => {}
+			}
+		}
+	}
+
+	async fn detect_network_mixer_interactions(
+		&self,
+		nid: PrimaryId,
+		mixer_addresses: &HashSet<String>,
+	) -> Result<bool> {
+		// skip network if "process" step is not done yet
+		let processed_block_height =
+			Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerProcessTail(nid))
+				.await?
+				.map(|v| v.value)
+				.unwrap_or(0);
+		let process_step_synced = Config::get_many::<_, (BlockHeight, BlockHeight)>(
+			self.app.db(),
+			vec![ConfigKey::IndexerProcessChunk(nid, 0), ConfigKey::IndexerProcessModule(nid, 0)],
+		)
+		.await?
+		.is_empty();
+		if processed_block_height == 0 || !process_step_synced {
+			return Ok(true);
+		}
+
+		let block_height =
+			Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerMixer(nid))
+				.await?
+				.map(|v| v.value)
+				.unwrap_or(0);
+		if block_height >= processed_block_height {
+			return Ok(true);
+		}
+
+		let block_height_min = block_height + 1;
+		let block_height_max = cmp::min(block_height + BLOCKS_PER_LOOP, processed_block_height);
+
+		let transfers = Transfer::get_all_by_block_range_excluding_fees(
+			&self.app.warehouse,
+			nid,
+			(block_height_min, block_height_max),
+		)
+		.await?;
+
+		let mut annotations = vec![];
+		for transfer in transfers.into_iter() {
+			if mixer_addresses.contains(&transfer.to_address) &&
+				!mixer_addresses.contains(&transfer.from_address)
+			{
+				annotations.push(Annotation::new_model(
+					nid,
+					&transfer.from_address,
+					AnnotationKind::MixerDeposit,
+					&transfer.to_address,
+				));
+			} else if mixer_addresses.contains(&transfer.from_address) &&
+				!mixer_addresses.contains(&transfer.to_address)
+			{
+				let depositor = self.match_mixer_deposit(nid, &transfer).await?;
+				annotations.push(Annotation::new_model(
+					nid,
+					&transfer.to_address,
+					AnnotationKind::MixerWithdrawal,
+					&depositor.unwrap_or(transfer.from_address.clone()),
+				));
+			}
+		}
+
+		if !annotations.is_empty() {
+			Annotation::create_many(self.app.db(), annotations).await?;
+		}
+
+		Config::set::<_, BlockHeight>(
+			self.app.db(),
+			ConfigKey::IndexerMixer(nid),
+			block_height_max,
+		)
+		.await?;
+
+		Ok(block_height_max >= processed_block_height)
+	}
+
+	// depositor of the only earlier deposit into the same mixer whose amount
+	// covers the withdrawal plus a plausible relayer fee
+	async fn match_mixer_deposit(
+		&self,
+		network_id: PrimaryId,
+		withdrawal: &Transfer,
+	) -> Result<Option<String>> {
+		let amount = withdrawal.relative_amount;
+		let max_amount = amount.saturating_mul(U256::from(100)) / U256::from(100 - MAX_FEE_PERCENT);
+
+		let deposits = Transfer::get_all_by_to_address_and_min_amount(
+			&self.app.warehouse,
+			network_id,
+			&withdrawal.from_address,
+			amount,
+			(withdrawal.block_height.saturating_sub(MATCH_WINDOW_BLOCKS), withdrawal.block_height),
+		)
+		.await?
+		.into_iter()
+		.filter(|d| d.relative_amount <= max_amount)
+		.map(|d| d.from_address)
+		.collect::<HashSet<String>>();
+
+		Ok(match deposits.len() {
+			1 => deposits.into_iter().next(),
+			_ => None,
+		})
+	}
+}
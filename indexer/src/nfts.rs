@@ -0,0 +1,288 @@
+use base64::{engine::general_purpose, Engine as _};
+use eyre::{bail, Result};
+use reqwest::{
+	dns::{Addrs, Name, Resolve, Resolving},
+	redirect, Url,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+	collections::{HashMap, HashSet},
+	net::{IpAddr, SocketAddr},
+	sync::Arc,
+	time::SystemTime,
+};
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration, Instant},
+};
+use tracing::debug;
+
+use crate::Indexer;
+use barreleye_common::{
+	chain::WarehouseData,
+	models::{set, BasicModel, Nft, NftActiveModel, PrimaryId},
+	utils,
+};
+
+const NFTS_PER_LOOP: u64 = 25;
+const MAX_ATTEMPTS: i32 = 5;
+const RETRY_AFTER_SECS: u64 = 60 * 60;
+const DEAD_HOST_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_METADATA_SIZE: usize = 256 * 1024;
+
+#[derive(Deserialize)]
+struct NftMetadata {
+	name: Option<String>,
+	description: Option<String>,
+	image: Option<String>,
+}
+
+// @NOTE token uris come from arbitrary contracts, so hosts that resolve to
+// private, loopback or link-local addresses are refused. resolving happens
+// here (instead of checking upfront) so the checked address is the one that
+// gets connected to
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+	fn resolve(&self, name: Name) -> Resolving {
+		Box::pin(async move {
+			let addrs = tokio::net::lookup_host((name.as_str(), 0))
+				.await?
+				.filter(|addr| is_public_ip(addr.ip()))
+				.collect::<Vec<SocketAddr>>();
+
+			if addrs.is_empty() {
+				return Err(format!("no public address for {}", name.as_str()).into());
+			}
+
+			Ok(Box::new(addrs.into_iter()) as Addrs)
+		})
+	}
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+	match ip {
+		IpAddr::V4(ip) => {
+			let octets = ip.octets();
+			!(ip.is_private() ||
+				ip.is_loopback() ||
+				ip.is_link_local() ||
+				ip.is_unspecified() ||
+				ip.is_broadcast() ||
+				ip.is_multicast() ||
+				ip.is_documentation() ||
+				octets[0] == 0 ||
+				(octets[0] == 100 && (octets[1] & 0xc0) == 64))
+		}
+		IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+			Some(ip) => is_public_ip(IpAddr::V4(ip)),
+			_ => {
+				let segment = ip.segments()[0];
+				!(ip.is_loopback() ||
+					ip.is_unspecified() ||
+					ip.is_multicast() ||
+					(segment & 0xfe00) == 0xfc00 ||
+					(segment & 0xffc0) == 0xfe80)
+			}
+		},
+	}
+}
+
+impl Indexer {
+	// insert unresolved records for nfts that haven't been seen before
+	pub async fn register_nfts(
+		&self,
+		warehouse_data: &WarehouseData,
+		known_nfts: &mut HashSet<(PrimaryId, String, String)>,
+	) -> Result<()> {
+		let new_nfts = warehouse_data
+			.nfts
+			.iter()
+			.filter(|k| !known_nfts.contains(*k))
+			.cloned()
+			.collect::<HashSet<_>>();
+
+		if !new_nfts.is_empty() {
+			Nft::create_many(
+				self.app.db(),
+				new_nfts
+					.iter()
+					.map(|(network_id, address, token_id)| {
+						Nft::new_model(*network_id, address, token_id)
+					})
+					.collect(),
+			)
+			.await?;
+
+			known_nfts.extend(new_nfts);
+		}
+
+		Ok(())
+	}
+
+	// resolve `tokenURI` metadata for unresolved nfts
+	pub async fn resolve_nfts(&self, mut networks_updated: Receiver<SystemTime>) -> Result<()> {
+		// the ipfs gateway is configured by us (and is often a local node), so
+		// only urls that come from contracts go through the restricted client
+		let gateway_client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+		let client = reqwest::Client::builder()
+			.timeout(REQUEST_TIMEOUT)
+			.redirect(redirect::Policy::none())
+			.dns_resolver(Arc::new(PublicResolver))
+			.build()?;
+		let rate_limiter = utils::get_rate_limiter(self.app.settings.nft_metadata_rps);
+
+		// hosts that recently failed are skipped for a while, without counting
+		// it as an attempt
+		let mut dead_hosts = HashMap::<String, Instant>::new();
+
+		loop {
+			if !self.app.is_leading() {
+				sleep(Duration::from_secs(1)).await;
+				continue;
+			}
+
+			dead_hosts.retain(|_, failed_at| failed_at.elapsed() < DEAD_HOST_TIMEOUT);
+
+			let nfts = Nft::get_all_unresolved(
+				self.app.db(),
+				MAX_ATTEMPTS,
+				utils::ago_in_seconds(RETRY_AFTER_SECS),
+				NFTS_PER_LOOP,
+			)
+			.await?;
+
+			for nft in nfts.into_iter() {
+				let chain = match self.app.networks.read().await.get(&nft.network_id) {
+					Some(chain) => chain.clone(),
+					_ => continue,
+				};
+
+				let uri = match nft.uri.clone() {
+					Some(uri) => Some(uri),
+					_ => chain.get_nft_uri(&nft.address, &nft.token_id).await.ok().flatten(),
+				};
+
+				let mut data = NftActiveModel {
+					attempts: set(nft.attempts + 1),
+					uri: set(uri.clone()),
+					..Default::default()
+				};
+
+				if let Some(uri) = uri {
+					let url = self.get_metadata_url(&uri);
+					let host = url
+						.as_deref()
+						.and_then(|u| reqwest::Url::parse(u).ok())
+						.and_then(|u| u.host_str().map(|h| h.to_string()));
+
+					if host.as_ref().is_some_and(|h| dead_hosts.contains_key(h)) {
+						continue;
+					}
+
+					if let Some(rate_limiter) = &rate_limiter {
+						rate_limiter.until_ready().await;
+					}
+
+					let client = match uri.starts_with("ipfs://") {
+						true => &gateway_client,
+						_ => &client,
+					};
+
+					match self.fetch_metadata(client, &uri, url).await {
+						Ok(metadata) => {
+							let collection_name =
+								chain.get_collection_name(&nft.address).await.ok().flatten();
+
+							data.collection_name = set(collection_name);
+							data.name = set(metadata.name);
+							data.description = set(metadata.description);
+							data.image =
+								set(metadata.image.map(|v| self.get_metadata_url(&v).unwrap_or(v)));
+							data.is_resolved = set(true);
+						}
+						Err(e) => {
+							debug!(
+								nft = nft.id,
+								uri,
+								error = e.to_string(),
+								"Could not fetch metadata"
+							);
+							if let Some(host) = host {
+								dead_hosts.insert(host, Instant::now());
+							}
+						}
+					}
+				}
+
+				Nft::update_by_id(self.app.db(), &nft.id, data).await?;
+			}
+
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(10)) => {}
+			}
+		}
+	}
+
+	// http(s) url to fetch for a token uri (none for inline data uris)
+	fn get_metadata_url(&self, uri: &str) -> Option<String> {
+		if let Some(path) = uri.strip_prefix("ipfs://") {
+			let path = path.trim_start_matches("ipfs/");
+			let gateway = self.app.settings.ipfs_gateway.trim_end_matches('/');
+			Some(format!("{gateway}/{path}"))
+		} else if uri.starts_with("http://") || uri.starts_with("https://") {
+			Some(uri.to_string())
+		} else {
+			None
+		}
+	}
+
+	async fn fetch_metadata(
+		&self,
+		client: &reqwest::Client,
+		uri: &str,
+		url: Option<String>,
+	) -> Result<NftMetadata> {
+		if let Some(url) = url {
+			return fetch_json(client, &url).await;
+		}
+
+		// inline `data:application/json;base64,...` uris
+		match uri.split_once(',') {
+			Some((prefix, data)) if prefix.ends_with(";base64") => {
+				Ok(serde_json::from_slice(&general_purpose::STANDARD.decode(data)?)?)
+			}
+			Some((_, data)) => Ok(serde_json::from_str(data)?),
+			_ => Err(eyre::eyre!("unsupported uri")),
+		}
+	}
+}
+
+// fetch json, reading no more than `MAX_METADATA_SIZE` bytes of it
+async fn fetch_json<T: DeserializeOwned>(client: &reqwest::Client, url: &str) -> Result<T> {
+	// ip hosts are connected to directly, without going through the resolver
+	let url = Url::parse(url)?;
+	if let Some(ip) = url.host_str().and_then(|h| h.trim_matches(['[', ']']).parse::<IpAddr>().ok())
+	{
+		if !is_public_ip(ip) {
+			bail!("refusing to fetch from non-public address: {ip}");
+		}
+	}
+
+	let mut response = client.get(url).send().await?.error_for_status()?;
+
+	let mut body = vec![];
+	while let Some(chunk) = response.chunk().await? {
+		if body.len() + chunk.len() > MAX_METADATA_SIZE {
+			bail!("metadata is over {MAX_METADATA_SIZE} bytes");
+		}
+		body.extend_from_slice(&chunk);
+	}
+
+	Ok(serde_json::from_slice(&body)?)
+}
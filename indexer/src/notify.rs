@@ -0,0 +1,253 @@
+use eyre::Result;
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use serde_json::json;
+use std::{
+	collections::{HashMap, HashSet},
+	time::SystemTime,
+};
+use tokio::time::{sleep, Duration};
+use tracing::warn;
+
+use crate::Indexer;
+use barreleye_common::{
+	chain::WarehouseData,
+	models::{Address, BasicModel, Entity, PrimaryId, Tag, Webhook},
+	utils,
+};
+
+// older activity is history being caught up on, which nobody is waiting for
+const MAX_AGE_SECS: u64 = 60 * 60;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: u64 = 1_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Event {
+	#[serde(rename = "type")]
+	kind: String,
+	network: String,
+	tx_hash: Option<String>,
+	block_height: u64,
+	from: String,
+	to: String,
+	asset_address: Option<String>,
+	amount: Option<String>,
+	created_at: u32,
+	entities: Vec<EventEntity>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EventEntity {
+	id: String,
+	name: Option<String>,
+	address: String,
+	tags: Vec<String>,
+}
+
+impl Indexer {
+	// recent transfers and links that touch an address of a tagged entity, to
+	// be sent with `notify_webhooks()` once they're committed
+	pub(crate) async fn get_webhook_events(
+		&self,
+		warehouse_data: &WarehouseData,
+	) -> Result<Vec<Event>> {
+		if Webhook::get_all(self.app.db()).await?.is_empty() {
+			return Ok(vec![]);
+		}
+
+		let min_created_at = (SystemTime::now()
+			.duration_since(SystemTime::UNIX_EPOCH)?
+			.as_secs()
+			.saturating_sub(MAX_AGE_SECS)) as u32;
+
+		let transfers = warehouse_data
+			.transfers
+			.iter()
+			.filter(|t| t.created_at >= min_created_at)
+			.collect::<Vec<_>>();
+		let links = warehouse_data
+			.links
+			.iter()
+			.filter(|l| l.created_at >= min_created_at)
+			.collect::<Vec<_>>();
+		if transfers.is_empty() && links.is_empty() {
+			return Ok(vec![]);
+		}
+
+		// find tracked addresses
+		let mut addresses = HashSet::new();
+		for (from, to) in transfers
+			.iter()
+			.map(|t| (&t.from_address, &t.to_address))
+			.chain(links.iter().map(|l| (&l.from_address, &l.to_address)))
+		{
+			addresses.insert(from.clone());
+			addresses.insert(to.clone());
+		}
+		addresses.remove("");
+
+		let tracked = Address::get_all_by_addresses(
+			self.app.db(),
+			addresses.into_iter().collect(),
+			Some(false),
+		)
+		.await?;
+		if tracked.is_empty() {
+			return Ok(vec![]);
+		}
+
+		// only entities with tags are worth a notification
+		let entity_ids = tracked.iter().map(|a| a.entity_id).collect::<HashSet<_>>();
+		let mut entity_tags = HashMap::<PrimaryId, Vec<String>>::new();
+		for tag in Tag::get_all_by_entity_ids(
+			self.app.db(),
+			entity_ids.into_iter().collect::<Vec<_>>().into(),
+		)
+		.await?
+		.into_iter()
+		{
+			entity_tags.entry(tag.entity_id).or_default().push(tag.id);
+		}
+		if entity_tags.is_empty() {
+			return Ok(vec![]);
+		}
+
+		let entities = Entity::get_all_by_entity_ids(
+			self.app.db(),
+			entity_tags.keys().copied().collect::<Vec<_>>().into(),
+			Some(false),
+		)
+		.await?
+		.into_iter()
+		.map(|e| (e.entity_id, e))
+		.collect::<HashMap<_, _>>();
+
+		let mut tracked_map = HashMap::<(PrimaryId, String), Vec<EventEntity>>::new();
+		for address in tracked.into_iter() {
+			if let (Some(entity), Some(tags)) =
+				(entities.get(&address.entity_id), entity_tags.get(&address.entity_id))
+			{
+				tracked_map.entry((address.network_id, address.address.clone())).or_default().push(
+					EventEntity {
+						id: entity.id.clone(),
+						name: entity.name.clone(),
+						address: address.address,
+						tags: tags.clone(),
+					},
+				);
+			}
+		}
+
+		let get_entities = |network_id: u64, from: &str, to: &str| {
+			[from, to]
+				.into_iter()
+				.filter_map(|a| tracked_map.get(&(network_id as PrimaryId, a.to_string())))
+				.flatten()
+				.cloned()
+				.collect::<Vec<_>>()
+		};
+
+		let networks = self
+			.app
+			.networks
+			.read()
+			.await
+			.iter()
+			.map(|(nid, chain)| (*nid as u64, chain.get_network().id))
+			.collect::<HashMap<_, _>>();
+
+		// build events
+		let mut events = vec![];
+		for transfer in transfers.into_iter() {
+			let entities =
+				get_entities(transfer.network_id, &transfer.from_address, &transfer.to_address);
+			if let Some(network) =
+				networks.get(&transfer.network_id).filter(|_| !entities.is_empty())
+			{
+				events.push(Event {
+					kind: "transfer".to_string(),
+					network: network.clone(),
+					tx_hash: Some(transfer.tx_hash.clone()),
+					block_height: transfer.block_height,
+					from: transfer.from_address.clone(),
+					to: transfer.to_address.clone(),
+					asset_address: Some(transfer.asset_address.clone()).filter(|a| !a.is_empty()),
+					amount: Some(transfer.relative_amount.to_string()),
+					created_at: transfer.created_at,
+					entities,
+				});
+			}
+		}
+		for link in links.into_iter() {
+			let entities = get_entities(link.network_id, &link.from_address, &link.to_address);
+			if let Some(network) = networks.get(&link.network_id).filter(|_| !entities.is_empty()) {
+				events.push(Event {
+					kind: "link".to_string(),
+					network: network.clone(),
+					tx_hash: None,
+					block_height: link.block_height,
+					from: link.from_address.clone(),
+					to: link.to_address.clone(),
+					asset_address: None,
+					amount: None,
+					created_at: link.created_at,
+					entities,
+				});
+			}
+		}
+
+		Ok(events)
+	}
+
+	// @NOTE posts `events` to every registered webhook. delivery happens in the
+	// background and is retried with backoff, but it's best-effort: events of a
+	// failed delivery are dropped
+	pub(crate) async fn notify_webhooks(&self, events: &[Event]) -> Result<()> {
+		if events.is_empty() {
+			return Ok(());
+		}
+
+		let webhooks = Webhook::get_all(self.app.db()).await?;
+		if webhooks.is_empty() {
+			return Ok(());
+		}
+
+		let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+		let body = json!({ "events": events }).to_string();
+		for webhook in webhooks.into_iter() {
+			let client = client.clone();
+			let body = body.clone();
+
+			tokio::spawn(async move {
+				for attempt in 0..RETRY_ATTEMPTS {
+					let timestamp = utils::now().and_utc().timestamp() as u64;
+					let mut request = client
+						.post(&webhook.url)
+						.header(CONTENT_TYPE, "application/json")
+						.header("X-Barreleye-Timestamp", timestamp.to_string());
+					if let Some(signature) = webhook.get_signature(timestamp, &body) {
+						request = request.header("X-Barreleye-Signature", signature);
+					}
+
+					match request.body(body.clone()).send().await {
+						Ok(response) if response.status().is_success() => return,
+						_ if attempt + 1 < RETRY_ATTEMPTS => {
+							sleep(Duration::from_millis(INITIAL_BACKOFF * 2_u64.pow(attempt)))
+								.await;
+						}
+						Ok(response) => {
+							warn!(webhook = webhook.id, status = response.status().to_string());
+						}
+						Err(e) => warn!(webhook = webhook.id, error = e.to_string()),
+					}
+				}
+			});
+		}
+
+		Ok(())
+	}
+}
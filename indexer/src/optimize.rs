@@ -0,0 +1,100 @@
+use eyre::Result;
+use std::time::{Instant, SystemTime};
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::{debug, info, warn};
+
+use crate::Indexer;
+use barreleye_common::{
+	models::{
+		AddressActivityTable, AmountTable, ApprovalTable, BalanceSnapshotTable, BalanceTable,
+		BlockTable, Config, ConfigKey, DecodedCallTable, FeeTable, FunderTable, LinkTable,
+		NetworkStatsTable, PriceTable, StakingDepositTable, TransferTable, UserOperationTable,
+	},
+	utils,
+	warehouse::Driver as WarehouseDriver,
+};
+
+// replacing tables, followed by the summing/aggregating views built off them
+static TABLES: &[&str] = &[
+	TransferTable,
+	AmountTable,
+	LinkTable,
+	DecodedCallTable,
+	UserOperationTable,
+	FeeTable,
+	BlockTable,
+	StakingDepositTable,
+	ApprovalTable,
+	PriceTable,
+	BalanceTable,
+	BalanceSnapshotTable,
+	NetworkStatsTable,
+	FunderTable,
+	AddressActivityTable,
+];
+
+impl Indexer {
+	// @NOTE duplicate rows (eg: from re-processed blocks) only collapse when
+	// clickhouse merges parts on its own schedule, and until then they inflate
+	// links and balances. this forces a full merge every
+	// `warehouse_optimize_interval` seconds, within `maintenance_windows`
+	pub async fn optimize_warehouse(
+		&self,
+		mut networks_updated: Receiver<SystemTime>,
+	) -> Result<()> {
+		let settings = &self.app.settings;
+		let is_enabled = settings.warehouse_driver == WarehouseDriver::ClickHouse &&
+			settings.warehouse_optimize_interval > 0;
+
+		loop {
+			if is_enabled && self.app.is_leading() && self.is_optimize_due().await? {
+				// a table that fails is skipped until the next run
+				let started_at = Instant::now();
+				let mut optimized = 0;
+				for table in TABLES.iter() {
+					let table_started_at = Instant::now();
+					if let Err(e) = self.app.warehouse.optimize(table).await {
+						warn!(table, error = e.to_string());
+						continue;
+					}
+
+					optimized += 1;
+					debug!(
+						table,
+						duration_ms = table_started_at.elapsed().as_millis() as u64,
+						"Optimized table"
+					);
+				}
+
+				let duration_ms = started_at.elapsed().as_millis() as u64;
+				info!(tables = optimized, duration_ms, "Optimized warehouse");
+
+				Config::set::<_, u64>(self.app.db(), ConfigKey::IndexerOptimize, duration_ms)
+					.await?;
+			}
+
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(60)) => {}
+			}
+		}
+	}
+
+	async fn is_optimize_due(&self) -> Result<bool> {
+		if !self.is_in_maintenance_window() {
+			return Ok(false);
+		}
+
+		let interval = self.app.settings.warehouse_optimize_interval;
+		Ok(match Config::get::<_, u64>(self.app.db(), ConfigKey::IndexerOptimize).await? {
+			Some(v) => v.updated_at <= utils::ago_in_seconds(interval),
+			_ => true,
+		})
+	}
+}
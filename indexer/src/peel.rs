@@ -0,0 +1,270 @@
+use eyre::Result;
+use sea_orm::Set;
+use std::{
+	cmp,
+	collections::{HashMap, HashSet},
+	time::SystemTime,
+};
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::{debug, warn};
+
+use crate::Indexer;
+use barreleye_common::{
+	chain::U256,
+	models::{
+		Annotation, Config, ConfigKey, Network, PeelHop, PeelHopActiveModel, PrimaryId,
+		SoftDeleteModel, Transfer,
+	},
+	AnnotationKind, Architecture, BlockHeight,
+};
+
+const BLOCKS_PER_LOOP: BlockHeight = 100;
+
+// peeled output has to be at most 1/PEEL_RATIO of the transaction's total
+const PEEL_RATIO: u64 = 5;
+
+// hops needed before addresses get annotated
+const MIN_CHAIN_LENGTH: i32 = 4;
+
+struct Hop {
+	block_height: BlockHeight,
+	tx_hash: String,
+	input_address: String,
+	change_address: String,
+	peel_address: String,
+	peel_amount: U256,
+}
+
+impl Indexer {
+	// @NOTE walks processed bitcoin blocks in order, looking for
+	// one-input/two-output transactions that chain through their change
+	// addresses
+	pub async fn detect_peel_chains(
+		&self,
+		mut networks_updated: Receiver<SystemTime>,
+	) -> Result<()> {
+		loop {
+			if !self.app.is_leading() {
+				sleep(Duration::from_secs(1)).await;
+				continue;
+			}
+
+			// failures (eg: the warehouse being unreachable) are retried on the
+			// next run, without holding up other networks
+			let network_ids = match Network::get_all_existing(self.app.db(), Some(false)).await {
+				Ok(networks) => networks
+					.into_iter()
+					.filter(|n| n.architecture == Architecture::Bitcoin)
+					.map(|n| n.network_id)
+					.collect(),
+				Err(e) => {
+					warn!(peel_chains = "failed", error = e.to_string());
+					vec![]
+				}
+			};
+
+			let mut is_caught_up = true;
+			for nid in network_ids.into_iter() {
+				match self.detect_network_peel_chains(nid).await {
+					Ok(is_network_caught_up) => is_caught_up &= is_network_caught_up,
+					Err(e) => {
+						warn!(network_id = nid, peel_chains = "failed", error = e.to_string())
+					}
+				}
+			}
+
+			let pause = if is_caught_up { 10 } else { 0 };
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(pause)) => {}
+			}
+		}
+	}
+
+	async fn detect_network_peel_chains(&self, nid: PrimaryId) -> Result<bool> {
+		// skip network if "process" step is not done yet
+		let processed_block_height =
+			Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerProcessTail(nid))
+				.await?
+				.map(|v| v.value)
+				.unwrap_or(0);
+		let process_step_synced = Config::get_many::<_, (BlockHeight, BlockHeight)>(
+			self.app.db(),
+			vec![ConfigKey::IndexerProcessChunk(nid, 0), ConfigKey::IndexerProcessModule(nid, 0)],
+		)
+		.await?
+		.is_empty();
+		if processed_block_height == 0 || !process_step_synced {
+			return Ok(true);
+		}
+
+		let block_height =
+			Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerPeel(nid))
+				.await?
+				.map(|v| v.value)
+				.unwrap_or(0);
+		if block_height >= processed_block_height {
+			return Ok(true);
+		}
+
+		let block_height_min = block_height + 1;
+		let block_height_max = cmp::min(block_height + BLOCKS_PER_LOOP, processed_block_height);
+
+		let transfers = Transfer::get_all_by_block_range_excluding_fees(
+			&self.app.warehouse,
+			nid,
+			(block_height_min, block_height_max),
+		)
+		.await?;
+
+		self.save_peel_hops(nid, block_height_min, Self::find_peel_hops(transfers)).await?;
+
+		Config::set::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerPeel(nid), block_height_max)
+			.await?;
+
+		Ok(block_height_max >= processed_block_height)
+	}
+
+	fn find_peel_hops(transfers: Vec<Transfer>) -> Vec<Hop> {
+		let mut txs =
+			HashMap::<String, (BlockHeight, HashSet<String>, HashMap<String, U256>)>::new();
+		for t in transfers.into_iter() {
+			let (_, inputs, outputs) = txs.entry(t.tx_hash.clone()).or_insert((
+				t.block_height,
+				HashSet::new(),
+				HashMap::new(),
+			));
+
+			inputs.insert(t.from_address);
+			let amount = outputs.entry(t.to_address).or_insert(U256::zero());
+			*amount = amount.saturating_add(t.relative_amount);
+		}
+
+		let mut ret = vec![];
+		for (tx_hash, (block_height, inputs, outputs)) in txs.into_iter() {
+			if inputs.len() != 1 || outputs.len() != 2 {
+				continue;
+			}
+
+			let mut outputs = outputs.into_iter().collect::<Vec<_>>();
+			outputs.sort_by_key(|(_, amount)| *amount);
+			let (peel_address, peel_amount) = outputs[0].clone();
+			let (change_address, change_amount) = outputs[1].clone();
+
+			let total = peel_amount.saturating_add(change_amount);
+			if peel_amount.is_zero() || peel_amount.saturating_mul(U256::from(PEEL_RATIO)) > total {
+				continue;
+			}
+
+			ret.push(Hop {
+				block_height,
+				tx_hash,
+				input_address: inputs.into_iter().next().unwrap_or_default(),
+				change_address,
+				peel_address,
+				peel_amount,
+			});
+		}
+
+		ret.sort_by_key(|h| h.block_height);
+		ret
+	}
+
+	async fn save_peel_hops(
+		&self,
+		network_id: PrimaryId,
+		block_height_min: BlockHeight,
+		hops: Vec<Hop>,
+	) -> Result<()> {
+		if hops.is_empty() {
+			return Ok(());
+		}
+
+		// seed chain tails from previously saved hops: change address ->
+		// (block height, root tx hash, length)
+		let mut tails = HashMap::<String, (i64, String, i32)>::new();
+		for hop in PeelHop::get_all_by_change_addresses(
+			self.app.db(),
+			network_id,
+			hops.iter().map(|h| h.input_address.clone()).collect(),
+			block_height_min,
+		)
+		.await?
+		{
+			match tails.get(&hop.change_address) {
+				Some((block_height, _, _)) if *block_height > hop.block_height => {}
+				_ => {
+					tails.insert(
+						hop.change_address,
+						(hop.block_height, hop.root_tx_hash, hop.length),
+					);
+				}
+			}
+		}
+
+		let mut data = vec![];
+		let mut roots = HashMap::<String, i32>::new();
+		for hop in hops.into_iter() {
+			let (root_tx_hash, length) = match tails.get(&hop.input_address) {
+				Some((_, root_tx_hash, length)) => (root_tx_hash.clone(), length + 1),
+				None => (hop.tx_hash.clone(), 1),
+			};
+
+			tails.insert(
+				hop.change_address.clone(),
+				(hop.block_height as i64, root_tx_hash.clone(), length),
+			);
+
+			let max_length = roots.entry(root_tx_hash.clone()).or_default();
+			*max_length = cmp::max(*max_length, length);
+
+			data.push(PeelHopActiveModel {
+				network_id: Set(network_id),
+				block_height: Set(hop.block_height as i64),
+				tx_hash: Set(hop.tx_hash),
+				root_tx_hash: Set(root_tx_hash),
+				length: Set(length),
+				input_address: Set(hop.input_address),
+				change_address: Set(hop.change_address),
+				peel_address: Set(hop.peel_address),
+				peel_amount: Set(hop.peel_amount.to_string()),
+				..Default::default()
+			});
+		}
+
+		PeelHop::create_many(self.app.db(), data).await?;
+
+		// annotate every address along chains that are long enough
+		let mut annotations = vec![];
+		for (root_tx_hash, length) in roots.into_iter() {
+			if length < MIN_CHAIN_LENGTH {
+				continue;
+			}
+
+			for hop in
+				PeelHop::get_all_by_root_tx_hash(self.app.db(), network_id, &root_tx_hash).await?
+			{
+				for address in [&hop.input_address, &hop.change_address] {
+					annotations.push(Annotation::new_model(
+						network_id,
+						address,
+						AnnotationKind::PeelChain,
+						&root_tx_hash,
+					));
+				}
+			}
+		}
+
+		if !annotations.is_empty() {
+			Annotation::create_many(self.app.db(), annotations).await?;
+		}
+
+		Ok(())
+	}
+}
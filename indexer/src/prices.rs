@@ -0,0 +1,109 @@
+use eyre::Result;
+use std::{
+	collections::HashMap,
+	time::{Instant, SystemTime},
+};
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::{debug, info, warn};
+
+use crate::Indexer;
+use barreleye_common::{
+	models::{Price, PrimaryId},
+	prices::CoinGecko,
+	utils,
+	warehouse::Driver as WarehouseDriver,
+};
+
+// how long a network whose prices failed to fetch is left alone
+const RETRY_AFTER: Duration = Duration::from_secs(600);
+
+impl Indexer {
+	// @NOTE every `prices_interval` seconds, daily usd prices of the native
+	// asset are fetched for networks that have a `price_id`. the first fetch
+	// backfills `prices_backfill_days`, and later ones start from the last day
+	// fetched, so today's price stays fresh. progress is read from the
+	// warehouse itself, so a changed `price_id` (which clears the network's
+	// prices) starts over
+	pub async fn update_prices(&self, mut networks_updated: Receiver<SystemTime>) -> Result<()> {
+		let settings = &self.app.settings;
+		let is_enabled = settings.warehouse_driver == WarehouseDriver::ClickHouse &&
+			settings.prices_interval > 0;
+
+		let coingecko = CoinGecko::new(settings)?;
+		let mut failed_at = HashMap::<PrimaryId, Instant>::new();
+
+		loop {
+			if is_enabled && self.app.is_leading() {
+				failed_at.retain(|_, at| at.elapsed() < RETRY_AFTER);
+
+				let networks = self
+					.app
+					.networks
+					.read()
+					.await
+					.iter()
+					.filter_map(|(nid, chain)| chain.get_network().price_id.map(|id| (*nid, id)))
+					.collect::<Vec<_>>();
+
+				for (nid, price_id) in networks.into_iter() {
+					if failed_at.contains_key(&nid) {
+						continue;
+					}
+
+					let now = utils::now().and_utc().timestamp() as u32;
+					let today = (now / 86_400) as u16;
+
+					let last_price =
+						Price::get_last_native_by_network_id(&self.app.warehouse, nid).await?;
+					if matches!(
+						&last_price,
+						Some(p) if p.updated_at as u64 + settings.prices_interval > now as u64
+					) {
+						continue;
+					}
+
+					let days = match last_price {
+						Some(p) => today.saturating_sub(p.date) + 1,
+						None => settings.prices_backfill_days,
+					}
+					.clamp(1, settings.prices_backfill_days.max(1));
+
+					let daily_prices = match coingecko.get_daily_prices(&price_id, days).await {
+						Ok(v) => v,
+						Err(e) => {
+							warn!(network_id = nid, price_id, error = e.to_string());
+							failed_at.insert(nid, Instant::now());
+							continue;
+						}
+					};
+					if daily_prices.is_empty() {
+						failed_at.insert(nid, Instant::now());
+						continue;
+					}
+
+					Price::create_many(
+						&self.app.warehouse,
+						daily_prices
+							.iter()
+							.map(|p| Price::new(nid, "", p.date, p.price, now))
+							.collect(),
+					)
+					.await?;
+
+					info!(network_id = nid, price_id, days = daily_prices.len(), "Updated prices");
+				}
+			}
+
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(60)) => {}
+			}
+		}
+	}
+}
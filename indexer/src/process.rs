@@ -2,7 +2,7 @@ use eyre::{ErrReport, Result};
 use serde_json::{from_value as json_parse, json, Value as JsonValue};
 use std::{
 	cmp,
-	collections::HashMap,
+	collections::{HashMap, HashSet},
 	sync::{
 		atomic::{AtomicBool, Ordering},
 		Arc,
@@ -11,18 +11,21 @@ use std::{
 };
 use tokio::{
 	sync::{broadcast, mpsc, mpsc::Sender, watch::Receiver},
-	task::JoinSet,
-	time::{sleep, Duration},
+	task::{AbortHandle, JoinSet},
+	time::{interval, sleep, Duration},
 };
-use tracing::{debug, info, trace};
+use tracing::{debug, info, warn};
 
 use crate::Indexer;
 use barreleye_common::{
 	chain::{ModuleId, WarehouseData},
-	models::{Config, ConfigKey, PrimaryId},
+	models::{Block, Config, ConfigKey, PrimaryId},
 	BlockHeight,
 };
 
+// how often recently processed blocks are checked for being non-canonical
+const CANONICAL_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
 #[derive(Clone, Debug)]
 struct NetworkRange {
 	pub network_id: PrimaryId,
@@ -79,7 +82,12 @@ impl Indexer {
 	pub async fn process(&self, mut networks_updated: Receiver<SystemTime>) -> Result<()> {
 		let mut warehouse_data = WarehouseData::new();
 		let mut config_key_map = HashMap::<ConfigKey, serde_json::Value>::new();
+		let mut webhook_events = vec![];
+		let mut known_tokens = HashSet::<(PrimaryId, String)>::new();
+		let mut known_nfts = HashSet::<(PrimaryId, String, String)>::new();
+		let mut known_swaps = HashSet::<(PrimaryId, String)>::new();
 		let mut blocked_and_notified = false;
+		let mut is_recovered = false;
 
 		'indexing: loop {
 			if !self.app.is_leading() {
@@ -87,6 +95,11 @@ impl Indexer {
 				continue;
 			}
 
+			if !is_recovered {
+				self.recover_batch(ConfigKey::IndexerProcessBatch).await?;
+				is_recovered = true;
+			}
+
 			if self.app.should_reconnect().await? {
 				self.app.connect_networks(true).await?;
 			}
@@ -170,7 +183,7 @@ impl Indexer {
 							.get_module_ids()
 							.into_iter()
 							.map(|module_id| {
-								let mid = module_id as u16;
+								let mid = u16::from(module_id);
 								(ConfigKey::IndexerProcessModuleDone(nid, mid), 1u8)
 							})
 							.collect::<HashMap<_, _>>(),
@@ -202,9 +215,43 @@ impl Indexer {
 					);
 				}
 
+				// push backfills (specific modules over a bounded block range)
+				for (config_key, backfill) in
+					Config::get_many::<_, (BlockHeight, BlockHeight, Vec<u16>)>(
+						self.app.db(),
+						vec![ConfigKey::IndexerProcessBackfill(nid, 0)],
+					)
+					.await?
+				{
+					let (min, max, mids) = backfill.value;
+					let module_ids = chain
+						.get_module_ids()
+						.into_iter()
+						.filter(|module_id| mids.contains(&u16::from(*module_id)))
+						.collect::<Vec<_>>();
+
+					// modules might have been disabled since
+					if module_ids.is_empty() {
+						Config::delete(self.app.db(), config_key).await?;
+						continue;
+					}
+
+					// @NOTE what's left of the range is written again, and the views
+					// summed up from it would count it twice. so it's removed first,
+					// which is also safe to do again after a restart
+					if min < max {
+						let mids = module_ids.iter().map(|m| u16::from(*m)).collect::<Vec<_>>();
+						Block::rewind_modules(&self.app.warehouse, nid, (min + 1, max), &mids)
+							.await?;
+					}
+
+					network_params_map
+						.insert(config_key, NetworkRange::new(nid, min, Some(max), &module_ids));
+				}
+
 				// push individual modules that need to sync up
 				for module_id in chain.get_module_ids().into_iter() {
-					let mid = module_id as u16;
+					let mid = u16::from(module_id);
 
 					let ck_synced = ConfigKey::IndexerProcessModuleDone(nid, mid);
 					if Config::get::<_, u8>(self.app.db(), ck_synced).await?.is_none() {
@@ -221,6 +268,16 @@ impl Indexer {
 								let block_range = (0, last_processed_block);
 
 								if last_processed_block > 0 {
+									// a resynced module has rows from before, which
+									// have to come out of the views too
+									Block::rewind_modules(
+										&self.app.warehouse,
+										nid,
+										block_range,
+										&[mid],
+									)
+									.await?;
+
 									Config::set::<_, (BlockHeight, BlockHeight)>(
 										self.app.db(),
 										ck_block_range,
@@ -267,130 +324,31 @@ impl Indexer {
 			let thread_count = network_params_map.len();
 			debug!("Launching {thread_count} thread(s)…");
 
+			let mut reconnected_networks = self.app.subscribe_reconnected_networks();
+
 			let mut futures = JoinSet::new();
+			let mut network_tasks = HashMap::<PrimaryId, Vec<(ConfigKey, AbortHandle)>>::new();
 			for (config_key, network_params) in network_params_map.clone().into_iter() {
 				let (rtx, receipt) = mpsc::channel(1);
 				receipts.insert(config_key, rtx);
 
-				futures.spawn({
-					let nid = network_params.network_id;
-					let networks = self.app.networks.read().await;
-					let chain = networks[&network_params.network_id].clone();
-					let should_keep_going = should_keep_going.clone();
-					let mut pipe = Pipe::new(
+				let nid = network_params.network_id;
+				let pipe =
+					Pipe::new(config_key, pipe_sender.clone(), receipt, abort_sender.subscribe());
+				if let Some(task) = self
+					.spawn_process_task(
+						&mut futures,
 						config_key,
-						pipe_sender.clone(),
-						receipt,
-						abort_sender.subscribe(),
-					);
-					let db = self.app.db().clone();
-					let storage = self.app.storage.clone();
-
-					async move {
-						let mut warehouse_data = WarehouseData::new();
-
-						let mut block_height = network_params.range.0;
-						let block_height_max = network_params.range.1;
-
-						let config_value = |block_height| match config_key {
-							ConfigKey::IndexerProcessTail(_) => {
-								json!(block_height)
-							}
-							ConfigKey::IndexerProcessChunk(_, _) |
-							ConfigKey::IndexerProcessModule(_, _)
-								if block_height_max.is_some() =>
-							{
-								json!((block_height, block_height_max.unwrap()))
-							}
-							_ => panic!("no return value for {config_key}"),
-						};
-
-						while should_keep_going.load(Ordering::SeqCst) {
-							match block_height_max {
-								Some(block_height_max) if block_height + 1 > block_height_max => {
-									// push no matter what (even if no warehouse
-									// data) so that config keys get updated
-									pipe.push(
-										config_value(block_height),
-										warehouse_data.clone(),
-										true,
-									)
-									.await?;
-
-									break;
-								}
-								None => {
-									let last_synced_block_height = Config::get::<_, BlockHeight>(
-										&db,
-										ConfigKey::IndexerSyncTail(nid),
-									)
-									.await?
-									.map(|v| v.value)
-									.unwrap_or(0);
-
-									if block_height + 1 > last_synced_block_height {
-										// push only if have some warehouse
-										// data; otherwise, it's ok
-										// if config keys get updated later
-										if !warehouse_data.is_empty() {
-											pipe.push(
-												config_value(block_height),
-												warehouse_data.clone(),
-												true,
-											)
-											.await?;
-										}
-
-										// wait a bit
-										let timeout =
-											cmp::min(chain.get_network().block_time, 5_000);
-										sleep(Duration::from_millis(timeout as u64)).await;
-										continue;
-									}
-								}
-								_ => {}
-							}
-
-							block_height += 1;
-
-							let is_done = tokio::select! {
-								_ = pipe.abort.recv() => true,
-								new_data = chain.process_block(
-									storage.clone(),
-									block_height,
-									network_params.modules.clone(),
-								) => match new_data? {
-									Some(new_data) => {
-										warehouse_data += new_data;
-										false
-									},
-									None => true,
-								},
-							};
-
-							if is_done || warehouse_data.len() > 100 {
-								pipe.push(
-									config_value(block_height),
-									warehouse_data.clone(),
-									false,
-								)
-								.await?;
-								warehouse_data.clear();
-							}
-
-							if is_done {
-								break;
-							}
-						}
-
-						Ok::<_, ErrReport>(())
-					}
-				});
+						network_params,
+						pipe,
+						should_keep_going.clone(),
+					)
+					.await
+				{
+					network_tasks.entry(nid).or_default().push((config_key, task));
+				}
 			}
 
-			// drop the original non-cloned
-			drop(pipe_sender);
-
 			// periodically show progress
 			tokio::spawn({
 				let s = self.clone();
@@ -403,6 +361,7 @@ impl Indexer {
 				abort_sender.send(())?;
 				Ok(())
 			};
+			let mut canonical_check = interval(CANONICAL_CHECK_INTERVAL);
 			loop {
 				tokio::select! {
 					_ = networks_updated.changed() => {
@@ -410,35 +369,136 @@ impl Indexer {
 						abort()?;
 						break 'indexing Ok(());
 					}
-					result = futures.join_next() => {
-						if let Some(task_result) = result {
+					_ = canonical_check.tick(), if !self.app.settings.offline => {
+						let network_ids =
+							self.app.networks.read().await.keys().copied().collect::<Vec<_>>();
+
+						for nid in network_ids.into_iter() {
+							let block_height = match self.find_non_canonical_block(nid).await {
+								Ok(block_height) => block_height,
+								Err(e) => {
+									warn!(network_id = nid, error = e.to_string());
+									None
+								}
+							};
+
+							if let Some(block_height) = block_height {
+								debug!("Restarting… (non-canonical block {block_height})");
+								abort()?;
+
+								// uncommitted data might be non-canonical too; markers
+								// weren't saved either, so it all gets processed again
+								warehouse_data.clear();
+								config_key_map.clear();
+								webhook_events.clear();
+
+								self.rollback_processed_blocks(nid, block_height).await?;
+								continue 'indexing;
+							}
+						}
+					}
+					Ok(nid) = reconnected_networks.recv() => {
+						// running threads still hold the previous chain (and its rate
+						// limiter), so they're restarted with the new one from the last
+						// block height they pushed
+						for (config_key, task) in network_tasks.remove(&nid).unwrap_or_default() {
+							if task.is_finished() {
+								continue;
+							}
+							task.abort();
+
+							let Some(mut network_params) =
+								network_params_map.get(&config_key).cloned()
+							else {
+								continue;
+							};
+
+							let config_value = match config_key_map.get(&config_key) {
+								Some(config_value) => Some(config_value.clone()),
+								None => Config::get::<_, JsonValue>(self.app.db(), config_key)
+									.await?
+									.map(|v| v.value),
+							};
+							if let Some(block_height) = config_value.and_then(|v| {
+								v.as_u64().or_else(|| v.get(0).and_then(JsonValue::as_u64))
+							}) {
+								network_params.range.0 = block_height;
+							}
+
+							let (rtx, receipt) = mpsc::channel(1);
+							receipts.insert(config_key, rtx);
+
+							let pipe = Pipe::new(
+								config_key,
+								pipe_sender.clone(),
+								receipt,
+								abort_sender.subscribe(),
+							);
+							if let Some(task) = self
+								.spawn_process_task(
+									&mut futures,
+									config_key,
+									network_params,
+									pipe,
+									should_keep_going.clone(),
+								)
+								.await
+							{
+								network_tasks.entry(nid).or_default().push((config_key, task));
+							}
+						}
+					}
+					result = futures.join_next() => match result {
+						Some(Err(e)) if e.is_cancelled() => {}
+						Some(task_result) => {
 							if let Err(e) = task_result? {
 								break 'indexing Err(e);
 							}
-						} else {
-							break;
 						}
-					}
+						None => break,
+					},
 					Some((config_key, config_value, new_data, force_commit)) = pipe_receiver.recv() => {
 						if !self.app.is_leading() {
 							abort()?;
 							break;
 						}
 
+						// register never-seen tokens and nfts
+						self.register_tokens(&new_data, &mut known_tokens).await?;
+						self.register_nfts(&new_data, &mut known_nfts).await?;
+						self.annotate_swaps(&new_data, &mut known_swaps).await?;
+
+						// activity on tagged entities, for webhooks once it's committed
+						match self.get_webhook_events(&new_data).await {
+							Ok(events) => webhook_events.extend(events),
+							Err(e) => warn!(webhooks = "skipped", error = e.to_string()),
+						}
+
+						// flag anomalies, but only in live blocks (history lookups need
+						// the warehouse, so they're skipped while it's unreachable)
+						if let ConfigKey::IndexerProcessTail(nid) = config_key {
+							if let Err(e) = self.detect_anomalies(nid, &new_data).await {
+								warn!(anomalies = "skipped", error = e.to_string());
+							}
+						}
+
 						// update results
 						warehouse_data += new_data;
 						config_key_map.insert(config_key, config_value);
 
 						// batch save in warehouse
-						if warehouse_data.should_commit(force_commit) {
-							trace!(warehouse = "pushing", records = warehouse_data.len());
-
-							// push to warehouse
-							warehouse_data.commit(self.app.warehouse.clone()).await?;
-
-							// commit config marker updates
+						if warehouse_data.should_commit(force_commit) &&
+							self.commit_batch(
+								ConfigKey::IndexerProcessBatch,
+								&mut warehouse_data,
+								&config_key_map,
+							)
+							.await?
+						{
+							// commit config marker updates (all or nothing)
+							let db_tx = self.app.db_tx().await?;
 							for (config_key, config_value) in config_key_map.iter() {
-								let db = self.app.db();
+								let db = &db_tx;
 								let key = *config_key;
 								let value = config_value.clone();
 
@@ -481,6 +541,19 @@ impl Indexer {
 										let value = json_parse::<u8>(value)?;
 										Config::set::<_, u8>(db, key, value).await?;
 									}
+									ConfigKey::IndexerProcessBackfill(_, _) => {
+										let value =
+											json_parse::<(BlockHeight, BlockHeight, Vec<u16>)>(value)?;
+
+										if value.0 < value.1 {
+											Config::set::<_, (BlockHeight, BlockHeight, Vec<u16>)>(
+												db, key, value,
+											)
+											.await?;
+										} else {
+											Config::delete(db, key).await?;
+										}
+									}
 									_ => {}
 								}
 							}
@@ -491,17 +564,27 @@ impl Indexer {
 							for (config_key, _) in config_key_map.iter() {
 								if let ConfigKey::IndexerProcessModuleDone(nid, mid) = config_key {
 									let ck_block_range = ConfigKey::IndexerProcessModule(*nid, *mid);
-									Config::delete(self.app.db(), ck_block_range).await?;
+									Config::delete(&db_tx, ck_block_range).await?;
 								}
 							}
 
+							// confirm batch
+							Config::delete(&db_tx, ConfigKey::IndexerProcessBatch).await?;
+							db_tx.commit().await?;
+
 							// reset config key markers
 							config_key_map.clear();
+
+							// let webhooks know about activity on tagged entities
+							if let Err(e) = self.notify_webhooks(&webhook_events).await {
+								warn!(webhooks = "skipped", error = e.to_string());
+							}
+							webhook_events.clear();
 						}
 
-						// release thread so it can keep going
+						// release thread so it can keep going (unless it got restarted)
 						if let Some(receipt) = receipts.get(&config_key) {
-							receipt.send(()).await.unwrap();
+							receipt.send(()).await.ok();
 						}
 					}
 				}
@@ -509,6 +592,112 @@ impl Indexer {
 		}
 	}
 
+	// @NOTE returns `None` when the network is not around anymore (eg: it could
+	// not be reconnected)
+	async fn spawn_process_task(
+		&self,
+		futures: &mut JoinSet<Result<()>>,
+		config_key: ConfigKey,
+		network_params: NetworkRange,
+		mut pipe: Pipe,
+		should_keep_going: Arc<AtomicBool>,
+	) -> Option<AbortHandle> {
+		let nid = network_params.network_id;
+		let chain = self.app.networks.read().await.get(&nid).cloned()?;
+		let db = self.app.db().clone();
+		let storage = self.app.storage.clone();
+
+		Some(futures.spawn(async move {
+			let mut warehouse_data = WarehouseData::new();
+
+			let mut block_height = network_params.range.0;
+			let block_height_max = network_params.range.1;
+			let mids = network_params
+				.modules
+				.iter()
+				.map(|module_id| u16::from(*module_id))
+				.collect::<Vec<_>>();
+
+			let config_value = |block_height| match config_key {
+				ConfigKey::IndexerProcessTail(_) => {
+					json!(block_height)
+				}
+				ConfigKey::IndexerProcessChunk(_, _) | ConfigKey::IndexerProcessModule(_, _)
+					if block_height_max.is_some() =>
+				{
+					json!((block_height, block_height_max.unwrap()))
+				}
+				ConfigKey::IndexerProcessBackfill(_, _) if block_height_max.is_some() => {
+					json!((block_height, block_height_max.unwrap(), &mids))
+				}
+				_ => panic!("no return value for {config_key}"),
+			};
+
+			while should_keep_going.load(Ordering::SeqCst) {
+				match block_height_max {
+					Some(block_height_max) if block_height + 1 > block_height_max => {
+						// push no matter what (even if no warehouse
+						// data) so that config keys get updated
+						pipe.push(config_value(block_height), warehouse_data.clone(), true).await?;
+
+						break;
+					}
+					None => {
+						let last_synced_block_height =
+							Config::get::<_, BlockHeight>(&db, ConfigKey::IndexerSyncTail(nid))
+								.await?
+								.map(|v| v.value)
+								.unwrap_or(0);
+
+						if block_height + 1 > last_synced_block_height {
+							// push only if have some warehouse
+							// data; otherwise, it's ok
+							// if config keys get updated later
+							if !warehouse_data.is_empty() {
+								pipe.push(config_value(block_height), warehouse_data.clone(), true)
+									.await?;
+							}
+
+							// wait a bit
+							let timeout = cmp::min(chain.get_network().block_time, 5_000);
+							sleep(Duration::from_millis(timeout as u64)).await;
+							continue;
+						}
+					}
+					_ => {}
+				}
+
+				block_height += 1;
+
+				let is_done = tokio::select! {
+					_ = pipe.abort.recv() => true,
+					new_data = chain.process_block(
+						storage.clone(),
+						block_height,
+						network_params.modules.clone(),
+					) => match new_data? {
+						Some(new_data) => {
+							warehouse_data += new_data;
+							false
+						},
+						None => true,
+					},
+				};
+
+				if is_done || warehouse_data.len() > 100 {
+					pipe.push(config_value(block_height), warehouse_data.clone(), false).await?;
+					warehouse_data.clear();
+				}
+
+				if is_done {
+					break;
+				}
+			}
+
+			Ok::<_, ErrReport>(())
+		}))
+	}
+
 	async fn show_process_progress(&self, secs: u64) -> Result<()> {
 		loop {
 			sleep(Duration::from_secs(secs)).await;
@@ -0,0 +1,251 @@
+use eyre::Result;
+use serde_json::json;
+use std::{
+	cmp,
+	collections::{HashMap, HashSet},
+	time::SystemTime,
+};
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::{debug, info, warn};
+
+use crate::Indexer;
+use barreleye_common::{
+	models::{
+		Address, BasicModel, Config, ConfigKey, Entity, EntityTag, Network, PrimaryId, Tag,
+		Transfer,
+	},
+	producers::{self, KnownProducer},
+	BlockHeight, RiskLevel,
+};
+
+const BLOCKS_PER_LOOP: BlockHeight = 100;
+
+// tag that auto-created producer entities get
+const TAG_NAME: &str = "Block Producer";
+
+impl Indexer {
+	// @NOTE labels the addresses blocks are paid out to (coinbase outputs on
+	// bitcoin, fee recipients on evm chains). they're matched against a seed
+	// list of mining pools and block builders, either by the address itself or
+	// by the text the producer put into the block. producers that aren't known
+	// are left alone
+	pub async fn label_block_producers(
+		&self,
+		mut networks_updated: Receiver<SystemTime>,
+	) -> Result<()> {
+		loop {
+			if !self.app.is_leading() {
+				sleep(Duration::from_secs(1)).await;
+				continue;
+			}
+
+			let network_ids = self.app.networks.read().await.keys().copied().collect::<Vec<_>>();
+
+			// failures (eg: the rpc node being briefly unreachable) are retried on
+			// the next run, without holding up other networks
+			let mut is_caught_up = true;
+			for nid in network_ids.into_iter() {
+				match self.label_network_block_producers(nid).await {
+					Ok(is_network_caught_up) => is_caught_up &= is_network_caught_up,
+					Err(e) => {
+						warn!(network_id = nid, block_producers = "failed", error = e.to_string())
+					}
+				}
+			}
+
+			let pause = if is_caught_up { 10 } else { 0 };
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(pause)) => {}
+			}
+		}
+	}
+
+	async fn label_network_block_producers(&self, nid: PrimaryId) -> Result<bool> {
+		// skip network if "process" step is not done yet
+		let processed_block_height =
+			Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerProcessTail(nid))
+				.await?
+				.map(|v| v.value)
+				.unwrap_or(0);
+		let process_step_synced = Config::get_many::<_, (BlockHeight, BlockHeight)>(
+			self.app.db(),
+			vec![ConfigKey::IndexerProcessChunk(nid, 0), ConfigKey::IndexerProcessModule(nid, 0)],
+		)
+		.await?
+		.is_empty();
+		if processed_block_height == 0 || !process_step_synced {
+			return Ok(true);
+		}
+
+		let block_height =
+			Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerProducer(nid))
+				.await?
+				.map(|v| v.value)
+				.unwrap_or(0);
+		if block_height >= processed_block_height {
+			return Ok(true);
+		}
+
+		let block_height_min = block_height + 1;
+		let block_height_max = cmp::min(block_height + BLOCKS_PER_LOOP, processed_block_height);
+
+		let block_producers = Transfer::get_producers_by_block_range(
+			&self.app.warehouse,
+			nid,
+			(block_height_min, block_height_max),
+		)
+		.await?;
+
+		self.save_block_producers(nid, block_producers).await?;
+
+		Config::set::<_, BlockHeight>(
+			self.app.db(),
+			ConfigKey::IndexerProducer(nid),
+			block_height_max,
+		)
+		.await?;
+
+		Ok(block_height_max >= processed_block_height)
+	}
+
+	async fn save_block_producers(
+		&self,
+		network_id: PrimaryId,
+		block_producers: Vec<(BlockHeight, String)>,
+	) -> Result<()> {
+		if block_producers.is_empty() {
+			return Ok(());
+		}
+
+		// already labeled addresses are left alone
+		let labeled = Address::get_all_by_addresses(
+			self.app.db(),
+			block_producers.iter().map(|(_, address)| address.clone()).collect(),
+			None,
+		)
+		.await?
+		.into_iter()
+		.filter(|a| a.network_id == network_id)
+		.map(|a| a.address)
+		.collect::<HashSet<String>>();
+
+		let (network, chain) = match (
+			Network::get(self.app.db(), network_id).await?,
+			self.app.networks.read().await.get(&network_id).cloned(),
+		) {
+			(Some(network), Some(chain)) => (network, chain),
+			_ => return Ok(()),
+		};
+
+		// address -> (producer, block, tag); every address is only looked at once
+		let mut checked = HashSet::new();
+		let mut matches = HashMap::<String, (&KnownProducer, BlockHeight, Option<String>)>::new();
+		for (block_height, address) in block_producers.into_iter() {
+			if labeled.contains(&address) || !checked.insert(address.clone()) {
+				continue;
+			}
+
+			let producer = match producers::find_known_producer(&address, None) {
+				Some(producer) => Some((producer, None)),
+				_ => {
+					let tag = chain.get_block_producer_tag(block_height).await?;
+					producers::find_known_producer(&address, tag.as_deref())
+						.map(|producer| (producer, tag))
+				}
+			};
+
+			if let Some((producer, tag)) = producer {
+				matches.insert(address, (producer, block_height, tag));
+			}
+		}
+
+		if matches.is_empty() {
+			return Ok(());
+		}
+
+		let tag_id = match Tag::get_by_name(self.app.db(), TAG_NAME).await? {
+			Some(tag) => tag.tag_id,
+			_ => Tag::create(self.app.db(), Tag::new_model(None, TAG_NAME, RiskLevel::Low)).await?,
+		};
+
+		let mut entity_ids = HashMap::<&str, PrimaryId>::new();
+		let mut addresses = vec![];
+		for (address, (producer, block_height, tag)) in matches.iter() {
+			let entity_id = match entity_ids.get(producer.name) {
+				Some(entity_id) => *entity_id,
+				_ => {
+					let entity_id = self.get_or_create_producer_entity(producer, tag_id).await?;
+					entity_ids.insert(producer.name, entity_id);
+					entity_id
+				}
+			};
+
+			addresses.push(Address::new_model(
+				None,
+				entity_id,
+				network_id,
+				&network.id,
+				address,
+				"Detected block producer",
+				Some(json!({ "blockHeight": block_height, "coinbaseTag": tag })),
+			));
+		}
+
+		let new_addresses = matches.keys().cloned().collect::<Vec<String>>();
+		info!(network = %network.name, addresses = new_addresses.len(), "Labeled block producers");
+
+		Address::create_many(self.app.db(), addresses).await?;
+
+		// tell the link step about newly created addresses
+		Config::set_many::<_, PrimaryId>(
+			self.app.db(),
+			Address::get_all_by_addresses(self.app.db(), new_addresses, Some(false))
+				.await?
+				.into_iter()
+				.filter(|a| a.network_id == network_id)
+				.map(|a| (ConfigKey::NewlyAddedAddress(a.network_id, a.address_id), a.address_id))
+				.collect::<HashMap<ConfigKey, PrimaryId>>(),
+		)
+		.await?;
+
+		// invalidate cached labels
+		Config::set::<_, u8>(self.app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+
+		Ok(())
+	}
+
+	async fn get_or_create_producer_entity(
+		&self,
+		producer: &KnownProducer,
+		tag_id: PrimaryId,
+	) -> Result<PrimaryId> {
+		let entity = Entity::get_by_name(self.app.db(), producer.name, Some(false)).await?;
+		let entity_id = match entity {
+			Some(entity) => entity.entity_id,
+			_ => {
+				Entity::create(
+					self.app.db(),
+					Entity::new_model(
+						None,
+						Some(producer.name.to_string()),
+						"Detected block producer",
+						None,
+					),
+				)
+				.await?
+			}
+		};
+
+		EntityTag::create_many(self.app.db(), vec![EntityTag::new_model(entity_id, tag_id)])
+			.await?;
+
+		Ok(entity_id)
+	}
+}
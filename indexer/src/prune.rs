@@ -0,0 +1,190 @@
+use eyre::Result;
+use sea_orm::ColumnTrait;
+use std::{
+	collections::{HashMap, HashSet},
+	time::{Instant, SystemTime},
+};
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::{debug, info};
+
+use crate::Indexer;
+use barreleye_common::{
+	models::{
+		Address, AddressActivity, AddressColumn, Amount, Approval, Balance, BalanceSnapshot, Block,
+		Config, ConfigKey, DecodedCall, Entity, Fee, Funder, Link, Network, NetworkColumn,
+		NetworkStats, Price, PrimaryId, PrimaryIds, PruneStats, SoftDeleteModel, StakingDeposit,
+		Transfer, UserOperation,
+	},
+	utils,
+};
+
+impl Indexer {
+	// @NOTE prunes soft-deleted data every `prune_interval` seconds, and only
+	// within `maintenance_windows` if any are set. the last run is kept in the db, so
+	// restarts and failovers don't set off another round of warehouse deletes
+	pub async fn prune(&self, mut networks_updated: Receiver<SystemTime>) -> Result<()> {
+		loop {
+			if self.app.is_leading() && self.is_prune_due().await? {
+				let started_at = Instant::now();
+				let (addresses, entities, networks) = self.prune_data().await?;
+				let duration_ms = started_at.elapsed().as_millis() as u64;
+
+				info!(addresses, entities, networks, duration_ms, "Pruned deleted data");
+
+				let mut stats =
+					Config::get::<_, PruneStats>(self.app.db(), ConfigKey::IndexerPrune)
+						.await?
+						.map(|v| v.value)
+						.unwrap_or_default();
+				stats.runs += 1;
+				stats.addresses += addresses;
+				stats.entities += entities;
+				stats.networks += networks;
+				stats.duration_ms += duration_ms;
+				Config::set::<_, PruneStats>(self.app.db(), ConfigKey::IndexerPrune, stats).await?;
+			}
+
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(60)) => {}
+			}
+		}
+	}
+
+	async fn is_prune_due(&self) -> Result<bool> {
+		if !self.is_in_maintenance_window() {
+			return Ok(false);
+		}
+
+		Ok(match Config::get::<_, PruneStats>(self.app.db(), ConfigKey::IndexerPrune).await? {
+			Some(v) => v.updated_at <= utils::ago_in_seconds(self.app.settings.prune_interval),
+			_ => true,
+		})
+	}
+
+	// returns how many addresses, entities and networks were removed
+	async fn prune_data(&self) -> Result<(u64, u64, u64)> {
+		let (mut addresses_pruned, mut networks_pruned) = (0, 0);
+
+		// prune all soft-deleted addresses
+		let addresses = Address::get_all_deleted(self.app.db()).await?;
+		if !addresses.is_empty() {
+			// delete all upstream configs
+			Config::delete_many(
+				self.app.db(),
+				addresses
+					.iter()
+					.map(|a| ConfigKey::IndexerLink(a.network_id, a.address_id))
+					.collect(),
+			)
+			.await?;
+
+			// delete all addresses
+			addresses_pruned = Address::prune_all_where(
+				self.app.db(),
+				AddressColumn::AddressId.is_in(Into::<PrimaryIds>::into(addresses.clone())),
+			)
+			.await?;
+
+			// delete links from warehouse
+			let mut sources: HashMap<PrimaryId, HashSet<String>> = HashMap::new();
+			for address in addresses.into_iter() {
+				if let Some(set) = sources.get_mut(&address.network_id) {
+					set.insert(address.address);
+				} else {
+					sources.insert(address.network_id, HashSet::from([address.address]));
+				}
+			}
+			Link::delete_all_by_sources(&self.app.warehouse, sources).await?;
+		}
+
+		// prune all soft-deleted entities
+		let entities_pruned = Entity::prune_all(self.app.db()).await?;
+
+		// prune all soft-deleted networks
+		let deleted_networks = Network::get_all_existing(self.app.db(), Some(true)).await?;
+		if !deleted_networks.is_empty() {
+			let network_ids: PrimaryIds = deleted_networks.clone().into();
+
+			// delete all associated configs
+			Config::delete_all_by_keywords(
+				self.app.db(),
+				deleted_networks.clone().iter().map(|n| format!("n{}", n.network_id)).collect(),
+			)
+			.await?;
+
+			// delete all addresses
+			Address::prune_all_where(
+				self.app.db(),
+				AddressColumn::NetworkId.is_in(network_ids.clone()),
+			)
+			.await?;
+
+			// delete from warehouse
+			let (
+				transfers_deleted,
+				balances_deleted,
+				snapshots_deleted,
+				stats_deleted,
+				amounts_deleted,
+				links_deleted,
+				decoded_calls_deleted,
+				user_operations_deleted,
+				fees_deleted,
+				blocks_deleted,
+				staking_deposits_deleted,
+				approvals_deleted,
+				funders_deleted,
+				address_activity_deleted,
+				prices_deleted,
+			) = tokio::join!(
+				Transfer::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				Balance::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				BalanceSnapshot::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				NetworkStats::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				Amount::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				Link::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				DecodedCall::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				UserOperation::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				Fee::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				Block::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				StakingDeposit::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				Approval::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				Funder::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				AddressActivity::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+				Price::delete_all_by_network_id(&self.app.warehouse, network_ids.clone()),
+			);
+
+			transfers_deleted
+				.and(balances_deleted)
+				.and(snapshots_deleted)
+				.and(stats_deleted)
+				.and(amounts_deleted)
+				.and(links_deleted)
+				.and(decoded_calls_deleted)
+				.and(user_operations_deleted)
+				.and(fees_deleted)
+				.and(blocks_deleted)
+				.and(staking_deposits_deleted)
+				.and(approvals_deleted)
+				.and(funders_deleted)
+				.and(address_activity_deleted)
+				.and(prices_deleted)?;
+
+			// finally delete only the networks we grabbed earlier
+			networks_pruned = Network::prune_all_where(
+				self.app.db(),
+				NetworkColumn::NetworkId.is_in(network_ids),
+			)
+			.await?;
+		}
+
+		Ok((addresses_pruned, entities_pruned, networks_pruned))
+	}
+}
@@ -0,0 +1,165 @@
+use eyre::Result;
+use std::time::SystemTime;
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::{debug, info, warn};
+
+use crate::Indexer;
+use barreleye_common::{
+	chain::U256,
+	models::{Alert, Amount, Balance, Config, ConfigKey},
+	utils, AlertKind, BlockHeight,
+};
+
+// addresses checked per network and run
+const SAMPLE_SIZE: u64 = 10;
+
+// how far back to look for recently active addresses to sample
+const SAMPLE_BLOCKS: BlockHeight = 1_000;
+
+impl Indexer {
+	// @NOTE every `reconcile_interval` seconds, the native balances of a few
+	// recently active addresses are summed up from the warehouse and compared
+	// to what the node reports. a mismatch means a module missed something
+	// (eg: internal transfers or fees), so it's raised as a `BalanceDrift`
+	// alert. only chains that can report balances are checked
+	pub async fn reconcile_balances(
+		&self,
+		mut networks_updated: Receiver<SystemTime>,
+	) -> Result<()> {
+		let interval = self.app.settings.reconcile_interval;
+
+		loop {
+			if interval > 0 && self.app.is_leading() {
+				let networks = self
+					.app
+					.networks
+					.read()
+					.await
+					.iter()
+					.map(|(nid, chain)| (*nid, chain.clone()))
+					.collect::<Vec<_>>();
+
+				for (nid, chain) in networks.into_iter() {
+					let last_run = Config::get::<_, BlockHeight>(
+						self.app.db(),
+						ConfigKey::IndexerReconcile(nid),
+					)
+					.await?;
+					if matches!(&last_run, Some(v) if v.updated_at > utils::ago_in_seconds(interval))
+					{
+						continue;
+					}
+
+					// skip network if "process" step is not done yet
+					let processed_block_height = Config::get::<_, BlockHeight>(
+						self.app.db(),
+						ConfigKey::IndexerProcessTail(nid),
+					)
+					.await?
+					.map(|v| v.value)
+					.unwrap_or(0);
+					let process_step_synced = Config::get_many::<_, (BlockHeight, BlockHeight)>(
+						self.app.db(),
+						vec![
+							ConfigKey::IndexerProcessChunk(nid, 0),
+							ConfigKey::IndexerProcessModule(nid, 0),
+						],
+					)
+					.await?
+					.is_empty();
+					if processed_block_height == 0 || !process_step_synced {
+						continue;
+					}
+
+					let addresses = Amount::get_all_native_addresses_by_block_range(
+						&self.app.warehouse,
+						nid,
+						processed_block_height.saturating_sub(SAMPLE_BLOCKS),
+						processed_block_height,
+						SAMPLE_SIZE,
+					)
+					.await?;
+
+					let mut alerts = vec![];
+					let mut is_ahead = false;
+					for address in addresses.iter() {
+						let (block_height, node_balance) =
+							match chain.get_balance(address, processed_block_height).await {
+								Ok(Some(v)) => v,
+								Ok(None) => break,
+								Err(e) => {
+									warn!(network_id = nid, address, error = e.to_string());
+									continue;
+								}
+							};
+
+						// nodes that report as of their tip might be ahead of what's
+						// been processed
+						if block_height > processed_block_height {
+							is_ahead = true;
+							break;
+						}
+
+						let warehouse_balance = Balance::get_all_by_address_at_block_height(
+							&self.app.warehouse,
+							nid,
+							address,
+							block_height,
+						)
+						.await?
+						.into_iter()
+						.find(|b| b.asset_address.is_empty())
+						.map(|b| b.balance)
+						.unwrap_or(U256::zero());
+
+						if warehouse_balance != node_balance {
+							alerts.push(Alert::new_model(
+								nid,
+								AlertKind::BalanceDrift,
+								block_height,
+								"",
+								address,
+								"",
+								warehouse_balance.to_string(),
+								format!(
+									"warehouse balance is {warehouse_balance}, node reports \
+									 {node_balance}"
+								),
+							));
+						}
+					}
+
+					if !alerts.is_empty() {
+						warn!(network_id = nid, drifted = alerts.len(), "Balances drifted");
+						Alert::create_many(self.app.db(), alerts).await?;
+					}
+
+					// try again once processing catches up
+					if is_ahead {
+						continue;
+					}
+
+					info!(network_id = nid, addresses = addresses.len(), "Reconciled balances");
+
+					Config::set::<_, BlockHeight>(
+						self.app.db(),
+						ConfigKey::IndexerReconcile(nid),
+						processed_block_height,
+					)
+					.await?;
+				}
+			}
+
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(60)) => {}
+			}
+		}
+	}
+}
@@ -0,0 +1,36 @@
+use eyre::Result;
+use std::collections::HashSet;
+
+use crate::Indexer;
+use barreleye_common::{
+	chain::WarehouseData,
+	models::{Annotation, PrimaryId},
+	AnnotationKind,
+};
+
+impl Indexer {
+	// annotate recipients of dex swaps with the pool they swapped through
+	pub async fn annotate_swaps(
+		&self,
+		warehouse_data: &WarehouseData,
+		known_swaps: &mut HashSet<(PrimaryId, String)>,
+	) -> Result<()> {
+		let mut annotations = vec![];
+		for (network_id, address, pool) in warehouse_data.swaps.iter() {
+			if known_swaps.insert((*network_id, address.clone())) {
+				annotations.push(Annotation::new_model(
+					*network_id,
+					address,
+					AnnotationKind::Swap,
+					pool,
+				));
+			}
+		}
+
+		if !annotations.is_empty() {
+			Annotation::create_many(self.app.db(), annotations).await?;
+		}
+
+		Ok(())
+	}
+}
@@ -1,19 +1,29 @@
 use eyre::{Report, Result};
-use futures::future;
-use std::{collections::HashMap, error::Error, time::SystemTime};
+use sea_orm::ConnectionTrait;
+use std::{
+	collections::{HashMap, HashSet},
+	error::Error,
+	time::SystemTime,
+};
 use tokio::{
 	sync::watch,
-	task,
+	task::{AbortHandle, JoinSet},
 	time::{sleep, Duration},
 };
-use tracing::info;
+use tracing::{error, info, warn};
 
 use crate::Indexer;
 use barreleye_common::{
+	chain::BlockHashes,
 	models::{Config, ConfigKey, PrimaryId},
 	BlockHeight,
 };
 
+// how many block hashes are kept around to find where the chain connects again
+const MAX_REPAIR_DEPTH: usize = 64;
+
+type SyncTaskResult = (PrimaryId, Result<(), Box<dyn Error + Send + Sync>>);
+
 #[derive(Clone, Debug)]
 struct NetworkRange {
 	pub network_id: PrimaryId,
@@ -49,81 +59,180 @@ impl Indexer {
 				}
 
 				_ = sleep(Duration::from_secs(0)) => {
-					let network_range_map = self.get_network_ranges().await?;
+					let network_range_map = self.get_network_ranges(None).await?;
 
 					if network_range_map.is_empty() {
 						sleep(Duration::from_secs(3)).await;
 						continue;
 					}
 
-					let mut tasks = vec![];
-					for (_config_key, network_range) in network_range_map.clone().into_iter() {
-						let task = task::spawn({
-							let networks = self.app.networks.read().await;
-							let chain = networks[&network_range.network_id].clone();
-							let db = self.app.db().clone();
-							let storage = self.app.storage.clone();
-
-							async move {
-								match network_range.range {
-									(start, Some(end)) => {
-										let config_key = ConfigKey::IndexerSyncChunk(network_range.network_id, end);
-
-										for block_height in start..end {
-											chain.extract_block(storage.clone(), block_height).await?;
-
-											Config::set::<_, (BlockHeight, BlockHeight)>(
-												&db,
-												config_key,
-												(block_height, end),
-											)
-											.await?;
-										}
-
-										// chunk is done, can delete
-										Config::delete(&db, config_key).await?;
+					let mut reconnected_networks = self.app.subscribe_reconnected_networks();
+
+					let mut tasks = JoinSet::new();
+					let mut network_tasks = HashMap::<PrimaryId, Vec<AbortHandle>>::new();
+					for (_config_key, network_range) in network_range_map.into_iter() {
+						let nid = network_range.network_id;
+						if let Some(task) = self.spawn_sync_task(&mut tasks, network_range).await {
+							network_tasks.entry(nid).or_default().push(task);
+						}
+					}
+
+					let mut failed_network_ids = HashSet::new();
+					loop {
+						tokio::select! {
+							Ok(nid) = reconnected_networks.recv() => {
+								// running tasks still hold the previous chain (and its rate
+								// limiter), so they're restarted from their saved markers with the
+								// new one
+								for task in network_tasks.remove(&nid).unwrap_or_default() {
+									task.abort();
+								}
+
+								failed_network_ids.remove(&nid);
+								let network_range_map = self.get_network_ranges(Some(nid)).await?;
+								for (_config_key, network_range) in network_range_map.into_iter() {
+									if let Some(task) = self.spawn_sync_task(&mut tasks, network_range).await {
+										network_tasks.entry(nid).or_default().push(task);
 									}
-									(start, None) => {
-										loop {
-											let latest_block_height = chain.get_block_height().await?;
-
-											for block_height in start..=latest_block_height {
-												chain.extract_block(storage.clone(), block_height).await?;
-
-												let config_key = ConfigKey::IndexerSyncTail(network_range.network_id);
-												Config::set::<_, BlockHeight>(&db, config_key, block_height).await?;
-											}
-
-											sleep(Duration::from_millis(
-												chain.get_network().block_time as u64,
-											))
-											.await;
-										}
+								}
+							}
+							result = tasks.join_next() => match result {
+								None => break,
+								Some(Err(e)) if e.is_cancelled() => {}
+								Some(Err(e)) => {
+									return Err(Report::msg(format!("A task failed: {e:?}")));
+								}
+								Some(Ok((nid, Err(e)))) => {
+									// move on to another rpc endpoint if the current one stopped
+									// responding (its tasks get restarted once it's reconnected)
+									if failed_network_ids.insert(nid) &&
+										self.app.failover_network(nid).await?
+									{
+										warn!(network_id = nid, error = e.to_string(), "Switched rpc endpoint");
 									}
 								}
-
-								Ok::<(), Box<dyn Error + Send + Sync>>(())
+								Some(Ok((_, Ok(())))) => {}
 							}
+						}
+					}
+				}
+			}
+		}
+	}
 
-						});
-
-						tasks.push(task);
+	// @NOTE returns `None` when the network is not around anymore (eg: it could
+	// not be reconnected)
+	async fn spawn_sync_task(
+		&self,
+		tasks: &mut JoinSet<SyncTaskResult>,
+		network_range: NetworkRange,
+	) -> Option<AbortHandle> {
+		let nid = network_range.network_id;
+		let chain = self.app.networks.read().await.get(&nid).cloned()?;
+		let db = self.app.db().clone();
+		let storage = self.app.storage.clone();
+
+		let task = async move {
+			match network_range.range {
+				(start, Some(end)) => {
+					let config_key = ConfigKey::IndexerSyncChunk(nid, end);
+
+					for block_height in start..end {
+						chain.extract_block(storage.clone(), block_height).await?;
+
+						Config::set::<_, (BlockHeight, BlockHeight)>(
+							&db,
+							config_key,
+							(block_height, end),
+						)
+						.await?;
 					}
 
-					let results = future::join_all(tasks).await;
-					if let Some(err) = results.into_iter().find(|result| result.is_err()) {
-						return Err(Report::msg(format!("A task failed: {:?}", err.as_ref().unwrap_err())));
+					// chunk is done, can delete
+					Config::delete(&db, config_key).await?;
+				}
+				(start, None) => {
+					let mut block_height = start;
+					let mut repair_depth = 0;
+
+					loop {
+						// stay behind the tip by however many confirmations
+						// the network requires
+						let latest_block_height = chain
+							.get_block_height()
+							.await?
+							.saturating_sub(chain.get_network().get_confirmations());
+
+						while block_height <= latest_block_height {
+							let Some(block_hashes) =
+								chain.extract_block(storage.clone(), block_height).await?
+							else {
+								break;
+							};
+
+							let config_key = ConfigKey::IndexerSyncTail(nid);
+							if verify_continuity(&db, nid, block_height, block_hashes).await? {
+								Config::set::<_, BlockHeight>(&db, config_key, block_height)
+									.await?;
+								block_height += 1;
+								repair_depth = 0;
+								continue;
+							}
+
+							error!(
+								network = chain.get_network().name,
+								block_height,
+								sync = "discontinuity"
+							);
+
+							// walking back this far means the rpc endpoint is most
+							// likely serving a different chain than before
+							repair_depth += 1;
+							if repair_depth > MAX_REPAIR_DEPTH {
+								return Err(Report::msg(format!(
+									"{} does not connect to previously synced blocks at block \
+									 {block_height}",
+									chain.get_network().name,
+								))
+								.into());
+							}
+
+							// otherwise extract the parent again (chain reorged),
+							// and this block too once the parent connects
+							for h in [block_height, block_height - 1] {
+								storage.get(nid, h)?.invalidate_manifest()?;
+							}
+							block_height -= 1;
+							Config::set::<_, BlockHeight>(
+								&db,
+								config_key,
+								block_height.saturating_sub(1),
+							)
+							.await?;
+						}
+
+						sleep(Duration::from_millis(chain.get_network().block_time as u64)).await;
 					}
 				}
 			}
-		}
+
+			Ok::<(), Box<dyn Error + Send + Sync>>(())
+		};
+
+		Some(tasks.spawn(async move { (nid, task.await) }))
 	}
 
-	async fn get_network_ranges(&self) -> Result<HashMap<ConfigKey, NetworkRange>> {
+	async fn get_network_ranges(
+		&self,
+		network_id: Option<PrimaryId>,
+	) -> Result<HashMap<ConfigKey, NetworkRange>> {
 		let mut ret = HashMap::new();
 
-		for (network_id, _) in self.app.networks.read().await.iter() {
-			let nid = *network_id;
+		for (nid, chain) in self.app.networks.read().await.iter() {
+			let nid = *nid;
+			if network_id.is_some_and(|network_id| network_id != nid) {
+				continue;
+			}
 
 			let mut last_copied_block =
 				Config::get::<_, BlockHeight>(self.app.db(), ConfigKey::IndexerSyncTail(nid))
@@ -131,7 +240,10 @@ impl Indexer {
 					.map(|h| h.value)
 					.unwrap_or(0);
 
-			let block_height = self.get_updated_block_height(nid, Some(last_copied_block)).await?;
+			let block_height = self
+				.get_updated_block_height(nid, Some(last_copied_block))
+				.await?
+				.saturating_sub(chain.get_network().get_confirmations());
 
 			// if first time, split up network into chunks for faster
 			// initial syncing
@@ -240,3 +352,42 @@ impl Indexer {
 		}
 	}
 }
+
+// @NOTE checks that an extracted tail block builds on the previously extracted
+// one (and if so, remembers its hash). when it doesn't, the caller walks back
+// and extracts parents again until the chain connects, which repairs shallow
+// reorgs. chains that don't connect at all mean the rpc endpoint is serving a
+// different chain than before
+async fn verify_continuity<C>(
+	db: &C,
+	network_id: PrimaryId,
+	block_height: BlockHeight,
+	block_hashes: BlockHashes,
+) -> Result<bool>
+where
+	C: ConnectionTrait,
+{
+	let config_key = ConfigKey::IndexerSyncBlockHashes(network_id);
+
+	let mut recent = Config::get::<_, Vec<(BlockHeight, String)>>(db, config_key)
+		.await?
+		.map(|h| h.value)
+		.unwrap_or_default();
+	recent.retain(|(h, _)| *h < block_height);
+
+	let is_continuous = match recent.last() {
+		Some((h, hash)) if *h + 1 == block_height => *hash == block_hashes.parent_hash,
+		_ => true,
+	};
+
+	if is_continuous {
+		recent.push((block_height, block_hashes.hash));
+		if recent.len() > MAX_REPAIR_DEPTH {
+			recent.drain(..recent.len() - MAX_REPAIR_DEPTH);
+		}
+
+		Config::set::<_, Vec<(BlockHeight, String)>>(db, config_key, recent).await?;
+	}
+
+	Ok(is_continuous)
+}
@@ -0,0 +1,99 @@
+use eyre::Result;
+use std::{collections::HashSet, time::SystemTime};
+use tokio::{
+	sync::watch::Receiver,
+	time::{sleep, Duration},
+};
+use tracing::debug;
+
+use crate::Indexer;
+use barreleye_common::{
+	chain::WarehouseData,
+	models::{set, BasicModel, PrimaryId, Token, TokenActiveModel},
+	utils,
+};
+
+const TOKENS_PER_LOOP: u64 = 25;
+const RETRY_AFTER_SECS: u64 = 60 * 60;
+
+impl Indexer {
+	// insert placeholder tokens for assets that haven't been seen before
+	pub async fn register_tokens(
+		&self,
+		warehouse_data: &WarehouseData,
+		known_tokens: &mut HashSet<(PrimaryId, String)>,
+	) -> Result<()> {
+		let new_tokens = warehouse_data
+			.transfers
+			.iter()
+			.filter(|t| !t.asset_address.is_empty())
+			.map(|t| (t.network_id as PrimaryId, t.asset_address.clone()))
+			.filter(|k| !known_tokens.contains(k))
+			.collect::<HashSet<_>>();
+
+		if !new_tokens.is_empty() {
+			Token::create_placeholders(
+				self.app.db(),
+				new_tokens
+					.iter()
+					.map(|(network_id, address)| Token::new_placeholder_model(*network_id, address))
+					.collect(),
+			)
+			.await?;
+
+			known_tokens.extend(new_tokens);
+		}
+
+		Ok(())
+	}
+
+	// fill in metadata for placeholder tokens
+	pub async fn discover_tokens(&self, mut networks_updated: Receiver<SystemTime>) -> Result<()> {
+		loop {
+			if !self.app.is_leading() {
+				sleep(Duration::from_secs(1)).await;
+				continue;
+			}
+
+			let tokens = Token::get_all_placeholders(
+				self.app.db(),
+				utils::ago_in_seconds(RETRY_AFTER_SECS),
+				TOKENS_PER_LOOP,
+			)
+			.await?;
+
+			for token in tokens.into_iter() {
+				let chain = match self.app.networks.read().await.get(&token.network_id) {
+					Some(chain) => chain.clone(),
+					_ => continue,
+				};
+
+				// on failure, only `updated_at` is touched so it's retried later
+				let mut data = TokenActiveModel::default();
+				if let Ok(Some(metadata)) = chain.get_token_metadata(&token.address).await {
+					data.decimals = set(metadata.decimals as i16);
+					data.is_placeholder = set(false);
+
+					// name and symbol are unique, so keep placeholders on conflict
+					let mut named_data = data.clone();
+					named_data.name = set(metadata.name);
+					named_data.symbol = set(metadata.symbol);
+					if Token::update_by_id(self.app.db(), &token.id, named_data).await.is_ok() {
+						debug!(token = token.address, "Discovered token");
+						continue;
+					}
+				}
+
+				Token::update_by_id(self.app.db(), &token.id, data).await?;
+			}
+
+			tokio::select! {
+				_ = networks_updated.changed() => {
+					debug!("Restarting… (networks updated)");
+					break Ok(());
+				}
+				_ = sleep(Duration::from_secs(10)) => {}
+			}
+		}
+	}
+}
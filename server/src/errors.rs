@@ -8,6 +8,8 @@ use eyre::{ErrReport, Report};
 use sea_orm::DbErr;
 use serde_json::json;
 
+use crate::REQUEST_ID;
+
 #[derive(Debug, Display, Error)]
 pub enum ServerError {
 	#[display("unauthorized")]
@@ -46,6 +48,9 @@ pub enum ServerError {
 	#[display("not found")]
 	NotFound,
 
+	#[display("too many requests: retry in {retry_after} seconds")]
+	TooManyRequests { retry_after: u64 },
+
 	#[display("rekt")]
 	Internal { error: Report },
 }
@@ -56,13 +61,20 @@ impl IntoResponse for ServerError {
 			ServerError::NotFound => StatusCode::NOT_FOUND,
 			ServerError::Unauthorized => StatusCode::UNAUTHORIZED,
 			ServerError::TooEarly { .. } => StatusCode::from_u16(425).unwrap(),
+			ServerError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
 			ServerError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
 			_ => StatusCode::BAD_REQUEST,
 		};
 
-		let body = Json(json!({
-			"error": self.to_string(),
-		}));
+		let body = Json(match REQUEST_ID.try_with(|id| id.clone()) {
+			Ok(request_id) => json!({
+				"error": self.to_string(),
+				"requestId": request_id,
+			}),
+			_ => json!({
+				"error": self.to_string(),
+			}),
+		});
 
 		(http_code, body).into_response()
 	}
@@ -0,0 +1,65 @@
+use axum::{
+	extract::{Query, State},
+	http::header,
+	response::{IntoResponse, Response},
+	Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{
+	errors::ServerError,
+	oidc::{OidcClient, SESSION_COOKIE},
+	ServerResult,
+};
+use barreleye_common::{
+	models::{BasicModel, Session},
+	utils, App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	code: String,
+	state: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseData {
+	session: Session,
+	token: String,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Extension(oidc_client): Extension<Option<Arc<OidcClient>>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Response> {
+	let oidc_client = oidc_client.ok_or(ServerError::NotFound)?;
+
+	let claims = oidc_client
+		.authenticate(&payload.code, &payload.state)
+		.await
+		.map_err(|e| ServerError::BadRequest { reason: e.to_string() })?;
+
+	// authenticated by the identity provider, but not necessarily one of ours
+	if !oidc_client.is_allowed(&claims) {
+		return Err(ServerError::Unauthorized);
+	}
+	let subject = claims.sub.unwrap_or_default();
+
+	// good time to get rid of stale sessions
+	Session::delete_all_expired(app.db()).await?;
+
+	// create new session
+	let ttl = app.settings.session_ttl;
+	let (session, token) = Session::new_model(&subject, utils::in_seconds(ttl));
+	let session_id = Session::create(app.db(), session).await?;
+	let session = Session::get(app.db(), session_id).await?.ok_or(ServerError::NotFound)?;
+
+	let cookie =
+		format!("{SESSION_COOKIE}={token}; HttpOnly; Secure; SameSite=Lax; Path=/; Max-Age={ttl}");
+
+	Ok(([(header::SET_COOKIE, cookie)], Json(ResponseData { session, token })).into_response())
+}
@@ -0,0 +1,14 @@
+use axum::{
+	response::{IntoResponse, Redirect, Response},
+	Extension,
+};
+use std::sync::Arc;
+
+use crate::{errors::ServerError, oidc::OidcClient, ServerResult};
+
+pub async fn handler(
+	Extension(oidc_client): Extension<Option<Arc<OidcClient>>>,
+) -> ServerResult<Response> {
+	let oidc_client = oidc_client.ok_or(ServerError::NotFound)?;
+	Ok(Redirect::to(&oidc_client.get_login_url().await?).into_response())
+}
@@ -0,0 +1,30 @@
+use axum::{
+	extract::State,
+	http::{header, HeaderMap, StatusCode},
+	response::{IntoResponse, Response},
+	Extension,
+};
+use std::sync::Arc;
+
+use crate::{
+	errors::ServerError,
+	oidc::{self, OidcClient, SESSION_COOKIE},
+	ServerResult,
+};
+use barreleye_common::{models::Session, App};
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Extension(oidc_client): Extension<Option<Arc<OidcClient>>>,
+	headers: HeaderMap,
+) -> ServerResult<Response> {
+	oidc_client.ok_or(ServerError::NotFound)?;
+
+	if let Some(token) = oidc::get_session_token(&headers) {
+		Session::delete_by_token(app.db(), &token).await?;
+	}
+
+	let cookie = format!("{SESSION_COOKIE}=; HttpOnly; Secure; SameSite=Lax; Path=/; Max-Age=0");
+
+	Ok((StatusCode::NO_CONTENT, [(header::SET_COOKIE, cookie)]).into_response())
+}
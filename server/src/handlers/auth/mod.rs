@@ -0,0 +1,18 @@
+use axum::{
+	routing::{get, post},
+	Router,
+};
+use std::sync::Arc;
+
+use barreleye_common::App;
+
+mod callback;
+mod login;
+mod logout;
+
+pub fn get_routes() -> Router<Arc<App>> {
+	Router::new()
+		.route("/login", get(login::handler))
+		.route("/callback", get(callback::handler))
+		.route("/logout", post(logout::handler))
+}
@@ -0,0 +1,33 @@
+use axum::{extract::State, http::header, response::IntoResponse};
+use std::sync::Arc;
+
+use crate::ServerResult;
+use barreleye_common::{
+	metrics::NetworkProgress,
+	models::{Config, ConfigKey, Network, SoftDeleteModel},
+	App,
+};
+
+// @NOTE prometheus scrape target. progress comes from the database, so any
+// instance reports it for all networks; everything else is what this
+// instance itself has done since it started
+pub async fn handler(State(app): State<Arc<App>>) -> ServerResult<impl IntoResponse> {
+	let mut networks = vec![];
+
+	for network in Network::get_all_existing(app.db(), Some(false)).await?.into_iter() {
+		let nid = network.network_id;
+
+		let synced = Config::get::<_, f64>(app.db(), ConfigKey::IndexerSyncProgress(nid))
+			.await?
+			.map(|v| v.value)
+			.unwrap_or(0.0);
+		let processed = Config::get::<_, f64>(app.db(), ConfigKey::IndexerProcessProgress(nid))
+			.await?
+			.map(|v| v.value)
+			.unwrap_or(0.0);
+
+		networks.push(NetworkProgress { network_id: nid, name: network.name, synced, processed });
+	}
+
+	Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], app.metrics.render(networks)))
+}
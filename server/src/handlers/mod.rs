@@ -1,10 +1,44 @@
-use axum::Router;
+use axum::{
+	extract::Request,
+	http::{header, HeaderValue},
+	middleware::{self, Next},
+	response::Response,
+	routing::get,
+	Router,
+};
 use std::sync::Arc;
 
 use barreleye_common::App;
 
-mod v1;
+mod auth;
+mod metrics;
+mod readyz;
+pub mod v1;
+mod v2;
 
 pub fn get_routes() -> Router<Arc<App>> {
-	Router::new().nest("/v1", v1::get_routes())
+	Router::new()
+		.route("/readyz", get(readyz::handler))
+		.route("/metrics", get(metrics::handler))
+		.nest("/auth", auth::get_routes())
+		.nest("/v1", v1::get_routes().layer(middleware::from_fn(deprecate_superseded)))
+		.nest("/v2", v2::get_routes())
+}
+
+// point clients of v1 endpoints that changed in v2 to their successor
+async fn deprecate_superseded(req: Request, next: Next) -> Response {
+	let path = req.uri().path().trim_end_matches('/').to_string();
+	let mut response = next.run(req).await;
+
+	if v2::SUPERSEDED_ROUTES.contains(&path.as_str()) {
+		let headers = response.headers_mut();
+		headers.insert("deprecation", HeaderValue::from_static("true"));
+
+		let link = format!("</v2{path}>; rel=\"successor-version\"");
+		if let Ok(link) = HeaderValue::from_str(&link) {
+			headers.insert(header::LINK, link);
+		}
+	}
+
+	response
 }
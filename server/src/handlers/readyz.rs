@@ -0,0 +1,25 @@
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use std::sync::Arc;
+
+use barreleye_common::App;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	database: bool,
+	warehouse: bool,
+}
+
+// @NOTE meant for load balancers and orchestrators: 503 until both the
+// database and the warehouse are reachable. warehouse status is as of its last
+// health check, which also re-establishes the connection when it drops
+pub async fn handler(State(app): State<Arc<App>>) -> (StatusCode, Json<Response>) {
+	let database = app.db().ping().await.is_ok();
+	let warehouse = app.warehouse.is_healthy();
+
+	let status =
+		if database && warehouse { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+	(status, Json(Response { database, warehouse }))
+}
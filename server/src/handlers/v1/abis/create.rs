@@ -0,0 +1,77 @@
+use axum::{extract::State, Json};
+use sea_orm::{ColumnTrait, Condition};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{is_valid_id, Abi, AbiColumn, BasicModel, Config, ConfigKey, Network},
+	App, Architecture, IdPrefix,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	id: Option<String>,
+	network: String,
+	name: String,
+	address: String,
+	abi: JsonValue,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<Json<Abi>> {
+	// check that id is valid
+	if let Some(id) = payload.id.clone() {
+		if !is_valid_id(&id, IdPrefix::Abi) || Abi::get_by_id(app.db(), &id).await?.is_some() {
+			return Err(ServerError::InvalidParam { field: "id".to_string(), value: id });
+		}
+	}
+
+	// fetch network (only evm calls can be decoded)
+	let network = Network::get_by_id(app.db(), &payload.network)
+		.await?
+		.filter(|n| n.architecture == Architecture::Evm)
+		.ok_or(ServerError::InvalidParam {
+			field: "network".to_string(),
+			value: payload.network,
+		})?;
+
+	// check that abi can be parsed
+	if !Abi::is_valid_abi(&payload.abi) {
+		return Err(ServerError::InvalidParam {
+			field: "abi".to_string(),
+			value: payload.abi.to_string(),
+		});
+	}
+
+	// check for duplicate network + address
+	let address = app.format_address(&payload.address).await?;
+	if !Abi::get_all_where(
+		app.db(),
+		Condition::all()
+			.add(AbiColumn::NetworkId.eq(network.network_id))
+			.add(AbiColumn::Address.eq(address.clone())),
+	)
+	.await?
+	.is_empty()
+	{
+		return Err(ServerError::Duplicate { field: "address".to_string(), value: address });
+	}
+
+	// create new
+	let abi_id = Abi::create(
+		app.db(),
+		Abi::new_model(payload.id, network.network_id, &payload.name, &address, payload.abi),
+	)
+	.await?;
+
+	// reconnect networks, so the indexer picks up the new abi
+	Config::set::<_, u8>(app.db(), ConfigKey::NetworksUpdated, 1).await?;
+
+	// return newly created
+	Ok(Abi::get(app.db(), abi_id).await?.unwrap().into())
+}
@@ -0,0 +1,46 @@
+use axum::{extract::State, http::StatusCode, Json};
+use sea_orm::ColumnTrait;
+use serde::Deserialize;
+use std::{collections::HashSet, sync::Arc};
+
+use crate::ServerResult;
+use barreleye_common::{
+	models::{Abi, AbiColumn, BasicModel, Config, ConfigKey, PrimaryId},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	abis: HashSet<String>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<StatusCode> {
+	// exit if no input
+	if payload.abis.is_empty() {
+		return Ok(StatusCode::NO_CONTENT);
+	}
+
+	// get all abis
+	let all_abis = Abi::get_all_where(app.db(), AbiColumn::Id.is_in(payload.abis)).await?;
+
+	// proceed only when there's something to delete
+	if all_abis.is_empty() {
+		return Ok(StatusCode::NO_CONTENT);
+	}
+
+	// delete all associated abis
+	Abi::delete_all_where(
+		app.db(),
+		AbiColumn::AbiId.is_in(all_abis.iter().map(|a| a.abi_id).collect::<Vec<PrimaryId>>()),
+	)
+	.await?;
+
+	// reconnect networks, so the indexer stops decoding with deleted abis
+	Config::set::<_, u8>(app.db(), ConfigKey::NetworksUpdated, 1).await?;
+
+	Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,40 @@
+use axum::{
+	extract::{Path, State},
+	Json,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{Abi, BasicModel, Network},
+	App,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	abi: Abi,
+	networks: Vec<Network>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Path(abi_id): Path<String>,
+) -> ServerResult<Json<Response>> {
+	if let Some(abi) = Abi::get_by_id(app.db(), &abi_id).await? {
+		let networks =
+			Network::get_all_by_network_ids(app.db(), abi.network_id.into(), Some(false))
+				.await?
+				.into_iter()
+				.map(|mut n| {
+					n.mask_rpc_endpoints();
+					n
+				})
+				.collect::<Vec<Network>>();
+
+		Ok(Response { abi, networks }.into())
+	} else {
+		Err(ServerError::NotFound)
+	}
+}
@@ -0,0 +1,51 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::{utils::Fields, ServerResult};
+use barreleye_common::{
+	models::{Abi, BasicModel, Network, PrimaryId},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	offset: Option<u64>,
+	limit: Option<u64>,
+	fields: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	abis: Vec<Abi>,
+	networks: Vec<Network>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Value>> {
+	let fields = Fields::parse(payload.fields, &["abis", "networks"])?;
+
+	let abis = Abi::get_all_paginated(app.db(), payload.offset, payload.limit).await?;
+
+	let networks = if fields.has("networks") {
+		let network_ids = abis.iter().map(|a| a.network_id).collect::<Vec<PrimaryId>>();
+		Network::get_all_by_network_ids(app.db(), network_ids.into(), Some(false))
+			.await?
+			.into_iter()
+			.map(|mut n| {
+				n.mask_rpc_endpoints();
+				n
+			})
+			.collect::<Vec<Network>>()
+	} else {
+		vec![]
+	};
+
+	fields.apply(Response { abis, networks })
+}
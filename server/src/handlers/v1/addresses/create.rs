@@ -126,6 +126,9 @@ pub async fn handler(
 	)
 	.await?;
 
+	// invalidate cached labels
+	Config::set::<_, u8>(app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+
 	// return newly created
 	Ok(Address::get_all_by_entity_id_network_id_and_addresses(
 		app.db(),
@@ -5,7 +5,9 @@ use std::{collections::HashSet, sync::Arc};
 
 use crate::ServerResult;
 use barreleye_common::{
-	models::{set, Address, AddressActiveModel, AddressColumn, BasicModel, PrimaryId},
+	models::{
+		set, Address, AddressActiveModel, AddressColumn, BasicModel, Config, ConfigKey, PrimaryId,
+	},
 	App,
 };
 
@@ -42,5 +44,8 @@ pub async fn handler(
 	)
 	.await?;
 
+	// invalidate cached labels
+	Config::set::<_, u8>(app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+
 	Ok(StatusCode::NO_CONTENT)
 }
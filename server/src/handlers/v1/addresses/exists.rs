@@ -0,0 +1,77 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+};
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{AddressActivity, Network, SoftDeleteModel},
+	App, BlockHeight,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	address: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseNetwork {
+	network: String,
+	address: String,
+	first_block_height: BlockHeight,
+	last_block_height: BlockHeight,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	address: String,
+	exists: bool,
+	networks: Vec<ResponseNetwork>,
+}
+
+// @NOTE whether an address sent or received anything on any network, as a
+// cheap check before a full `/v1/info` lookup. the address is looked up the
+// way each network formats it (eg: checksummed on evm)
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	// address goes into the query as-is
+	let address = payload.address.trim().to_string();
+	if address.is_empty() || !address.chars().all(|c| c.is_ascii_alphanumeric()) {
+		return Err(ServerError::InvalidParam { field: "address".to_string(), value: address });
+	}
+
+	let mut addresses = HashSet::from([address.clone()]);
+	for chain in app.networks.read().await.values() {
+		addresses.insert(chain.format_address(&address));
+	}
+
+	let networks_map = Network::get_all_existing(app.db(), Some(false))
+		.await?
+		.into_iter()
+		.map(|n| (n.network_id as u64, n.id))
+		.collect::<HashMap<_, _>>();
+
+	let networks =
+		AddressActivity::get_all_by_addresses(&app.warehouse, addresses.into_iter().collect())
+			.await?
+			.into_iter()
+			.filter_map(|a| {
+				networks_map.get(&a.network_id).map(|network| ResponseNetwork {
+					network: network.clone(),
+					address: a.address,
+					first_block_height: a.first_block_height,
+					last_block_height: a.last_block_height,
+				})
+			})
+			.collect::<Vec<_>>();
+
+	Ok(Response { address, exists: !networks.is_empty(), networks }.into())
+}
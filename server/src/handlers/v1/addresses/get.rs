@@ -8,7 +8,7 @@ use std::sync::Arc;
 use crate::{errors::ServerError, ServerResult};
 use barreleye_common::{
 	models::{Address, Network, SoftDeleteModel},
-	utils, App,
+	App,
 };
 
 #[derive(Serialize)]
@@ -28,7 +28,7 @@ pub async fn handler(
 				.await?
 				.into_iter()
 				.map(|mut n| {
-					n.rpc_endpoint = utils::with_masked_auth(&n.rpc_endpoint);
+					n.mask_rpc_endpoints();
 					n
 				})
 				.collect::<Vec<Network>>();
@@ -2,12 +2,13 @@ use axum::{extract::State, Json};
 use axum_extra::extract::Query;
 use sea_orm::ColumnTrait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::sync::Arc;
 
-use crate::ServerResult;
+use crate::{utils::Fields, ServerResult};
 use barreleye_common::{
 	models::{Address, AddressColumn, BasicModel, Network, PrimaryId},
-	utils, App,
+	App,
 };
 
 #[derive(Deserialize)]
@@ -15,6 +16,7 @@ use barreleye_common::{
 pub struct Payload {
 	offset: Option<u64>,
 	limit: Option<u64>,
+	fields: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -27,7 +29,9 @@ pub struct Response {
 pub async fn handler(
 	State(app): State<Arc<App>>,
 	Query(payload): Query<Payload>,
-) -> ServerResult<Json<Response>> {
+) -> ServerResult<Json<Value>> {
+	let fields = Fields::parse(payload.fields, &["addresses", "networks"])?;
+
 	let addresses = Address::get_all_paginated_where(
 		app.db(),
 		AddressColumn::IsDeleted.eq(false),
@@ -36,15 +40,19 @@ pub async fn handler(
 	)
 	.await?;
 
-	let network_ids = addresses.iter().map(|a| a.network_id).collect::<Vec<PrimaryId>>();
-	let networks = Network::get_all_by_network_ids(app.db(), network_ids.into(), Some(false))
-		.await?
-		.into_iter()
-		.map(|mut n| {
-			n.rpc_endpoint = utils::with_masked_auth(&n.rpc_endpoint);
-			n
-		})
-		.collect::<Vec<Network>>();
+	let networks = if fields.has("networks") {
+		let network_ids = addresses.iter().map(|a| a.network_id).collect::<Vec<PrimaryId>>();
+		Network::get_all_by_network_ids(app.db(), network_ids.into(), Some(false))
+			.await?
+			.into_iter()
+			.map(|mut n| {
+				n.mask_rpc_endpoints();
+				n
+			})
+			.collect::<Vec<Network>>()
+	} else {
+		vec![]
+	};
 
-	Ok(Response { addresses, networks }.into())
+	fields.apply(Response { addresses, networks })
 }
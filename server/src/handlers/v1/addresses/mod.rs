@@ -8,6 +8,7 @@ use barreleye_common::App;
 
 mod create;
 mod delete;
+mod exists;
 mod get;
 mod list;
 
@@ -15,6 +16,7 @@ pub fn get_routes() -> Router<Arc<App>> {
 	Router::new()
 		.route("/", post(create::handler))
 		.route("/", get(list::handler))
+		.route("/exists", get(exists::handler))
 		.route("/:id", get(get::handler))
 		.route("/", delete(delete::handler))
 }
@@ -0,0 +1,70 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::{errors::ServerError, utils::Fields, ServerResult};
+use barreleye_common::{
+	models::{Alert, Network, PrimaryId, SoftDeleteModel},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	network: Option<String>,
+	is_acknowledged: Option<bool>,
+	offset: Option<u64>,
+	limit: Option<u64>,
+	fields: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	alerts: Vec<Alert>,
+	networks: Vec<Network>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Value>> {
+	let fields = Fields::parse(payload.fields, &["alerts", "networks"])?;
+
+	let network_id = match payload.network {
+		Some(id) => Some(
+			Network::get_existing_by_id(app.db(), &id)
+				.await?
+				.ok_or(ServerError::InvalidParam { field: "network".to_string(), value: id })?
+				.network_id,
+		),
+		None => None,
+	};
+
+	let alerts = Alert::get_all_paginated_by_network_id(
+		app.db(),
+		network_id,
+		payload.is_acknowledged,
+		payload.offset,
+		payload.limit,
+	)
+	.await?;
+
+	let networks = if fields.has("networks") {
+		let network_ids = alerts.iter().map(|a| a.network_id).collect::<Vec<PrimaryId>>();
+		Network::get_all_by_network_ids(app.db(), network_ids.into(), None)
+			.await?
+			.into_iter()
+			.map(|mut n| {
+				n.mask_rpc_endpoints();
+				n
+			})
+			.collect::<Vec<Network>>()
+	} else {
+		vec![]
+	};
+
+	fields.apply(Response { alerts, networks })
+}
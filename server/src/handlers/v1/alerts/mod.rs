@@ -0,0 +1,14 @@
+use axum::{
+	routing::{get, put},
+	Router,
+};
+use std::sync::Arc;
+
+use barreleye_common::App;
+
+mod list;
+mod update;
+
+pub fn get_routes() -> Router<Arc<App>> {
+	Router::new().route("/", get(list::handler)).route("/:id", put(update::handler))
+}
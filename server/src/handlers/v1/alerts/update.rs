@@ -0,0 +1,40 @@
+use axum::{
+	extract::{Path, State},
+	http::StatusCode,
+	Json,
+};
+use sea_orm::ActiveModelTrait;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{optional_set, Alert, AlertActiveModel, BasicModel},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	is_acknowledged: Option<bool>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Path(alert_id): Path<String>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<StatusCode> {
+	if Alert::get_by_id(app.db(), &alert_id).await?.is_none() {
+		return Err(ServerError::NotFound);
+	}
+
+	let update_data = AlertActiveModel {
+		is_acknowledged: optional_set(payload.is_acknowledged),
+		..Default::default()
+	};
+	if update_data.is_changed() {
+		Alert::update_by_id(app.db(), &alert_id, update_data).await?;
+	}
+
+	Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,47 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{Annotation, Network, SoftDeleteModel},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	network: Option<String>,
+	address: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	annotations: Vec<Annotation>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	let address = payload.address.trim().to_string();
+	if address.is_empty() {
+		return Err(ServerError::MissingInputParams);
+	}
+
+	let network_id = match payload.network {
+		Some(id) => Some(
+			Network::get_existing_by_id(app.db(), &id)
+				.await?
+				.ok_or(ServerError::InvalidParam { field: "network".to_string(), value: id })?
+				.network_id,
+		),
+		None => None,
+	};
+
+	let annotations = Annotation::get_all_by_addresses(app.db(), network_id, vec![address]).await?;
+
+	Ok(Response { annotations }.into())
+}
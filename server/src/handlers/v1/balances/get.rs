@@ -0,0 +1,166 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use sea_orm::ColumnTrait;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{
+		Balance, BasicModel, Config, ConfigKey, Network, SoftDeleteModel, StakingDeposit, Token,
+		TokenColumn,
+	},
+	utils, App, Architecture, BlockHeight,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	network: String,
+	address: String,
+	block_height: BlockHeight,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseAsset {
+	token: Option<String>,
+	balance: String,
+	balance_formatted: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	network: String,
+	address: String,
+	block_height: BlockHeight,
+	assets: Vec<ResponseAsset>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	staked: Option<ResponseAsset>,
+	tokens: Vec<Token>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	let network = Network::get_existing_by_id(app.db(), &payload.network).await?.ok_or(
+		ServerError::InvalidParam { field: "network".to_string(), value: payload.network },
+	)?;
+	let nid = network.network_id;
+
+	if !payload.address.chars().all(|c| c.is_ascii_alphanumeric()) {
+		return Err(ServerError::InvalidParam {
+			field: "address".to_string(),
+			value: payload.address,
+		});
+	}
+
+	let address = match app.networks.read().await.get(&nid) {
+		Some(chain) => chain.format_address(&payload.address),
+		_ => payload.address,
+	};
+
+	// results are only cacheable once the requested block has been fully
+	// processed, otherwise they'd still change
+	let processed_block_height =
+		Config::get::<_, BlockHeight>(app.db(), ConfigKey::IndexerProcessTail(nid))
+			.await?
+			.map(|v| v.value);
+	let processed = Config::get::<_, f64>(app.db(), ConfigKey::IndexerProcessProgress(nid))
+		.await?
+		.map(|v| v.value)
+		.unwrap_or(0.0);
+	let is_cacheable =
+		processed >= 1.0 && processed_block_height.is_some_and(|h| h >= payload.block_height);
+
+	app.balance_cache.sync(app.db()).await?;
+
+	let key = (nid, address.clone(), payload.block_height);
+	let balances = match app.balance_cache.get(&key).await {
+		Some(balances) => balances,
+		None => {
+			let balances = Balance::get_all_by_address_at_block_height(
+				&app.warehouse,
+				nid,
+				&address,
+				payload.block_height,
+			)
+			.await?;
+
+			if is_cacheable {
+				app.balance_cache.insert(key, balances.clone()).await;
+			}
+
+			balances
+		}
+	};
+
+	// fetch tokens for decimals
+	let asset_addresses = balances
+		.iter()
+		.filter(|b| !b.asset_address.is_empty())
+		.map(|b| b.asset_address.clone())
+		.collect::<Vec<String>>();
+	let tokens = if asset_addresses.is_empty() {
+		vec![]
+	} else {
+		Token::get_all_where(app.db(), TokenColumn::Address.is_in(asset_addresses))
+			.await?
+			.into_iter()
+			.filter(|t| t.network_id == nid)
+			.collect()
+	};
+	let tokens_map = tokens.iter().map(|t| (t.address.clone(), t)).collect::<HashMap<_, _>>();
+
+	let assets = balances
+		.into_iter()
+		.map(|b| {
+			let token = tokens_map.get(&b.asset_address);
+			let decimals = match token {
+				_ if b.asset_address.is_empty() => Some(network.architecture.native_decimals()),
+				Some(token) if !token.is_placeholder => Some(token.decimals as u16),
+				_ => None,
+			};
+
+			ResponseAsset {
+				token: token.map(|t| t.id.clone()),
+				balance: b.balance.to_string(),
+				balance_formatted: decimals.map(|d| utils::format_amount(b.balance, d)),
+			}
+		})
+		.collect();
+
+	// @NOTE funds deposited into beacon chain staking have left the address's
+	// balance, but are still its funds. they're reported separately (and not
+	// counted in `assets`) since they can't be moved until withdrawn
+	let staked = if network.architecture == Architecture::Evm {
+		let staked = StakingDeposit::get_staked_at_block_height(
+			&app.warehouse,
+			nid,
+			&address,
+			payload.block_height,
+		)
+		.await?;
+		let decimals = network.architecture.native_decimals();
+
+		Some(staked).filter(|s| !s.is_zero()).map(|s| ResponseAsset {
+			token: None,
+			balance: s.to_string(),
+			balance_formatted: Some(utils::format_amount(s, decimals)),
+		})
+	} else {
+		None
+	};
+
+	Ok(Response {
+		network: network.id,
+		address,
+		block_height: payload.block_height,
+		assets,
+		staked,
+		tokens,
+	}
+	.into())
+}
@@ -0,0 +1,11 @@
+use axum::{routing::get, Router};
+use std::sync::Arc;
+
+use barreleye_common::App;
+
+mod get;
+mod top;
+
+pub fn get_routes() -> Router<Arc<App>> {
+	Router::new().route("/", get(get::handler)).route("/top", get(top::handler))
+}
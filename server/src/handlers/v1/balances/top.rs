@@ -0,0 +1,146 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	chain::U256,
+	models::{Address, Balance, BasicModel, Entity, Network, SoftDeleteModel, Token},
+	utils, App,
+};
+
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 1_000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	network: String,
+	token: Option<String>,
+	cursor: Option<String>,
+	limit: Option<u64>,
+	#[serde(default)]
+	with_entities: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseHolder {
+	address: String,
+	balance: String,
+	balance_formatted: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	entity: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	network: String,
+	token: Option<Token>,
+	holders: Vec<ResponseHolder>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	entities: Option<Vec<Entity>>,
+	next_cursor: Option<String>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	let network = Network::get_existing_by_id(app.db(), &payload.network).await?.ok_or(
+		ServerError::InvalidParam { field: "network".to_string(), value: payload.network },
+	)?;
+
+	let token = match payload.token {
+		Some(token_id) => match Token::get_by_id(app.db(), &token_id).await? {
+			Some(token) if token.network_id == network.network_id => Some(token),
+			_ => {
+				return Err(ServerError::InvalidParam {
+					field: "token".to_string(),
+					value: token_id,
+				})
+			}
+		},
+		None => None,
+	};
+
+	let cursor = match payload.cursor {
+		Some(cursor) => Some(
+			utils::decode_cursor::<(String, String)>(&cursor)
+				.filter(|(_, address)| address.chars().all(|c| c.is_ascii_alphanumeric()))
+				.and_then(|(balance, address)| Some((U256::from_dec_str(&balance).ok()?, address)))
+				.ok_or(ServerError::InvalidParam { field: "cursor".to_string(), value: cursor })?,
+		),
+		None => None,
+	};
+
+	let limit = payload.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+	let balances = Balance::get_top_by_asset_address(
+		&app.warehouse,
+		network.network_id,
+		&token.as_ref().map(|t| t.address.clone()).unwrap_or_default(),
+		cursor,
+		limit,
+	)
+	.await?;
+
+	// a full page means there might be more
+	let next_cursor = match balances.last() {
+		Some(b) if balances.len() as u64 == limit => {
+			Some(utils::encode_cursor(&(b.balance.to_string(), b.address.clone())))
+		}
+		_ => None,
+	};
+
+	let decimals = match &token {
+		Some(token) if token.is_placeholder => None,
+		Some(token) => Some(token.decimals as u16),
+		None => Some(network.architecture.native_decimals()),
+	};
+
+	// optionally annotate holders with known entities
+	let mut address_map = HashMap::new();
+	let mut entities = None;
+	if payload.with_entities && !balances.is_empty() {
+		let addresses = Address::get_all_by_addresses(
+			app.db(),
+			balances.iter().map(|b| b.address.clone()).collect(),
+			Some(false),
+		)
+		.await?
+		.into_iter()
+		.filter(|a| a.network_id == network.network_id)
+		.collect::<Vec<Address>>();
+
+		let fetched_entities = Entity::get_all_by_entity_ids(
+			app.db(),
+			addresses.iter().map(|a| a.entity_id).collect::<Vec<_>>().into(),
+			Some(false),
+		)
+		.await?;
+
+		let entity_ids =
+			fetched_entities.iter().map(|e| (e.entity_id, e.id.clone())).collect::<HashMap<_, _>>();
+		for address in addresses.into_iter() {
+			if let Some(id) = entity_ids.get(&address.entity_id) {
+				address_map.insert(address.address, id.clone());
+			}
+		}
+
+		entities = Some(fetched_entities);
+	}
+
+	let holders = balances
+		.into_iter()
+		.map(|b| ResponseHolder {
+			entity: address_map.get(&b.address).cloned(),
+			address: b.address,
+			balance: b.balance.to_string(),
+			balance_formatted: decimals.map(|d| utils::format_amount(b.balance, d)),
+		})
+		.collect();
+
+	Ok(Response { network: network.id, token, holders, entities, next_cursor }.into())
+}
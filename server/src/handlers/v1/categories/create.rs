@@ -0,0 +1,56 @@
+use axum::{extract::State, Json};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{is_valid_id, BasicModel, Category},
+	App, IdPrefix,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	id: Option<String>,
+	name: String,
+	severity: i16,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<Json<Category>> {
+	// check that id is valid
+	if let Some(id) = payload.id.clone() {
+		if !is_valid_id(&id, IdPrefix::Category) ||
+			Category::get_by_id(app.db(), &id).await?.is_some()
+		{
+			return Err(ServerError::InvalidParam { field: "id".to_string(), value: id });
+		}
+	}
+
+	// check that severity is in range
+	if !Category::is_valid_severity(payload.severity) {
+		return Err(ServerError::InvalidParam {
+			field: "severity".to_string(),
+			value: payload.severity.to_string(),
+		});
+	}
+
+	// check for duplicate name
+	if Category::get_by_name(app.db(), &payload.name).await?.is_some() {
+		return Err(ServerError::Duplicate { field: "name".to_string(), value: payload.name });
+	}
+
+	// create new
+	let category_id = Category::create(
+		app.db(),
+		Category::new_model(payload.id, &payload.name, payload.severity),
+	)
+	.await?;
+
+	// return newly created
+	let mut category = Category::get(app.db(), category_id).await?.unwrap();
+	category.tags = Some(vec![]);
+	Ok(category.into())
+}
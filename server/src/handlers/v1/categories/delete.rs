@@ -0,0 +1,56 @@
+use axum::{extract::State, http::StatusCode, Json};
+use sea_orm::ColumnTrait;
+use serde::Deserialize;
+use std::{collections::HashSet, sync::Arc};
+
+use crate::ServerResult;
+use barreleye_common::{
+	models::{
+		set, BasicModel, Category, CategoryColumn, Config, ConfigKey, PrimaryId, Tag,
+		TagActiveModel, TagColumn,
+	},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	categories: HashSet<String>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<StatusCode> {
+	// exit if no input
+	if payload.categories.is_empty() {
+		return Ok(StatusCode::NO_CONTENT);
+	}
+
+	// get all categories
+	let all_categories =
+		Category::get_all_where(app.db(), CategoryColumn::Id.is_in(payload.categories)).await?;
+
+	// proceed only when there's something to delete
+	if all_categories.is_empty() {
+		return Ok(StatusCode::NO_CONTENT);
+	}
+
+	let category_ids = all_categories.iter().map(|c| c.category_id).collect::<Vec<PrimaryId>>();
+
+	// unmap associated tags
+	Tag::update_all_where(
+		app.db(),
+		TagColumn::CategoryId.is_in(category_ids.clone()),
+		TagActiveModel { category_id: set(None), ..Default::default() },
+	)
+	.await?;
+
+	// delete all categories
+	Category::delete_all_where(app.db(), CategoryColumn::CategoryId.is_in(category_ids)).await?;
+
+	// invalidate cached labels
+	Config::set::<_, u8>(app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+
+	Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,27 @@
+use axum::{
+	extract::{Path, State},
+	Json,
+};
+use std::sync::Arc;
+
+use crate::{
+	errors::ServerError, handlers::v1::categories::get_tags_by_category_ids, ServerResult,
+};
+use barreleye_common::{
+	models::{BasicModel, Category},
+	App,
+};
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Path(category_id): Path<String>,
+) -> ServerResult<Json<Category>> {
+	if let Some(mut category) = Category::get_by_id(app.db(), &category_id).await? {
+		let tags_map = get_tags_by_category_ids(app.clone(), category.category_id.into()).await?;
+
+		category.tags = tags_map.get(&category.category_id).cloned().or(Some(vec![]));
+		Ok(category.into())
+	} else {
+		Err(ServerError::NotFound)
+	}
+}
@@ -0,0 +1,32 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{handlers::v1::categories::get_tags_by_category_ids, ServerResult};
+use barreleye_common::{
+	models::{BasicModel, Category},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	offset: Option<u64>,
+	limit: Option<u64>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Vec<Category>>> {
+	let mut categories =
+		Category::get_all_paginated(app.db(), payload.offset, payload.limit).await?;
+
+	let tags_map = get_tags_by_category_ids(app.clone(), categories.clone().into()).await?;
+	for category in categories.iter_mut() {
+		category.tags = tags_map.get(&category.category_id).cloned().or(Some(vec![]));
+	}
+
+	Ok(categories.into())
+}
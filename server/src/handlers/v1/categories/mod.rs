@@ -0,0 +1,41 @@
+use axum::{
+	routing::{delete, get, post, put},
+	Router,
+};
+use eyre::Result;
+use std::{collections::HashMap, sync::Arc};
+
+use barreleye_common::{
+	models::{PrimaryId, PrimaryIds, Tag},
+	App,
+};
+
+mod create;
+mod delete;
+mod get;
+mod list;
+mod update;
+
+pub fn get_routes() -> Router<Arc<App>> {
+	Router::new()
+		.route("/", post(create::handler))
+		.route("/", get(list::handler))
+		.route("/:id", get(get::handler))
+		.route("/:id", put(update::handler))
+		.route("/", delete(delete::handler))
+}
+
+// tag ids mapped to each category
+pub async fn get_tags_by_category_ids(
+	app: Arc<App>,
+	category_ids: PrimaryIds,
+) -> Result<HashMap<PrimaryId, Vec<String>>> {
+	let mut tags_map = HashMap::<PrimaryId, Vec<String>>::new();
+	for tag in Tag::get_all_by_category_ids(app.db(), category_ids).await? {
+		if let Some(category_id) = tag.category_id {
+			tags_map.entry(category_id).or_default().push(tag.id);
+		}
+	}
+
+	Ok(tags_map)
+}
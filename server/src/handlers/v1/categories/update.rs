@@ -0,0 +1,65 @@
+use axum::{
+	extract::{Path, State},
+	http::StatusCode,
+	Json,
+};
+use sea_orm::ActiveModelTrait;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{optional_set, BasicModel, Category, CategoryActiveModel, Config, ConfigKey},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	name: Option<String>,
+	severity: Option<i16>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Path(category_id): Path<String>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<StatusCode> {
+	if let Some(category) = Category::get_by_id(app.db(), &category_id).await? {
+		// check that severity is in range
+		if let Some(severity) = payload.severity {
+			if !Category::is_valid_severity(severity) {
+				return Err(ServerError::InvalidParam {
+					field: "severity".to_string(),
+					value: severity.to_string(),
+				});
+			}
+		}
+
+		// check for duplicate name
+		if let Some(name) = payload.name.clone() {
+			if let Some(other_category) = Category::get_by_name(app.db(), &name).await? {
+				if other_category.id != category.id {
+					return Err(ServerError::Duplicate { field: "name".to_string(), value: name });
+				}
+			}
+		}
+
+		// update
+		let update_data = CategoryActiveModel {
+			name: optional_set(payload.name),
+			severity: optional_set(payload.severity),
+			..Default::default()
+		};
+		if update_data.is_changed() {
+			Category::update_by_id(app.db(), &category_id, update_data).await?;
+		}
+
+		// invalidate cached labels
+		Config::set::<_, u8>(app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+
+		Ok(StatusCode::NO_CONTENT)
+	} else {
+		Err(ServerError::NotFound)
+	}
+}
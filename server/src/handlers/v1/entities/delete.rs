@@ -6,8 +6,8 @@ use std::{collections::HashSet, sync::Arc};
 use crate::ServerResult;
 use barreleye_common::{
 	models::{
-		set, Address, AddressActiveModel, AddressColumn, BasicModel, Entity, EntityActiveModel,
-		EntityColumn, PrimaryId,
+		set, Address, AddressActiveModel, AddressColumn, BasicModel, Config, ConfigKey, Entity,
+		EntityActiveModel, EntityColumn, PrimaryId,
 	},
 	App,
 };
@@ -53,5 +53,8 @@ pub async fn handler(
 	)
 	.await?;
 
+	// invalidate cached labels
+	Config::set::<_, u8>(app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+
 	Ok(StatusCode::NO_CONTENT)
 }
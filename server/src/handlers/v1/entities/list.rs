@@ -4,10 +4,12 @@ use axum::{
 };
 use sea_orm::ColumnTrait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::sync::Arc;
 
 use crate::{
 	handlers::v1::entities::{get_addresses_data, get_tags_data},
+	utils::Fields,
 	ServerResult,
 };
 use barreleye_common::{
@@ -20,6 +22,7 @@ use barreleye_common::{
 pub struct Payload {
 	offset: Option<u64>,
 	limit: Option<u64>,
+	fields: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -34,7 +37,9 @@ pub struct Response {
 pub async fn handler(
 	State(app): State<Arc<App>>,
 	Query(payload): Query<Payload>,
-) -> ServerResult<Json<Response>> {
+) -> ServerResult<Json<Value>> {
+	let fields = Fields::parse(payload.fields, &["entities", "tags", "addresses", "networks"])?;
+
 	let mut entities = Entity::get_all_paginated_where(
 		app.db(),
 		EntityColumn::IsDeleted.eq(false),
@@ -56,5 +61,5 @@ pub async fn handler(
 		entity.addresses = addresses_map.get(&entity.entity_id).cloned().or(Some(vec![]));
 	}
 
-	Ok(Response { entities, tags, addresses, networks }.into())
+	fields.apply(Response { entities, tags, addresses, networks })
 }
@@ -7,7 +7,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use barreleye_common::{
 	models::{Address, Network, PrimaryId, PrimaryIds, Tag},
-	utils, App,
+	App,
 };
 
 mod create;
@@ -59,7 +59,7 @@ pub async fn get_addresses_data(
 	let networks = networks_map
 		.into_values()
 		.map(|mut n| {
-			n.rpc_endpoint = utils::with_masked_auth(&n.rpc_endpoint);
+			n.mask_rpc_endpoints();
 			n
 		})
 		.collect::<Vec<Network>>();
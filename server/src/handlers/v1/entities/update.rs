@@ -10,8 +10,8 @@ use std::sync::Arc;
 use crate::{errors::ServerError, utils::extract_primary_ids, ServerResult};
 use barreleye_common::{
 	models::{
-		optional_set, BasicModel, Entity, EntityActiveModel, EntityTag, SoftDeleteModel, Tag,
-		TagColumn,
+		optional_set, BasicModel, Config, ConfigKey, Entity, EntityActiveModel, EntityTag,
+		SoftDeleteModel, Tag, TagColumn,
 	},
 	App, IdPrefix,
 };
@@ -94,6 +94,9 @@ pub async fn handler(
 			.await?;
 		}
 
+		// invalidate cached labels
+		Config::set::<_, u8>(app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+
 		Ok(StatusCode::NO_CONTENT)
 	} else {
 		Err(ServerError::NotFound)
@@ -0,0 +1,77 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{Fee, Network, PrimaryId, SoftDeleteModel},
+	utils, App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	network: Option<String>,
+	address: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseFees {
+	network: String,
+	transactions: u64,
+	gas_used: u64,
+	total_fee: String,
+	total_fee_formatted: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	address: Option<String>,
+	fees: Vec<ResponseFees>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	// fees are summarized per network, so default to all of them
+	let networks = match payload.network {
+		Some(network_id) => vec![Network::get_existing_by_id(app.db(), &network_id).await?.ok_or(
+			ServerError::InvalidParam { field: "network".to_string(), value: network_id },
+		)?],
+		None => Network::get_all_existing(app.db(), Some(false)).await?,
+	};
+	let networks_map =
+		networks.into_iter().map(|n| (n.network_id, n)).collect::<HashMap<PrimaryId, _>>();
+
+	let address = match payload.address {
+		Some(address) => Some(app.format_address(&address).await?),
+		_ => None,
+	};
+
+	let fees = Fee::get_summaries(
+		&app.warehouse,
+		Some(networks_map.keys().copied().collect::<Vec<_>>().into()),
+		address.clone(),
+	)
+	.await?
+	.into_iter()
+	.filter_map(|s| {
+		networks_map.get(&(s.network_id as PrimaryId)).map(|n| ResponseFees {
+			network: n.id.clone(),
+			transactions: s.transactions,
+			gas_used: s.gas_used,
+			total_fee: s.total_fee.to_string(),
+			total_fee_formatted: utils::format_amount(
+				s.total_fee,
+				n.architecture.native_decimals(),
+			),
+		})
+	})
+	.collect();
+
+	Ok(Response { address, fees }.into())
+}
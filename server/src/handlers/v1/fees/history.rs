@@ -0,0 +1,122 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{Network, PrimaryId, SoftDeleteModel, Transfer},
+	utils, App,
+};
+
+#[derive(Deserialize, Serialize, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Interval {
+	Day,
+	#[default]
+	Month,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	address: String,
+	network: Option<String>,
+	interval: Option<Interval>,
+	from: Option<String>,
+	to: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponsePeriod {
+	network: String,
+	date: String,
+	transactions: u64,
+	total_fee: String,
+	total_fee_formatted: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	address: String,
+	interval: Interval,
+	periods: Vec<ResponsePeriod>,
+}
+
+// @NOTE fees paid by an address over time, eg: for tax and accounting. these
+// come from fee transfers, so blocks processed before those modules were added
+// need a resync of the modules to show up
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	// periods are per network, so default to all of them
+	let networks = match payload.network {
+		Some(network_id) => vec![Network::get_existing_by_id(app.db(), &network_id).await?.ok_or(
+			ServerError::InvalidParam { field: "network".to_string(), value: network_id },
+		)?],
+		None => Network::get_all_existing(app.db(), Some(false)).await?,
+	};
+	let networks_map =
+		networks.into_iter().map(|n| (n.network_id, n)).collect::<HashMap<PrimaryId, _>>();
+
+	// check date range
+	let parse_date = |field: &str, date: Option<String>| match date {
+		Some(date) => utils::parse_date(&date)
+			.map(Some)
+			.ok_or(ServerError::InvalidParam { field: field.to_string(), value: date }),
+		None => Ok(None),
+	};
+	let from = parse_date("from", payload.from)?;
+	let to = parse_date("to", payload.to)?;
+	if let (Some(from), Some(to)) = (from, to) {
+		if from > to {
+			return Err(ServerError::InvalidParam {
+				field: "from".to_string(),
+				value: from.to_string(),
+			});
+		}
+	}
+
+	// address goes into the query as-is
+	if !payload.address.chars().all(|c| c.is_ascii_alphanumeric()) {
+		return Err(ServerError::InvalidParam {
+			field: "address".to_string(),
+			value: payload.address,
+		});
+	}
+	let address = app.format_address(&payload.address).await?;
+	let interval = payload.interval.unwrap_or_default();
+
+	let periods = if networks_map.is_empty() {
+		vec![]
+	} else {
+		Transfer::get_fee_periods(
+			&app.warehouse,
+			networks_map.keys().copied().collect::<Vec<_>>().into(),
+			&address,
+			interval == Interval::Month,
+			from,
+			to,
+		)
+		.await?
+		.into_iter()
+		.filter_map(|p| {
+			networks_map.get(&(p.network_id as PrimaryId)).map(|n| ResponsePeriod {
+				network: n.id.clone(),
+				date: utils::date_from_days(p.date).to_string(),
+				transactions: p.transactions,
+				total_fee: p.total_fee.to_string(),
+				total_fee_formatted: utils::format_amount(
+					p.total_fee,
+					n.architecture.native_decimals(),
+				),
+			})
+		})
+		.collect()
+	};
+
+	Ok(Response { address, interval, periods }.into())
+}
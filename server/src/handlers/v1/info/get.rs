@@ -1,26 +1,62 @@
 use axum::{extract::State, Json};
 use axum_extra::extract::Query;
+use chrono::{DateTime, NaiveDateTime};
 use eyre::Result;
 use sea_orm::ColumnTrait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::{
+	cmp,
 	collections::{HashMap, HashSet},
+	future::Future,
 	sync::Arc,
 };
+use uuid::Uuid;
 
-use crate::{errors::ServerError, ServerResult};
+use crate::{errors::ServerError, utils::Fields, ServerResult};
 use barreleye_common::{
+	chain::U256,
 	models::{
-		Address, Amount, Balance, BasicModel, Entity, Link, Network, PrimaryId, SanitizedEntity,
-		SanitizedNetwork, SanitizedTag, Tag, Token, TokenColumn,
+		Address, Allowance, Amount, Annotation, Approval, Balance, BasicModel, BridgeTransfer,
+		Category, Config, ConfigKey, DailyAmount, Entity, Funder, JoinedTag, Link, Network, Price,
+		PrimaryId, SanitizedEntity, SanitizedNetwork, SanitizedTag, Tag, Token, TokenColumn,
+		Transfer,
 	},
-	App, RiskLevel, RiskReason,
+	prices, utils, AnnotationKind, App, RiskLevel, RiskReason,
 };
 
+// how many bridges a trail is followed back through
+const MAX_BRIDGE_HOPS: usize = 3;
+
+// queries screened at once with a comma-separated `q`
+const MAX_QUERIES: usize = 100;
+
+// transfers looked up at once when sources are expanded
+const TRANSFERS_BATCH_SIZE: usize = 1_000;
+
+// response sections that `fields` can select
+pub const FIELDS: &[&str] = &[
+	"addresses",
+	"risk",
+	"exposure",
+	"assets",
+	"tokens",
+	"sources",
+	"approvals",
+	"activity",
+	"funders",
+	"networks",
+	"entities",
+	"tags",
+];
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Payload {
 	q: String,
+	fields: Option<String>,
+	// `transfers` lists the transfers behind each source's hops
+	expand: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -28,6 +64,22 @@ pub struct Payload {
 pub struct ResponseRisk {
 	level: RiskLevel,
 	reasons: HashSet<RiskReason>,
+	// highest severity among exposure categories
+	#[serde(skip_serializing_if = "Option::is_none")]
+	severity: Option<i16>,
+}
+
+// @NOTE exposure broken down by the deployment's own categories: `direct` is
+// when the requested addresses are labeled with it, and `sources` is how many
+// sources are
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseExposure {
+	pub category: String,
+	pub name: String,
+	pub severity: i16,
+	pub direct: bool,
+	pub sources: u64,
 }
 
 #[derive(Serialize)]
@@ -36,6 +88,12 @@ pub struct ResponseAsset {
 	network: String,
 	token: Option<String>,
 	balance: String,
+	balance_formatted: Option<String>,
+	// only for assets with a known price
+	#[serde(skip_serializing_if = "Option::is_none")]
+	value_usd: Option<f64>,
+	#[serde(skip)]
+	raw_balance: U256,
 }
 
 #[derive(Serialize, Eq, PartialEq, Hash)]
@@ -51,34 +109,264 @@ pub struct ResponseToken {
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResponseSource {
-	network: String,
-	entity: String,
-	from: String,
-	to: String,
+	pub network: String,
+	pub entity: String,
+	pub from: String,
+	pub to: String,
+	pub hops: u64,
+	// funds passed through a mixer, so the trail is less reliable
+	pub obfuscated: bool,
+	// bridges funds crossed on their way to `to`, latest first
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub bridges: Vec<String>,
+	// only with `expand=transfers`
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub transfers: Option<Vec<ResponseSourceTransfer>>,
+}
+
+// one hop of a source's trail, in the order funds moved
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseSourceTransfer {
+	pub network: String,
+	pub tx_hash: String,
+	pub block_height: u64,
+	pub from: String,
+	pub to: String,
+	pub token: Option<String>,
+	pub amount: String,
+	// for native assets with a known price on the day of the transfer
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value_usd: Option<f64>,
+	pub transferred_at: Option<NaiveDateTime>,
+}
+
+// unlimited allowance granted by one of the requested addresses
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseApproval {
+	pub network: String,
+	pub token: String,
+	pub owner: String,
+	pub spender: String,
+	// set when the spender is labeled
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub entity: Option<String>,
+	pub amount: String,
+}
+
+// @NOTE `dormantDays` is how long the address hasn't sent anything for, and
+// `coinAge` the value-weighted age (in days) of what each asset's balance is
+// made of, assuming the oldest coins get spent first
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseActivity {
+	pub network: String,
+	pub address: String,
+	pub last_sent_at: Option<NaiveDateTime>,
+	pub dormant_days: Option<u64>,
+	pub coin_age: Vec<ResponseCoinAge>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseCoinAge {
+	pub token: Option<String>,
+	pub days: f64,
+}
+
+// first transfer into one of the requested addresses
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseFunder {
+	pub network: String,
+	pub address: String,
+	pub funder: String,
+	// set when the funder is labeled
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub entity: Option<String>,
+	pub tx_hash: String,
+	pub block_height: u64,
+	pub funded_at: Option<NaiveDateTime>,
+}
+
+// link leading to one of the requested addresses, possibly through bridges
+struct TracedLink {
+	link: Link,
+	to_address: String,
 	hops: u64,
+	bridge_entity_ids: Vec<PrimaryId>,
+	// transfers of the whole trail, including the ones before bridges
+	transfer_uuids: Vec<Uuid>,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
-	addresses: Vec<String>,
-	risk: ResponseRisk,
-	assets: Vec<ResponseAsset>,
-	tokens: Vec<ResponseToken>,
-	sources: Vec<ResponseSource>,
-	networks: Vec<SanitizedNetwork>,
-	entities: Vec<SanitizedEntity>,
-	tags: Vec<SanitizedTag>,
+	pub addresses: Vec<String>,
+	pub risk: ResponseRisk,
+	pub exposure: Vec<ResponseExposure>,
+	pub assets: Vec<ResponseAsset>,
+	pub tokens: Vec<ResponseToken>,
+	pub sources: Vec<ResponseSource>,
+	pub approvals: Vec<ResponseApproval>,
+	pub activity: Vec<ResponseActivity>,
+	pub funders: Vec<ResponseFunder>,
+	pub networks: Vec<SanitizedNetwork>,
+	pub entities: Vec<SanitizedEntity>,
+	pub tags: Vec<SanitizedTag>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseResult {
+	q: String,
+	#[serde(flatten)]
+	info: Value,
+}
+
+// when several queries are screened at once
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResponse {
+	results: Vec<ResponseResult>,
 }
 
 pub async fn handler(
 	State(app): State<Arc<App>>,
 	Query(payload): Query<Payload>,
-) -> ServerResult<Json<Response>> {
-	let addresses = {
+) -> ServerResult<Json<Value>> {
+	let queries = parse_queries(&payload.q)?;
+	let cache_key = format!(
+		"v1:{}:{}:{}",
+		queries.join(","),
+		payload.fields.clone().unwrap_or_default(),
+		payload.expand.clone().unwrap_or_default()
+	);
+	let fields = Fields::parse(payload.fields, FIELDS)?;
+
+	let expand_transfers = match payload.expand.as_deref().map(|v| v.trim()) {
+		None | Some("") => false,
+		Some("transfers") => true,
+		Some(v) => {
+			return Err(ServerError::InvalidParam {
+				field: "expand".to_string(),
+				value: v.to_string(),
+			})
+		}
+	};
+
+	get_cached(app.clone(), &cache_key, async {
+		if queries.len() == 1 {
+			return fields.apply(get_info(app, &queries[0], &fields, expand_transfers).await?);
+		}
+
+		let mut results = vec![];
+		for (q, info) in queries
+			.clone()
+			.into_iter()
+			.zip(get_infos(app, queries, &fields, expand_transfers).await?)
+		{
+			results.push(ResponseResult { q, info: fields.apply(info)?.0 });
+		}
+
+		Ok(Json(serde_json::to_value(BatchResponse { results })?))
+	})
+	.await
+}
+
+// @NOTE responses are cached under the request along with a data version,
+// which moves whenever blocks get processed (incl chunks, module resyncs and
+// backfills) or bridges and mixers get traced, so that a cached response never
+// outlives the data it was built from. markers get removed once they're done,
+// so how many there are is part of the version too. labels changing clear the
+// cache altogether
+pub async fn get_cached<F>(app: Arc<App>, key: &str, f: F) -> ServerResult<Json<Value>>
+where
+	F: Future<Output = ServerResult<Json<Value>>>,
+{
+	if !app.info_cache.is_enabled() {
+		return f.await;
+	}
+
+	app.info_cache.sync(app.db()).await?;
+
+	let markers = Config::get_many::<_, Value>(
+		app.db(),
+		vec![
+			ConfigKey::IndexerProcessTail(0),
+			ConfigKey::IndexerProcessChunk(0, 0),
+			ConfigKey::IndexerProcessModule(0, 0),
+			ConfigKey::IndexerProcessBackfill(0, 0),
+			ConfigKey::IndexerBridge(0),
+			ConfigKey::IndexerMixer(0),
+			ConfigKey::NetworksUpdated,
+		],
+	)
+	.await?;
+	let data_version = format!(
+		"{}.{}",
+		markers.values().map(|v| v.updated_at.and_utc().timestamp_micros()).max().unwrap_or(0),
+		markers.len()
+	);
+
+	let key = format!("{key}@{data_version}");
+	if let Some(value) = app.info_cache.get(&key).await {
+		return Ok(value.into());
+	}
+
+	let response = f.await?;
+	app.info_cache.insert(key, response.0.clone()).await;
+
+	Ok(response)
+}
+
+// comma-separated addresses and/or entity ids, without duplicates
+fn parse_queries(q: &str) -> ServerResult<Vec<String>> {
+	let mut ret = vec![];
+	for q in q.split(',').map(|q| q.trim()).filter(|q| !q.is_empty()) {
+		if !ret.iter().any(|r| r == q) {
+			ret.push(q.to_string());
+		}
+	}
+
+	if ret.is_empty() {
+		return Err(ServerError::MissingInputParams);
+	} else if ret.len() > MAX_QUERIES {
+		return Err(ServerError::ExceededLimit { field: "q".to_string(), limit: MAX_QUERIES });
+	}
+
+	Ok(ret)
+}
+
+// shared with later api versions, which only reshape the response
+pub async fn get_info(
+	app: Arc<App>,
+	q: &str,
+	fields: &Fields,
+	expand_transfers: bool,
+) -> ServerResult<Response> {
+	Ok(get_infos(app, vec![q.to_string()], fields, expand_transfers).await?.remove(0))
+}
+
+// @NOTE warehouse and label lookups are done once for the whole batch, and
+// split up by query afterwards. sections left out of `fields` come back empty,
+// and labels (along with the links they're traced through) are only looked up
+// when a section needs them
+pub async fn get_infos(
+	app: Arc<App>,
+	queries: Vec<String>,
+	fields: &Fields,
+	expand_transfers: bool,
+) -> ServerResult<Vec<Response>> {
+	let needs_labels = fields.has_any(&["risk", "exposure", "sources", "entities", "tags"]);
+
+	// addresses behind each query
+	let mut queried_addresses = vec![];
+	for q in queries.iter() {
 		let mut ret = HashSet::new();
 
-		let q = payload.q.trim();
+		let q = q.trim();
 		if q.is_empty() {
 			return Err(ServerError::MissingInputParams);
 		}
@@ -94,73 +382,125 @@ pub async fn handler(
 			ret.insert(q.to_string());
 		}
 
-		ret.into_iter().collect::<Vec<String>>()
-	};
+		queried_addresses.push(ret.into_iter().collect::<Vec<String>>());
+	}
+
+	let all_addresses = queried_addresses
+		.iter()
+		.flatten()
+		.cloned()
+		.collect::<HashSet<String>>()
+		.into_iter()
+		.collect::<Vec<String>>();
 
 	// find links
-	let links = Link::get_all_disinct_by_addresses(&app.warehouse, addresses.clone()).await?;
+	let links = if needs_labels {
+		trace_bridges(
+			app.clone(),
+			Link::get_all_disinct_by_addresses(&app.warehouse, all_addresses.clone()).await?,
+		)
+		.await?
+	} else {
+		vec![]
+	};
+
+	// unlimited allowances, so their spenders' labels get looked up as well
+	let approvals = if fields.has_any(&["risk", "approvals"]) {
+		Approval::get_all_allowances_by_owners(&app.warehouse, all_addresses.clone())
+			.await?
+			.into_iter()
+			.filter(|a| a.is_unlimited())
+			.collect::<Vec<Allowance>>()
+	} else {
+		vec![]
+	};
 
-	async fn get_assets(
+	// first funders, so their labels get looked up as well
+	let funders = match fields.has("funders") {
+		true => Funder::get_all_by_addresses(&app.warehouse, all_addresses.clone()).await?,
+		false => vec![],
+	};
+
+	async fn get_balances(
 		app: Arc<App>,
 		addresses: Vec<String>,
-	) -> Result<(Vec<ResponseAsset>, Vec<ResponseToken>)> {
-		let mut assets_map = HashMap::new();
-		let mut tokens = HashSet::new();
+	) -> Result<(Vec<Balance>, HashMap<(PrimaryId, String), Token>)> {
+		let balances = Balance::get_all_by_addresses(&app.warehouse, addresses)
+			.await?
+			.into_iter()
+			.filter(|b| !b.balance.is_zero())
+			.collect::<Vec<Balance>>();
 
-		let n = app.networks.read().await;
-		let all_balances = Balance::get_all_by_addresses(&app.warehouse, addresses).await?;
-		if !all_balances.is_empty() {
-			let mut all_addresses = HashSet::new();
+		let asset_addresses = balances
+			.iter()
+			.filter(|b| !b.asset_address.is_empty())
+			.map(|b| b.asset_address.clone())
+			.collect::<HashSet<String>>();
+		let tokens = if asset_addresses.is_empty() {
+			HashMap::new()
+		} else {
+			Token::get_all_where(app.db(), TokenColumn::Address.is_in(asset_addresses))
+				.await?
+				.into_iter()
+				.map(|t| ((t.network_id, t.address.clone()), t))
+				.collect()
+		};
 
-			// insert assets
-			for balance_data in all_balances.into_iter() {
-				if balance_data.balance.is_zero() {
-					continue;
-				}
+		Ok((balances, tokens))
+	}
 
-				let network_id = balance_data.network_id as PrimaryId;
-				if let Some(chain) = n.get(&network_id) {
-					let network = chain.get_network();
-
-					let key = (network_id, balance_data.asset_address.clone());
-					assets_map.insert(
-						key,
-						ResponseAsset {
-							network: network.id,
-							token: None,
-							balance: balance_data.balance.to_string(),
-						},
-					);
-
-					// @TODO optimize further; should be a tuple of (network_id,
-					// address)
-					all_addresses.insert(balance_data.asset_address);
-				}
+	async fn get_source_transfers(
+		app: Arc<App>,
+		uuids: Vec<Uuid>,
+	) -> Result<(
+		HashMap<Uuid, Transfer>,
+		HashMap<(PrimaryId, String), Token>,
+		HashMap<(PrimaryId, u16), f64>,
+	)> {
+		let mut transfers = HashMap::new();
+		for chunk in uuids.chunks(TRANSFERS_BATCH_SIZE) {
+			for transfer in Transfer::get_all_by_uuids(&app.warehouse, chunk.to_vec()).await? {
+				transfers.insert(transfer.uuid, transfer);
 			}
+		}
 
-			// fetch tokens
-			if !assets_map.is_empty() {
-				let all_tokens =
-					Token::get_all_where(app.db(), TokenColumn::Address.is_in(all_addresses))
-						.await?;
-				for token in all_tokens {
-					let key = (token.network_id, token.address.clone());
-					if let Some(asset) = assets_map.get_mut(&key) {
-						asset.token = Some(token.id.clone());
-
-						tokens.insert(ResponseToken {
-							id: token.id,
-							name: token.name,
-							symbol: token.symbol,
-							address: token.address,
-							decimals: token.decimals as u16,
-						});
-					}
-				}
+		let asset_addresses = transfers
+			.values()
+			.filter(|t| !t.asset_address.is_empty())
+			.map(|t| t.asset_address.clone())
+			.collect::<HashSet<String>>();
+		let tokens = if asset_addresses.is_empty() {
+			HashMap::new()
+		} else {
+			Token::get_all_where(app.db(), TokenColumn::Address.is_in(asset_addresses))
+				.await?
+				.into_iter()
+				.map(|t| ((t.network_id, t.address.clone()), t))
+				.collect()
+		};
+
+		// usd prices of the days transfers happened on
+		let dates = transfers.values().map(|t| (t.created_at / 86_400) as u16);
+		let native_prices = match (dates.clone().min(), dates.max()) {
+			(Some(from), Some(to)) => {
+				Price::get_all_native_by_dates(
+					&app.warehouse,
+					transfers
+						.values()
+						.map(|t| t.network_id as PrimaryId)
+						.collect::<HashSet<PrimaryId>>()
+						.into_iter()
+						.collect::<Vec<PrimaryId>>()
+						.into(),
+					from,
+					to,
+				)
+				.await?
 			}
-		}
+			_ => HashMap::new(),
+		};
 
-		Ok((assets_map.into_values().collect(), tokens.into_iter().collect()))
+		Ok((transfers, tokens, native_prices))
 	}
 
 	async fn get_entities_data(
@@ -169,136 +509,733 @@ pub async fn handler(
 	) -> Result<(
 		HashMap<(PrimaryId, String), PrimaryId>,
 		HashMap<PrimaryId, Entity>,
-		Vec<Tag>,
-		RiskLevel,
+		HashSet<PrimaryId>,
+		HashMap<String, Tag>,
 	)> {
 		let mut address_map = HashMap::new();
 		let mut entities = HashMap::new();
-		let mut tags = vec![];
-		let mut risk_level = RiskLevel::Low;
+		let mut mixer_entity_ids = HashSet::new();
+		let mut tags = HashMap::<String, Tag>::new();
 
-		let addresses = Address::get_all_by_addresses(app.db(), addresses, Some(false)).await?;
+		// drop cached labels if entities or addresses changed since last time
+		app.entity_cache.sync(app.db()).await?;
 
-		if !addresses.is_empty() {
-			address_map = addresses
+		let mut labels = vec![];
+		let mut missing_addresses = vec![];
+		for address in addresses.into_iter() {
+			match app.entity_cache.get(&address).await {
+				Some(cached_labels) => labels.extend(cached_labels),
+				None => missing_addresses.push(address),
+			}
+		}
+
+		if !missing_addresses.is_empty() {
+			let mut fetched_labels = missing_addresses
 				.iter()
-				.map(|a| ((a.network_id, a.address.clone()), a.entity_id))
-				.collect::<HashMap<(PrimaryId, String), PrimaryId>>();
+				.map(|a| (a.clone(), vec![]))
+				.collect::<HashMap<String, Vec<(Address, Entity, Vec<JoinedTag>)>>>();
 
-			let entity_ids = addresses.into_iter().map(|a| a.entity_id).collect::<Vec<PrimaryId>>();
-			for entity in Entity::get_all_by_entity_ids(app.db(), entity_ids.into(), Some(false))
-				.await?
-				.into_iter()
-			{
-				entities.insert(entity.entity_id, entity);
-			}
+			let addresses =
+				Address::get_all_by_addresses(app.db(), missing_addresses, Some(false)).await?;
 
-			if !entities.is_empty() {
-				let joined_tags = Tag::get_all_by_entity_ids(
-					app.db(),
-					entities.clone().into_keys().collect::<Vec<PrimaryId>>().into(),
-				)
-				.await?;
-
-				let mut map = HashMap::<PrimaryId, Vec<String>>::new();
-				for joined_tag in joined_tags.iter() {
-					if let Some(ids) = map.get_mut(&joined_tag.entity_id) {
-						ids.push(joined_tag.id.clone());
-					} else {
-						map.insert(joined_tag.entity_id, vec![joined_tag.id.clone()]);
-					}
+			if !addresses.is_empty() {
+				let entity_ids = addresses.iter().map(|a| a.entity_id).collect::<Vec<PrimaryId>>();
+				let fetched_entities =
+					Entity::get_all_by_entity_ids(app.db(), entity_ids.into(), Some(false))
+						.await?
+						.into_iter()
+						.map(|e| (e.entity_id, e))
+						.collect::<HashMap<PrimaryId, Entity>>();
 
-					if joined_tag.risk_level > risk_level {
-						risk_level = joined_tag.risk_level;
+				let mut fetched_tags = HashMap::<PrimaryId, Vec<JoinedTag>>::new();
+				if !fetched_entities.is_empty() {
+					for joined_tag in Tag::get_all_by_entity_ids(
+						app.db(),
+						fetched_entities.clone().into_keys().collect::<Vec<PrimaryId>>().into(),
+					)
+					.await?
+					{
+						fetched_tags.entry(joined_tag.entity_id).or_default().push(joined_tag);
 					}
 				}
 
-				for (entity_id, entity) in entities.iter_mut() {
-					entity.tags = map.get(entity_id).cloned().or(Some(vec![]));
+				for address in addresses.into_iter() {
+					if let Some(entity) = fetched_entities.get(&address.entity_id) {
+						let entity_tags =
+							fetched_tags.get(&entity.entity_id).cloned().unwrap_or_default();
+
+						if let Some(set) = fetched_labels.get_mut(&address.address) {
+							set.push((address, entity.clone(), entity_tags));
+						}
+					}
 				}
+			}
 
-				tags = joined_tags.into_iter().map(|jt| jt.into()).collect();
+			// cache misses as well, since most screened addresses are unlabeled
+			for (address, address_labels) in fetched_labels.into_iter() {
+				app.entity_cache.insert(address, address_labels.clone()).await;
+				labels.extend(address_labels);
 			}
 		}
 
-		Ok((address_map, entities, tags, risk_level))
-	}
+		for (address, mut entity, joined_tags) in labels.into_iter() {
+			address_map.insert((address.network_id, address.address), address.entity_id);
+
+			if joined_tags.iter().any(|jt| jt.is_mixer) {
+				mixer_entity_ids.insert(entity.entity_id);
+			}
 
-	pub async fn get_networks(app: Arc<App>, addresses: Vec<String>) -> Result<Vec<Network>> {
-		let mut ret = vec![];
+			entity.tags = Some(joined_tags.iter().map(|jt| jt.id.clone()).collect());
+			entities.insert(entity.entity_id, entity);
 
-		let n = app.networks.read().await;
-		let network_ids =
-			Amount::get_all_network_ids_by_addresses(&app.warehouse, addresses).await?;
-		if !network_ids.is_empty() {
-			for (_, chain) in n.iter().filter(|(network_id, _)| network_ids.contains(network_id)) {
-				ret.push(chain.get_network());
+			for joined_tag in joined_tags.into_iter() {
+				tags.insert(joined_tag.id.clone(), joined_tag.into());
 			}
 		}
 
-		Ok(ret)
+		Ok((address_map, entities, mixer_entity_ids, tags))
 	}
 
-	let (assets_data, networks, entities_data) = tokio::join!(
-		get_assets(app.clone(), addresses.clone()),
-		get_networks(app.clone(), addresses.clone()),
-		get_entities_data(app.clone(), {
+	let (balances_data, daily_amounts, address_networks, entities_data, annotations, categories) = tokio::join!(
+		async {
+			// activity names tokens the same way assets do
+			match fields.has_any(&["assets", "tokens", "activity"]) {
+				true => get_balances(app.clone(), all_addresses.clone()).await,
+				false => Ok((vec![], HashMap::new())),
+			}
+		},
+		async {
+			match fields.has("activity") {
+				true => {
+					Amount::get_all_daily_by_addresses(&app.warehouse, all_addresses.clone()).await
+				}
+				false => Ok(vec![]),
+			}
+		},
+		async {
+			match fields.has("networks") {
+				true => {
+					Amount::get_all_network_ids_by_address(&app.warehouse, all_addresses.clone())
+						.await
+				}
+				false => Ok(vec![]),
+			}
+		},
+		async {
+			if !needs_labels && approvals.is_empty() && funders.is_empty() {
+				return Ok((HashMap::new(), HashMap::new(), HashSet::new(), HashMap::new()));
+			}
+
 			let mut entity_addresses =
-				links.iter().map(|l| l.from_address.clone()).collect::<HashSet<String>>();
+				links.iter().map(|l| l.link.from_address.clone()).collect::<HashSet<String>>();
+			entity_addresses.extend(approvals.iter().map(|a| a.spender.clone()));
+			entity_addresses.extend(funders.iter().map(|f| f.funder.clone()));
 
-			for address in addresses.clone() {
+			for address in all_addresses.clone() {
 				entity_addresses.insert(address);
 			}
 
-			entity_addresses.into_iter().collect::<Vec<_>>()
-		}),
+			get_entities_data(app.clone(), entity_addresses.into_iter().collect::<Vec<_>>()).await
+		},
+		async {
+			// direct mixer interactions or funds coming out of one
+			match fields.has("risk") {
+				true => Annotation::get_all_by_addresses(app.db(), None, all_addresses.clone())
+					.await
+					.map(|annotations| {
+						annotations
+							.into_iter()
+							.filter(|a| {
+								matches!(
+									a.kind,
+									AnnotationKind::MixerDeposit | AnnotationKind::MixerWithdrawal
+								)
+							})
+							.map(|a| a.address)
+							.collect::<HashSet<String>>()
+					}),
+				false => Ok(HashSet::new()),
+			}
+		},
+		async {
+			match fields.has_any(&["risk", "exposure"]) {
+				true => Category::get_all(app.db()).await,
+				false => Ok(vec![]),
+			}
+		},
 	);
 
-	let (assets, tokens) = assets_data?;
-	let (address_map, entities_map, tags, risk_level) = entities_data?;
-
-	// assemble sources
-	let mut sources = vec![];
-	let n = app.networks.read().await;
-	for link in links.into_iter() {
-		let network_id = link.network_id as PrimaryId;
-		if let Some(chain) = n.get(&network_id) {
-			let network = chain.get_network();
-
-			if let Some(&entity_id) = address_map.get(&(network_id, link.from_address.clone())) {
-				if let Some(entity) = entities_map.get(&entity_id) {
-					sources.push(ResponseSource {
-						network: network.id,
-						from: link.from_address,
-						to: link.to_address,
-						entity: entity.id.clone(),
-						hops: link.transfer_uuids.len() as u64,
+	let (balances, tokens) = balances_data?;
+	let daily_amounts = daily_amounts?;
+	let address_networks = address_networks?;
+	let (address_map, entities_map, mixer_entity_ids, tags_map) = entities_data?;
+	let mixer_addresses = annotations?;
+	let categories = categories?;
+
+	let all_networks = app
+		.networks
+		.read()
+		.await
+		.iter()
+		.map(|(network_id, chain)| (*network_id, chain.get_network()))
+		.collect::<HashMap<PrimaryId, Network>>();
+
+	// current usd prices of native assets
+	let native_prices = match fields.has("assets") {
+		true => {
+			Price::get_latest_native_by_network_ids(
+				&app.warehouse,
+				all_networks
+					.values()
+					.filter(|n| n.price_id.is_some())
+					.map(|n| n.network_id)
+					.collect::<Vec<PrimaryId>>()
+					.into(),
+			)
+			.await?
+		}
+		false => HashMap::new(),
+	};
+
+	// @NOTE with `expand=transfers`, the transfers behind each hop are looked
+	// up, but only for links that start at a labeled address (the ones that
+	// end up as sources)
+	let is_expanded = expand_transfers && fields.has("sources");
+	let (source_transfers, source_tokens, source_prices) = if is_expanded {
+		let uuids = links
+			.iter()
+			.filter(|l| {
+				address_map
+					.contains_key(&(l.link.network_id as PrimaryId, l.link.from_address.clone()))
+			})
+			.flat_map(|l| l.transfer_uuids.iter().copied())
+			.collect::<HashSet<Uuid>>();
+
+		get_source_transfers(app.clone(), uuids.into_iter().collect()).await?
+	} else {
+		(HashMap::new(), HashMap::new(), HashMap::new())
+	};
+
+	let mut ret = vec![];
+	for addresses in queried_addresses.into_iter() {
+		let queried = addresses.iter().cloned().collect::<HashSet<String>>();
+
+		let mut response_tokens = HashSet::new();
+
+		// assemble sources
+		let mut sources = vec![];
+		let mut entity_ids = HashSet::new();
+		for traced_link in links.iter().filter(|l| queried.contains(&l.to_address)) {
+			let link = &traced_link.link;
+			let network_id = link.network_id as PrimaryId;
+			if let Some(network) = all_networks.get(&network_id) {
+				if let Some(&entity_id) = address_map.get(&(network_id, link.from_address.clone()))
+				{
+					if let Some(entity) = entities_map.get(&entity_id) {
+						entity_ids.insert(entity_id);
+						entity_ids.extend(
+							traced_link
+								.bridge_entity_ids
+								.iter()
+								.filter(|id| entities_map.contains_key(id)),
+						);
+
+						sources.push(ResponseSource {
+							network: network.id.clone(),
+							from: link.from_address.clone(),
+							to: traced_link.to_address.clone(),
+							entity: entity.id.clone(),
+							hops: traced_link.hops,
+							obfuscated: mixer_entity_ids.contains(&entity_id),
+							bridges: traced_link
+								.bridge_entity_ids
+								.iter()
+								.filter_map(|id| entities_map.get(id).map(|e| e.id.clone()))
+								.collect(),
+							transfers: expand_transfers.then(|| {
+								traced_link
+									.transfer_uuids
+									.iter()
+									.filter_map(|uuid| source_transfers.get(uuid))
+									.filter_map(|t| {
+										get_source_transfer(
+											t,
+											&all_networks,
+											&source_tokens,
+											&source_prices,
+											&mut response_tokens,
+										)
+									})
+									.collect()
+							}),
+						});
+					}
+				}
+			}
+		}
+
+		let mut risk_reasons = HashSet::new();
+		for ((_, address), entity_id) in address_map.iter() {
+			if queried.contains(address) && entities_map.contains_key(entity_id) {
+				entity_ids.insert(*entity_id);
+				risk_reasons.insert(RiskReason::Entity);
+			}
+		}
+		if !sources.is_empty() {
+			risk_reasons.insert(RiskReason::Source);
+		}
+
+		// @NOTE an unlimited approval lets the spender move the owner's tokens at
+		// any point later, so a spender with risky tags counts as if the funds
+		// were exposed to it already
+		let mut response_approvals = vec![];
+		for allowance in approvals.iter().filter(|a| queried.contains(&a.owner)) {
+			let network_id = allowance.network_id as PrimaryId;
+			if let Some(network) = all_networks.get(&network_id) {
+				let entity = address_map
+					.get(&(network_id, allowance.spender.clone()))
+					.and_then(|id| entities_map.get(id));
+
+				if let Some(entity) = entity {
+					let spender_risk_level = entity
+						.tags
+						.iter()
+						.flatten()
+						.filter_map(|id| tags_map.get(id).map(|t| t.risk_level))
+						.max()
+						.unwrap_or(RiskLevel::Low);
+					if spender_risk_level > RiskLevel::Low {
+						entity_ids.insert(entity.entity_id);
+						risk_reasons.insert(RiskReason::Approval);
+					}
+				}
+
+				response_approvals.push(ResponseApproval {
+					network: network.id.clone(),
+					token: allowance.token_address.clone(),
+					owner: allowance.owner.clone(),
+					spender: allowance.spender.clone(),
+					entity: entity.map(|e| e.id.clone()),
+					amount: allowance.amount.to_string(),
+				});
+			}
+		}
+
+		let entities = entity_ids
+			.iter()
+			.filter_map(|id| entities_map.get(id).map(|e| (*id, e.clone())))
+			.collect::<HashMap<PrimaryId, Entity>>();
+		let tags = entities
+			.values()
+			.flat_map(|e| e.tags.iter().flatten())
+			.collect::<HashSet<&String>>()
+			.into_iter()
+			.filter_map(|id| tags_map.get(id).cloned())
+			.collect::<Vec<Tag>>();
+
+		let mut risk_level = tags.iter().map(|t| t.risk_level).max().unwrap_or(RiskLevel::Low);
+		if addresses.iter().any(|a| mixer_addresses.contains(a)) ||
+			sources.iter().any(|s| s.obfuscated)
+		{
+			risk_reasons.insert(RiskReason::Mixer);
+			risk_level = cmp::max(risk_level, RiskLevel::High);
+		}
+
+		let exposure =
+			get_exposure(&categories, &addresses, &address_map, &entities, &tags, &sources);
+
+		// assets
+		let mut assets_map = HashMap::new();
+		for balance_data in balances.iter().filter(|b| queried.contains(&b.address)) {
+			let network_id = balance_data.network_id as PrimaryId;
+			if let Some(network) = all_networks.get(&network_id) {
+				let mut asset = ResponseAsset {
+					network: network.id.clone(),
+					token: None,
+					balance: balance_data.balance.to_string(),
+					// native assets are known upfront, tokens get theirs below
+					balance_formatted: Some(balance_data.balance)
+						.filter(|_| balance_data.asset_address.is_empty())
+						.map(|b| utils::format_amount(b, network.architecture.native_decimals())),
+					value_usd: None,
+					raw_balance: balance_data.balance,
+				};
+
+				// wrapped native tokens are valued like the native asset
+				if prices::is_priced(network, &balance_data.asset_address) {
+					asset.value_usd = native_prices.get(&network_id).and_then(|price| {
+						prices::to_usd(
+							asset.raw_balance,
+							network.architecture.native_decimals(),
+							*price,
+						)
+					});
+				}
+
+				let key = (network_id, balance_data.asset_address.clone());
+				if let Some(token) = tokens.get(&key) {
+					asset.token = Some(token.id.clone());
+
+					// placeholder tokens don't have known decimals yet
+					if !token.is_placeholder {
+						asset.balance_formatted =
+							Some(utils::format_amount(asset.raw_balance, token.decimals as u16));
+					}
+
+					response_tokens.insert(ResponseToken {
+						id: token.id.clone(),
+						name: token.name.clone(),
+						symbol: token.symbol.clone(),
+						address: token.address.clone(),
+						decimals: token.decimals as u16,
 					});
 				}
+
+				assets_map.insert(key, asset);
 			}
 		}
+
+		let mut response_funders = vec![];
+		for funder in funders.iter().filter(|f| queried.contains(&f.address)) {
+			let network_id = funder.network_id as PrimaryId;
+			if let Some(network) = all_networks.get(&network_id) {
+				response_funders.push(ResponseFunder {
+					network: network.id.clone(),
+					address: funder.address.clone(),
+					funder: funder.funder.clone(),
+					entity: address_map
+						.get(&(network_id, funder.funder.clone()))
+						.and_then(|id| entities_map.get(id))
+						.map(|e| e.id.clone()),
+					tx_hash: funder.tx_hash.clone(),
+					block_height: funder.block_height,
+					funded_at: DateTime::from_timestamp(funder.created_at as i64, 0)
+						.map(|d| d.naive_utc()),
+				});
+			}
+		}
+
+		let activity = get_activity(&daily_amounts, &queried, &all_networks, &tokens);
+
+		let networks = address_networks
+			.iter()
+			.filter(|(address, _)| queried.contains(address))
+			.map(|(_, network_id)| *network_id)
+			.collect::<HashSet<PrimaryId>>()
+			.into_iter()
+			.filter_map(|network_id| all_networks.get(&network_id).cloned())
+			.map(|n| n.into())
+			.collect();
+
+		ret.push(Response {
+			risk: ResponseRisk {
+				level: risk_level,
+				reasons: risk_reasons,
+				severity: exposure.iter().map(|e| e.severity).max(),
+			},
+			addresses,
+			exposure,
+			assets: assets_map.into_values().collect(),
+			tokens: response_tokens.into_iter().collect(),
+			sources,
+			approvals: response_approvals,
+			activity,
+			funders: response_funders,
+			networks,
+			entities: entities.into_values().map(|e| e.into()).collect(),
+			tags: tags.into_iter().map(|t| t.into()).collect(),
+		});
 	}
 
-	let mut risk_reasons = HashSet::new();
-	for (_, network_address) in address_map.keys() {
-		if addresses.contains(network_address) {
-			risk_reasons.insert(RiskReason::Entity);
-			break;
+	Ok(ret)
+}
+
+// a transfer behind one of a source's hops, whose token gets added to
+// `response_tokens`
+fn get_source_transfer(
+	transfer: &Transfer,
+	all_networks: &HashMap<PrimaryId, Network>,
+	tokens: &HashMap<(PrimaryId, String), Token>,
+	native_prices: &HashMap<(PrimaryId, u16), f64>,
+	response_tokens: &mut HashSet<ResponseToken>,
+) -> Option<ResponseSourceTransfer> {
+	let network_id = transfer.network_id as PrimaryId;
+	let network = all_networks.get(&network_id)?;
+
+	let token = tokens.get(&(network_id, transfer.asset_address.clone()));
+	if let Some(token) = token {
+		response_tokens.insert(ResponseToken {
+			id: token.id.clone(),
+			name: token.name.clone(),
+			symbol: token.symbol.clone(),
+			address: token.address.clone(),
+			decimals: token.decimals as u16,
+		});
+	}
+
+	let value_usd = Some(transfer)
+		.filter(|t| prices::is_priced(network, &t.asset_address))
+		.and_then(|t| native_prices.get(&(network_id, (t.created_at / 86_400) as u16)))
+		.and_then(|price| {
+			prices::to_usd(transfer.relative_amount, network.architecture.native_decimals(), *price)
+		});
+
+	Some(ResponseSourceTransfer {
+		network: network.id.clone(),
+		tx_hash: transfer.tx_hash.clone(),
+		block_height: transfer.block_height,
+		from: transfer.from_address.clone(),
+		to: transfer.to_address.clone(),
+		token: token.map(|t| t.id.clone()),
+		amount: transfer.relative_amount.to_string(),
+		value_usd,
+		transferred_at: DateTime::from_timestamp(transfer.created_at as i64, 0)
+			.map(|d| d.naive_utc()),
+	})
+}
+
+// dormancy and coin age per network of each requested address
+fn get_activity(
+	daily_amounts: &[DailyAmount],
+	queried: &HashSet<String>,
+	all_networks: &HashMap<PrimaryId, Network>,
+	tokens: &HashMap<(PrimaryId, String), Token>,
+) -> Vec<ResponseActivity> {
+	let now = utils::now().and_utc().timestamp() as u32;
+	let today = (now / 86_400) as u16;
+
+	// rows come sorted by date within each address and asset
+	let mut by_address = HashMap::<(PrimaryId, String), HashMap<String, Vec<DailyAmount>>>::new();
+	for daily_amount in daily_amounts.iter().filter(|d| queried.contains(&d.address)) {
+		by_address
+			.entry((daily_amount.network_id as PrimaryId, daily_amount.address.clone()))
+			.or_default()
+			.entry(daily_amount.asset_address.clone())
+			.or_default()
+			.push(daily_amount.clone());
+	}
+
+	let mut ret = vec![];
+	for ((network_id, address), assets) in by_address.into_iter() {
+		let Some(network) = all_networks.get(&network_id) else {
+			continue;
+		};
+
+		let last_sent_at =
+			assets.values().flatten().map(|d| d.last_sent_at).filter(|t| *t > 0).max();
+
+		// tokens that were never registered are left out, same as with assets
+		let mut coin_age = vec![];
+		for (asset_address, amounts) in assets.iter() {
+			let token = match asset_address.is_empty() {
+				true => None,
+				_ => match tokens.get(&(network_id, asset_address.clone())) {
+					Some(token) => Some(token.id.clone()),
+					None => continue,
+				},
+			};
+
+			if let Some(days) = DailyAmount::get_coin_age(amounts, today) {
+				coin_age.push(ResponseCoinAge { token, days });
+			}
 		}
+		coin_age.sort_by(|a, b| a.token.cmp(&b.token));
+
+		ret.push(ResponseActivity {
+			network: network.id.clone(),
+			address,
+			last_sent_at: last_sent_at
+				.and_then(|t| DateTime::from_timestamp(t as i64, 0))
+				.map(|d| d.naive_utc()),
+			dormant_days: last_sent_at.map(|t| (now.saturating_sub(t) / 86_400) as u64),
+			coin_age,
+		});
 	}
-	if !sources.is_empty() {
-		risk_reasons.insert(RiskReason::Source);
+	ret.sort_by(|a, b| (&a.network, &a.address).cmp(&(&b.network, &b.address)));
+
+	ret
+}
+
+// categories that labeled entities (requested addresses or sources) fall
+// under, highest severity first
+fn get_exposure(
+	categories: &[Category],
+	addresses: &[String],
+	address_map: &HashMap<(PrimaryId, String), PrimaryId>,
+	entities_map: &HashMap<PrimaryId, Entity>,
+	tags: &[Tag],
+	sources: &[ResponseSource],
+) -> Vec<ResponseExposure> {
+	let tag_categories = tags
+		.iter()
+		.filter_map(|t| t.category_id.map(|category_id| (t.id.clone(), category_id)))
+		.collect::<HashMap<String, PrimaryId>>();
+	if tag_categories.is_empty() {
+		return vec![];
 	}
 
-	Ok(Response {
-		addresses,
-		risk: ResponseRisk { level: risk_level, reasons: risk_reasons },
-		assets,
-		tokens,
-		sources,
-		networks: networks?.into_iter().map(|n| n.into()).collect(),
-		entities: entities_map.into_values().map(|e| e.into()).collect(),
-		tags: tags.into_iter().map(|t| t.into()).collect(),
+	let get_category_ids = |entity: &Entity| {
+		entity
+			.tags
+			.iter()
+			.flatten()
+			.filter_map(|tag_id| tag_categories.get(tag_id).copied())
+			.collect::<HashSet<PrimaryId>>()
+	};
+
+	// directly labeled
+	let mut direct_category_ids = HashSet::new();
+	for ((_, address), entity_id) in address_map.iter() {
+		if addresses.contains(address) {
+			if let Some(entity) = entities_map.get(entity_id) {
+				direct_category_ids.extend(get_category_ids(entity));
+			}
+		}
 	}
-	.into())
+
+	// labeled sources
+	let entities_by_id =
+		entities_map.values().map(|e| (e.id.clone(), e)).collect::<HashMap<String, &Entity>>();
+	let mut source_counts = HashMap::<PrimaryId, u64>::new();
+	for source in sources.iter() {
+		if let Some(entity) = entities_by_id.get(&source.entity) {
+			for category_id in get_category_ids(entity) {
+				*source_counts.entry(category_id).or_default() += 1;
+			}
+		}
+	}
+
+	let mut ret = categories
+		.iter()
+		.filter_map(|c| {
+			let direct = direct_category_ids.contains(&c.category_id);
+			let sources = source_counts.get(&c.category_id).copied().unwrap_or(0);
+
+			(direct || sources > 0).then(|| ResponseExposure {
+				category: c.id.clone(),
+				name: c.name.clone(),
+				severity: c.severity,
+				direct,
+				sources,
+			})
+		})
+		.collect::<Vec<ResponseExposure>>();
+	ret.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.category.cmp(&b.category)));
+
+	ret
+}
+
+// @NOTE links that start at a bridge are followed back to the network the
+// funds were deposited on, so that sources over there count as well. the
+// release a link starts with identifies the bridge transfer, and the trail
+// continues with links into the depositor (or the depositor itself, if it's
+// labeled). bridge links are kept too, since bridges are sources as well
+async fn trace_bridges(app: Arc<App>, links: Vec<Link>) -> Result<Vec<TracedLink>> {
+	let bridge_addresses = Tag::get_all_bridge_addresses(app.db())
+		.await?
+		.into_iter()
+		.map(|a| (a.network_id, a.address))
+		.collect::<HashSet<(PrimaryId, String)>>();
+
+	let mut ret = vec![];
+	let mut pending = links
+		.into_iter()
+		.map(|link| TracedLink {
+			to_address: link.to_address.clone(),
+			hops: link.transfer_uuids.len() as u64,
+			bridge_entity_ids: vec![],
+			transfer_uuids: link.transfer_uuids.iter().map(|u| u.0).collect(),
+			link,
+		})
+		.collect::<Vec<TracedLink>>();
+
+	for _ in 0..MAX_BRIDGE_HOPS {
+		let bridged = pending
+			.iter()
+			.filter(|t| {
+				bridge_addresses
+					.contains(&(t.link.network_id as PrimaryId, t.link.from_address.clone()))
+			})
+			.filter_map(|t| t.link.transfer_uuids.first().map(|u| (u.0, t)))
+			.collect::<Vec<_>>();
+		if bridged.is_empty() {
+			break;
+		}
+
+		// transfers out of the bridge that each link starts with
+		let releases = Transfer::get_all_by_uuids(
+			&app.warehouse,
+			bridged.iter().map(|(uuid, _)| *uuid).collect(),
+		)
+		.await?
+		.into_iter()
+		.map(|t| (t.uuid, (t.network_id as PrimaryId, t.tx_hash, t.to_address)))
+		.collect::<HashMap<_, _>>();
+
+		let bridge_transfers =
+			BridgeTransfer::get_all_by_releases(app.db(), releases.values().cloned().collect())
+				.await?
+				.into_iter()
+				.map(|bt| ((bt.to_network_id, bt.to_tx_hash.clone(), bt.to_address.clone()), bt))
+				.collect::<HashMap<_, _>>();
+
+		let origins = bridged
+			.into_iter()
+			.filter_map(|(uuid, t)| {
+				releases.get(&uuid).and_then(|r| bridge_transfers.get(r)).map(|bt| (t, bt))
+			})
+			.collect::<Vec<_>>();
+
+		// links into depositors, on the networks they deposited on
+		let origin_links = Link::get_all_disinct_by_addresses(
+			&app.warehouse,
+			origins.iter().map(|(_, bt)| bt.from_address.clone()).collect(),
+		)
+		.await?;
+
+		let mut next = vec![];
+		for (t, bt) in origins.into_iter() {
+			let mut bridge_entity_ids = t.bridge_entity_ids.clone();
+			bridge_entity_ids.push(bt.entity_id);
+
+			next.push(TracedLink {
+				link: Link::new(
+					bt.from_network_id,
+					bt.from_block_height as u64,
+					&bt.from_address,
+					&bt.from_address,
+					vec![],
+					0,
+				),
+				to_address: t.to_address.clone(),
+				hops: t.hops,
+				bridge_entity_ids: bridge_entity_ids.clone(),
+				transfer_uuids: t.transfer_uuids.clone(),
+			});
+
+			for link in origin_links.iter().filter(|l| {
+				l.network_id as PrimaryId == bt.from_network_id && l.to_address == bt.from_address
+			}) {
+				next.push(TracedLink {
+					link: link.clone(),
+					to_address: t.to_address.clone(),
+					hops: t.hops + link.transfer_uuids.len() as u64,
+					bridge_entity_ids: bridge_entity_ids.clone(),
+					// funds went through the link first, then the bridge
+					transfer_uuids: link
+						.transfer_uuids
+						.iter()
+						.map(|u| u.0)
+						.chain(t.transfer_uuids.iter().copied())
+						.collect(),
+				});
+			}
+		}
+
+		ret.append(&mut pending);
+		pending = next;
+	}
+
+	ret.append(&mut pending);
+	Ok(ret)
 }
@@ -12,6 +12,7 @@ use barreleye_common::{
 #[serde(rename_all = "camelCase")]
 pub struct Payload {
 	id: Option<String>,
+	client_identity: Option<String>,
 }
 
 pub async fn handler(
@@ -26,8 +27,19 @@ pub async fn handler(
 		}
 	}
 
+	// check that client identity is not taken
+	if let Some(client_identity) = payload.client_identity.clone() {
+		if ApiKey::get_by_client_identity(app.db(), &client_identity).await?.is_some() {
+			return Err(ServerError::Duplicate {
+				field: "clientIdentity".to_string(),
+				value: client_identity,
+			});
+		}
+	}
+
 	// create new
-	let api_key_id = ApiKey::create(app.db(), ApiKey::new_model(payload.id)).await?;
+	let api_key_id =
+		ApiKey::create(app.db(), ApiKey::new_model(payload.id, payload.client_identity)).await?;
 
 	// return newly created
 	Ok(ApiKey::get(app.db(), api_key_id).await?.unwrap().format().into())
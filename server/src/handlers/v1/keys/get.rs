@@ -21,8 +21,8 @@ pub async fn handler(
 	State(app): State<Arc<App>>,
 	Path(api_key_id): Path<String>,
 ) -> ServerResult<Json<Response>> {
-	ApiKey::get_by_id(app.db(), &api_key_id)
-		.await?
-		.map(|key| Response { key: key.format() }.into())
-		.ok_or(ServerError::NotFound)
+	let key = ApiKey::get_by_id(app.db(), &api_key_id).await?.ok_or(ServerError::NotFound)?;
+	let key = ApiKey::with_usage(app.db(), vec![key.format()]).await?.remove(0);
+
+	Ok(Response { key }.into())
 }
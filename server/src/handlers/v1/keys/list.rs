@@ -31,6 +31,7 @@ pub async fn handler(
 		.iter()
 		.map(|k| k.format())
 		.collect::<Vec<ApiKey>>();
+	let keys = ApiKey::with_usage(app.db(), keys).await?;
 
 	Ok(Response { keys }.into())
 }
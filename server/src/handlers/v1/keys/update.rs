@@ -17,6 +17,7 @@ use barreleye_common::{
 #[serde(rename_all = "camelCase")]
 pub struct Payload {
 	is_active: Option<bool>,
+	client_identity: Option<String>,
 }
 
 pub async fn handler(
@@ -25,9 +26,26 @@ pub async fn handler(
 	Json(payload): Json<Payload>,
 ) -> ServerResult<StatusCode> {
 	match ApiKey::get_by_id(app.db(), &api_key_id).await? {
-		Some(_) => {
+		Some(api_key) => {
+			// check that client identity is not taken by another key
+			if let Some(client_identity) = payload.client_identity.clone() {
+				if let Some(other) =
+					ApiKey::get_by_client_identity(app.db(), &client_identity).await?
+				{
+					if other.api_key_id != api_key.api_key_id {
+						return Err(ServerError::Duplicate {
+							field: "clientIdentity".to_string(),
+							value: client_identity,
+						});
+					}
+				}
+			}
+
 			let update_data = ApiKeyActiveModel {
 				is_active: optional_set(payload.is_active),
+				client_identity: optional_set(
+					payload.client_identity.map(|v| (!v.is_empty()).then_some(v)),
+				),
 				..Default::default()
 			};
 			if update_data.is_changed() {
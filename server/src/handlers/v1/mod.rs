@@ -3,25 +3,59 @@ use std::sync::Arc;
 
 use barreleye_common::App;
 
+mod abis;
 mod addresses;
+mod alerts;
+mod annotations;
+mod balances;
+mod categories;
 mod entities;
+mod fees;
 mod heartbeat;
-mod info;
+pub mod info;
 mod keys;
 mod networks;
+mod nfts;
+mod paths;
+mod query;
+mod report_schedules;
+pub mod reports;
 mod stats;
+mod system;
 mod tags;
 mod tokens;
+mod transfers;
+mod travel_rule;
+mod webhooks;
 
 pub fn get_routes() -> Router<Arc<App>> {
+	get_shared_routes().nest("/info", info::get_routes())
+}
+
+// routes whose responses are the same across api versions
+pub fn get_shared_routes() -> Router<Arc<App>> {
 	Router::new()
 		.nest("/heartbeat", heartbeat::get_routes())
 		.nest("/stats", stats::get_routes())
+		.nest("/system", system::get_routes())
 		.nest("/keys", keys::get_routes())
 		.nest("/networks", networks::get_routes())
 		.nest("/entities", entities::get_routes())
 		.nest("/addresses", addresses::get_routes())
 		.nest("/tokens", tokens::get_routes())
+		.nest("/abis", abis::get_routes())
+		.nest("/transfers", transfers::get_routes())
+		.nest("/paths", paths::get_routes())
+		.nest("/nfts", nfts::get_routes())
 		.nest("/tags", tags::get_routes())
-		.nest("/info", info::get_routes())
+		.nest("/categories", categories::get_routes())
+		.nest("/balances", balances::get_routes())
+		.nest("/fees", fees::get_routes())
+		.nest("/alerts", alerts::get_routes())
+		.nest("/annotations", annotations::get_routes())
+		.nest("/query", query::get_routes())
+		.nest("/travel-rule", travel_rule::get_routes())
+		.nest("/reports", reports::get_routes())
+		.nest("/report-schedules", report_schedules::get_routes())
+		.nest("/webhooks", webhooks::get_routes())
 }
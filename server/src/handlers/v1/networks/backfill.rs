@@ -0,0 +1,97 @@
+use axum::{
+	extract::{Path, State},
+	http::StatusCode,
+	Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{Config, ConfigKey, Network, SoftDeleteModel},
+	App, BlockHeight,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	from: BlockHeight,
+	to: BlockHeight,
+	modules: Option<Vec<u16>>,
+}
+
+// @NOTE re-processes `from..=to` for the given modules (all of them by
+// default), eg: after adding a module that needs historical data for a window.
+// the indexer picks it up like any other chunk, so only blocks that have been
+// processed before can be backfilled. whatever the modules wrote in the range
+// is removed (and taken out of the balances and stats) before it's written
+// again
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Path(network_id): Path<String>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<StatusCode> {
+	let network =
+		Network::get_existing_by_id(app.db(), &network_id).await?.ok_or(ServerError::NotFound)?;
+	let nid = network.network_id;
+
+	let chain = app
+		.networks
+		.read()
+		.await
+		.get(&nid)
+		.cloned()
+		.ok_or(ServerError::InvalidService { name: network.name })?;
+
+	// check block range
+	let processed_block_height =
+		Config::get::<_, BlockHeight>(app.db(), ConfigKey::IndexerProcessTail(nid))
+			.await?
+			.map(|v| v.value)
+			.unwrap_or(0);
+	if payload.from > payload.to {
+		return Err(ServerError::InvalidParam {
+			field: "from".to_string(),
+			value: payload.from.to_string(),
+		});
+	}
+	if payload.to > processed_block_height {
+		return Err(ServerError::TooEarly {
+			reason: format!("block {} hasn't been processed yet", payload.to),
+		});
+	}
+
+	// check modules
+	let module_ids = chain.get_module_ids().into_iter().map(u16::from).collect::<Vec<_>>();
+	let mids = payload.modules.unwrap_or_else(|| module_ids.clone());
+	let invalid_mids = mids.iter().filter(|mid| !module_ids.contains(mid)).collect::<Vec<_>>();
+	if mids.is_empty() || !invalid_mids.is_empty() {
+		return Err(ServerError::InvalidValues {
+			field: "modules".to_string(),
+			values: invalid_mids.iter().map(|mid| mid.to_string()).collect::<Vec<_>>().join(", "),
+		});
+	}
+
+	// one backfill per end block at a time
+	let config_key = ConfigKey::IndexerProcessBackfill(nid, payload.to);
+	if Config::get::<_, (BlockHeight, BlockHeight, Vec<u16>)>(app.db(), config_key).await?.is_some()
+	{
+		return Err(ServerError::Duplicate {
+			field: "to".to_string(),
+			value: payload.to.to_string(),
+		});
+	}
+
+	// blocks are processed after the range's lower bound
+	Config::set::<_, (BlockHeight, BlockHeight, Vec<u16>)>(
+		app.db(),
+		config_key,
+		(payload.from.saturating_sub(1), payload.to, mids),
+	)
+	.await?;
+
+	// restart indexing, so the new range gets picked up
+	Config::set::<_, u8>(app.db(), ConfigKey::NetworksUpdated, 1).await?;
+
+	Ok(StatusCode::NO_CONTENT)
+}
@@ -4,8 +4,9 @@ use std::sync::Arc;
 
 use crate::{errors::ServerError, ServerResult};
 use barreleye_common::{
-	chain::{Bitcoin, ChainTrait, Evm},
-	models::{is_valid_id, BasicModel, Config, ConfigKey, Network},
+	chain,
+	models::{is_valid_id, set, BasicModel, Config, ConfigKey, Network},
+	prices::{self, is_valid_price_id},
 	App, Architecture, IdPrefix,
 };
 
@@ -17,8 +18,17 @@ pub struct Payload {
 	architecture: Architecture,
 	block_time: u64,
 	rpc_endpoint: String,
+	// fallbacks, in order
+	rpc_endpoints: Option<Vec<String>>,
 	chain_id: Option<u64>,
 	rps: Option<u32>,
+	token_allowlist: Option<Vec<String>>,
+	token_denylist: Option<Vec<String>>,
+	large_transfer_threshold: Option<String>,
+	confirmations: Option<u32>,
+	wrapped_native_tokens: Option<Vec<String>>,
+	dormancy_days: Option<u32>,
+	price_id: Option<String>,
 }
 
 pub async fn handler(
@@ -37,6 +47,50 @@ pub async fn handler(
 		}
 	}
 
+	// check that price id looks like a coingecko id
+	if let Some(price_id) = payload.price_id.clone() {
+		if !is_valid_price_id(&price_id) {
+			return Err(ServerError::InvalidParam {
+				field: "priceId".to_string(),
+				value: price_id,
+			});
+		}
+	}
+
+	// check that support for the architecture was compiled in
+	if !chain::is_supported(payload.architecture) {
+		return Err(ServerError::InvalidParam {
+			field: "architecture".to_string(),
+			value: format!("{:?}", payload.architecture),
+		});
+	}
+
+	// check threshold is a valid usd amount
+	if let Some(threshold) = payload.large_transfer_threshold.clone() {
+		if prices::parse_usd(&threshold).is_none() {
+			return Err(ServerError::InvalidParam {
+				field: "largeTransferThreshold".to_string(),
+				value: threshold,
+			});
+		}
+	}
+
+	// check that token lists only have valid contract addresses
+	for (field, addresses) in
+		[("tokenAllowlist", &payload.token_allowlist), ("tokenDenylist", &payload.token_denylist)]
+	{
+		let invalid_addresses = chain::get_invalid_token_addresses(
+			payload.architecture,
+			addresses.as_deref().unwrap_or_default(),
+		);
+		if !invalid_addresses.is_empty() {
+			return Err(ServerError::InvalidValues {
+				field: field.to_string(),
+				values: invalid_addresses.join(", "),
+			});
+		}
+	}
+
 	// check name for soft-deleted matches
 	if Network::get_by_name(app.db(), &payload.name, Some(true)).await?.is_some() {
 		return Err(ServerError::TooEarly {
@@ -65,30 +119,40 @@ pub async fn handler(
 		});
 	}
 
-	// check rpc connection
-	let n = Network { rpc_endpoint: payload.rpc_endpoint.clone(), ..Default::default() };
-	let mut boxed_chain: Box<dyn ChainTrait> = match payload.architecture {
-		Architecture::Bitcoin => Box::new(Bitcoin::new(n)),
-		Architecture::Evm => Box::new(Evm::new(n)),
+	// check rpc connection (any of the endpoints will do)
+	let rpc_endpoints = payload.rpc_endpoints.and_then(Network::to_token_list);
+	let n = Network {
+		architecture: payload.architecture,
+		rpc_endpoint: payload.rpc_endpoint.clone(),
+		rpc_endpoints: rpc_endpoints.clone(),
+		..Default::default()
 	};
+	let mut boxed_chain = chain::new_boxed_chain(n, &app.plugins)?;
 	if !boxed_chain.connect().await? {
 		return Err(ServerError::InvalidService { name: boxed_chain.get_network().name });
 	}
 
 	// create new
-	let network_id = Network::create(
-		app.db(),
-		Network::new_model(
-			payload.id,
-			&payload.name,
-			payload.architecture,
-			chain_id as i64,
-			payload.block_time as i64,
-			payload.rpc_endpoint,
-			rps as i32,
-		),
-	)
-	.await?;
+	let mut network = Network::new_model(
+		payload.id,
+		&payload.name,
+		payload.architecture,
+		chain_id as i64,
+		payload.block_time as i64,
+		payload.rpc_endpoint,
+		rps as i32,
+	);
+	network.rpc_endpoints = set(rpc_endpoints);
+	network.token_allowlist = set(payload.token_allowlist.and_then(Network::to_token_list));
+	network.token_denylist = set(payload.token_denylist.and_then(Network::to_token_list));
+	network.large_transfer_threshold = set(payload.large_transfer_threshold);
+	network.confirmations = set(payload.confirmations.unwrap_or(0) as i32);
+	network.wrapped_native_tokens = set(payload.wrapped_native_tokens.and_then(|addresses| {
+		Network::to_token_list(addresses.iter().map(|a| boxed_chain.format_address(a)).collect())
+	}));
+	network.dormancy_days = set(payload.dormancy_days.filter(|v| *v > 0).map(|v| v as i32));
+	network.price_id = set(payload.price_id);
+	let network_id = Network::create(app.db(), network).await?;
 
 	// update config
 	Config::set::<_, u8>(app.db(), ConfigKey::NetworksUpdated, 1).await?;
@@ -0,0 +1,79 @@
+use axum::{
+	extract::{Path, State},
+	http::StatusCode,
+	Json,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	chain::genesis,
+	models::{Network, SoftDeleteModel},
+	App, Architecture,
+};
+
+#[derive(Deserialize)]
+pub struct GenesisAccount {
+	balance: String,
+}
+
+// same shape as geth's genesis.json (other fields are ignored)
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	alloc: HashMap<String, GenesisAccount>,
+	timestamp: Option<String>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Path(network_id): Path<String>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<StatusCode> {
+	let network =
+		Network::get_existing_by_id(app.db(), &network_id).await?.ok_or(ServerError::NotFound)?;
+
+	let chain = app
+		.networks
+		.read()
+		.await
+		.get(&network.network_id)
+		.cloned()
+		.ok_or(ServerError::InvalidService { name: network.name })?;
+
+	// parse balances
+	let mut alloc = HashMap::new();
+	for (address, account) in payload.alloc.into_iter() {
+		let Some(balance) = genesis::parse_quantity(&account.balance) else {
+			return Err(ServerError::InvalidParam { field: "alloc".to_string(), value: address });
+		};
+
+		// geth leaves out the `0x` prefix
+		let address = match network.architecture {
+			Architecture::Evm if !address.starts_with("0x") => format!("0x{address}"),
+			_ => address,
+		};
+		alloc.insert(chain.format_address(&address), balance);
+	}
+
+	// genesis timestamp (defaults to the epoch)
+	let mut created_at = 0;
+	if let Some(timestamp) = payload.timestamp {
+		match genesis::parse_quantity(&timestamp) {
+			Some(t) => created_at = t.low_u32(),
+			_ => {
+				return Err(ServerError::InvalidParam {
+					field: "timestamp".to_string(),
+					value: timestamp,
+				})
+			}
+		}
+	}
+
+	// balances are derived from amounts on insert, so this is all it takes
+	let mut warehouse_data = genesis::get_warehouse_data(network.network_id, alloc, created_at);
+	warehouse_data.commit(app.warehouse.clone()).await?;
+
+	Ok(StatusCode::NO_CONTENT)
+}
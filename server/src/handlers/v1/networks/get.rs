@@ -8,7 +8,7 @@ use std::sync::Arc;
 use crate::{errors::ServerError, ServerResult};
 use barreleye_common::{
 	models::{Network, SoftDeleteModel},
-	utils, App,
+	App,
 };
 
 #[derive(Serialize)]
@@ -24,7 +24,7 @@ pub async fn handler(
 	Network::get_existing_by_id(app.db(), &network_id)
 		.await?
 		.map(|mut n| {
-			n.rpc_endpoint = utils::with_masked_auth(&n.rpc_endpoint);
+			n.mask_rpc_endpoints();
 			Response { network: n }.into()
 		})
 		.ok_or(ServerError::NotFound)
@@ -7,7 +7,7 @@ use std::sync::Arc;
 use crate::ServerResult;
 use barreleye_common::{
 	models::{BasicModel, Network, NetworkColumn},
-	utils, App,
+	App,
 };
 
 #[derive(Deserialize)]
@@ -36,7 +36,7 @@ pub async fn handler(
 	.await?
 	.into_iter()
 	.map(|mut n| {
-		n.rpc_endpoint = utils::with_masked_auth(&n.rpc_endpoint);
+		n.mask_rpc_endpoints();
 		n
 	})
 	.collect::<Vec<Network>>();
@@ -6,10 +6,14 @@ use std::sync::Arc;
 
 use barreleye_common::App;
 
+mod backfill;
 mod create;
 mod delete;
+mod genesis;
 mod get;
 mod list;
+mod resync;
+mod stats;
 mod update;
 
 pub fn get_routes() -> Router<Arc<App>> {
@@ -18,5 +22,9 @@ pub fn get_routes() -> Router<Arc<App>> {
 		.route("/", get(list::handler))
 		.route("/:id", get(get::handler))
 		.route("/:id", put(update::handler))
+		.route("/:id/stats", get(stats::handler))
+		.route("/:id/genesis", post(genesis::handler))
+		.route("/:id/backfill", post(backfill::handler))
+		.route("/:id/modules/:module_id/resync", post(resync::handler))
 		.route("/", delete(delete::handler))
 }
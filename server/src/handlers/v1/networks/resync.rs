@@ -0,0 +1,58 @@
+use axum::{
+	extract::{Path, State},
+	http::StatusCode,
+};
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{Config, ConfigKey, DecodedCall, Fee, Network, SoftDeleteModel},
+	App,
+};
+
+// @NOTE recomputes a single module over the network's history, without
+// reprocessing any of the others. the indexer then treats it like a newly
+// added module, and removes its transfers and amounts (and what the views
+// summed up from them) before it starts over
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Path((network_id, module_id)): Path<(String, u16)>,
+) -> ServerResult<StatusCode> {
+	let network =
+		Network::get_existing_by_id(app.db(), &network_id).await?.ok_or(ServerError::NotFound)?;
+	let nid = network.network_id;
+
+	let chain = app
+		.networks
+		.read()
+		.await
+		.get(&nid)
+		.cloned()
+		.ok_or(ServerError::InvalidService { name: network.name })?;
+
+	let module = chain
+		.get_module_ids()
+		.into_iter()
+		.find(|m| u16::from(*m) == module_id)
+		.ok_or(ServerError::NotFound)?;
+
+	// drop what the module has written so far, besides what the views are
+	// summed up from
+	DecodedCall::delete_all_by_module_id(&app.warehouse, nid, module).await?;
+	Fee::delete_all_by_module_id(&app.warehouse, nid, module).await?;
+
+	// reset its markers, so it syncs up from the start
+	Config::delete_many(
+		app.db(),
+		vec![
+			ConfigKey::IndexerProcessModuleDone(nid, module_id),
+			ConfigKey::IndexerProcessModule(nid, module_id),
+		],
+	)
+	.await?;
+
+	// restart indexing, so the module gets picked up
+	Config::set::<_, u8>(app.db(), ConfigKey::NetworksUpdated, 1).await?;
+
+	Ok(StatusCode::NO_CONTENT)
+}
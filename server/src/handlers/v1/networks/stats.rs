@@ -0,0 +1,133 @@
+use axum::{
+	extract::{Path, State},
+	Json,
+};
+use axum_extra::extract::Query;
+use sea_orm::ColumnTrait;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{
+		BasicModel, Config, ConfigKey, Network, NetworkStats, SoftDeleteModel, Token, TokenColumn,
+	},
+	utils, App, BlockHeight,
+};
+
+const DEFAULT_DAYS: u16 = 30;
+const MAX_DAYS: u16 = 365;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	days: Option<u16>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseValue {
+	token: Option<String>,
+	amount: String,
+	amount_formatted: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseDay {
+	date: String,
+	transactions: u64,
+	transfers: u64,
+	active_addresses: u64,
+	value_moved: Vec<ResponseValue>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	network: String,
+	block_height: BlockHeight,
+	indexed_block_height: BlockHeight,
+	days: Vec<ResponseDay>,
+	tokens: Vec<Token>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Path(network_id): Path<String>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	let network =
+		Network::get_existing_by_id(app.db(), &network_id).await?.ok_or(ServerError::NotFound)?;
+	let nid = network.network_id;
+
+	let days = payload.days.unwrap_or(DEFAULT_DAYS);
+	if days == 0 || days > MAX_DAYS {
+		return Err(ServerError::InvalidParam {
+			field: "days".to_string(),
+			value: days.to_string(),
+		});
+	}
+
+	let block_height = Config::get::<_, BlockHeight>(app.db(), ConfigKey::BlockHeight(nid))
+		.await?
+		.map(|v| v.value)
+		.unwrap_or(0);
+	let indexed_block_height =
+		Config::get::<_, BlockHeight>(app.db(), ConfigKey::IndexerProcessTail(nid))
+			.await?
+			.map(|v| v.value)
+			.unwrap_or(0);
+
+	let (stats, values) = tokio::join!(
+		NetworkStats::get_all_by_network_id(&app.warehouse, nid, days),
+		NetworkStats::get_all_values_by_network_id(&app.warehouse, nid, days),
+	);
+	let (stats, values) = (stats?, values?);
+
+	// fetch tokens for decimals
+	let asset_addresses = values
+		.iter()
+		.filter(|v| !v.asset_address.is_empty())
+		.map(|v| v.asset_address.clone())
+		.collect::<Vec<String>>();
+	let tokens = if asset_addresses.is_empty() {
+		vec![]
+	} else {
+		Token::get_all_where(app.db(), TokenColumn::Address.is_in(asset_addresses))
+			.await?
+			.into_iter()
+			.filter(|t| t.network_id == nid)
+			.collect()
+	};
+	let tokens_map = tokens.iter().map(|t| (t.address.clone(), t)).collect::<HashMap<_, _>>();
+
+	let mut values_map = HashMap::<u16, Vec<ResponseValue>>::new();
+	for value in values.into_iter() {
+		let token = tokens_map.get(&value.asset_address);
+		let decimals = match token {
+			_ if value.asset_address.is_empty() => Some(network.architecture.native_decimals()),
+			Some(token) if !token.is_placeholder => Some(token.decimals as u16),
+			_ => None,
+		};
+
+		values_map.entry(value.date).or_default().push(ResponseValue {
+			token: token.map(|t| t.id.clone()),
+			amount: value.value_moved.to_string(),
+			amount_formatted: decimals.map(|d| utils::format_amount(value.value_moved, d)),
+		});
+	}
+
+	let days = stats
+		.into_iter()
+		.map(|s| ResponseDay {
+			date: utils::date_from_days(s.date).to_string(),
+			transactions: s.transactions,
+			transfers: s.transfers,
+			active_addresses: s.active_addresses,
+			value_moved: values_map.remove(&s.date).unwrap_or_default(),
+		})
+		.collect();
+
+	Ok(Response { network: network.id, block_height, indexed_block_height, days, tokens }.into())
+}
@@ -9,9 +9,12 @@ use std::sync::Arc;
 
 use crate::{errors::ServerError, ServerResult};
 use barreleye_common::{
+	chain,
 	models::{
-		optional_set, BasicModel, Config, ConfigKey, Network, NetworkActiveModel, SoftDeleteModel,
+		optional_set, BasicModel, Config, ConfigKey, Network, NetworkActiveModel, Price,
+		SoftDeleteModel,
 	},
+	prices::{self, is_valid_price_id},
 	App, Architecture,
 };
 
@@ -23,7 +26,18 @@ pub struct Payload {
 	chain_id: Option<u64>,
 	block_time: Option<u64>,
 	rpc_endpoint: Option<String>,
+	// fallbacks, in order (an empty list removes them)
+	rpc_endpoints: Option<Vec<String>>,
 	rps: Option<u32>,
+	token_allowlist: Option<Vec<String>>,
+	token_denylist: Option<Vec<String>>,
+	large_transfer_threshold: Option<String>,
+	confirmations: Option<u32>,
+	wrapped_native_tokens: Option<Vec<String>>,
+	// zero turns dormancy alerts off
+	dormancy_days: Option<u32>,
+	// an empty string turns prices off
+	price_id: Option<String>,
 }
 
 pub async fn handler(
@@ -69,26 +83,122 @@ pub async fn handler(
 		}
 	}
 
+	// check threshold is a valid usd amount (empty string removes it)
+	if let Some(threshold) = payload.large_transfer_threshold.clone() {
+		if !threshold.is_empty() && prices::parse_usd(&threshold).is_none() {
+			return Err(ServerError::InvalidParam {
+				field: "largeTransferThreshold".to_string(),
+				value: threshold,
+			});
+		}
+	}
+
+	// check that token lists only have valid contract addresses
+	for (field, addresses) in
+		[("tokenAllowlist", &payload.token_allowlist), ("tokenDenylist", &payload.token_denylist)]
+	{
+		let invalid_addresses = chain::get_invalid_token_addresses(
+			payload.architecture.unwrap_or(network.architecture),
+			addresses.as_deref().unwrap_or_default(),
+		);
+		if !invalid_addresses.is_empty() {
+			return Err(ServerError::InvalidValues {
+				field: field.to_string(),
+				values: invalid_addresses.join(", "),
+			});
+		}
+	}
+
+	// check that price id looks like a coingecko id (empty string removes it)
+	if let Some(price_id) = payload.price_id.clone() {
+		if !price_id.is_empty() && !is_valid_price_id(&price_id) {
+			return Err(ServerError::InvalidParam {
+				field: "priceId".to_string(),
+				value: price_id,
+			});
+		}
+	}
+
+	// prices of a different asset have to be fetched from scratch
+	let is_price_id_changed = payload
+		.price_id
+		.as_ref()
+		.is_some_and(|v| network.price_id.clone().unwrap_or_default() != *v);
+
+	// addresses are matched against indexed ones, so they're formatted the same
+	let mut wrapped_native_tokens = None;
+	if let Some(addresses) = payload.wrapped_native_tokens {
+		let mut formatted_addresses = vec![];
+		for address in addresses.iter() {
+			formatted_addresses.push(app.format_address(address).await?);
+		}
+		wrapped_native_tokens = Some(formatted_addresses);
+	}
+
 	let update_data = NetworkActiveModel {
 		name: optional_set(payload.name.clone()),
 		architecture: optional_set(payload.architecture),
 		chain_id: optional_set(payload.chain_id.map(|v| v as i64)),
 		block_time: optional_set(payload.block_time.map(|v| v as i64)),
 		rpc_endpoint: optional_set(payload.rpc_endpoint.clone()),
+		rpc_endpoints: optional_set(payload.rpc_endpoints.map(Network::to_token_list)),
 		rps: optional_set(payload.rps.map(|v| v as i32)),
+		token_allowlist: optional_set(payload.token_allowlist.map(Network::to_token_list)),
+		token_denylist: optional_set(payload.token_denylist.map(Network::to_token_list)),
+		large_transfer_threshold: optional_set(
+			payload.large_transfer_threshold.map(|v| (!v.is_empty()).then_some(v)),
+		),
+		confirmations: optional_set(payload.confirmations.map(|v| v as i32)),
+		wrapped_native_tokens: optional_set(wrapped_native_tokens.map(Network::to_token_list)),
+		dormancy_days: optional_set(payload.dormancy_days.map(|v| (v > 0).then_some(v as i32))),
+		price_id: optional_set(payload.price_id.map(|v| (!v.is_empty()).then_some(v))),
 		..Default::default()
 	};
 
+	// rpc endpoints and rps only affect the network's own connection
+	let is_rpc_update = [
+		update_data.name.is_not_set(),
+		update_data.architecture.is_not_set(),
+		update_data.chain_id.is_not_set(),
+		update_data.block_time.is_not_set(),
+		update_data.token_allowlist.is_not_set(),
+		update_data.token_denylist.is_not_set(),
+		update_data.large_transfer_threshold.is_not_set(),
+		update_data.confirmations.is_not_set(),
+		update_data.wrapped_native_tokens.is_not_set(),
+		update_data.dormancy_days.is_not_set(),
+		update_data.price_id.is_not_set(),
+	]
+	.into_iter()
+	.all(|is_not_set| is_not_set);
+
 	if update_data.is_changed() {
+		if is_price_id_changed {
+			Price::delete_all_by_network_id(&app.warehouse, network.network_id.into()).await?;
+		}
+
 		// update network
 		Network::update_by_id(app.db(), &network_id, update_data).await?;
 
-		// update config
-		Config::set::<_, u8>(app.db(), ConfigKey::NetworksUpdated, 1).await?;
+		if is_rpc_update {
+			// the indexer reconnects only this network; everything else keeps going
+			let nid = network.network_id;
+			Config::set::<_, u8>(app.db(), ConfigKey::NetworkRpcUpdated(nid), 1).await?;
 
-		// update app's networks
-		let mut networks = app.networks.write().await;
-		*networks = app.get_networks().await?;
+			if !app.settings.is_indexer {
+				if let Some(n) = Network::get(app.db(), nid).await? {
+					let chain = chain::new_boxed_chain(n, &app.plugins)?;
+					app.networks.write().await.insert(nid, Arc::new(chain));
+				}
+			}
+		} else {
+			// update config
+			Config::set::<_, u8>(app.db(), ConfigKey::NetworksUpdated, 1).await?;
+
+			// update app's networks
+			let mut networks = app.networks.write().await;
+			*networks = app.get_networks().await?;
+		}
 	}
 
 	Ok(StatusCode::NO_CONTENT)
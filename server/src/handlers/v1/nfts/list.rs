@@ -0,0 +1,48 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{Network, Nft, SoftDeleteModel},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	network: String,
+	address: String,
+	token_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	nfts: Vec<Nft>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	let network = Network::get_existing_by_id(app.db(), &payload.network).await?.ok_or(
+		ServerError::InvalidParam { field: "network".to_string(), value: payload.network },
+	)?;
+
+	let address = match app.networks.read().await.get(&network.network_id) {
+		Some(chain) => chain.format_address(&payload.address),
+		_ => payload.address,
+	};
+
+	let nfts = Nft::get_all_by_network_id_and_address(
+		app.db(),
+		network.network_id,
+		&address,
+		payload.token_id,
+	)
+	.await?;
+
+	Ok(Response { nfts }.into())
+}
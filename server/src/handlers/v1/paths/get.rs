@@ -0,0 +1,240 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use sea_orm::{ColumnTrait, Condition};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{BasicModel, Network, Price, SoftDeleteModel, Token, TokenColumn, Transfer},
+	prices, App, BlockHeight,
+};
+
+const DEFAULT_MAX_HOPS: u64 = 5;
+const MAX_HOPS: u64 = 10;
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 100;
+
+// addresses expanded and transfers looked at per hop, so passing through busy
+// addresses (eg: exchanges) doesn't make the search explode
+const MAX_FRONTIER: usize = 1_000;
+const MAX_TRANSFERS_PER_HOP: u64 = 10_000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	network: String,
+	from: String,
+	to: String,
+	max_hops: Option<u64>,
+	limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseTransfer {
+	id: String,
+	block_height: BlockHeight,
+	tx_hash: String,
+	from: String,
+	to: String,
+	token: Option<String>,
+	amount: String,
+	// for native assets with a known price on the day of the transfer
+	#[serde(skip_serializing_if = "Option::is_none")]
+	value_usd: Option<f64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponsePath {
+	hops: usize,
+	transfers: Vec<ResponseTransfer>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	network: String,
+	from: String,
+	to: String,
+	paths: Vec<ResponsePath>,
+	tokens: Vec<Token>,
+}
+
+// @NOTE chains of transfers that moved funds from `from` to `to`, found by
+// walking outgoing transfers one hop at a time. each hop has to happen at or
+// after the block funds arrived in, and only the fewest hops needed are
+// returned. busy addresses along the way are only partially walked, so a
+// path that exists can still be missed
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	let network = Network::get_existing_by_id(app.db(), &payload.network).await?.ok_or(
+		ServerError::InvalidParam { field: "network".to_string(), value: payload.network },
+	)?;
+	let nid = network.network_id;
+
+	// addresses go into the query as-is
+	for (field, address) in [("from", &payload.from), ("to", &payload.to)] {
+		if address.is_empty() || !address.chars().all(|c| c.is_ascii_alphanumeric()) {
+			return Err(ServerError::InvalidParam {
+				field: field.to_string(),
+				value: address.clone(),
+			});
+		}
+	}
+	let (from, to) = match app.networks.read().await.get(&nid) {
+		Some(chain) => (chain.format_address(&payload.from), chain.format_address(&payload.to)),
+		_ => (payload.from, payload.to),
+	};
+	if from == to {
+		return Err(ServerError::InvalidParam { field: "to".to_string(), value: to });
+	}
+
+	let max_hops = payload.max_hops.unwrap_or(DEFAULT_MAX_HOPS);
+	if max_hops == 0 || max_hops > MAX_HOPS {
+		return Err(ServerError::InvalidParam {
+			field: "maxHops".to_string(),
+			value: max_hops.to_string(),
+		});
+	}
+	let limit = payload.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+	// walk forward until `to` is reached, keeping the transfers that reached
+	// each address first (address -> block height funds arrived at)
+	let mut reached = HashMap::from([(from.clone(), 0)]);
+	let mut incoming = HashMap::<String, Vec<Transfer>>::new();
+	let mut frontier = reached.clone();
+	for _ in 0..max_hops {
+		if frontier.is_empty() || reached.contains_key(&to) {
+			break;
+		}
+
+		let mut next = HashMap::<String, BlockHeight>::new();
+		for transfer in
+			Transfer::get_all_from_addresses(&app.warehouse, nid, frontier, MAX_TRANSFERS_PER_HOP)
+				.await?
+				.into_iter()
+		{
+			if reached.contains_key(&transfer.to_address) {
+				continue;
+			}
+
+			let block_height = next.entry(transfer.to_address.clone()).or_insert(u64::MAX);
+			*block_height = (*block_height).min(transfer.block_height);
+			incoming.entry(transfer.to_address.clone()).or_default().push(transfer);
+		}
+
+		reached.extend(next.clone());
+
+		// earliest arrivals are walked first
+		let mut next = next.into_iter().collect::<Vec<_>>();
+		next.sort_by_key(|(_, block_height)| *block_height);
+		frontier = next.into_iter().take(MAX_FRONTIER).collect();
+	}
+
+	// walk back from `to`
+	let mut paths = vec![];
+	if reached.contains_key(&to) {
+		collect_paths(&incoming, &from, &to, u64::MAX, &mut vec![], &mut paths, limit);
+	}
+
+	let tokens = if paths.iter().flatten().any(|t| !t.asset_address.is_empty()) {
+		Token::get_all_where(
+			app.db(),
+			Condition::all().add(TokenColumn::NetworkId.eq(nid)).add(
+				TokenColumn::Address.is_in(paths.iter().flatten().map(|t| t.asset_address.clone())),
+			),
+		)
+		.await?
+	} else {
+		vec![]
+	};
+	let token_ids =
+		tokens.iter().map(|t| (t.address.clone(), t.id.clone())).collect::<HashMap<_, _>>();
+
+	// usd prices of the days priced transfers happened on
+	let dates = paths
+		.iter()
+		.flatten()
+		.filter(|t| prices::is_priced(&network, &t.asset_address))
+		.map(|t| (t.created_at / 86_400) as u16)
+		.collect::<Vec<u16>>();
+	let native_prices = match (dates.iter().min(), dates.iter().max()) {
+		(Some(from), Some(to)) => {
+			Price::get_all_native_by_dates(&app.warehouse, nid.into(), *from, *to).await?
+		}
+		_ => HashMap::new(),
+	};
+	let decimals = network.architecture.native_decimals();
+
+	Ok(Response {
+		network: network.id.clone(),
+		from,
+		to,
+		paths: paths
+			.into_iter()
+			.map(|transfers| ResponsePath {
+				hops: transfers.len(),
+				transfers: transfers
+					.into_iter()
+					.map(|t| ResponseTransfer {
+						id: t.uuid.to_string(),
+						block_height: t.block_height,
+						token: token_ids.get(&t.asset_address).cloned(),
+						value_usd: Some(&t)
+							.filter(|t| prices::is_priced(&network, &t.asset_address))
+							.and_then(|t| native_prices.get(&(nid, (t.created_at / 86_400) as u16)))
+							.and_then(|price| prices::to_usd(t.relative_amount, decimals, *price)),
+						tx_hash: t.tx_hash,
+						from: t.from_address,
+						to: t.to_address,
+						amount: t.relative_amount.to_string(),
+					})
+					.collect(),
+			})
+			.collect(),
+		tokens,
+	}
+	.into())
+}
+
+// @NOTE every address was first reached at the block its earliest incoming
+// transfer is in, and was only walked on from there, so every transfer that
+// passes the block height check leads back to `from` (no dead ends to search)
+fn collect_paths(
+	incoming: &HashMap<String, Vec<Transfer>>,
+	from: &str,
+	address: &str,
+	max_block_height: BlockHeight,
+	suffix: &mut Vec<Transfer>,
+	paths: &mut Vec<Vec<Transfer>>,
+	limit: usize,
+) {
+	for transfer in incoming.get(address).into_iter().flatten() {
+		if paths.len() >= limit {
+			return;
+		}
+		if transfer.block_height > max_block_height {
+			continue;
+		}
+
+		suffix.push(transfer.clone());
+		if transfer.from_address == from {
+			paths.push(suffix.iter().rev().cloned().collect());
+		} else {
+			collect_paths(
+				incoming,
+				from,
+				&transfer.from_address,
+				transfer.block_height,
+				suffix,
+				paths,
+				limit,
+			);
+		}
+		suffix.pop();
+	}
+}
@@ -0,0 +1,10 @@
+use axum::{routing::post, Router};
+use std::sync::Arc;
+
+use barreleye_common::App;
+
+mod run;
+
+pub fn get_routes() -> Router<Arc<App>> {
+	Router::new().route("/", post(run::handler))
+}
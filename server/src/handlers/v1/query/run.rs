@@ -0,0 +1,79 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+use tokio::time::{timeout, Duration};
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{
+		AddressActivityTable, AmountTable, ApprovalTable, BalanceSnapshotTable, BalanceTable,
+		BlockTable, DecodedCallTable, FeeTable, FunderTable, LinkTable, NetworkStatsTable,
+		PriceTable, StakingDepositTable, TransferTable, UserOperationTable,
+	},
+	warehouse::{query, Driver},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	query: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	rows: Vec<JsonValue>,
+	is_truncated: bool,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<Json<Response>> {
+	let tables = [
+		TransferTable,
+		AmountTable,
+		LinkTable,
+		BalanceTable,
+		BalanceSnapshotTable,
+		NetworkStatsTable,
+		DecodedCallTable,
+		UserOperationTable,
+		FeeTable,
+		BlockTable,
+		StakingDepositTable,
+		ApprovalTable,
+		FunderTable,
+		AddressActivityTable,
+		PriceTable,
+	];
+
+	let statement = query::sanitize(&payload.query, &tables, &app.settings.warehouse_driver)
+		.map_err(|e| ServerError::BadRequest { reason: e.to_string() })?;
+
+	// fetch one extra row to know whether results were cut off
+	let max_rows = app.settings.query_max_rows;
+	let mut sql = format!("SELECT * FROM ({statement}) AS q LIMIT {}", max_rows + 1);
+	if app.settings.warehouse_driver == Driver::ClickHouse {
+		// `readonly` has to come last, since it stops any setting after it
+		sql = format!(
+			"{sql} SETTINGS max_execution_time = {}, readonly = 1",
+			app.settings.query_timeout
+		);
+	}
+
+	let mut rows = timeout(
+		Duration::from_secs(app.settings.query_timeout),
+		app.warehouse.select_read_only::<JsonValue>(&sql),
+	)
+	.await
+	.map_err(|_| ServerError::BadRequest { reason: "query timed out".to_string() })?
+	.map_err(|e| ServerError::BadRequest { reason: e.to_string() })?;
+
+	let is_truncated = rows.len() > max_rows;
+	rows.truncate(max_rows);
+
+	Ok(Response { rows, is_truncated }.into())
+}
@@ -0,0 +1,70 @@
+use axum::{extract::State, Json};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{BasicModel, ReportSchedule},
+	App,
+};
+
+// shortest time in between runs
+const MIN_INTERVAL_SECONDS: u64 = 60 * 60;
+
+// subjects per schedule, since each one is a full report
+const MAX_WATCHLIST: usize = 100;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	name: String,
+	watchlist: Vec<String>,
+	interval_seconds: u64,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<Json<ReportSchedule>> {
+	if app.storage.get_root().is_none() {
+		return Err(ServerError::BadRequest {
+			reason: "reports are archived to storage, which is not configured".to_string(),
+		});
+	}
+
+	let mut watchlist = payload
+		.watchlist
+		.into_iter()
+		.map(|q| q.trim().to_string())
+		.filter(|q| !q.is_empty())
+		.collect::<Vec<String>>();
+	watchlist.sort_unstable();
+	watchlist.dedup();
+
+	if watchlist.is_empty() {
+		return Err(ServerError::MissingInputParams);
+	}
+	if watchlist.len() > MAX_WATCHLIST {
+		return Err(ServerError::ExceededLimit {
+			field: "watchlist".to_string(),
+			limit: MAX_WATCHLIST,
+		});
+	}
+
+	if payload.interval_seconds < MIN_INTERVAL_SECONDS {
+		return Err(ServerError::InvalidParam {
+			field: "intervalSeconds".to_string(),
+			value: payload.interval_seconds.to_string(),
+		});
+	}
+
+	// create new
+	let report_schedule_id = ReportSchedule::create(
+		app.db(),
+		ReportSchedule::new_model(&payload.name, watchlist, payload.interval_seconds),
+	)
+	.await?;
+
+	// return newly created
+	Ok(ReportSchedule::get(app.db(), report_schedule_id).await?.unwrap().into())
+}
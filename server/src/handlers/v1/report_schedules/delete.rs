@@ -0,0 +1,35 @@
+use axum::{extract::State, http::StatusCode, Json};
+use sea_orm::ColumnTrait;
+use serde::Deserialize;
+use std::{collections::HashSet, sync::Arc};
+
+use crate::ServerResult;
+use barreleye_common::{
+	models::{BasicModel, ReportSchedule, ReportScheduleColumn},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	report_schedules: HashSet<String>,
+}
+
+// archived reports are kept
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<StatusCode> {
+	// exit if no input
+	if payload.report_schedules.is_empty() {
+		return Ok(StatusCode::NO_CONTENT);
+	}
+
+	ReportSchedule::delete_all_where(
+		app.db(),
+		ReportScheduleColumn::Id.is_in(payload.report_schedules),
+	)
+	.await?;
+
+	Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,18 @@
+use axum::{
+	routing::{delete, get, post},
+	Router,
+};
+use std::sync::Arc;
+
+use barreleye_common::App;
+
+mod create;
+mod delete;
+mod list;
+
+pub fn get_routes() -> Router<Arc<App>> {
+	Router::new()
+		.route("/", post(create::handler))
+		.route("/", get(list::handler))
+		.route("/", delete(delete::handler))
+}
@@ -0,0 +1,204 @@
+use axum::{extract::State, Json};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{BTreeSet, HashMap, HashSet},
+	sync::Arc,
+};
+
+use crate::{
+	errors::ServerError,
+	handlers::v1::info::get::{get_info, Response as InfoResponse},
+	utils::Fields,
+	ServerResult,
+};
+use barreleye_common::{
+	models::{Address, Entity, Link, Network, PrimaryId, Transfer},
+	utils, App, IdPrefix, StoredReport,
+};
+
+// latest transfers included in a report's history
+const MAX_TRANSFERS: u64 = 1_000;
+
+// links whose paths make up the graph snapshot
+const MAX_GRAPH_LINKS: usize = 100;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	q: String,
+}
+
+#[derive(Serialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportTransfer {
+	network: String,
+	block_height: u64,
+	tx_hash: String,
+	from: String,
+	to: String,
+	asset: Option<String>,
+	amount: String,
+	created_at: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportNode {
+	address: String,
+	entity: Option<String>,
+}
+
+// transfers that funds took from each source, as nodes and edges
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportGraph {
+	nodes: Vec<ReportNode>,
+	edges: Vec<ReportTransfer>,
+}
+
+// @NOTE a point-in-time record for filings (eg: suspicious activity reports):
+// everything `/v1/info` returns for the subject (exposure, sources, assets and
+// labels), along with its latest transfers and the paths funds took from
+// each source
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+	pub id: String,
+	pub subject: String,
+	created_at: NaiveDateTime,
+	#[serde(flatten)]
+	info: InfoResponse,
+	transfers: Vec<ReportTransfer>,
+	graph: ReportGraph,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<Json<Report>> {
+	if app.storage.get_root().is_none() {
+		return Err(ServerError::BadRequest {
+			reason: "reports are archived to storage, which is not configured".to_string(),
+		});
+	}
+
+	Ok(create_report(app, &payload.q).await?.into())
+}
+
+// compiles a report for `q` (an address or an entity id) and archives it
+pub async fn create_report(app: Arc<App>, q: &str) -> ServerResult<Report> {
+	let subject = q.trim().to_string();
+	let info = get_info(app.clone(), &subject, &Fields::default(), false).await?;
+
+	// transfer history
+	let transfers =
+		Transfer::get_latest_by_addresses(&app.warehouse, info.addresses.clone(), MAX_TRANSFERS)
+			.await?;
+
+	// graph snapshot
+	let links = Link::get_all_disinct_by_addresses(&app.warehouse, info.addresses.clone()).await?;
+	let edges = Transfer::get_all_by_uuids(
+		&app.warehouse,
+		links
+			.into_iter()
+			.take(MAX_GRAPH_LINKS)
+			.flat_map(|l| l.transfer_uuids.into_iter().map(|u| u.0))
+			.collect(),
+	)
+	.await?;
+
+	// network and entity ids to render with
+	let network_ids = transfers
+		.iter()
+		.chain(edges.iter())
+		.map(|t| t.network_id as PrimaryId)
+		.collect::<HashSet<PrimaryId>>();
+	let networks = if network_ids.is_empty() {
+		HashMap::new()
+	} else {
+		Network::get_all_by_network_ids(
+			app.db(),
+			network_ids.into_iter().collect::<Vec<PrimaryId>>().into(),
+			None,
+		)
+		.await?
+		.into_iter()
+		.map(|n| (n.network_id, n.id))
+		.collect::<HashMap<PrimaryId, String>>()
+	};
+	let to_report_transfer = |t: Transfer| ReportTransfer {
+		network: networks.get(&(t.network_id as PrimaryId)).cloned().unwrap_or_default(),
+		block_height: t.block_height,
+		tx_hash: t.tx_hash,
+		from: t.from_address,
+		to: t.to_address,
+		asset: Some(t.asset_address).filter(|a| !a.is_empty()),
+		amount: t.relative_amount.to_string(),
+		created_at: t.created_at,
+	};
+
+	let mut edges = edges
+		.into_iter()
+		.map(to_report_transfer)
+		.collect::<HashSet<ReportTransfer>>()
+		.into_iter()
+		.collect::<Vec<ReportTransfer>>();
+	edges.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.tx_hash.cmp(&b.tx_hash)));
+
+	let node_addresses = edges
+		.iter()
+		.flat_map(|e| [e.from.clone(), e.to.clone()])
+		.filter(|a| !a.is_empty())
+		.collect::<BTreeSet<String>>();
+	let node_entities = if node_addresses.is_empty() {
+		HashMap::new()
+	} else {
+		let addresses = Address::get_all_by_addresses(
+			app.db(),
+			node_addresses.iter().cloned().collect(),
+			Some(false),
+		)
+		.await?;
+
+		let entities = Entity::get_all_by_entity_ids(
+			app.db(),
+			addresses.iter().map(|a| a.entity_id).collect::<Vec<PrimaryId>>().into(),
+			Some(false),
+		)
+		.await?
+		.into_iter()
+		.map(|e| (e.entity_id, e.id))
+		.collect::<HashMap<PrimaryId, String>>();
+
+		addresses
+			.into_iter()
+			.filter_map(|a| Some((a.address, entities.get(&a.entity_id)?.clone())))
+			.collect::<HashMap<String, String>>()
+	};
+
+	let report = Report {
+		id: utils::new_unique_id(IdPrefix::Report),
+		subject,
+		created_at: utils::now(),
+		info,
+		transfers: transfers.into_iter().map(to_report_transfer).collect(),
+		graph: ReportGraph {
+			nodes: node_addresses
+				.into_iter()
+				.map(|address| ReportNode { entity: node_entities.get(&address).cloned(), address })
+				.collect(),
+			edges,
+		},
+	};
+
+	// archive
+	app.storage.put_report(&StoredReport {
+		id: report.id.clone(),
+		subject: report.subject.clone(),
+		created_at: report.created_at.and_utc().timestamp() as u64,
+		report: serde_json::to_string(&report)?,
+	})?;
+
+	Ok(report)
+}
@@ -0,0 +1,24 @@
+use axum::{
+	extract::{Path, State},
+	Json,
+};
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{models::is_valid_id, App, IdPrefix};
+
+// reports are returned as they were archived
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Path(report_id): Path<String>,
+) -> ServerResult<Json<JsonValue>> {
+	if !is_valid_id(&report_id, IdPrefix::Report) {
+		return Err(ServerError::NotFound);
+	}
+
+	match app.storage.get_report(&report_id)? {
+		Some(report) => Ok(serde_json::from_str::<JsonValue>(&report.report)?.into()),
+		None => Err(ServerError::NotFound),
+	}
+}
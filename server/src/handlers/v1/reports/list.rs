@@ -0,0 +1,29 @@
+use axum::{extract::State, Json};
+use chrono::{DateTime, NaiveDateTime};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::ServerResult;
+use barreleye_common::App;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseReport {
+	id: String,
+	subject: String,
+	created_at: Option<NaiveDateTime>,
+}
+
+pub async fn handler(State(app): State<Arc<App>>) -> ServerResult<Json<Vec<ResponseReport>>> {
+	Ok(app
+		.storage
+		.list_reports()?
+		.into_iter()
+		.map(|r| ResponseReport {
+			id: r.id,
+			subject: r.subject,
+			created_at: DateTime::from_timestamp(r.created_at as i64, 0).map(|d| d.naive_utc()),
+		})
+		.collect::<Vec<ResponseReport>>()
+		.into())
+}
@@ -0,0 +1,18 @@
+use axum::{
+	routing::{get, post},
+	Router,
+};
+use std::sync::Arc;
+
+use barreleye_common::App;
+
+pub mod create;
+mod get;
+mod list;
+
+pub fn get_routes() -> Router<Arc<App>> {
+	Router::new()
+		.route("/", post(create::handler))
+		.route("/", get(list::handler))
+		.route("/:id", get(get::handler))
+}
@@ -5,7 +5,7 @@ use std::sync::Arc;
 use crate::ServerResult;
 use barreleye_common::{
 	models::{BasicModel, Config, ConfigKey, Network},
-	App,
+	App, DbPoolStats,
 };
 
 #[derive(Serialize)]
@@ -15,12 +15,16 @@ pub struct ResponseNetwork {
 	block_height: u64,
 	synced: f64,
 	processed: f64,
+	is_connected: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	connection_error: Option<String>,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
 	networks: Vec<ResponseNetwork>,
+	database: DbPoolStats,
 }
 
 pub async fn handler(State(app): State<Arc<App>>) -> ServerResult<Json<Response>> {
@@ -44,13 +48,23 @@ pub async fn handler(State(app): State<Arc<App>>) -> ServerResult<Json<Response>
 			.map(|v| v.value)
 			.unwrap_or(0.0);
 
+		// set by the indexer while it can't reach the network's rpc endpoint
+		let connection_error =
+			Config::get::<_, String>(app.db(), ConfigKey::NetworkDisconnected(nid))
+				.await?
+				.map(|v| v.value);
+
 		networks.push(ResponseNetwork {
 			name: network.name,
 			block_height,
 			synced: (synced * 1000000.0).round() / 1000000.0,
 			processed: (processed * 1000000.0).round() / 1000000.0,
+			is_connected: connection_error.is_none(),
+			connection_error,
 		});
 	}
 
-	Ok(Response { networks }.into())
+	let database = app.db_pool_stats().await?;
+
+	Ok(Response { networks, database }.into())
 }
@@ -4,7 +4,8 @@ use std::sync::Arc;
 use barreleye_common::App;
 
 mod get;
+mod timeseries;
 
 pub fn get_routes() -> Router<Arc<App>> {
-	Router::new().route("/", get(get::handler))
+	Router::new().route("/", get(get::handler)).route("/timeseries", get(timeseries::handler))
 }
@@ -0,0 +1,112 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{BasicModel, Network, NetworkStats, SoftDeleteModel, StatsInterval, Token},
+	utils, App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	network: String,
+	asset: Option<String>,
+	interval: Option<StatsInterval>,
+	from: Option<String>,
+	to: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponsePeriod {
+	date: String,
+	transactions: u64,
+	transfers: u64,
+	active_addresses: u64,
+	volume: String,
+	volume_formatted: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	network: String,
+	token: Option<Token>,
+	interval: StatsInterval,
+	periods: Vec<ResponsePeriod>,
+}
+
+// @NOTE transfer counts and volume of one asset over time, for charts. comes
+// from the daily rollups, so it's cheap even for long ranges; `asset` is a
+// token id and leaving it out means the network's native asset
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	let network = Network::get_existing_by_id(app.db(), &payload.network).await?.ok_or(
+		ServerError::InvalidParam { field: "network".to_string(), value: payload.network },
+	)?;
+
+	let token = match payload.asset {
+		Some(token_id) => match Token::get_by_id(app.db(), &token_id).await? {
+			Some(token) if token.network_id == network.network_id => Some(token),
+			_ => {
+				return Err(ServerError::InvalidParam {
+					field: "asset".to_string(),
+					value: token_id,
+				})
+			}
+		},
+		None => None,
+	};
+
+	// check date range
+	let parse_date = |field: &str, date: Option<String>| match date {
+		Some(date) => utils::parse_date(&date)
+			.map(Some)
+			.ok_or(ServerError::InvalidParam { field: field.to_string(), value: date }),
+		None => Ok(None),
+	};
+	let from = parse_date("from", payload.from)?;
+	let to = parse_date("to", payload.to)?;
+	if let (Some(from), Some(to)) = (from, to) {
+		if from > to {
+			return Err(ServerError::InvalidParam {
+				field: "from".to_string(),
+				value: from.to_string(),
+			});
+		}
+	}
+
+	let decimals = match &token {
+		Some(token) if token.is_placeholder => None,
+		Some(token) => Some(token.decimals as u16),
+		None => Some(network.architecture.native_decimals()),
+	};
+
+	let interval = payload.interval.unwrap_or_default();
+	let periods = NetworkStats::get_periods_by_asset_address(
+		&app.warehouse,
+		network.network_id,
+		&token.as_ref().map(|t| t.address.clone()).unwrap_or_default(),
+		interval,
+		from,
+		to,
+	)
+	.await?
+	.into_iter()
+	.map(|p| ResponsePeriod {
+		date: utils::date_from_days(p.period).to_string(),
+		transactions: p.transactions,
+		transfers: p.transfers,
+		active_addresses: p.active_addresses,
+		volume: p.value_moved.to_string(),
+		volume_formatted: decimals.map(|d| utils::format_amount(p.value_moved, d)),
+	})
+	.collect();
+
+	Ok(Response { network: network.id, token, interval, periods }.into())
+}
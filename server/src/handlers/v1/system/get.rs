@@ -0,0 +1,82 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::ServerResult;
+use barreleye_common::{
+	models::{Config, ConfigKey, Network, SoftDeleteModel},
+	utils, App, BlockHeight,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseNetwork {
+	id: String,
+	name: String,
+	is_connected: bool,
+	chain_head: BlockHeight,
+	synced_height: BlockHeight,
+	processed_height: BlockHeight,
+	lag: BlockHeight,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	version: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	commit: Option<String>,
+	uptime: i64,
+	instance: Uuid,
+	is_primary: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	primary: Option<Uuid>,
+	networks: Vec<ResponseNetwork>,
+}
+
+// @NOTE everything monitoring needs without access to the database. `commit`
+// is whatever `BARRELEYE_COMMIT` was set to at build time, `uptime` is in
+// seconds and heights are as last recorded by the indexer (`lag` is how far
+// processing is behind the chain head)
+pub async fn handler(State(app): State<Arc<App>>) -> ServerResult<Json<Response>> {
+	let mut networks = vec![];
+	for network in Network::get_all_existing(app.db(), Some(false)).await?.into_iter() {
+		let nid = network.network_id;
+
+		let chain_head = get_height(&app, ConfigKey::BlockHeight(nid)).await?;
+		let synced_height = get_height(&app, ConfigKey::IndexerSyncTail(nid)).await?;
+		let processed_height = get_height(&app, ConfigKey::IndexerProcessTail(nid)).await?;
+
+		let is_connected = Config::get::<_, String>(app.db(), ConfigKey::NetworkDisconnected(nid))
+			.await?
+			.is_none();
+
+		networks.push(ResponseNetwork {
+			id: network.id,
+			name: network.name,
+			is_connected,
+			chain_head,
+			synced_height,
+			processed_height,
+			lag: chain_head.saturating_sub(processed_height),
+		});
+	}
+
+	let primary = Config::get::<_, Uuid>(app.db(), ConfigKey::Primary).await?.map(|v| v.value);
+
+	Ok(Response {
+		version: env!("CARGO_PKG_VERSION").to_string(),
+		commit: option_env!("BARRELEYE_COMMIT").map(|c| c.to_string()),
+		uptime: (utils::now() - app.started_at).num_seconds(),
+		instance: app.uuid,
+		is_primary: app.is_primary(),
+		primary,
+		networks,
+	}
+	.into())
+}
+
+async fn get_height(app: &App, key: ConfigKey) -> ServerResult<BlockHeight> {
+	Ok(Config::get::<_, BlockHeight>(app.db(), key).await?.map(|v| v.value).unwrap_or(0))
+}
@@ -4,7 +4,7 @@ use std::sync::Arc;
 
 use crate::{errors::ServerError, ServerResult};
 use barreleye_common::{
-	models::{is_valid_id, BasicModel, Tag},
+	models::{is_valid_id, set, BasicModel, Category, Tag},
 	App, IdPrefix, RiskLevel,
 };
 
@@ -14,6 +14,10 @@ pub struct Payload {
 	id: Option<String>,
 	name: String,
 	risk_level: RiskLevel,
+	is_mixer: Option<bool>,
+	is_exchange: Option<bool>,
+	is_bridge: Option<bool>,
+	category: Option<String>,
 }
 
 pub async fn handler(
@@ -32,11 +36,30 @@ pub async fn handler(
 		return Err(ServerError::Duplicate { field: "name".to_string(), value: payload.name });
 	}
 
+	// check that category exists
+	let category_id = match payload.category.clone() {
+		Some(category) => match Category::get_by_id(app.db(), &category).await? {
+			Some(c) => Some(c.category_id),
+			None => {
+				return Err(ServerError::InvalidParam {
+					field: "category".to_string(),
+					value: category,
+				})
+			}
+		},
+		None => None,
+	};
+
 	// create new
-	let tag_id =
-		Tag::create(app.db(), Tag::new_model(payload.id, &payload.name, payload.risk_level))
-			.await?;
+	let mut tag = Tag::new_model(payload.id, &payload.name, payload.risk_level);
+	tag.is_mixer = set(payload.is_mixer.unwrap_or(false));
+	tag.is_exchange = set(payload.is_exchange.unwrap_or(false));
+	tag.is_bridge = set(payload.is_bridge.unwrap_or(false));
+	tag.category_id = set(category_id);
+	let tag_id = Tag::create(app.db(), tag).await?;
 
 	// return newly created
-	Ok(Tag::get(app.db(), tag_id).await?.unwrap().into())
+	let mut tag = Tag::get(app.db(), tag_id).await?.unwrap();
+	tag.category = payload.category;
+	Ok(tag.into())
 }
@@ -5,7 +5,7 @@ use std::{collections::HashSet, sync::Arc};
 
 use crate::ServerResult;
 use barreleye_common::{
-	models::{BasicModel, PrimaryId, Tag, TagColumn},
+	models::{BasicModel, Config, ConfigKey, PrimaryId, Tag, TagColumn},
 	App,
 };
 
@@ -39,5 +39,8 @@ pub async fn handler(
 	)
 	.await?;
 
+	// invalidate cached labels
+	Config::set::<_, u8>(app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+
 	Ok(StatusCode::NO_CONTENT)
 }
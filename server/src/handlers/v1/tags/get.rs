@@ -5,7 +5,11 @@ use axum::{
 use serde::Serialize;
 use std::sync::Arc;
 
-use crate::{errors::ServerError, handlers::v1::tags::get_data_by_tag_ids, ServerResult};
+use crate::{
+	errors::ServerError,
+	handlers::v1::tags::{get_data_by_tag_ids, set_categories},
+	ServerResult,
+};
 use barreleye_common::{
 	models::{Address, BasicModel, Entity, Network, Tag},
 	App,
@@ -25,6 +29,8 @@ pub async fn handler(
 	Path(tag_id): Path<String>,
 ) -> ServerResult<Json<Response>> {
 	if let Some(mut tag) = Tag::get_by_id(app.db(), &tag_id).await? {
+		set_categories(app.clone(), std::slice::from_mut(&mut tag)).await?;
+
 		let (tags_map, entities, addresses, networks) =
 			get_data_by_tag_ids(app.clone(), tag.tag_id.into()).await?;
 
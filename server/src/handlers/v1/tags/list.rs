@@ -1,9 +1,14 @@
 use axum::{extract::State, Json};
 use axum_extra::extract::Query;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::sync::Arc;
 
-use crate::{handlers::v1::tags::get_data_by_tag_ids, ServerResult};
+use crate::{
+	handlers::v1::tags::{get_data_by_tag_ids, set_categories},
+	utils::Fields,
+	ServerResult,
+};
 use barreleye_common::{
 	models::{Address, BasicModel, Entity, Network, Tag},
 	App,
@@ -14,6 +19,7 @@ use barreleye_common::{
 pub struct Payload {
 	offset: Option<u64>,
 	limit: Option<u64>,
+	fields: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -28,8 +34,11 @@ pub struct Response {
 pub async fn handler(
 	State(app): State<Arc<App>>,
 	Query(payload): Query<Payload>,
-) -> ServerResult<Json<Response>> {
+) -> ServerResult<Json<Value>> {
+	let fields = Fields::parse(payload.fields, &["tags", "entities", "addresses", "networks"])?;
+
 	let mut tags = Tag::get_all_paginated(app.db(), payload.offset, payload.limit).await?;
+	set_categories(app.clone(), &mut tags).await?;
 
 	let (tags_map, entities, addresses, networks) =
 		get_data_by_tag_ids(app.clone(), tags.clone().into()).await?;
@@ -38,5 +47,5 @@ pub async fn handler(
 		tag.entities = tags_map.get(&tag.tag_id).cloned().or(Some(vec![]));
 	}
 
-	Ok(Response { tags, entities, addresses, networks }.into())
+	fields.apply(Response { tags, entities, addresses, networks })
 }
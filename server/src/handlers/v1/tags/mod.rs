@@ -3,11 +3,14 @@ use axum::{
 	Router,
 };
 use eyre::Result;
+use sea_orm::ColumnTrait;
 use std::{collections::HashMap, sync::Arc};
 
 use barreleye_common::{
-	models::{Address, Entity, Network, PrimaryId, PrimaryIds},
-	utils, App,
+	models::{
+		Address, BasicModel, Category, CategoryColumn, Entity, Network, PrimaryId, PrimaryIds, Tag,
+	},
+	App,
 };
 
 mod create;
@@ -25,6 +28,27 @@ pub fn get_routes() -> Router<Arc<App>> {
 		.route("/", delete(delete::handler))
 }
 
+// sets public category ids on tags that are mapped to one
+pub async fn set_categories(app: Arc<App>, tags: &mut [Tag]) -> Result<()> {
+	let category_ids = tags.iter().filter_map(|t| t.category_id).collect::<Vec<PrimaryId>>();
+	if category_ids.is_empty() {
+		return Ok(());
+	}
+
+	let categories =
+		Category::get_all_where(app.db(), CategoryColumn::CategoryId.is_in(category_ids))
+			.await?
+			.into_iter()
+			.map(|c| (c.category_id, c.id))
+			.collect::<HashMap<PrimaryId, String>>();
+
+	for tag in tags.iter_mut() {
+		tag.category = tag.category_id.and_then(|id| categories.get(&id).cloned());
+	}
+
+	Ok(())
+}
+
 pub async fn get_data_by_tag_ids(
 	app: Arc<App>,
 	tag_ids: PrimaryIds,
@@ -40,7 +64,7 @@ pub async fn get_data_by_tag_ids(
 		.await?
 		.into_iter()
 		.map(|mut n| {
-			n.rpc_endpoint = utils::with_masked_auth(&n.rpc_endpoint);
+			n.mask_rpc_endpoints();
 			n
 		})
 		.collect::<Vec<Network>>();
@@ -5,11 +5,15 @@ use axum::{
 };
 use sea_orm::ActiveModelTrait;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
+use tracing::info;
 
 use crate::{errors::ServerError, ServerResult};
 use barreleye_common::{
-	models::{optional_set, BasicModel, Tag, TagActiveModel},
+	models::{
+		optional_set, Address, BasicModel, Category, Config, ConfigKey, Entity, EntityTag,
+		ReportSchedule, Tag, TagActiveModel,
+	},
 	App, RiskLevel,
 };
 
@@ -18,6 +22,10 @@ use barreleye_common::{
 pub struct Payload {
 	name: Option<String>,
 	risk_level: Option<RiskLevel>,
+	is_mixer: Option<bool>,
+	is_exchange: Option<bool>,
+	is_bridge: Option<bool>,
+	category: Option<String>,
 }
 
 pub async fn handler(
@@ -35,16 +43,73 @@ pub async fn handler(
 			}
 		}
 
+		// check that category exists (an empty one unsets it)
+		let category_id = match payload.category.clone() {
+			Some(category) if category.is_empty() => Some(None),
+			Some(category) => match Category::get_by_id(app.db(), &category).await? {
+				Some(c) => Some(Some(c.category_id)),
+				None => {
+					return Err(ServerError::InvalidParam {
+						field: "category".to_string(),
+						value: category,
+					})
+				}
+			},
+			None => None,
+		};
+
 		// update
 		let update_data = TagActiveModel {
 			name: optional_set(payload.name),
 			risk_level: optional_set(payload.risk_level),
+			is_mixer: optional_set(payload.is_mixer),
+			is_exchange: optional_set(payload.is_exchange),
+			is_bridge: optional_set(payload.is_bridge),
+			category_id: optional_set(category_id),
 			..Default::default()
 		};
 		if update_data.is_changed() {
 			Tag::update_by_id(app.db(), &tag_id, update_data).await?;
 		}
 
+		// invalidate cached labels
+		Config::set::<_, u8>(app.db(), ConfigKey::EntitiesUpdated, 1).await?;
+
+		// @NOTE screening results of the tagged entities depend on the risk level,
+		// so besides cached ones going stale above, scheduled reports watching
+		// those entities (or their addresses) are compiled again right away
+		// instead of on their next regular run
+		if payload.risk_level.is_some_and(|risk_level| risk_level != tag.risk_level) {
+			let entity_ids = EntityTag::get_all_by_tag_ids(app.db(), vec![tag.tag_id].into())
+				.await?
+				.into_iter()
+				.map(|et| et.entity_id)
+				.collect::<Vec<_>>();
+
+			if !entity_ids.is_empty() {
+				let mut subjects = HashSet::new();
+				for entity in
+					Entity::get_all_by_entity_ids(app.db(), entity_ids.clone().into(), Some(false))
+						.await?
+						.into_iter()
+				{
+					subjects.insert(entity.id);
+				}
+				for address in
+					Address::get_all_by_entity_ids(app.db(), entity_ids.into(), Some(false))
+						.await?
+						.into_iter()
+				{
+					subjects.insert(address.address);
+				}
+
+				let rescheduled = ReportSchedule::run_now_by_subjects(app.db(), subjects).await?;
+				if rescheduled > 0 {
+					info!(tag = %tag.id, rescheduled, "Risk level changed");
+				}
+			}
+		}
+
 		Ok(StatusCode::NO_CONTENT)
 	} else {
 		Err(ServerError::NotFound)
@@ -1,12 +1,13 @@
 use axum::{extract::State, Json};
 use axum_extra::extract::Query;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::sync::Arc;
 
-use crate::ServerResult;
+use crate::{utils::Fields, ServerResult};
 use barreleye_common::{
 	models::{BasicModel, Network, PrimaryId, Token},
-	utils, App,
+	App,
 };
 
 #[derive(Deserialize)]
@@ -14,6 +15,7 @@ use barreleye_common::{
 pub struct Payload {
 	offset: Option<u64>,
 	limit: Option<u64>,
+	fields: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -26,18 +28,24 @@ pub struct Response {
 pub async fn handler(
 	State(app): State<Arc<App>>,
 	Query(payload): Query<Payload>,
-) -> ServerResult<Json<Response>> {
+) -> ServerResult<Json<Value>> {
+	let fields = Fields::parse(payload.fields, &["tokens", "networks"])?;
+
 	let tokens = Token::get_all_paginated(app.db(), payload.offset, payload.limit).await?;
 
-	let network_ids = tokens.iter().map(|t| t.network_id).collect::<Vec<PrimaryId>>();
-	let networks = Network::get_all_by_network_ids(app.db(), network_ids.into(), Some(false))
-		.await?
-		.into_iter()
-		.map(|mut n| {
-			n.rpc_endpoint = utils::with_masked_auth(&n.rpc_endpoint);
-			n
-		})
-		.collect::<Vec<Network>>();
+	let networks = if fields.has("networks") {
+		let network_ids = tokens.iter().map(|t| t.network_id).collect::<Vec<PrimaryId>>();
+		Network::get_all_by_network_ids(app.db(), network_ids.into(), Some(false))
+			.await?
+			.into_iter()
+			.map(|mut n| {
+				n.mask_rpc_endpoints();
+				n
+			})
+			.collect::<Vec<Network>>()
+	} else {
+		vec![]
+	};
 
-	Ok(Response { tokens, networks }.into())
+	fields.apply(Response { tokens, networks })
 }
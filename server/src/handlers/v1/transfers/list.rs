@@ -0,0 +1,159 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use sea_orm::{ColumnTrait, Condition};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{BasicModel, Network, SoftDeleteModel, Token, TokenColumn, Transfer, TransferFilter},
+	utils, App, BlockHeight,
+};
+
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 1_000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	network: String,
+	address: Option<String>,
+	filter: Option<String>,
+	cursor: Option<String>,
+	limit: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseTransfer {
+	id: String,
+	block_height: BlockHeight,
+	tx_hash: String,
+	from: String,
+	to: String,
+	token: Option<String>,
+	amount: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	network: String,
+	transfers: Vec<ResponseTransfer>,
+	tokens: Vec<Token>,
+	next_cursor: Option<String>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	let network = Network::get_existing_by_id(app.db(), &payload.network).await?.ok_or(
+		ServerError::InvalidParam { field: "network".to_string(), value: payload.network },
+	)?;
+	let nid = network.network_id;
+
+	let address = match payload.address {
+		Some(address) if !address.chars().all(|c| c.is_ascii_alphanumeric()) => {
+			return Err(ServerError::InvalidParam { field: "address".to_string(), value: address })
+		}
+		Some(address) => Some(match app.networks.read().await.get(&nid) {
+			Some(chain) => chain.format_address(&address),
+			_ => address,
+		}),
+		None => None,
+	};
+
+	// parse filter and resolve token symbols into addresses
+	let conditions = match payload.filter.filter(|f| !f.trim().is_empty()) {
+		Some(filter) => {
+			let transfer_filter = TransferFilter::parse(&filter).map_err(|e| {
+				ServerError::InvalidParam { field: "filter".to_string(), value: e.to_string() }
+			})?;
+
+			let mut assets = HashMap::<String, Vec<String>>::new();
+			let symbols = transfer_filter.get_assets();
+			if !symbols.is_empty() {
+				let tokens = Token::get_all_where(app.db(), TokenColumn::NetworkId.eq(nid)).await?;
+
+				for symbol in symbols.into_iter() {
+					let addresses = tokens
+						.iter()
+						.filter(|t| t.symbol.eq_ignore_ascii_case(&symbol))
+						.map(|t| t.address.clone())
+						.collect::<Vec<String>>();
+					if !addresses.is_empty() {
+						assets.insert(symbol, addresses);
+					}
+				}
+			}
+
+			transfer_filter.to_sql(address.as_deref(), &assets).map_err(|e| {
+				ServerError::InvalidParam { field: "filter".to_string(), value: e.to_string() }
+			})?
+		}
+		None => None,
+	};
+
+	let cursor = match payload.cursor {
+		Some(cursor) => Some(
+			utils::decode_cursor::<(BlockHeight, String)>(&cursor)
+				.and_then(|(block_height, uuid)| Some((block_height, Uuid::parse_str(&uuid).ok()?)))
+				.ok_or(ServerError::InvalidParam { field: "cursor".to_string(), value: cursor })?,
+		),
+		None => None,
+	};
+
+	let limit = payload.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+	let transfers = Transfer::get_all_by_address_paginated(
+		&app.warehouse,
+		nid,
+		address.as_deref(),
+		conditions,
+		cursor,
+		limit,
+	)
+	.await?;
+
+	// a full page means there might be more
+	let next_cursor = match transfers.last() {
+		Some(t) if transfers.len() as u64 == limit => {
+			Some(utils::encode_cursor(&(t.block_height, t.uuid.to_string())))
+		}
+		_ => None,
+	};
+
+	let tokens = if transfers.iter().any(|t| !t.asset_address.is_empty()) {
+		Token::get_all_where(
+			app.db(),
+			Condition::all()
+				.add(TokenColumn::NetworkId.eq(nid))
+				.add(TokenColumn::Address.is_in(transfers.iter().map(|t| t.asset_address.clone()))),
+		)
+		.await?
+	} else {
+		vec![]
+	};
+	let token_ids =
+		tokens.iter().map(|t| (t.address.clone(), t.id.clone())).collect::<HashMap<_, _>>();
+
+	Ok(Response {
+		network: network.id,
+		transfers: transfers
+			.into_iter()
+			.map(|t| ResponseTransfer {
+				id: t.uuid.to_string(),
+				block_height: t.block_height,
+				token: token_ids.get(&t.asset_address).cloned(),
+				tx_hash: t.tx_hash,
+				from: t.from_address,
+				to: t.to_address,
+				amount: t.relative_amount.to_string(),
+			})
+			.collect(),
+		tokens,
+		next_cursor,
+	}
+	.into())
+}
@@ -0,0 +1,228 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{BTreeSet, HashMap},
+	sync::Arc,
+};
+
+use crate::{
+	errors::ServerError,
+	handlers::v1::info::get::{get_info, ResponseRisk, ResponseSource},
+	utils::Fields,
+	ServerResult,
+};
+use barreleye_common::{
+	chain::ModuleId,
+	models::{Address, Entity, Network, PrimaryId, SoftDeleteModel, Transfer},
+	App,
+};
+
+// parties screened per side, since every one of them is a full lookup
+const MAX_PARTIES: usize = 20;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	network: String,
+	tx: String,
+}
+
+// @NOTE the subset of ivms101 that can be filled in from labels: parties are
+// legal persons named after the entities their addresses belong to, with the
+// addresses as account numbers. natural persons are never known here
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegalPersonNameIdentifier {
+	legal_person_name: String,
+	legal_person_name_identifier_type: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegalPersonName {
+	name_identifier: Vec<LegalPersonNameIdentifier>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegalPerson {
+	name: LegalPersonName,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Person {
+	legal_person: LegalPerson,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Originator {
+	originator_persons: Vec<Person>,
+	account_number: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Beneficiary {
+	beneficiary_persons: Vec<Person>,
+	account_number: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ivms101 {
+	originator: Originator,
+	beneficiary: Beneficiary,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseScreening {
+	address: String,
+	entity: Option<String>,
+	risk: ResponseRisk,
+	sources: Vec<ResponseSource>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseScreenings {
+	originator: Vec<ResponseScreening>,
+	beneficiary: Vec<ResponseScreening>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	network: String,
+	tx_hash: String,
+	ivms101: Ivms101,
+	screening: ResponseScreenings,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Response>> {
+	let network = Network::get_existing_by_id(app.db(), &payload.network).await?.ok_or(
+		ServerError::InvalidParam { field: "network".to_string(), value: payload.network },
+	)?;
+	let nid = network.network_id;
+
+	if payload.tx.is_empty() || !payload.tx.chars().all(|c| c.is_ascii_alphanumeric()) {
+		return Err(ServerError::InvalidParam { field: "tx".to_string(), value: payload.tx });
+	}
+
+	// fees aren't sent to the beneficiary, so they're left out
+	let fee_module_ids: [u16; 2] =
+		[ModuleId::BitcoinFeeTransfer.into(), ModuleId::EvmFeeTransfer.into()];
+	let transfers = Transfer::get_all_by_tx_hash(&app.warehouse, nid, &payload.tx)
+		.await?
+		.into_iter()
+		.filter(|t| !fee_module_ids.contains(&t.module_id))
+		.collect::<Vec<Transfer>>();
+	if transfers.is_empty() {
+		return Err(ServerError::NotFound);
+	}
+
+	let originators = transfers
+		.iter()
+		.filter(|t| !t.from_address.is_empty())
+		.map(|t| t.from_address.clone())
+		.collect::<BTreeSet<String>>();
+	let beneficiaries = transfers
+		.iter()
+		.filter(|t| !t.to_address.is_empty() && !originators.contains(&t.to_address))
+		.map(|t| t.to_address.clone())
+		.collect::<BTreeSet<String>>();
+
+	let (originator_persons, originator_screenings) =
+		screen_parties(app.clone(), nid, originators.into_iter().take(MAX_PARTIES).collect())
+			.await?;
+	let (beneficiary_persons, beneficiary_screenings) =
+		screen_parties(app.clone(), nid, beneficiaries.into_iter().take(MAX_PARTIES).collect())
+			.await?;
+
+	Ok(Response {
+		network: network.id,
+		tx_hash: payload.tx,
+		ivms101: Ivms101 {
+			originator: Originator {
+				originator_persons,
+				account_number: originator_screenings.iter().map(|s| s.address.clone()).collect(),
+			},
+			beneficiary: Beneficiary {
+				beneficiary_persons,
+				account_number: beneficiary_screenings.iter().map(|s| s.address.clone()).collect(),
+			},
+		},
+		screening: ResponseScreenings {
+			originator: originator_screenings,
+			beneficiary: beneficiary_screenings,
+		},
+	}
+	.into())
+}
+
+// entities behind `addresses` as legal persons, along with each address'
+// screening results
+async fn screen_parties(
+	app: Arc<App>,
+	network_id: PrimaryId,
+	addresses: Vec<String>,
+) -> ServerResult<(Vec<Person>, Vec<ResponseScreening>)> {
+	let address_entities = Address::get_all_by_addresses(app.db(), addresses.clone(), Some(false))
+		.await?
+		.into_iter()
+		.filter(|a| a.network_id == network_id)
+		.map(|a| (a.address, a.entity_id))
+		.collect::<HashMap<String, PrimaryId>>();
+
+	let entities = if address_entities.is_empty() {
+		HashMap::new()
+	} else {
+		Entity::get_all_by_entity_ids(
+			app.db(),
+			address_entities.values().copied().collect::<Vec<PrimaryId>>().into(),
+			Some(false),
+		)
+		.await?
+		.into_iter()
+		.map(|e| (e.entity_id, e))
+		.collect::<HashMap<PrimaryId, Entity>>()
+	};
+
+	let mut persons = vec![];
+	let mut screenings = vec![];
+	for address in addresses.into_iter() {
+		let entity = address_entities.get(&address).and_then(|id| entities.get(id));
+		let info = get_info(app.clone(), &address, &Fields::default(), false).await?;
+
+		screenings.push(ResponseScreening {
+			address,
+			entity: entity.map(|e| e.id.clone()),
+			risk: info.risk,
+			sources: info.sources,
+		});
+	}
+
+	// one legal person per entity, named after it
+	let mut entities = entities.into_values().collect::<Vec<Entity>>();
+	entities.sort_by(|a, b| a.id.cmp(&b.id));
+	for entity in entities.into_iter() {
+		persons.push(Person {
+			legal_person: LegalPerson {
+				name: LegalPersonName {
+					name_identifier: vec![LegalPersonNameIdentifier {
+						legal_person_name: entity.name.unwrap_or(entity.description),
+						legal_person_name_identifier_type: "LEGL".to_string(),
+					}],
+				},
+			},
+		});
+	}
+
+	Ok((persons, screenings))
+}
@@ -0,0 +1,36 @@
+use axum::{extract::State, Json};
+use reqwest::Url;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{errors::ServerError, ServerResult};
+use barreleye_common::{
+	models::{BasicModel, Webhook},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	url: String,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<Json<Webhook>> {
+	let url = payload.url.trim().to_string();
+
+	// only http endpoints can be posted to
+	let is_valid_url =
+		Url::parse(&url).map(|u| ["http", "https"].contains(&u.scheme())).unwrap_or(false);
+	if !is_valid_url {
+		return Err(ServerError::InvalidParam { field: "url".to_string(), value: url });
+	}
+
+	// create new
+	let webhook_id = Webhook::create(app.db(), Webhook::new_model(&url)).await?;
+
+	// return newly created (the only time its secret is shown)
+	Ok(Webhook::get(app.db(), webhook_id).await?.unwrap().with_signing_secret().into())
+}
@@ -0,0 +1,30 @@
+use axum::{extract::State, http::StatusCode, Json};
+use sea_orm::ColumnTrait;
+use serde::Deserialize;
+use std::{collections::HashSet, sync::Arc};
+
+use crate::ServerResult;
+use barreleye_common::{
+	models::{BasicModel, Webhook, WebhookColumn},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	webhooks: HashSet<String>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Json(payload): Json<Payload>,
+) -> ServerResult<StatusCode> {
+	// exit if no input
+	if payload.webhooks.is_empty() {
+		return Ok(StatusCode::NO_CONTENT);
+	}
+
+	Webhook::delete_all_where(app.db(), WebhookColumn::Id.is_in(payload.webhooks)).await?;
+
+	Ok(StatusCode::NO_CONTENT)
+}
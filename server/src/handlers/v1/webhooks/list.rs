@@ -0,0 +1,24 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::ServerResult;
+use barreleye_common::{
+	models::{BasicModel, Webhook},
+	App,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	offset: Option<u64>,
+	limit: Option<u64>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Vec<Webhook>>> {
+	Ok(Webhook::get_all_paginated(app.db(), payload.offset, payload.limit).await?.into())
+}
@@ -0,0 +1,104 @@
+use axum::{extract::State, Json};
+use axum_extra::extract::Query;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::{
+	errors::ServerError,
+	handlers::v1::info::get::{
+		get_cached, get_info, ResponseActivity, ResponseApproval, ResponseAsset, ResponseExposure,
+		ResponseFunder, ResponseRisk, ResponseSource, ResponseToken, FIELDS,
+	},
+	utils::Fields,
+	ServerResult,
+};
+use barreleye_common::{
+	models::{SanitizedEntity, SanitizedNetwork, SanitizedTag},
+	utils, App,
+};
+
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 1_000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+	q: String,
+	cursor: Option<String>,
+	limit: Option<u64>,
+	fields: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseSources {
+	items: Vec<ResponseSource>,
+	next_cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+	addresses: Vec<String>,
+	risk: ResponseRisk,
+	exposure: Vec<ResponseExposure>,
+	assets: Vec<ResponseAsset>,
+	tokens: Vec<ResponseToken>,
+	sources: ResponseSources,
+	approvals: Vec<ResponseApproval>,
+	activity: Vec<ResponseActivity>,
+	funders: Vec<ResponseFunder>,
+	networks: Vec<SanitizedNetwork>,
+	entities: Vec<SanitizedEntity>,
+	tags: Vec<SanitizedTag>,
+}
+
+pub async fn handler(
+	State(app): State<Arc<App>>,
+	Query(payload): Query<Payload>,
+) -> ServerResult<Json<Value>> {
+	let cache_key =
+		format!("v2:{}:{}", payload.q.trim(), payload.fields.clone().unwrap_or_default());
+	let fields = Fields::parse(payload.fields, FIELDS)?;
+	let offset = match payload.cursor {
+		Some(cursor) => utils::decode_cursor::<u64>(&cursor)
+			.ok_or(ServerError::InvalidParam { field: "cursor".to_string(), value: cursor })?,
+		None => 0,
+	};
+	let limit = payload.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+	get_cached(app.clone(), &format!("{cache_key}:{offset}:{limit}"), async {
+		let info = get_info(app, &payload.q, &fields, false).await?;
+
+		// sources can get long for busy addresses, so they're paginated
+		let mut sources = info.sources;
+		sources.sort_by(|a, b| {
+			(&a.network, &a.entity, &a.from, &a.to).cmp(&(&b.network, &b.entity, &b.from, &b.to))
+		});
+
+		let total = sources.len() as u64;
+		let items = sources.into_iter().skip(offset as usize).take(limit as usize).collect();
+		let next_cursor = if offset + limit < total {
+			Some(utils::encode_cursor(&(offset + limit)))
+		} else {
+			None
+		};
+
+		fields.apply(Response {
+			addresses: info.addresses,
+			risk: info.risk,
+			exposure: info.exposure,
+			assets: info.assets,
+			tokens: info.tokens,
+			sources: ResponseSources { items, next_cursor },
+			approvals: info.approvals,
+			activity: info.activity,
+			funders: info.funders,
+			networks: info.networks,
+			entities: info.entities,
+			tags: info.tags,
+		})
+	})
+	.await
+}
@@ -0,0 +1,14 @@
+use axum::Router;
+use std::sync::Arc;
+
+use crate::handlers::v1;
+use barreleye_common::App;
+
+mod info;
+
+// v1 paths with a breaking replacement in v2
+pub const SUPERSEDED_ROUTES: &[&str] = &["/info"];
+
+pub fn get_routes() -> Router<Arc<App>> {
+	v1::get_shared_routes().nest("/info", info::get_routes())
+}
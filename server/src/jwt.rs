@@ -0,0 +1,155 @@
+use eyre::{bail, eyre, Result};
+use jsonwebtoken::{
+	decode, decode_header,
+	jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet},
+	Algorithm, DecodingKey, Validation,
+};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use barreleye_common::Settings;
+
+// how long fetched keys are trusted before they're refetched
+const JWKS_TTL: Duration = Duration::from_secs(3_600);
+
+// min time between refetches triggered by unknown key ids
+const JWKS_MIN_REFRESH: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Claims {
+	pub sub: Option<String>,
+	pub nonce: Option<String>,
+	pub email: Option<String>,
+	pub email_verified: Option<bool>,
+}
+
+// @NOTE validates bearer tokens issued by an external identity provider
+// against its published key set (JWKS). keys are cached and refetched when
+// they expire or when a token references an unknown key (eg: after rotation)
+pub struct JwtValidator {
+	jwks_url: String,
+	issuer: Option<String>,
+	audience: Option<String>,
+	jwks: RwLock<Option<(Instant, JwkSet)>>,
+}
+
+impl JwtValidator {
+	pub fn new(settings: &Settings) -> Option<Self> {
+		settings.jwt_jwks_url.clone().map(|jwks_url| {
+			Self::with_jwks(jwks_url, settings.jwt_issuer.clone(), settings.jwt_audience.clone())
+		})
+	}
+
+	pub fn with_jwks(jwks_url: String, issuer: Option<String>, audience: Option<String>) -> Self {
+		Self { jwks_url, issuer, audience, jwks: RwLock::new(None) }
+	}
+
+	// jwts are three base64 segments, unlike api keys
+	pub fn is_jwt(token: &str) -> bool {
+		token.split('.').count() == 3
+	}
+
+	pub async fn validate(&self, token: &str) -> Result<Claims> {
+		let header = decode_header(token)?;
+		let kid = header.kid.clone().ok_or(eyre!("missing `kid` in token header"))?;
+
+		let jwk = match self.get_keys(false).await?.find(&kid).cloned() {
+			Some(jwk) => jwk,
+			None => self
+				.get_keys(true)
+				.await?
+				.find(&kid)
+				.cloned()
+				.ok_or(eyre!("unknown key id `{kid}`"))?,
+		};
+
+		// the header is untrusted, so its algorithm has to be one the key allows
+		if !get_allowed_algorithms(&jwk).contains(&header.alg) {
+			bail!("algorithm `{:?}` is not allowed for key id `{kid}`", header.alg);
+		}
+
+		let mut validation = Validation::new(header.alg);
+		if let Some(issuer) = &self.issuer {
+			validation.set_issuer(&[issuer]);
+		}
+		match &self.audience {
+			Some(audience) => validation.set_audience(&[audience]),
+			None => validation.validate_aud = false,
+		}
+
+		Ok(decode::<Claims>(token, &DecodingKey::from_jwk(&jwk)?, &validation)?.claims)
+	}
+
+	async fn get_keys(&self, force_refresh: bool) -> Result<JwkSet> {
+		if let Some((fetched_at, jwks)) = &*self.jwks.read().await {
+			let ttl = if force_refresh { JWKS_MIN_REFRESH } else { JWKS_TTL };
+			if fetched_at.elapsed() < ttl {
+				return Ok(jwks.clone());
+			}
+		}
+
+		let jwks = reqwest::get(&self.jwks_url).await?.error_for_status()?.json::<JwkSet>().await?;
+		*self.jwks.write().await = Some((Instant::now(), jwks.clone()));
+
+		Ok(jwks)
+	}
+}
+
+// @NOTE a key's own `alg` pins the algorithm. keys without one allow the
+// asymmetric algorithms that fit their type. symmetric (hmac) keys are never
+// accepted, since a published key set should only have public keys in it
+fn get_allowed_algorithms(jwk: &Jwk) -> Vec<Algorithm> {
+	let ret = match (jwk.common.key_algorithm, &jwk.algorithm) {
+		(Some(key_algorithm), _) => {
+			key_algorithm.to_string().parse::<Algorithm>().into_iter().collect()
+		}
+		(None, AlgorithmParameters::RSA(_)) => vec![
+			Algorithm::RS256,
+			Algorithm::RS384,
+			Algorithm::RS512,
+			Algorithm::PS256,
+			Algorithm::PS384,
+			Algorithm::PS512,
+		],
+		(None, AlgorithmParameters::EllipticCurve(params)) => match params.curve {
+			EllipticCurve::P256 => vec![Algorithm::ES256],
+			EllipticCurve::P384 => vec![Algorithm::ES384],
+			_ => vec![],
+		},
+		(None, AlgorithmParameters::OctetKeyPair(params)) => match params.curve {
+			EllipticCurve::Ed25519 => vec![Algorithm::EdDSA],
+			_ => vec![],
+		},
+		(None, AlgorithmParameters::OctetKey(_)) => vec![],
+	};
+
+	ret.into_iter()
+		.filter(|a| !matches!(a, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn jwk(value: serde_json::Value) -> Jwk {
+		serde_json::from_value(value).unwrap()
+	}
+
+	#[test]
+	fn test_get_allowed_algorithms() {
+		let rsa = jwk(serde_json::json!({ "kty": "RSA", "kid": "a", "n": "AQAB", "e": "AQAB" }));
+		assert!(get_allowed_algorithms(&rsa).contains(&Algorithm::RS256));
+		assert!(!get_allowed_algorithms(&rsa).contains(&Algorithm::HS256));
+
+		let pinned = jwk(
+			serde_json::json!({ "kty": "RSA", "kid": "a", "alg": "PS256", "n": "AQAB", "e": "AQAB" }),
+		);
+		assert_eq!(get_allowed_algorithms(&pinned), vec![Algorithm::PS256]);
+
+		let hmac =
+			jwk(serde_json::json!({ "kty": "oct", "kid": "a", "alg": "HS256", "k": "c2VjcmV0" }));
+		assert!(get_allowed_algorithms(&hmac).is_empty());
+	}
+}
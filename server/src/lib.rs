@@ -1,51 +1,158 @@
 use axum::{
 	error_handling::HandleErrorLayer,
-	extract::{Request, State},
-	http::{header, Method, StatusCode, Uri},
+	extract::{ConnectInfo, MatchedPath, Request, State},
+	http::{header, HeaderValue, Method, StatusCode, Uri},
 	middleware::{self, Next},
-	response::Response,
-	BoxError, Router,
+	response::{IntoResponse, Response},
+	BoxError, Extension, Router,
 };
 use console::style;
 use eyre::{Report, Result};
+use hyper::body::Incoming;
+use hyper_util::{
+	rt::{TokioExecutor, TokioIo},
+	server::conn::auto,
+	service::TowerToHyperService,
+};
+use rustls::ServerConfig;
 use signal::unix::SignalKind;
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+	net::SocketAddr,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 use tokio::{net::TcpListener, signal};
-use tower::ServiceBuilder;
+use tokio_rustls::TlsAcceptor;
+use tower::{ServiceBuilder, ServiceExt};
 use tower_http::{trace, trace::TraceLayer, LatencyUnit};
-use tracing::Level;
+use tracing::{debug, warn};
 
-use crate::errors::ServerError;
+use crate::{
+	errors::ServerError, jwt::JwtValidator, oidc::OidcClient, rate_limit::RateLimiter,
+	tls::ClientIdentity, usage::UsageTracker,
+};
 use barreleye_common::{
-	models::ApiKey, quit, App, AppError, Progress, ProgressReadyType, ProgressStep, Warnings,
+	models::{ApiKey, Session},
+	quit, App, AppError, Progress, ProgressReadyType, ProgressStep, Warnings,
 };
 
 mod errors;
 mod handlers;
+mod jwt;
+mod oidc;
+mod rate_limit;
+mod schedules;
+mod tls;
+mod usage;
 mod utils;
 
 pub type ServerResult<T> = Result<T, ServerError>;
 
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// route groups that require a user session when oidc is configured (on top
+// of every request that isn't a read)
+const ADMIN_ROUTES: &[&str] = &[
+	"networks",
+	"entities",
+	"keys",
+	"addresses",
+	"tags",
+	"categories",
+	"abis",
+	"webhooks",
+	"reports",
+	"report-schedules",
+	"alerts",
+	"query",
+];
+
+tokio::task_local! {
+	// id of the request being handled, so errors can reference it
+	pub static REQUEST_ID: String;
+}
+
+type AuthState = (Arc<App>, Option<Arc<JwtValidator>>, Option<Arc<OidcClient>>);
+
 pub struct Server {
 	app: Arc<App>,
+	rate_limiter: Arc<RateLimiter>,
+	usage_tracker: Arc<UsageTracker>,
+	jwt_validator: Option<Arc<JwtValidator>>,
+	oidc_client: Option<Arc<OidcClient>>,
 }
 
 impl Server {
 	pub fn new(app: Arc<App>) -> Self {
-		Self { app }
+		let rate_limiter = Arc::new(RateLimiter::new(app.settings.api_rate_limit));
+		let jwt_validator = JwtValidator::new(&app.settings).map(Arc::new);
+		let oidc_client = OidcClient::new(&app.settings).map(Arc::new);
+		let usage_tracker = Arc::new(UsageTracker::default());
+		Self { app, rate_limiter, usage_tracker, jwt_validator, oidc_client }
 	}
 
-	async fn auth(State(app): State<Arc<App>>, req: Request, next: Next) -> ServerResult<Response> {
-		if ApiKey::count(app.db()).await? == 0 {
+	async fn auth(
+		State((app, jwt_validator, oidc_client)): State<AuthState>,
+		mut req: Request,
+		next: Next,
+	) -> ServerResult<Response> {
+		let path = req.uri().path().to_string();
+
+		// login flow and readiness probes are always reachable
+		if path.starts_with("/auth/") || path == "/readyz" {
+			return Ok(next.run(req).await);
+		}
+
+		// @NOTE with oidc configured, management endpoints (and anything that
+		// writes) are only available to logged-in users; api keys are kept for
+		// machine-to-machine traffic
+		let group = path.split('/').nth(2).unwrap_or_default();
+		let is_admin_route = ADMIN_ROUTES.contains(&group) ||
+			!matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+		if oidc_client.is_some() && is_admin_route {
+			let token = oidc::get_session_token(req.headers()).ok_or(ServerError::Unauthorized)?;
+
+			return match Session::get_active_by_token(app.db(), &token).await? {
+				Some(session) => {
+					req.extensions_mut().insert(session);
+					Ok(next.run(req).await)
+				}
+				None => Err(ServerError::Unauthorized),
+			};
+		}
+
+		// without any credentials configured the api is open
+		if jwt_validator.is_none() && ApiKey::count(app.db()).await? == 0 {
 			return Ok(next.run(req).await);
 		}
 
-		for public_endpoint in ["/v1/info"].iter() {
-			if req.uri().to_string().starts_with(public_endpoint) {
+		for public_endpoint in ["/v1/info", "/v2/info"].iter() {
+			if path.starts_with(public_endpoint) {
 				return Ok(next.run(req).await);
 			}
 		}
 
+		// clients identified by their certificate, either on this connection or
+		// as forwarded by a trusted proxy
+		let remote_addr = req.extensions().get::<ConnectInfo<SocketAddr>>().map(|c| &c.0);
+		let client_identity = match &app.settings.tls_client_header {
+			Some(header)
+				if tls::is_trusted_proxy(&app.settings.tls_client_header_proxies, remote_addr) =>
+			{
+				tls::get_identity_from_header(req.headers(), header)
+			}
+			_ => req.extensions().get::<ClientIdentity>().cloned(),
+		};
+		if let Some(ClientIdentity(identity)) = client_identity {
+			match ApiKey::get_by_client_identity(app.db(), &identity).await? {
+				Some(api_key) if api_key.is_active => {
+					req.extensions_mut().insert(api_key);
+					return Ok(next.run(req).await);
+				}
+				_ => debug!("no active api key for client identity `{identity}`"),
+			}
+		}
+
 		let authorization = req
 			.headers()
 			.get(header::AUTHORIZATION)
@@ -58,6 +165,22 @@ impl Server {
 			_ => return Err(ServerError::Unauthorized),
 		};
 
+		// tokens issued by the identity provider
+		if let Some(jwt_validator) = jwt_validator {
+			if JwtValidator::is_jwt(&token) {
+				return match jwt_validator.validate(&token).await {
+					Ok(claims) => {
+						req.extensions_mut().insert(claims);
+						Ok(next.run(req).await)
+					}
+					Err(e) => {
+						debug!("rejected jwt: {e}");
+						Err(ServerError::Unauthorized)
+					}
+				};
+			}
+		}
+
 		match ApiKey::get_by_hashing(app.db(), &token)
 			.await
 			.map_err(|_| ServerError::Unauthorized)?
@@ -67,14 +190,169 @@ impl Server {
 					ApiKey::hide_key(app.db(), api_key.api_key_id).await?;
 				}
 
+				// downstream middleware (eg: rate limiting) needs to know the caller
+				req.extensions_mut().insert(api_key);
+
 				Ok(next.run(req).await)
 			}
 			_ => Err(ServerError::Unauthorized),
 		}
 	}
 
+	// honors a sane incoming `X-Request-Id`, otherwise generates one
+	async fn request_id(mut req: Request, next: Next) -> Response {
+		let request_id = req
+			.headers()
+			.get(REQUEST_ID_HEADER)
+			.and_then(|v| v.to_str().ok())
+			.filter(|id| {
+				!id.is_empty() && id.len() <= 128 && id.chars().all(|c| c.is_ascii_graphic())
+			})
+			.map(|id| id.to_string())
+			.unwrap_or_else(|| barreleye_common::utils::new_uuid().to_string());
+
+		if let Ok(value) = HeaderValue::from_str(&request_id) {
+			req.headers_mut().insert(REQUEST_ID_HEADER, value.clone());
+
+			let mut response = REQUEST_ID.scope(request_id, next.run(req)).await;
+			response.headers_mut().insert(REQUEST_ID_HEADER, value);
+
+			response
+		} else {
+			next.run(req).await
+		}
+	}
+
+	async fn rate_limit(
+		State(rate_limiter): State<Arc<RateLimiter>>,
+		req: Request,
+		next: Next,
+	) -> ServerResult<Response> {
+		let quota = match req.extensions().get::<ApiKey>() {
+			Some(api_key) if rate_limiter.is_enabled() => rate_limiter.check(api_key.api_key_id),
+			_ => return Ok(next.run(req).await),
+		};
+
+		let mut response = if quota.is_exceeded {
+			ServerError::TooManyRequests { retry_after: quota.retry_after() }.into_response()
+		} else {
+			next.run(req).await
+		};
+		quota.set_headers(response.headers_mut());
+
+		Ok(response)
+	}
+
+	// counts requests per api key and endpoint (eg: `GET /v1/keys/:id`)
+	async fn track_usage(
+		State(usage_tracker): State<Arc<UsageTracker>>,
+		req: Request,
+		next: Next,
+	) -> Response {
+		if let (Some(api_key), Some(path)) =
+			(req.extensions().get::<ApiKey>(), req.extensions().get::<MatchedPath>())
+		{
+			usage_tracker.record(api_key.api_key_id, format!("{} {}", req.method(), path.as_str()));
+		}
+
+		next.run(req).await
+	}
+
+	// times requests by route (eg: `/v1/keys/:id`), including rejected ones
+	async fn record_metrics(State(app): State<Arc<App>>, req: Request, next: Next) -> Response {
+		let started_at = Instant::now();
+		let method = req.method().to_string();
+		let route = req.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string());
+
+		let response = next.run(req).await;
+
+		if let Some(route) = route {
+			app.metrics.observe_http_request(
+				&method,
+				&route,
+				response.status().as_u16(),
+				started_at.elapsed(),
+			);
+		}
+
+		response
+	}
+
+	async fn flush_usage(&self) {
+		if let Err(e) = self.usage_tracker.flush(self.app.db()).await {
+			warn!(usage = "could not save", error = e.to_string());
+		}
+	}
+
+	// @NOTE `axum::serve` doesn't do tls, so connections are accepted here in
+	// order to pass each one's client certificate on to the auth middleware
+	async fn serve_tls(
+		listener: TcpListener,
+		app: Router,
+		config: Arc<ServerConfig>,
+	) -> Result<()> {
+		let acceptor = TlsAcceptor::from(config);
+
+		let shutdown = Self::shutdown_signal();
+		tokio::pin!(shutdown);
+
+		loop {
+			let (stream, remote_addr) = tokio::select! {
+				accepted = listener.accept() => match accepted {
+					Ok(accepted) => accepted,
+					Err(e) => {
+						debug!("could not accept connection: {e}");
+						continue;
+					}
+				},
+				_ = &mut shutdown => break,
+			};
+
+			let acceptor = acceptor.clone();
+			let app = app.clone();
+
+			tokio::spawn(async move {
+				let stream = match acceptor.accept(stream).await {
+					Ok(stream) => stream,
+					Err(e) => {
+						debug!("tls handshake with {remote_addr} failed: {e}");
+						return;
+					}
+				};
+
+				let client_identity = stream
+					.get_ref()
+					.1
+					.peer_certificates()
+					.and_then(|certs| certs.first())
+					.and_then(tls::get_identity);
+
+				let service = app.map_request(move |mut req: hyper::Request<Incoming>| {
+					req.extensions_mut().insert(ConnectInfo(remote_addr));
+					if let Some(client_identity) = client_identity.clone() {
+						req.extensions_mut().insert(client_identity);
+					}
+					req
+				});
+
+				if let Err(e) = auto::Builder::new(TokioExecutor::new())
+					.serve_connection_with_upgrades(
+						TokioIo::new(stream),
+						TowerToHyperService::new(service),
+					)
+					.await
+				{
+					debug!("connection with {remote_addr} closed: {e}");
+				}
+			});
+		}
+
+		Ok(())
+	}
+
 	pub async fn start(&self, warnings: Warnings, progress: Progress) -> Result<()> {
 		let settings = self.app.settings.clone();
+		let tls_config = tls::get_config(&settings)?;
 
 		async fn handle_404() -> ServerResult<StatusCode> {
 			Err(ServerError::NotFound)
@@ -90,8 +368,21 @@ impl Server {
 
 		let app = Router::new()
 			.nest("/", handlers::get_routes())
-			.route_layer(middleware::from_fn_with_state(self.app.clone(), Self::auth))
+			.route_layer(middleware::from_fn_with_state(
+				self.usage_tracker.clone(),
+				Self::track_usage,
+			))
+			.route_layer(middleware::from_fn_with_state(
+				self.rate_limiter.clone(),
+				Self::rate_limit,
+			))
+			.route_layer(middleware::from_fn_with_state(
+				(self.app.clone(), self.jwt_validator.clone(), self.oidc_client.clone()),
+				Self::auth,
+			))
+			.route_layer(middleware::from_fn_with_state(self.app.clone(), Self::record_metrics))
 			.fallback(handle_404)
+			.layer(Extension(self.oidc_client.clone()))
 			.layer(
 				ServiceBuilder::new()
 					.layer(HandleErrorLayer::new(handle_timeout_error))
@@ -99,7 +390,21 @@ impl Server {
 			)
 			.layer(
 				TraceLayer::new_for_http()
-					.make_span_with(trace::DefaultMakeSpan::new().level(Level::INFO))
+					.make_span_with(|req: &Request| {
+						let request_id = req
+							.headers()
+							.get(REQUEST_ID_HEADER)
+							.and_then(|v| v.to_str().ok())
+							.unwrap_or_default();
+
+						tracing::info_span!(
+							"request",
+							method = %req.method(),
+							uri = %req.uri(),
+							version = ?req.version(),
+							request_id,
+						)
+					})
 					.on_request(())
 					.on_response(
 						trace::DefaultOnResponse::new()
@@ -107,6 +412,7 @@ impl Server {
 							.latency_unit(LatencyUnit::Millis),
 					),
 			)
+			.layer(middleware::from_fn(Self::request_id))
 			.with_state(self.app.clone());
 
 		let show_progress = |addr: &str| {
@@ -153,7 +459,43 @@ impl Server {
 
 			if let Some(listener) = listener {
 				self.app.set_is_ready();
-				axum::serve(listener, app).with_graceful_shutdown(Self::shutdown_signal()).await?;
+
+				let flush_loop = async {
+					loop {
+						tokio::time::sleep(usage::FLUSH_INTERVAL).await;
+						self.flush_usage().await;
+					}
+				};
+
+				let schedules_loop = async {
+					loop {
+						tokio::time::sleep(schedules::CHECK_INTERVAL).await;
+						if let Err(e) = schedules::deliver_scheduled_reports(self.app.clone()).await {
+							warn!(schedules = "could not deliver", error = e.to_string());
+						}
+					}
+				};
+
+				let serve = async {
+					match tls_config {
+						Some(tls_config) => Self::serve_tls(listener, app, tls_config).await,
+						None => Ok(axum::serve(
+							listener,
+							app.into_make_service_with_connect_info::<SocketAddr>(),
+						)
+						.with_graceful_shutdown(Self::shutdown_signal())
+						.await?),
+					}
+				};
+
+				tokio::select! {
+					result = serve => result?,
+					_ = flush_loop => {},
+					_ = schedules_loop => {},
+				}
+
+				// save whatever was buffered since the last flush
+				self.flush_usage().await;
 			}
 		}
 
@@ -0,0 +1,180 @@
+use axum::http::{header, HeaderMap};
+use eyre::{bail, Result};
+use serde::Deserialize;
+use std::{
+	collections::HashMap,
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+use tokio::sync::OnceCell;
+
+use crate::jwt::{Claims, JwtValidator};
+use barreleye_common::{models::Session, utils, Settings};
+
+// name of the cookie holding the session token
+pub const SESSION_COOKIE: &str = "barreleye_session";
+
+// how long a user has to complete the login at the identity provider
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Deserialize)]
+struct Discovery {
+	authorization_endpoint: String,
+	token_endpoint: String,
+	jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+	id_token: String,
+}
+
+// @NOTE authorization code flow against an OpenID Connect provider. pending
+// logins are kept in memory, so the callback has to reach the same server
+// that started the login
+pub struct OidcClient {
+	issuer: String,
+	client_id: String,
+	client_secret: Option<String>,
+	redirect_url: String,
+	allowed_subjects: Vec<String>,
+	allowed_emails: Vec<String>,
+	provider: OnceCell<(Discovery, JwtValidator)>,
+	pending: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl OidcClient {
+	pub fn new(settings: &Settings) -> Option<Self> {
+		Some(Self {
+			issuer: settings.oidc_issuer.clone()?.trim_end_matches('/').to_string(),
+			client_id: settings.oidc_client_id.clone()?,
+			client_secret: settings.oidc_client_secret.clone(),
+			redirect_url: settings.oidc_redirect_url.clone()?,
+			allowed_subjects: settings.oidc_allowed_subjects.clone(),
+			allowed_emails: settings.oidc_allowed_emails.clone(),
+			provider: OnceCell::new(),
+			pending: Mutex::new(HashMap::new()),
+		})
+	}
+
+	async fn get_provider(&self) -> Result<&(Discovery, JwtValidator)> {
+		self.provider
+			.get_or_try_init(|| async {
+				let discovery =
+					reqwest::get(format!("{}/.well-known/openid-configuration", self.issuer))
+						.await?
+						.error_for_status()?
+						.json::<Discovery>()
+						.await?;
+
+				let validator = JwtValidator::with_jwks(
+					discovery.jwks_uri.clone(),
+					Some(self.issuer.clone()),
+					Some(self.client_id.clone()),
+				);
+
+				Ok((discovery, validator))
+			})
+			.await
+	}
+
+	pub async fn get_login_url(&self) -> Result<String> {
+		let (discovery, _) = self.get_provider().await?;
+
+		let state = utils::new_uuid().simple().to_string();
+		let nonce = utils::new_uuid().simple().to_string();
+
+		{
+			let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+			pending.retain(|_, (_, started_at)| started_at.elapsed() < LOGIN_TIMEOUT);
+			pending.insert(state.clone(), (nonce.clone(), Instant::now()));
+		}
+
+		// emails are only in the id token when asked for
+		let scope = match self.allowed_emails.is_empty() {
+			true => "openid",
+			false => "openid email",
+		};
+
+		let mut url = reqwest::Url::parse(&discovery.authorization_endpoint)?;
+		url.query_pairs_mut()
+			.append_pair("response_type", "code")
+			.append_pair("client_id", &self.client_id)
+			.append_pair("redirect_uri", &self.redirect_url)
+			.append_pair("scope", scope)
+			.append_pair("state", &state)
+			.append_pair("nonce", &nonce);
+
+		Ok(url.to_string())
+	}
+
+	// exchanges the authorization code and returns the user's id token claims
+	pub async fn authenticate(&self, code: &str, state: &str) -> Result<Claims> {
+		let nonce = match self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(state) {
+			Some((nonce, started_at)) if started_at.elapsed() < LOGIN_TIMEOUT => nonce,
+			_ => bail!("unknown or expired login"),
+		};
+
+		let (discovery, validator) = self.get_provider().await?;
+
+		let mut form = vec![
+			("grant_type", "authorization_code"),
+			("code", code),
+			("redirect_uri", &self.redirect_url),
+			("client_id", &self.client_id),
+		];
+		if let Some(client_secret) = &self.client_secret {
+			form.push(("client_secret", client_secret));
+		}
+
+		let token = reqwest::Client::new()
+			.post(&discovery.token_endpoint)
+			.form(&form)
+			.send()
+			.await?
+			.error_for_status()?
+			.json::<TokenResponse>()
+			.await?;
+
+		let claims = validator.validate(&token.id_token).await?;
+		if claims.nonce.as_deref() != Some(nonce.as_str()) {
+			bail!("nonce mismatch");
+		}
+		if claims.sub.is_none() {
+			bail!("missing `sub` claim");
+		}
+
+		Ok(claims)
+	}
+
+	// whether the user is on the allowlist, by subject or by verified email
+	pub fn is_allowed(&self, claims: &Claims) -> bool {
+		let is_allowed_subject =
+			claims.sub.as_ref().is_some_and(|sub| self.allowed_subjects.contains(sub));
+		let is_allowed_email = claims.email_verified == Some(true) &&
+			claims.email.as_ref().is_some_and(|email| {
+				self.allowed_emails.iter().any(|e| e.eq_ignore_ascii_case(email))
+			});
+
+		is_allowed_subject || is_allowed_email
+	}
+}
+
+// session tokens come either as a bearer token or as a cookie
+pub fn get_session_token(headers: &HeaderMap) -> Option<String> {
+	let authorization = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+	if let Some(("Bearer", token)) = authorization.and_then(|a| a.split_once(' ')) {
+		if Session::is_token(token) {
+			return Some(token.to_string());
+		}
+	}
+
+	headers
+		.get_all(header::COOKIE)
+		.iter()
+		.filter_map(|v| v.to_str().ok())
+		.flat_map(|v| v.split(';'))
+		.filter_map(|c| c.trim().split_once('='))
+		.find(|(name, _)| *name == SESSION_COOKIE)
+		.map(|(_, token)| token.to_string())
+}
@@ -0,0 +1,70 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+use std::{collections::HashMap, sync::Mutex};
+
+use barreleye_common::{models::PrimaryId, utils};
+
+// length of a rate-limit window
+const WINDOW_SECS: u64 = 60;
+
+pub struct Quota {
+	pub limit: u32,
+	pub remaining: u32,
+	pub reset_at: u64,
+	pub is_exceeded: bool,
+}
+
+impl Quota {
+	pub fn retry_after(&self) -> u64 {
+		self.reset_at.saturating_sub(utils::now().and_utc().timestamp() as u64)
+	}
+
+	pub fn set_headers(&self, headers: &mut HeaderMap) {
+		headers.insert("x-ratelimit-limit", HeaderValue::from(self.limit));
+		headers.insert("x-ratelimit-remaining", HeaderValue::from(self.remaining));
+		headers.insert("x-ratelimit-reset", HeaderValue::from(self.reset_at));
+
+		if self.is_exceeded {
+			headers.insert(header::RETRY_AFTER, HeaderValue::from(self.retry_after()));
+		}
+	}
+}
+
+// @NOTE fixed-window request counter per api key. state is kept in memory,
+// so with multiple servers each one enforces the limit on its own
+pub struct RateLimiter {
+	limit: u32,
+	windows: Mutex<HashMap<PrimaryId, (u64, u32)>>,
+}
+
+impl RateLimiter {
+	pub fn new(limit: u32) -> Self {
+		Self { limit, windows: Mutex::new(HashMap::new()) }
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.limit > 0
+	}
+
+	pub fn check(&self, api_key_id: PrimaryId) -> Quota {
+		let now = utils::now().and_utc().timestamp() as u64;
+		let window_start = now - now % WINDOW_SECS;
+
+		let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+		let (started_at, count) = windows.entry(api_key_id).or_insert((window_start, 0));
+		if *started_at != window_start {
+			(*started_at, *count) = (window_start, 0);
+		}
+
+		let is_exceeded = *count >= self.limit;
+		if !is_exceeded {
+			*count += 1;
+		}
+
+		Quota {
+			limit: self.limit,
+			remaining: self.limit - *count,
+			reset_at: window_start + WINDOW_SECS,
+			is_exceeded,
+		}
+	}
+}
@@ -0,0 +1,40 @@
+use eyre::Result;
+use std::{sync::Arc, time::Duration};
+use tracing::{info, warn};
+
+use crate::handlers::v1::reports::create::create_report;
+use barreleye_common::{models::ReportSchedule, App};
+
+// how often report schedules are checked for due runs
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// @NOTE compiles reports for every subject on due schedules. a run is claimed
+// before anything is compiled, so a failing subject is skipped until the next
+// run instead of being retried right away
+pub async fn deliver_scheduled_reports(app: Arc<App>) -> Result<()> {
+	if app.storage.get_root().is_none() {
+		return Ok(());
+	}
+
+	for schedule in ReportSchedule::get_all_due(app.db()).await?.into_iter() {
+		if !ReportSchedule::claim(app.db(), &schedule).await? {
+			continue;
+		}
+
+		let mut report_ids = vec![];
+		for q in schedule.get_watchlist().into_iter() {
+			match create_report(app.clone(), &q).await {
+				Ok(report) => report_ids.push(report.id),
+				Err(e) => {
+					warn!(schedule = %schedule.id, subject = %q, error = e.to_string(), "Skipped report")
+				}
+			}
+		}
+
+		info!(schedule = %schedule.id, reports = report_ids.len(), "Delivered scheduled reports");
+		ReportSchedule::set_last_report_ids(app.db(), schedule.report_schedule_id, report_ids)
+			.await?;
+	}
+
+	Ok(())
+}
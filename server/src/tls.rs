@@ -0,0 +1,148 @@
+use axum::http::HeaderMap;
+use eyre::{eyre, Result};
+use rustls::{
+	crypto::ring,
+	pki_types::{CertificateDer, PrivateKeyDer},
+	server::WebPkiClientVerifier,
+	RootCertStore, ServerConfig,
+};
+use std::{
+	fs::File,
+	io::BufReader,
+	net::{IpAddr, SocketAddr},
+	path::Path,
+	sync::Arc,
+};
+use x509_parser::prelude::*;
+
+use barreleye_common::Settings;
+
+// identity of the client certificate presented on the current connection
+#[derive(Debug, Clone)]
+pub struct ClientIdentity(pub String);
+
+// @NOTE builds the listener's tls config. with `tls_client_ca` set, the
+// handshake fails for clients without a certificate signed by one of those cas
+pub fn get_config(settings: &Settings) -> Result<Option<Arc<ServerConfig>>> {
+	let (Some(cert), Some(key)) = (&settings.tls_cert, &settings.tls_key) else {
+		return Ok(None);
+	};
+
+	let provider = Arc::new(ring::default_provider());
+	let builder = ServerConfig::builder_with_provider(provider.clone())
+		.with_safe_default_protocol_versions()?;
+
+	let builder = match &settings.tls_client_ca {
+		Some(client_ca) => {
+			let mut roots = RootCertStore::empty();
+			for cert in read_certs(client_ca)? {
+				roots.add(cert)?;
+			}
+
+			builder.with_client_cert_verifier(
+				WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider).build()?,
+			)
+		}
+		None => builder.with_no_client_auth(),
+	};
+
+	let mut config = builder.with_single_cert(read_certs(cert)?, read_key(key)?)?;
+	config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+	Ok(Some(Arc::new(config)))
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+	let mut reader = BufReader::new(File::open(path)?);
+	Ok(rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()?)
+}
+
+fn read_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
+	let mut reader = BufReader::new(File::open(path)?);
+	rustls_pemfile::private_key(&mut reader)?
+		.ok_or(eyre!("no private key found in `{}`", path.display()))
+}
+
+// common name of a (validated) client certificate
+pub fn get_identity(cert: &CertificateDer) -> Option<ClientIdentity> {
+	let (_, cert) = X509Certificate::from_der(cert).ok()?;
+	let common_name = cert.subject().iter_common_name().next()?.as_str().ok()?;
+
+	Some(ClientIdentity(common_name.to_string()))
+}
+
+// @NOTE a forwarded identity is only as trustworthy as whoever set the
+// header, so it's only read off requests that come straight from one of the
+// configured proxies; anyone else could claim any identity with it
+pub fn is_trusted_proxy(proxies: &[IpAddr], remote_addr: Option<&SocketAddr>) -> bool {
+	remote_addr.is_some_and(|addr| proxies.contains(&addr.ip().to_canonical()))
+}
+
+// identity forwarded by a trusted tls-terminating proxy; accepts either a
+// bare common name or a full subject dn (eg: `CN=client,O=Acme`)
+pub fn get_identity_from_header(headers: &HeaderMap, header: &str) -> Option<ClientIdentity> {
+	let value = headers.get(header)?.to_str().ok()?.trim();
+	if value.is_empty() {
+		return None;
+	}
+
+	let common_name = if value.contains('=') {
+		value
+			.split([',', '/'])
+			.filter_map(|part| part.trim().split_once('='))
+			.find(|(k, _)| k.eq_ignore_ascii_case("cn"))
+			.map(|(_, v)| v.trim())?
+	} else {
+		value
+	};
+
+	Some(ClientIdentity(common_name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use axum::http::HeaderValue;
+
+	#[test]
+	fn test_get_identity_from_header() {
+		let header = "x-ssl-client-s-dn";
+
+		for (value, expected) in [
+			("client-1", Some("client-1")),
+			("CN=client-2,O=Acme,C=US", Some("client-2")),
+			("/C=US/O=Acme/CN=client-3", Some("client-3")),
+			("O=Acme", None),
+			("", None),
+		] {
+			let mut headers = HeaderMap::new();
+			headers.insert(header, HeaderValue::from_static(value));
+
+			assert_eq!(
+				get_identity_from_header(&headers, header).map(|i| i.0).as_deref(),
+				expected,
+				"{value}"
+			);
+		}
+	}
+
+	#[test]
+	fn test_is_trusted_proxy() {
+		let proxies = ["10.0.0.1".parse().unwrap(), "fd00::1".parse().unwrap()];
+
+		for (remote_addr, expected) in [
+			(Some("10.0.0.1:443"), true),
+			(Some("[::ffff:10.0.0.1]:443"), true),
+			(Some("[fd00::1]:443"), true),
+			(Some("10.0.0.2:443"), false),
+			(None, false),
+		] {
+			let remote_addr = remote_addr.map(|a| a.parse::<SocketAddr>().unwrap());
+			assert_eq!(
+				is_trusted_proxy(&proxies, remote_addr.as_ref()),
+				expected,
+				"{remote_addr:?}"
+			);
+		}
+	}
+}
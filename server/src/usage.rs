@@ -0,0 +1,40 @@
+use eyre::Result;
+use sea_orm::{prelude::DateTime, DatabaseConnection};
+use std::{collections::HashMap, mem, sync::Mutex, time::Duration};
+
+use barreleye_common::{
+	models::{ApiKeyUsage, PrimaryId},
+	utils,
+};
+
+// how often buffered usage is written to the database
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+// @NOTE per-key request counts are buffered in memory and flushed every so
+// often, so handling a request doesn't also mean writing to the database.
+// counts buffered since the last flush are lost if the server crashes
+#[derive(Default)]
+pub struct UsageTracker {
+	buffer: Mutex<HashMap<(PrimaryId, String), (i64, DateTime)>>,
+}
+
+impl UsageTracker {
+	pub fn record(&self, api_key_id: PrimaryId, endpoint: String) {
+		let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+		let (count, last_used_at) =
+			buffer.entry((api_key_id, endpoint)).or_insert((0, utils::now()));
+
+		*count += 1;
+		*last_used_at = utils::now();
+	}
+
+	pub async fn flush(&self, db: &DatabaseConnection) -> Result<()> {
+		let buffer = mem::take(&mut *self.buffer.lock().unwrap_or_else(|e| e.into_inner()));
+
+		for ((api_key_id, endpoint), (count, last_used_at)) in buffer.into_iter() {
+			ApiKeyUsage::increment(db, api_key_id, &endpoint, count, last_used_at).await?;
+		}
+
+		Ok(())
+	}
+}
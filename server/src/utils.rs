@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use axum::Json;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 
 use crate::{errors::ServerError, ServerResult};
 use barreleye_common::{
@@ -39,3 +42,81 @@ pub fn extract_primary_ids(
 
 	Ok(vec![])
 }
+
+// @NOTE sparse fieldsets: `fields=risk,tags` limits a response to those
+// top-level sections, so that handlers can skip fetching the rest. no
+// `fields` means everything
+#[derive(Default)]
+pub struct Fields(Option<HashSet<String>>);
+
+impl Fields {
+	pub fn parse(fields: Option<String>, allowed: &[&str]) -> ServerResult<Self> {
+		let Some(fields) = fields.filter(|f| !f.trim().is_empty()) else {
+			return Ok(Self(None));
+		};
+
+		let fields = fields
+			.split(',')
+			.map(|f| f.trim().to_string())
+			.filter(|f| !f.is_empty())
+			.collect::<HashSet<String>>();
+
+		let mut invalid_fields =
+			fields.iter().filter(|f| !allowed.contains(&f.as_str())).cloned().collect::<Vec<_>>();
+		if !invalid_fields.is_empty() {
+			invalid_fields.sort_unstable();
+			return Err(ServerError::InvalidValues {
+				field: "fields".to_string(),
+				values: invalid_fields.join(", "),
+			});
+		}
+
+		Ok(Self(Some(fields)))
+	}
+
+	pub fn has(&self, field: &str) -> bool {
+		self.0.as_ref().is_none_or(|fields| fields.contains(field))
+	}
+
+	pub fn has_any(&self, fields: &[&str]) -> bool {
+		fields.iter().any(|f| self.has(f))
+	}
+
+	// drops sections that weren't asked for
+	pub fn apply<T>(&self, response: T) -> ServerResult<Json<Value>>
+	where
+		T: Serialize,
+	{
+		let mut value = serde_json::to_value(response)?;
+
+		if let (Some(fields), Some(map)) = (&self.0, value.as_object_mut()) {
+			map.retain(|k, _| fields.contains(k));
+		}
+
+		Ok(value.into())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn test_fields() {
+		let allowed = &["risk", "sources", "tags"];
+
+		let fields = Fields::parse(None, allowed).unwrap();
+		assert!(fields.has("sources"));
+
+		let fields = Fields::parse(Some("risk, tags".to_string()), allowed).unwrap();
+		assert!(fields.has("risk") && fields.has("tags"));
+		assert!(!fields.has("sources"));
+		assert_eq!(
+			fields.apply(json!({"risk": 1, "sources": [], "tags": []})).unwrap().0,
+			json!({"risk": 1, "tags": []})
+		);
+
+		assert!(Fields::parse(Some("risk,assets".to_string()), allowed).is_err());
+	}
+}
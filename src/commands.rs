@@ -0,0 +1,165 @@
+use console::style;
+use eyre::{bail, Result};
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+
+use barreleye_common::{
+	labels,
+	models::{Config, ConfigKey, Network, SoftDeleteModel},
+	settings::{Command, ConfigCommand, LabelsCommand, SnapshotCommand},
+	snapshot::{self, TableManifest},
+	verify, App,
+};
+
+pub async fn run(app: Arc<App>, command: Command) -> Result<()> {
+	match command {
+		Command::Snapshot(command) => run_snapshot(app, command).await,
+		Command::Config(command) => run_config(app, command).await,
+		Command::Labels(command) => run_labels(app, command).await,
+		Command::Verify { network, sample } => run_verify(app, network, sample).await,
+	}
+}
+
+async fn run_snapshot(app: Arc<App>, command: SnapshotCommand) -> Result<()> {
+	match command {
+		SnapshotCommand::Export { path } => {
+			let manifest = snapshot::export(&app, &path).await?;
+
+			show_tables(&manifest.db_tables);
+			show_tables(&manifest.warehouse_tables);
+			show_status(&format!("storage: {} files", manifest.storage.files.len()));
+			println!("\nSnapshot written to {}", path.display());
+		}
+		SnapshotCommand::Import { path } => {
+			let (manifest, missing_files) = snapshot::import(&app, &path).await?;
+
+			show_tables(&manifest.db_tables);
+			show_tables(&manifest.warehouse_tables);
+			for file in missing_files.iter() {
+				show_status(&format!("missing from storage: {file}"));
+			}
+			println!("\nSnapshot from {} imported", manifest.created_at);
+
+			if !missing_files.is_empty() {
+				println!(
+					"{} of {} extracted files are missing; copy them over from {}",
+					missing_files.len(),
+					manifest.storage.files.len(),
+					manifest.storage.root.unwrap_or_default(),
+				);
+			}
+		}
+	}
+
+	Ok(())
+}
+
+// @NOTE keys are parsed into `ConfigKey` first, so typos don't silently create
+// configs that nothing reads
+async fn run_config(app: Arc<App>, command: ConfigCommand) -> Result<()> {
+	match command {
+		ConfigCommand::List { filter } => {
+			for config in Config::get_all(app.db()).await? {
+				if filter.as_ref().is_none_or(|f| config.key.contains(f)) {
+					println!("{} {}", config.key, style(config.value).bold().dim());
+				}
+			}
+		}
+		ConfigCommand::Get { key } => {
+			// read raw, since the point may be to look at a value that doesn't parse
+			let config_key = key.parse::<ConfigKey>()?;
+			let Some(config) = Config::get_all(app.db())
+				.await?
+				.into_iter()
+				.find(|c| c.key == config_key.to_string())
+			else {
+				bail!("{config_key} is not set");
+			};
+
+			println!("{}", config.value);
+			show_status(&format!("updated at {}", config.updated_at));
+			if let Err(e) = config_key.validate_value(&config.value) {
+				show_status(&e.to_string());
+			}
+		}
+		ConfigCommand::Set { key, value } => {
+			let config_key = key.parse::<ConfigKey>()?;
+			config_key.validate_value(&value)?;
+
+			let value = serde_json::from_str::<JsonValue>(&value)?;
+			Config::set::<_, JsonValue>(app.db(), config_key, value).await?;
+			println!("{config_key} set");
+		}
+		ConfigCommand::Delete { key } => {
+			let config_key = key.parse::<ConfigKey>()?;
+			Config::delete(app.db(), config_key).await?;
+			println!("{config_key} deleted");
+		}
+	}
+
+	Ok(())
+}
+
+async fn run_labels(app: Arc<App>, command: LabelsCommand) -> Result<()> {
+	match command {
+		LabelsCommand::Import { path, network, format, on_conflict, currency } => {
+			let Some(network) = Network::get_existing_by_id(app.db(), &network).await? else {
+				bail!("network {network} does not exist");
+			};
+
+			let mut labels = labels::read(format, &path)?;
+			if let Some(currency) = currency.map(|c| c.to_uppercase()) {
+				labels.retain(|l| l.currency.as_ref().is_none_or(|c| *c == currency));
+			}
+			show_status(&format!("read: {} labels", labels.len()));
+
+			let stats = labels::import(&app, &network, labels, on_conflict).await?;
+			show_status(&format!("entities: {} created", stats.entities_created));
+			show_status(&format!("tags: {} created", stats.tags_created));
+			show_status(&format!(
+				"addresses: {} created, {} replaced, {} skipped",
+				stats.addresses_created, stats.addresses_replaced, stats.addresses_skipped
+			));
+			println!("\nLabels imported into {}", network.name);
+		}
+	}
+
+	Ok(())
+}
+
+async fn run_verify(app: Arc<App>, network: String, sample: usize) -> Result<()> {
+	let Some(network) = Network::get_existing_by_id(app.db(), &network).await? else {
+		bail!("network {network} does not exist");
+	};
+
+	let reports = verify::verify(&app, &network, sample).await?;
+
+	let mut discrepancies = 0;
+	for report in reports.iter() {
+		if report.discrepancies.is_empty() {
+			show_status(&format!("block {}: ok", report.block_height));
+		}
+		for discrepancy in report.discrepancies.iter() {
+			show_status(&format!("block {}: {discrepancy}", report.block_height));
+		}
+		discrepancies += report.discrepancies.len();
+	}
+
+	// @NOTE exits with an error on any discrepancy, so it can gate scripts
+	if discrepancies > 0 {
+		bail!("{discrepancies} discrepancies in {} sampled blocks", reports.len());
+	}
+	println!("\n{} sampled blocks match {}", reports.len(), network.name);
+
+	Ok(())
+}
+
+fn show_tables(tables: &[TableManifest]) {
+	for t in tables.iter() {
+		show_status(&format!("{}: {} rows", t.table, t.rows));
+	}
+}
+
+fn show_status(status: &str) {
+	println!("{} {}", style("↳").bold().dim(), style(status).bold().dim());
+}
@@ -0,0 +1,286 @@
+use console::style;
+use eyre::Result;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+use barreleye_common::{
+	chain::{self, plugin, Plugins},
+	models::{set, BasicModel, Config, ConfigKey},
+	utils, Db, Progress, ProgressStep, Storage, Warehouse,
+};
+pub use barreleye_common::{
+	chain::{PluginModule, PluginTrait},
+	models::Network,
+	App, AppError, Architecture, Settings, Warnings,
+};
+use barreleye_indexer::Indexer;
+use barreleye_server::Server;
+
+// network to register on startup (same fields as `POST /v1/networks`)
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+	pub id: Option<String>,
+	pub name: String,
+	pub architecture: Architecture,
+	pub chain_id: u64,
+	pub block_time: u64,
+	pub rpc_endpoint: String,
+	pub rps: u32,
+	// in usd
+	pub large_transfer_threshold: Option<String>,
+	pub confirmations: Option<u32>,
+}
+
+#[derive(Default)]
+pub struct BarreleyeBuilder {
+	settings: Option<Settings>,
+	warnings: Warnings,
+	networks: Vec<NetworkConfig>,
+	plugins: Plugins,
+	show_progress: bool,
+}
+
+impl BarreleyeBuilder {
+	// defaults to `Settings::from_args(["barreleye"])`
+	pub fn settings(self, settings: Settings) -> Self {
+		Self { settings: Some(settings), ..self }
+	}
+
+	pub fn warnings(self, warnings: Warnings) -> Self {
+		Self { warnings, ..self }
+	}
+
+	pub fn network(mut self, network: NetworkConfig) -> Self {
+		self.networks.push(network);
+		self
+	}
+
+	// registers a plugin; `--plugins` picks which of them are enabled
+	pub fn plugin(mut self, plugin: Arc<dyn PluginTrait>) -> Self {
+		self.plugins.push(plugin);
+		self
+	}
+
+	// print startup steps to stdout, like the cli does
+	pub fn show_progress(self, show_progress: bool) -> Self {
+		Self { show_progress, ..self }
+	}
+
+	pub async fn build(self) -> Result<Barreleye> {
+		let settings = match self.settings {
+			Some(settings) => Arc::new(settings),
+			None => Arc::new(Settings::from_args(["barreleye"]).await?.0),
+		};
+
+		let progress = if self.show_progress {
+			Progress::new(settings.is_indexer)
+		} else {
+			Progress::silent()
+		};
+		progress.show(ProgressStep::Setup);
+
+		let warehouse = Arc::new(
+			Warehouse::new(settings.clone())
+				.await
+				.map_err(|url| AppError::WarehouseConnection { url: url.to_string() })?,
+		);
+		warehouse.start_health_checks();
+
+		let storage = Arc::new(
+			Storage::new(settings.clone())
+				.map_err(|url| AppError::StorageConnection { url: url.to_string() })?,
+		);
+		storage.start_credentials_refresh().await?;
+		storage.start_catalog_registration();
+		storage.start_delta_commits();
+
+		let db = Arc::new(
+			Db::new(settings.clone())
+				.await
+				.map_err(|url| AppError::DatabaseConnection { url: url.to_string() })?,
+		);
+
+		if self.show_progress {
+			show_connections(&settings);
+		}
+
+		progress.show(ProgressStep::Migrations);
+		warehouse.run_migrations().await?;
+		db.run_migrations().await?;
+
+		let plugins = plugin::get_enabled(self.plugins, &settings.plugins)?;
+		let app = Arc::new(App::new(settings.clone(), storage, db, warehouse, plugins).await?);
+
+		let mut warnings = self.warnings;
+		warnings.extend(app.get_warnings().await?);
+
+		let barreleye = Barreleye { app, warnings, progress };
+		for network in self.networks.into_iter() {
+			barreleye.add_network(network).await?;
+		}
+
+		Ok(barreleye)
+	}
+}
+
+// @NOTE embeds barreleye into another program, eg:
+//
+// let barreleye = Barreleye::builder()
+// 	.settings(Settings::from_args(["barreleye", "--mode", "indexer"]).await?.0)
+// 	.network(NetworkConfig { .. })
+// 	.build()
+// 	.await?;
+// barreleye.start().await?;
+pub struct Barreleye {
+	app: Arc<App>,
+	warnings: Warnings,
+	progress: Progress,
+}
+
+impl Barreleye {
+	pub fn builder() -> BarreleyeBuilder {
+		BarreleyeBuilder::default()
+	}
+
+	pub fn app(&self) -> Arc<App> {
+		self.app.clone()
+	}
+
+	// registers a network unless one with the same name already exists
+	pub async fn add_network(&self, config: NetworkConfig) -> Result<Network> {
+		if let Some(network) = Network::get_by_name(self.app.db(), &config.name, None).await? {
+			return Ok(network);
+		}
+
+		let n = Network {
+			architecture: config.architecture,
+			rpc_endpoint: config.rpc_endpoint.clone(),
+			..Default::default()
+		};
+		let mut boxed_chain = chain::new_boxed_chain(n, &self.app.plugins)?;
+		if !boxed_chain.connect().await? {
+			return Err(AppError::Network {
+				error: format!("could not connect to {}", config.name),
+			}
+			.into());
+		}
+
+		let mut network = Network::new_model(
+			config.id,
+			&config.name,
+			config.architecture,
+			config.chain_id as i64,
+			config.block_time as i64,
+			config.rpc_endpoint,
+			config.rps as i32,
+		);
+		network.large_transfer_threshold = set(config.large_transfer_threshold);
+		network.confirmations = set(config.confirmations.unwrap_or(0) as i32);
+		let network_id = Network::create(self.app.db(), network).await?;
+
+		Config::set::<_, u8>(self.app.db(), ConfigKey::NetworksUpdated, 1).await?;
+
+		*self.app.networks.write().await = self.app.get_networks().await?;
+
+		Ok(Network::get(self.app.db(), network_id).await?.unwrap())
+	}
+
+	// runs the indexer and/or the server (depending on settings) until one of
+	// them stops
+	pub async fn start(&self) -> Result<()> {
+		let settings = self.app.settings.clone();
+		let mut set = JoinSet::new();
+
+		if settings.is_indexer {
+			self.progress.show(ProgressStep::Networks);
+			self.app
+				.connect_networks(false)
+				.await
+				.map_err(|e| AppError::Network { error: e.to_string() })?;
+
+			// unreachable networks don't stop startup; they're retried in the background
+			let mut warnings = self.warnings.clone();
+			let mut names =
+				self.app.get_disconnected_networks().await.into_values().collect::<Vec<_>>();
+			names.sort();
+			for name in names.into_iter() {
+				warnings.push(format!("{name} is not connected (retrying in the background)"));
+			}
+			if settings.offline {
+				warnings.push("Running offline (only extracted blocks are processed)".to_string());
+			}
+
+			set.spawn({
+				let a = self.app.clone();
+				let w = warnings;
+				let p = self.progress.clone();
+
+				async move {
+					let indexer = Indexer::new(a);
+					indexer.start(w, p).await
+				}
+			});
+		}
+
+		if settings.is_server {
+			set.spawn({
+				let a = self.app.clone();
+				let w = self.warnings.clone();
+				let p = self.progress.clone();
+
+				async move {
+					let server = Server::new(a);
+					server.start(w, p).await
+				}
+			});
+		} else {
+			self.app.set_is_ready();
+		}
+
+		while let Some(res) = set.join_next().await {
+			res??;
+		}
+
+		Ok(())
+	}
+}
+
+fn show_connections(settings: &Settings) {
+	fn show_setting(driver: &str, url: &str, tag: &str) {
+		println!(
+			"          {} {} {}{}",
+			style("↳").bold().dim(),
+			style(format!("{tag}:")).bold().dim(),
+			if !driver.is_empty() {
+				format!(
+					"{}{}{} ",
+					style("[").bold().dim(),
+					style(driver).bold(),
+					style("]").bold().dim()
+				)
+			} else {
+				"".to_string()
+			},
+			style(url.to_string()).bold().dim(),
+		);
+	}
+
+	let (storage_type, storage_path) = if let Some(path) = settings.storage_path.clone() {
+		("".to_string(), format!("{}", path.display()))
+	} else if let Some(s3) = settings.storage_url.clone() {
+		(s3.service.to_string(), s3.url)
+	} else {
+		panic!("storage setting must be set");
+	};
+	show_setting(&storage_type, &storage_path, "Storage");
+	show_setting(
+		&settings.database_driver.to_string(),
+		&utils::with_masked_auth(&settings.database),
+		"Database",
+	);
+	show_setting(
+		&settings.warehouse_driver.to_string(),
+		&utils::with_masked_auth(&settings.warehouse),
+		"Warehouse",
+	);
+}